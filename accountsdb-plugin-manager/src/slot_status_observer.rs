@@ -0,0 +1,120 @@
+/// Observes bank notifications from replay and forwards slot status changes
+/// to the loaded plugins
+use {
+    crate::accountsdb_plugin_manager::AccountsDbPluginManager,
+    crossbeam_channel::{Receiver, RecvTimeoutError},
+    log::*,
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::SlotStatus,
+    solana_rpc::optimistically_confirmed_bank_tracker::BankNotification,
+    solana_sdk::clock::Slot,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+#[derive(Debug)]
+pub(crate) struct SlotStatusObserver {
+    bank_notification_receiver_service: Option<JoinHandle<()>>,
+    exit_updated_slot_server: Arc<AtomicBool>,
+}
+
+impl SlotStatusObserver {
+    pub fn new(
+        bank_notification_receiver: Receiver<BankNotification>,
+        plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+    ) -> Self {
+        let exit_updated_slot_server = Arc::new(AtomicBool::new(false));
+
+        Self {
+            bank_notification_receiver_service: Some(Self::run_bank_notification_receiver(
+                bank_notification_receiver,
+                exit_updated_slot_server.clone(),
+                plugin_manager,
+            )),
+            exit_updated_slot_server,
+        }
+    }
+
+    pub fn join(&mut self) -> thread::Result<()> {
+        self.exit_updated_slot_server.store(true, Ordering::Relaxed);
+        self.bank_notification_receiver_service
+            .take()
+            .map(JoinHandle::join)
+            .unwrap()
+    }
+
+    fn run_bank_notification_receiver(
+        bank_notification_receiver: Receiver<BankNotification>,
+        exit: Arc<AtomicBool>,
+        plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+    ) -> JoinHandle<()> {
+        Builder::new()
+            .name("bank_notification_receiver".to_string())
+            .spawn(move || {
+                while !exit.load(Ordering::Relaxed) {
+                    match bank_notification_receiver.recv_timeout(Duration::from_secs(1)) {
+                        Ok(BankNotification::OptimisticallyConfirmed(slot)) => {
+                            Self::notify_slot_status(
+                                &plugin_manager,
+                                slot,
+                                None,
+                                SlotStatus::Confirmed,
+                            );
+                        }
+                        Ok(BankNotification::Frozen(bank)) => {
+                            Self::notify_slot_status(
+                                &plugin_manager,
+                                bank.slot(),
+                                Some(bank.parent_slot()),
+                                SlotStatus::Processed,
+                            );
+                        }
+                        Ok(BankNotification::Root(bank)) => {
+                            Self::notify_slot_status(
+                                &plugin_manager,
+                                bank.slot(),
+                                None,
+                                SlotStatus::Rooted,
+                            );
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            })
+            .unwrap()
+    }
+
+    fn notify_slot_status(
+        plugin_manager: &RwLock<AccountsDbPluginManager>,
+        slot: Slot,
+        parent: Option<Slot>,
+        slot_status: SlotStatus,
+    ) {
+        let plugin_manager = plugin_manager.read().unwrap();
+        for plugin in plugin_manager.plugins.iter() {
+            match plugin.update_slot_status(slot, parent, slot_status) {
+                Err(err) => {
+                    error!(
+                        "Failed to update slot status at slot {}, error: {} to plugin {}",
+                        slot,
+                        err,
+                        plugin.name()
+                    )
+                }
+                Ok(_) => {
+                    trace!(
+                        "Successfully updated slot status at slot {} to plugin {}",
+                        slot,
+                        plugin.name()
+                    );
+                }
+            }
+        }
+    }
+}
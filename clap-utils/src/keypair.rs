@@ -10,6 +10,7 @@ use {
     solana_remote_wallet::{
         locator::{Locator as RemoteWalletLocator, LocatorError as RemoteWalletLocatorError},
         remote_keypair::generate_remote_keypair,
+        remote_signer::{RemoteSigner, RemoteSignerError, RemoteSignerLocator},
         remote_wallet::{maybe_wallet_manager, RemoteWalletError, RemoteWalletManager},
     },
     solana_sdk::{
@@ -211,6 +212,7 @@ const SIGNER_SOURCE_FILEPATH: &str = "file";
 const SIGNER_SOURCE_USB: &str = "usb";
 const SIGNER_SOURCE_STDIN: &str = "stdin";
 const SIGNER_SOURCE_PUBKEY: &str = "pubkey";
+const SIGNER_SOURCE_REMOTE: &str = "remote";
 
 pub(crate) enum SignerSourceKind {
     Prompt,
@@ -218,6 +220,7 @@ pub(crate) enum SignerSourceKind {
     Usb(RemoteWalletLocator),
     Stdin,
     Pubkey(Pubkey),
+    Remote(RemoteSignerLocator),
 }
 
 impl AsRef<str> for SignerSourceKind {
@@ -228,6 +231,7 @@ impl AsRef<str> for SignerSourceKind {
             Self::Usb(_) => SIGNER_SOURCE_USB,
             Self::Stdin => SIGNER_SOURCE_STDIN,
             Self::Pubkey(_) => SIGNER_SOURCE_PUBKEY,
+            Self::Remote(_) => SIGNER_SOURCE_REMOTE,
         }
     }
 }
@@ -246,6 +250,8 @@ pub(crate) enum SignerSourceError {
     #[error(transparent)]
     RemoteWalletLocatorError(#[from] RemoteWalletLocatorError),
     #[error(transparent)]
+    RemoteSignerError(#[from] RemoteSignerError),
+    #[error(transparent)]
     DerivationPathError(#[from] DerivationPathError),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
@@ -285,6 +291,9 @@ pub(crate) fn parse_signer_source<S: AsRef<str>>(
                         legacy: false,
                     }),
                     SIGNER_SOURCE_STDIN => Ok(SignerSource::new(SignerSourceKind::Stdin)),
+                    SIGNER_SOURCE_REMOTE => Ok(SignerSource::new(SignerSourceKind::Remote(
+                        RemoteSignerLocator::new_from_uri(&uri)?,
+                    ))),
                     _ => {
                         #[cfg(target_family = "windows")]
                         // On Windows, an absolute path's drive letter will be parsed as the URI
@@ -398,6 +407,7 @@ pub fn signer_from_path_with_config(
                 Err(RemoteWalletError::NoDeviceFound.into())
             }
         }
+        SignerSourceKind::Remote(locator) => Ok(Box::new(RemoteSigner::new(locator)?)),
         SignerSourceKind::Pubkey(pubkey) => {
             let presigner = pubkeys_sigs_of(matches, SIGNER_ARG.name)
                 .as_ref()
@@ -426,6 +436,7 @@ pub fn pubkey_from_path(
     let SignerSource { kind, .. } = parse_signer_source(path)?;
     match kind {
         SignerSourceKind::Pubkey(pubkey) => Ok(pubkey),
+        SignerSourceKind::Remote(locator) => Ok(locator.pubkey),
         _ => Ok(signer_from_path(matches, path, keypair_name, wallet_manager)?.pubkey()),
     }
 }
@@ -774,6 +785,19 @@ mod tests {
                 derivation_path: d,
                 legacy: false,
             } if u == expected_locator && d == expected_derivation_path));
+        let remote_pubkey = Pubkey::new_unique();
+        let remote = format!("remote://signer.internal:7700?pubkey={}", remote_pubkey);
+        assert!(
+            matches!(parse_signer_source(&remote).unwrap(), SignerSource {
+                kind: SignerSourceKind::Remote(r),
+                derivation_path: None,
+                legacy: false,
+            } if r.pubkey == remote_pubkey && r.auth_keypair_path.is_none())
+        );
+        assert!(matches!(
+            parse_signer_source("remote://signer.internal:7700"),
+            Err(SignerSourceError::RemoteSignerError(_))
+        ));
         // Catchall into SignerSource::Filepath fails
         let junk = "sometextthatisnotapubkeyorfile".to_string();
         assert!(Pubkey::from_str(&junk).is_err());
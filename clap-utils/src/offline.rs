@@ -27,6 +27,13 @@ pub const DUMP_TRANSACTION_MESSAGE: ArgConstant<'static> = ArgConstant {
     help: "Display the base64 encoded binary transaction message in sign-only mode",
 };
 
+pub const OUTPUT_TRANSACTION_ARG: ArgConstant<'static> = ArgConstant {
+    name: "output_transaction",
+    long: "output-transaction",
+    help: "In sign-only mode, write the partially signed transaction to this file, \
+           for the remaining signers to sign with `solana sign-transaction`",
+};
+
 pub fn blockhash_arg<'a, 'b>() -> Arg<'a, 'b> {
     Arg::with_name(BLOCKHASH_ARG.name)
         .long(BLOCKHASH_ARG.long)
@@ -63,6 +70,15 @@ pub fn dump_transaction_message<'a, 'b>() -> Arg<'a, 'b> {
         .help(DUMP_TRANSACTION_MESSAGE.help)
 }
 
+pub fn output_transaction_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(OUTPUT_TRANSACTION_ARG.name)
+        .long(OUTPUT_TRANSACTION_ARG.long)
+        .takes_value(true)
+        .value_name("FILEPATH")
+        .requires(SIGN_ONLY_ARG.name)
+        .help(OUTPUT_TRANSACTION_ARG.help)
+}
+
 pub trait ArgsConfig {
     fn blockhash_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
         arg
@@ -76,6 +92,9 @@ pub trait ArgsConfig {
     fn dump_transaction_message_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
         arg
     }
+    fn output_transaction_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
+        arg
+    }
 }
 
 pub trait OfflineArgs {
@@ -89,6 +108,7 @@ impl OfflineArgs for App<'_, '_> {
             .arg(config.sign_only_arg(sign_only_arg()))
             .arg(config.signer_arg(signer_arg()))
             .arg(config.dump_transaction_message_arg(dump_transaction_message()))
+            .arg(config.output_transaction_arg(output_transaction_arg()))
     }
     fn offline_args(self) -> Self {
         struct NullArgsConfig {}
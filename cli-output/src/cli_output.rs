@@ -28,6 +28,7 @@ use {
         stake::state::{Authorized, Lockup},
         stake_history::StakeHistoryEntry,
         transaction::{Transaction, TransactionError},
+        versioned_transaction::VersionedTransaction,
     },
     solana_transaction_status::{
         EncodedConfirmedBlock, EncodedTransaction, TransactionConfirmationStatus,
@@ -1578,6 +1579,8 @@ pub struct CliSignOnlyData {
     pub blockhash: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub transaction_file: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub signers: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
@@ -1596,6 +1599,9 @@ impl fmt::Display for CliSignOnlyData {
         if let Some(message) = self.message.as_ref() {
             writeln_name_value(f, "Transaction Message:", message)?;
         }
+        if let Some(transaction_file) = self.transaction_file.as_ref() {
+            writeln_name_value(f, "Transaction File:", transaction_file)?;
+        }
         if !self.signers.is_empty() {
             writeln!(f, "{}", style("Signers (Pubkey=Signature):").bold())?;
             for signer in self.signers.iter() {
@@ -2010,6 +2016,33 @@ impl fmt::Display for CliUpgradeableBuffers {
 #[derive(Debug, Default)]
 pub struct ReturnSignersConfig {
     pub dump_transaction_message: bool,
+    /// Write the partially signed transaction to this file, for other parties to sign with
+    /// `solana sign-transaction`
+    pub output_transaction: Option<String>,
+}
+
+/// Write a transaction, base64-encoded, for another party to sign or submit
+pub fn write_transaction_file(
+    path: &str,
+    tx: &Transaction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let data = bincode::serialize(tx)?;
+    if let Some(outdir) = std::path::Path::new(path).parent() {
+        if !outdir.as_os_str().is_empty() {
+            std::fs::create_dir_all(outdir)?;
+        }
+    }
+    std::fs::write(path, format!("{}\n", base64::encode(&data)))?;
+    Ok(())
+}
+
+/// Read a transaction written by `write_transaction_file`
+pub fn read_transaction_file(path: &str) -> Result<Transaction, Box<dyn std::error::Error>> {
+    let encoded = std::fs::read_to_string(path)?;
+    let data = base64::decode(encoded.trim())
+        .map_err(|err| format!("Unable to decode transaction file {}: {}", path, err))?;
+    bincode::deserialize(&data)
+        .map_err(|err| format!("Unable to deserialize transaction file {}: {}", path, err).into())
 }
 
 pub fn return_signers(
@@ -2047,10 +2080,14 @@ pub fn return_signers_with_config(
     } else {
         None
     };
+    if let Some(path) = &config.output_transaction {
+        write_transaction_file(path, tx)?;
+    }
 
     let cli_command = CliSignOnlyData {
         blockhash: tx.message.recent_blockhash.to_string(),
         message,
+        transaction_file: config.output_transaction.clone(),
         signers,
         absent,
         bad_sig,
@@ -2128,7 +2165,7 @@ pub enum CliSignatureVerificationStatus {
 }
 
 impl CliSignatureVerificationStatus {
-    pub fn verify_transaction(tx: &Transaction) -> Vec<Self> {
+    pub fn verify_transaction(tx: &VersionedTransaction) -> Vec<Self> {
         tx.verify_with_results()
             .iter()
             .zip(&tx.signatures)
@@ -2263,7 +2300,7 @@ pub struct CliTransaction {
     #[serde(skip_serializing)]
     pub slot: Option<Slot>,
     #[serde(skip_serializing)]
-    pub decoded_transaction: Transaction,
+    pub decoded_transaction: VersionedTransaction,
     #[serde(skip_serializing)]
     pub prefix: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -2507,6 +2544,7 @@ mod tests {
             .to_string();
         let config = ReturnSignersConfig {
             dump_transaction_message: true,
+            ..ReturnSignersConfig::default()
         };
         let res = return_signers_with_config(&tx, &OutputFormat::JsonCompact, &config).unwrap();
         let sign_only = parse_sign_only_reply_string(&res);
@@ -2515,6 +2553,20 @@ mod tests {
         assert_eq!(sign_only.present_signers[0].0, present.pubkey());
         assert_eq!(sign_only.absent_signers[0], absent.pubkey());
         assert_eq!(sign_only.bad_signers[0], bad.pubkey());
+
+        let path = std::env::temp_dir()
+            .join(format!("test_return_signers-{}.tx", Pubkey::new_unique()))
+            .to_str()
+            .unwrap()
+            .to_string();
+        let config = ReturnSignersConfig {
+            output_transaction: Some(path.clone()),
+            ..ReturnSignersConfig::default()
+        };
+        let res = return_signers_with_config(&tx, &OutputFormat::Display, &config).unwrap();
+        assert!(res.contains(&path));
+        assert_eq!(read_transaction_file(&path).unwrap(), tx);
+        std::fs::remove_file(path).unwrap();
     }
 
     #[test]
@@ -4,13 +4,19 @@ use {
     console::style,
     indicatif::{ProgressBar, ProgressStyle},
     solana_sdk::{
-        clock::UnixTimestamp, hash::Hash, message::Message, native_token::lamports_to_sol,
-        program_utils::limited_deserialize, pubkey::Pubkey, stake, transaction::Transaction,
+        clock::UnixTimestamp,
+        hash::Hash,
+        message::{v0::LoadedAddresses, Message, VersionedMessage},
+        native_token::lamports_to_sol,
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        stake,
+        versioned_transaction::VersionedTransaction,
     },
     solana_transaction_status::UiTransactionStatusMeta,
     spl_memo::id as spl_memo_id,
     spl_memo::v1::id as spl_memo_v1_id,
-    std::{collections::HashMap, fmt, io},
+    std::{collections::HashMap, convert::TryFrom, fmt, io},
 };
 
 #[derive(Clone, Debug)]
@@ -157,15 +163,49 @@ fn format_account_mode(message: &Message, index: usize) -> String {
     )
 }
 
+fn format_account_key(account_key: Option<&Pubkey>) -> String {
+    account_key
+        .map(ToString::to_string)
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+// Addresses loaded from lookup tables are only known once a transaction has
+// been processed, so v0 messages are resolved with the loaded addresses from
+// their status meta and otherwise displayed with only their static keys
+fn resolve_message(
+    message: &VersionedMessage,
+    transaction_status: &Option<UiTransactionStatusMeta>,
+) -> Message {
+    match message {
+        VersionedMessage::Legacy(message) => message.clone(),
+        VersionedMessage::V0(message) => transaction_status
+            .as_ref()
+            .and_then(|transaction_status| transaction_status.loaded_addresses.as_ref())
+            .and_then(|loaded_addresses| LoadedAddresses::try_from(loaded_addresses).ok())
+            .and_then(|loaded_addresses| {
+                message
+                    .clone()
+                    .try_into_legacy_message(loaded_addresses)
+                    .ok()
+            })
+            .unwrap_or_else(|| Message {
+                header: message.header.clone(),
+                account_keys: message.account_keys.clone(),
+                recent_blockhash: message.recent_blockhash,
+                instructions: message.instructions.clone(),
+            }),
+    }
+}
+
 pub fn write_transaction<W: io::Write>(
     w: &mut W,
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
     block_time: Option<UnixTimestamp>,
 ) -> io::Result<()> {
-    let message = &transaction.message;
+    let message = &resolve_message(&transaction.message, transaction_status);
     if let Some(block_time) = block_time {
         writeln!(
             w,
@@ -218,6 +258,25 @@ pub fn write_transaction<W: io::Write>(
             },
         )?;
     }
+    if let Some(address_table_lookups) = transaction.message.address_table_lookups() {
+        for (lookup_index, lookup) in address_table_lookups.iter().enumerate() {
+            writeln!(
+                w,
+                "{}Address Table Lookup {}: {}",
+                prefix, lookup_index, lookup.account_key
+            )?;
+            writeln!(
+                w,
+                "{}  Writable Indexes: {:?}",
+                prefix, lookup.writable_indexes
+            )?;
+            writeln!(
+                w,
+                "{}  Readonly Indexes: {:?}",
+                prefix, lookup.readonly_indexes
+            )?;
+        }
+    }
     for (instruction_index, instruction) in message.instructions.iter().enumerate() {
         let program_pubkey = message.account_keys[instruction.program_id_index as usize];
         writeln!(w, "{}Instruction {}", prefix, instruction_index)?;
@@ -227,11 +286,14 @@ pub fn write_transaction<W: io::Write>(
             prefix, program_pubkey, instruction.program_id_index
         )?;
         for (account_index, account) in instruction.accounts.iter().enumerate() {
-            let account_pubkey = message.account_keys[*account as usize];
+            let account_pubkey = message.account_keys.get(*account as usize);
             writeln!(
                 w,
                 "{}  Account {}: {} ({})",
-                prefix, account_index, account_pubkey, account
+                prefix,
+                account_index,
+                format_account_key(account_pubkey),
+                account
             )?;
         }
 
@@ -364,7 +426,7 @@ pub fn write_transaction<W: io::Write>(
 }
 
 pub fn println_transaction(
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
@@ -389,7 +451,7 @@ pub fn println_transaction(
 
 pub fn writeln_transaction(
     f: &mut dyn fmt::Write,
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
@@ -19,9 +19,9 @@ use solana_clap_utils::{
 };
 use solana_cli_output::{
     display::{build_balance_message, println_name_value},
-    return_signers_with_config, CliAccount, CliSignature, CliSignatureVerificationStatus,
-    CliTransaction, CliTransactionConfirmation, CliValidatorsSortOrder, OutputFormat,
-    ReturnSignersConfig,
+    read_transaction_file, return_signers_with_config, CliAccount, CliSignature,
+    CliSignatureVerificationStatus, CliTransaction, CliTransactionConfirmation,
+    CliValidatorsSortOrder, OutputFormat, ReturnSignersConfig,
 };
 use solana_client::{
     blockhash_query::BlockhashQuery,
@@ -43,12 +43,14 @@ use solana_sdk::{
     hash::Hash,
     instruction::InstructionError,
     message::Message,
+    offchain_message::OffchainMessage,
     pubkey::Pubkey,
     signature::{Signature, Signer, SignerError},
     stake::{self, instruction::LockupArgs, state::Lockup},
     system_instruction::{self, SystemError},
     system_program,
     transaction::{Transaction, TransactionError},
+    versioned_transaction::VersionedTransaction,
 };
 use solana_transaction_status::{EncodedTransaction, UiTransactionEncoding};
 use solana_vote_program::vote_state::VoteAuthorize;
@@ -157,6 +159,11 @@ pub enum CliCommand {
         nonce_authority: SignerIndex,
         memo: Option<String>,
         new_authority: Pubkey,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        output_transaction: Option<String>,
+        blockhash_query: BlockhashQuery,
+        fee_payer: SignerIndex,
     },
     CreateNonceAccount {
         nonce_account: SignerIndex,
@@ -170,6 +177,11 @@ pub enum CliCommand {
         nonce_account: Pubkey,
         nonce_authority: SignerIndex,
         memo: Option<String>,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        output_transaction: Option<String>,
+        blockhash_query: BlockhashQuery,
+        fee_payer: SignerIndex,
     },
     ShowNonceAccount {
         nonce_account_pubkey: Pubkey,
@@ -181,6 +193,11 @@ pub enum CliCommand {
         memo: Option<String>,
         destination_account_pubkey: Pubkey,
         lamports: u64,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        output_transaction: Option<String>,
+        blockhash_query: BlockhashQuery,
+        fee_payer: SignerIndex,
     },
     // Program Deployment
     Deploy {
@@ -201,6 +218,7 @@ pub enum CliCommand {
         amount: SpendAmount,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -213,6 +231,7 @@ pub enum CliCommand {
         stake_authority: SignerIndex,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -227,6 +246,7 @@ pub enum CliCommand {
         force: bool,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -238,6 +258,7 @@ pub enum CliCommand {
         stake_authority: SignerIndex,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -253,6 +274,7 @@ pub enum CliCommand {
         stake_authority: SignerIndex,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -273,6 +295,7 @@ pub enum CliCommand {
         new_authorizations: Vec<StakeAuthorizationIndexed>,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -288,6 +311,7 @@ pub enum CliCommand {
         new_custodian_signer: Option<SignerIndex>,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -302,6 +326,7 @@ pub enum CliCommand {
         custodian: Option<SignerIndex>,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         blockhash_query: BlockhashQuery,
         nonce_account: Option<Pubkey>,
         nonce_authority: SignerIndex,
@@ -369,7 +394,7 @@ pub enum CliCommand {
         use_lamports_unit: bool,
     },
     Confirm(Signature),
-    DecodeTransaction(Transaction),
+    DecodeTransaction(VersionedTransaction),
     ResolveSigner(Option<String>),
     ShowAccount {
         pubkey: Pubkey,
@@ -382,6 +407,7 @@ pub enum CliCommand {
         from: SignerIndex,
         sign_only: bool,
         dump_transaction_message: bool,
+        output_transaction: Option<String>,
         allow_unfunded_recipient: bool,
         no_wait: bool,
         blockhash_query: BlockhashQuery,
@@ -392,6 +418,18 @@ pub enum CliCommand {
         derived_address_seed: Option<String>,
         derived_address_program_id: Option<Pubkey>,
     },
+    SignOffchainMessage {
+        message: OffchainMessage,
+    },
+    SignTransaction {
+        transaction_file: String,
+        broadcast: bool,
+    },
+    VerifyOffchainSignature {
+        signer_pubkey: Option<Pubkey>,
+        signature: Signature,
+        message: OffchainMessage,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -879,6 +917,42 @@ pub fn parse_command(
                 signers: vec![],
             })
         }
+        ("sign-offchain-message", Some(matches)) => {
+            let message = parse_offchain_message(matches)?;
+            Ok(CliCommandInfo {
+                command: CliCommand::SignOffchainMessage { message },
+                signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+            })
+        }
+        ("sign-transaction", Some(matches)) => Ok(CliCommandInfo {
+            command: CliCommand::SignTransaction {
+                transaction_file: value_t_or_exit!(matches, "transaction_file", String),
+                broadcast: matches.is_present("broadcast"),
+            },
+            signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+        }),
+        ("verify-offchain-signature", Some(matches)) => {
+            let message = parse_offchain_message(matches)?;
+            let signature = matches
+                .value_of("signature")
+                .unwrap()
+                .parse()
+                .map_err(|_| CliError::BadParameter("Invalid signature".to_string()))?;
+            let signer_pubkey = pubkey_of_signer(matches, "signer", wallet_manager)?;
+            let signers = if signer_pubkey.is_some() {
+                vec![]
+            } else {
+                vec![default_signer.signer_from_path(matches, wallet_manager)?]
+            };
+            Ok(CliCommandInfo {
+                command: CliCommand::VerifyOffchainSignature {
+                    signer_pubkey,
+                    signature,
+                    message,
+                },
+                signers,
+            })
+        }
         ("resolve-signer", Some(matches)) => {
             let signer_path = resolve_signer(matches, "signer", wallet_manager)?;
             Ok(CliCommandInfo {
@@ -891,6 +965,9 @@ pub fn parse_command(
             let to = pubkey_of_signer(matches, "to", wallet_manager)?.unwrap();
             let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
             let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+            let output_transaction = matches
+                .value_of(OUTPUT_TRANSACTION_ARG.name)
+                .map(String::from);
             let no_wait = matches.is_present("no_wait");
             let blockhash_query = BlockhashQuery::new_from_matches(matches);
             let nonce_account = pubkey_of_signer(matches, NONCE_ARG.name, wallet_manager)?;
@@ -922,6 +999,7 @@ pub fn parse_command(
                     to,
                     sign_only,
                     dump_transaction_message,
+                    output_transaction,
                     allow_unfunded_recipient,
                     no_wait,
                     blockhash_query,
@@ -1089,8 +1167,9 @@ fn process_confirm(
                                 .transaction
                                 .decode()
                                 .expect("Successful decode");
-                            let json_transaction = EncodedTransaction::encode(
+                            let json_transaction = EncodedTransaction::encode_versioned(
                                 decoded_transaction.clone(),
+                                None,
                                 UiTransactionEncoding::Json,
                             );
 
@@ -1130,11 +1209,117 @@ fn process_confirm(
 }
 
 #[allow(clippy::unnecessary_wraps)]
-fn process_decode_transaction(config: &CliConfig, transaction: &Transaction) -> ProcessResult {
+fn parse_offchain_message(matches: &ArgMatches<'_>) -> Result<OffchainMessage, CliError> {
+    let message = matches.value_of("message").unwrap();
+    OffchainMessage::new(message.as_bytes())
+        .map_err(|err| CliError::BadParameter(format!("Invalid off-chain message: {}", err)))
+}
+
+fn process_sign_offchain_message(config: &CliConfig, message: &OffchainMessage) -> ProcessResult {
+    Ok(message.sign(config.signers[0])?.to_string())
+}
+
+fn process_sign_transaction(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    transaction_file: &str,
+    broadcast: bool,
+) -> ProcessResult {
+    let mut transaction = read_transaction_file(transaction_file)?;
+
+    // Show the signer what they are about to sign
+    if matches!(
+        config.output_format,
+        OutputFormat::Display | OutputFormat::DisplayVerbose
+    ) {
+        let transaction = VersionedTransaction::from(transaction.clone());
+        println!("{}", process_decode_transaction(config, &transaction)?);
+    }
+
+    let signer = config.signers[0];
+    let signer_pubkey = signer.pubkey();
+    let num_required_signatures = transaction.message.header.num_required_signatures as usize;
+    let signer_index = transaction.message.account_keys[..num_required_signatures]
+        .iter()
+        .position(|pubkey| *pubkey == signer_pubkey);
+    let message_data = transaction.message_data();
+    let is_complete = |transaction: &Transaction| {
+        transaction
+            .verify_with_results()
+            .into_iter()
+            .all(|valid| valid)
+    };
+    match signer_index {
+        Some(index) => {
+            if !transaction.signatures[index].verify(signer_pubkey.as_ref(), &message_data) {
+                let recent_blockhash = transaction.message.recent_blockhash;
+                transaction.try_partial_sign(&[signer], recent_blockhash)?;
+            }
+        }
+        None if broadcast && is_complete(&transaction) => {}
+        None => {
+            return Err(CliError::BadParameter(format!(
+                "{} is not a required signer of this transaction",
+                signer_pubkey
+            ))
+            .into())
+        }
+    }
+
+    let signers_output = return_signers_with_config(
+        &transaction,
+        &config.output_format,
+        &ReturnSignersConfig {
+            output_transaction: Some(transaction_file.to_string()),
+            ..ReturnSignersConfig::default()
+        },
+    )?;
+    if !broadcast {
+        return Ok(signers_output);
+    }
+    if !is_complete(&transaction) {
+        println!("{}", signers_output);
+        return Err(CliError::BadParameter(
+            "Transaction is missing required signatures".to_string(),
+        )
+        .into());
+    }
+    let signature = rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?;
+    Ok(config.output_format.formatted_string(&CliSignature {
+        signature: signature.to_string(),
+    }))
+}
+
+fn process_verify_offchain_signature(
+    config: &CliConfig,
+    signer_pubkey: &Option<Pubkey>,
+    signature: &Signature,
+    message: &OffchainMessage,
+) -> ProcessResult {
+    let signer = if let Some(pubkey) = signer_pubkey {
+        *pubkey
+    } else {
+        config.signers[0].pubkey()
+    };
+    if message.verify(&signer, signature) {
+        Ok("Signature is valid".to_string())
+    } else {
+        Err(CliError::BadParameter("Invalid signature".to_string()).into())
+    }
+}
+
+fn process_decode_transaction(
+    config: &CliConfig,
+    transaction: &VersionedTransaction,
+) -> ProcessResult {
     let sigverify_status = CliSignatureVerificationStatus::verify_transaction(transaction);
     let decode_transaction = CliTransaction {
         decoded_transaction: transaction.clone(),
-        transaction: EncodedTransaction::encode(transaction.clone(), UiTransactionEncoding::Json),
+        transaction: EncodedTransaction::encode_versioned(
+            transaction.clone(),
+            None,
+            UiTransactionEncoding::Json,
+        ),
         meta: None,
         block_time: None,
         slot: None,
@@ -1195,6 +1380,7 @@ fn process_transfer(
     from: SignerIndex,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     allow_unfunded_recipient: bool,
     no_wait: bool,
     blockhash_query: &BlockhashQuery,
@@ -1284,6 +1470,7 @@ fn process_transfer(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -1465,6 +1652,11 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             nonce_authority,
             memo,
             new_authority,
+            sign_only,
+            dump_transaction_message,
+            output_transaction,
+            blockhash_query,
+            fee_payer,
         } => process_authorize_nonce_account(
             &rpc_client,
             config,
@@ -1472,6 +1664,11 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *nonce_authority,
             memo.as_ref(),
             new_authority,
+            *sign_only,
+            *dump_transaction_message,
+            output_transaction.as_ref(),
+            blockhash_query,
+            *fee_payer,
         ),
         // Create nonce account
         CliCommand::CreateNonceAccount {
@@ -1498,12 +1695,22 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             nonce_account,
             nonce_authority,
             memo,
+            sign_only,
+            dump_transaction_message,
+            output_transaction,
+            blockhash_query,
+            fee_payer,
         } => process_new_nonce(
             &rpc_client,
             config,
             nonce_account,
             *nonce_authority,
             memo.as_ref(),
+            *sign_only,
+            *dump_transaction_message,
+            output_transaction.as_ref(),
+            blockhash_query,
+            *fee_payer,
         ),
         // Show the contents of a nonce account
         CliCommand::ShowNonceAccount {
@@ -1522,6 +1729,11 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             memo,
             destination_account_pubkey,
             lamports,
+            sign_only,
+            dump_transaction_message,
+            output_transaction,
+            blockhash_query,
+            fee_payer,
         } => process_withdraw_from_nonce_account(
             &rpc_client,
             config,
@@ -1530,6 +1742,11 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             memo.as_ref(),
             destination_account_pubkey,
             *lamports,
+            *sign_only,
+            *dump_transaction_message,
+            output_transaction.as_ref(),
+            blockhash_query,
+            *fee_payer,
         ),
 
         // Program Deployment
@@ -1565,6 +1782,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             amount,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             ref nonce_account,
             nonce_authority,
@@ -1583,6 +1801,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *amount,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             nonce_account.as_ref(),
             *nonce_authority,
@@ -1595,6 +1814,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             stake_authority,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority,
@@ -1608,6 +1828,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *stake_authority,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             *nonce_account,
             *nonce_authority,
@@ -1622,6 +1843,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             force,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority,
@@ -1636,6 +1858,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *force,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             *nonce_account,
             *nonce_authority,
@@ -1647,6 +1870,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             stake_authority,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority,
@@ -1662,6 +1886,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *stake_authority,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             *nonce_account,
             *nonce_authority,
@@ -1677,6 +1902,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             stake_authority,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority,
@@ -1690,6 +1916,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *stake_authority,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             *nonce_account,
             *nonce_authority,
@@ -1716,6 +1943,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             ref new_authorizations,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority,
@@ -1731,6 +1959,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *custodian,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             *nonce_account,
             *nonce_authority,
@@ -1745,6 +1974,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             new_custodian_signer,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority,
@@ -1759,6 +1989,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *custodian,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             *nonce_account,
             *nonce_authority,
@@ -1773,6 +2004,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             custodian,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             ref nonce_account,
             nonce_authority,
@@ -1789,6 +2021,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *custodian,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             blockhash_query,
             nonce_account.as_ref(),
             *nonce_authority,
@@ -1931,6 +2164,18 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
                 Ok("Signer is valid".to_string())
             }
         }
+        CliCommand::SignOffchainMessage { message } => {
+            process_sign_offchain_message(config, message)
+        }
+        CliCommand::SignTransaction {
+            transaction_file,
+            broadcast,
+        } => process_sign_transaction(&rpc_client, config, transaction_file, *broadcast),
+        CliCommand::VerifyOffchainSignature {
+            signer_pubkey,
+            signature,
+            message,
+        } => process_verify_offchain_signature(config, signer_pubkey, signature, message),
         CliCommand::ShowAccount {
             pubkey,
             output_file,
@@ -1942,6 +2187,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             from,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             allow_unfunded_recipient,
             no_wait,
             ref blockhash_query,
@@ -1959,6 +2205,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *from,
             *sign_only,
             *dump_transaction_message,
+            output_transaction.as_ref(),
             *allow_unfunded_recipient,
             *no_wait,
             blockhash_query,
@@ -2218,6 +2465,63 @@ pub fn app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> App<'ab, '
                         .help("The signer path to resolve")
                 )
         )
+        .subcommand(
+            SubCommand::with_name("sign-offchain-message")
+                .about("Sign an off-chain message with the default signer")
+                .arg(
+                    Arg::with_name("message")
+                        .index(1)
+                        .value_name("MESSAGE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The message text to sign"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify-offchain-signature")
+                .about("Verify an off-chain message signature")
+                .arg(
+                    Arg::with_name("message")
+                        .index(1)
+                        .value_name("MESSAGE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The message text that was signed"),
+                )
+                .arg(
+                    Arg::with_name("signature")
+                        .index(2)
+                        .value_name("SIGNATURE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The message signature to verify"),
+                )
+                .arg(
+                    pubkey!(Arg::with_name("signer")
+                        .long("signer")
+                        .value_name("SIGNER_ADDRESS")
+                        .required(false),
+                        "The address of the signer [default: cli config keypair]. "),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("sign-transaction")
+                .about("Add the default signer's signature to a partially signed transaction file")
+                .arg(
+                    Arg::with_name("transaction_file")
+                        .index(1)
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Transaction file written by a command run with `--sign-only --output-transaction`"),
+                )
+                .arg(
+                    Arg::with_name("broadcast")
+                        .long("broadcast")
+                        .takes_value(false)
+                        .help("Submit the transaction to the cluster once it carries all required signatures"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("transfer")
                 .about("Transfer funds between system accounts")
@@ -2312,6 +2616,7 @@ pub fn app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> App<'ab, '
 mod tests {
     use super::*;
     use serde_json::{json, Value};
+    use solana_cli_output::{parse_sign_only_reply_string, write_transaction_file};
     use solana_client::{
         blockhash_query,
         mock_sender::SIGNATURE,
@@ -2622,6 +2927,63 @@ mod tests {
                 signers: vec![],
             }
         );
+
+        // Test SignOffchainMessage Subcommand
+        let message = OffchainMessage::new(b"Hello, world!").unwrap();
+        let test_sign_offchain_message = test_commands.clone().get_matches_from(vec![
+            "test",
+            "sign-offchain-message",
+            "Hello, world!",
+        ]);
+        assert_eq!(
+            parse_command(&test_sign_offchain_message, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::SignOffchainMessage {
+                    message: message.clone(),
+                },
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
+            }
+        );
+
+        // Test SignTransaction Subcommand
+        let test_sign_transaction = test_commands.clone().get_matches_from(vec![
+            "test",
+            "sign-transaction",
+            "transaction.txt",
+            "--broadcast",
+        ]);
+        assert_eq!(
+            parse_command(&test_sign_transaction, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::SignTransaction {
+                    transaction_file: "transaction.txt".to_string(),
+                    broadcast: true,
+                },
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
+            }
+        );
+
+        // Test VerifyOffchainSignature Subcommand
+        let signature = Signature::new(&[1; 64]);
+        let test_verify_offchain_signature = test_commands.clone().get_matches_from(vec![
+            "test",
+            "verify-offchain-signature",
+            "Hello, world!",
+            &signature.to_string(),
+            "--signer",
+            &pubkey_string,
+        ]);
+        assert_eq!(
+            parse_command(&test_verify_offchain_signature, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VerifyOffchainSignature {
+                    signer_pubkey: Some(pubkey),
+                    signature,
+                    message,
+                },
+                signers: vec![],
+            }
+        );
     }
 
     #[test]
@@ -2640,6 +3002,25 @@ mod tests {
         config.command = CliCommand::Address;
         assert_eq!(process_command(&config).unwrap(), pubkey);
 
+        let message = OffchainMessage::new(b"Hello, world!").unwrap();
+        config.command = CliCommand::SignOffchainMessage {
+            message: message.clone(),
+        };
+        let signature: Signature = process_command(&config).unwrap().parse().unwrap();
+        assert!(message.verify(&keypair.pubkey(), &signature));
+        config.command = CliCommand::VerifyOffchainSignature {
+            signer_pubkey: None,
+            signature,
+            message: message.clone(),
+        };
+        assert!(process_command(&config).is_ok());
+        config.command = CliCommand::VerifyOffchainSignature {
+            signer_pubkey: Some(solana_sdk::pubkey::new_rand()),
+            signature,
+            message,
+        };
+        assert!(process_command(&config).is_err());
+
         config.command = CliCommand::Balance {
             pubkey: None,
             use_lamports_unit: true,
@@ -2716,6 +3097,7 @@ mod tests {
             amount: SpendAmount::Some(30),
             sign_only: false,
             dump_transaction_message: false,
+            output_transaction: None,
             blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
             nonce_account: None,
             nonce_authority: 0,
@@ -2737,6 +3119,7 @@ mod tests {
             custodian: None,
             sign_only: false,
             dump_transaction_message: false,
+            output_transaction: None,
             blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
             nonce_account: None,
             nonce_authority: 0,
@@ -2754,6 +3137,7 @@ mod tests {
             stake_authority: 0,
             sign_only: false,
             dump_transaction_message: false,
+            output_transaction: None,
             blockhash_query: BlockhashQuery::default(),
             nonce_account: None,
             nonce_authority: 0,
@@ -2771,6 +3155,7 @@ mod tests {
             stake_authority: 0,
             sign_only: false,
             dump_transaction_message: false,
+            output_transaction: None,
             blockhash_query: BlockhashQuery::default(),
             nonce_account: None,
             nonce_authority: 0,
@@ -2793,6 +3178,7 @@ mod tests {
             stake_authority: 1,
             sign_only: false,
             dump_transaction_message: false,
+            output_transaction: None,
             blockhash_query: BlockhashQuery::default(),
             nonce_account: None,
             nonce_authority: 0,
@@ -2980,6 +3366,7 @@ mod tests {
                     from: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     allow_unfunded_recipient: false,
                     no_wait: false,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -3007,6 +3394,7 @@ mod tests {
                     from: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     allow_unfunded_recipient: false,
                     no_wait: false,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -3039,6 +3427,7 @@ mod tests {
                     from: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     allow_unfunded_recipient: true,
                     no_wait: true,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -3074,6 +3463,7 @@ mod tests {
                     from: 0,
                     sign_only: true,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     allow_unfunded_recipient: false,
                     no_wait: false,
                     blockhash_query: BlockhashQuery::None(blockhash),
@@ -3114,6 +3504,7 @@ mod tests {
                     from: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     allow_unfunded_recipient: false,
                     no_wait: false,
                     blockhash_query: BlockhashQuery::FeeCalculator(
@@ -3158,6 +3549,7 @@ mod tests {
                     from: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     allow_unfunded_recipient: false,
                     no_wait: false,
                     blockhash_query: BlockhashQuery::FeeCalculator(
@@ -3200,6 +3592,7 @@ mod tests {
                     from: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     allow_unfunded_recipient: false,
                     no_wait: false,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -3214,4 +3607,64 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_cli_sign_transaction() {
+        let fee_payer = Keypair::new();
+        let source = Keypair::new();
+        let message = Message::new(
+            &[system_instruction::transfer(
+                &source.pubkey(),
+                &solana_sdk::pubkey::new_rand(),
+                42,
+            )],
+            Some(&fee_payer.pubkey()),
+        );
+        let mut tx = Transaction::new_unsigned(message);
+        tx.try_partial_sign(&[&fee_payer], Hash::new_unique())
+            .unwrap();
+        let transaction_file = make_tmp_path("transaction_file");
+        write_transaction_file(&transaction_file, &tx).unwrap();
+
+        // Not a required signer
+        let other = Keypair::new();
+        let mut config = CliConfig {
+            rpc_client: Some(Arc::new(RpcClient::new_mock("succeeds".to_string()))),
+            command: CliCommand::SignTransaction {
+                transaction_file: transaction_file.clone(),
+                broadcast: false,
+            },
+            signers: vec![&other],
+            output_format: OutputFormat::JsonCompact,
+            ..CliConfig::default()
+        };
+        assert!(process_command(&config).is_err());
+
+        // Incomplete transactions are not broadcast
+        config.signers = vec![&fee_payer];
+        config.command = CliCommand::SignTransaction {
+            transaction_file: transaction_file.clone(),
+            broadcast: true,
+        };
+        assert!(process_command(&config).is_err());
+
+        config.signers = vec![&source];
+        config.command = CliCommand::SignTransaction {
+            transaction_file: transaction_file.clone(),
+            broadcast: false,
+        };
+        let sign_only = parse_sign_only_reply_string(&process_command(&config).unwrap());
+        assert!(sign_only.has_all_signers());
+        let tx = read_transaction_file(&transaction_file).unwrap();
+        assert!(tx.verify_with_results().into_iter().all(|valid| valid));
+
+        config.command = CliCommand::SignTransaction {
+            transaction_file,
+            broadcast: true,
+        };
+        assert_eq!(
+            process_command(&config).unwrap(),
+            format!("{{\"signature\":\"{}\"}}", tx.signatures[0])
+        );
+    }
 }
@@ -8,19 +8,24 @@ use crate::{
     spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
 };
 use clap::{App, Arg, ArgMatches, SubCommand};
+use num_traits::FromPrimitive;
 use solana_clap_utils::{
+    fee_payer::{fee_payer_arg, FEE_PAYER_ARG},
     input_parsers::*,
     input_validators::*,
     keypair::{DefaultSigner, SignerIndex},
     memo::{memo_arg, MEMO_ARG},
     nonce::*,
+    offline::*,
 };
-use solana_cli_output::CliNonceAccount;
-use solana_client::{nonce_utils::*, rpc_client::RpcClient};
+use solana_cli_output::{return_signers_with_config, CliNonceAccount, ReturnSignersConfig};
+use solana_client::{blockhash_query::BlockhashQuery, nonce_utils::*, rpc_client::RpcClient};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     account::Account,
+    decode_error::DecodeError,
     hash::Hash,
+    instruction::Instruction,
     message::Message,
     nonce::{self, State},
     pubkey::Pubkey,
@@ -57,6 +62,8 @@ impl NonceSubCommands for App<'_, '_> {
                         "Account to be granted authority of the nonce account. "),
                 )
                 .arg(nonce_authority_arg())
+                .arg(fee_payer_arg())
+                .offline_args()
                 .arg(memo_arg()),
         )
         .subcommand(
@@ -118,6 +125,8 @@ impl NonceSubCommands for App<'_, '_> {
                         "Address of the nonce account. "),
                 )
                 .arg(nonce_authority_arg())
+                .arg(fee_payer_arg())
+                .offline_args()
                 .arg(memo_arg()),
         )
         .subcommand(
@@ -165,6 +174,8 @@ impl NonceSubCommands for App<'_, '_> {
                         .help("The amount to withdraw from the nonce account, in SOL"),
                 )
                 .arg(nonce_authority_arg())
+                .arg(fee_payer_arg())
+                .offline_args()
                 .arg(memo_arg()),
         )
     }
@@ -180,10 +191,16 @@ pub fn parse_authorize_nonce_account(
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
     let (nonce_authority, nonce_authority_pubkey) =
         signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
 
-    let payer_provided = None;
     let signer_info = default_signer.generate_unique_signers(
-        vec![payer_provided, nonce_authority],
+        vec![fee_payer, nonce_authority],
         matches,
         wallet_manager,
     )?;
@@ -194,6 +211,11 @@ pub fn parse_authorize_nonce_account(
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
             memo,
             new_authority,
+            sign_only,
+            dump_transaction_message,
+            output_transaction,
+            blockhash_query,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
         },
         signers: signer_info.signers,
     })
@@ -252,10 +274,16 @@ pub fn parse_new_nonce(
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
     let (nonce_authority, nonce_authority_pubkey) =
         signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
 
-    let payer_provided = None;
     let signer_info = default_signer.generate_unique_signers(
-        vec![payer_provided, nonce_authority],
+        vec![fee_payer, nonce_authority],
         matches,
         wallet_manager,
     )?;
@@ -265,6 +293,11 @@ pub fn parse_new_nonce(
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
             memo,
+            sign_only,
+            dump_transaction_message,
+            output_transaction,
+            blockhash_query,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
         },
         signers: signer_info.signers,
     })
@@ -299,10 +332,16 @@ pub fn parse_withdraw_from_nonce_account(
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
     let (nonce_authority, nonce_authority_pubkey) =
         signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
 
-    let payer_provided = None;
     let signer_info = default_signer.generate_unique_signers(
-        vec![payer_provided, nonce_authority],
+        vec![fee_payer, nonce_authority],
         matches,
         wallet_manager,
     )?;
@@ -314,6 +353,11 @@ pub fn parse_withdraw_from_nonce_account(
             memo,
             destination_account_pubkey,
             lamports,
+            sign_only,
+            dump_transaction_message,
+            output_transaction,
+            blockhash_query,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
         },
         signers: signer_info.signers,
     })
@@ -339,6 +383,54 @@ pub fn check_nonce_account(
     }
 }
 
+/// Sign a transaction of instructions requiring the nonce authority, then either return its
+/// signatures in sign-only mode or send it
+#[allow(clippy::too_many_arguments)]
+fn process_nonce_authority_transaction<E>(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    ixs: Vec<Instruction>,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    output_transaction: Option<&String>,
+    blockhash_query: &BlockhashQuery,
+    fee_payer: SignerIndex,
+) -> ProcessResult
+where
+    E: 'static + std::error::Error + DecodeError<E> + FromPrimitive,
+{
+    let (recent_blockhash, fee_calculator) =
+        blockhash_query.get_blockhash_and_fee_calculator(rpc_client, config.commitment)?;
+
+    let fee_payer = config.signers[fee_payer];
+    let message = Message::new(&ixs, Some(&fee_payer.pubkey()));
+    let mut tx = Transaction::new_unsigned(message);
+
+    if sign_only {
+        tx.try_partial_sign(&config.signers, recent_blockhash)?;
+        return_signers_with_config(
+            &tx,
+            &config.output_format,
+            &ReturnSignersConfig {
+                dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
+            },
+        )
+    } else {
+        tx.try_sign(&config.signers, recent_blockhash)?;
+        check_account_for_fee_with_commitment(
+            rpc_client,
+            &fee_payer.pubkey(),
+            &fee_calculator,
+            &tx.message,
+            config.commitment,
+        )?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
+        log_instruction_custom_error::<E>(result, config)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn process_authorize_nonce_account(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -346,9 +438,12 @@ pub fn process_authorize_nonce_account(
     nonce_authority: SignerIndex,
     memo: Option<&String>,
     new_authority: &Pubkey,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    output_transaction: Option<&String>,
+    blockhash_query: &BlockhashQuery,
+    fee_payer: SignerIndex,
 ) -> ProcessResult {
-    let (recent_blockhash, fee_calculator) = rpc_client.get_recent_blockhash()?;
-
     let nonce_authority = config.signers[nonce_authority];
     let ixs = vec![authorize_nonce_account(
         nonce_account,
@@ -356,19 +451,16 @@ pub fn process_authorize_nonce_account(
         new_authority,
     )]
     .with_memo(memo);
-    let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
-    tx.try_sign(&config.signers, recent_blockhash)?;
-
-    check_account_for_fee_with_commitment(
+    process_nonce_authority_transaction::<NonceError>(
         rpc_client,
-        &config.signers[0].pubkey(),
-        &fee_calculator,
-        &tx.message,
-        config.commitment,
-    )?;
-    let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
-    log_instruction_custom_error::<NonceError>(result, config)
+        config,
+        ixs,
+        sign_only,
+        dump_transaction_message,
+        output_transaction,
+        blockhash_query,
+        fee_payer,
+    )
 }
 
 pub fn process_create_nonce_account(
@@ -469,24 +561,32 @@ pub fn process_get_nonce(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn process_new_nonce(
     rpc_client: &RpcClient,
     config: &CliConfig,
     nonce_account: &Pubkey,
     nonce_authority: SignerIndex,
     memo: Option<&String>,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    output_transaction: Option<&String>,
+    blockhash_query: &BlockhashQuery,
+    fee_payer: SignerIndex,
 ) -> ProcessResult {
     check_unique_pubkeys(
-        (&config.signers[0].pubkey(), "cli keypair".to_string()),
+        (&config.signers[fee_payer].pubkey(), "fee_payer".to_string()),
         (nonce_account, "nonce_account_pubkey".to_string()),
     )?;
 
-    if let Err(err) = rpc_client.get_account(nonce_account) {
-        return Err(CliError::BadParameter(format!(
-            "Unable to advance nonce account {}. error: {}",
-            nonce_account, err
-        ))
-        .into());
+    if !sign_only {
+        if let Err(err) = rpc_client.get_account(nonce_account) {
+            return Err(CliError::BadParameter(format!(
+                "Unable to advance nonce account {}. error: {}",
+                nonce_account, err
+            ))
+            .into());
+        }
     }
 
     let nonce_authority = config.signers[nonce_authority];
@@ -495,19 +595,16 @@ pub fn process_new_nonce(
         &nonce_authority.pubkey(),
     )]
     .with_memo(memo);
-    let (recent_blockhash, fee_calculator) = rpc_client.get_recent_blockhash()?;
-    let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
-    tx.try_sign(&config.signers, recent_blockhash)?;
-    check_account_for_fee_with_commitment(
+    process_nonce_authority_transaction::<SystemError>(
         rpc_client,
-        &config.signers[0].pubkey(),
-        &fee_calculator,
-        &tx.message,
-        config.commitment,
-    )?;
-    let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
-    log_instruction_custom_error::<SystemError>(result, config)
+        config,
+        ixs,
+        sign_only,
+        dump_transaction_message,
+        output_transaction,
+        blockhash_query,
+        fee_payer,
+    )
 }
 
 pub fn process_show_nonce_account(
@@ -540,6 +637,7 @@ pub fn process_show_nonce_account(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn process_withdraw_from_nonce_account(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -548,9 +646,12 @@ pub fn process_withdraw_from_nonce_account(
     memo: Option<&String>,
     destination_account_pubkey: &Pubkey,
     lamports: u64,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    output_transaction: Option<&String>,
+    blockhash_query: &BlockhashQuery,
+    fee_payer: SignerIndex,
 ) -> ProcessResult {
-    let (recent_blockhash, fee_calculator) = rpc_client.get_recent_blockhash()?;
-
     let nonce_authority = config.signers[nonce_authority];
     let ixs = vec![withdraw_nonce_account(
         nonce_account,
@@ -559,18 +660,16 @@ pub fn process_withdraw_from_nonce_account(
         lamports,
     )]
     .with_memo(memo);
-    let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
-    tx.try_sign(&config.signers, recent_blockhash)?;
-    check_account_for_fee_with_commitment(
+    process_nonce_authority_transaction::<NonceError>(
         rpc_client,
-        &config.signers[0].pubkey(),
-        &fee_calculator,
-        &tx.message,
-        config.commitment,
-    )?;
-    let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
-    log_instruction_custom_error::<NonceError>(result, config)
+        config,
+        ixs,
+        sign_only,
+        dump_transaction_message,
+        output_transaction,
+        blockhash_query,
+        fee_payer,
+    )
 }
 
 #[cfg(test)]
@@ -626,6 +725,11 @@ mod tests {
                     nonce_authority: 0,
                     memo: None,
                     new_authority: Pubkey::default(),
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    output_transaction: None,
+                    blockhash_query: BlockhashQuery::default(),
+                    fee_payer: 0,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -648,6 +752,11 @@ mod tests {
                     nonce_authority: 1,
                     memo: None,
                     new_authority: Pubkey::default(),
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    output_transaction: None,
+                    blockhash_query: BlockhashQuery::default(),
+                    fee_payer: 0,
                 },
                 signers: vec![
                     read_keypair_file(&default_keypair_file).unwrap().into(),
@@ -733,6 +842,11 @@ mod tests {
                     nonce_account: nonce_account.pubkey(),
                     nonce_authority: 0,
                     memo: None,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    output_transaction: None,
+                    blockhash_query: BlockhashQuery::default(),
+                    fee_payer: 0,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -754,6 +868,11 @@ mod tests {
                     nonce_account: nonce_account.pubkey(),
                     nonce_authority: 1,
                     memo: None,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    output_transaction: None,
+                    blockhash_query: BlockhashQuery::default(),
+                    fee_payer: 0,
                 },
                 signers: vec![
                     read_keypair_file(&default_keypair_file).unwrap().into(),
@@ -800,7 +919,12 @@ mod tests {
                     nonce_authority: 0,
                     memo: None,
                     destination_account_pubkey: nonce_account_pubkey,
-                    lamports: 42_000_000_000
+                    lamports: 42_000_000_000,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    output_transaction: None,
+                    blockhash_query: BlockhashQuery::default(),
+                    fee_payer: 0,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -829,7 +953,90 @@ mod tests {
                     nonce_authority: 1,
                     memo: None,
                     destination_account_pubkey: nonce_account_pubkey,
-                    lamports: 42_000_000_000
+                    lamports: 42_000_000_000,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    output_transaction: None,
+                    blockhash_query: BlockhashQuery::default(),
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into()
+                ],
+            }
+        );
+
+        // Test WithdrawFromNonceAccount Subcommand, signing offline for the nonce authority
+        let blockhash = hash(&[1u8]);
+        let blockhash_string = blockhash.to_string();
+        let test_withdraw_from_nonce_account = test_commands.clone().get_matches_from(vec![
+            "test",
+            "withdraw-from-nonce-account",
+            &keypair_file,
+            &nonce_account_string,
+            "42",
+            "--nonce-authority",
+            &authority_keypair_file,
+            "--sign-only",
+            "--blockhash",
+            &blockhash_string,
+            "--output-transaction",
+            "withdraw.tx",
+        ]);
+        assert_eq!(
+            parse_command(
+                &test_withdraw_from_nonce_account,
+                &default_signer,
+                &mut None
+            )
+            .unwrap(),
+            CliCommandInfo {
+                command: CliCommand::WithdrawFromNonceAccount {
+                    nonce_account: read_keypair_file(&keypair_file).unwrap().pubkey(),
+                    nonce_authority: 1,
+                    memo: None,
+                    destination_account_pubkey: nonce_account_pubkey,
+                    lamports: 42_000_000_000,
+                    sign_only: true,
+                    dump_transaction_message: false,
+                    output_transaction: Some("withdraw.tx".to_string()),
+                    blockhash_query: BlockhashQuery::None(blockhash),
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into()
+                ],
+            }
+        );
+
+        // Test AuthorizeNonceAccount Subcommand with a separate fee payer
+        let test_authorize_nonce_account = test_commands.clone().get_matches_from(vec![
+            "test",
+            "authorize-nonce-account",
+            &keypair_file,
+            &Pubkey::default().to_string(),
+            "--fee-payer",
+            &authority_keypair_file,
+            "--sign-only",
+            "--blockhash",
+            &blockhash_string,
+            "--dump-transaction-message",
+        ]);
+        assert_eq!(
+            parse_command(&test_authorize_nonce_account, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::AuthorizeNonceAccount {
+                    nonce_account: read_keypair_file(&keypair_file).unwrap().pubkey(),
+                    nonce_authority: 0,
+                    memo: None,
+                    new_authority: Pubkey::default(),
+                    sign_only: true,
+                    dump_transaction_message: true,
+                    output_transaction: None,
+                    blockhash_query: BlockhashQuery::None(blockhash),
+                    fee_payer: 1,
                 },
                 signers: vec![
                     read_keypair_file(&default_keypair_file).unwrap().into(),
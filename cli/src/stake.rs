@@ -675,6 +675,9 @@ pub fn parse_create_stake_account(
     let amount = SpendAmount::new_from_matches(matches, "amount");
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of_signer(matches, NONCE_ARG.name, wallet_manager)?;
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -714,6 +717,7 @@ pub fn parse_create_stake_account(
             amount,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -737,6 +741,9 @@ pub fn parse_stake_delegate_stake(
     let force = matches.is_present("force");
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of(matches, NONCE_ARG.name);
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -761,6 +768,7 @@ pub fn parse_stake_delegate_stake(
             force,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -838,6 +846,9 @@ pub fn parse_stake_authorize(
     };
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of(matches, NONCE_ARG.name);
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -881,6 +892,7 @@ pub fn parse_stake_authorize(
             new_authorizations,
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -907,6 +919,9 @@ pub fn parse_split_stake(
 
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of(matches, NONCE_ARG.name);
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -929,6 +944,7 @@ pub fn parse_split_stake(
             stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -954,6 +970,9 @@ pub fn parse_merge_stake(
 
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of(matches, NONCE_ARG.name);
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -977,6 +996,7 @@ pub fn parse_merge_stake(
             stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -996,6 +1016,9 @@ pub fn parse_stake_deactivate_stake(
         pubkey_of_signer(matches, "stake_account_pubkey", wallet_manager)?.unwrap();
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of(matches, NONCE_ARG.name);
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -1019,6 +1042,7 @@ pub fn parse_stake_deactivate_stake(
             stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -1042,6 +1066,9 @@ pub fn parse_stake_withdraw_stake(
     let amount = SpendAmount::new_from_matches(matches, "amount");
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of(matches, NONCE_ARG.name);
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -1071,6 +1098,7 @@ pub fn parse_stake_withdraw_stake(
             withdraw_authority: signer_info.index_of(withdraw_authority_pubkey).unwrap(),
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -1105,6 +1133,9 @@ pub fn parse_stake_set_lockup(
 
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let output_transaction = matches
+        .value_of(OUTPUT_TRANSACTION_ARG.name)
+        .map(String::from);
     let blockhash_query = BlockhashQuery::new_from_matches(matches);
     let nonce_account = pubkey_of(matches, NONCE_ARG.name);
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
@@ -1140,6 +1171,7 @@ pub fn parse_stake_set_lockup(
             custodian: signer_info.index_of(custodian_pubkey).unwrap(),
             sign_only,
             dump_transaction_message,
+            output_transaction,
             blockhash_query,
             nonce_account,
             nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
@@ -1197,6 +1229,7 @@ pub fn process_create_stake_account(
     amount: SpendAmount,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<&Pubkey>,
     nonce_authority: SignerIndex,
@@ -1328,6 +1361,7 @@ pub fn process_create_stake_account(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -1346,6 +1380,7 @@ pub fn process_stake_authorize(
     custodian: Option<SignerIndex>,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<Pubkey>,
     nonce_authority: SignerIndex,
@@ -1412,6 +1447,7 @@ pub fn process_stake_authorize(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -1448,6 +1484,7 @@ pub fn process_deactivate_stake_account(
     stake_authority: SignerIndex,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<Pubkey>,
     nonce_authority: SignerIndex,
@@ -1492,6 +1529,7 @@ pub fn process_deactivate_stake_account(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -1527,6 +1565,7 @@ pub fn process_withdraw_stake(
     custodian: Option<SignerIndex>,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<&Pubkey>,
     nonce_authority: SignerIndex,
@@ -1591,6 +1630,7 @@ pub fn process_withdraw_stake(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -1623,6 +1663,7 @@ pub fn process_split_stake(
     stake_authority: SignerIndex,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<Pubkey>,
     nonce_authority: SignerIndex,
@@ -1736,6 +1777,7 @@ pub fn process_split_stake(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -1769,6 +1811,7 @@ pub fn process_merge_stake(
     stake_authority: SignerIndex,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<Pubkey>,
     nonce_authority: SignerIndex,
@@ -1843,6 +1886,7 @@ pub fn process_merge_stake(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -1881,6 +1925,7 @@ pub fn process_stake_set_lockup(
     custodian: SignerIndex,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<Pubkey>,
     nonce_authority: SignerIndex,
@@ -1919,6 +1964,7 @@ pub fn process_stake_set_lockup(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -2231,6 +2277,7 @@ pub fn process_delegate_stake(
     force: bool,
     sign_only: bool,
     dump_transaction_message: bool,
+    output_transaction: Option<&String>,
     blockhash_query: &BlockhashQuery,
     nonce_account: Option<Pubkey>,
     nonce_authority: SignerIndex,
@@ -2322,6 +2369,7 @@ pub fn process_delegate_stake(
             &config.output_format,
             &ReturnSignersConfig {
                 dump_transaction_message,
+                output_transaction: output_transaction.cloned(),
             },
         )
     } else {
@@ -2428,6 +2476,7 @@ mod tests {
                     ],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2476,6 +2525,7 @@ mod tests {
                     ],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2528,6 +2578,7 @@ mod tests {
                     ],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2564,6 +2615,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2597,6 +2649,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2636,6 +2689,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2672,6 +2726,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2705,6 +2760,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2744,6 +2800,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2790,6 +2847,7 @@ mod tests {
                     ],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2841,6 +2899,7 @@ mod tests {
                     ],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2894,6 +2953,7 @@ mod tests {
                     ],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2931,6 +2991,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -2967,6 +3028,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3007,6 +3069,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3044,6 +3107,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3080,6 +3144,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3120,6 +3185,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3161,6 +3227,7 @@ mod tests {
                     }],
                     sign_only: true,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::None(blockhash),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3203,6 +3270,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::Cluster,
                         blockhash
@@ -3258,6 +3326,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::NonceAccount(nonce_account),
                         blockhash
@@ -3299,6 +3368,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::Cluster,
                         blockhash
@@ -3345,6 +3415,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::NonceAccount(nonce_account_pubkey),
                         blockhash
@@ -3390,6 +3461,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3433,6 +3505,7 @@ mod tests {
                     }],
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::Cluster,
                         blockhash
@@ -3487,6 +3560,7 @@ mod tests {
                     amount: SpendAmount::Some(50_000_000_000),
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3527,6 +3601,7 @@ mod tests {
                     amount: SpendAmount::Some(50_000_000_000),
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3566,6 +3641,7 @@ mod tests {
                     amount: SpendAmount::Some(50_000_000_000),
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3635,6 +3711,7 @@ mod tests {
                     amount: SpendAmount::Some(50_000_000_000),
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::NonceAccount(nonce_account),
                         nonce_hash
@@ -3671,6 +3748,7 @@ mod tests {
                     force: false,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::default(),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3702,6 +3780,7 @@ mod tests {
                     force: false,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::default(),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3735,6 +3814,7 @@ mod tests {
                     force: true,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::default(),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3766,6 +3846,7 @@ mod tests {
                     force: false,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::Cluster,
                         blockhash
@@ -3798,6 +3879,7 @@ mod tests {
                     force: false,
                     sign_only: true,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::None(blockhash),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3834,6 +3916,7 @@ mod tests {
                     force: false,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::Cluster,
                         blockhash
@@ -3882,6 +3965,7 @@ mod tests {
                     force: false,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::NonceAccount(nonce_account),
                         blockhash
@@ -3921,6 +4005,7 @@ mod tests {
                     force: false,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3954,6 +4039,7 @@ mod tests {
                     custodian: None,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -3987,6 +4073,7 @@ mod tests {
                     custodian: None,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -4025,6 +4112,7 @@ mod tests {
                     custodian: Some(1),
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -4071,6 +4159,7 @@ mod tests {
                     custodian: None,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::NonceAccount(nonce_account),
                         nonce_hash
@@ -4104,6 +4193,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::default(),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -4131,6 +4221,7 @@ mod tests {
                     stake_authority: 1,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::default(),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -4165,6 +4256,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::Cluster,
                         blockhash
@@ -4195,6 +4287,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: true,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::None(blockhash),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -4229,6 +4322,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::Cluster,
                         blockhash
@@ -4275,6 +4369,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::NonceAccount(nonce_account),
                         blockhash
@@ -4309,6 +4404,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -4346,6 +4442,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::default(),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -4409,6 +4506,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::FeeCalculator(
                         blockhash_query::Source::NonceAccount(nonce_account),
                         nonce_hash
@@ -4452,6 +4550,7 @@ mod tests {
                     stake_authority: 0,
                     sign_only: false,
                     dump_transaction_message: false,
+                    output_transaction: None,
                     blockhash_query: BlockhashQuery::default(),
                     nonce_account: None,
                     nonce_authority: 0,
@@ -140,6 +140,11 @@ fn full_battery_tests(
         nonce_account,
         nonce_authority: index,
         memo: None,
+        sign_only: false,
+        dump_transaction_message: false,
+        output_transaction: None,
+        blockhash_query: BlockhashQuery::default(),
+        fee_payer: 0,
     };
     process_command(&config_payer).unwrap();
 
@@ -160,6 +165,11 @@ fn full_battery_tests(
         memo: None,
         destination_account_pubkey: payee_pubkey,
         lamports: 100,
+        sign_only: false,
+        dump_transaction_message: false,
+        output_transaction: None,
+        blockhash_query: BlockhashQuery::default(),
+        fee_payer: 0,
     };
     process_command(&config_payer).unwrap();
     check_recent_balance(1000, &rpc_client, &config_payer.signers[0].pubkey());
@@ -180,6 +190,11 @@ fn full_battery_tests(
         nonce_authority: index,
         memo: None,
         new_authority: new_authority.pubkey(),
+        sign_only: false,
+        dump_transaction_message: false,
+        output_transaction: None,
+        blockhash_query: BlockhashQuery::default(),
+        fee_payer: 0,
     };
     process_command(&config_payer).unwrap();
 
@@ -188,6 +203,11 @@ fn full_battery_tests(
         nonce_account,
         nonce_authority: index,
         memo: None,
+        sign_only: false,
+        dump_transaction_message: false,
+        output_transaction: None,
+        blockhash_query: BlockhashQuery::default(),
+        fee_payer: 0,
     };
     process_command(&config_payer).unwrap_err();
 
@@ -197,6 +217,11 @@ fn full_battery_tests(
         nonce_account,
         nonce_authority: 1,
         memo: None,
+        sign_only: false,
+        dump_transaction_message: false,
+        output_transaction: None,
+        blockhash_query: BlockhashQuery::default(),
+        fee_payer: 0,
     };
     process_command(&config_payer).unwrap();
 
@@ -207,6 +232,11 @@ fn full_battery_tests(
         memo: None,
         destination_account_pubkey: payee_pubkey,
         lamports: 100,
+        sign_only: false,
+        dump_transaction_message: false,
+        output_transaction: None,
+        blockhash_query: BlockhashQuery::default(),
+        fee_payer: 0,
     };
     process_command(&config_payer).unwrap();
     check_recent_balance(1000, &rpc_client, &config_payer.signers[0].pubkey());
@@ -301,6 +331,7 @@ fn test_create_account_with_seed() {
         from: 0,
         sign_only: true,
         dump_transaction_message: true,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::None(nonce_hash),
@@ -327,6 +358,7 @@ fn test_create_account_with_seed() {
         from: 0,
         sign_only: false,
         dump_transaction_message: true,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::FeeCalculator(
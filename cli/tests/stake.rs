@@ -72,6 +72,7 @@ fn test_stake_delegation_force() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -90,6 +91,7 @@ fn test_stake_delegation_force() {
         force: false,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -106,6 +108,7 @@ fn test_stake_delegation_force() {
         force: true,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -161,6 +164,7 @@ fn test_seed_stake_delegation_and_deactivation() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -178,6 +182,7 @@ fn test_seed_stake_delegation_and_deactivation() {
         force: true,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -192,6 +197,7 @@ fn test_seed_stake_delegation_and_deactivation() {
         stake_authority: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -243,6 +249,7 @@ fn test_stake_delegation_and_deactivation() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -261,6 +268,7 @@ fn test_stake_delegation_and_deactivation() {
         force: true,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -275,6 +283,7 @@ fn test_stake_delegation_and_deactivation() {
         stake_authority: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -346,6 +355,7 @@ fn test_offline_stake_delegation_and_deactivation() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -364,6 +374,7 @@ fn test_offline_stake_delegation_and_deactivation() {
         force: true,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -385,6 +396,7 @@ fn test_offline_stake_delegation_and_deactivation() {
         force: true,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(blockhash_query::Source::Cluster, blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -400,6 +412,7 @@ fn test_offline_stake_delegation_and_deactivation() {
         stake_authority: 0,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -419,6 +432,7 @@ fn test_offline_stake_delegation_and_deactivation() {
         stake_authority: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(blockhash_query::Source::Cluster, blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -467,6 +481,7 @@ fn test_nonced_stake_delegation_and_deactivation() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -507,6 +522,7 @@ fn test_nonced_stake_delegation_and_deactivation() {
         force: true,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_account.pubkey()),
             nonce_hash,
@@ -534,6 +550,7 @@ fn test_nonced_stake_delegation_and_deactivation() {
         stake_authority: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_account.pubkey()),
             nonce_hash,
@@ -599,6 +616,7 @@ fn test_stake_authorize() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -622,6 +640,7 @@ fn test_stake_authorize() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -663,6 +682,7 @@ fn test_stake_authorize() {
         ],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -694,6 +714,7 @@ fn test_stake_authorize() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -725,6 +746,7 @@ fn test_stake_authorize() {
         }],
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -749,6 +771,7 @@ fn test_stake_authorize() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(blockhash_query::Source::Cluster, blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -805,6 +828,7 @@ fn test_stake_authorize() {
         }],
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(nonce_hash),
         nonce_account: Some(nonce_account.pubkey()),
         nonce_authority: 0,
@@ -830,6 +854,7 @@ fn test_stake_authorize() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_account.pubkey()),
             sign_only.blockhash,
@@ -925,6 +950,7 @@ fn test_stake_authorize_with_fee_payer() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -948,6 +974,7 @@ fn test_stake_authorize_with_fee_payer() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -975,6 +1002,7 @@ fn test_stake_authorize_with_fee_payer() {
         }],
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -999,6 +1027,7 @@ fn test_stake_authorize_with_fee_payer() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(blockhash_query::Source::Cluster, blockhash),
         nonce_account: None,
         nonce_authority: 0,
@@ -1070,6 +1099,7 @@ fn test_stake_split() {
         amount: SpendAmount::Some(10 * minimum_stake_balance),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -1119,6 +1149,7 @@ fn test_stake_split() {
         stake_authority: 0,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(nonce_hash),
         nonce_account: Some(nonce_account.pubkey()),
         nonce_authority: 0,
@@ -1139,6 +1170,7 @@ fn test_stake_split() {
         stake_authority: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_account.pubkey()),
             sign_only.blockhash,
@@ -1226,6 +1258,7 @@ fn test_stake_set_lockup() {
         amount: SpendAmount::Some(10 * minimum_stake_balance),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -1254,6 +1287,7 @@ fn test_stake_set_lockup() {
         custodian: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1290,6 +1324,7 @@ fn test_stake_set_lockup() {
         custodian: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1311,6 +1346,7 @@ fn test_stake_set_lockup() {
         custodian: 1,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1344,6 +1380,7 @@ fn test_stake_set_lockup() {
         custodian: 1,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1392,6 +1429,7 @@ fn test_stake_set_lockup() {
         custodian: 0,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(nonce_hash),
         nonce_account: Some(nonce_account_pubkey),
         nonce_authority: 0,
@@ -1411,6 +1449,7 @@ fn test_stake_set_lockup() {
         custodian: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_account_pubkey),
             sign_only.blockhash,
@@ -1508,6 +1547,7 @@ fn test_offline_nonced_create_stake_account_and_withdraw() {
         amount: SpendAmount::Some(50_000),
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(nonce_hash),
         nonce_account: Some(nonce_pubkey),
         nonce_authority: 0,
@@ -1532,6 +1572,7 @@ fn test_offline_nonced_create_stake_account_and_withdraw() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_pubkey),
             sign_only.blockhash,
@@ -1567,6 +1608,7 @@ fn test_offline_nonced_create_stake_account_and_withdraw() {
         custodian: None,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(nonce_hash),
         nonce_account: Some(nonce_pubkey),
         nonce_authority: 0,
@@ -1586,6 +1628,7 @@ fn test_offline_nonced_create_stake_account_and_withdraw() {
         custodian: None,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_pubkey),
             sign_only.blockhash,
@@ -1622,6 +1665,7 @@ fn test_offline_nonced_create_stake_account_and_withdraw() {
         amount: SpendAmount::Some(50_000),
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::None(nonce_hash),
         nonce_account: Some(nonce_pubkey),
         nonce_authority: 0,
@@ -1644,6 +1688,7 @@ fn test_offline_nonced_create_stake_account_and_withdraw() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::FeeCalculator(
             blockhash_query::Source::NonceAccount(nonce_pubkey),
             sign_only.blockhash,
@@ -1697,6 +1742,7 @@ fn test_stake_checked_instructions() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -1717,6 +1763,7 @@ fn test_stake_checked_instructions() {
         amount: SpendAmount::Some(50_000),
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
         nonce_account: None,
         nonce_authority: 0,
@@ -1740,6 +1787,7 @@ fn test_stake_checked_instructions() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1761,6 +1809,7 @@ fn test_stake_checked_instructions() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1791,6 +1840,7 @@ fn test_stake_checked_instructions() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1816,6 +1866,7 @@ fn test_stake_checked_instructions() {
         }],
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1849,6 +1900,7 @@ fn test_stake_checked_instructions() {
         custodian: 1,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -1865,6 +1917,7 @@ fn test_stake_checked_instructions() {
         custodian: 1,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         blockhash_query: BlockhashQuery::default(),
         nonce_account: None,
         nonce_authority: 0,
@@ -59,6 +59,7 @@ fn test_transfer() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -80,6 +81,7 @@ fn test_transfer() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -113,6 +115,7 @@ fn test_transfer() {
         from: 0,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::None(blockhash),
@@ -135,6 +138,7 @@ fn test_transfer() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::FeeCalculator(blockhash_query::Source::Cluster, blockhash),
@@ -183,6 +187,7 @@ fn test_transfer() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::FeeCalculator(
@@ -216,6 +221,11 @@ fn test_transfer() {
         nonce_authority: 0,
         memo: None,
         new_authority: offline_pubkey,
+        sign_only: false,
+        dump_transaction_message: false,
+        output_transaction: None,
+        blockhash_query: BlockhashQuery::default(),
+        fee_payer: 0,
     };
     process_command(&config).unwrap();
     check_recent_balance(49_975 - minimum_nonce_balance, &rpc_client, &sender_pubkey);
@@ -238,6 +248,7 @@ fn test_transfer() {
         from: 0,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::None(nonce_hash),
@@ -259,6 +270,7 @@ fn test_transfer() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::FeeCalculator(
@@ -333,6 +345,7 @@ fn test_transfer_multisession_signing() {
         from: 1,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::None(blockhash),
@@ -364,6 +377,7 @@ fn test_transfer_multisession_signing() {
         from: 1,
         sign_only: true,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::None(blockhash),
@@ -392,6 +406,7 @@ fn test_transfer_multisession_signing() {
         from: 1,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::FeeCalculator(blockhash_query::Source::Cluster, blockhash),
@@ -447,6 +462,7 @@ fn test_transfer_all() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -500,6 +516,7 @@ fn test_transfer_unfunded_recipient() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: false,
         no_wait: false,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
@@ -563,6 +580,7 @@ fn test_transfer_with_seed() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
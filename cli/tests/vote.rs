@@ -70,6 +70,7 @@ fn test_vote_authorize_and_withdraw() {
         from: 0,
         sign_only: false,
         dump_transaction_message: false,
+        output_transaction: None,
         allow_unfunded_recipient: true,
         no_wait: false,
         blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
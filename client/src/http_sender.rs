@@ -8,6 +8,7 @@ use {
         rpc_response::RpcSimulateTransactionResult,
         rpc_sender::RpcSender,
     },
+    async_trait::async_trait,
     log::*,
     reqwest::{
         self,
@@ -19,13 +20,13 @@ use {
             atomic::{AtomicU64, Ordering},
             Arc,
         },
-        thread::sleep,
         time::Duration,
     },
+    tokio::time::sleep,
 };
 
 pub struct HttpSender {
-    client: Arc<reqwest::blocking::Client>,
+    client: Arc<reqwest::Client>,
     url: String,
     request_id: AtomicU64,
 }
@@ -44,15 +45,11 @@ impl HttpSender {
     ///
     /// The URL is an HTTP URL, usually for port 8899.
     pub fn new_with_timeout(url: String, timeout: Duration) -> Self {
-        // `reqwest::blocking::Client` panics if run in a tokio async context.  Shuttle the
-        // request to a different tokio thread to avoid this
         let client = Arc::new(
-            tokio::task::block_in_place(move || {
-                reqwest::blocking::Client::builder()
-                    .timeout(timeout)
-                    .build()
-            })
-            .expect("build rpc client"),
+            reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .expect("build rpc client"),
         );
 
         Self {
@@ -70,106 +67,176 @@ struct RpcErrorObject {
     data: serde_json::Value,
 }
 
-impl RpcSender for HttpSender {
-    fn send(&self, request: RpcRequest, params: serde_json::Value) -> Result<serde_json::Value> {
-        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
-        let request_json = request.build_request_json(request_id, params).to_string();
-
+impl HttpSender {
+    /// Post a JSON-RPC request body, retrying while the server asks us to slow down, and
+    /// return the parsed JSON response.
+    async fn post(&self, request_json: String) -> Result<serde_json::Value> {
         let mut too_many_requests_retries = 5;
         loop {
-            // `reqwest::blocking::Client` panics if run in a tokio async context.  Shuttle the
-            // request to a different tokio thread to avoid this
-            let response = {
-                let client = self.client.clone();
-                let request_json = request_json.clone();
-                tokio::task::block_in_place(move || {
-                    client
-                        .post(&self.url)
-                        .header(CONTENT_TYPE, "application/json")
-                        .body(request_json)
-                        .send()
-                })
-            };
-
-            match response {
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        if response.status() == StatusCode::TOO_MANY_REQUESTS
-                            && too_many_requests_retries > 0
-                        {
-                            let mut duration = Duration::from_millis(500);
-                            if let Some(retry_after) = response.headers().get(RETRY_AFTER) {
-                                if let Ok(retry_after) = retry_after.to_str() {
-                                    if let Ok(retry_after) = retry_after.parse::<u64>() {
-                                        if retry_after < 120 {
-                                            duration = Duration::from_secs(retry_after);
-                                        }
-                                    }
+            let response = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(request_json.clone())
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                if response.status() == StatusCode::TOO_MANY_REQUESTS
+                    && too_many_requests_retries > 0
+                {
+                    let mut duration = Duration::from_millis(500);
+                    if let Some(retry_after) = response.headers().get(RETRY_AFTER) {
+                        if let Ok(retry_after) = retry_after.to_str() {
+                            if let Ok(retry_after) = retry_after.parse::<u64>() {
+                                if retry_after < 120 {
+                                    duration = Duration::from_secs(retry_after);
                                 }
                             }
+                        }
+                    }
 
-                            too_many_requests_retries -= 1;
-                            debug!(
-                                "Too many requests: server responded with {:?}, {} retries left, pausing for {:?}",
-                                response, too_many_requests_retries, duration
-                            );
+                    too_many_requests_retries -= 1;
+                    debug!(
+                        "Too many requests: server responded with {:?}, {} retries left, pausing for {:?}",
+                        response, too_many_requests_retries, duration
+                    );
 
-                            sleep(duration);
-                            continue;
+                    sleep(duration).await;
+                    continue;
+                }
+                return Err(response.error_for_status().unwrap_err().into());
+            }
+
+            let response_text = response.text().await?;
+            return Ok(serde_json::from_str(&response_text)?);
+        }
+    }
+}
+
+/// Extract the result, or the error, from a single JSON-RPC response object.
+fn parse_response(json: &serde_json::Value) -> Result<serde_json::Value> {
+    if json["error"].is_object() {
+        return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
+            Ok(rpc_error_object) => {
+                let data = match rpc_error_object.code {
+                    rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                        match serde_json::from_value::<RpcSimulateTransactionResult>(
+                            json["error"]["data"].clone(),
+                        ) {
+                            Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
+                            Err(err) => {
+                                debug!(
+                                    "Failed to deserialize RpcSimulateTransactionResult: {:?}",
+                                    err
+                                );
+                                RpcResponseErrorData::Empty
+                            }
                         }
-                        return Err(response.error_for_status().unwrap_err().into());
                     }
-
-                    let response_text = tokio::task::block_in_place(move || response.text())?;
-
-                    let json: serde_json::Value = serde_json::from_str(&response_text)?;
-                    if json["error"].is_object() {
-                        return match serde_json::from_value::<RpcErrorObject>(json["error"].clone())
-                        {
-                            Ok(rpc_error_object) => {
-                                let data = match rpc_error_object.code {
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
-                                        match serde_json::from_value::<RpcSimulateTransactionResult>(json["error"]["data"].clone()) {
-                                            Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
-                                            Err(err) => {
-                                                debug!("Failed to deserialize RpcSimulateTransactionResult: {:?}", err);
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
-                                        match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(json["error"]["data"].clone()) {
-                                            Ok(rpc_custom_error::NodeUnhealthyErrorData {num_slots_behind}) => RpcResponseErrorData::NodeUnhealthy {num_slots_behind},
-                                            Err(_err) => {
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    _ => RpcResponseErrorData::Empty
-                                };
-
-                                Err(RpcError::RpcResponseError {
-                                    code: rpc_error_object.code,
-                                    message: rpc_error_object.message,
-                                    data,
-                                }
-                                .into())
+                    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
+                        match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(
+                            json["error"]["data"].clone(),
+                        ) {
+                            Ok(rpc_custom_error::NodeUnhealthyErrorData { num_slots_behind }) => {
+                                RpcResponseErrorData::NodeUnhealthy { num_slots_behind }
                             }
-                            Err(err) => Err(RpcError::RpcRequestError(format!(
-                                "Failed to deserialize RPC error response: {} [{}]",
-                                serde_json::to_string(&json["error"]).unwrap(),
-                                err
-                            ))
-                            .into()),
-                        };
+                            Err(_err) => RpcResponseErrorData::Empty,
+                        }
                     }
-                    return Ok(json["result"].clone());
-                }
-                Err(err) => {
-                    return Err(err.into());
+                    _ => RpcResponseErrorData::Empty,
+                };
+
+                Err(RpcError::RpcResponseError {
+                    code: rpc_error_object.code,
+                    message: rpc_error_object.message,
+                    data,
                 }
+                .into())
+            }
+            Err(err) => Err(RpcError::RpcRequestError(format!(
+                "Failed to deserialize RPC error response: {} [{}]",
+                serde_json::to_string(&json["error"]).unwrap(),
+                err
+            ))
+            .into()),
+        };
+    }
+    Ok(json["result"].clone())
+}
+
+#[async_trait]
+impl RpcSender for HttpSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let request_json = request.build_request_json(request_id, params).to_string();
+        parse_response(&self.post(request_json).await?)
+    }
+
+    async fn send_batch(
+        &self,
+        requests: Vec<(RpcRequest, serde_json::Value)>,
+    ) -> Result<Vec<Result<serde_json::Value>>> {
+        if requests.is_empty() {
+            return Ok(vec![]);
+        }
+        let first_request_id = self
+            .request_id
+            .fetch_add(requests.len() as u64, Ordering::Relaxed);
+        let request_json = serde_json::Value::Array(
+            requests
+                .into_iter()
+                .zip(first_request_id..)
+                .map(|((request, params), request_id)| {
+                    request.build_request_json(request_id, params)
+                })
+                .collect(),
+        );
+        let num_requests = request_json.as_array().unwrap().len();
+
+        let json = self.post(request_json.to_string()).await?;
+        let responses = match json.as_array() {
+            Some(responses) => responses,
+            // A batch the server could not process at all is answered with a single error
+            None => {
+                parse_response(&json)?;
+                return Err(RpcError::RpcRequestError(format!(
+                    "Expected an array in response to a batch request: {}",
+                    json
+                ))
+                .into());
+            }
+        };
+
+        // Responses to a batch may arrive in any order
+        let mut results: Vec<Option<Result<serde_json::Value>>> =
+            (0..num_requests).map(|_| None).collect();
+        for response in responses {
+            let index = response["id"]
+                .as_u64()
+                .and_then(|request_id| request_id.checked_sub(first_request_id))
+                .map(|index| index as usize)
+                .filter(|index| *index < num_requests);
+            match index {
+                Some(index) => results[index] = Some(parse_response(response)),
+                None => debug!("Ignoring batch response with unexpected id: {}", response),
             }
         }
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| {
+                    Err(
+                        RpcError::RpcRequestError("No response to request in batch".to_string())
+                            .into(),
+                    )
+                })
+            })
+            .collect())
     }
 }
 
@@ -180,14 +247,16 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn http_sender_on_tokio_multi_thread() {
         let http_sender = HttpSender::new("http://localhost:1234".to_string());
-        let _ = http_sender.send(RpcRequest::GetVersion, serde_json::Value::Null);
+        let _ = http_sender
+            .send(RpcRequest::GetVersion, serde_json::Value::Null)
+            .await;
     }
 
     #[tokio::test(flavor = "current_thread")]
-    #[should_panic(expected = "can call blocking only when running on the multi-threaded runtime")]
-    async fn http_sender_ontokio_current_thread_should_panic() {
-        // RpcClient::new() will panic in the tokio current-thread runtime due to `tokio::task::block_in_place()` usage, and there
-        // doesn't seem to be a way to detect whether the tokio runtime is multi_thread or current_thread...
-        let _ = HttpSender::new("http://localhost:1234".to_string());
+    async fn http_sender_on_tokio_current_thread() {
+        let http_sender = HttpSender::new("http://localhost:1234".to_string());
+        let _ = http_sender
+            .send(RpcRequest::GetVersion, serde_json::Value::Null)
+            .await;
     }
 }
@@ -9,6 +9,7 @@ use {
         },
         rpc_sender::RpcSender,
     },
+    async_trait::async_trait,
     serde_json::{json, Number, Value},
     solana_sdk::{
         epoch_info::EpochInfo,
@@ -57,6 +58,9 @@ pub struct MockSender {
 ///    [`MockSender::new_with_mocks`] constructor. This type is a [`HashMap`]
 ///    from [`RpcRequest`] to a JSON [`Value`] response, Any entries in this map
 ///    override the default behavior for the given request.
+///
+/// Batches of requests are answered one request at a time, in the same way as
+/// individual requests.
 impl MockSender {
     pub fn new(url: String) -> Self {
         Self::new_with_mocks(url, Mocks::default())
@@ -70,8 +74,13 @@ impl MockSender {
     }
 }
 
+#[async_trait]
 impl RpcSender for MockSender {
-    fn send(&self, request: RpcRequest, params: serde_json::Value) -> Result<serde_json::Value> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
         if let Some(value) = self.mocks.write().unwrap().remove(&request) {
             return Ok(value);
         }
@@ -173,6 +182,11 @@ impl RpcSender for MockSender {
                     logs: None,
                     accounts: None,
                     units_consumed: None,
+                    units_consumed_per_instruction: None,
+                    account_diffs: None,
+                    inner_instructions: None,
+                    pre_token_balances: None,
+                    post_token_balances: None,
                 },
             })?,
             RpcRequest::GetMinimumBalanceForRentExemption => Value::Number(Number::from(20)),
@@ -0,0 +1,2 @@
+pub mod pubsub_client;
+pub mod rpc_client;
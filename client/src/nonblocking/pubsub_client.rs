@@ -0,0 +1,579 @@
+//! A client for subscribing to messages from the RPC server.
+//!
+//! The [`PubsubClient`] maintains a single websocket connection to the
+//! server's [PubSub endpoint][ps], over which any number of subscriptions may
+//! be multiplexed. Each subscription is delivered as a [`Stream`] of
+//! notifications, along with a function that unsubscribes from the server.
+//!
+//! [ps]: https://docs.solana.com/developing/clients/jsonrpc-api#subscription-websocket
+//! [`Stream`]: futures::stream::Stream
+//!
+//! The blocking client in [`crate::pubsub_client`] is a thin wrapper around
+//! this one.
+
+use {
+    crate::{
+        rpc_config::{
+            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
+            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
+            RpcTransactionLogsFilter,
+        },
+        rpc_response::{
+            Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
+            RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+        },
+    },
+    futures::{
+        future::{ready, BoxFuture, FutureExt},
+        sink::SinkExt,
+        stream::{BoxStream, StreamExt},
+    },
+    log::*,
+    serde::de::DeserializeOwned,
+    serde_json::{json, Map, Value},
+    solana_account_decoder::UiAccount,
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    std::collections::BTreeMap,
+    thiserror::Error,
+    tokio::{
+        net::TcpStream,
+        sync::{mpsc, oneshot},
+        task::JoinHandle,
+        time::{interval_at, Duration, Instant},
+    },
+    tokio_stream::wrappers::UnboundedReceiverStream,
+    tokio_tungstenite::{
+        connect_async,
+        tungstenite::{
+            protocol::frame::{coding::CloseCode, CloseFrame},
+            Message,
+        },
+        MaybeTlsStream, WebSocketStream,
+    },
+    url::{ParseError, Url},
+};
+
+/// Interval at which the connection is pinged to keep it alive. A ping that
+/// goes unanswered for this long closes the connection.
+pub(crate) const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+pub type PubsubClientResult<T = ()> = Result<T, PubsubClientError>;
+
+#[derive(Debug, Error)]
+pub enum PubsubClientError {
+    #[error("url parse error")]
+    UrlParseError(#[from] ParseError),
+
+    #[error("unable to connect to server")]
+    ConnectionError(#[from] tokio_tungstenite::tungstenite::Error),
+
+    #[error("websocket error")]
+    WsError(#[from] Box<tokio_tungstenite::tungstenite::Error>),
+
+    #[error("connection closed ({0})")]
+    ConnectionClosed(String),
+
+    #[error("json parse error")]
+    JsonParseError(#[from] serde_json::error::Error),
+
+    #[error("subscribe failed: {reason}")]
+    SubscribeFailed { reason: String, message: String },
+
+    #[error("unexpected message format: {0}")]
+    UnexpectedMessageError(String),
+}
+
+/// Unsubscribes from the server, completing once the server has acknowledged
+/// the request or the connection has closed.
+pub type UnsubscribeFn = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// A stream of subscription notifications, and the function that ends it.
+pub type SubscribeResult<'a, T> = PubsubClientResult<(BoxStream<'a, T>, UnsubscribeFn)>;
+
+type SubscribeResponseMsg =
+    Result<(mpsc::UnboundedReceiver<Value>, UnsubscribeFn), PubsubClientError>;
+type SubscribeRequestMsg = (String, Value, oneshot::Sender<SubscribeResponseMsg>);
+type UnsubscribeRequestMsg = (String, u64, oneshot::Sender<()>);
+
+#[derive(Debug, Deserialize)]
+struct RpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+/// An asynchronous client of the RPC server's PubSub endpoint.
+///
+/// Subscriptions are issued through a single websocket, which is serviced by a
+/// task spawned onto the current Tokio runtime when the client is created.
+/// Dropping every stream and the client closes the connection; [`shutdown`]
+/// does so gracefully and reports any connection error.
+///
+/// [`shutdown`]: PubsubClient::shutdown
+pub struct PubsubClient {
+    subscribe_sender: mpsc::UnboundedSender<SubscribeRequestMsg>,
+    shutdown_sender: oneshot::Sender<()>,
+    ws: JoinHandle<PubsubClientResult>,
+}
+
+impl PubsubClient {
+    /// Connect to the PubSub endpoint at `url`, e.g. "ws://localhost:8900".
+    pub async fn new(url: &str) -> PubsubClientResult<Self> {
+        Self::new_with_ping_interval(url, PING_INTERVAL).await
+    }
+
+    /// Connect to the PubSub endpoint at `url`, pinging the server every `ping_interval`. A
+    /// ping that goes unanswered for `ping_interval` closes the connection, so that a
+    /// half-open connection is not mistaken for a quiet one.
+    pub async fn new_with_ping_interval(
+        url: &str,
+        ping_interval: Duration,
+    ) -> PubsubClientResult<Self> {
+        let url = Url::parse(url)?;
+        let (ws, _response) = connect_async(url)
+            .await
+            .map_err(PubsubClientError::ConnectionError)?;
+
+        let (subscribe_sender, subscribe_receiver) = mpsc::unbounded_channel();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+
+        Ok(Self {
+            subscribe_sender,
+            shutdown_sender,
+            ws: tokio::spawn(Self::run_ws(
+                ws,
+                subscribe_receiver,
+                shutdown_receiver,
+                ping_interval,
+            )),
+        })
+    }
+
+    /// Close the websocket, ending all subscriptions.
+    pub async fn shutdown(self) -> PubsubClientResult {
+        let _ = self.shutdown_sender.send(());
+        self.ws
+            .await
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))?
+    }
+
+    /// Issue a `<operation>Subscribe` request with `params` and return the
+    /// resulting notifications, deserialized as `T`.
+    pub(crate) async fn subscribe<'a, T>(
+        &self,
+        operation: &str,
+        params: Value,
+    ) -> SubscribeResult<'a, T>
+    where
+        T: DeserializeOwned + Send + 'a,
+    {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.subscribe_sender
+            .send((operation.to_string(), params, response_sender))
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))?;
+
+        let (notifications, unsubscribe) = response_receiver
+            .await
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))??;
+        Ok((
+            UnboundedReceiverStream::new(notifications)
+                .filter_map(|value| {
+                    ready(
+                        serde_json::from_value::<T>(value)
+                            .map_err(|err| warn!("unable to parse notification: {}", err))
+                            .ok(),
+                    )
+                })
+                .boxed(),
+            unsubscribe,
+        ))
+    }
+
+    pub async fn account_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> SubscribeResult<'_, RpcResponse<UiAccount>> {
+        let params = json!([pubkey.to_string(), config]);
+        self.subscribe("account", params).await
+    }
+
+    pub async fn program_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcProgramAccountsConfig>,
+    ) -> SubscribeResult<'_, RpcResponse<RpcKeyedAccount>> {
+        let params = json!([pubkey.to_string(), config]);
+        self.subscribe("program", params).await
+    }
+
+    pub async fn logs_subscribe(
+        &self,
+        filter: RpcTransactionLogsFilter,
+        config: RpcTransactionLogsConfig,
+    ) -> SubscribeResult<'_, RpcResponse<RpcLogsResponse>> {
+        self.subscribe("logs", json!([filter, config])).await
+    }
+
+    pub async fn signature_subscribe(
+        &self,
+        signature: &Signature,
+        config: Option<RpcSignatureSubscribeConfig>,
+    ) -> SubscribeResult<'_, RpcResponse<RpcSignatureResult>> {
+        let params = json!([signature.to_string(), config]);
+        self.subscribe("signature", params).await
+    }
+
+    pub async fn slot_subscribe(&self) -> SubscribeResult<'_, SlotInfo> {
+        self.subscribe("slot", json!([])).await
+    }
+
+    pub async fn slot_updates_subscribe(&self) -> SubscribeResult<'_, SlotUpdate> {
+        self.subscribe("slotsUpdates", json!([])).await
+    }
+
+    pub async fn root_subscribe(&self) -> SubscribeResult<'_, Slot> {
+        self.subscribe("root", json!([])).await
+    }
+
+    /// Subscribe to votes observed in gossip. The server must have been started with
+    /// `--rpc-pubsub-enable-vote-subscription`.
+    pub async fn vote_subscribe(&self) -> SubscribeResult<'_, RpcVote> {
+        self.subscribe("vote", json!([])).await
+    }
+
+    /// Subscribe to confirmed or finalized blocks. The server must have been started with
+    /// `--rpc-pubsub-enable-block-subscription`.
+    pub async fn block_subscribe(
+        &self,
+        filter: RpcBlockSubscribeFilter,
+        config: Option<RpcBlockSubscribeConfig>,
+    ) -> SubscribeResult<'_, RpcResponse<RpcBlockUpdate>> {
+        self.subscribe("block", json!([filter, config])).await
+    }
+
+    async fn run_ws(
+        mut ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        mut subscribe_receiver: mpsc::UnboundedReceiver<SubscribeRequestMsg>,
+        mut shutdown_receiver: oneshot::Receiver<()>,
+        ping_interval: Duration,
+    ) -> PubsubClientResult {
+        let mut request_id: u64 = 0;
+
+        let mut requests_subscribe = BTreeMap::new();
+        let mut requests_unsubscribe = BTreeMap::<u64, oneshot::Sender<()>>::new();
+        let mut subscriptions = BTreeMap::<u64, mpsc::UnboundedSender<Value>>::new();
+        let (unsubscribe_sender, mut unsubscribe_receiver) =
+            mpsc::unbounded_channel::<UnsubscribeRequestMsg>();
+        let mut ping_ticks = interval_at(Instant::now() + ping_interval, ping_interval);
+        let mut ping_sent: Option<Instant> = None;
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown_receiver => {
+                    let frame = CloseFrame { code: CloseCode::Normal, reason: "".into() };
+                    ws.send(Message::Close(Some(frame))).await.map_err(Box::new)?;
+                    ws.flush().await.map_err(Box::new)?;
+                    break;
+                },
+                _ = ping_ticks.tick() => match ping_sent {
+                    Some(sent) if sent.elapsed() >= ping_interval => {
+                        return Err(PubsubClientError::ConnectionClosed(format!(
+                            "no pong received within {:?}",
+                            ping_interval
+                        )));
+                    }
+                    // A tick delivered late after a stall; the ping is still outstanding
+                    Some(_) => (),
+                    None => {
+                        ws.send(Message::Ping(Vec::new())).await.map_err(Box::new)?;
+                        ping_sent = Some(Instant::now());
+                    }
+                },
+                Some((operation, params, response_sender)) = subscribe_receiver.recv() => {
+                    request_id += 1;
+                    let method = format!("{}Subscribe", operation);
+                    let text = json!({
+                        "jsonrpc": "2.0", "id": request_id, "method": method, "params": params
+                    })
+                    .to_string();
+                    ws.send(Message::Text(text)).await.map_err(Box::new)?;
+                    requests_subscribe.insert(request_id, (operation, response_sender));
+                },
+                Some((operation, subscription_id, response_sender)) =
+                    unsubscribe_receiver.recv() =>
+                {
+                    subscriptions.remove(&subscription_id);
+                    request_id += 1;
+                    let method = format!("{}Unsubscribe", operation);
+                    let text = json!({
+                        "jsonrpc": "2.0", "id": request_id, "method": method,
+                        "params": [subscription_id]
+                    })
+                    .to_string();
+                    ws.send(Message::Text(text)).await.map_err(Box::new)?;
+                    requests_unsubscribe.insert(request_id, response_sender);
+                },
+                next_message = ws.next() => {
+                    let message = match next_message {
+                        Some(message) => message.map_err(Box::new)?,
+                        None => break,
+                    };
+                    trace!("ws.next(): {:?}", &message);
+
+                    let text = match message {
+                        Message::Text(text) => text,
+                        Message::Binary(_data) => continue, // Ignore
+                        Message::Ping(data) => {
+                            ws.send(Message::Pong(data)).await.map_err(Box::new)?;
+                            continue
+                        },
+                        Message::Pong(_data) => {
+                            ping_sent = None;
+                            continue
+                        },
+                        Message::Close(_frame) => break,
+                    };
+                    let mut json: Map<String, Value> = serde_json::from_str(&text)?;
+
+                    // Subscribe/unsubscribe response, e.g.
+                    // `{"jsonrpc":"2.0","result":5308752,"id":1}`
+                    if let Some(id) = json.get("id") {
+                        let id = id.as_u64().ok_or_else(|| {
+                            PubsubClientError::UnexpectedMessageError(format!(
+                                "invalid `id` in {}",
+                                text
+                            ))
+                        })?;
+
+                        let error = json.get("error").map(|error| {
+                            match serde_json::from_value::<RpcErrorObject>(error.clone()) {
+                                Ok(RpcErrorObject { code, message }) => {
+                                    format!("{} ({})", message, code)
+                                }
+                                Err(err) => {
+                                    format!("failed to parse error object ({}): {}", err, error)
+                                }
+                            }
+                        });
+
+                        if let Some(response_sender) = requests_unsubscribe.remove(&id) {
+                            let _ = response_sender.send(()); // The caller may have gone away
+                        } else if let Some((operation, response_sender)) =
+                            requests_subscribe.remove(&id)
+                        {
+                            let subscription_id = match error {
+                                Some(reason) => {
+                                    let error = PubsubClientError::SubscribeFailed {
+                                        reason,
+                                        message: text,
+                                    };
+                                    let _ = response_sender.send(Err(error));
+                                    continue;
+                                }
+                                None => json
+                                    .get("result")
+                                    .and_then(Value::as_u64)
+                                    .ok_or_else(|| {
+                                        PubsubClientError::UnexpectedMessageError(format!(
+                                            "invalid `result` in {}",
+                                            text
+                                        ))
+                                    })?,
+                            };
+
+                            let (notifications_sender, notifications_receiver) =
+                                mpsc::unbounded_channel();
+                            let unsubscribe_sender = unsubscribe_sender.clone();
+                            let unsubscribe = Box::new(move || {
+                                async move {
+                                    let (response_sender, response_receiver) =
+                                        oneshot::channel();
+                                    // Nothing to do if the websocket has already closed
+                                    if unsubscribe_sender
+                                        .send((operation, subscription_id, response_sender))
+                                        .is_ok()
+                                    {
+                                        let _ = response_receiver.await;
+                                    }
+                                }
+                                .boxed()
+                            });
+
+                            let response = Ok((notifications_receiver, unsubscribe));
+                            if response_sender.send(response).is_ok() {
+                                subscriptions.insert(subscription_id, notifications_sender);
+                            }
+                        } else {
+                            error!("unknown request id: {}", id);
+                        }
+                        continue;
+                    }
+
+                    // Notification, e.g.
+                    // `{"jsonrpc":"2.0","method":"slotNotification",
+                    //   "params":{"result":{...},"subscription":3}}`
+                    if let Some(Value::Object(params)) = json.get_mut("params") {
+                        let subscription_id = params.get("subscription").and_then(Value::as_u64);
+                        if let Some(subscription_id) = subscription_id {
+                            let notifications_sender = subscriptions.get(&subscription_id);
+                            let delivered = match (notifications_sender, params.remove("result")) {
+                                (Some(notifications_sender), Some(result)) => {
+                                    notifications_sender.send(result).is_ok()
+                                }
+                                (Some(_), None) => true,
+                                (None, _) => false,
+                            };
+
+                            // The subscriber went away without unsubscribing, so do it for them
+                            if !delivered {
+                                if let Some(operation) = json
+                                    .get("method")
+                                    .and_then(Value::as_str)
+                                    .and_then(|method| method.strip_suffix("Notification"))
+                                {
+                                    let (response_sender, _) = oneshot::channel();
+                                    let _ = unsubscribe_sender.send((
+                                        operation.to_string(),
+                                        subscription_id,
+                                        response_sender,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tokio::net::TcpListener, tokio_tungstenite::accept_async};
+
+    /// Serve a single websocket connection that acknowledges subscribe requests with a
+    /// subscription id of ten times the request id, and unsubscribe requests with `true`.
+    /// Returns the server's url, the requests it received, and a sender for notifications.
+    async fn start_mock_server() -> (
+        String,
+        mpsc::UnboundedReceiver<Value>,
+        mpsc::UnboundedSender<Value>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (requests_sender, requests_receiver) = mpsc::unbounded_channel();
+        let (notifications_sender, mut notifications_receiver) = mpsc::unbounded_channel::<Value>();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            loop {
+                tokio::select! {
+                    Some(notification) = notifications_receiver.recv() => {
+                        ws.send(Message::Text(notification.to_string())).await.unwrap();
+                    }
+                    message = ws.next() => {
+                        let text = match message {
+                            Some(Ok(Message::Text(text))) => text,
+                            Some(Ok(_)) => continue,
+                            _ => break,
+                        };
+                        let request: Value = serde_json::from_str(&text).unwrap();
+                        let id = request["id"].as_u64().unwrap();
+                        let result = if request["method"].as_str().unwrap().ends_with("Unsubscribe") {
+                            json!(true)
+                        } else {
+                            json!(id * 10)
+                        };
+                        let response = json!({"jsonrpc": "2.0", "result": result, "id": id});
+                        ws.send(Message::Text(response.to_string())).await.unwrap();
+                        let _ = requests_sender.send(request);
+                    }
+                }
+            }
+        });
+
+        (url, requests_receiver, notifications_sender)
+    }
+
+    fn notification(method: &str, subscription: u64, result: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": {"result": result, "subscription": subscription},
+        })
+    }
+
+    #[tokio::test]
+    async fn test_multiplexed_subscriptions() {
+        let (url, mut requests, notifications) = start_mock_server().await;
+        let client = PubsubClient::new(&url).await.unwrap();
+
+        let (mut slots, _slot_unsubscribe) = client.slot_subscribe().await.unwrap();
+        let (mut roots, _root_unsubscribe) = client.root_subscribe().await.unwrap();
+        assert_eq!(requests.recv().await.unwrap()["method"], "slotSubscribe");
+        assert_eq!(requests.recv().await.unwrap()["method"], "rootSubscribe");
+
+        // Interleaved notifications are routed by subscription id
+        let slot_info = json!({"slot": 8, "parent": 7, "root": 6});
+        notifications
+            .send(notification("rootNotification", 20, json!(7)))
+            .unwrap();
+        notifications
+            .send(notification("slotNotification", 10, slot_info))
+            .unwrap();
+        notifications
+            .send(notification("rootNotification", 20, json!(8)))
+            .unwrap();
+        assert_eq!(
+            slots.next().await,
+            Some(SlotInfo {
+                slot: 8,
+                parent: 7,
+                root: 6
+            })
+        );
+        assert_eq!(roots.next().await, Some(7));
+        assert_eq!(roots.next().await, Some(8));
+
+        drop((slots, roots));
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe() {
+        let (url, mut requests, notifications) = start_mock_server().await;
+        let client = PubsubClient::new(&url).await.unwrap();
+
+        let (mut slots, slot_unsubscribe) = client.slot_subscribe().await.unwrap();
+        let (mut roots, _root_unsubscribe) = client.root_subscribe().await.unwrap();
+        requests.recv().await.unwrap();
+        requests.recv().await.unwrap();
+
+        // Unsubscribing ends that subscription's stream and leaves the other open
+        slot_unsubscribe().await;
+        let request = requests.recv().await.unwrap();
+        assert_eq!(request["method"], "slotUnsubscribe");
+        assert_eq!(request["params"], json!([10]));
+        assert_eq!(slots.next().await, None);
+
+        notifications
+            .send(notification("rootNotification", 20, json!(9)))
+            .unwrap();
+        assert_eq!(roots.next().await, Some(9));
+
+        // A subscriber that goes away is unsubscribed on its next notification
+        drop(roots);
+        notifications
+            .send(notification("rootNotification", 20, json!(10)))
+            .unwrap();
+        let request = requests.recv().await.unwrap();
+        assert_eq!(request["method"], "rootUnsubscribe");
+        assert_eq!(request["params"], json!([20]));
+
+        drop(slots);
+        client.shutdown().await.unwrap();
+    }
+}
@@ -1,47 +1,165 @@
+//! A blocking client for subscribing to messages from the RPC server.
+//!
+//! Each subscription is serviced by the asynchronous
+//! [`crate::nonblocking::pubsub_client::PubsubClient`], driven as a task on a
+//! runtime shared by all blocking subscriptions, with notifications forwarded
+//! to a channel or handler. Handlers run on that runtime and must not block.
+//!
+//! Subscriptions made through [`ReconnectingPubsubClient`] survive the loss of
+//! the websocket, including one that silently stops answering pings: the
+//! connection is re-established with exponential backoff and the original
+//! subscribe request re-issued, while [`PubsubConnectionEvent`]s report each
+//! change of connection state and any gap in notifications.
+
+pub use crate::nonblocking::pubsub_client::PubsubClientError;
 use {
     crate::{
+        nonblocking,
         rpc_config::{
-            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
+            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
+            RpcTransactionLogsFilter,
         },
         rpc_response::{
-            Response as RpcResponse, RpcLogsResponse, RpcSignatureResult, SlotInfo, SlotUpdate,
+            Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
+            RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
         },
     },
+    futures::{executor::block_on, StreamExt},
+    lazy_static::lazy_static,
     log::*,
     serde::de::DeserializeOwned,
-    serde_json::{
-        json,
-        value::Value::{Number, Object},
-        Map, Value,
-    },
-    solana_sdk::signature::Signature,
+    serde_json::{json, Value},
+    solana_account_decoder::UiAccount,
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
     std::{
+        cmp::min,
         marker::PhantomData,
         sync::{
-            atomic::{AtomicBool, Ordering},
-            mpsc::{channel, Receiver},
-            Arc, RwLock,
+            mpsc::{channel, Receiver, Sender},
+            Mutex,
         },
-        thread::JoinHandle,
+        time::Duration,
+    },
+    tokio::{
+        runtime::{Builder, Runtime},
+        sync::watch,
+        task::JoinHandle,
+        time::sleep,
     },
-    thiserror::Error,
-    tungstenite::{client::AutoStream, connect, Message, WebSocket},
-    url::{ParseError, Url},
 };
 
-#[derive(Debug, Error)]
-pub enum PubsubClientError {
-    #[error("url parse error")]
-    UrlParseError(#[from] ParseError),
+lazy_static! {
+    static ref PUBSUB_RUNTIME: Runtime = Builder::new_multi_thread()
+        .thread_name("pubsubclient")
+        .enable_all()
+        .build()
+        .unwrap();
+}
+
+type Connection<T> = (
+    nonblocking::pubsub_client::PubsubClient,
+    futures::stream::BoxStream<'static, T>,
+    nonblocking::pubsub_client::UnsubscribeFn,
+);
+
+/// How a [`ReconnectingPubsubClient`] subscription re-establishes a dropped connection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PubsubReconnectConfig {
+    /// Delay before the first reconnect attempt
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay, which doubles after each failed attempt
+    pub max_backoff: Duration,
+    /// Consecutive failed attempts after which the subscription ends, or `None` to retry forever
+    pub max_attempts: Option<usize>,
+    /// Interval at which the connection is pinged. A ping unanswered for this long is treated
+    /// as a dropped connection, which catches half-open connections that never report an error.
+    pub ping_interval: Duration,
+}
+
+impl Default for PubsubReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_attempts: None,
+            ping_interval: nonblocking::pubsub_client::PING_INTERVAL,
+        }
+    }
+}
+
+impl PubsubReconnectConfig {
+    fn next_backoff(&self, backoff: Duration) -> Duration {
+        min(backoff * 2, self.max_backoff)
+    }
+}
+
+/// Changes in the connection state of a reconnecting subscription.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PubsubConnectionEvent {
+    /// The websocket closed. `last_seen_slot` is the slot of the most recent notification
+    /// received, if any.
+    Disconnected {
+        reason: String,
+        last_seen_slot: Option<Slot>,
+    },
+    /// Attempt number `attempt` to reconnect will be made after `backoff`
+    Reconnecting { attempt: usize, backoff: Duration },
+    /// The subscription was re-established. Notifications for slots after `last_seen_slot`
+    /// that were sent while disconnected have been missed.
+    Resubscribed { last_seen_slot: Option<Slot> },
+    /// No reconnect attempt succeeded within `PubsubReconnectConfig::max_attempts`, and the
+    /// subscription has ended
+    GaveUp { attempts: usize },
+}
+
+/// The slot a notification refers to, used to report gaps across reconnects.
+pub trait NotificationSlot {
+    fn notification_slot(&self) -> Option<Slot>;
+}
+
+impl<T> NotificationSlot for RpcResponse<T> {
+    fn notification_slot(&self) -> Option<Slot> {
+        Some(self.context.slot)
+    }
+}
+
+impl NotificationSlot for SlotInfo {
+    fn notification_slot(&self) -> Option<Slot> {
+        Some(self.slot)
+    }
+}
+
+impl NotificationSlot for SlotUpdate {
+    fn notification_slot(&self) -> Option<Slot> {
+        Some(self.slot())
+    }
+}
+
+impl NotificationSlot for Slot {
+    fn notification_slot(&self) -> Option<Slot> {
+        Some(*self)
+    }
+}
 
-    #[error("unable to connect to server")]
-    ConnectionError(#[from] tungstenite::Error),
+impl NotificationSlot for RpcVote {
+    fn notification_slot(&self) -> Option<Slot> {
+        self.slots.last().copied()
+    }
+}
 
-    #[error("json parse error")]
-    JsonParseError(#[from] serde_json::error::Error),
+struct Reconnect<T> {
+    config: PubsubReconnectConfig,
+    events: Mutex<Sender<PubsubConnectionEvent>>,
+    notification_slot: fn(&T) -> Option<Slot>,
+}
 
-    #[error("unexpected message format: {0}")]
-    UnexpectedMessageError(String),
+impl<T> Reconnect<T> {
+    fn send_event(&self, event: PubsubConnectionEvent) {
+        if let Err(err) = self.events.lock().unwrap().send(event) {
+            trace!("connection event dropped: {:?}", err.0);
+        }
+    }
 }
 
 pub struct PubsubClientSubscription<T>
@@ -50,10 +168,8 @@ where
 {
     message_type: PhantomData<T>,
     operation: &'static str,
-    socket: Arc<RwLock<WebSocket<AutoStream>>>,
-    subscription_id: u64,
+    exit: watch::Sender<bool>,
     t_cleanup: Option<JoinHandle<()>>,
-    exit: Arc<AtomicBool>,
 }
 
 impl<T> Drop for PubsubClientSubscription<T>
@@ -63,95 +179,216 @@ where
     fn drop(&mut self) {
         self.send_unsubscribe()
             .unwrap_or_else(|_| warn!("unable to unsubscribe from websocket"));
-        self.socket
-            .write()
-            .unwrap()
-            .close(None)
-            .unwrap_or_else(|_| warn!("unable to close websocket"));
     }
 }
 
 impl<T> PubsubClientSubscription<T>
 where
-    T: DeserializeOwned,
+    T: DeserializeOwned + Send + 'static,
 {
-    fn send_subscribe(
-        writable_socket: &Arc<RwLock<WebSocket<AutoStream>>>,
-        body: String,
-    ) -> Result<u64, PubsubClientError> {
-        writable_socket
-            .write()
-            .unwrap()
-            .write_message(Message::Text(body))?;
-        let message = writable_socket.write().unwrap().read_message()?;
-        Self::extract_subscription_id(message)
-    }
-
-    fn extract_subscription_id(message: Message) -> Result<u64, PubsubClientError> {
-        let message_text = &message.into_text()?;
-        let json_msg: Map<String, Value> = serde_json::from_str(message_text)?;
-
-        if let Some(Number(x)) = json_msg.get("result") {
-            if let Some(x) = x.as_u64() {
-                return Ok(x);
+    fn subscribe(
+        url: &str,
+        operation: &'static str,
+        params: Value,
+        reconnect: Option<Reconnect<T>>,
+        mut handler: impl FnMut(T) -> bool + Send + 'static,
+    ) -> Result<Self, PubsubClientError> {
+        let url = url.to_string();
+        let ping_interval = reconnect
+            .as_ref()
+            .map(|reconnect| reconnect.config.ping_interval)
+            .unwrap_or(nonblocking::pubsub_client::PING_INTERVAL);
+        let (exit, mut exit_receiver) = watch::channel(false);
+        let (ready_sender, ready_receiver) = channel();
+
+        let t_cleanup = PUBSUB_RUNTIME.spawn(async move {
+            let connection = Self::connect(&url, operation, &params, ping_interval).await;
+            let mut connection = match connection {
+                Ok(connection) => {
+                    let _ = ready_sender.send(Ok(()));
+                    connection
+                }
+                Err(err) => {
+                    let _ = ready_sender.send(Err(err));
+                    return;
+                }
+            };
+            let mut last_seen_slot = None;
+
+            loop {
+                let (client, mut notifications, unsubscribe) = connection;
+                let mut disconnected = false;
+                loop {
+                    tokio::select! {
+                        _ = exit_receiver.changed() => {
+                            unsubscribe().await;
+                            break;
+                        }
+                        message = notifications.next() => match message {
+                            Some(message) => {
+                                if let Some(reconnect) = &reconnect {
+                                    let slot = (reconnect.notification_slot)(&message);
+                                    last_seen_slot = slot.or(last_seen_slot);
+                                }
+                                if !handler(message) {
+                                    unsubscribe().await;
+                                    break;
+                                }
+                            }
+                            None => {
+                                disconnected = true;
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                drop(notifications);
+                let result = client.shutdown().await;
+                if !disconnected {
+                    if let Err(err) = result {
+                        info!("websocket shutdown error: {:?}", err);
+                    }
+                    break;
+                }
+
+                let reconnect = match &reconnect {
+                    Some(reconnect) => reconnect,
+                    None => {
+                        info!("receive error: websocket closed");
+                        break;
+                    }
+                };
+                let reason = match result {
+                    Ok(()) => "websocket closed".to_string(),
+                    Err(err) => err.to_string(),
+                };
+                info!("{} subscription disconnected: {}", operation, reason);
+                reconnect.send_event(PubsubConnectionEvent::Disconnected {
+                    reason,
+                    last_seen_slot,
+                });
+
+                connection =
+                    match Self::reconnect(&url, operation, &params, reconnect, &mut exit_receiver)
+                        .await
+                    {
+                        Some(connection) => connection,
+                        None => break,
+                    };
+                reconnect.send_event(PubsubConnectionEvent::Resubscribed { last_seen_slot });
             }
-        }
-        // TODO: Add proper JSON RPC response/error handling...
-        Err(PubsubClientError::UnexpectedMessageError(format!(
-            "{:?}",
-            json_msg
-        )))
-    }
+            info!("websocket - exited receive loop");
+        });
 
-    pub fn send_unsubscribe(&self) -> Result<(), PubsubClientError> {
-        let method = format!("{}Unsubscribe", self.operation);
-        self.socket
-            .write()
-            .unwrap()
-            .write_message(Message::Text(
-                json!({
-                "jsonrpc":"2.0","id":1,"method":method,"params":[self.subscription_id]
-                })
-                .to_string(),
-            ))
-            .map_err(|err| err.into())
+        ready_receiver
+            .recv()
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))??;
+
+        Ok(Self {
+            message_type: PhantomData,
+            operation,
+            exit,
+            t_cleanup: Some(t_cleanup),
+        })
     }
 
-    fn read_message(
-        writable_socket: &Arc<RwLock<WebSocket<AutoStream>>>,
-    ) -> Result<T, PubsubClientError> {
-        let message = writable_socket.write().unwrap().read_message()?;
-        let message_text = &message.into_text().unwrap();
-        let json_msg: Map<String, Value> = serde_json::from_str(message_text)?;
+    async fn connect(
+        url: &str,
+        operation: &'static str,
+        params: &Value,
+        ping_interval: Duration,
+    ) -> Result<Connection<T>, PubsubClientError> {
+        let client =
+            nonblocking::pubsub_client::PubsubClient::new_with_ping_interval(url, ping_interval)
+                .await?;
+        let (notifications, unsubscribe) = client.subscribe::<T>(operation, params.clone()).await?;
+        Ok((client, notifications, unsubscribe))
+    }
 
-        if let Some(Object(params)) = json_msg.get("params") {
-            if let Some(result) = params.get("result") {
-                let x: T = serde_json::from_value::<T>(result.clone()).unwrap();
-                return Ok(x);
+    /// Reconnect and resubscribe with exponential backoff, returning `None` once the attempts
+    /// are exhausted or the subscription is ended by the caller.
+    async fn reconnect(
+        url: &str,
+        operation: &'static str,
+        params: &Value,
+        reconnect: &Reconnect<T>,
+        exit_receiver: &mut watch::Receiver<bool>,
+    ) -> Option<Connection<T>> {
+        let mut backoff = reconnect.config.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            if reconnect.config.max_attempts == Some(attempt) {
+                warn!(
+                    "{} subscription: giving up after {} reconnect attempts",
+                    operation, attempt
+                );
+                reconnect.send_event(PubsubConnectionEvent::GaveUp { attempts: attempt });
+                return None;
+            }
+            attempt += 1;
+            reconnect.send_event(PubsubConnectionEvent::Reconnecting { attempt, backoff });
+
+            let result = tokio::select! {
+                _ = exit_receiver.changed() => return None,
+                result = async {
+                    sleep(backoff).await;
+                    Self::connect(url, operation, params, reconnect.config.ping_interval).await
+                } => result,
+            };
+            match result {
+                Ok(connection) => return Some(connection),
+                Err(err) => info!(
+                    "{} subscription: reconnect attempt {} failed: {}",
+                    operation, attempt, err
+                ),
             }
+            backoff = reconnect.config.next_backoff(backoff);
         }
+    }
+}
 
-        // TODO: Add proper JSON RPC response/error handling...
-        Err(PubsubClientError::UnexpectedMessageError(format!(
-            "{:?}",
-            json_msg
-        )))
+impl<T> PubsubClientSubscription<T>
+where
+    T: DeserializeOwned,
+{
+    pub fn send_unsubscribe(&self) -> Result<(), PubsubClientError> {
+        self.exit.send(true).map_err(|_| {
+            PubsubClientError::ConnectionClosed(format!(
+                "{} subscription already ended",
+                self.operation
+            ))
+        })
     }
 
     pub fn shutdown(&mut self) -> std::thread::Result<()> {
         if self.t_cleanup.is_some() {
-            info!("websocket thread - shutting down");
-            self.exit.store(true, Ordering::Relaxed);
-            let x = self.t_cleanup.take().unwrap().join();
-            info!("websocket thread - shut down.");
+            info!("websocket task - shutting down");
+            let _ = self.exit.send(true);
+            let x = block_on(self.t_cleanup.take().unwrap()).map_err(|err| {
+                if err.is_panic() {
+                    err.into_panic()
+                } else {
+                    Box::new(err.to_string()) as Box<dyn std::any::Any + Send>
+                }
+            });
+            info!("websocket task - shut down.");
             x
         } else {
-            warn!("websocket thread - already shut down.");
+            warn!("websocket task - already shut down.");
             Ok(())
         }
     }
 }
 
+pub type AccountSubscription = (
+    PubsubClientSubscription<RpcResponse<UiAccount>>,
+    Receiver<RpcResponse<UiAccount>>,
+);
+pub type ProgramSubscription = (
+    PubsubClientSubscription<RpcResponse<RpcKeyedAccount>>,
+    Receiver<RpcResponse<RpcKeyedAccount>>,
+);
 pub type LogsSubscription = (
     PubsubClientSubscription<RpcResponse<RpcLogsResponse>>,
     Receiver<RpcResponse<RpcLogsResponse>>,
@@ -161,238 +398,404 @@ pub type SignatureSubscription = (
     PubsubClientSubscription<RpcResponse<RpcSignatureResult>>,
     Receiver<RpcResponse<RpcSignatureResult>>,
 );
+pub type RootSubscription = (PubsubClientSubscription<Slot>, Receiver<Slot>);
+pub type VoteSubscription = (PubsubClientSubscription<RpcVote>, Receiver<RpcVote>);
+pub type BlockSubscription = (
+    PubsubClientSubscription<RpcResponse<RpcBlockUpdate>>,
+    Receiver<RpcResponse<RpcBlockUpdate>>,
+);
 
 pub struct PubsubClient {}
 
 impl PubsubClient {
+    fn subscribe_with_receiver<T>(
+        url: &str,
+        operation: &'static str,
+        params: Value,
+    ) -> Result<(PubsubClientSubscription<T>, Receiver<T>), PubsubClientError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        subscribe_with_receiver(url, operation, params, None)
+    }
+
+    pub fn account_subscribe(
+        url: &str,
+        pubkey: &Pubkey,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<AccountSubscription, PubsubClientError> {
+        let params = json!([pubkey.to_string(), config]);
+        Self::subscribe_with_receiver(url, "account", params)
+    }
+
+    pub fn program_subscribe(
+        url: &str,
+        pubkey: &Pubkey,
+        config: Option<RpcProgramAccountsConfig>,
+    ) -> Result<ProgramSubscription, PubsubClientError> {
+        let params = json!([pubkey.to_string(), config]);
+        Self::subscribe_with_receiver(url, "program", params)
+    }
+
     pub fn logs_subscribe(
         url: &str,
         filter: RpcTransactionLogsFilter,
         config: RpcTransactionLogsConfig,
     ) -> Result<LogsSubscription, PubsubClientError> {
-        let url = Url::parse(url)?;
-        let (socket, _response) = connect(url)?;
-        let (sender, receiver) = channel();
-
-        let socket = Arc::new(RwLock::new(socket));
-        let socket_clone = socket.clone();
-        let exit = Arc::new(AtomicBool::new(false));
-        let exit_clone = exit.clone();
-
-        let subscription_id =
-            PubsubClientSubscription::<RpcResponse<RpcLogsResponse>>::send_subscribe(
-                &socket_clone,
-                json!({
-                    "jsonrpc":"2.0","id":1,"method":"logsSubscribe","params":[filter, config]
-                })
-                .to_string(),
-            )?;
-
-        let t_cleanup = std::thread::spawn(move || {
-            loop {
-                if exit_clone.load(Ordering::Relaxed) {
-                    break;
-                }
+        Self::subscribe_with_receiver(url, "logs", json!([filter, config]))
+    }
 
-                match PubsubClientSubscription::read_message(&socket_clone) {
-                    Ok(message) => match sender.send(message) {
-                        Ok(_) => (),
-                        Err(err) => {
-                            info!("receive error: {:?}", err);
-                            break;
-                        }
-                    },
-                    Err(err) => {
-                        info!("receive error: {:?}", err);
-                        break;
-                    }
-                }
-            }
+    pub fn slot_subscribe(url: &str) -> Result<SlotsSubscription, PubsubClientError> {
+        Self::subscribe_with_receiver(url, "slot", json!([]))
+    }
 
-            info!("websocket - exited receive loop");
-        });
+    pub fn signature_subscribe(
+        url: &str,
+        signature: &Signature,
+        config: Option<RpcSignatureSubscribeConfig>,
+    ) -> Result<SignatureSubscription, PubsubClientError> {
+        let params = json!([signature.to_string(), config]);
+        Self::subscribe_with_receiver(url, "signature", params)
+    }
 
-        let result = PubsubClientSubscription {
-            message_type: PhantomData,
-            operation: "logs",
-            socket,
-            subscription_id,
-            t_cleanup: Some(t_cleanup),
-            exit,
-        };
+    pub fn root_subscribe(url: &str) -> Result<RootSubscription, PubsubClientError> {
+        Self::subscribe_with_receiver(url, "root", json!([]))
+    }
 
-        Ok((result, receiver))
+    /// Subscribe to votes observed in gossip. The server must have been started with
+    /// `--rpc-pubsub-enable-vote-subscription`.
+    pub fn vote_subscribe(url: &str) -> Result<VoteSubscription, PubsubClientError> {
+        Self::subscribe_with_receiver(url, "vote", json!([]))
     }
 
-    pub fn slot_subscribe(url: &str) -> Result<SlotsSubscription, PubsubClientError> {
-        let url = Url::parse(url)?;
-        let (socket, _response) = connect(url)?;
-        let (sender, receiver) = channel::<SlotInfo>();
-
-        let socket = Arc::new(RwLock::new(socket));
-        let socket_clone = socket.clone();
-        let exit = Arc::new(AtomicBool::new(false));
-        let exit_clone = exit.clone();
-        let subscription_id = PubsubClientSubscription::<SlotInfo>::send_subscribe(
-            &socket_clone,
-            json!({
-                "jsonrpc":"2.0","id":1,"method":"slotSubscribe","params":[]
-            })
-            .to_string(),
-        )?;
+    /// Subscribe to confirmed or finalized blocks. The server must have been started with
+    /// `--rpc-pubsub-enable-block-subscription`.
+    pub fn block_subscribe(
+        url: &str,
+        filter: RpcBlockSubscribeFilter,
+        config: Option<RpcBlockSubscribeConfig>,
+    ) -> Result<BlockSubscription, PubsubClientError> {
+        Self::subscribe_with_receiver(url, "block", json!([filter, config]))
+    }
 
-        let t_cleanup = std::thread::spawn(move || {
-            loop {
-                if exit_clone.load(Ordering::Relaxed) {
-                    break;
-                }
-                match PubsubClientSubscription::read_message(&socket_clone) {
-                    Ok(message) => match sender.send(message) {
-                        Ok(_) => (),
-                        Err(err) => {
-                            info!("receive error: {:?}", err);
-                            break;
-                        }
-                    },
-                    Err(err) => {
-                        info!("receive error: {:?}", err);
-                        break;
-                    }
+    pub fn slot_updates_subscribe(
+        url: &str,
+        handler: impl Fn(SlotUpdate) + Send + 'static,
+    ) -> Result<PubsubClientSubscription<SlotUpdate>, PubsubClientError> {
+        PubsubClientSubscription::subscribe(url, "slotsUpdates", json!([]), None, move |message| {
+            handler(message);
+            true
+        })
+    }
+}
+
+fn subscribe_with_receiver<T>(
+    url: &str,
+    operation: &'static str,
+    params: Value,
+    reconnect: Option<Reconnect<T>>,
+) -> Result<(PubsubClientSubscription<T>, Receiver<T>), PubsubClientError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let (sender, receiver) = channel();
+    let subscription =
+        PubsubClientSubscription::subscribe(url, operation, params, reconnect, move |message| {
+            match sender.send(message) {
+                Ok(_) => true,
+                Err(err) => {
+                    info!("receive error: {:?}", err);
+                    false
                 }
             }
+        })?;
+    Ok((subscription, receiver))
+}
 
-            info!("websocket - exited receive loop");
-        });
+/// A subscription that reconnects when its websocket drops, together with its notifications
+/// and connection state events.
+pub type ReconnectingSubscription<T> = (
+    PubsubClientSubscription<T>,
+    Receiver<T>,
+    Receiver<PubsubConnectionEvent>,
+);
 
-        let result = PubsubClientSubscription {
-            message_type: PhantomData,
-            operation: "slot",
-            socket,
-            subscription_id,
-            t_cleanup: Some(t_cleanup),
-            exit,
+/// Issues subscriptions that transparently reconnect and resubscribe when their websocket
+/// drops, per its [`PubsubReconnectConfig`].
+///
+/// The initial connection must succeed for a subscription to be returned. Afterwards, the
+/// notification receiver stays open across reconnects and only ends when the subscription is
+/// shut down or reconnecting gives up. Signature subscriptions, which end after their first
+/// notification, are not offered.
+pub struct ReconnectingPubsubClient {
+    url: String,
+    config: PubsubReconnectConfig,
+}
+
+impl ReconnectingPubsubClient {
+    pub fn new(url: &str, config: PubsubReconnectConfig) -> Self {
+        Self {
+            url: url.to_string(),
+            config,
+        }
+    }
+
+    fn subscribe<T>(
+        &self,
+        operation: &'static str,
+        params: Value,
+    ) -> Result<ReconnectingSubscription<T>, PubsubClientError>
+    where
+        T: DeserializeOwned + NotificationSlot + Send + 'static,
+    {
+        let (events, events_receiver) = channel();
+        let reconnect = Reconnect {
+            config: self.config.clone(),
+            events: Mutex::new(events),
+            notification_slot: T::notification_slot,
         };
+        let (subscription, receiver) =
+            subscribe_with_receiver(&self.url, operation, params, Some(reconnect))?;
+        Ok((subscription, receiver, events_receiver))
+    }
 
-        Ok((result, receiver))
+    pub fn account_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<ReconnectingSubscription<RpcResponse<UiAccount>>, PubsubClientError> {
+        self.subscribe("account", json!([pubkey.to_string(), config]))
     }
 
-    pub fn signature_subscribe(
-        url: &str,
-        signature: &Signature,
-        config: Option<RpcSignatureSubscribeConfig>,
-    ) -> Result<SignatureSubscription, PubsubClientError> {
-        let url = Url::parse(url)?;
-        let (socket, _response) = connect(url)?;
-        let (sender, receiver) = channel();
-
-        let socket = Arc::new(RwLock::new(socket));
-        let socket_clone = socket.clone();
-        let exit = Arc::new(AtomicBool::new(false));
-        let exit_clone = exit.clone();
-        let body = json!({
-            "jsonrpc":"2.0",
-            "id":1,
-            "method":"signatureSubscribe",
-            "params":[
-                signature.to_string(),
-                config
-            ]
-        })
-        .to_string();
-        let subscription_id =
-            PubsubClientSubscription::<RpcResponse<RpcSignatureResult>>::send_subscribe(
-                &socket_clone,
-                body,
-            )?;
-
-        let t_cleanup = std::thread::spawn(move || {
-            loop {
-                if exit_clone.load(Ordering::Relaxed) {
-                    break;
-                }
+    pub fn program_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcProgramAccountsConfig>,
+    ) -> Result<ReconnectingSubscription<RpcResponse<RpcKeyedAccount>>, PubsubClientError> {
+        self.subscribe("program", json!([pubkey.to_string(), config]))
+    }
 
-                let message: Result<RpcResponse<RpcSignatureResult>, PubsubClientError> =
-                    PubsubClientSubscription::read_message(&socket_clone);
+    pub fn logs_subscribe(
+        &self,
+        filter: RpcTransactionLogsFilter,
+        config: RpcTransactionLogsConfig,
+    ) -> Result<ReconnectingSubscription<RpcResponse<RpcLogsResponse>>, PubsubClientError> {
+        self.subscribe("logs", json!([filter, config]))
+    }
 
-                if let Ok(msg) = message {
-                    match sender.send(msg.clone()) {
-                        Ok(_) => (),
-                        Err(err) => {
-                            info!("receive error: {:?}", err);
-                            break;
-                        }
-                    }
-                } else {
-                    info!("receive error: {:?}", message);
-                    break;
-                }
-            }
+    pub fn slot_subscribe(&self) -> Result<ReconnectingSubscription<SlotInfo>, PubsubClientError> {
+        self.subscribe("slot", json!([]))
+    }
 
-            info!("websocket - exited receive loop");
-        });
+    pub fn slot_updates_subscribe(
+        &self,
+    ) -> Result<ReconnectingSubscription<SlotUpdate>, PubsubClientError> {
+        self.subscribe("slotsUpdates", json!([]))
+    }
 
-        let result = PubsubClientSubscription {
-            message_type: PhantomData,
-            operation: "signature",
-            socket,
-            subscription_id,
-            t_cleanup: Some(t_cleanup),
-            exit,
-        };
+    pub fn root_subscribe(&self) -> Result<ReconnectingSubscription<Slot>, PubsubClientError> {
+        self.subscribe("root", json!([]))
+    }
 
-        Ok((result, receiver))
+    pub fn vote_subscribe(&self) -> Result<ReconnectingSubscription<RpcVote>, PubsubClientError> {
+        self.subscribe("vote", json!([]))
     }
 
-    pub fn slot_updates_subscribe(
-        url: &str,
-        handler: impl Fn(SlotUpdate) + Send + 'static,
-    ) -> Result<PubsubClientSubscription<SlotUpdate>, PubsubClientError> {
-        let url = Url::parse(url)?;
-        let (socket, _response) = connect(url)?;
-
-        let socket = Arc::new(RwLock::new(socket));
-        let exit = Arc::new(AtomicBool::new(false));
-        let exit_clone = exit.clone();
-        let subscription_id = PubsubClientSubscription::<SlotUpdate>::send_subscribe(
-            &socket,
-            json!({
-                "jsonrpc":"2.0","id":1,"method":"slotsUpdatesSubscribe","params":[]
-            })
-            .to_string(),
-        )?;
+    pub fn block_subscribe(
+        &self,
+        filter: RpcBlockSubscribeFilter,
+        config: Option<RpcBlockSubscribeConfig>,
+    ) -> Result<ReconnectingSubscription<RpcResponse<RpcBlockUpdate>>, PubsubClientError> {
+        self.subscribe("block", json!([filter, config]))
+    }
+}
 
-        let t_cleanup = {
-            let socket = socket.clone();
-            std::thread::spawn(move || {
-                loop {
-                    if exit_clone.load(Ordering::Relaxed) {
-                        break;
+#[cfg(test)]
+mod tests {
+    // see core/tests/client.rs#test_slot_subscription()
+
+    use {
+        super::*,
+        crate::rpc_response::RpcResponseContext,
+        assert_matches::assert_matches,
+        futures::SinkExt,
+        tokio::net::TcpListener,
+        tokio_tungstenite::{accept_async, tungstenite::Message},
+    };
+
+    fn root_notification(subscription: u64, root: Slot) -> Message {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "rootNotification",
+            "params": {"result": root, "subscription": subscription},
+        });
+        Message::Text(notification.to_string())
+    }
+
+    /// Serve three connections in turn, each of which acknowledges one subscribe request and
+    /// sends root notifications. The first connection is then dropped without a closing
+    /// handshake, the second stops reading so that pings go unanswered, and the third keeps
+    /// answering requests. Returns the server's url and the subscribe requests it received.
+    fn start_mock_server(runtime: &Runtime) -> (String, Receiver<Value>) {
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (requests_sender, requests_receiver) = channel();
+
+        runtime.spawn(async move {
+            let connection_roots = [vec![1, 2], vec![3], vec![5]];
+            for (subscription, roots) in connection_roots.iter().enumerate() {
+                let subscription = subscription as u64;
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                let request: Value = match ws.next().await {
+                    Some(Ok(Message::Text(text))) => serde_json::from_str(&text).unwrap(),
+                    message => panic!("unexpected message: {:?}", message),
+                };
+                let response = json!({"jsonrpc": "2.0", "result": subscription, "id": request["id"]});
+                ws.send(Message::Text(response.to_string())).await.unwrap();
+                requests_sender.send(request).unwrap();
+                for root in roots {
+                    ws.send(root_notification(subscription, *root)).await.unwrap();
+                }
+
+                match subscription {
+                    0 => drop(ws),
+                    1 => {
+                        tokio::spawn(async move {
+                            let _ws = ws;
+                            sleep(Duration::from_secs(60)).await;
+                        });
                     }
-                    match PubsubClientSubscription::read_message(&socket) {
-                        Ok(message) => handler(message),
-                        Err(err) => {
-                            info!("receive error: {:?}", err);
-                            break;
-                        }
+                    _ => {
+                        tokio::spawn(async move {
+                            while let Some(Ok(message)) = ws.next().await {
+                                if let Message::Text(text) = message {
+                                    let request: Value = serde_json::from_str(&text).unwrap();
+                                    let response =
+                                        json!({"jsonrpc": "2.0", "result": true, "id": request["id"]});
+                                    let _ = ws.send(Message::Text(response.to_string())).await;
+                                }
+                            }
+                        });
                     }
                 }
+            }
+        });
 
-                info!("websocket - exited receive loop");
-            })
+        (url, requests_receiver)
+    }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let config = PubsubReconnectConfig {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            max_attempts: None,
+            ..PubsubReconnectConfig::default()
         };
+        let mut backoff = config.initial_backoff;
+        let mut backoffs = vec![];
+        for _ in 0..5 {
+            backoffs.push(backoff.as_secs());
+            backoff = config.next_backoff(backoff);
+        }
+        assert_eq!(backoffs, vec![1, 2, 4, 5, 5]);
+    }
 
-        Ok(PubsubClientSubscription {
-            message_type: PhantomData,
-            operation: "slotsUpdates",
-            socket,
-            subscription_id,
-            t_cleanup: Some(t_cleanup),
-            exit,
-        })
+    #[test]
+    fn test_reconnecting_subscription() {
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        let (url, requests) = start_mock_server(&runtime);
+        let backoff = Duration::from_millis(10);
+        let config = PubsubReconnectConfig {
+            initial_backoff: backoff,
+            max_backoff: backoff,
+            max_attempts: Some(3),
+            ping_interval: Duration::from_millis(100),
+        };
+        let client = ReconnectingPubsubClient::new(&url, config);
+        let (mut subscription, roots, events) = client.root_subscribe().unwrap();
+        let timeout = Duration::from_secs(10);
+
+        // The socket is dropped after two notifications
+        assert_eq!(roots.recv_timeout(timeout), Ok(1));
+        assert_eq!(roots.recv_timeout(timeout), Ok(2));
+        assert_matches!(
+            events.recv_timeout(timeout),
+            Ok(PubsubConnectionEvent::Disconnected {
+                last_seen_slot: Some(2),
+                ..
+            })
+        );
+        assert_eq!(
+            events.recv_timeout(timeout),
+            Ok(PubsubConnectionEvent::Reconnecting {
+                attempt: 1,
+                backoff
+            })
+        );
+        assert_eq!(
+            events.recv_timeout(timeout),
+            Ok(PubsubConnectionEvent::Resubscribed {
+                last_seen_slot: Some(2)
+            })
+        );
+
+        // The second connection goes silent after one notification, and is dropped once a
+        // ping goes unanswered
+        assert_eq!(roots.recv_timeout(timeout), Ok(3));
+        assert_eq!(
+            events.recv_timeout(timeout),
+            Ok(PubsubConnectionEvent::Disconnected {
+                reason: "connection closed (no pong received within 100ms)".to_string(),
+                last_seen_slot: Some(3),
+            })
+        );
+        assert_eq!(
+            events.recv_timeout(timeout),
+            Ok(PubsubConnectionEvent::Reconnecting {
+                attempt: 1,
+                backoff
+            })
+        );
+        assert_eq!(
+            events.recv_timeout(timeout),
+            Ok(PubsubConnectionEvent::Resubscribed {
+                last_seen_slot: Some(3)
+            })
+        );
+        assert_eq!(roots.recv_timeout(timeout), Ok(5));
+
+        // Each connection was sent the original subscribe request
+        let requests: Vec<_> = requests.try_iter().collect();
+        assert_eq!(requests.len(), 3);
+        for request in requests {
+            assert_eq!(request["method"], "rootSubscribe");
+            assert_eq!(request["params"], json!([]));
+        }
+
+        subscription.shutdown().unwrap();
+        assert!(roots.recv_timeout(timeout).is_err());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    // see core/tests/client.rs#test_slot_subscription()
+    #[test]
+    fn test_notification_slot() {
+        let response = RpcResponse {
+            context: RpcResponseContext { slot: 7 },
+            value: (),
+        };
+        assert_eq!(response.notification_slot(), Some(7));
+        assert_eq!(42u64.notification_slot(), Some(42));
+        let vote = RpcVote {
+            slots: vec![],
+            hash: String::default(),
+            timestamp: None,
+        };
+        assert_eq!(vote.notification_slot(), None);
+        let vote = RpcVote {
+            slots: vec![3, 4],
+            ..vote
+        };
+        assert_eq!(vote.notification_slot(), Some(4));
+    }
 }
@@ -0,0 +1,273 @@
+//! Sends transactions to the QUIC TPU endpoint of a node, one transaction per unidirectional
+//! stream, reusing a connection per destination while it stays open.
+//!
+//! Sends are queued on the client's runtime rather than awaited, and a transaction that cannot
+//! be delivered over QUIC is sent to the node's UDP TPU address instead.
+
+use {
+    log::*,
+    quinn::{ClientConfig, Connection, Endpoint, IdleTimeout, NewConnection, VarInt},
+    solana_sdk::quic::{QUIC_PORT_OFFSET, QUIC_TPU_ALPN_PROTOCOL_ID},
+    std::{
+        collections::HashMap,
+        net::{SocketAddr, UdpSocket},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::{Duration, SystemTime},
+    },
+    thiserror::Error,
+    tokio::runtime::Runtime,
+};
+
+/// Most sends queued at once; beyond it transactions are sent over UDP
+const MAX_IN_FLIGHT_SENDS: usize = 1024;
+/// How long a connection, including one being established, may stay idle
+const QUIC_MAX_TIMEOUT_MS: u32 = 2_000;
+const QUIC_KEEP_ALIVE_MS: u64 = 1_000;
+
+#[derive(Error, Debug)]
+pub enum QuicClientError {
+    #[error("connect error: {0}")]
+    ConnectError(#[from] quinn::ConnectError),
+
+    #[error("connection error: {0}")]
+    ConnectionError(#[from] quinn::ConnectionError),
+
+    #[error("write error: {0}")]
+    WriteError(#[from] quinn::WriteError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+type Result<T> = std::result::Result<T, QuicClientError>;
+
+/// TPU nodes present throwaway self-signed certificates, so there is nothing to verify
+struct SkipServerVerification;
+
+impl rustls::client::ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> std::result::Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+/// The address of the QUIC TPU endpoint paired with a node's UDP TPU address
+pub fn quic_tpu_address(tpu_address: &SocketAddr) -> SocketAddr {
+    SocketAddr::new(
+        tpu_address.ip(),
+        tpu_address.port().saturating_add(QUIC_PORT_OFFSET),
+    )
+}
+
+/// The endpoint and its cached connections, shared with queued sends
+struct QuicSender {
+    endpoint: Endpoint,
+    connections: Mutex<HashMap<SocketAddr, Connection>>,
+}
+
+impl QuicSender {
+    async fn send(&self, addr: &SocketAddr, data: &[u8]) -> Result<()> {
+        if let Some(connection) = self.cached_connection(addr) {
+            match Self::send_on_connection(&connection, data).await {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    // The cached connection may have been closed by the server, retry once
+                    // on a fresh one
+                    debug!("quic send to {} failed, reconnecting: {}", addr, err);
+                    self.connections.lock().unwrap().remove(addr);
+                }
+            }
+        }
+        let connection = self.connect(addr).await?;
+        Self::send_on_connection(&connection, data).await
+    }
+
+    fn cached_connection(&self, addr: &SocketAddr) -> Option<Connection> {
+        self.connections.lock().unwrap().get(addr).cloned()
+    }
+
+    async fn connect(&self, addr: &SocketAddr) -> Result<Connection> {
+        let NewConnection { connection, .. } = self.endpoint.connect(*addr, "solana-tpu")?.await?;
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(*addr, connection.clone());
+        Ok(connection)
+    }
+
+    async fn send_on_connection(connection: &Connection, data: &[u8]) -> Result<()> {
+        let mut send_stream = connection.open_uni().await?;
+        send_stream.write_all(data).await?;
+        send_stream.finish().await?;
+        Ok(())
+    }
+}
+
+pub struct QuicClient {
+    runtime: Runtime,
+    sender: Arc<QuicSender>,
+    udp_socket: Arc<UdpSocket>,
+    in_flight_sends: Arc<AtomicUsize>,
+}
+
+impl QuicClient {
+    pub fn new() -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("sol-quic-client")
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+
+        let mut crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+            .with_no_client_auth();
+        crypto.alpn_protocols = vec![QUIC_TPU_ALPN_PROTOCOL_ID.to_vec()];
+
+        let mut client_config = ClientConfig::new(Arc::new(crypto));
+        let transport_config = Arc::get_mut(&mut client_config.transport).unwrap();
+        transport_config
+            .max_idle_timeout(Some(IdleTimeout::from(VarInt::from(QUIC_MAX_TIMEOUT_MS))));
+        transport_config.keep_alive_interval(Some(Duration::from_millis(QUIC_KEEP_ALIVE_MS)));
+
+        let mut endpoint = {
+            let _guard = runtime.enter();
+            Endpoint::client(SocketAddr::from(([0, 0, 0, 0], 0)))?
+        };
+        endpoint.set_default_client_config(client_config);
+
+        Ok(Self {
+            runtime,
+            sender: Arc::new(QuicSender {
+                endpoint,
+                connections: Mutex::new(HashMap::new()),
+            }),
+            udp_socket: Arc::new(UdpSocket::bind("0.0.0.0:0")?),
+            in_flight_sends: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Queues a transaction for the QUIC endpoint paired with the UDP `tpu_address` and returns
+    /// without waiting for it to be sent. The transaction is sent to `tpu_address` over UDP
+    /// instead if it cannot be delivered over QUIC, or if too many sends are already queued.
+    pub fn send_wire_transaction(&self, tpu_address: &SocketAddr, data: &[u8]) {
+        if self.in_flight_sends.fetch_add(1, Ordering::Relaxed) >= MAX_IN_FLIGHT_SENDS {
+            self.in_flight_sends.fetch_sub(1, Ordering::Relaxed);
+            send_over_udp(&self.udp_socket, tpu_address, data);
+            return;
+        }
+        let sender = self.sender.clone();
+        let udp_socket = self.udp_socket.clone();
+        let in_flight_sends = self.in_flight_sends.clone();
+        let tpu_address = *tpu_address;
+        let data = data.to_vec();
+        self.runtime.spawn(async move {
+            let addr = quic_tpu_address(&tpu_address);
+            if let Err(err) = sender.send(&addr, &data).await {
+                debug!("quic send to {} failed, sending over UDP: {}", addr, err);
+                send_over_udp(&udp_socket, &tpu_address, &data);
+            }
+            in_flight_sends.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+fn send_over_udp(udp_socket: &UdpSocket, tpu_address: &SocketAddr, data: &[u8]) {
+    if let Err(err) = udp_socket.send_to(data, tpu_address) {
+        warn!("Failed to send transaction to {}: {}", tpu_address, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_streamer::quic::{spawn_server, QuicServerConfig, StakedNodes},
+        std::{
+            sync::{atomic::AtomicBool, mpsc::channel, RwLock},
+            time::Instant,
+        },
+    };
+
+    #[test]
+    fn test_quic_tpu_address() {
+        let tpu_address = SocketAddr::from(([127, 0, 0, 1], 8003));
+        assert_eq!(
+            quic_tpu_address(&tpu_address),
+            SocketAddr::from(([127, 0, 0, 1], 8003 + QUIC_PORT_OFFSET))
+        );
+    }
+
+    #[test]
+    fn test_quic_client_send() {
+        solana_logger::setup();
+        let exit = Arc::new(AtomicBool::new(false));
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let quic_address = socket.local_addr().unwrap();
+        let tpu_address =
+            SocketAddr::new(quic_address.ip(), quic_address.port() - QUIC_PORT_OFFSET);
+        let (packet_sender, packet_receiver) = channel();
+        let server = spawn_server(
+            socket,
+            packet_sender,
+            exit.clone(),
+            Arc::new(RwLock::new(StakedNodes::default())),
+            QuicServerConfig::default(),
+        )
+        .unwrap();
+
+        let client = QuicClient::new().unwrap();
+        let transactions: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 100]).collect();
+        for transaction in &transactions {
+            client.send_wire_transaction(&tpu_address, transaction);
+        }
+
+        let mut received = vec![];
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while received.len() < transactions.len() && Instant::now() < deadline {
+            if let Ok(packets) = packet_receiver.recv_timeout(Duration::from_millis(100)) {
+                received.extend(
+                    packets
+                        .packets
+                        .iter()
+                        .map(|packet| packet.data[..packet.meta.size].to_vec()),
+                );
+            }
+        }
+        received.sort();
+        assert_eq!(received, transactions);
+        // All were sent over the one cached connection
+        assert_eq!(client.sender.connections.lock().unwrap().len(), 1);
+
+        exit.store(true, Ordering::Relaxed);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_quic_client_udp_fallback() {
+        solana_logger::setup();
+        // Nothing serves QUIC next to this TPU address
+        let tpu_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tpu_address = tpu_socket.local_addr().unwrap();
+        tpu_socket
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+
+        let client = QuicClient::new().unwrap();
+        client.send_wire_transaction(&tpu_address, &[7; 100]);
+
+        let mut buf = [0; 1024];
+        let (size, _) = tpu_socket.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..size], &[7; 100][..]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Batches of RPC requests, sent to a node in a single JSON-RPC round-trip.
+//!
+//! Requests are queued with [`RpcBatch::add`], which returns a typed
+//! [`RpcBatchHandle`] for each. Once the batch has been sent, with
+//! [`RpcClient::send_batch`], each handle retrieves its own result from the
+//! [`RpcBatchResponse`]; one request failing does not affect the others.
+//!
+//! [`RpcClient::send_batch`]: crate::rpc_client::RpcClient::send_batch
+
+use {
+    crate::{
+        client_error::{ClientError, Result as ClientResult},
+        rpc_request::{RpcError, RpcRequest},
+    },
+    serde::de::DeserializeOwned,
+    serde_json::Value,
+    std::marker::PhantomData,
+};
+
+/// Requests to be sent to a node together, in a single JSON-RPC batch.
+#[derive(Debug, Default)]
+pub struct RpcBatch {
+    requests: Vec<(RpcRequest, Value)>,
+}
+
+/// Refers to the result, of type `T`, of one request in an [`RpcBatch`].
+#[derive(Debug)]
+pub struct RpcBatchHandle<T> {
+    index: usize,
+    _result: PhantomData<fn() -> T>,
+}
+
+impl RpcBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a request, returning the handle by which to retrieve its result.
+    ///
+    /// `params` must be a JSON array, or null for a request without parameters.
+    pub fn add<T: DeserializeOwned>(
+        &mut self,
+        request: RpcRequest,
+        params: Value,
+    ) -> ClientResult<RpcBatchHandle<T>> {
+        if !(params.is_array() || params.is_null()) {
+            return Err(ClientError::new_with_request(
+                RpcError::RpcRequestError("Params must be an array or null".to_string()).into(),
+                request,
+            ));
+        }
+        self.requests.push((request, params));
+        Ok(RpcBatchHandle {
+            index: self.requests.len() - 1,
+            _result: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    pub(crate) fn into_requests(self) -> Vec<(RpcRequest, Value)> {
+        self.requests
+    }
+}
+
+/// The results of the requests in an [`RpcBatch`].
+#[derive(Debug)]
+pub struct RpcBatchResponse {
+    results: Vec<(RpcRequest, Option<ClientResult<Value>>)>,
+}
+
+impl RpcBatchResponse {
+    pub(crate) fn new(requests: Vec<RpcRequest>, results: Vec<ClientResult<Value>>) -> Self {
+        assert_eq!(requests.len(), results.len());
+        Self {
+            results: requests
+                .into_iter()
+                .zip(results)
+                .map(|(request, result)| {
+                    (
+                        request,
+                        Some(result.map_err(|err| err.into_with_request(request))),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Take the result of the request that `handle` refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `handle` belongs to a different batch.
+    pub fn take<T: DeserializeOwned>(&mut self, handle: RpcBatchHandle<T>) -> ClientResult<T> {
+        let (request, result) = &mut self.results[handle.index];
+        let request = *request;
+        let value = result.take().unwrap_or_else(|| {
+            Err(RpcError::RpcRequestError("Batch result already taken".to_string()).into())
+        })?;
+        serde_json::from_value(value)
+            .map_err(|err| ClientError::new_with_request(err.into(), request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn test_rpc_batch_response() {
+        let mut batch = RpcBatch::new();
+        let slot = batch.add::<u64>(RpcRequest::GetSlot, Value::Null).unwrap();
+        let version = batch
+            .add::<String>(RpcRequest::GetVersion, Value::Null)
+            .unwrap();
+        let balance = batch
+            .add::<u64>(RpcRequest::GetBalance, json!(["bogus"]))
+            .unwrap();
+        // Params that are neither an array nor null are refused, and not queued
+        let err = batch
+            .add::<u64>(RpcRequest::GetBalance, json!("bogus"))
+            .unwrap_err();
+        assert_eq!(err.request(), Some(&RpcRequest::GetBalance));
+        assert_eq!(batch.len(), 3);
+
+        let requests: Vec<_> = batch
+            .into_requests()
+            .into_iter()
+            .map(|(request, _)| request)
+            .collect();
+        let mut response = RpcBatchResponse::new(
+            requests,
+            vec![
+                Ok(json!(42)),
+                Ok(json!({ "solana-core": "1.8.0" })),
+                Err(RpcError::RpcRequestError("invalid pubkey".to_string()).into()),
+            ],
+        );
+
+        assert_eq!(response.take(slot).unwrap(), 42);
+        // The result is of the wrong type
+        let err = response.take(version).unwrap_err();
+        assert_eq!(err.request(), Some(&RpcRequest::GetVersion));
+        let err = response.take(balance).unwrap_err();
+        assert_eq!(err.request(), Some(&RpcRequest::GetBalance));
+    }
+}
@@ -19,6 +19,9 @@ pub const JSON_RPC_SERVER_ERROR_KEY_EXCLUDED_FROM_SECONDARY_INDEX: i64 = -32010;
 pub const JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE: i64 = -32011;
 pub const JSON_RPC_SCAN_ERROR: i64 = -32012;
 pub const JSON_RPC_SERVER_ERROR_TRANSACTION_SIGNATURE_LEN_MISMATCH: i64 = -32013;
+pub const JSON_RPC_SERVER_ERROR_PROGRAM_ACCOUNTS_CURSOR_EXPIRED: i64 = -32014;
+pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32015;
+pub const JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_AVAILABLE: i64 = -32016;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -54,6 +57,12 @@ pub enum RpcCustomError {
     ScanError { message: String },
     #[error("TransactionSignatureLenMismatch")]
     TransactionSignatureLenMismatch,
+    #[error("ProgramAccountsCursorExpired")]
+    ProgramAccountsCursorExpired { slot: Slot },
+    #[error("MinContextSlotNotReached")]
+    MinContextSlotNotReached { context_slot: Slot },
+    #[error("AccountHistoryNotAvailable")]
+    AccountHistoryNotAvailable { slot: Slot },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -62,6 +71,12 @@ pub struct NodeUnhealthyErrorData {
     pub num_slots_behind: Option<Slot>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinContextSlotNotReachedErrorData {
+    pub context_slot: Slot,
+}
+
 impl From<RpcCustomError> for Error {
     fn from(e: RpcCustomError) -> Self {
         match e {
@@ -161,6 +176,30 @@ impl From<RpcCustomError> for Error {
                 message: "Transaction signature length mismatch".to_string(),
                 data: None,
             },
+            RpcCustomError::ProgramAccountsCursorExpired { slot } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_PROGRAM_ACCOUNTS_CURSOR_EXPIRED),
+                message: format!(
+                    "Cursor expired: bank at slot {} is no longer available; \
+                    restart pagination from the first page",
+                    slot
+                ),
+                data: None,
+            },
+            RpcCustomError::MinContextSlotNotReached { context_slot } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED),
+                message: "Minimum context slot has not been reached".to_string(),
+                data: Some(serde_json::json!(MinContextSlotNotReachedErrorData {
+                    context_slot
+                })),
+            },
+            RpcCustomError::AccountHistoryNotAvailable { slot } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_AVAILABLE),
+                message: format!(
+                    "Account state at slot {} is not available from this node",
+                    slot
+                ),
+                data: None,
+            },
         }
     }
 }
@@ -10,6 +10,7 @@ use {
 pub enum RpcRequest {
     DeregisterNode,
     GetAccountInfo,
+    GetAccountModifications,
     GetBalance,
     GetBlock,
     GetBlockHeight,
@@ -58,6 +59,7 @@ pub enum RpcRequest {
     GetMinimumBalanceForRentExemption,
     GetMultipleAccounts,
     GetProgramAccounts,
+    GetProgramAccountsPaginated,
     GetRecentBlockhash,
     GetRecentPerformanceSamples,
     GetSnapshotSlot,
@@ -78,6 +80,7 @@ pub enum RpcRequest {
     GetTokenSupply,
     GetTransaction,
     GetTransactionCount,
+    GetTransactionRetryStatus,
     GetVersion,
     GetVoteAccounts,
     MinimumLedgerSlot,
@@ -94,6 +97,7 @@ impl fmt::Display for RpcRequest {
         let method = match self {
             RpcRequest::DeregisterNode => "deregisterNode",
             RpcRequest::GetAccountInfo => "getAccountInfo",
+            RpcRequest::GetAccountModifications => "getAccountModifications",
             RpcRequest::GetBalance => "getBalance",
             RpcRequest::GetBlock => "getBlock",
             RpcRequest::GetBlockHeight => "getBlockHeight",
@@ -126,6 +130,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetMinimumBalanceForRentExemption => "getMinimumBalanceForRentExemption",
             RpcRequest::GetMultipleAccounts => "getMultipleAccounts",
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
+            RpcRequest::GetProgramAccountsPaginated => "getProgramAccountsPaginated",
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
             RpcRequest::GetSnapshotSlot => "getSnapshotSlot",
@@ -146,6 +151,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetTokenSupply => "getTokenSupply",
             RpcRequest::GetTransaction => "getTransaction",
             RpcRequest::GetTransactionCount => "getTransactionCount",
+            RpcRequest::GetTransactionRetryStatus => "getTransactionRetryStatus",
             RpcRequest::GetVersion => "getVersion",
             RpcRequest::GetVoteAccounts => "getVoteAccounts",
             RpcRequest::MinimumLedgerSlot => "minimumLedgerSlot",
@@ -167,6 +173,7 @@ pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
+pub const MAX_GET_PROGRAM_ACCOUNTS_PAGE_SIZE: usize = 10_000;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
 
 // Validators that are this number of slots behind are considered delinquent
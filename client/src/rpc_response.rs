@@ -9,9 +9,12 @@ use {
         transaction::{Result, TransactionError},
     },
     solana_transaction_status::{
-        ConfirmedTransactionStatusWithSignature, TransactionConfirmationStatus,
+        AccountModification, ConfirmedTransactionStatusWithSignature,
+        TransactionConfirmationStatus, UiConfirmedBlock, UiInnerInstructions,
+        UiTransactionTokenBalance,
     },
     std::{collections::HashMap, fmt, net::SocketAddr},
+    thiserror::Error,
 };
 
 pub type RpcResult<T> = client_error::Result<Response<T>>;
@@ -116,6 +119,14 @@ pub struct RpcKeyedAccount {
     pub account: UiAccount,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcKeyedAccountsPage {
+    pub accounts: Vec<RpcKeyedAccount>,
+    /// Opaque cursor to pass back for the next page; `None` once the last page is reached
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct SlotInfo {
     pub slot: Slot,
@@ -197,6 +208,29 @@ pub struct RpcLogsResponse {
     pub logs: Vec<String>,
 }
 
+// A more human-friendly version of Vote, with the bank state signature base58 encoded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcVote {
+    pub slots: Vec<Slot>,
+    pub hash: String,
+    pub timestamp: Option<UnixTimestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Error, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcBlockUpdateError {
+    #[error("block store error")]
+    BlockStoreError,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockUpdate {
+    pub slot: Slot,
+    pub block: Option<UiConfirmedBlock>,
+    pub err: Option<RpcBlockUpdateError>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ProcessedSignatureResult {
@@ -329,6 +363,26 @@ pub struct RpcSimulateTransactionResult {
     pub logs: Option<Vec<String>>,
     pub accounts: Option<Vec<Option<UiAccount>>>,
     pub units_consumed: Option<u64>,
+    // Only set when a state diff is requested, and left out of the response otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units_consumed_per_instruction: Option<Vec<u64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_diffs: Option<Vec<RpcSimulateTransactionAccountDiff>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_instructions: Option<Vec<UiInnerInstructions>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
+}
+
+/// State of a writable account before and after a simulated transaction
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionAccountDiff {
+    pub pubkey: String,
+    pub pre: UiAccount,
+    pub post: UiAccount,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -390,6 +444,27 @@ pub struct RpcConfirmedTransactionStatusWithSignature {
     pub confirmation_status: Option<TransactionConfirmationStatus>,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountModification {
+    pub signature: String,
+    pub slot: Slot,
+    pub err: Option<TransactionError>,
+    pub block_time: Option<UnixTimestamp>,
+    pub pre_lamports: u64,
+    pub post_lamports: u64,
+    pub confirmation_status: Option<TransactionConfirmationStatus>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountModifications {
+    pub modifications: Vec<RpcAccountModification>,
+    /// Set when the search stopped before the end of the account's history; pass it as `before`
+    /// to continue the search
+    pub next_before: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcPerfSample {
@@ -399,6 +474,35 @@ pub struct RpcPerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcTransactionRetryState {
+    Pending,
+    Rooted,
+    Failed,
+    Expired,
+    MaxRetriesReached,
+    Dropped,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionRetryLeader {
+    /// Leader identity, if known
+    pub identity: Option<String>,
+    pub tpu: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionRetryStatus {
+    pub state: RpcTransactionRetryState,
+    pub retries: usize,
+    pub max_retries: Option<usize>,
+    pub last_valid_slot: Slot,
+    pub leaders: Vec<RpcTransactionRetryLeader>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcInflationReward {
@@ -428,3 +532,25 @@ impl From<ConfirmedTransactionStatusWithSignature> for RpcConfirmedTransactionSt
         }
     }
 }
+
+impl From<AccountModification> for RpcAccountModification {
+    fn from(value: AccountModification) -> Self {
+        let AccountModification {
+            signature,
+            slot,
+            err,
+            block_time,
+            pre_lamports,
+            post_lamports,
+        } = value;
+        Self {
+            signature: signature.to_string(),
+            slot,
+            err,
+            block_time,
+            pre_lamports,
+            post_lamports,
+            confirmation_status: None,
+        }
+    }
+}
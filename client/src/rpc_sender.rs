@@ -1,6 +1,9 @@
 //! A transport for RPC calls.
 
-use crate::{client_error::Result, rpc_request::RpcRequest};
+use {
+    crate::{client_error::Result, rpc_request::RpcRequest},
+    async_trait::async_trait,
+};
 
 /// A transport for RPC calls.
 ///
@@ -10,9 +13,33 @@ use crate::{client_error::Result, rpc_request::RpcRequest};
 /// It is typically implemented by [`HttpSender`] in production, and
 /// [`MockSender`] in unit tests.
 ///
-/// [`RpcClient`]: crate::rpc_client::RpcClient
+/// [`RpcClient`]: crate::nonblocking::rpc_client::RpcClient
 /// [`HttpSender`]: crate::http_sender::HttpSender
 /// [`MockSender`]: crate::mock_sender::MockSender
+#[async_trait]
 pub trait RpcSender {
-    fn send(&self, request: RpcRequest, params: serde_json::Value) -> Result<serde_json::Value>;
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value>;
+
+    /// Send several requests at once, returning a result for each, in order.
+    ///
+    /// The outer `Result` is an error only if the batch as a whole failed.
+    /// The default implementation sends the requests one at a time; senders
+    /// that can do better, such as [`HttpSender`], send them in a single
+    /// JSON-RPC batch.
+    ///
+    /// [`HttpSender`]: crate::http_sender::HttpSender
+    async fn send_batch(
+        &self,
+        requests: Vec<(RpcRequest, serde_json::Value)>,
+    ) -> Result<Vec<Result<serde_json::Value>>> {
+        let mut results = Vec::with_capacity(requests.len());
+        for (request, params) in requests {
+            results.push(self.send(request, params).await);
+        }
+        Ok(results)
+    }
 }
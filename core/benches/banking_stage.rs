@@ -10,7 +10,7 @@ use rayon::prelude::*;
 use solana_core::banking_stage::{BankingStage, BankingStageStats};
 use solana_core::cost_model::CostModel;
 use solana_core::cost_tracker::CostTracker;
-use solana_entry::entry::{next_hash, Entry};
+use solana_entry::entry::Entry;
 use solana_gossip::cluster_info::ClusterInfo;
 use solana_gossip::cluster_info::Node;
 use solana_ledger::blockstore_processor::process_entries;
@@ -78,7 +78,7 @@ fn bench_consume_buffered(bencher: &mut Bencher) {
         let mut packets = VecDeque::new();
         for batch in batches {
             let batch_len = batch.packets.len();
-            packets.push_back((batch, vec![0usize; batch_len], false));
+            packets.push_back((batch, vec![0usize; batch_len], false, 0));
         }
         let (s, _r) = unbounded();
         // This tests the performance of buffering packets.
@@ -310,11 +310,7 @@ fn simulate_process_entries(
     }
 
     // Transfer lamports to each other
-    let entry = Entry {
-        num_hashes: 1,
-        hash: next_hash(&bank.last_blockhash(), 1, &tx_vector),
-        transactions: tx_vector,
-    };
+    let entry = Entry::new(&bank.last_blockhash(), 1, tx_vector);
     process_entries(&bank, &mut [entry], randomize_txs, None, None).unwrap();
 }
 
@@ -9,7 +9,9 @@ use rayon::ThreadPool;
 use solana_gossip::cluster_info::{ClusterInfo, MAX_SNAPSHOT_HASHES};
 use solana_runtime::{
     accounts_db,
-    snapshot_package::{AccountsPackage, AccountsPackagePre, AccountsPackageReceiver},
+    snapshot_package::{
+        AccountsPackage, AccountsPackagePre, AccountsPackageReceiver, SnapshotType,
+    },
 };
 use solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey};
 use std::collections::{HashMap, HashSet};
@@ -36,7 +38,6 @@ impl AccountsHashVerifier {
         trusted_validators: Option<HashSet<Pubkey>>,
         halt_on_trusted_validators_accounts_hash_mismatch: bool,
         fault_injection_rate_slots: u64,
-        snapshot_interval_slots: u64,
     ) -> Self {
         let exit = exit.clone();
         let cluster_info = cluster_info.clone();
@@ -68,7 +69,6 @@ impl AccountsHashVerifier {
                                 &mut hashes,
                                 &exit,
                                 fault_injection_rate_slots,
-                                snapshot_interval_slots,
                                 thread_pool_storage.as_ref(),
                             );
                         }
@@ -93,13 +93,13 @@ impl AccountsHashVerifier {
         hashes: &mut Vec<(Slot, Hash)>,
         exit: &Arc<AtomicBool>,
         fault_injection_rate_slots: u64,
-        snapshot_interval_slots: u64,
         thread_pool: Option<&ThreadPool>,
     ) {
+        let snapshot_type = accounts_package.snapshot_type;
         let accounts_package = solana_runtime::snapshot_utils::process_accounts_package_pre(
             accounts_package,
             thread_pool,
-            None,
+            snapshot_type.and_then(|snapshot_type| snapshot_type.incremental_snapshot_base_slot()),
         );
         Self::process_accounts_package(
             accounts_package,
@@ -110,10 +110,11 @@ impl AccountsHashVerifier {
             hashes,
             exit,
             fault_injection_rate_slots,
-            snapshot_interval_slots,
+            snapshot_type,
         );
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_accounts_package(
         accounts_package: AccountsPackage,
         cluster_info: &ClusterInfo,
@@ -123,7 +124,7 @@ impl AccountsHashVerifier {
         hashes: &mut Vec<(Slot, Hash)>,
         exit: &Arc<AtomicBool>,
         fault_injection_rate_slots: u64,
-        snapshot_interval_slots: u64,
+        snapshot_type: Option<SnapshotType>,
     ) {
         let hash = accounts_package.hash;
         if fault_injection_rate_slots != 0
@@ -154,7 +155,7 @@ impl AccountsHashVerifier {
             }
         }
 
-        if accounts_package.block_height % snapshot_interval_slots == 0 {
+        if snapshot_type.is_some() {
             if let Some(pending_snapshot_package) = pending_snapshot_package.as_ref() {
                 *pending_snapshot_package.lock().unwrap() = Some(accounts_package);
             }
@@ -303,7 +304,7 @@ mod tests {
                 &mut hashes,
                 &exit,
                 0,
-                100,
+                None,
             );
             // sleep for 1ms to create a newer timestmap for gossip entry
             // otherwise the timestamp won't be newer.
@@ -39,6 +39,7 @@ use solana_sdk::{
     signature::Signature,
     timing::{duration_as_ms, timestamp},
     transaction::{self, Transaction, TransactionError},
+    versioned_transaction::VersionedTransaction,
 };
 use solana_transaction_status::token_balances::{
     collect_token_balances, TransactionTokenBalancesSet,
@@ -58,9 +59,10 @@ use std::{
     time::Instant,
 };
 
-/// (packets, valid_indexes, forwarded)
-/// Set of packets with a list of which are valid and if this batch has been forwarded.
-type PacketsAndOffsets = (Packets, Vec<usize>, bool);
+/// (packets, valid_indexes, forwarded, priority)
+/// Set of packets with a list of which are valid, if this batch has been forwarded and the
+/// highest priority among its valid packets.
+type PacketsAndOffsets = (Packets, Vec<usize>, bool, u64);
 
 pub type UnprocessedPackets = VecDeque<PacketsAndOffsets>;
 
@@ -343,8 +345,8 @@ impl BankingStage {
         all_packets: impl Iterator<Item = &'a PacketsAndOffsets>,
     ) -> Vec<&'a Packet> {
         all_packets
-            .filter(|(_p, _indexes, forwarded)| !forwarded)
-            .flat_map(|(p, valid_indexes, _forwarded)| {
+            .filter(|(_p, _indexes, forwarded, _priority)| !forwarded)
+            .flat_map(|(p, valid_indexes, _forwarded, _priority)| {
                 valid_indexes.iter().map(move |x| &p.packets[*x])
             })
             .collect()
@@ -408,22 +410,22 @@ impl BankingStage {
         let mut proc_start = Measure::start("consume_buffered_process");
         let mut reached_end_of_slot = None;
 
-        buffered_packets.retain_mut(|(msgs, ref mut original_unprocessed_indexes, _forwarded)| {
-            if let Some((next_leader, bank)) = &reached_end_of_slot {
+        // Buffered packets are consumed in priority order across all of the batches, and each
+        // batch keeps its packets that are still unprocessed
+        let mut still_unprocessed_indexes = vec![vec![]; buffered_packets.len()];
+        for (batch_index, packet_indexes) in Self::prioritized_packet_indexes(buffered_packets) {
+            let msgs = &buffered_packets[batch_index].0;
+            let new_unprocessed_indexes = if let Some((next_leader, bank)) = &reached_end_of_slot {
                 // We've hit the end of this slot, no need to perform more processing,
                 // just filter the remaining packets for the invalid (e.g. too old) ones
-                let new_unprocessed_indexes = Self::filter_unprocessed_packets(
+                Self::filter_unprocessed_packets(
                     bank,
                     msgs,
-                    original_unprocessed_indexes,
+                    &packet_indexes,
                     my_pubkey,
                     *next_leader,
                     cost_tracker,
                     banking_stage_stats,
-                );
-                Self::update_buffered_packets_with_new_unprocessed(
-                    original_unprocessed_indexes,
-                    new_unprocessed_indexes,
                 )
             } else {
                 let bank_start = poh_recorder.lock().unwrap().bank_start();
@@ -439,7 +441,7 @@ impl BankingStage {
                             &bank_creation_time,
                             recorder,
                             msgs,
-                            original_unprocessed_indexes.to_owned(),
+                            packet_indexes,
                             transaction_status_sender.clone(),
                             gossip_vote_sender,
                             banking_stage_stats,
@@ -456,28 +458,36 @@ impl BankingStage {
                     }
                     new_tx_count += processed;
                     // Out of the buffered packets just retried, collect any still unprocessed
-                    // transactions in this batch for forwarding
+                    // transactions for forwarding
                     rebuffered_packets_len += new_unprocessed_indexes.len();
-                    let has_more_unprocessed_transactions =
-                        Self::update_buffered_packets_with_new_unprocessed(
-                            original_unprocessed_indexes,
-                            new_unprocessed_indexes,
-                        );
                     if let Some(test_fn) = &test_fn {
                         test_fn();
                     }
-                    has_more_unprocessed_transactions
+                    new_unprocessed_indexes
                 } else {
-                    rebuffered_packets_len += original_unprocessed_indexes.len();
-                    // `original_unprocessed_indexes` must have remaining packets to process
-                    // if not yet processed.
-                    assert!(Self::packet_has_more_unprocessed_transactions(
-                        original_unprocessed_indexes
-                    ));
-                    true
+                    rebuffered_packets_len += packet_indexes.len();
+                    packet_indexes
                 }
-            }
-        });
+            };
+            still_unprocessed_indexes[batch_index].extend(new_unprocessed_indexes);
+        }
+
+        let mut still_unprocessed_indexes = still_unprocessed_indexes.into_iter();
+        buffered_packets.retain_mut(
+            |(msgs, ref mut original_unprocessed_indexes, _forwarded, priority)| {
+                let mut new_unprocessed_indexes = still_unprocessed_indexes.next().unwrap();
+                new_unprocessed_indexes.sort_unstable();
+                let has_more_unprocessed_transactions =
+                    Self::update_buffered_packets_with_new_unprocessed(
+                        original_unprocessed_indexes,
+                        new_unprocessed_indexes,
+                    );
+                if has_more_unprocessed_transactions {
+                    *priority = Self::batch_priority(msgs, original_unprocessed_indexes);
+                }
+                has_more_unprocessed_transactions
+            },
+        );
 
         proc_start.stop();
 
@@ -643,8 +653,8 @@ impl BankingStage {
         };
         let _ = Self::forward_buffered_packets(socket, &addr, buffered_packets);
         if hold {
-            buffered_packets.retain(|(_, index, _)| !index.is_empty());
-            for (_, _, forwarded) in buffered_packets.iter_mut() {
+            buffered_packets.retain(|(_, index, _, _)| !index.is_empty());
+            for (_, _, forwarded, _) in buffered_packets.iter_mut() {
                 *forwarded = true;
             }
         } else {
@@ -741,9 +751,9 @@ impl BankingStage {
     }
 
     #[allow(clippy::match_wild_err_arm)]
-    fn record_transactions<'a>(
+    fn record_transactions(
         bank_slot: Slot,
-        txs: impl Iterator<Item = &'a Transaction>,
+        txs: &[SanitizedTransaction],
         results: &[TransactionExecutionResult],
         recorder: &TransactionRecorder,
     ) -> (Result<usize, PohRecorderError>, Vec<usize>) {
@@ -754,7 +764,7 @@ impl BankingStage {
             .enumerate()
             .filter_map(|(i, ((r, _n), x))| {
                 if Bank::can_commit(r) {
-                    Some((x.clone(), i))
+                    Some((x.to_versioned_transaction(), i))
                 } else {
                     None
                 }
@@ -847,7 +857,7 @@ impl BankingStage {
 
         let mut record_time = Measure::start("record_time");
         let (num_to_commit, retryable_record_txs) =
-            Self::record_transactions(bank.slot(), batch.transactions_iter(), &results, poh);
+            Self::record_transactions(bank.slot(), batch.sanitized_transactions(), &results, poh);
         inc_new_counter_info!(
             "banking_stage-record_transactions_num_to_commit",
             *num_to_commit.as_ref().unwrap_or(&0)
@@ -877,7 +887,10 @@ impl BankingStage {
 
             bank_utils::find_and_send_votes(sanitized_txs, &tx_results, Some(gossip_vote_sender));
             if let Some(transaction_status_sender) = transaction_status_sender {
-                let txs = batch.transactions_iter().cloned().collect();
+                let txs = sanitized_txs
+                    .iter()
+                    .map(|tx| tx.clone().into_owned())
+                    .collect();
                 let post_balances = bank.collect_balances(batch);
                 let post_token_balances = collect_token_balances(bank, batch, &mut mint_decimals);
                 transaction_status_sender.send_transaction_status_batch(
@@ -1052,29 +1065,32 @@ impl BankingStage {
     }
 
     // This function deserializes packets into transactions, computes the blake3 hash of transaction messages,
-    // and verifies secp256k1 instructions. A list of valid transactions are returned with their message hashes
+    // loads addresses from address lookup tables, and verifies secp256k1 instructions. A list of valid transactions are returned with their message hashes
     // and packet indexes.
     // Also returned is packet indexes for transaction should be retried due to cost limits.
     #[allow(clippy::needless_collect)]
     fn transactions_from_packets(
         msgs: &Packets,
         transaction_indexes: &[usize],
-        libsecp256k1_0_5_upgrade_enabled: bool,
+        bank: &Bank,
         cost_tracker: &Arc<RwLock<CostTracker>>,
         banking_stage_stats: &BankingStageStats,
     ) -> (Vec<SanitizedTransaction<'static>>, Vec<usize>, Vec<usize>) {
         let mut retryable_transaction_packet_indexes: Vec<usize> = vec![];
 
+        // Give transactions paying more per requested compute unit the first claim on contended
+        // account write locks within the batch
         let verified_transactions_with_packet_indexes: Vec<_> = transaction_indexes
             .iter()
+            .sorted_by_key(|tx_index| cmp::Reverse(msgs.packets[**tx_index].meta.priority))
             .filter_map(|tx_index| {
                 let p = &msgs.packets[*tx_index];
-                let tx: Transaction = limited_deserialize(&p.data[0..p.meta.size]).ok()?;
-                tx.verify_precompiles(libsecp256k1_0_5_upgrade_enabled)
-                    .ok()?;
+                let tx: VersionedTransaction = limited_deserialize(&p.data[0..p.meta.size]).ok()?;
                 let message_bytes = Self::packet_message(p)?;
                 let message_hash = Message::hash_raw_message(message_bytes);
-                let tx = SanitizedTransaction::try_create(Cow::Owned(tx), message_hash).ok()?;
+                let tx = bank.sanitize_transaction(tx, message_hash).ok()?;
+                tx.verify_precompiles(bank.libsecp256k1_0_5_upgrade_enabled())
+                    .ok()?;
                 Some((tx, *tx_index))
             })
             .collect();
@@ -1168,7 +1184,7 @@ impl BankingStage {
             Self::transactions_from_packets(
                 msgs,
                 &packet_indexes,
-                bank.libsecp256k1_0_5_upgrade_enabled(),
+                bank,
                 cost_tracker,
                 banking_stage_stats,
             );
@@ -1270,7 +1286,7 @@ impl BankingStage {
             Self::transactions_from_packets(
                 msgs,
                 transaction_indexes,
-                bank.libsecp256k1_0_5_upgrade_enabled(),
+                bank,
                 cost_tracker,
                 banking_stage_stats,
             );
@@ -1499,15 +1515,70 @@ impl BankingStage {
                 .fetch_add(packet_duplicate_check_time.as_us(), Ordering::Relaxed);
         }
         if Self::packet_has_more_unprocessed_transactions(&packet_indexes) {
+            let priority = Self::batch_priority(&packets, &packet_indexes);
             if unprocessed_packets.len() >= batch_limit {
                 *dropped_batches_count += 1;
-                unprocessed_packets.pop_front();
+                // Evict the oldest of the batches whose best packet has the lowest priority,
+                // unless the new batch ranks even lower, in which case it is the one dropped
+                let lowest_priority_batch = unprocessed_packets
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, (_, _, _, batch_priority))| *batch_priority)
+                    .map(|(index, (_, _, _, batch_priority))| (index, *batch_priority));
+                match lowest_priority_batch {
+                    Some((index, lowest_priority)) if lowest_priority <= priority => {
+                        unprocessed_packets.remove(index);
+                    }
+                    _ => return,
+                }
             }
             *newly_buffered_packets_count += packet_indexes.len();
-            unprocessed_packets.push_back((packets, packet_indexes, false));
+            unprocessed_packets.push_back((packets, packet_indexes, false, priority));
         }
     }
 
+    /// Highest priority among the given packets of a batch
+    fn batch_priority(packets: &Packets, packet_indexes: &[usize]) -> u64 {
+        packet_indexes
+            .iter()
+            .map(|index| packets.packets[*index].meta.priority)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Rank the unprocessed packets of all buffered batches by priority, highest first and in
+    /// arrival order among equal priorities.  Consecutive packets of the same batch are grouped
+    /// as `(batch_index, packet_indexes)` so that they're processed together.
+    fn prioritized_packet_indexes(
+        buffered_packets: &UnprocessedPackets,
+    ) -> Vec<(usize, Vec<usize>)> {
+        let mut priority_index: Vec<_> = buffered_packets
+            .iter()
+            .enumerate()
+            .flat_map(|(batch_index, (msgs, packet_indexes, _, _))| {
+                packet_indexes.iter().map(move |packet_index| {
+                    (
+                        msgs.packets[*packet_index].meta.priority,
+                        batch_index,
+                        *packet_index,
+                    )
+                })
+            })
+            .collect();
+        priority_index.sort_by_key(|(priority, _, _)| cmp::Reverse(*priority));
+
+        let mut prioritized_packet_indexes: Vec<(usize, Vec<usize>)> = vec![];
+        for (_, batch_index, packet_index) in priority_index {
+            match prioritized_packet_indexes.last_mut() {
+                Some((last_batch_index, packet_indexes)) if *last_batch_index == batch_index => {
+                    packet_indexes.push(packet_index)
+                }
+                _ => prioritized_packet_indexes.push((batch_index, vec![packet_index])),
+            }
+        }
+        prioritized_packet_indexes
+    }
+
     fn packet_has_more_unprocessed_transactions(packet_indexes: &[usize]) -> bool {
         !packet_indexes.is_empty()
     }
@@ -1806,7 +1877,7 @@ mod tests {
                 if !entries.is_empty() {
                     blockhash = entries.last().unwrap().hash;
                     for entry in entries {
-                        bank.process_transactions(entry.transactions.iter())
+                        bank.process_entry_transactions(entry.transactions)
                             .iter()
                             .for_each(|x| assert_eq!(*x, Ok(())));
                     }
@@ -1918,8 +1989,8 @@ mod tests {
                 .collect();
 
             let bank = Bank::new_no_wallclock_throttle(&genesis_config);
-            for entry in &entries {
-                bank.process_transactions(entry.transactions.iter())
+            for entry in entries {
+                bank.process_entry_transactions(entry.transactions)
                     .iter()
                     .for_each(|x| assert_eq!(*x, Ok(())));
             }
@@ -1976,18 +2047,18 @@ mod tests {
             let keypair2 = Keypair::new();
             let pubkey2 = solana_sdk::pubkey::new_rand();
 
-            let transactions = vec![
-                system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash()),
-                system_transaction::transfer(&keypair2, &pubkey2, 1, genesis_config.hash()),
+            let transactions: Vec<SanitizedTransaction> = vec![
+                system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash())
+                    .try_into()
+                    .unwrap(),
+                system_transaction::transfer(&keypair2, &pubkey2, 1, genesis_config.hash())
+                    .try_into()
+                    .unwrap(),
             ];
 
             let mut results = vec![(Ok(()), None), (Ok(()), None)];
-            let _ = BankingStage::record_transactions(
-                bank.slot(),
-                transactions.iter(),
-                &results,
-                &recorder,
-            );
+            let _ =
+                BankingStage::record_transactions(bank.slot(), &transactions, &results, &recorder);
             let (_bank, (entry, _tick_height)) = entry_receiver.recv().unwrap();
             assert_eq!(entry.transactions.len(), transactions.len());
 
@@ -1999,12 +2070,8 @@ mod tests {
                 )),
                 None,
             );
-            let (res, retryable) = BankingStage::record_transactions(
-                bank.slot(),
-                transactions.iter(),
-                &results,
-                &recorder,
-            );
+            let (res, retryable) =
+                BankingStage::record_transactions(bank.slot(), &transactions, &results, &recorder);
             res.unwrap();
             assert!(retryable.is_empty());
             let (_bank, (entry, _tick_height)) = entry_receiver.recv().unwrap();
@@ -2012,12 +2079,8 @@ mod tests {
 
             // Other TransactionErrors should not be recorded
             results[0] = (Err(TransactionError::AccountNotFound), None);
-            let (res, retryable) = BankingStage::record_transactions(
-                bank.slot(),
-                transactions.iter(),
-                &results,
-                &recorder,
-            );
+            let (res, retryable) =
+                BankingStage::record_transactions(bank.slot(), &transactions, &results, &recorder);
             res.unwrap();
             assert!(retryable.is_empty());
             let (_bank, (entry, _tick_height)) = entry_receiver.recv().unwrap();
@@ -2028,7 +2091,7 @@ mod tests {
             // txs
             let (res, retryable) = BankingStage::record_transactions(
                 bank.slot() + 1,
-                transactions.iter(),
+                &transactions,
                 &results,
                 &recorder,
             );
@@ -2585,6 +2648,8 @@ mod tests {
             let transaction_status_service = TransactionStatusService::new(
                 transaction_status_receiver,
                 Arc::new(AtomicU64::default()),
+                true,
+                None,
                 blockstore.clone(),
                 &Arc::new(AtomicBool::new(false)),
             );
@@ -2709,6 +2774,7 @@ mod tests {
                 all_packets,
                 (0..num_conflicting_transactions).into_iter().collect(),
                 false,
+                0,
             )]
             .into_iter()
             .collect();
@@ -2782,7 +2848,7 @@ mod tests {
             let mut buffered_packets: UnprocessedPackets = packets_vec
                 .clone()
                 .into_iter()
-                .map(|single_packets| (single_packets, vec![0], false))
+                .map(|single_packets| (single_packets, vec![0], false, 0))
                 .collect();
 
             let (continue_sender, continue_receiver) = unbounded();
@@ -2826,7 +2892,7 @@ mod tests {
                         buffered_packets.len(),
                         packets_vec[interrupted_iteration + 1..].len()
                     );
-                    for ((remaining_unprocessed_packet, _, _forwarded), original_packet) in
+                    for ((remaining_unprocessed_packet, _, _forwarded, _), original_packet) in
                         buffered_packets
                             .iter()
                             .zip(&packets_vec[interrupted_iteration + 1..])
@@ -2867,7 +2933,7 @@ mod tests {
         // Create `Packets` with 1 unprocessed element
         let single_element_packets = Packets::new(vec![Packet::default()]);
         let mut unprocessed_packets: UnprocessedPackets =
-            vec![(single_element_packets.clone(), vec![0], false)]
+            vec![(single_element_packets.clone(), vec![0], false, 0)]
                 .into_iter()
                 .collect();
         // Set the limit to 2
@@ -2956,6 +3022,91 @@ mod tests {
         assert_eq!(newly_buffered_packets_count, 2);
     }
 
+    #[test]
+    fn test_push_unprocessed_priority() {
+        solana_logger::setup();
+        let priority_packets = |priorities: &[u64]| {
+            let txs: Vec<_> = priorities
+                .iter()
+                .map(|_| {
+                    system_transaction::transfer(
+                        &Keypair::new(),
+                        &Pubkey::new_unique(),
+                        1,
+                        Hash::default(),
+                    )
+                })
+                .collect();
+            let mut packets = to_packets_chunked(&txs, txs.len()).pop().unwrap();
+            for (packet, priority) in packets.packets.iter_mut().zip(priorities) {
+                packet.meta.priority = *priority;
+            }
+            packets
+        };
+        let duplicates = Arc::new(Mutex::new((
+            LruCache::new(DEFAULT_LRU_SIZE),
+            PacketHasher::default(),
+        )));
+        let mut unprocessed_packets = UnprocessedPackets::default();
+        let mut dropped_batches_count = 0;
+        let mut newly_buffered_packets_count = 0;
+        let banking_stage_stats = BankingStageStats::default();
+        let batch_limit = 2;
+        let mut push = |unprocessed_packets: &mut UnprocessedPackets, packets: Packets| {
+            let packet_indexes = (0..packets.packets.len()).collect();
+            BankingStage::push_unprocessed(
+                unprocessed_packets,
+                packets,
+                packet_indexes,
+                &mut dropped_batches_count,
+                &mut newly_buffered_packets_count,
+                batch_limit,
+                &duplicates,
+                &banking_stage_stats,
+            );
+        };
+        let priorities = |unprocessed_packets: &UnprocessedPackets| {
+            unprocessed_packets
+                .iter()
+                .map(|(_, _, _, priority)| *priority)
+                .collect::<Vec<_>>()
+        };
+
+        // Batches are buffered whole, in arrival order
+        push(&mut unprocessed_packets, priority_packets(&[10]));
+        push(&mut unprocessed_packets, priority_packets(&[20]));
+        assert_eq!(priorities(&unprocessed_packets), vec![10, 20]);
+
+        // A full buffer drops a new batch with a lower priority than all buffered batches
+        push(&mut unprocessed_packets, priority_packets(&[5]));
+        assert_eq!(priorities(&unprocessed_packets), vec![10, 20]);
+
+        // ...and otherwise evicts the lowest priority batch
+        push(&mut unprocessed_packets, priority_packets(&[15]));
+        assert_eq!(priorities(&unprocessed_packets), vec![20, 15]);
+
+        // A batch ranks by its best packet, and keeps all of its packets
+        let mut unprocessed_packets = UnprocessedPackets::default();
+        push(&mut unprocessed_packets, priority_packets(&[15]));
+        push(&mut unprocessed_packets, priority_packets(&[5, 30, 5]));
+        assert_eq!(priorities(&unprocessed_packets), vec![15, 30]);
+        assert_eq!(unprocessed_packets[1].1, vec![0, 1, 2]);
+        push(&mut unprocessed_packets, priority_packets(&[20]));
+        assert_eq!(priorities(&unprocessed_packets), vec![30, 20]);
+
+        // Packets are still consumed individually by priority across batches, and in arrival
+        // order among equal priorities
+        push(&mut unprocessed_packets, priority_packets(&[20, 5]));
+        assert_eq!(priorities(&unprocessed_packets), vec![30, 20]);
+        assert_eq!(unprocessed_packets[1].1, vec![0, 1]);
+        assert_eq!(
+            BankingStage::prioritized_packet_indexes(&unprocessed_packets),
+            vec![(0, vec![1]), (1, vec![0]), (0, vec![0, 2]), (1, vec![1])]
+        );
+        assert_eq!(dropped_batches_count, 4);
+        assert_eq!(newly_buffered_packets_count, 10);
+    }
+
     #[test]
     fn test_packet_message() {
         let keypair = Keypair::new();
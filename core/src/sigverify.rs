@@ -32,6 +32,7 @@ impl Default for TransactionSigVerifier {
 impl SigVerifier for TransactionSigVerifier {
     fn verify_batch(&self, mut batch: Vec<Packets>) -> Vec<Packets> {
         sigverify::ed25519_verify(&mut batch, &self.recycler, &self.recycler_out);
+        sigverify::set_packet_priorities(&mut batch);
         batch
     }
 }
@@ -0,0 +1,73 @@
+//! The `staked_nodes_updater_service` keeps the stake of each node's IP address, used to admit
+//! QUIC TPU connections, in step with the root bank and gossip.
+
+use solana_gossip::cluster_info::ClusterInfo;
+use solana_runtime::bank_forks::BankForks;
+use solana_streamer::quic::StakedNodes;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread::{self, sleep, Builder, JoinHandle},
+    time::{Duration, Instant},
+};
+
+const IP_TO_STAKE_REFRESH_DURATION: Duration = Duration::from_secs(5);
+const SLEEP_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct StakedNodesUpdaterService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl StakedNodesUpdaterService {
+    pub fn new(
+        exit: Arc<AtomicBool>,
+        cluster_info: Arc<ClusterInfo>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        staked_nodes: Arc<RwLock<StakedNodes>>,
+    ) -> Self {
+        let thread_hdl = Builder::new()
+            .name("sol-sn-updater".to_string())
+            .spawn(move || {
+                let mut last_refresh = None;
+                while !exit.load(Ordering::Relaxed) {
+                    if last_refresh.map_or(true, |last_refresh: Instant| {
+                        last_refresh.elapsed() >= IP_TO_STAKE_REFRESH_DURATION
+                    }) {
+                        let (ip_stake_map, total_stake) =
+                            Self::compute_ip_to_stake_map(&cluster_info, &bank_forks);
+                        let mut staked_nodes = staked_nodes.write().unwrap();
+                        staked_nodes.ip_stake_map = ip_stake_map;
+                        staked_nodes.total_stake = total_stake;
+                        last_refresh = Some(Instant::now());
+                    }
+                    sleep(SLEEP_INTERVAL);
+                }
+            })
+            .unwrap();
+
+        Self { thread_hdl }
+    }
+
+    fn compute_ip_to_stake_map(
+        cluster_info: &ClusterInfo,
+        bank_forks: &RwLock<BankForks>,
+    ) -> (HashMap<IpAddr, u64>, u64) {
+        let staked_nodes = bank_forks.read().unwrap().root_bank().staked_nodes();
+        let total_stake = staked_nodes.values().sum();
+        let mut ip_stake_map = HashMap::new();
+        for (contact_info, _) in cluster_info.all_peers() {
+            if let Some(stake) = staked_nodes.get(&contact_info.id) {
+                *ip_stake_map.entry(contact_info.tpu.ip()).or_default() += stake;
+            }
+        }
+        (ip_stake_map, total_stake)
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
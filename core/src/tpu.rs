@@ -13,6 +13,7 @@ use crate::{
     fetch_stage::FetchStage,
     sigverify::TransactionSigVerifier,
     sigverify_stage::SigVerifyStage,
+    staked_nodes_updater_service::StakedNodesUpdaterService,
 };
 use crossbeam_channel::unbounded;
 use solana_gossip::cluster_info::ClusterInfo;
@@ -26,6 +27,7 @@ use solana_runtime::{
     bank_forks::BankForks,
     vote_sender_types::{ReplayVoteReceiver, ReplayVoteSender},
 };
+use solana_streamer::quic::{spawn_server, QuicServerConfig, StakedNodes};
 use std::{
     net::UdpSocket,
     sync::{
@@ -44,6 +46,8 @@ pub struct Tpu {
     banking_stage: BankingStage,
     cluster_info_vote_listener: ClusterInfoVoteListener,
     broadcast_stage: BroadcastStage,
+    tpu_quic_t: Option<thread::JoinHandle<()>>,
+    staked_nodes_updater_service: Option<StakedNodesUpdaterService>,
 }
 
 impl Tpu {
@@ -55,6 +59,7 @@ impl Tpu {
         retransmit_slots_receiver: RetransmitSlotsReceiver,
         transactions_sockets: Vec<UdpSocket>,
         tpu_forwards_sockets: Vec<UdpSocket>,
+        transactions_quic_socket: UdpSocket,
         broadcast_sockets: Vec<UdpSocket>,
         subscriptions: &Arc<RpcSubscriptions>,
         transaction_status_sender: Option<TransactionStatusSender>,
@@ -70,6 +75,7 @@ impl Tpu {
         replay_vote_sender: ReplayVoteSender,
         bank_notification_sender: Option<BankNotificationSender>,
         tpu_coalesce_ms: u64,
+        tpu_use_quic: bool,
         cluster_confirmed_slot_sender: GossipDuplicateConfirmedSlotsSender,
         cost_model: &Arc<RwLock<CostModel>>,
     ) -> Self {
@@ -82,6 +88,37 @@ impl Tpu {
             poh_recorder,
             tpu_coalesce_ms,
         );
+
+        let (tpu_quic_t, staked_nodes_updater_service) = if tpu_use_quic {
+            let staked_nodes = Arc::new(RwLock::new(StakedNodes::default()));
+            match spawn_server(
+                transactions_quic_socket,
+                packet_sender,
+                exit.clone(),
+                staked_nodes.clone(),
+                QuicServerConfig::default(),
+            ) {
+                Ok(tpu_quic_t) => {
+                    let staked_nodes_updater_service = StakedNodesUpdaterService::new(
+                        exit.clone(),
+                        cluster_info.clone(),
+                        bank_forks.clone(),
+                        staked_nodes,
+                    );
+                    (Some(tpu_quic_t), Some(staked_nodes_updater_service))
+                }
+                Err(err) => {
+                    error!(
+                        "Failed to start the TPU QUIC server, serving UDP only: {}",
+                        err
+                    );
+                    (None, None)
+                }
+            }
+        } else {
+            (None, None)
+        };
+
         let (verified_sender, verified_receiver) = unbounded();
 
         let sigverify_stage = {
@@ -134,16 +171,24 @@ impl Tpu {
             banking_stage,
             cluster_info_vote_listener,
             broadcast_stage,
+            tpu_quic_t,
+            staked_nodes_updater_service,
         }
     }
 
     pub fn join(self) -> thread::Result<()> {
-        let results = vec![
+        let mut results = vec![
             self.fetch_stage.join(),
             self.sigverify_stage.join(),
             self.cluster_info_vote_listener.join(),
             self.banking_stage.join(),
         ];
+        if let Some(tpu_quic_t) = self.tpu_quic_t {
+            results.push(tpu_quic_t.join());
+        }
+        if let Some(staked_nodes_updater_service) = self.staked_nodes_updater_service {
+            results.push(staked_nodes_updater_service.join());
+        }
         let broadcast_result = self.broadcast_stage.join();
         for result in results {
             result?;
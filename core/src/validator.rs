@@ -18,6 +18,7 @@ use crate::{
 };
 use crossbeam_channel::{bounded, unbounded};
 use rand::{thread_rng, Rng};
+use solana_accountsdb_plugin_manager::accountsdb_plugin_service::AccountsDbPluginService;
 use solana_entry::poh::compute_hash_time_ns;
 use solana_gossip::{
     cluster_info::{
@@ -42,6 +43,7 @@ use solana_poh::{
     poh_service::{self, PohService},
 };
 use solana_rpc::{
+    account_history_service::AccountHistoryService,
     max_slots::MaxSlots,
     optimistically_confirmed_bank_tracker::{
         OptimisticallyConfirmedBank, OptimisticallyConfirmedBankTracker,
@@ -51,11 +53,14 @@ use solana_rpc::{
     rpc_pubsub_service::{PubSubConfig, PubSubService},
     rpc_service::JsonRpcService,
     rpc_subscriptions::RpcSubscriptions,
+    send_transaction_service,
+    transaction_notifier_interface::TransactionNotifier,
     transaction_status_service::TransactionStatusService,
 };
 use solana_runtime::{
-    accounts_db::AccountShrinkThreshold,
+    accounts_db::{AccountShrinkThreshold, AccountsDbConfig},
     accounts_index::AccountSecondaryIndexes,
+    accounts_update_notifier_interface::AccountsUpdateNotifier,
     bank::Bank,
     bank_forks::BankForks,
     commitment::BlockCommitmentCache,
@@ -99,6 +104,7 @@ pub struct ValidatorConfig {
     pub voting_disabled: bool,
     pub account_paths: Vec<PathBuf>,
     pub account_shrink_paths: Option<Vec<PathBuf>>,
+    pub accountsdb_plugin_config_files: Option<Vec<PathBuf>>,
     pub rpc_config: JsonRpcConfig,
     pub rpc_addrs: Option<(SocketAddr, SocketAddr)>, // (JsonRpc, JsonRpcPubSub)
     pub pubsub_config: PubSubConfig,
@@ -130,8 +136,7 @@ pub struct ValidatorConfig {
     pub contact_debug_interval: u64,
     pub contact_save_interval: u64,
     pub bpf_jit: bool,
-    pub send_transaction_retry_ms: u64,
-    pub send_transaction_leader_forward_count: u64,
+    pub send_transaction_service_config: send_transaction_service::Config,
     pub no_poh_speed_test: bool,
     pub poh_pinned_cpu_core: usize,
     pub poh_hashes_per_batch: u64,
@@ -141,9 +146,12 @@ pub struct ValidatorConfig {
     pub accounts_db_test_hash_calculation: bool,
     pub accounts_db_use_index_hash_calculation: bool,
     pub tpu_coalesce_ms: u64,
+    /// Also receive transactions on the QUIC TPU endpoint
+    pub tpu_use_quic: bool,
     pub validator_exit: Arc<RwLock<Exit>>,
     pub no_wait_for_vote_to_start_leader: bool,
     pub accounts_shrink_ratio: AccountShrinkThreshold,
+    pub accounts_db_config: Option<AccountsDbConfig>,
 }
 
 impl Default for ValidatorConfig {
@@ -157,6 +165,7 @@ impl Default for ValidatorConfig {
             max_ledger_shreds: None,
             account_paths: Vec::new(),
             account_shrink_paths: None,
+            accountsdb_plugin_config_files: None,
             rpc_config: JsonRpcConfig::default(),
             rpc_addrs: None,
             pubsub_config: PubSubConfig::default(),
@@ -187,8 +196,7 @@ impl Default for ValidatorConfig {
             contact_debug_interval: DEFAULT_CONTACT_DEBUG_INTERVAL_MILLIS,
             contact_save_interval: DEFAULT_CONTACT_SAVE_INTERVAL_MILLIS,
             bpf_jit: false,
-            send_transaction_retry_ms: 2000,
-            send_transaction_leader_forward_count: 2,
+            send_transaction_service_config: send_transaction_service::Config::default(),
             no_poh_speed_test: true,
             poh_pinned_cpu_core: poh_service::DEFAULT_PINNED_CPU_CORE,
             poh_hashes_per_batch: poh_service::DEFAULT_HASHES_PER_BATCH,
@@ -198,9 +206,11 @@ impl Default for ValidatorConfig {
             accounts_db_test_hash_calculation: false,
             accounts_db_use_index_hash_calculation: true,
             tpu_coalesce_ms: DEFAULT_TPU_COALESCE_MS,
+            tpu_use_quic: false,
             validator_exit: Arc::new(RwLock::new(Exit::default())),
             no_wait_for_vote_to_start_leader: true,
             accounts_shrink_ratio: AccountShrinkThreshold::default(),
+            accounts_db_config: None,
         }
     }
 }
@@ -240,6 +250,8 @@ struct TransactionHistoryServices {
     rewards_recorder_service: Option<RewardsRecorderService>,
     cache_block_meta_sender: Option<CacheBlockMetaSender>,
     cache_block_meta_service: Option<CacheBlockMetaService>,
+    account_history_service: Option<AccountHistoryService>,
+    max_complete_account_history_slot: Arc<AtomicU64>,
 }
 
 pub struct Validator {
@@ -251,6 +263,7 @@ pub struct Validator {
     transaction_status_service: Option<TransactionStatusService>,
     rewards_recorder_service: Option<RewardsRecorderService>,
     cache_block_meta_service: Option<CacheBlockMetaService>,
+    account_history_service: Option<AccountHistoryService>,
     sample_performance_service: Option<SamplePerformanceService>,
     gossip_service: GossipService,
     serve_repair_service: ServeRepairService,
@@ -262,6 +275,7 @@ pub struct Validator {
     tvu: Tvu,
     ip_echo_server: Option<solana_net_utils::IpEchoServer>,
     pub cluster_info: Arc<ClusterInfo>,
+    accountsdb_plugin_service: Option<AccountsDbPluginService>,
 }
 
 // in the distant future, get rid of ::new()/exit() and use Result properly...
@@ -351,6 +365,14 @@ impl Validator {
                 cleanup_accounts_path(accounts_path);
             }
         }
+        // Cold storages are only ever read by the process that compressed them
+        if let Some(cold_storage) = config
+            .accounts_db_config
+            .as_ref()
+            .and_then(|config| config.cold_storage.as_ref())
+        {
+            cleanup_accounts_path(&cold_storage.path);
+        }
         start.stop();
         info!("done. {}", start);
 
@@ -364,6 +386,27 @@ impl Validator {
                 .register_exit(Box::new(move || exit.store(true, Ordering::Relaxed)));
         }
 
+        let mut bank_notification_senders = Vec::new();
+
+        let accountsdb_plugin_service =
+            if let Some(accountsdb_plugin_config_files) = &config.accountsdb_plugin_config_files {
+                let (confirmed_bank_sender, confirmed_bank_receiver) = unbounded();
+                bank_notification_senders.push(confirmed_bank_sender);
+                let result = AccountsDbPluginService::new(
+                    confirmed_bank_receiver,
+                    accountsdb_plugin_config_files,
+                );
+                match result {
+                    Ok(accountsdb_plugin_service) => Some(accountsdb_plugin_service),
+                    Err(err) => {
+                        error!("Failed to load the AccountsDb plugin: {:?}", err);
+                        abort();
+                    }
+                }
+            } else {
+                None
+            };
+
         let (replay_vote_sender, replay_vote_receiver) = unbounded();
         let (
             genesis_config,
@@ -381,6 +424,8 @@ impl Validator {
                 rewards_recorder_service,
                 cache_block_meta_sender,
                 cache_block_meta_service,
+                account_history_service,
+                max_complete_account_history_slot,
             },
             tower,
         ) = new_banks_from_ledger(
@@ -393,6 +438,12 @@ impl Validator {
             config.enforce_ulimit_nofile,
             &start_progress,
             config.no_poh_speed_test,
+            accountsdb_plugin_service
+                .as_ref()
+                .and_then(|plugin_service| plugin_service.get_accounts_update_notifier()),
+            accountsdb_plugin_service
+                .as_ref()
+                .and_then(|plugin_service| plugin_service.get_transaction_notifier()),
         );
 
         *start_progress.write().unwrap() = ValidatorStartProgress::StartingServices;
@@ -454,12 +505,14 @@ impl Validator {
         let optimistically_confirmed_bank =
             OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
 
-        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_with_vote_subscription(
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_with_config(
             &exit,
+            max_complete_transaction_status_slot.clone(),
+            blockstore.clone(),
             bank_forks.clone(),
             block_commitment_cache.clone(),
             optimistically_confirmed_bank.clone(),
-            config.pubsub_config.enable_vote_subscription,
+            &config.pubsub_config,
         ));
 
         let max_slots = Arc::new(MaxSlots::default());
@@ -543,11 +596,11 @@ impl Validator {
                     config.trusted_validators.clone(),
                     rpc_override_health_check.clone(),
                     optimistically_confirmed_bank.clone(),
-                    config.send_transaction_retry_ms,
-                    config.send_transaction_leader_forward_count,
+                    config.send_transaction_service_config.clone(),
                     max_slots.clone(),
                     leader_schedule_cache.clone(),
                     max_complete_transaction_status_slot,
+                    max_complete_account_history_slot,
                 )),
                 if config.rpc_config.minimal_api {
                     None
@@ -565,11 +618,13 @@ impl Validator {
                     bank_forks.clone(),
                     optimistically_confirmed_bank,
                     rpc_subscriptions.clone(),
+                    bank_notification_senders.pop(),
                 )),
                 Some(bank_notification_sender),
             )
         } else {
-            (None, None, None, None)
+            // Without RPC, replay notifies the plugin service directly
+            (None, None, None, bank_notification_senders.pop())
         };
 
         if config.dev_halt_at_slot.is_some() {
@@ -614,6 +669,10 @@ impl Validator {
                 if is_snapshot_config_invalid(
                     snapshot_config.snapshot_interval_slots,
                     config.accounts_hash_interval_slots,
+                ) || is_incremental_snapshot_config_invalid(
+                    snapshot_config.incremental_snapshot_archive_interval_slots,
+                    snapshot_config.snapshot_interval_slots,
+                    config.accounts_hash_interval_slots,
                 ) {
                     error!("Snapshot config is invalid");
                 }
@@ -682,6 +741,19 @@ impl Validator {
         let rpc_completed_slots_service =
             RpcCompletedSlotsService::spawn(completed_slots_receiver, rpc_subscriptions.clone());
 
+        // Incremental snapshots are based on the full snapshot the validator booted from, if any
+        let last_full_snapshot_slot = config
+            .snapshot_config
+            .as_ref()
+            .filter(|snapshot_config| {
+                snapshot_config.incremental_snapshot_archive_interval_slots != Slot::MAX
+            })
+            .and_then(|snapshot_config| {
+                solana_runtime::snapshot_utils::get_highest_full_snapshot_archive_slot(
+                    &snapshot_config.snapshot_package_output_path,
+                )
+            });
+
         let tvu = Tvu::new(
             vote_account,
             authorized_voter_keypairs,
@@ -751,6 +823,7 @@ impl Validator {
             },
             &max_slots,
             &cost_model,
+            last_full_snapshot_slot,
         );
 
         let tpu = Tpu::new(
@@ -760,6 +833,7 @@ impl Validator {
             retransmit_slots_receiver,
             node.sockets.tpu,
             node.sockets.tpu_forwards,
+            node.sockets.tpu_quic,
             node.sockets.broadcast,
             &rpc_subscriptions,
             transaction_status_sender,
@@ -775,6 +849,7 @@ impl Validator {
             replay_vote_sender,
             bank_notification_sender,
             config.tpu_coalesce_ms,
+            config.tpu_use_quic,
             cluster_confirmed_slot_sender,
             &cost_model,
         );
@@ -791,6 +866,7 @@ impl Validator {
             transaction_status_service,
             rewards_recorder_service,
             cache_block_meta_service,
+            account_history_service,
             sample_performance_service,
             snapshot_packager_service,
             completed_data_sets_service,
@@ -801,6 +877,7 @@ impl Validator {
             ip_echo_server,
             validator_exit: config.validator_exit.clone(),
             cluster_info,
+            accountsdb_plugin_service,
         }
     }
 
@@ -883,6 +960,12 @@ impl Validator {
                 .expect("cache_block_meta_service");
         }
 
+        if let Some(account_history_service) = self.account_history_service {
+            account_history_service
+                .join()
+                .expect("account_history_service");
+        }
+
         if let Some(sample_performance_service) = self.sample_performance_service {
             sample_performance_service
                 .join()
@@ -905,6 +988,12 @@ impl Validator {
         if let Some(ip_echo_server) = self.ip_echo_server {
             ip_echo_server.shutdown_background();
         }
+
+        if let Some(accountsdb_plugin_service) = self.accountsdb_plugin_service {
+            accountsdb_plugin_service
+                .join()
+                .expect("accountsdb_plugin_service");
+        }
     }
 }
 
@@ -1048,6 +1137,8 @@ fn new_banks_from_ledger(
     enforce_ulimit_nofile: bool,
     start_progress: &Arc<RwLock<ValidatorStartProgress>>,
     no_poh_speed_test: bool,
+    accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    transaction_notifier: Option<TransactionNotifier>,
 ) -> (
     GenesisConfig,
     BankForks,
@@ -1125,6 +1216,23 @@ fn new_banks_from_ledger(
         None
     };
 
+    let max_complete_account_history_slot = Arc::new(AtomicU64::default());
+    let (account_history_service, accounts_update_notifier) =
+        if config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_account_history {
+            let (account_history_service, accounts_update_notifier) = AccountHistoryService::new(
+                blockstore.clone(),
+                accounts_update_notifier,
+                max_complete_account_history_slot.clone(),
+                exit,
+            );
+            (
+                Some(account_history_service),
+                Some(accounts_update_notifier),
+            )
+        } else {
+            (None, accounts_update_notifier)
+        };
+
     let process_options = blockstore_processor::ProcessOptions {
         bpf_jit: config.bpf_jit,
         poh_verify,
@@ -1135,19 +1243,28 @@ fn new_banks_from_ledger(
         account_indexes: config.account_indexes.clone(),
         accounts_db_caching_enabled: config.accounts_db_caching_enabled,
         shrink_ratio: config.accounts_shrink_ratio,
+        accounts_update_notifier,
+        accounts_db_config: config.accounts_db_config.clone(),
         ..blockstore_processor::ProcessOptions::default()
     };
 
-    let transaction_history_services =
-        if config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history {
+    let enable_rpc_transaction_history =
+        config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history;
+    let mut transaction_history_services =
+        if enable_rpc_transaction_history || transaction_notifier.is_some() {
             initialize_rpc_transaction_history_services(
                 blockstore.clone(),
                 exit,
+                enable_rpc_transaction_history,
                 config.rpc_config.enable_cpi_and_log_storage,
+                transaction_notifier,
             )
         } else {
             TransactionHistoryServices::default()
         };
+    transaction_history_services.account_history_service = account_history_service;
+    transaction_history_services.max_complete_account_history_slot =
+        max_complete_account_history_slot;
 
     let (mut bank_forks, mut leader_schedule_cache, snapshot_hash) = bank_forks_utils::load(
         &genesis_config,
@@ -1324,7 +1441,9 @@ fn backup_and_clear_blockstore(ledger_path: &Path, start_slot: Slot, shred_versi
 fn initialize_rpc_transaction_history_services(
     blockstore: Arc<Blockstore>,
     exit: &Arc<AtomicBool>,
+    enable_rpc_transaction_history: bool,
     enable_cpi_and_log_storage: bool,
+    transaction_notifier: Option<TransactionNotifier>,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
     let (transaction_status_sender, transaction_status_receiver) = unbounded();
@@ -1335,6 +1454,8 @@ fn initialize_rpc_transaction_history_services(
     let transaction_status_service = Some(TransactionStatusService::new(
         transaction_status_receiver,
         max_complete_transaction_status_slot.clone(),
+        enable_rpc_transaction_history,
+        transaction_notifier,
         blockstore.clone(),
         exit,
     ));
@@ -1362,6 +1483,8 @@ fn initialize_rpc_transaction_history_services(
         rewards_recorder_service,
         cache_block_meta_sender,
         cache_block_meta_service,
+        account_history_service: None,
+        max_complete_account_history_slot: Arc::default(),
     }
 }
 
@@ -1587,6 +1710,22 @@ pub fn is_snapshot_config_invalid(
             || snapshot_interval_slots % accounts_hash_interval_slots != 0)
 }
 
+/// Incremental snapshots must be requested on accounts hash intervals, and must fall between full
+/// snapshots.  `Slot::MAX` disables incremental snapshots.
+pub fn is_incremental_snapshot_config_invalid(
+    incremental_snapshot_archive_interval_slots: u64,
+    snapshot_interval_slots: u64,
+    accounts_hash_interval_slots: u64,
+) -> bool {
+    incremental_snapshot_archive_interval_slots != Slot::MAX
+        && (incremental_snapshot_archive_interval_slots == 0
+            || is_snapshot_config_invalid(
+                incremental_snapshot_archive_interval_slots,
+                accounts_hash_interval_slots,
+            )
+            || incremental_snapshot_archive_interval_slots >= snapshot_interval_slots)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1796,6 +1935,15 @@ mod tests {
         assert!(!is_snapshot_config_invalid(5, 5));
     }
 
+    #[test]
+    fn test_interval_check_incremental() {
+        assert!(!is_incremental_snapshot_config_invalid(Slot::MAX, 500, 100));
+        assert!(!is_incremental_snapshot_config_invalid(100, 500, 100));
+        assert!(is_incremental_snapshot_config_invalid(0, 500, 100));
+        assert!(is_incremental_snapshot_config_invalid(150, 500, 100));
+        assert!(is_incremental_snapshot_config_invalid(500, 500, 100));
+    }
+
     #[test]
     #[should_panic]
     fn test_poh_speed() {
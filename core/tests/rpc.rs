@@ -110,6 +110,8 @@ fn test_rpc_send_tx() {
         encoding: Some(UiAccountEncoding::Base64),
         commitment: None,
         data_slice: None,
+        min_context_slot: None,
+        at_slot: None,
     };
     let req = json_req!(
         "getAccountInfo",
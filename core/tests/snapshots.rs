@@ -29,6 +29,11 @@ macro_rules! DEFINE_SNAPSHOT_VERSION_PARAMETERIZED_TEST_FUNCTIONS {
             fn test_slots_to_snapshot() {
                 run_test_slots_to_snapshot(SNAPSHOT_VERSION, CLUSTER_TYPE)
             }
+
+            #[test]
+            fn test_bank_forks_incremental_snapshot() {
+                run_test_bank_forks_incremental_snapshot(SNAPSHOT_VERSION, CLUSTER_TYPE)
+            }
         }
     };
 }
@@ -49,6 +54,7 @@ mod tests {
         bank_forks::BankForks,
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
         snapshot_config::SnapshotConfig,
+        snapshot_package::SnapshotType,
         snapshot_utils::{
             self, ArchiveFormat, SnapshotVersion, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         },
@@ -94,7 +100,9 @@ mod tests {
         fn new(
             snapshot_version: SnapshotVersion,
             cluster_type: ClusterType,
-            snapshot_interval_slots: u64,
+            accounts_hash_interval_slots: Slot,
+            snapshot_interval_slots: Slot,
+            incremental_snapshot_archive_interval_slots: Slot,
         ) -> SnapshotTestConfig {
             let accounts_dir = TempDir::new().unwrap();
             let snapshot_dir = TempDir::new().unwrap();
@@ -111,13 +119,15 @@ mod tests {
                 false,
                 accounts_db::AccountShrinkThreshold::default(),
                 false,
+                None,
             );
             bank0.freeze();
             let mut bank_forks = BankForks::new(bank0);
-            bank_forks.accounts_hash_interval_slots = snapshot_interval_slots;
+            bank_forks.accounts_hash_interval_slots = accounts_hash_interval_slots;
 
             let snapshot_config = SnapshotConfig {
                 snapshot_interval_slots,
+                incremental_snapshot_archive_interval_slots,
                 snapshot_package_output_path: PathBuf::from(snapshot_output_path.path()),
                 snapshot_path: PathBuf::from(snapshot_dir.path()),
                 archive_format: ArchiveFormat::TarBzip2,
@@ -176,6 +186,7 @@ mod tests {
             accounts_db::AccountShrinkThreshold::default(),
             check_hash_calculation,
             false,
+            None,
         )
         .unwrap();
 
@@ -207,7 +218,8 @@ mod tests {
     {
         solana_logger::setup();
         // Set up snapshotting config
-        let mut snapshot_test_config = SnapshotTestConfig::new(snapshot_version, cluster_type, 1);
+        let mut snapshot_test_config =
+            SnapshotTestConfig::new(snapshot_version, cluster_type, 1, 1, Slot::MAX);
 
         let bank_forks = &mut snapshot_test_config.bank_forks;
         let mint_keypair = &snapshot_test_config.genesis_config_info.mint_keypair;
@@ -231,7 +243,8 @@ mod tests {
                 // set_root should send a snapshot request
                 bank_forks.set_root(bank.slot(), &request_sender, None);
                 bank.update_accounts_hash();
-                snapshot_request_handler.handle_snapshot_requests(false, false, false, 0);
+                snapshot_request_handler
+                    .handle_snapshot_requests(false, false, false, 0, &mut None);
             }
         }
 
@@ -316,7 +329,8 @@ mod tests {
         solana_logger::setup();
 
         // Set up snapshotting config
-        let mut snapshot_test_config = SnapshotTestConfig::new(snapshot_version, cluster_type, 1);
+        let mut snapshot_test_config =
+            SnapshotTestConfig::new(snapshot_version, cluster_type, 1, 1, Slot::MAX);
 
         let bank_forks = &mut snapshot_test_config.bank_forks;
         let snapshots_dir = &snapshot_test_config.snapshot_dir;
@@ -381,6 +395,7 @@ mod tests {
                 snapshot_config.snapshot_version,
                 &snapshot_config.archive_format,
                 None,
+                Some(SnapshotType::FullSnapshot),
             )
             .unwrap();
 
@@ -542,6 +557,8 @@ mod tests {
                 snapshot_version,
                 cluster_type,
                 (*add_root_interval * num_set_roots * 2) as u64,
+                (*add_root_interval * num_set_roots * 2) as u64,
+                Slot::MAX,
             );
             let mut current_bank = snapshot_test_config.bank_forks[0].clone();
             let request_sender = AbsRequestSender::new(Some(snapshot_sender));
@@ -610,4 +627,135 @@ mod tests {
             );
         }
     }
+
+    /// Take full and incremental snapshots while banks are rooted, then restore from the latest
+    /// full+incremental pair and compare the restored bank to the original.  An account that is
+    /// drained to zero lamports after the last full snapshot must stay drained in the restored
+    /// bank.
+    fn run_test_bank_forks_incremental_snapshot(
+        snapshot_version: SnapshotVersion,
+        cluster_type: ClusterType,
+    ) {
+        solana_logger::setup();
+
+        const INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS: Slot = 4;
+        const FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS: Slot =
+            INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS * 3;
+        const LAST_SLOT: Slot = FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS * 2
+            + INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS * 2;
+        const DRAIN_SLOT: Slot = FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS * 2 + 1;
+
+        let mut snapshot_test_config = SnapshotTestConfig::new(
+            snapshot_version,
+            cluster_type,
+            INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
+            FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
+            INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
+        );
+
+        let bank_forks = &mut snapshot_test_config.bank_forks;
+        let mint_keypair = &snapshot_test_config.genesis_config_info.mint_keypair;
+        let drained_keypair = Keypair::new();
+
+        let (s, snapshot_request_receiver) = unbounded();
+        let (accounts_package_sender, accounts_package_receiver) = channel();
+        let request_sender = AbsRequestSender::new(Some(s));
+        let snapshot_request_handler = SnapshotRequestHandler {
+            snapshot_config: snapshot_test_config.snapshot_config.clone(),
+            snapshot_request_receiver,
+            accounts_package_sender,
+        };
+
+        let mut last_full_snapshot_slot = None;
+        for slot in 0..LAST_SLOT {
+            let mut bank = Bank::new_from_parent(&bank_forks[slot], &Pubkey::default(), slot + 1);
+            let blockhash = bank.parent().unwrap().last_blockhash();
+            let tx = if bank.slot() == DRAIN_SLOT {
+                system_transaction::transfer(
+                    &drained_keypair,
+                    &mint_keypair.pubkey(),
+                    bank.get_balance(&drained_keypair.pubkey()),
+                    blockhash,
+                )
+            } else {
+                system_transaction::transfer(mint_keypair, &drained_keypair.pubkey(), 1, blockhash)
+            };
+            assert_eq!(bank.process_transaction(&tx), Ok(()));
+            goto_end_of_slot(&mut bank);
+            let bank = bank_forks.insert(bank);
+
+            // set_root should send a snapshot request
+            bank_forks.set_root(bank.slot(), &request_sender, None);
+            bank.update_accounts_hash();
+            snapshot_request_handler.handle_snapshot_requests(
+                false,
+                false,
+                false,
+                0,
+                &mut last_full_snapshot_slot,
+            );
+
+            for accounts_package in accounts_package_receiver.try_iter() {
+                let snapshot_type = match accounts_package.snapshot_type {
+                    Some(snapshot_type) => snapshot_type,
+                    None => continue,
+                };
+                let accounts_package = snapshot_utils::process_accounts_package_pre(
+                    accounts_package,
+                    None,
+                    snapshot_type.incremental_snapshot_base_slot(),
+                );
+                snapshot_utils::archive_snapshot_package(
+                    &accounts_package,
+                    DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                )
+                .unwrap();
+            }
+        }
+        assert_eq!(
+            last_full_snapshot_slot,
+            Some(FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS * 2)
+        );
+
+        let snapshot_config = &snapshot_test_config.snapshot_config;
+        let full_snapshot_archive_info = snapshot_utils::get_highest_full_snapshot_archive_info(
+            &snapshot_config.snapshot_package_output_path,
+        )
+        .unwrap();
+        assert_eq!(
+            *full_snapshot_archive_info.slot(),
+            FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS * 2
+        );
+        let incremental_snapshot_archive_info =
+            snapshot_utils::get_highest_incremental_snapshot_archive_info(
+                &snapshot_config.snapshot_package_output_path,
+                *full_snapshot_archive_info.slot(),
+            )
+            .unwrap();
+        assert_eq!(*incremental_snapshot_archive_info.slot(), LAST_SLOT);
+
+        let (deserialized_bank, _timing) = snapshot_utils::bank_from_snapshot_archives(
+            &[snapshot_test_config.accounts_dir.path().to_path_buf()],
+            &[],
+            &snapshot_config.snapshot_path,
+            full_snapshot_archive_info.path(),
+            Some(incremental_snapshot_archive_info.path()),
+            ArchiveFormat::TarBzip2,
+            &snapshot_test_config.genesis_config_info.genesis_config,
+            None,
+            None,
+            AccountSecondaryIndexes::default(),
+            false,
+            None,
+            accounts_db::AccountShrinkThreshold::default(),
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let bank = snapshot_test_config.bank_forks[LAST_SLOT].clone();
+        assert_eq!(*bank, deserialized_bank);
+        assert_eq!(deserialized_bank.get_balance(&drained_keypair.pubkey()), 0);
+    }
 }
@@ -22,9 +22,8 @@ use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::sanitized_transaction::SanitizedTransaction;
 use solana_sdk::timing;
 use solana_sdk::transaction::{Result, Transaction, TransactionError};
-use std::borrow::Cow;
+use solana_sdk::versioned_transaction::VersionedTransaction;
 use std::cell::RefCell;
-use std::convert::TryFrom;
 use std::ffi::OsStr;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Once;
@@ -118,7 +117,7 @@ pub struct Entry {
     /// An unordered list of transactions that were observed before the Entry ID was
     /// generated. They may have been observed before a previous Entry ID but were
     /// pushed back into this list to ensure deterministic interpretation of the ledger.
-    pub transactions: Vec<Transaction>,
+    pub transactions: Vec<VersionedTransaction>,
 }
 
 /// Typed entry to distinguish between transaction and tick entries
@@ -127,9 +126,13 @@ pub enum EntryType<'a> {
     Tick(Hash),
 }
 
-impl<'a> TryFrom<&'a Entry> for EntryType<'a> {
-    type Error = TransactionError;
-    fn try_from(entry: &'a Entry) -> Result<Self> {
+impl EntryType<'static> {
+    /// Sanitize the transactions of an entry with `sanitize`, which is
+    /// responsible for resolving any address table lookups.
+    pub fn try_from_entry<F>(entry: &Entry, sanitize: F) -> Result<Self>
+    where
+        F: Fn(VersionedTransaction, Hash) -> Result<SanitizedTransaction<'static>>,
+    {
         if entry.transactions.is_empty() {
             Ok(EntryType::Tick(entry.hash))
         } else {
@@ -137,7 +140,10 @@ impl<'a> TryFrom<&'a Entry> for EntryType<'a> {
                 entry
                     .transactions
                     .iter()
-                    .map(SanitizedTransaction::try_from)
+                    .map(|tx| {
+                        let message_hash = tx.message.hash();
+                        sanitize(tx.clone(), message_hash)
+                    })
                     .collect::<Result<_>>()?,
             ))
         }
@@ -146,7 +152,18 @@ impl<'a> TryFrom<&'a Entry> for EntryType<'a> {
 
 impl Entry {
     /// Creates the next Entry `num_hashes` after `start_hash`.
-    pub fn new(prev_hash: &Hash, mut num_hashes: u64, transactions: Vec<Transaction>) -> Self {
+    pub fn new(prev_hash: &Hash, num_hashes: u64, transactions: Vec<Transaction>) -> Self {
+        let transactions = transactions.into_iter().map(Into::into).collect();
+        Self::new_versioned(prev_hash, num_hashes, transactions)
+    }
+
+    /// Creates the next Entry `num_hashes` after `start_hash` from versioned
+    /// transactions.
+    pub fn new_versioned(
+        prev_hash: &Hash,
+        mut num_hashes: u64,
+        transactions: Vec<VersionedTransaction>,
+    ) -> Self {
         // If you passed in transactions, but passed in num_hashes == 0, then
         // next_hash will generate the next hash and set num_hashes == 1
         if num_hashes == 0 && !transactions.is_empty() {
@@ -201,7 +218,7 @@ impl Entry {
     }
 }
 
-pub fn hash_transactions(transactions: &[Transaction]) -> Hash {
+pub fn hash_transactions(transactions: &[VersionedTransaction]) -> Hash {
     // a hash of a slice of transactions only needs to hash the signatures
     let signatures: Vec<_> = transactions
         .iter()
@@ -219,7 +236,11 @@ pub fn hash_transactions(transactions: &[Transaction]) -> Hash {
 /// a signature, the final hash will be a hash of both the previous ID and
 /// the signature.  If num_hashes is zero and there's no transaction data,
 ///  start_hash is returned.
-pub fn next_hash(start_hash: &Hash, num_hashes: u64, transactions: &[Transaction]) -> Hash {
+pub fn next_hash(
+    start_hash: &Hash,
+    num_hashes: u64,
+    transactions: &[VersionedTransaction],
+) -> Hash {
     if num_hashes == 0 && transactions.is_empty() {
         return *start_hash;
     }
@@ -358,12 +379,17 @@ pub trait EntrySlice {
     fn verify_tick_hash_count(&self, tick_hash_count: &mut u64, hashes_per_tick: u64) -> bool;
     /// Counts tick entries
     fn tick_count(&self) -> u64;
-    fn verify_and_hash_transactions(
+    /// Verifies and sanitizes the transactions of each entry. `sanitize` is responsible for
+    /// resolving any address table lookups of versioned transactions.
+    fn verify_and_hash_transactions<F>(
         &self,
         skip_verification: bool,
         libsecp256k1_0_5_upgrade_enabled: bool,
         verify_tx_signatures_len: bool,
-    ) -> Result<Vec<EntryType<'_>>>;
+        sanitize: F,
+    ) -> Result<Vec<EntryType<'static>>>
+    where
+        F: Fn(VersionedTransaction, Hash) -> Result<SanitizedTransaction<'static>> + Sync;
 }
 
 impl EntrySlice for [Entry] {
@@ -514,29 +540,36 @@ impl EntrySlice for [Entry] {
         }
     }
 
-    fn verify_and_hash_transactions<'a>(
-        &'a self,
+    fn verify_and_hash_transactions<F>(
+        &self,
         skip_verification: bool,
         libsecp256k1_0_5_upgrade_enabled: bool,
         verify_tx_signatures_len: bool,
-    ) -> Result<Vec<EntryType<'a>>> {
-        let verify_and_hash = |tx: &'a Transaction| -> Result<SanitizedTransaction<'a>> {
+        sanitize: F,
+    ) -> Result<Vec<EntryType<'static>>>
+    where
+        F: Fn(VersionedTransaction, Hash) -> Result<SanitizedTransaction<'static>> + Sync,
+    {
+        let verify_and_hash = |tx: &VersionedTransaction| -> Result<SanitizedTransaction> {
             let message_hash = if !skip_verification {
                 let size =
                     bincode::serialized_size(tx).map_err(|_| TransactionError::SanitizeFailure)?;
                 if size > PACKET_DATA_SIZE as u64 {
                     return Err(TransactionError::SanitizeFailure);
                 }
-                tx.verify_precompiles(libsecp256k1_0_5_upgrade_enabled)?;
                 if verify_tx_signatures_len && !tx.verify_signatures_len() {
                     return Err(TransactionError::SanitizeFailure);
                 }
                 tx.verify_and_hash_message()?
             } else {
-                tx.message().hash()
+                tx.message.hash()
             };
 
-            SanitizedTransaction::try_create(Cow::Borrowed(tx), message_hash)
+            let sanitized_tx = sanitize(tx.clone(), message_hash)?;
+            if !skip_verification {
+                sanitized_tx.verify_precompiles(libsecp256k1_0_5_upgrade_enabled)?;
+            }
+            Ok(sanitized_tx)
         };
 
         PAR_THREAD_POOL.with(|thread_pool| {
@@ -718,6 +751,7 @@ pub fn create_random_ticks(num_ticks: u64, max_hashes_per_tick: u64, mut hash: H
 /// Creates the next Tick or Transaction Entry `num_hashes` after `start_hash`.
 pub fn next_entry(prev_hash: &Hash, num_hashes: u64, transactions: Vec<Transaction>) -> Entry {
     assert!(num_hashes > 0 || transactions.is_empty());
+    let transactions = transactions.into_iter().map(Into::into).collect::<Vec<_>>();
     Entry {
         num_hashes,
         hash: next_hash(prev_hash, num_hashes, &transactions),
@@ -731,14 +765,27 @@ mod tests {
     use crate::entry::Entry;
     use solana_sdk::{
         hash::{hash, new_rand as hash_new_rand, Hash},
-        message::Message,
+        instruction::CompiledInstruction,
+        message::{
+            v0::{self, LoadedAddresses, MessageAddressTableLookup},
+            Message, MessageHeader, VersionedMessage,
+        },
         packet::PACKET_DATA_SIZE,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
-        system_instruction, system_transaction,
+        system_instruction, system_program, system_transaction,
         transaction::Transaction,
     };
 
+    fn sanitize_legacy(
+        tx: VersionedTransaction,
+        message_hash: Hash,
+    ) -> Result<SanitizedTransaction<'static>> {
+        SanitizedTransaction::try_create_versioned(tx, message_hash, |_| {
+            Err(TransactionError::UnsupportedVersion)
+        })
+    }
+
     #[test]
     fn test_entry_verify() {
         let zero = Hash::default();
@@ -761,8 +808,8 @@ mod tests {
         assert!(e0.verify(&zero));
 
         // Next, swap two transactions and ensure verification fails.
-        e0.transactions[0] = tx1; // <-- attack
-        e0.transactions[1] = tx0;
+        e0.transactions[0] = tx1.into(); // <-- attack
+        e0.transactions[1] = tx0.into();
         assert!(!e0.verify(&zero));
     }
 
@@ -815,7 +862,7 @@ mod tests {
         let tx0 = system_transaction::transfer(&keypair, &Pubkey::new_unique(), 42, zero);
         let entry0 = next_entry(&zero, 1, vec![tx0.clone()]);
         assert_eq!(entry0.num_hashes, 1);
-        assert_eq!(entry0.hash, next_hash(&zero, 1, &[tx0]));
+        assert_eq!(entry0.hash, next_hash(&zero, 1, &[tx0.into()]));
     }
 
     #[test]
@@ -928,7 +975,7 @@ mod tests {
             let entries = vec![next_entry(&recent_blockhash, 1, vec![tx])];
             assert_eq!(
                 entries[..]
-                    .verify_and_hash_transactions(false, false, false)
+                    .verify_and_hash_transactions(false, false, false, sanitize_legacy)
                     .err(),
                 Some(TransactionError::SanitizeFailure),
             );
@@ -938,11 +985,11 @@ mod tests {
             let tx = make_transaction(TestCase::AddSignature);
             let entries = vec![next_entry(&recent_blockhash, 1, vec![tx])];
             assert!(entries[..]
-                .verify_and_hash_transactions(false, false, false)
+                .verify_and_hash_transactions(false, false, false, sanitize_legacy)
                 .is_ok());
             assert_eq!(
                 entries[..]
-                    .verify_and_hash_transactions(false, false, true)
+                    .verify_and_hash_transactions(false, false, true, sanitize_legacy)
                     .err(),
                 Some(TransactionError::SanitizeFailure)
             );
@@ -978,7 +1025,7 @@ mod tests {
             let entries = vec![next_entry(&recent_blockhash, 1, vec![tx])];
             assert_eq!(
                 entries[..]
-                    .verify_and_hash_transactions(false, false, false)
+                    .verify_and_hash_transactions(false, false, false, sanitize_legacy)
                     .err(),
                 Some(TransactionError::AccountLoadedTwice)
             );
@@ -1006,7 +1053,7 @@ mod tests {
             let entries = vec![next_entry(&recent_blockhash, 1, vec![tx.clone()])];
             assert!(bincode::serialized_size(&tx).unwrap() <= PACKET_DATA_SIZE as u64);
             assert!(entries[..]
-                .verify_and_hash_transactions(false, false, false)
+                .verify_and_hash_transactions(false, false, false, sanitize_legacy)
                 .is_ok());
         }
         // Big transaction.
@@ -1016,7 +1063,7 @@ mod tests {
             assert!(bincode::serialized_size(&tx).unwrap() > PACKET_DATA_SIZE as u64);
             assert_eq!(
                 entries[..]
-                    .verify_and_hash_transactions(false, false, false)
+                    .verify_and_hash_transactions(false, false, false, sanitize_legacy)
                     .err(),
                 Some(TransactionError::SanitizeFailure)
             );
@@ -1029,16 +1076,75 @@ mod tests {
             assert_eq!(
                 bincode::serialized_size(&tx).unwrap() <= PACKET_DATA_SIZE as u64,
                 entries[..]
-                    .verify_and_hash_transactions(false, false, false)
+                    .verify_and_hash_transactions(false, false, false, sanitize_legacy)
                     .is_ok(),
             );
         }
     }
 
+    #[test]
+    fn test_verify_and_hash_v0_transactions() {
+        let keypair = Keypair::new();
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![Pubkey::new_unique()],
+            readonly: vec![],
+        };
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![keypair.pubkey(), system_program::id()],
+            recent_blockhash: Hash::default(),
+            instructions: vec![CompiledInstruction::new(
+                1,
+                &system_instruction::SystemInstruction::Transfer { lamports: 1 },
+                vec![0, 2],
+            )],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        });
+        let tx = VersionedTransaction::try_new(message, &[&keypair]).unwrap();
+        let entries = vec![Entry::new_versioned(&Hash::default(), 1, vec![tx])];
+
+        assert_eq!(
+            entries[..]
+                .verify_and_hash_transactions(false, false, false, sanitize_legacy)
+                .err(),
+            Some(TransactionError::UnsupportedVersion)
+        );
+
+        let entry_types = entries[..]
+            .verify_and_hash_transactions(false, false, false, |tx, message_hash| {
+                SanitizedTransaction::try_create_versioned(tx, message_hash, |_| {
+                    Ok(loaded_addresses.clone())
+                })
+            })
+            .unwrap();
+        match &entry_types[0] {
+            EntryType::Transactions(transactions) => {
+                assert_eq!(transactions[0].loaded_addresses(), Some(&loaded_addresses));
+                assert_eq!(
+                    transactions[0].message.account_keys,
+                    vec![
+                        keypair.pubkey(),
+                        loaded_addresses.writable[0],
+                        system_program::id()
+                    ]
+                );
+            }
+            EntryType::Tick(_) => panic!("expected transactions"),
+        }
+    }
+
     #[test]
     fn test_verify_tick_hash_count() {
         let hashes_per_tick = 10;
-        let tx = Transaction::default();
+        let tx = VersionedTransaction::default();
 
         let no_hash_tx_entry = Entry {
             transactions: vec![tx.clone()],
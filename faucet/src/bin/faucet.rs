@@ -1,15 +1,20 @@
 use {
     clap::{crate_description, crate_name, values_t, App, Arg},
     log::*,
-    solana_clap_utils::input_parsers::{lamports_of_sol, value_of},
+    solana_clap_utils::{
+        input_parsers::{lamports_of_sol, value_of},
+        input_validators::is_port,
+    },
     solana_faucet::{
-        faucet::{run_faucet, Faucet, FAUCET_PORT},
+        faucet::{run_faucet, AccessList, Faucet, FaucetConfig, FAUCET_PORT},
+        http_server::run_faucet_http,
         socketaddr,
     },
     solana_sdk::signature::read_keypair_file,
     std::{
-        collections::HashSet,
         net::{IpAddr, Ipv4Addr, SocketAddr},
+        path::{Path, PathBuf},
+        process::exit,
         sync::{Arc, Mutex},
         thread,
     },
@@ -56,6 +61,26 @@ async fn main() {
                 .takes_value(true)
                 .help("Request limit for a single request, in SOL"),
         )
+        .arg(
+            Arg::with_name("per_ip_cap")
+                .long("per-ip-cap")
+                .value_name("NUM")
+                .takes_value(true)
+                .help(
+                    "Request limit for time slice per IP address, in SOL \
+                       [default: --per-time-cap]",
+                ),
+        )
+        .arg(
+            Arg::with_name("per_address_cap")
+                .long("per-address-cap")
+                .value_name("NUM")
+                .takes_value(true)
+                .help(
+                    "Request limit for time slice per recipient address, in SOL \
+                       [default: --per-time-cap]",
+                ),
+        )
         .arg(
             Arg::with_name("allowed_ip")
                 .long("allow-ip")
@@ -67,6 +92,31 @@ async fn main() {
                     recipient address will be used to check request limits instead",
                 ),
         )
+        .arg(
+            Arg::with_name("access_list")
+                .long("access-list")
+                .value_name("PATH")
+                .takes_value(true)
+                .help(
+                    "File of IP addresses and recipient addresses to exempt from request \
+                    limits or to refuse, one `allow <ENTRY>` or `deny <ENTRY>` per line",
+                ),
+        )
+        .arg(
+            Arg::with_name("state_file")
+                .long("state-file")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Save request limit counters to this file, so that they survive restarts"),
+        )
+        .arg(
+            Arg::with_name("http_port")
+                .long("http-port")
+                .value_name("PORT")
+                .takes_value(true)
+                .validator(is_port)
+                .help("Also serve airdrop requests as JSON over HTTP on this port"),
+        )
         .get_matches();
 
     let faucet_keypair = read_keypair_file(matches.value_of("keypair").unwrap())
@@ -75,29 +125,52 @@ async fn main() {
     let time_slice = value_of(&matches, "slice");
     let per_time_cap = lamports_of_sol(&matches, "per_time_cap");
     let per_request_cap = lamports_of_sol(&matches, "per_request_cap");
+    let per_ip_cap = lamports_of_sol(&matches, "per_ip_cap");
+    let per_address_cap = lamports_of_sol(&matches, "per_address_cap");
 
-    let allowed_ips: HashSet<_> = values_t!(matches.values_of("allowed_ip"), IpAddr)
-        .unwrap_or_default()
-        .into_iter()
-        .collect();
+    let mut access_list = matches
+        .value_of("access_list")
+        .map(|path| {
+            AccessList::load(Path::new(path)).unwrap_or_else(|err| {
+                eprintln!("Failed to read access list {}: {}", path, err);
+                exit(1);
+            })
+        })
+        .unwrap_or_default();
+    access_list
+        .allowed_ips
+        .extend(values_t!(matches.values_of("allowed_ip"), IpAddr).unwrap_or_default());
 
     let faucet_addr = socketaddr!(0, FAUCET_PORT);
 
-    let faucet = Arc::new(Mutex::new(Faucet::new_with_allowed_ips(
+    let faucet = Arc::new(Mutex::new(Faucet::new_with_config(
         faucet_keypair,
-        time_slice,
-        per_time_cap,
-        per_request_cap,
-        allowed_ips,
+        FaucetConfig {
+            time_slice,
+            per_time_cap,
+            per_request_cap,
+            per_ip_cap,
+            per_address_cap,
+            access_list,
+            state_path: matches.value_of("state_file").map(PathBuf::from),
+        },
     )));
 
     let faucet1 = faucet.clone();
     thread::spawn(move || loop {
-        let time = faucet1.lock().unwrap().time_slice;
+        let time = faucet1.lock().unwrap().time_until_clear();
         thread::sleep(time);
         debug!("clearing ip cache");
         faucet1.lock().unwrap().clear_caches();
     });
 
+    if let Some(http_port) = value_of::<u16>(&matches, "http_port") {
+        tokio::spawn(run_faucet_http(
+            faucet.clone(),
+            socketaddr!(0, http_port),
+            None,
+        ));
+    }
+
     run_faucet(faucet, faucet_addr, None).await;
 }
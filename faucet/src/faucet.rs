@@ -1,8 +1,11 @@
 //! The `faucet` module provides an object for launching a Solana Faucet,
 //! which is the custodian of any remaining lamports in a mint.
 //! The Solana Faucet builds and sends airdrop transactions,
-//! checking requests against a single-request cap and a per-IP limit
-//! for a given time time_slice.
+//! checking requests against a single-request cap and per-IP and
+//! per-recipient limits for a given time time_slice.
+//!
+//! Requests may be exempted from or denied by an access list, and the
+//! per-time-slice counters may be persisted so that they survive restarts.
 
 use {
     bincode::{deserialize, serialize, serialized_size},
@@ -19,12 +22,16 @@ use {
         pubkey::Pubkey,
         signature::{Keypair, Signer},
         system_instruction,
+        timing::timestamp,
         transaction::Transaction,
     },
     std::{
         collections::{HashMap, HashSet},
+        fs,
         io::{Read, Write},
         net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream},
+        path::{Path, PathBuf},
+        str::FromStr,
         sync::{mpsc::Sender, Arc, Mutex},
         thread,
         time::Duration,
@@ -73,6 +80,12 @@ pub enum FaucetError {
 
     #[error("limit reached; req: ◎{0}, to: {1}, current: ◎{2}, cap: ◎{3}")]
     PerTimeCapExceeded(f64, String, f64, f64),
+
+    #[error("requests from or to {0} are denied")]
+    Denied(String),
+
+    #[error("invalid access list entry on line {0}: {1}")]
+    InvalidAccessListEntry(usize, String),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -99,14 +112,107 @@ pub enum FaucetTransaction {
     Memo((Transaction, String)),
 }
 
+/// IP addresses and recipients that are exempt from their time-slice limits, or whose
+/// requests are refused outright
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessList {
+    pub allowed_ips: HashSet<IpAddr>,
+    pub allowed_addresses: HashSet<Pubkey>,
+    pub denied_ips: HashSet<IpAddr>,
+    pub denied_addresses: HashSet<Pubkey>,
+}
+
+impl AccessList {
+    /// Reads an access list file, in which each line is either blank, a `#` comment, or
+    /// `allow` or `deny` followed by an IP address or a base58 pubkey:
+    ///
+    /// ```text
+    /// allow 203.0.113.1
+    /// deny 4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T
+    /// ```
+    pub fn load(path: &Path) -> Result<Self, FaucetError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    fn parse(contents: &str) -> Result<Self, FaucetError> {
+        let mut access_list = Self::default();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_entry = || FaucetError::InvalidAccessListEntry(index + 1, line.to_string());
+            let mut words = line.split_whitespace();
+            let (action, entry) = match (words.next(), words.next(), words.next()) {
+                (Some(action), Some(entry), None) => (action, entry),
+                _ => return Err(invalid_entry()),
+            };
+            let allow = match action {
+                "allow" => true,
+                "deny" => false,
+                _ => return Err(invalid_entry()),
+            };
+            if let Ok(ip) = IpAddr::from_str(entry) {
+                if allow {
+                    access_list.allowed_ips.insert(ip);
+                } else {
+                    access_list.denied_ips.insert(ip);
+                }
+            } else if let Ok(address) = Pubkey::from_str(entry) {
+                if allow {
+                    access_list.allowed_addresses.insert(address);
+                } else {
+                    access_list.denied_addresses.insert(address);
+                }
+            } else {
+                return Err(invalid_entry());
+            }
+        }
+        Ok(access_list)
+    }
+}
+
+/// Configuration of a `Faucet`'s limits
+#[derive(Clone, Debug, Default)]
+pub struct FaucetConfig {
+    /// Length of the time slice over which requests are limited, in seconds; defaults to
+    /// `TIME_SLICE`
+    pub time_slice: Option<u64>,
+    /// Lamports any one IP address or recipient may request per time slice, unless
+    /// overridden by `per_ip_cap` or `per_address_cap`
+    pub per_time_cap: Option<u64>,
+    /// Lamports a single request may ask for
+    pub per_request_cap: Option<u64>,
+    /// Lamports any one IP address may request per time slice
+    pub per_ip_cap: Option<u64>,
+    /// Lamports any one recipient may receive per time slice
+    pub per_address_cap: Option<u64>,
+    pub access_list: AccessList,
+    /// File in which the time-slice counters are saved, so that they survive restarts
+    pub state_path: Option<PathBuf>,
+}
+
+/// The time-slice counters, as saved to `FaucetConfig::state_path`
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+struct LimitState {
+    slice_start: u64,
+    ip_cache: HashMap<IpAddr, u64>,
+    address_cache: HashMap<Pubkey, u64>,
+}
+
 pub struct Faucet {
     faucet_keypair: Keypair,
     ip_cache: HashMap<IpAddr, u64>,
     address_cache: HashMap<Pubkey, u64>,
+    /// When the current time slice began, in milliseconds since the epoch
+    slice_start: u64,
     pub time_slice: Duration,
     per_time_cap: Option<u64>,
     per_request_cap: Option<u64>,
-    allowed_ips: HashSet<IpAddr>,
+    per_ip_cap: Option<u64>,
+    per_address_cap: Option<u64>,
+    access_list: AccessList,
+    state_path: Option<PathBuf>,
 }
 
 impl Faucet {
@@ -132,7 +238,32 @@ impl Faucet {
         per_request_cap: Option<u64>,
         allowed_ips: HashSet<IpAddr>,
     ) -> Self {
-        let time_slice = Duration::new(time_input.unwrap_or(TIME_SLICE), 0);
+        Self::new_with_config(
+            faucet_keypair,
+            FaucetConfig {
+                time_slice: time_input,
+                per_time_cap,
+                per_request_cap,
+                access_list: AccessList {
+                    allowed_ips,
+                    ..AccessList::default()
+                },
+                ..FaucetConfig::default()
+            },
+        )
+    }
+
+    pub fn new_with_config(faucet_keypair: Keypair, config: FaucetConfig) -> Self {
+        let time_slice = Duration::new(config.time_slice.unwrap_or(TIME_SLICE), 0);
+        let per_request_cap = config.per_request_cap;
+        let per_time_cap = [
+            config.per_ip_cap.or(config.per_time_cap),
+            config.per_address_cap.or(config.per_time_cap),
+        ]
+        .iter()
+        .flatten()
+        .min()
+        .copied();
         if let Some((per_request_cap, per_time_cap)) = per_request_cap.zip(per_time_cap) {
             if per_time_cap < per_request_cap {
                 warn!(
@@ -143,14 +274,75 @@ impl Faucet {
                 );
             }
         }
+
+        let mut state = config
+            .state_path
+            .as_deref()
+            .map(Self::load_state)
+            .unwrap_or_default();
+        // Counters from a time slice that has since ended no longer apply
+        if timestamp().saturating_sub(state.slice_start) >= time_slice.as_millis() as u64 {
+            state = LimitState {
+                slice_start: timestamp(),
+                ..LimitState::default()
+            };
+        }
+
         Self {
             faucet_keypair,
-            ip_cache: HashMap::new(),
-            address_cache: HashMap::new(),
+            ip_cache: state.ip_cache,
+            address_cache: state.address_cache,
+            slice_start: state.slice_start,
             time_slice,
-            per_time_cap,
+            per_time_cap: config.per_time_cap,
             per_request_cap,
-            allowed_ips,
+            per_ip_cap: config.per_ip_cap,
+            per_address_cap: config.per_address_cap,
+            access_list: config.access_list,
+            state_path: config.state_path,
+        }
+    }
+
+    fn load_state(state_path: &Path) -> LimitState {
+        if !state_path.exists() {
+            return LimitState::default();
+        }
+        match fs::read(state_path)
+            .map_err(FaucetError::from)
+            .and_then(|bytes| deserialize(&bytes).map_err(FaucetError::from))
+        {
+            Ok(state) => state,
+            Err(err) => {
+                warn!(
+                    "Ignoring unreadable faucet limit state {:?}: {}",
+                    state_path, err
+                );
+                LimitState::default()
+            }
+        }
+    }
+
+    fn save_state(&self) {
+        let state_path = match &self.state_path {
+            Some(state_path) => state_path,
+            None => return,
+        };
+        let state = LimitState {
+            slice_start: self.slice_start,
+            ip_cache: self.ip_cache.clone(),
+            address_cache: self.address_cache.clone(),
+        };
+        // Write to a temporary file first so that a crash never leaves a truncated file behind
+        let tmp_path = state_path.with_extension("tmp");
+        let result = serialize(&state)
+            .map_err(FaucetError::from)
+            .and_then(|bytes| fs::write(&tmp_path, bytes).map_err(FaucetError::from))
+            .and_then(|()| fs::rename(&tmp_path, state_path).map_err(FaucetError::from));
+        if let Err(err) = result {
+            warn!(
+                "Failed to save faucet limit state {:?}: {}",
+                state_path, err
+            );
         }
     }
 
@@ -161,7 +353,7 @@ impl Faucet {
     ) -> Result<(), FaucetError> {
         let new_total = to.check_cache(self, request_amount);
         to.datapoint_info(request_amount, new_total);
-        if let Some(cap) = self.per_time_cap {
+        if let Some(cap) = to.cap(self) {
             if new_total > cap {
                 return Err(FaucetError::PerTimeCapExceeded(
                     lamports_to_sol(request_amount),
@@ -177,9 +369,45 @@ impl Faucet {
     pub fn clear_caches(&mut self) {
         self.ip_cache.clear();
         self.address_cache.clear();
+        self.slice_start = timestamp();
+        self.save_state();
+    }
+
+    /// How long until the current time slice ends and the caches should be cleared
+    pub fn time_until_clear(&self) -> Duration {
+        let elapsed = Duration::from_millis(timestamp().saturating_sub(self.slice_start));
+        self.time_slice
+            .checked_sub(elapsed)
+            .unwrap_or_else(|| Duration::from_secs(0))
+    }
+
+    fn check_access(&self, ip: &IpAddr, to: &Pubkey) -> Result<(), FaucetError> {
+        if self.access_list.denied_ips.contains(ip) {
+            return Err(FaucetError::Denied(ip.to_string()));
+        }
+        if self.access_list.denied_addresses.contains(to) {
+            return Err(FaucetError::Denied(to.to_string()));
+        }
+        Ok(())
+    }
+
+    fn check_request_limits(
+        &mut self,
+        request_amount: u64,
+        ip: IpAddr,
+        to: Pubkey,
+    ) -> Result<(), FaucetError> {
+        if !ip.is_loopback() && !self.access_list.allowed_ips.contains(&ip) {
+            self.check_time_request_limit(request_amount, ip)?;
+        }
+        if !self.access_list.allowed_addresses.contains(&to) {
+            self.check_time_request_limit(request_amount, to)?;
+        }
+        Ok(())
     }
 
-    /// Checks per-request and per-time-ip limits; if both pass, this method returns a signed
+    /// Checks the access list, and per-request, per-time-ip and per-time-recipient limits; if
+    /// all pass, this method returns a signed
     /// SystemProgram::Transfer transaction from the faucet keypair to the requested recipient. If
     /// the request exceeds this per-request limit, this method returns a signed SPL Memo
     /// transaction with the memo: "request too large; req: <REQUEST> SOL cap: <CAP> SOL"
@@ -202,6 +430,7 @@ impl Faucet {
                     to
                 );
 
+                self.check_access(&ip, &to)?;
                 if let Some(cap) = self.per_request_cap {
                     if lamports > cap {
                         let memo = format!(
@@ -223,10 +452,9 @@ impl Faucet {
                         )));
                     }
                 }
-                if !ip.is_loopback() && !self.allowed_ips.contains(&ip) {
-                    self.check_time_request_limit(lamports, ip)?;
-                }
-                self.check_time_request_limit(lamports, to)?;
+                let limits_result = self.check_request_limits(lamports, ip, to);
+                self.save_state();
+                limits_result?;
 
                 let transfer_instruction =
                     system_instruction::transfer(&mint_pubkey, &to, lamports);
@@ -451,6 +679,7 @@ async fn process(
 
 pub trait LimitByTime {
     fn check_cache(&self, faucet: &mut Faucet, request_amount: u64) -> u64;
+    fn cap(&self, faucet: &Faucet) -> Option<u64>;
     fn datapoint_info(&self, request_amount: u64, new_total: u64);
 }
 
@@ -463,6 +692,10 @@ impl LimitByTime for IpAddr {
             .or_insert(request_amount)
     }
 
+    fn cap(&self, faucet: &Faucet) -> Option<u64> {
+        faucet.per_ip_cap.or(faucet.per_time_cap)
+    }
+
     fn datapoint_info(&self, request_amount: u64, new_total: u64) {
         datapoint_info!(
             "faucet-airdrop",
@@ -482,6 +715,10 @@ impl LimitByTime for Pubkey {
             .or_insert(request_amount)
     }
 
+    fn cap(&self, faucet: &Faucet) -> Option<u64> {
+        faucet.per_address_cap.or(faucet.per_time_cap)
+    }
+
     fn datapoint_info(&self, request_amount: u64, new_total: u64) {
         datapoint_info!(
             "faucet-airdrop",
@@ -681,4 +918,153 @@ mod tests {
         let bad_bytes = "bad bytes".as_bytes();
         assert!(faucet.process_faucet_request(bad_bytes, ip).is_err());
     }
+
+    #[test]
+    fn test_access_list_parse() {
+        let address = Pubkey::new_unique();
+        let contents = format!(
+            "# devnet access list\n\nallow 203.0.113.1\n  deny {}  \ndeny 198.51.100.7\n",
+            address
+        );
+        let access_list = AccessList::parse(&contents).unwrap();
+        assert_eq!(
+            access_list.allowed_ips,
+            vec![socketaddr!([203, 0, 113, 1], 0).ip()]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            access_list.denied_ips,
+            vec![socketaddr!([198, 51, 100, 7], 0).ip()]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            access_list.denied_addresses,
+            vec![address].into_iter().collect()
+        );
+        assert!(access_list.allowed_addresses.is_empty());
+
+        assert!(matches!(
+            AccessList::parse("allow 203.0.113.1\nallow nonsense"),
+            Err(FaucetError::InvalidAccessListEntry(2, _))
+        ));
+        assert!(matches!(
+            AccessList::parse("permit 203.0.113.1"),
+            Err(FaucetError::InvalidAccessListEntry(1, _))
+        ));
+        assert!(matches!(
+            AccessList::parse("allow 203.0.113.1 198.51.100.7"),
+            Err(FaucetError::InvalidAccessListEntry(1, _))
+        ));
+    }
+
+    #[test]
+    fn test_faucet_config_limits() {
+        let ip = socketaddr!([203, 0, 113, 1], 0).ip();
+        let other_ip = socketaddr!([203, 0, 113, 2], 0).ip();
+        let to = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let request = |lamports, to| FaucetRequest::GetAirdrop {
+            lamports,
+            to,
+            blockhash: Hash::default(),
+        };
+
+        // Separate per-IP and per-recipient caps
+        let mut faucet = Faucet::new_with_config(
+            Keypair::new(),
+            FaucetConfig {
+                per_ip_cap: Some(3),
+                per_address_cap: Some(2),
+                ..FaucetConfig::default()
+            },
+        );
+        assert!(faucet.build_airdrop_transaction(request(2, to), ip).is_ok());
+        assert!(faucet
+            .build_airdrop_transaction(request(1, to), ip)
+            .is_err());
+        assert!(faucet
+            .build_airdrop_transaction(request(1, other), ip)
+            .is_err());
+        assert!(faucet
+            .build_airdrop_transaction(request(1, other), other_ip)
+            .is_ok());
+
+        // Allowed recipients are exempt from the per-recipient cap, denied ones are refused
+        let mut faucet = Faucet::new_with_config(
+            Keypair::new(),
+            FaucetConfig {
+                per_time_cap: Some(2),
+                access_list: AccessList {
+                    allowed_addresses: vec![to].into_iter().collect(),
+                    denied_ips: vec![other_ip].into_iter().collect(),
+                    denied_addresses: vec![other].into_iter().collect(),
+                    ..AccessList::default()
+                },
+                ..FaucetConfig::default()
+            },
+        );
+        let loopback = socketaddr!([127, 0, 0, 1], 0).ip();
+        assert!(faucet
+            .build_airdrop_transaction(request(2, to), loopback)
+            .is_ok());
+        assert!(faucet
+            .build_airdrop_transaction(request(2, to), loopback)
+            .is_ok());
+        assert!(matches!(
+            faucet.build_airdrop_transaction(request(1, other), loopback),
+            Err(FaucetError::Denied(_))
+        ));
+        assert!(matches!(
+            faucet.build_airdrop_transaction(request(1, to), other_ip),
+            Err(FaucetError::Denied(_))
+        ));
+    }
+
+    #[test]
+    fn test_limit_state_persistence() {
+        let state_dir =
+            std::env::temp_dir().join(format!("faucet-limit-state-{}", Pubkey::new_unique()));
+        fs::create_dir_all(&state_dir).unwrap();
+        let state_path = state_dir.join("limits");
+        let config = FaucetConfig {
+            per_time_cap: Some(2),
+            state_path: Some(state_path.clone()),
+            ..FaucetConfig::default()
+        };
+        let ip = socketaddr!([203, 0, 113, 1], 0).ip();
+        let request = FaucetRequest::GetAirdrop {
+            lamports: 2,
+            to: Pubkey::new_unique(),
+            blockhash: Hash::default(),
+        };
+
+        let mut faucet = Faucet::new_with_config(Keypair::new(), config.clone());
+        assert!(faucet.build_airdrop_transaction(request, ip).is_ok());
+        drop(faucet);
+
+        // The counters survive a restart
+        let mut faucet = Faucet::new_with_config(Keypair::new(), config.clone());
+        assert_eq!(faucet.ip_cache.get(&ip), Some(&2));
+        assert!(faucet.build_airdrop_transaction(request, ip).is_err());
+
+        // ...until the time slice is over
+        faucet.clear_caches();
+        drop(faucet);
+        let faucet = Faucet::new_with_config(Keypair::new(), config.clone());
+        assert!(faucet.ip_cache.is_empty());
+        drop(faucet);
+
+        // Counters from an earlier time slice are discarded
+        let mut faucet = Faucet::new_with_config(Keypair::new(), config.clone());
+        assert!(faucet.build_airdrop_transaction(request, ip).is_ok());
+        faucet.slice_start = faucet.slice_start.saturating_sub(TIME_SLICE * 1000);
+        faucet.save_state();
+        drop(faucet);
+        let faucet = Faucet::new_with_config(Keypair::new(), config);
+        assert!(faucet.ip_cache.is_empty());
+
+        fs::remove_dir_all(&state_dir).unwrap();
+    }
 }
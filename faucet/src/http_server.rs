@@ -0,0 +1,245 @@
+//! The `http_server` module serves airdrop requests as JSON over HTTP, alongside the bincode
+//! TCP protocol, for clients that cannot speak the latter.
+//!
+//! `POST /airdrop` with a body such as
+//! `{"pubkey": "<base58>", "lamports": 1000000000, "blockhash": "<base58>"}` is answered with
+//! `{"transaction": "<base64>"}`, the signed airdrop transaction for the client to submit, or
+//! `{"error": "<message>"}`.
+
+use {
+    crate::faucet::{Faucet, FaucetError, FaucetRequest, FaucetTransaction},
+    hyper::{
+        body::HttpBody,
+        server::conn::AddrStream,
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Response, Server, StatusCode,
+    },
+    log::*,
+    serde_derive::Deserialize,
+    serde_json::json,
+    solana_sdk::{hash::Hash, pubkey::Pubkey},
+    std::{
+        convert::Infallible,
+        net::{IpAddr, SocketAddr},
+        str::FromStr,
+        sync::{mpsc::Sender, Arc, Mutex},
+    },
+};
+
+/// Airdrop requests are tiny; anything larger is refused unread
+const MAX_REQUEST_BODY_SIZE: usize = 1024;
+
+#[derive(Deserialize, Debug)]
+struct HttpAirdropRequest {
+    pubkey: String,
+    lamports: u64,
+    blockhash: String,
+}
+
+pub async fn run_faucet_http(
+    faucet: Arc<Mutex<Faucet>>,
+    faucet_http_addr: SocketAddr,
+    sender: Option<Sender<Result<SocketAddr, String>>>,
+) {
+    let builder = Server::try_bind(&faucet_http_addr).map_err(|err| {
+        format!(
+            "Unable to bind faucet HTTP server to {:?}, check the address is not already in use: {}",
+            faucet_http_addr, err
+        )
+    });
+    let builder = match builder {
+        Ok(builder) => builder,
+        Err(err) => {
+            error!("Faucet HTTP server failed to start: {}", err);
+            if let Some(sender) = sender {
+                sender.send(Err(err)).unwrap();
+            }
+            return;
+        }
+    };
+
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let faucet = faucet.clone();
+        let ip = conn.remote_addr().ip();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(request, faucet.clone(), ip)
+            }))
+        }
+    });
+    let server = builder.serve(make_service);
+    if let Some(sender) = sender {
+        sender.send(Ok(server.local_addr())).unwrap();
+    }
+    info!("Faucet HTTP server listening on: {}", server.local_addr());
+
+    if let Err(err) = server.await {
+        error!("Faucet HTTP server failed: {}", err);
+    }
+}
+
+async fn handle_request(
+    request: Request<Body>,
+    faucet: Arc<Mutex<Faucet>>,
+    ip: IpAddr,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::POST || request.uri().path() != "/airdrop" {
+        return Ok(error_response(StatusCode::NOT_FOUND, "not found"));
+    }
+
+    let mut body = request.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        match chunk {
+            Ok(chunk) => bytes.extend_from_slice(&chunk),
+            Err(err) => return Ok(error_response(StatusCode::BAD_REQUEST, &err.to_string())),
+        }
+        if bytes.len() > MAX_REQUEST_BODY_SIZE {
+            return Ok(error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "request too large",
+            ));
+        }
+    }
+
+    let request = match parse_request(&bytes) {
+        Ok(request) => request,
+        Err(err) => return Ok(error_response(StatusCode::BAD_REQUEST, &err)),
+    };
+    info!(
+        "HTTP airdrop transaction requested from {}: {:?}",
+        ip, request
+    );
+
+    let result = faucet
+        .lock()
+        .unwrap()
+        .build_airdrop_transaction(request, ip);
+    let response = match result {
+        Ok(FaucetTransaction::Airdrop(transaction)) => {
+            info!("Airdrop transaction granted");
+            let transaction = bincode::serialize(&transaction).unwrap();
+            json_response(
+                StatusCode::OK,
+                json!({ "transaction": base64::encode(transaction) }),
+            )
+        }
+        Ok(FaucetTransaction::Memo((_, memo))) => {
+            warn!("Airdrop request refused: {}", memo);
+            error_response(StatusCode::BAD_REQUEST, &memo)
+        }
+        Err(err) => {
+            warn!("Airdrop transaction failed: {}", err);
+            let status = match err {
+                FaucetError::PerTimeCapExceeded(..) => StatusCode::TOO_MANY_REQUESTS,
+                FaucetError::Denied(_) => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, &err.to_string())
+        }
+    };
+    Ok(response)
+}
+
+fn parse_request(bytes: &[u8]) -> Result<FaucetRequest, String> {
+    let request: HttpAirdropRequest =
+        serde_json::from_slice(bytes).map_err(|err| format!("invalid request: {}", err))?;
+    let to = Pubkey::from_str(&request.pubkey).map_err(|_| "invalid pubkey".to_string())?;
+    let blockhash =
+        Hash::from_str(&request.blockhash).map_err(|_| "invalid blockhash".to_string())?;
+    Ok(FaucetRequest::GetAirdrop {
+        lamports: request.lamports,
+        to,
+        blockhash,
+    })
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::faucet::FaucetConfig,
+        serde_json::Value,
+        solana_sdk::{
+            message::Message,
+            signature::{Keypair, Signer},
+            system_instruction,
+            transaction::Transaction,
+        },
+        tokio::runtime::Runtime,
+    };
+
+    fn post(faucet: &Arc<Mutex<Faucet>>, ip: IpAddr, body: String) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/airdrop")
+            .body(Body::from(body))
+            .unwrap();
+        Runtime::new().unwrap().block_on(async {
+            let response = handle_request(request, faucet.clone(), ip).await.unwrap();
+            let status = response.status();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (status, serde_json::from_slice(&body).unwrap())
+        })
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let keypair = Keypair::new();
+        let to = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let lamports = 50;
+        let message = Message::new(
+            &[system_instruction::transfer(
+                &keypair.pubkey(),
+                &to,
+                lamports,
+            )],
+            Some(&keypair.pubkey()),
+        );
+        let expected_tx = Transaction::new(&[&keypair], message, blockhash);
+
+        let faucet = Arc::new(Mutex::new(Faucet::new_with_config(
+            keypair,
+            FaucetConfig {
+                per_time_cap: Some(lamports),
+                ..FaucetConfig::default()
+            },
+        )));
+        let ip = IpAddr::from([203, 0, 113, 1]);
+        let body = json!({
+            "pubkey": to.to_string(),
+            "lamports": lamports,
+            "blockhash": blockhash.to_string(),
+        })
+        .to_string();
+
+        let (status, response) = post(&faucet, ip, body.clone());
+        assert_eq!(status, StatusCode::OK);
+        let transaction = base64::decode(response["transaction"].as_str().unwrap()).unwrap();
+        let transaction: Transaction = bincode::deserialize(&transaction).unwrap();
+        assert_eq!(transaction, expected_tx);
+
+        let (status, response) = post(&faucet, ip, body);
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(response["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("limit reached"));
+
+        let (status, _) = post(&faucet, ip, "{\"pubkey\": \"bogus\"}".to_string());
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
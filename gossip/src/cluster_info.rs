@@ -42,7 +42,7 @@ use {
     solana_measure::measure::Measure,
     solana_metrics::{inc_new_counter_debug, inc_new_counter_error},
     solana_net_utils::{
-        bind_common, bind_common_in_range, bind_in_range, find_available_port_in_range,
+        bind_common, bind_common_in_range, bind_in_range, bind_to, find_available_port_in_range,
         multi_bind_in_range, PortRange,
     },
     solana_perf::packet::{
@@ -56,6 +56,7 @@ use {
         feature_set::{self, FeatureSet},
         hash::Hash,
         pubkey::Pubkey,
+        quic::QUIC_PORT_OFFSET,
         sanitize::{Sanitize, SanitizeError},
         signature::{Keypair, Signable, Signature, Signer},
         timing::timestamp,
@@ -73,7 +74,7 @@ use {
         collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
         fmt::Debug,
         fs::{self, File},
-        io::BufReader,
+        io::{self, BufReader},
         iter::repeat,
         net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, UdpSocket},
         ops::{Deref, Div},
@@ -2679,6 +2680,8 @@ pub struct Sockets {
     pub tvu_forwards: Vec<UdpSocket>,
     pub tpu: Vec<UdpSocket>,
     pub tpu_forwards: Vec<UdpSocket>,
+    /// Bound to the TPU port plus `QUIC_PORT_OFFSET`, within the same port range
+    pub tpu_quic: UdpSocket,
     pub broadcast: Vec<UdpSocket>,
     pub repair: UdpSocket,
     pub retransmit_sockets: Vec<UdpSocket>,
@@ -2698,7 +2701,21 @@ impl Node {
     }
     pub fn new_localhost_with_pubkey(pubkey: &Pubkey) -> Self {
         let bind_ip_addr = IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0));
-        let tpu = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let localhost_ip_addr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        // The QUIC port is derived from the TPU port, so pick a TPU port that leaves it free
+        let (tpu, tpu_quic) = (0..100)
+            .find_map(|_| {
+                let tpu = UdpSocket::bind("127.0.0.1:0").unwrap();
+                let tpu_port = tpu.local_addr().unwrap().port();
+                let tpu_quic = bind_to(
+                    localhost_ip_addr,
+                    tpu_port.checked_add(QUIC_PORT_OFFSET)?,
+                    false,
+                )
+                .ok()?;
+                Some((tpu, tpu_quic))
+            })
+            .expect("tpu_quic bind");
         let (gossip_port, (gossip, ip_echo)) =
             bind_common_in_range(bind_ip_addr, (1024, 65535)).unwrap();
         let gossip_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), gossip_port);
@@ -2740,6 +2757,7 @@ impl Node {
                 tvu_forwards: vec![tvu_forwards],
                 tpu: vec![tpu],
                 tpu_forwards: vec![tpu_forwards],
+                tpu_quic,
                 broadcast,
                 repair,
                 retransmit_sockets: vec![retransmit_socket],
@@ -2767,6 +2785,37 @@ impl Node {
     fn bind(bind_ip_addr: IpAddr, port_range: PortRange) -> (u16, UdpSocket) {
         bind_in_range(bind_ip_addr, port_range).expect("Failed to bind")
     }
+    /// Bind `num_tpu_sockets` TPU sockets to a port in `port_range` whose QUIC port,
+    /// `QUIC_PORT_OFFSET` above it, is free and also in `port_range`, and the QUIC socket
+    fn bind_tpu_with_quic(
+        bind_ip_addr: IpAddr,
+        port_range: PortRange,
+        num_tpu_sockets: usize,
+    ) -> io::Result<(u16, Vec<UdpSocket>, UdpSocket)> {
+        const NUM_TRIES: usize = 100;
+        let tpu_port_range = (port_range.0, port_range.1.saturating_sub(QUIC_PORT_OFFSET));
+        if tpu_port_range.0 >= tpu_port_range.1 {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("port range {:?} cannot hold a TPU QUIC port", port_range),
+            ));
+        }
+        // TPU ports whose QUIC port is taken are held until a pair is found, so that the
+        // next attempt picks a different port
+        let mut unusable_tpu_sockets = vec![];
+        for _ in 0..NUM_TRIES {
+            let (tpu_port, tpu_sockets) =
+                multi_bind_in_range(bind_ip_addr, tpu_port_range, num_tpu_sockets)?;
+            match bind_to(bind_ip_addr, tpu_port + QUIC_PORT_OFFSET, false) {
+                Ok(tpu_quic) => return Ok((tpu_port, tpu_sockets, tpu_quic)),
+                Err(_) => unusable_tpu_sockets.push(tpu_sockets),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("no free TPU and TPU QUIC port pair in {:?}", port_range),
+        ))
+    }
 
     pub fn new_single_bind(
         pubkey: &Pubkey,
@@ -2778,7 +2827,8 @@ impl Node {
             Self::get_gossip_port(gossip_addr, port_range, bind_ip_addr);
         let (tvu_port, tvu) = Self::bind(bind_ip_addr, port_range);
         let (tvu_forwards_port, tvu_forwards) = Self::bind(bind_ip_addr, port_range);
-        let (tpu_port, tpu) = Self::bind(bind_ip_addr, port_range);
+        let (tpu_port, tpu, tpu_quic) =
+            Self::bind_tpu_with_quic(bind_ip_addr, port_range, 1).expect("tpu bind");
         let (tpu_forwards_port, tpu_forwards) = Self::bind(bind_ip_addr, port_range);
         let (_, retransmit_socket) = Self::bind(bind_ip_addr, port_range);
         let (repair_port, repair) = Self::bind(bind_ip_addr, port_range);
@@ -2812,8 +2862,9 @@ impl Node {
                 ip_echo: Some(ip_echo),
                 tvu: vec![tvu],
                 tvu_forwards: vec![tvu_forwards],
-                tpu: vec![tpu],
+                tpu,
                 tpu_forwards: vec![tpu_forwards],
+                tpu_quic,
                 broadcast: vec![broadcast],
                 repair,
                 retransmit_sockets: vec![retransmit_socket],
@@ -2837,8 +2888,8 @@ impl Node {
         let (tvu_forwards_port, tvu_forwards_sockets) =
             multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tvu_forwards multi_bind");
 
-        let (tpu_port, tpu_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 32).expect("tpu multi_bind");
+        let (tpu_port, tpu_sockets, tpu_quic) =
+            Self::bind_tpu_with_quic(bind_ip_addr, port_range, 32).expect("tpu multi_bind");
 
         let (tpu_forwards_port, tpu_forwards_sockets) =
             multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tpu_forwards multi_bind");
@@ -2877,6 +2928,7 @@ impl Node {
                 tvu_forwards: tvu_forwards_sockets,
                 tpu: tpu_sockets,
                 tpu_forwards: tpu_forwards_sockets,
+                tpu_quic,
                 broadcast,
                 repair,
                 retransmit_sockets,
@@ -3364,6 +3416,11 @@ mod tests {
 
         check_sockets(&node.sockets.tvu, ip, range);
         check_sockets(&node.sockets.tpu, ip, range);
+        check_socket(&node.sockets.tpu_quic, ip, range);
+        assert_eq!(
+            node.sockets.tpu_quic.local_addr().unwrap().port(),
+            node.info.tpu.port() + QUIC_PORT_OFFSET
+        );
     }
 
     #[test]
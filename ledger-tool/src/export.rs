@@ -0,0 +1,950 @@
+/// The `export` subcommand
+use arrow::{
+    array::{ArrayRef, BooleanArray, Int64Array, StringArray, UInt64Array, UInt8Array},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    ipc::writer::FileWriter,
+    record_batch::RecordBatch,
+};
+use clap::{value_t, value_t_or_exit, values_t_or_exit, App, Arg, ArgMatches, SubCommand};
+use log::*;
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use solana_clap_utils::input_validators::is_slot;
+use solana_ledger::{blockstore::Blockstore, blockstore_db::AccessType};
+use solana_runtime::hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE};
+use solana_sdk::{
+    clock::{Epoch, Slot},
+    epoch_schedule::EpochSchedule,
+    instruction::CompiledInstruction,
+    message::VersionedMessage,
+    pubkey::Pubkey,
+};
+use solana_transaction_status::{
+    parse_instruction, resolve_message, ConfirmedBlock, TransactionTokenBalance,
+    TransactionWithStatusMeta,
+};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::exit,
+    result::Result,
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Number of rows buffered per table before a record batch is written out
+const ROWS_PER_BATCH: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ExportFormat {
+    Parquet,
+    Arrow,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Arrow => "arrow",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parquet" => Ok(ExportFormat::Parquet),
+            "arrow" => Ok(ExportFormat::Arrow),
+            _ => Err(format!("Unsupported export format: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ExportTable {
+    Transactions,
+    Instructions,
+    Balances,
+    Rewards,
+}
+
+impl FromStr for ExportTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transactions" => Ok(ExportTable::Transactions),
+            "instructions" => Ok(ExportTable::Instructions),
+            "balances" => Ok(ExportTable::Balances),
+            "rewards" => Ok(ExportTable::Rewards),
+            _ => Err(format!("Unknown table: {}", s)),
+        }
+    }
+}
+
+/// Column-oriented row buffer for one exported table
+trait Columns: Default {
+    const TABLE: &'static str;
+
+    fn schema() -> Schema;
+    fn len(&self) -> usize;
+    fn into_arrays(self) -> Vec<ArrayRef>;
+}
+
+fn string_array(values: Vec<String>) -> ArrayRef {
+    Arc::new(values.iter().map(Some).collect::<StringArray>())
+}
+
+fn optional_string_array(values: Vec<Option<String>>) -> ArrayRef {
+    Arc::new(values.iter().map(Option::as_ref).collect::<StringArray>())
+}
+
+#[derive(Default)]
+struct TransactionColumns {
+    slot: Vec<u64>,
+    block_time: Vec<Option<i64>>,
+    transaction_index: Vec<u64>,
+    signature: Vec<String>,
+    version: Vec<String>,
+    recent_blockhash: Vec<String>,
+    success: Vec<Option<bool>>,
+    err: Vec<Option<String>>,
+    fee: Vec<Option<u64>>,
+    num_signatures: Vec<u64>,
+    num_accounts: Vec<u64>,
+    num_instructions: Vec<u64>,
+}
+
+impl Columns for TransactionColumns {
+    const TABLE: &'static str = "transactions";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("block_time", DataType::Int64, true),
+            Field::new("transaction_index", DataType::UInt64, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("version", DataType::Utf8, false),
+            Field::new("recent_blockhash", DataType::Utf8, false),
+            Field::new("success", DataType::Boolean, true),
+            Field::new("err", DataType::Utf8, true),
+            Field::new("fee", DataType::UInt64, true),
+            Field::new("num_signatures", DataType::UInt64, false),
+            Field::new("num_accounts", DataType::UInt64, false),
+            Field::new("num_instructions", DataType::UInt64, false),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn into_arrays(self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(UInt64Array::from(self.slot)),
+            Arc::new(Int64Array::from(self.block_time)),
+            Arc::new(UInt64Array::from(self.transaction_index)),
+            string_array(self.signature),
+            string_array(self.version),
+            string_array(self.recent_blockhash),
+            Arc::new(BooleanArray::from(self.success)),
+            optional_string_array(self.err),
+            Arc::new(UInt64Array::from(self.fee)),
+            Arc::new(UInt64Array::from(self.num_signatures)),
+            Arc::new(UInt64Array::from(self.num_accounts)),
+            Arc::new(UInt64Array::from(self.num_instructions)),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct InstructionColumns {
+    slot: Vec<u64>,
+    signature: Vec<String>,
+    instruction_index: Vec<u64>,
+    inner_instruction_index: Vec<Option<u64>>,
+    program_id: Vec<Option<String>>,
+    accounts: Vec<String>,
+    data: Vec<String>,
+    program: Vec<Option<String>>,
+    parsed: Vec<Option<String>>,
+}
+
+impl Columns for InstructionColumns {
+    const TABLE: &'static str = "instructions";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("instruction_index", DataType::UInt64, false),
+            Field::new("inner_instruction_index", DataType::UInt64, true),
+            Field::new("program_id", DataType::Utf8, true),
+            Field::new("accounts", DataType::Utf8, false),
+            Field::new("data", DataType::Utf8, false),
+            Field::new("program", DataType::Utf8, true),
+            Field::new("parsed", DataType::Utf8, true),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn into_arrays(self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(UInt64Array::from(self.slot)),
+            string_array(self.signature),
+            Arc::new(UInt64Array::from(self.instruction_index)),
+            Arc::new(UInt64Array::from(self.inner_instruction_index)),
+            optional_string_array(self.program_id),
+            string_array(self.accounts),
+            string_array(self.data),
+            optional_string_array(self.program),
+            optional_string_array(self.parsed),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct BalanceColumns {
+    slot: Vec<u64>,
+    signature: Vec<String>,
+    account_index: Vec<u64>,
+    pubkey: Vec<String>,
+    pre_balance: Vec<Option<u64>>,
+    post_balance: Vec<Option<u64>>,
+    mint: Vec<Option<String>>,
+    token_decimals: Vec<Option<u8>>,
+    pre_token_amount: Vec<Option<String>>,
+    post_token_amount: Vec<Option<String>>,
+}
+
+impl Columns for BalanceColumns {
+    const TABLE: &'static str = "balances";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("account_index", DataType::UInt64, false),
+            Field::new("pubkey", DataType::Utf8, false),
+            Field::new("pre_balance", DataType::UInt64, true),
+            Field::new("post_balance", DataType::UInt64, true),
+            Field::new("mint", DataType::Utf8, true),
+            Field::new("token_decimals", DataType::UInt8, true),
+            Field::new("pre_token_amount", DataType::Utf8, true),
+            Field::new("post_token_amount", DataType::Utf8, true),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn into_arrays(self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(UInt64Array::from(self.slot)),
+            string_array(self.signature),
+            Arc::new(UInt64Array::from(self.account_index)),
+            string_array(self.pubkey),
+            Arc::new(UInt64Array::from(self.pre_balance)),
+            Arc::new(UInt64Array::from(self.post_balance)),
+            optional_string_array(self.mint),
+            Arc::new(UInt8Array::from(self.token_decimals)),
+            optional_string_array(self.pre_token_amount),
+            optional_string_array(self.post_token_amount),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct RewardColumns {
+    slot: Vec<u64>,
+    pubkey: Vec<String>,
+    lamports: Vec<i64>,
+    post_balance: Vec<u64>,
+    reward_type: Vec<Option<String>>,
+    commission: Vec<Option<u8>>,
+}
+
+impl Columns for RewardColumns {
+    const TABLE: &'static str = "rewards";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("pubkey", DataType::Utf8, false),
+            Field::new("lamports", DataType::Int64, false),
+            Field::new("post_balance", DataType::UInt64, false),
+            Field::new("reward_type", DataType::Utf8, true),
+            Field::new("commission", DataType::UInt8, true),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn into_arrays(self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(UInt64Array::from(self.slot)),
+            string_array(self.pubkey),
+            Arc::new(Int64Array::from(self.lamports)),
+            Arc::new(UInt64Array::from(self.post_balance)),
+            optional_string_array(self.reward_type),
+            Arc::new(UInt8Array::from(self.commission)),
+        ]
+    }
+}
+
+enum BatchWriter {
+    Parquet(ArrowWriter<File>),
+    Arrow(FileWriter<File>),
+}
+
+impl BatchWriter {
+    fn create(
+        path: &Path,
+        schema: SchemaRef,
+        format: ExportFormat,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::create(path)
+            .map_err(|err| format!("Unable to create {}: {}", path.display(), err))?;
+        Ok(match format {
+            ExportFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                BatchWriter::Parquet(ArrowWriter::try_new(file, schema, Some(properties))?)
+            }
+            ExportFormat::Arrow => BatchWriter::Arrow(FileWriter::try_new(file, &schema)?),
+        })
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            BatchWriter::Parquet(writer) => writer.write(batch)?,
+            BatchWriter::Arrow(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            BatchWriter::Parquet(mut writer) => {
+                writer.close()?;
+            }
+            BatchWriter::Arrow(mut writer) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+/// Buffers the rows of one table and writes them to a file per epoch partition, laid out as
+/// `<output_dir>/<table>/epoch=<epoch>/<table>-<first_slot>.<extension>`
+struct TableExporter<C: Columns> {
+    output_dir: PathBuf,
+    format: ExportFormat,
+    schema: SchemaRef,
+    rows: C,
+    writer: Option<BatchWriter>,
+}
+
+impl<C: Columns> TableExporter<C> {
+    fn new(output_dir: &Path, format: ExportFormat) -> Self {
+        Self {
+            output_dir: output_dir.join(C::TABLE),
+            format,
+            schema: Arc::new(C::schema()),
+            rows: C::default(),
+            writer: None,
+        }
+    }
+
+    fn start_partition(
+        &mut self,
+        epoch: Epoch,
+        first_slot: Slot,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.finish_partition()?;
+
+        let partition_dir = self.output_dir.join(format!("epoch={}", epoch));
+        fs::create_dir_all(&partition_dir).map_err(|err| {
+            format!(
+                "Unable to create directory {}: {}",
+                partition_dir.display(),
+                err
+            )
+        })?;
+        let path = partition_dir.join(format!(
+            "{}-{}.{}",
+            C::TABLE,
+            first_slot,
+            self.format.extension()
+        ));
+        info!("Exporting {} to {}", C::TABLE, path.display());
+        self.writer = Some(BatchWriter::create(
+            &path,
+            self.schema.clone(),
+            self.format,
+        )?);
+        Ok(())
+    }
+
+    fn rows_mut(&mut self) -> &mut C {
+        &mut self.rows
+    }
+
+    fn maybe_flush(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.rows.len() >= ROWS_PER_BATCH {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.rows.len() == 0 {
+            return Ok(());
+        }
+        let rows = std::mem::take(&mut self.rows);
+        let batch = RecordBatch::try_new(self.schema.clone(), rows.into_arrays())?;
+        self.writer
+            .as_mut()
+            .expect("partition started before rows were added")
+            .write(&batch)
+    }
+
+    fn finish_partition(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.flush()?;
+        if let Some(writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+struct Exporter {
+    transactions: Option<TableExporter<TransactionColumns>>,
+    instructions: Option<TableExporter<InstructionColumns>>,
+    balances: Option<TableExporter<BalanceColumns>>,
+    rewards: Option<TableExporter<RewardColumns>>,
+    epoch: Option<Epoch>,
+}
+
+impl Exporter {
+    fn new(output_dir: &Path, format: ExportFormat, tables: &[ExportTable]) -> Self {
+        let enabled = |table| tables.contains(&table);
+        Self {
+            transactions: enabled(ExportTable::Transactions)
+                .then(|| TableExporter::new(output_dir, format)),
+            instructions: enabled(ExportTable::Instructions)
+                .then(|| TableExporter::new(output_dir, format)),
+            balances: enabled(ExportTable::Balances)
+                .then(|| TableExporter::new(output_dir, format)),
+            rewards: enabled(ExportTable::Rewards).then(|| TableExporter::new(output_dir, format)),
+            epoch: None,
+        }
+    }
+
+    fn add_block(
+        &mut self,
+        slot: Slot,
+        epoch: Epoch,
+        block: ConfirmedBlock,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.epoch != Some(epoch) {
+            if let Some(exporter) = self.transactions.as_mut() {
+                exporter.start_partition(epoch, slot)?;
+            }
+            if let Some(exporter) = self.instructions.as_mut() {
+                exporter.start_partition(epoch, slot)?;
+            }
+            if let Some(exporter) = self.balances.as_mut() {
+                exporter.start_partition(epoch, slot)?;
+            }
+            if let Some(exporter) = self.rewards.as_mut() {
+                exporter.start_partition(epoch, slot)?;
+            }
+            self.epoch = Some(epoch);
+        }
+
+        for (transaction_index, transaction) in block.transactions.iter().enumerate() {
+            self.add_transaction(slot, block.block_time, transaction_index, transaction);
+        }
+
+        if let Some(exporter) = self.rewards.as_mut() {
+            let rows = exporter.rows_mut();
+            for reward in &block.rewards {
+                rows.slot.push(slot);
+                rows.pubkey.push(reward.pubkey.clone());
+                rows.lamports.push(reward.lamports);
+                rows.post_balance.push(reward.post_balance);
+                rows.reward_type.push(
+                    reward
+                        .reward_type
+                        .map(|reward_type| reward_type.to_string()),
+                );
+                rows.commission.push(reward.commission);
+            }
+        }
+
+        self.maybe_flush()
+    }
+
+    fn add_transaction(
+        &mut self,
+        slot: Slot,
+        block_time: Option<i64>,
+        transaction_index: usize,
+        transaction: &TransactionWithStatusMeta,
+    ) {
+        let message = &transaction.transaction.message;
+        let signature = transaction
+            .transaction
+            .signatures
+            .get(0)
+            .map(|signature| signature.to_string())
+            .unwrap_or_default();
+        let meta = transaction.meta.as_ref();
+
+        // Resolve the full account key list, including any keys loaded from address lookup
+        // tables, so that instruction accounts and balances can be attributed
+        let resolved_message =
+            meta.and_then(|meta| resolve_message(message, &meta.loaded_addresses));
+        let (account_keys, instructions): (&[Pubkey], &[CompiledInstruction]) =
+            match &resolved_message {
+                Some(resolved_message) => (
+                    &resolved_message.account_keys,
+                    &resolved_message.instructions,
+                ),
+                None => (message.static_account_keys(), message.instructions()),
+            };
+
+        if let Some(exporter) = self.transactions.as_mut() {
+            let rows = exporter.rows_mut();
+            rows.slot.push(slot);
+            rows.block_time.push(block_time);
+            rows.transaction_index.push(transaction_index as u64);
+            rows.signature.push(signature.clone());
+            rows.version.push(match message {
+                VersionedMessage::Legacy(_) => "legacy".to_string(),
+                VersionedMessage::V0(_) => "0".to_string(),
+            });
+            rows.recent_blockhash
+                .push(message.recent_blockhash().to_string());
+            rows.success.push(meta.map(|meta| meta.status.is_ok()));
+            rows.err.push(
+                meta.and_then(|meta| meta.status.as_ref().err())
+                    .map(|err| err.to_string()),
+            );
+            rows.fee.push(meta.map(|meta| meta.fee));
+            rows.num_signatures
+                .push(transaction.transaction.signatures.len() as u64);
+            rows.num_accounts.push(account_keys.len() as u64);
+            rows.num_instructions.push(instructions.len() as u64);
+        }
+
+        if let Some(exporter) = self.instructions.as_mut() {
+            let rows = exporter.rows_mut();
+            for (instruction_index, instruction) in instructions.iter().enumerate() {
+                push_instruction(
+                    rows,
+                    slot,
+                    &signature,
+                    instruction_index as u64,
+                    None,
+                    instruction,
+                    account_keys,
+                );
+            }
+            let inner_instructions = meta.and_then(|meta| meta.inner_instructions.as_ref());
+            for inner_instructions in inner_instructions.into_iter().flatten() {
+                for (inner_instruction_index, instruction) in
+                    inner_instructions.instructions.iter().enumerate()
+                {
+                    push_instruction(
+                        rows,
+                        slot,
+                        &signature,
+                        inner_instructions.index as u64,
+                        Some(inner_instruction_index as u64),
+                        instruction,
+                        account_keys,
+                    );
+                }
+            }
+        }
+
+        if let Some(exporter) = self.balances.as_mut() {
+            let rows = exporter.rows_mut();
+            let token_balances = |token_balances: Option<&Vec<TransactionTokenBalance>>| {
+                token_balances
+                    .into_iter()
+                    .flatten()
+                    .map(|token_balance| (token_balance.account_index as usize, token_balance))
+                    .collect::<HashMap<_, _>>()
+            };
+            let pre_token_balances =
+                token_balances(meta.and_then(|meta| meta.pre_token_balances.as_ref()));
+            let post_token_balances =
+                token_balances(meta.and_then(|meta| meta.post_token_balances.as_ref()));
+
+            for (account_index, pubkey) in account_keys.iter().enumerate() {
+                let pre_token_balance = pre_token_balances.get(&account_index);
+                let post_token_balance = post_token_balances.get(&account_index);
+                let token_balance = pre_token_balance.or(post_token_balance);
+
+                rows.slot.push(slot);
+                rows.signature.push(signature.clone());
+                rows.account_index.push(account_index as u64);
+                rows.pubkey.push(pubkey.to_string());
+                rows.pre_balance
+                    .push(meta.and_then(|meta| meta.pre_balances.get(account_index).copied()));
+                rows.post_balance
+                    .push(meta.and_then(|meta| meta.post_balances.get(account_index).copied()));
+                rows.mint
+                    .push(token_balance.map(|token_balance| token_balance.mint.clone()));
+                rows.token_decimals.push(
+                    token_balance.map(|token_balance| token_balance.ui_token_amount.decimals),
+                );
+                rows.pre_token_amount.push(
+                    pre_token_balance
+                        .map(|token_balance| token_balance.ui_token_amount.amount.clone()),
+                );
+                rows.post_token_amount.push(
+                    post_token_balance
+                        .map(|token_balance| token_balance.ui_token_amount.amount.clone()),
+                );
+            }
+        }
+    }
+
+    fn maybe_flush(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(exporter) = self.transactions.as_mut() {
+            exporter.maybe_flush()?;
+        }
+        if let Some(exporter) = self.instructions.as_mut() {
+            exporter.maybe_flush()?;
+        }
+        if let Some(exporter) = self.balances.as_mut() {
+            exporter.maybe_flush()?;
+        }
+        if let Some(exporter) = self.rewards.as_mut() {
+            exporter.maybe_flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(exporter) = self.transactions.as_mut() {
+            exporter.finish_partition()?;
+        }
+        if let Some(exporter) = self.instructions.as_mut() {
+            exporter.finish_partition()?;
+        }
+        if let Some(exporter) = self.balances.as_mut() {
+            exporter.finish_partition()?;
+        }
+        if let Some(exporter) = self.rewards.as_mut() {
+            exporter.finish_partition()?;
+        }
+        Ok(())
+    }
+}
+
+fn push_instruction(
+    rows: &mut InstructionColumns,
+    slot: Slot,
+    signature: &str,
+    instruction_index: u64,
+    inner_instruction_index: Option<u64>,
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+) {
+    let program_id = account_keys.get(instruction.program_id_index as usize);
+    let parsed_instruction = program_id.and_then(|program_id| {
+        parse_instruction::parse(program_id, instruction, account_keys).ok()
+    });
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|index| {
+            account_keys
+                .get(*index as usize)
+                .map(|pubkey| pubkey.to_string())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    rows.slot.push(slot);
+    rows.signature.push(signature.to_string());
+    rows.instruction_index.push(instruction_index);
+    rows.inner_instruction_index.push(inner_instruction_index);
+    rows.program_id
+        .push(program_id.map(|program_id| program_id.to_string()));
+    rows.accounts
+        .push(serde_json::to_string(&accounts).unwrap_or_default());
+    rows.data
+        .push(bs58::encode(&instruction.data).into_string());
+    rows.program.push(
+        parsed_instruction
+            .as_ref()
+            .map(|parsed_instruction| parsed_instruction.program.clone()),
+    );
+    rows.parsed.push(
+        parsed_instruction
+            .as_ref()
+            .map(|parsed_instruction| parsed_instruction.parsed.to_string()),
+    );
+}
+
+fn export(
+    blockstore: &Blockstore,
+    epoch_schedule: &EpochSchedule,
+    starting_slot: Slot,
+    ending_slot: Slot,
+    output_dir: &Path,
+    format: ExportFormat,
+    tables: &[ExportTable],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut exporter = Exporter::new(output_dir, format, tables);
+    let mut num_blocks = 0;
+
+    for slot in blockstore.rooted_slot_iterator(starting_slot)? {
+        if slot > ending_slot {
+            break;
+        }
+        // A gap would silently drop rows from every table, so refuse to export around it
+        let block = blockstore
+            .get_rooted_block(slot, false)
+            .map_err(|err| format!("Unable to read rooted block in slot {}: {:?}", slot, err))?;
+        exporter.add_block(slot, epoch_schedule.get_epoch(slot), block)?;
+        num_blocks += 1;
+    }
+
+    exporter.finish()?;
+    println!("Exported {} blocks to {}", num_blocks, output_dir.display());
+    Ok(())
+}
+
+pub trait ExportSubCommand {
+    fn export_subcommand(self) -> Self;
+}
+
+impl ExportSubCommand for App<'_, '_> {
+    fn export_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("export")
+                .about(
+                    "Export rooted blocks to columnar files, partitioned by epoch, \
+                     for offline analysis",
+                )
+                .arg(
+                    Arg::with_name("output_dir")
+                        .long("output-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Write the exported tables into this directory"),
+                )
+                .arg(
+                    Arg::with_name("starting_slot")
+                        .long("starting-slot")
+                        .validator(is_slot)
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Start exporting at this slot"),
+                )
+                .arg(
+                    Arg::with_name("ending_slot")
+                        .long("ending-slot")
+                        .validator(is_slot)
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .help("Stop exporting at this slot [default: last rooted slot]"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(&["parquet", "arrow"])
+                        .default_value("parquet")
+                        .help("File format: Parquet or Arrow IPC"),
+                )
+                .arg(
+                    Arg::with_name("tables")
+                        .long("tables")
+                        .value_name("TABLE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .possible_values(&["transactions", "instructions", "balances", "rewards"])
+                        .default_value("transactions,instructions,balances,rewards")
+                        .help("Tables to export"),
+                ),
+        )
+    }
+}
+
+pub fn export_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
+    let output_dir = PathBuf::from(value_t_or_exit!(matches, "output_dir", String));
+    let starting_slot = value_t_or_exit!(matches, "starting_slot", Slot);
+    let ending_slot = value_t!(matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+    let format = value_t_or_exit!(matches, "format", ExportFormat);
+    let tables = values_t_or_exit!(matches, "tables", ExportTable);
+
+    let genesis_config = open_genesis_config(ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE);
+    let blockstore = crate::open_blockstore(ledger_path, AccessType::TryPrimaryThenSecondary, None);
+
+    export(
+        &blockstore,
+        &genesis_config.epoch_schedule,
+        starting_slot,
+        ending_slot,
+        &output_dir,
+        format,
+        &tables,
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("{:?}", err);
+        exit(1);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        arrow::{array::Array, ipc::reader::FileReader},
+        parquet::{
+            arrow::{ArrowReader, ParquetFileArrowReader},
+            file::reader::SerializedFileReader,
+        },
+        solana_ledger::{blockstore::make_many_slot_entries, get_tmp_ledger_path_auto_delete},
+        tempfile::TempDir,
+    };
+
+    fn check_schema<C: Columns>() {
+        let schema = Arc::new(C::schema());
+        let batch = RecordBatch::try_new(schema.clone(), C::default().into_arrays()).unwrap();
+        assert_eq!(batch.schema(), schema);
+    }
+
+    #[test]
+    fn test_columns_match_schema() {
+        check_schema::<TransactionColumns>();
+        check_schema::<InstructionColumns>();
+        check_schema::<BalanceColumns>();
+        check_schema::<RewardColumns>();
+    }
+
+    fn read_batches(path: &Path, format: ExportFormat) -> Vec<RecordBatch> {
+        let file = File::open(path).unwrap();
+        match format {
+            ExportFormat::Parquet => {
+                let reader = SerializedFileReader::new(file).unwrap();
+                let mut reader = ParquetFileArrowReader::new(Arc::new(reader));
+                reader
+                    .get_record_reader(ROWS_PER_BATCH)
+                    .unwrap()
+                    .map(Result::unwrap)
+                    .collect()
+            }
+            ExportFormat::Arrow => FileReader::try_new(file)
+                .unwrap()
+                .map(Result::unwrap)
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_table_exporter() {
+        for format in [ExportFormat::Parquet, ExportFormat::Arrow].iter().copied() {
+            let output_dir = TempDir::new().unwrap();
+            let mut exporter = TableExporter::<RewardColumns>::new(output_dir.path(), format);
+            exporter.start_partition(3, 100).unwrap();
+            let rows = exporter.rows_mut();
+            for (slot, commission) in [(100, Some(5)), (101, None)].iter().copied() {
+                rows.slot.push(slot);
+                rows.pubkey.push(Pubkey::new_unique().to_string());
+                rows.lamports.push(-1);
+                rows.post_balance.push(42);
+                rows.reward_type.push(None);
+                rows.commission.push(commission);
+            }
+            exporter.finish_partition().unwrap();
+
+            let path = output_dir
+                .path()
+                .join("rewards/epoch=3")
+                .join(format!("rewards-100.{}", format.extension()));
+            let batches = read_batches(&path, format);
+            assert_eq!(batches.len(), 1);
+            let batch = &batches[0];
+            assert_eq!(batch.schema().fields(), RewardColumns::schema().fields());
+            assert_eq!(batch.num_rows(), 2);
+            let slots = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<UInt64Array>()
+                .unwrap();
+            assert_eq!(slots.values(), &[100, 101]);
+            let commissions = batch
+                .column(5)
+                .as_any()
+                .downcast_ref::<UInt8Array>()
+                .unwrap();
+            assert_eq!(commissions.value(0), 5);
+            assert!(commissions.is_null(1));
+        }
+    }
+
+    #[test]
+    fn test_export_missing_block() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 2, 1);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots(vec![0, 1, 2].iter()).unwrap();
+        let epoch_schedule = EpochSchedule::default();
+        let tables = [ExportTable::Transactions];
+
+        let output_dir = TempDir::new().unwrap();
+        export(
+            &blockstore,
+            &epoch_schedule,
+            0,
+            1,
+            output_dir.path(),
+            ExportFormat::Arrow,
+            &tables,
+        )
+        .unwrap();
+        assert!(output_dir
+            .path()
+            .join("transactions/epoch=0/transactions-0.arrow")
+            .exists());
+
+        // Slot 2 is rooted, but its block is not in the blockstore
+        let output_dir = TempDir::new().unwrap();
+        let err = export(
+            &blockstore,
+            &epoch_schedule,
+            0,
+            2,
+            output_dir.path(),
+            ExportFormat::Arrow,
+            &tables,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unable to read rooted block in slot 2: SlotUnavailable"
+        );
+    }
+}
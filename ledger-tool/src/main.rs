@@ -49,6 +49,7 @@ use solana_sdk::{
     shred_version::compute_shred_version,
     stake::{self, state::StakeState},
     system_program,
+    transaction::TransactionError,
 };
 use solana_stake_program::stake_state::{self, PointValue};
 use solana_vote_program::{
@@ -56,7 +57,6 @@ use solana_vote_program::{
     vote_state::{self, VoteState},
 };
 use std::{
-    borrow::Cow,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     ffi::OsStr,
     fs::{self, File},
@@ -69,6 +69,10 @@ use std::{
 
 mod bigtable;
 use bigtable::*;
+mod export;
+use export::*;
+mod snapshot;
+use snapshot::*;
 
 #[derive(PartialEq)]
 enum LedgerOutputMethod {
@@ -210,9 +214,9 @@ fn output_slot(
             transactions += entry.transactions.len();
             hashes += entry.num_hashes;
             for transaction in &entry.transactions {
-                for instruction in &transaction.message().instructions {
-                    let program_id =
-                        transaction.message().account_keys[instruction.program_id_index as usize];
+                for instruction in transaction.message.instructions() {
+                    let program_id = transaction.message.static_account_keys()
+                        [instruction.program_id_index as usize];
                     *program_ids.entry(program_id).or_insert(0) += 1;
                 }
             }
@@ -695,6 +699,7 @@ fn load_bank_forks(
             snapshot_archive_path.unwrap_or_else(|| blockstore.ledger_path().to_path_buf());
         Some(SnapshotConfig {
             snapshot_interval_slots: 0, // Value doesn't matter
+            incremental_snapshot_archive_interval_slots: Slot::MAX,
             snapshot_package_output_path,
             snapshot_path,
             archive_format: ArchiveFormat::TarBzip2,
@@ -754,19 +759,30 @@ fn compute_slot_cost(blockstore: &Blockstore, slot: Slot) -> Result<(), String>
         transactions += entry.transactions.len();
         let mut cost_model = cost_model.write().unwrap();
         for transaction in &entry.transactions {
-            programs += transaction.message().instructions.len();
-            let transaction =
-                match SanitizedTransaction::try_create(Cow::Borrowed(transaction), Hash::default())
-                {
-                    Ok(tx) => tx,
-                    Err(err) => {
-                        warn!(
-                            "failed to sanitize transaction, err {:?}, tx {:?}",
-                            err, transaction
-                        );
-                        continue;
-                    }
-                };
+            programs += transaction.message.instructions().len();
+            let transaction = match SanitizedTransaction::try_create_versioned(
+                transaction.clone(),
+                Hash::default(),
+                |_| {
+                    // Lookup table accounts may have changed since this slot was
+                    // processed, so use the addresses recorded in its status
+                    blockstore
+                        .read_transaction_status((transaction.signatures[0], slot))
+                        .ok()
+                        .flatten()
+                        .map(|meta| meta.loaded_addresses)
+                        .ok_or(TransactionError::AddressLookupTableNotFound)
+                },
+            ) {
+                Ok(tx) => tx,
+                Err(err) => {
+                    warn!(
+                        "failed to sanitize transaction, err {:?}, tx {:?}",
+                        err, transaction
+                    );
+                    continue;
+                }
+            };
             let tx_cost = cost_model.calculate_cost(&transaction);
             if cost_tracker.try_add(tx_cost).is_err() {
                 println!(
@@ -981,6 +997,8 @@ fn main() {
                 .help("Show additional information where supported"),
         )
         .bigtable_subcommand()
+        .export_subcommand()
+        .snapshot_subcommand()
         .subcommand(
             SubCommand::with_name("print")
             .about("Print the ledger")
@@ -1535,6 +1553,8 @@ fn main() {
 
     match matches.subcommand() {
         ("bigtable", Some(arg_matches)) => bigtable_process_command(&ledger_path, arg_matches),
+        ("export", Some(arg_matches)) => export_process_command(&ledger_path, arg_matches),
+        ("snapshot", Some(arg_matches)) => snapshot_process_command(&ledger_path, arg_matches),
         ("print", Some(arg_matches)) => {
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
@@ -0,0 +1,440 @@
+/// The `snapshot` subcommand
+use clap::{value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand};
+use solana_runtime::{
+    accounts_db::AccountsDb,
+    bank::Bank,
+    hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
+    snapshot_utils::{self, UnpackedSnapshotArchive},
+};
+use solana_sdk::{
+    account::ReadableAccount, clock::Slot, genesis_config::GenesisConfig, hash::Hash,
+    native_token::lamports_to_sol, pubkey::Pubkey, sysvar,
+};
+use std::{
+    path::{Path, PathBuf},
+    process::exit,
+    result::Result,
+};
+use tempfile::TempDir;
+
+type Error = Box<dyn std::error::Error>;
+
+/// Unpack `snapshot_archive` into a new scratch directory under `unpack_dir`.  The account
+/// storages are only removed once the returned `TempDir` is dropped.
+fn unpack(
+    snapshot_archive: &Path,
+    unpack_dir: &Path,
+) -> Result<(UnpackedSnapshotArchive, TempDir), Error> {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("ledger-tool-snapshot-")
+        .tempdir_in(unpack_dir)?;
+    let unpacked_snapshot = snapshot_utils::unpack_snapshot_archive(
+        temp_dir.path(),
+        snapshot_archive,
+        &account_paths(&temp_dir),
+    )?;
+    Ok((unpacked_snapshot, temp_dir))
+}
+
+fn account_paths(temp_dir: &TempDir) -> Vec<PathBuf> {
+    vec![temp_dir.path().join("accounts")]
+}
+
+fn inspect(snapshot_archive: &Path) -> Result<(), Error> {
+    // Only the archive's headers are read, nothing is unpacked
+    let contents = snapshot_utils::inspect_snapshot_archive(snapshot_archive)?;
+
+    println!("Snapshot archive: {}", snapshot_archive.display());
+    if let Some(base_slot) = contents.base_slot {
+        println!(
+            "  Incremental snapshot of full snapshot slot: {}",
+            base_slot
+        );
+    }
+    println!("  Slot: {}", contents.archive_slot);
+    println!("  Accounts hash: {}", contents.archive_hash);
+    println!("  Snapshot version: {}", contents.snapshot_version);
+    println!(
+        "  Account storages: {}, {} bytes",
+        contents.storages.len(),
+        contents
+            .storages
+            .iter()
+            .map(|(_slot, _file_name, size)| size)
+            .sum::<u64>()
+    );
+    for (slot, file_name, size) in &contents.storages {
+        println!("    slot {}: {} ({} bytes)", slot, file_name, size);
+    }
+    Ok(())
+}
+
+/// A bank rebuilt from snapshot archives
+struct SnapshotBank {
+    bank: Bank,
+    /// Accounts hash in the name of the latest archive that the bank was rebuilt from
+    archive_hash: Hash,
+    /// Slot of the full snapshot, if the bank was also rebuilt from an incremental snapshot
+    full_snapshot_slot: Option<Slot>,
+    // Holds the bank's account storages, so must be dropped after the bank
+    _temp_dir: TempDir,
+}
+
+fn load_bank(
+    genesis_config: &GenesisConfig,
+    full_snapshot_archive: &Path,
+    incremental_snapshot_archive: Option<&Path>,
+    unpack_dir: &Path,
+) -> Result<SnapshotBank, Error> {
+    let print_summary = |snapshot_archive: &Path, unpacked_snapshot: &UnpackedSnapshotArchive| {
+        println!(
+            "{}: slot {}, snapshot version {}, {} account storages",
+            snapshot_archive.display(),
+            unpacked_snapshot.archive_slot,
+            unpacked_snapshot.snapshot_version,
+            unpacked_snapshot.storages.len()
+        )
+    };
+
+    let (full_snapshot, temp_dir) = unpack(full_snapshot_archive, unpack_dir)?;
+    print_summary(full_snapshot_archive, &full_snapshot);
+    let account_paths = account_paths(&temp_dir);
+    let incremental_snapshot = match incremental_snapshot_archive {
+        Some(incremental_snapshot_archive) => {
+            let incremental_snapshot = snapshot_utils::unpack_snapshot_archive(
+                temp_dir.path(),
+                incremental_snapshot_archive,
+                &account_paths,
+            )?;
+            print_summary(incremental_snapshot_archive, &incremental_snapshot);
+            Some(incremental_snapshot)
+        }
+        None => None,
+    };
+
+    let (archive_hash, full_snapshot_slot) = match &incremental_snapshot {
+        Some(incremental_snapshot) => (
+            incremental_snapshot.archive_hash,
+            Some(full_snapshot.archive_slot),
+        ),
+        None => (full_snapshot.archive_hash, None),
+    };
+    let bank = snapshot_utils::bank_from_unpacked_snapshot_archives(
+        &account_paths,
+        full_snapshot,
+        incremental_snapshot,
+        genesis_config,
+        None,
+    )?;
+    Ok(SnapshotBank {
+        bank,
+        archive_hash,
+        full_snapshot_slot,
+        _temp_dir: temp_dir,
+    })
+}
+
+fn verify(
+    genesis_config: &GenesisConfig,
+    full_snapshot_archive: &Path,
+    incremental_snapshot_archive: Option<&Path>,
+    unpack_dir: &Path,
+) -> Result<(), Error> {
+    // Unpacking validates the snapshot version and the slot in the archive name
+    let snapshot_bank = load_bank(
+        genesis_config,
+        full_snapshot_archive,
+        incremental_snapshot_archive,
+        unpack_dir,
+    )?;
+    let bank = &snapshot_bank.bank;
+
+    let accounts_hash = bank.get_accounts_hash();
+    if accounts_hash != snapshot_bank.archive_hash {
+        return Err(format!(
+            "accounts hash in the bank fields ({}) does not match the archive name ({})",
+            accounts_hash, snapshot_bank.archive_hash
+        )
+        .into());
+    }
+
+    println!("Recomputing the accounts hash of slot {}...", bank.slot());
+    if !bank.verify_snapshot_bank(false, snapshot_bank.full_snapshot_slot) {
+        return Err(format!(
+            "recomputed hashes of slot {} do not match the bank fields",
+            bank.slot()
+        )
+        .into());
+    }
+    println!(
+        "Snapshot verified: slot {}, accounts hash {}, capitalization {} SOL",
+        bank.slot(),
+        accounts_hash,
+        lamports_to_sol(bank.capitalization())
+    );
+    Ok(())
+}
+
+/// What `diff` compares and reports of an account
+struct AccountSummary {
+    /// Hash of the account's contents.  The slot the account was last stored in is left out, as
+    /// it differs between snapshots even when the contents do not.
+    hash: Hash,
+    lamports: u64,
+    owner: Pubkey,
+    data_len: usize,
+}
+
+/// Summaries of the accounts in a snapshot archive, sorted by pubkey
+fn load_account_summaries(
+    genesis_config: &GenesisConfig,
+    snapshot_archive: &Path,
+    unpack_dir: &Path,
+    include_sysvars: bool,
+) -> Result<Vec<(Pubkey, AccountSummary)>, Error> {
+    let snapshot_bank = load_bank(genesis_config, snapshot_archive, None, unpack_dir)?;
+    let mut accounts: Vec<_> = snapshot_bank
+        .bank
+        .get_all_accounts_with_modified_slots()
+        .map_err(|err| format!("failed to scan accounts: {:?}", err))?
+        .into_iter()
+        .filter(|(pubkey, _account, _slot)| include_sysvars || !sysvar::is_sysvar_id(pubkey))
+        .map(|(pubkey, account, _slot)| {
+            let summary = AccountSummary {
+                hash: AccountsDb::hash_account(0, &account, &pubkey),
+                lamports: account.lamports(),
+                owner: *account.owner(),
+                data_len: account.data().len(),
+            };
+            (pubkey, summary)
+        })
+        .collect();
+    accounts.sort_unstable_by_key(|(pubkey, _summary)| *pubkey);
+    Ok(accounts)
+}
+
+fn diff(
+    genesis_config: &GenesisConfig,
+    snapshot_archive_a: &Path,
+    snapshot_archive_b: &Path,
+    unpack_dir: &Path,
+    include_sysvars: bool,
+) -> Result<(), Error> {
+    // Only one bank is held at a time, and only the summaries of its accounts are kept
+    let accounts_a = load_account_summaries(
+        genesis_config,
+        snapshot_archive_a,
+        unpack_dir,
+        include_sysvars,
+    )?;
+    let accounts_b = load_account_summaries(
+        genesis_config,
+        snapshot_archive_b,
+        unpack_dir,
+        include_sysvars,
+    )?;
+
+    let print_only_in = |sign: char, pubkey: &Pubkey, account: &AccountSummary| {
+        println!(
+            "{} {}: {} SOL, owner {}",
+            sign,
+            pubkey,
+            lamports_to_sol(account.lamports),
+            account.owner
+        );
+    };
+    let mut only_in_a = 0;
+    let mut only_in_b = 0;
+    let mut changed = 0;
+    let mut unchanged = 0;
+    // Walk both sides in pubkey order
+    let mut accounts_a = accounts_a.iter().peekable();
+    let mut accounts_b = accounts_b.iter().peekable();
+    loop {
+        match (accounts_a.peek(), accounts_b.peek()) {
+            (None, None) => break,
+            (Some((pubkey_a, account_a)), Some((pubkey_b, _))) if pubkey_a < pubkey_b => {
+                only_in_a += 1;
+                print_only_in('-', pubkey_a, account_a);
+                accounts_a.next();
+            }
+            (Some((pubkey, account_a)), None) => {
+                only_in_a += 1;
+                print_only_in('-', pubkey, account_a);
+                accounts_a.next();
+            }
+            (Some((pubkey_a, account_a)), Some((pubkey_b, account_b))) if pubkey_a == pubkey_b => {
+                if account_a.hash == account_b.hash {
+                    unchanged += 1;
+                } else {
+                    changed += 1;
+                    println!(
+                        "~ {}: {} SOL -> {} SOL, data_len {} -> {}, owner {} -> {}",
+                        pubkey_a,
+                        lamports_to_sol(account_a.lamports),
+                        lamports_to_sol(account_b.lamports),
+                        account_a.data_len,
+                        account_b.data_len,
+                        account_a.owner,
+                        account_b.owner
+                    );
+                }
+                accounts_a.next();
+                accounts_b.next();
+            }
+            (_, Some((pubkey, account_b))) => {
+                only_in_b += 1;
+                print_only_in('+', pubkey, account_b);
+                accounts_b.next();
+            }
+        }
+    }
+
+    println!(
+        "{} accounts only in {}, {} only in {}, {} changed, {} unchanged",
+        only_in_a,
+        snapshot_archive_a.display(),
+        only_in_b,
+        snapshot_archive_b.display(),
+        changed,
+        unchanged
+    );
+    Ok(())
+}
+
+pub trait SnapshotSubCommand {
+    fn snapshot_subcommand(self) -> Self;
+}
+
+impl SnapshotSubCommand for App<'_, '_> {
+    fn snapshot_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("snapshot")
+                .about("Check snapshot archives without loading them into the ledger")
+                .setting(AppSettings::InferSubcommands)
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .arg(
+                    Arg::with_name("unpack_dir")
+                        .long("unpack-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .global(true)
+                        .help(
+                            "Unpack snapshot archives into a temporary directory under DIR \
+                             [default: the ledger directory]",
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("inspect")
+                        .about("Show the version, slot and account storages of a snapshot archive")
+                        .arg(
+                            Arg::with_name("snapshot_archive")
+                                .value_name("SNAPSHOT_ARCHIVE")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("Full or incremental snapshot archive"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about(
+                            "Rebuild the bank from a snapshot archive and recompute its \
+                             accounts hash",
+                        )
+                        .arg(
+                            Arg::with_name("snapshot_archive")
+                                .value_name("SNAPSHOT_ARCHIVE")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("Full snapshot archive"),
+                        )
+                        .arg(
+                            Arg::with_name("incremental_snapshot_archive")
+                                .long("incremental-snapshot-archive")
+                                .value_name("INCREMENTAL_SNAPSHOT_ARCHIVE")
+                                .takes_value(true)
+                                .help(
+                                    "Verify this incremental snapshot archive, based on the \
+                                     full snapshot archive",
+                                ),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("diff")
+                        .about("Compare the accounts of two full snapshot archives by pubkey")
+                        .arg(
+                            Arg::with_name("snapshot_archive_a")
+                                .value_name("SNAPSHOT_ARCHIVE_A")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("Full snapshot archive"),
+                        )
+                        .arg(
+                            Arg::with_name("snapshot_archive_b")
+                                .value_name("SNAPSHOT_ARCHIVE_B")
+                                .takes_value(true)
+                                .index(2)
+                                .required(true)
+                                .help("Full snapshot archive to compare against"),
+                        )
+                        .arg(
+                            Arg::with_name("include_sysvars")
+                                .long("include-sysvars")
+                                .takes_value(false)
+                                .help("Include sysvars too"),
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn snapshot_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
+    let unpack_dir = matches
+        .value_of("unpack_dir")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| ledger_path.to_path_buf());
+    let genesis_config = || open_genesis_config(ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE);
+
+    let result = match matches.subcommand() {
+        ("inspect", Some(arg_matches)) => {
+            let snapshot_archive =
+                PathBuf::from(value_t_or_exit!(arg_matches, "snapshot_archive", String));
+            inspect(&snapshot_archive)
+        }
+        ("verify", Some(arg_matches)) => {
+            let snapshot_archive =
+                PathBuf::from(value_t_or_exit!(arg_matches, "snapshot_archive", String));
+            let incremental_snapshot_archive = arg_matches
+                .value_of("incremental_snapshot_archive")
+                .map(PathBuf::from);
+            verify(
+                &genesis_config(),
+                &snapshot_archive,
+                incremental_snapshot_archive.as_deref(),
+                &unpack_dir,
+            )
+        }
+        ("diff", Some(arg_matches)) => {
+            let snapshot_archive_a =
+                PathBuf::from(value_t_or_exit!(arg_matches, "snapshot_archive_a", String));
+            let snapshot_archive_b =
+                PathBuf::from(value_t_or_exit!(arg_matches, "snapshot_archive_b", String));
+            diff(
+                &genesis_config(),
+                &snapshot_archive_a,
+                &snapshot_archive_b,
+                &unpack_dir,
+                arg_matches.is_present("include_sysvars"),
+            )
+        }
+        _ => unreachable!(),
+    };
+
+    result.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    });
+}
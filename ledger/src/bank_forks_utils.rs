@@ -11,7 +11,7 @@ use solana_entry::entry::VerifyRecyclers;
 use solana_runtime::{
     bank_forks::BankForks,
     snapshot_config::SnapshotConfig,
-    snapshot_utils::{self, FullSnapshotArchiveInfo},
+    snapshot_utils::{self, FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo},
 };
 use solana_sdk::{clock::Slot, genesis_config::GenesisConfig, hash::Hash};
 use std::{fs, path::PathBuf, process, result};
@@ -32,8 +32,8 @@ fn to_loadresult(
 
 /// Load the banks and accounts
 ///
-/// If a snapshot config is given, and a snapshot is found, it will be loaded.  Otherwise, load
-/// from genesis.
+/// If a snapshot config is given, and a snapshot is found, it will be loaded, along with the
+/// newest incremental snapshot built on top of it, if any.  Otherwise, load from genesis.
 pub fn load(
     genesis_config: &GenesisConfig,
     blockstore: &Blockstore,
@@ -58,6 +58,11 @@ pub fn load(
                 &snapshot_config.snapshot_package_output_path,
             )
         {
+            let incremental_snapshot_archive_info =
+                snapshot_utils::get_highest_incremental_snapshot_archive_info(
+                    &snapshot_config.snapshot_package_output_path,
+                    *full_snapshot_archive_info.slot(),
+                );
             return load_from_snapshot(
                 genesis_config,
                 blockstore,
@@ -68,6 +73,7 @@ pub fn load(
                 transaction_status_sender,
                 cache_block_meta_sender,
                 &full_snapshot_archive_info,
+                incremental_snapshot_archive_info.as_ref(),
             );
         } else {
             info!("No snapshot package available; will load from genesis");
@@ -116,11 +122,18 @@ fn load_from_snapshot(
     transaction_status_sender: Option<&TransactionStatusSender>,
     cache_block_meta_sender: Option<&CacheBlockMetaSender>,
     full_snapshot_archive_info: &FullSnapshotArchiveInfo,
+    incremental_snapshot_archive_info: Option<&IncrementalSnapshotArchiveInfo>,
 ) -> LoadResult {
     info!(
         "Loading snapshot package: {:?}",
         full_snapshot_archive_info.path()
     );
+    if let Some(incremental_snapshot_archive_info) = incremental_snapshot_archive_info {
+        info!(
+            "Loading incremental snapshot package: {:?}",
+            incremental_snapshot_archive_info.path()
+        );
+    }
 
     // Fail hard here if snapshot fails to load, don't silently continue
     if account_paths.is_empty() {
@@ -133,7 +146,7 @@ fn load_from_snapshot(
         &process_options.frozen_accounts,
         &snapshot_config.snapshot_path,
         full_snapshot_archive_info.path(),
-        None,
+        incremental_snapshot_archive_info.map(|archive_info| archive_info.path()),
         *full_snapshot_archive_info.archive_format(),
         genesis_config,
         process_options.debug_keys.clone(),
@@ -144,30 +157,33 @@ fn load_from_snapshot(
         process_options.shrink_ratio,
         process_options.accounts_db_test_hash_calculation,
         process_options.verify_index,
+        process_options.accounts_db_config.clone(),
     )
     .expect("Load from snapshot failed");
     if let Some(shrink_paths) = shrink_paths {
         deserialized_bank.set_shrink_paths(shrink_paths);
     }
+    if let Some(accounts_update_notifier) = &process_options.accounts_update_notifier {
+        deserialized_bank.set_accounts_update_notifier(accounts_update_notifier.clone());
+    }
 
     let deserialized_bank_slot_and_hash = (
         deserialized_bank.slot(),
         deserialized_bank.get_accounts_hash(),
     );
 
-    if deserialized_bank_slot_and_hash
-        != (
+    // The bank is rebuilt at the slot of the incremental snapshot, if one was loaded
+    let archive_slot_and_hash = incremental_snapshot_archive_info.map_or(
+        (
             *full_snapshot_archive_info.slot(),
             *full_snapshot_archive_info.hash(),
-        )
-    {
+        ),
+        |archive_info| (*archive_info.slot(), *archive_info.hash()),
+    );
+    if deserialized_bank_slot_and_hash != archive_slot_and_hash {
         error!(
             "Snapshot has mismatch:\narchive: {:?}\ndeserialized: {:?}",
-            (
-                full_snapshot_archive_info.slot(),
-                full_snapshot_archive_info.hash()
-            ),
-            deserialized_bank_slot_and_hash
+            archive_slot_and_hash, deserialized_bank_slot_and_hash
         );
         process::exit(1);
     }
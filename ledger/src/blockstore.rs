@@ -27,6 +27,7 @@ use solana_metrics::{datapoint_debug, datapoint_error};
 use solana_rayon_threadlimit::get_thread_count;
 use solana_runtime::hardened_unpack::{unpack_genesis_archive, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE};
 use solana_sdk::{
+    account::Account,
     clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND, MS_PER_TICK},
     genesis_config::{GenesisConfig, DEFAULT_GENESIS_ARCHIVE, DEFAULT_GENESIS_FILE},
     hash::Hash,
@@ -35,11 +36,13 @@ use solana_sdk::{
     signature::{Keypair, Signature, Signer},
     timing::timestamp,
     transaction::Transaction,
+    versioned_transaction::VersionedTransaction,
 };
 use solana_storage_proto::{StoredExtendedRewards, StoredTransactionStatusMeta};
 use solana_transaction_status::{
-    ConfirmedBlock, ConfirmedTransaction, ConfirmedTransactionStatusWithSignature, Rewards,
-    TransactionStatusMeta, TransactionWithStatusMeta,
+    AccountModification, ConfirmedBlock, ConfirmedTransaction,
+    ConfirmedTransactionStatusWithSignature, Rewards, TransactionStatusMeta,
+    TransactionWithStatusMeta,
 };
 use std::{
     borrow::Cow,
@@ -49,6 +52,7 @@ use std::{
     convert::TryInto,
     fs,
     io::{Error as IoError, ErrorKind},
+    ops::RangeInclusive,
     path::{Path, PathBuf},
     rc::Rc,
     sync::{
@@ -112,6 +116,18 @@ impl std::fmt::Display for InsertDataShredError {
     }
 }
 
+/// The transactions that changed an account, as found by `Blockstore::get_account_modifications`
+#[derive(Debug, Default, PartialEq)]
+pub struct AccountModifications {
+    pub modifications: Vec<AccountModification>,
+    /// Set when the search stopped before the end of the account's history: the signature of
+    /// the last transaction examined, to continue the search before
+    pub next_before: Option<Signature>,
+    /// The signature of the oldest transaction examined
+    pub last_examined: Option<Signature>,
+    pub num_examined: usize,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CompletedDataSetInfo {
     pub slot: Slot,
@@ -146,6 +162,7 @@ pub struct Blockstore {
     perf_samples_cf: LedgerColumn<cf::PerfSamples>,
     block_height_cf: LedgerColumn<cf::BlockHeight>,
     program_costs_cf: LedgerColumn<cf::ProgramCosts>,
+    account_history_cf: LedgerColumn<cf::AccountHistory>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
     last_root: Arc<RwLock<Slot>>,
     insert_shreds_lock: Arc<Mutex<()>>,
@@ -347,6 +364,7 @@ impl Blockstore {
         let perf_samples_cf = db.column();
         let block_height_cf = db.column();
         let program_costs_cf = db.column();
+        let account_history_cf = db.column();
         let bank_hash_cf = db.column();
 
         let db = Arc::new(db);
@@ -397,6 +415,7 @@ impl Blockstore {
             perf_samples_cf,
             block_height_cf,
             program_costs_cf,
+            account_history_cf,
             bank_hash_cf,
             new_shreds_signals: vec![],
             completed_slots_senders: vec![],
@@ -1969,7 +1988,7 @@ impl Blockstore {
     pub fn map_transactions_to_statuses<'a>(
         &self,
         slot: Slot,
-        iterator: impl Iterator<Item = Transaction> + 'a,
+        iterator: impl Iterator<Item = VersionedTransaction> + 'a,
     ) -> Vec<TransactionWithStatusMeta> {
         iterator
             .map(|transaction| {
@@ -1986,8 +2005,8 @@ impl Blockstore {
     }
 
     /// Initializes the TransactionStatusIndex column family with two records, `0` and `1`,
-    /// which are used as the primary index for entries in the TransactionStatus and
-    /// AddressSignatures columns. At any given time, one primary index is active (ie. new records
+    /// which are used as the primary index for entries in the TransactionStatus,
+    /// AddressSignatures and AccountHistory columns. At any given time, one primary index is active (ie. new records
     /// are stored under this index), the other is frozen.
     fn initialize_transaction_status_index(&self) -> Result<()> {
         self.transaction_status_index_cf
@@ -2001,7 +2020,9 @@ impl Blockstore {
         self.address_signatures_cf.put(
             cf::AddressSignatures::as_index(2),
             &AddressSignatureMeta::default(),
-        )
+        )?;
+        self.account_history_cf
+            .put(cf::AccountHistory::as_index(2), &Account::default())
     }
 
     /// Toggles the active primary index between `0` and `1`, and clears the stored max-slot of the
@@ -2125,6 +2146,66 @@ impl Blockstore {
         Ok(())
     }
 
+    /// Stores account versions, each keyed by the slot and write version of the store that
+    /// produced it, for historical account queries
+    pub fn write_account_history(&self, versions: &[(Slot, Pubkey, u64, Account)]) -> Result<()> {
+        let max_slot = match versions.iter().map(|(slot, ..)| *slot).max() {
+            Some(max_slot) => max_slot,
+            None => return Ok(()),
+        };
+        // Hold the index lock across the batch so that the whole batch lands in one primary index
+        let w_active_transaction_status_index =
+            self.active_transaction_status_index.write().unwrap();
+        let primary_index =
+            self.get_primary_index_to_write(max_slot, &w_active_transaction_status_index)?;
+        let mut batch = self.db.batch()?;
+        for (slot, pubkey, write_version, account) in versions {
+            batch.put::<cf::AccountHistory>(
+                (primary_index, *pubkey, *slot, *write_version),
+                account,
+            )?;
+        }
+        self.db.write(batch)
+    }
+
+    /// Returns the latest version of an account stored in a rooted slot no later than `slot`,
+    /// along with the slot it was stored in. Versions stored in slots that never became roots are
+    /// ignored, so these need not be purged when a fork is abandoned.
+    pub fn read_account_history(
+        &self,
+        pubkey: &Pubkey,
+        slot: Slot,
+    ) -> Result<Option<(Slot, Account)>> {
+        let mut latest: Option<(Slot, u64, Box<[u8]>)> = None;
+        for primary_index in 0..=1 {
+            let index_iterator = self.account_history_cf.iter(IteratorMode::From(
+                (primary_index, *pubkey, slot, u64::MAX),
+                IteratorDirection::Reverse,
+            ))?;
+            for ((i, address, version_slot, write_version), data) in index_iterator {
+                if i != primary_index || address != *pubkey {
+                    break;
+                }
+                if !self.is_root(version_slot) {
+                    continue;
+                }
+                let is_later = latest
+                    .as_ref()
+                    .map_or(true, |(latest_slot, latest_version, _)| {
+                        (version_slot, write_version) > (*latest_slot, *latest_version)
+                    });
+                if is_later {
+                    latest = Some((version_slot, write_version, data));
+                }
+                break;
+            }
+        }
+        match latest {
+            Some((slot, _, data)) => Ok(Some((slot, deserialize(&data)?))),
+            None => Ok(None),
+        }
+    }
+
     fn check_lowest_cleanup_slot(&self, slot: Slot) -> Result<std::sync::RwLockReadGuard<Slot>> {
         // lowest_cleanup_slot is the last slot that was not cleaned up by LedgerCleanupService
         let lowest_cleanup_slot = self.lowest_cleanup_slot.read().unwrap();
@@ -2279,7 +2360,7 @@ impl Blockstore {
         &self,
         slot: Slot,
         signature: Signature,
-    ) -> Result<Option<Transaction>> {
+    ) -> Result<Option<VersionedTransaction>> {
         let slot_entries = self.get_slot_entries(slot, 0)?;
         Ok(slot_entries
             .iter()
@@ -2345,6 +2426,17 @@ impl Blockstore {
         &self,
         pubkey: Pubkey,
         slot: Slot,
+    ) -> Result<Vec<(Slot, Signature)>> {
+        self.find_address_signatures_for_slot_with_filter(pubkey, slot, false)
+    }
+
+    // Like `find_address_signatures_for_slot`, but optionally only returns the signatures of
+    // transactions that locked the address for writing
+    fn find_address_signatures_for_slot_with_filter(
+        &self,
+        pubkey: Pubkey,
+        slot: Slot,
+        writable_only: bool,
     ) -> Result<Vec<(Slot, Signature)>> {
         let (lock, lowest_available_slot) = self.ensure_lowest_cleanup_slot();
         let mut signatures: Vec<(Slot, Signature)> = vec![];
@@ -2358,14 +2450,16 @@ impl Blockstore {
                 ),
                 IteratorDirection::Forward,
             ))?;
-            for ((i, address, transaction_slot, signature), _) in index_iterator {
+            for ((i, address, transaction_slot, signature), meta) in index_iterator {
                 if i != transaction_status_cf_primary_index
                     || transaction_slot > slot
                     || address != pubkey
                 {
                     break;
                 }
-                signatures.push((slot, signature));
+                if !writable_only || is_writable_address_signature(&meta) {
+                    signatures.push((slot, signature));
+                }
             }
         }
         drop(lock);
@@ -2408,6 +2502,131 @@ impl Blockstore {
                 String
             )
         );
+        self.find_confirmed_signatures_for_address(
+            address,
+            highest_slot,
+            before,
+            until,
+            limit,
+            false,
+        )
+    }
+
+    /// Returns the transactions that changed an account, newest first, along with the account's
+    /// balance before and after each. See `AccountModification::from_transaction` for what counts
+    /// as a change. The ledger does not record account data, so a data change is only detected
+    /// in `account_history_slots`, where the account history is complete.
+    ///
+    /// Only transactions that locked the account for writing are examined, and at most
+    /// `max_transactions` of them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_account_modifications(
+        &self,
+        address: Pubkey,
+        highest_slot: Slot, // highest_confirmed_root or highest_confirmed_slot
+        mut before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        max_transactions: usize,
+        account_history_slots: Option<RangeInclusive<Slot>>,
+    ) -> Result<AccountModifications> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            ("method", "get_account_modifications".to_string(), String)
+        );
+        let last_root = self.last_root();
+        let confirmed_unrooted_slots: Vec<_> = AncestorIterator::new_inclusive(highest_slot, self)
+            .take_while(|&slot| slot > last_root)
+            .collect();
+
+        let mut result = AccountModifications::default();
+        loop {
+            let batch_size = limit.min(max_transactions - result.num_examined);
+            if batch_size == 0 {
+                result.next_before = before;
+                return Ok(result);
+            }
+            let candidates = self.find_confirmed_signatures_for_address(
+                address,
+                highest_slot,
+                before,
+                until,
+                batch_size,
+                true,
+            )?;
+            let exhausted = candidates.len() < batch_size;
+            for candidate in candidates {
+                before = Some(candidate.signature);
+                result.last_examined = before;
+                result.num_examined += 1;
+                if let Some(modification) = self.get_account_modification(
+                    address,
+                    candidate,
+                    &confirmed_unrooted_slots,
+                    account_history_slots.as_ref(),
+                )? {
+                    result.modifications.push(modification);
+                    if result.modifications.len() == limit {
+                        result.next_before = before;
+                        return Ok(result);
+                    }
+                }
+            }
+            if exhausted {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn get_account_modification(
+        &self,
+        address: Pubkey,
+        candidate: ConfirmedTransactionStatusWithSignature,
+        confirmed_unrooted_slots: &[Slot],
+        account_history_slots: Option<&RangeInclusive<Slot>>,
+    ) -> Result<Option<AccountModification>> {
+        let transaction = match self
+            .get_transaction_with_status(candidate.signature, confirmed_unrooted_slots)?
+        {
+            Some(ConfirmedTransaction { transaction, .. }) => transaction,
+            None => return Ok(None),
+        };
+        let data_changed = match account_history_slots {
+            Some(slots) if slots.contains(&candidate.slot) => {
+                self.is_account_data_changed_in_slot(&address, candidate.slot)?
+            }
+            _ => false,
+        };
+        Ok(AccountModification::from_transaction(
+            &address,
+            candidate,
+            &transaction,
+            data_changed,
+        ))
+    }
+
+    // Whether the account history shows an account's data or owner changing in `slot`
+    fn is_account_data_changed_in_slot(&self, address: &Pubkey, slot: Slot) -> Result<bool> {
+        let after = match self.read_account_history(address, slot)? {
+            Some((version_slot, account)) if version_slot == slot => account,
+            _ => return Ok(false),
+        };
+        match self.read_account_history(address, slot.saturating_sub(1))? {
+            Some((_, before)) => Ok(before.data != after.data || before.owner != after.owner),
+            // There is no earlier version to compare with
+            None => Ok(false),
+        }
+    }
+
+    fn find_confirmed_signatures_for_address(
+        &self,
+        address: Pubkey,
+        highest_slot: Slot,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        writable_only: bool,
+    ) -> Result<Vec<ConfirmedTransactionStatusWithSignature>> {
         let last_root = self.last_root();
         let confirmed_unrooted_slots: Vec<_> = AncestorIterator::new_inclusive(highest_slot, self)
             .take_while(|&slot| slot > last_root)
@@ -2519,7 +2738,8 @@ impl Blockstore {
 
         // Get signatures in `slot`
         let mut get_initial_slot_timer = Measure::start("get_initial_slot_timer");
-        let mut signatures = self.find_address_signatures_for_slot(address, slot)?;
+        let mut signatures =
+            self.find_address_signatures_for_slot_with_filter(address, slot, writable_only)?;
         signatures.reverse();
         if let Some(excluded_signatures) = before_excluded_signatures.take() {
             address_signatures.extend(
@@ -2551,7 +2771,7 @@ impl Blockstore {
 
             // Iterate through starting_iterator until limit is reached
             while address_signatures.len() < limit {
-                if let Some(((i, key_address, slot, signature), _)) = starting_iterator.next() {
+                if let Some(((i, key_address, slot, signature), meta)) = starting_iterator.next() {
                     if slot == next_max_slot || slot < lowest_slot {
                         break;
                     }
@@ -2559,7 +2779,9 @@ impl Blockstore {
                         && key_address == address
                         && slot >= first_available_block
                     {
-                        if self.is_root(slot) || confirmed_unrooted_slots.contains(&slot) {
+                        if (self.is_root(slot) || confirmed_unrooted_slots.contains(&slot))
+                            && (!writable_only || is_writable_address_signature(&meta))
+                        {
                             address_signatures.push((slot, signature));
                         }
                         continue;
@@ -2570,8 +2792,11 @@ impl Blockstore {
 
             // Handle slots that cross primary indexes
             if next_max_slot >= lowest_slot {
-                let mut signatures =
-                    self.find_address_signatures_for_slot(address, next_max_slot)?;
+                let mut signatures = self.find_address_signatures_for_slot_with_filter(
+                    address,
+                    next_max_slot,
+                    writable_only,
+                )?;
                 signatures.reverse();
                 address_signatures.append(&mut signatures);
             }
@@ -2585,7 +2810,7 @@ impl Blockstore {
             IteratorDirection::Reverse,
         ))?;
         while address_signatures.len() < limit {
-            if let Some(((i, key_address, slot, signature), _)) = next_iterator.next() {
+            if let Some(((i, key_address, slot, signature), meta)) = next_iterator.next() {
                 // Skip next_max_slot, which is already included
                 if slot == next_max_slot {
                     continue;
@@ -2597,7 +2822,9 @@ impl Blockstore {
                     && key_address == address
                     && slot >= first_available_block
                 {
-                    if self.is_root(slot) || confirmed_unrooted_slots.contains(&slot) {
+                    if (self.is_root(slot) || confirmed_unrooted_slots.contains(&slot))
+                        && (!writable_only || is_writable_address_signature(&meta))
+                    {
                         address_signatures.push((slot, signature));
                     }
                     continue;
@@ -3630,6 +3857,13 @@ where
     Ok(())
 }
 
+// Whether an AddressSignatures record is for a transaction that locked the address for writing
+fn is_writable_address_signature(meta: &[u8]) -> bool {
+    deserialize::<AddressSignatureMeta>(meta)
+        .map(|meta| meta.writeable)
+        .unwrap_or(false)
+}
+
 fn is_orphan(meta: &SlotMeta) -> bool {
     // If we have no parent, then this is the head of a detached chain of
     // slots
@@ -4010,6 +4244,7 @@ pub mod tests {
     use solana_sdk::{
         hash::{self, hash, Hash},
         instruction::CompiledInstruction,
+        message::v0::LoadedAddresses,
         packet::PACKET_DATA_SIZE,
         pubkey::Pubkey,
         signature::Signature,
@@ -6194,7 +6429,9 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for (i, _account_key) in
+                    transaction.message.static_account_keys().iter().enumerate()
+                {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -6209,6 +6446,7 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    loaded_addresses: LoadedAddresses::default(),
                 }
                 .into();
                 ledger
@@ -6225,6 +6463,7 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    loaded_addresses: LoadedAddresses::default(),
                 }
                 .into();
                 ledger
@@ -6241,6 +6480,7 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    loaded_addresses: LoadedAddresses::default(),
                 }
                 .into();
                 ledger
@@ -6259,6 +6499,7 @@ pub mod tests {
                         pre_token_balances: Some(vec![]),
                         post_token_balances: Some(vec![]),
                         rewards: Some(vec![]),
+                        loaded_addresses: LoadedAddresses::default(),
                     }),
                 }
             })
@@ -6363,6 +6604,10 @@ pub mod tests {
             let pre_token_balances_vec = vec![];
             let post_token_balances_vec = vec![];
             let rewards_vec = vec![];
+            let test_loaded_addresses = LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique()],
+            };
 
             // result not found
             assert!(transaction_status_cf
@@ -6387,6 +6632,7 @@ pub mod tests {
                 pre_token_balances: Some(pre_token_balances_vec.clone()),
                 post_token_balances: Some(post_token_balances_vec.clone()),
                 rewards: Some(rewards_vec.clone()),
+                loaded_addresses: test_loaded_addresses.clone(),
             }
             .into();
             assert!(transaction_status_cf
@@ -6404,6 +6650,7 @@ pub mod tests {
                 pre_token_balances,
                 post_token_balances,
                 rewards,
+                loaded_addresses,
             } = transaction_status_cf
                 .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                     0,
@@ -6423,6 +6670,7 @@ pub mod tests {
             assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
             assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
             assert_eq!(rewards.unwrap(), rewards_vec);
+            assert_eq!(loaded_addresses, test_loaded_addresses);
 
             // insert value
             let status = TransactionStatusMeta {
@@ -6435,6 +6683,7 @@ pub mod tests {
                 pre_token_balances: Some(pre_token_balances_vec.clone()),
                 post_token_balances: Some(post_token_balances_vec.clone()),
                 rewards: Some(rewards_vec.clone()),
+                loaded_addresses: LoadedAddresses::default(),
             }
             .into();
             assert!(transaction_status_cf
@@ -6452,6 +6701,7 @@ pub mod tests {
                 pre_token_balances,
                 post_token_balances,
                 rewards,
+                loaded_addresses,
             } = transaction_status_cf
                 .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                     0,
@@ -6473,6 +6723,7 @@ pub mod tests {
             assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
             assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
             assert_eq!(rewards.unwrap(), rewards_vec);
+            assert_eq!(loaded_addresses, LoadedAddresses::default());
         }
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
@@ -6704,6 +6955,7 @@ pub mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                loaded_addresses: LoadedAddresses::default(),
             }
             .into();
 
@@ -6900,6 +7152,7 @@ pub mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                loaded_addresses: LoadedAddresses::default(),
             }
             .into();
 
@@ -7064,7 +7317,9 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for (i, _account_key) in
+                    transaction.message.static_account_keys().iter().enumerate()
+                {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -7087,6 +7342,7 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    loaded_addresses: LoadedAddresses::default(),
                 }
                 .into();
                 blockstore
@@ -7105,6 +7361,7 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        loaded_addresses: LoadedAddresses::default(),
                     }),
                 }
             })
@@ -7163,7 +7420,9 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for (i, _account_key) in
+                    transaction.message.static_account_keys().iter().enumerate()
+                {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -7186,6 +7445,7 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    loaded_addresses: LoadedAddresses::default(),
                 }
                 .into();
                 blockstore
@@ -7204,6 +7464,7 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        loaded_addresses: LoadedAddresses::default(),
                     }),
                 }
             })
@@ -7251,6 +7512,55 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_account_history() {
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let pubkey = solana_sdk::pubkey::new_rand();
+            let other = solana_sdk::pubkey::new_rand();
+            let account = |lamports| Account {
+                lamports,
+                ..Account::default()
+            };
+
+            blockstore
+                .write_account_history(&[
+                    (10, pubkey, 1, account(1)),
+                    (10, pubkey, 2, account(2)),
+                    (10, other, 3, account(3)),
+                ])
+                .unwrap();
+            // Purge to freeze index 0
+            blockstore.run_purge(0, 1, PurgeType::PrimaryIndex).unwrap();
+            blockstore
+                .write_account_history(&[(15, pubkey, 4, account(4)), (20, pubkey, 5, account(5))])
+                .unwrap();
+            blockstore.set_roots(vec![10, 20].iter()).unwrap();
+
+            assert_eq!(blockstore.read_account_history(&pubkey, 9).unwrap(), None);
+            // The latest write in a slot wins
+            assert_eq!(
+                blockstore.read_account_history(&pubkey, 10).unwrap(),
+                Some((10, account(2)))
+            );
+            // Slot 15 is not rooted
+            assert_eq!(
+                blockstore.read_account_history(&pubkey, 19).unwrap(),
+                Some((10, account(2)))
+            );
+            assert_eq!(
+                blockstore.read_account_history(&pubkey, 50).unwrap(),
+                Some((20, account(5)))
+            );
+            assert_eq!(
+                blockstore.read_account_history(&other, 50).unwrap(),
+                Some((10, account(3)))
+            );
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_get_confirmed_signatures_for_address() {
         let blockstore_path = get_tmp_ledger_path!();
@@ -7525,7 +7835,7 @@ pub mod tests {
                             .write_transaction_status(
                                 slot,
                                 transaction.signatures[0],
-                                transaction.message.account_keys.iter().collect(),
+                                transaction.message.static_account_keys().iter().collect(),
                                 vec![],
                                 TransactionStatusMeta::default(),
                             )
@@ -7549,7 +7859,7 @@ pub mod tests {
                             .write_transaction_status(
                                 slot,
                                 transaction.signatures[0],
-                                transaction.message.account_keys.iter().collect(),
+                                transaction.message.static_account_keys().iter().collect(),
                                 vec![],
                                 TransactionStatusMeta::default(),
                             )
@@ -7888,6 +8198,206 @@ pub mod tests {
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_get_account_modifications() {
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let address = solana_sdk::pubkey::new_rand();
+            let token_balances = |amount: &str| {
+                Some(vec![TransactionTokenBalance {
+                    account_index: 1,
+                    mint: Pubkey::new_unique().to_string(),
+                    ui_token_amount: UiTokenAmount {
+                        ui_amount: None,
+                        decimals: 0,
+                        amount: amount.to_string(),
+                        ui_amount_string: amount.to_string(),
+                    },
+                }])
+            };
+
+            // (locks address for writing, status, pre and post balances and token amounts of
+            // address), one transaction per slot from slot 2
+            let transactions = [
+                (true, Ok(()), 10, 7, None, None),
+                (true, Ok(()), 7, 7, None, None),
+                (
+                    true,
+                    Err(TransactionError::AccountNotFound),
+                    7,
+                    7,
+                    None,
+                    None,
+                ),
+                (false, Ok(()), 1, 1, None, None),
+                (true, Ok(()), 7, 7, token_balances("5"), token_balances("3")),
+                (true, Ok(()), 7, 7, None, None),
+                (true, Ok(()), 7, 7, None, None),
+            ];
+            let mut signatures = vec![];
+            for (
+                i,
+                (
+                    writable,
+                    status,
+                    pre_balance,
+                    post_balance,
+                    pre_token_balances,
+                    post_token_balances,
+                ),
+            ) in transactions.iter().cloned().enumerate()
+            {
+                let slot = i as Slot + 2;
+                let (keys, program_ids) = if writable {
+                    (vec![address], vec![solana_sdk::pubkey::new_rand()])
+                } else {
+                    (vec![solana_sdk::pubkey::new_rand()], vec![address])
+                };
+                let transaction = Transaction::new_with_compiled_instructions(
+                    &[&Keypair::new()],
+                    &keys,
+                    Hash::default(),
+                    program_ids,
+                    vec![CompiledInstruction::new(2, &(), vec![1])],
+                );
+                let entries = vec![next_entry_mut(
+                    &mut Hash::default(),
+                    0,
+                    vec![transaction.clone()],
+                )];
+                let shreds = entries_to_test_shreds(entries, slot, slot - 1, true, 0);
+                blockstore.insert_shreds(shreds, None, false).unwrap();
+
+                let account_keys = transaction.message.static_account_keys();
+                let (writable_keys, readonly_keys) = account_keys.split_at(2);
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        transaction.signatures[0],
+                        writable_keys.iter().collect(),
+                        readonly_keys.iter().collect(),
+                        TransactionStatusMeta {
+                            status,
+                            pre_balances: vec![0, pre_balance, 0],
+                            post_balances: vec![0, post_balance, 0],
+                            pre_token_balances,
+                            post_token_balances,
+                            ..TransactionStatusMeta::default()
+                        },
+                    )
+                    .unwrap();
+                signatures.push(transaction.signatures[0]);
+            }
+            blockstore
+                .set_roots((1..=8).collect::<Vec<_>>().iter())
+                .unwrap();
+
+            // The account history shows the data of the account changing in slot 7, but not in
+            // slot 8
+            let account = |data: u8| Account {
+                lamports: 7,
+                data: vec![data],
+                ..Account::default()
+            };
+            blockstore
+                .write_account_history(&[
+                    (2, address, 1, account(0)),
+                    (7, address, 2, account(1)),
+                    (8, address, 3, account(1)),
+                ])
+                .unwrap();
+
+            let modification = |i: usize, pre_lamports, post_lamports| AccountModification {
+                signature: signatures[i],
+                slot: i as Slot + 2,
+                err: None,
+                block_time: None,
+                pre_lamports,
+                post_lamports,
+            };
+
+            // Without the account history, only balance and token amount changes are detected.
+            // The failed transaction left the balance unchanged, and the fourth transaction only
+            // read the address.
+            let results = blockstore
+                .get_account_modifications(address, 8, None, None, usize::MAX, usize::MAX, None)
+                .unwrap();
+            assert_eq!(
+                results,
+                AccountModifications {
+                    modifications: vec![modification(4, 7, 7), modification(0, 10, 7)],
+                    next_before: None,
+                    last_examined: Some(signatures[0]),
+                    num_examined: 6,
+                }
+            );
+
+            // Data changes are detected where the account history is complete
+            let results = blockstore
+                .get_account_modifications(
+                    address,
+                    8,
+                    None,
+                    None,
+                    usize::MAX,
+                    usize::MAX,
+                    Some(3..=8),
+                )
+                .unwrap();
+            assert_eq!(
+                results.modifications,
+                vec![
+                    modification(5, 7, 7),
+                    modification(4, 7, 7),
+                    modification(0, 10, 7)
+                ]
+            );
+
+            // Pagination skips over transactions that did not modify the account
+            let results = blockstore
+                .get_account_modifications(address, 8, None, None, 1, usize::MAX, None)
+                .unwrap();
+            assert_eq!(results.modifications, vec![modification(4, 7, 7)]);
+            assert_eq!(results.next_before, Some(signatures[4]));
+            let results = blockstore
+                .get_account_modifications(
+                    address,
+                    8,
+                    results.next_before,
+                    None,
+                    1,
+                    usize::MAX,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(results.modifications, vec![modification(0, 10, 7)]);
+
+            // The work per search is bounded, and the search can be continued from where it
+            // stopped
+            let results = blockstore
+                .get_account_modifications(address, 8, None, None, usize::MAX, 3, None)
+                .unwrap();
+            assert_eq!(results.modifications, vec![modification(4, 7, 7)]);
+            assert_eq!(results.num_examined, 3);
+            assert_eq!(results.next_before, Some(signatures[4]));
+            let results = blockstore
+                .get_account_modifications(
+                    address,
+                    8,
+                    results.next_before,
+                    None,
+                    usize::MAX,
+                    4,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(results.modifications, vec![modification(0, 10, 7)]);
+            assert_eq!(results.next_before, None);
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     #[allow(clippy::same_item_push)]
     fn test_get_last_hash() {
@@ -7934,6 +8444,7 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    loaded_addresses: LoadedAddresses::default(),
                 }
                 .into();
                 transaction_status_cf
@@ -7950,7 +8461,8 @@ pub mod tests {
                 vec![CompiledInstruction::new(1, &(), vec![0])],
             ));
 
-            let map = blockstore.map_transactions_to_statuses(slot, transactions.into_iter());
+            let map = blockstore
+                .map_transactions_to_statuses(slot, transactions.into_iter().map(Into::into));
             assert_eq!(map.len(), 5);
             for (x, m) in map.iter().take(4).enumerate() {
                 assert_eq!(m.meta.as_ref().unwrap().fee, x as u64);
@@ -8481,6 +8993,7 @@ pub mod tests {
                     reward_type: Some(RewardType::Rent),
                     commission: None,
                 }]),
+                loaded_addresses: LoadedAddresses::default(),
             };
             let deprecated_status: StoredTransactionStatusMeta = status.clone().into();
             let protobuf_status: generated::TransactionStatusMeta = status.into();
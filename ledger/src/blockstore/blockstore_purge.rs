@@ -288,6 +288,7 @@ impl Blockstore {
                 .transaction_status_index_cf
                 .compact_range(0, 2)
                 .unwrap_or(false)
+            && self.account_history_cf.compact_range(0, 2).unwrap_or(false)
             && self
                 .rewards_cf
                 .compact_range(from_slot, to_slot)
@@ -334,9 +335,22 @@ impl Blockstore {
                 .flat_map(|entry| entry.transactions)
             {
                 if let Some(&signature) = transaction.signatures.get(0) {
+                    // Addresses loaded from lookup tables are only recorded in the
+                    // transaction status, so it must be read before it is deleted
+                    let loaded_addresses = self
+                        .read_transaction_status((signature, slot))?
+                        .map(|meta| meta.loaded_addresses)
+                        .unwrap_or_default();
                     batch.delete::<cf::TransactionStatus>((0, signature, slot))?;
                     batch.delete::<cf::TransactionStatus>((1, signature, slot))?;
-                    for pubkey in transaction.message.account_keys {
+                    for pubkey in transaction
+                        .message
+                        .static_account_keys()
+                        .iter()
+                        .chain(loaded_addresses.writable.iter())
+                        .chain(loaded_addresses.readonly.iter())
+                        .copied()
+                    {
                         batch.delete::<cf::AddressSignatures>((0, pubkey, slot, signature))?;
                         batch.delete::<cf::AddressSignatures>((1, pubkey, slot, signature))?;
                     }
@@ -383,6 +397,14 @@ impl Blockstore {
                         purged_index,
                         purged_index + 1,
                     )
+                    .is_ok()
+                & self
+                    .db
+                    .delete_range_cf::<cf::AccountHistory>(
+                        write_batch,
+                        purged_index,
+                        purged_index + 1,
+                    )
                     .is_ok();
         }
         Ok(())
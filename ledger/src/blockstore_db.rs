@@ -16,6 +16,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use solana_runtime::hardened_unpack::UnpackError;
 use solana_sdk::{
+    account::Account,
     clock::{Slot, UnixTimestamp},
     pubkey::Pubkey,
     signature::Signature,
@@ -75,6 +76,8 @@ const PERF_SAMPLES_CF: &str = "perf_samples";
 const BLOCK_HEIGHT_CF: &str = "block_height";
 /// Column family for ProgramCosts
 const PROGRAM_COSTS_CF: &str = "program_costs";
+/// Column family for Account History
+const ACCOUNT_HISTORY_CF: &str = "account_history";
 
 // 1 day is chosen for the same reasoning of DEFAULT_COMPACTION_SLOT_INTERVAL
 const PERIODIC_COMPACTION_SECONDS: u64 = 60 * 60 * 24;
@@ -186,6 +189,10 @@ pub mod columns {
     #[derive(Debug)]
     // The program costs column
     pub struct ProgramCosts;
+
+    #[derive(Debug)]
+    /// The account history column
+    pub struct AccountHistory;
 }
 
 pub enum AccessType {
@@ -356,6 +363,10 @@ impl Rocks {
             ProgramCosts::NAME,
             get_cf_options::<ProgramCosts>(&access_type, &oldest_slot),
         );
+        let account_history_cf_descriptor = ColumnFamilyDescriptor::new(
+            AccountHistory::NAME,
+            get_cf_options::<AccountHistory>(&access_type, &oldest_slot),
+        );
         // Don't forget to add to both run_purge_with_stats() and
         // compact_storage() in ledger/src/blockstore/blockstore_purge.rs!!
 
@@ -381,6 +392,7 @@ impl Rocks {
             (PerfSamples::NAME, perf_samples_cf_descriptor),
             (BlockHeight::NAME, block_height_cf_descriptor),
             (ProgramCosts::NAME, program_costs_cf_descriptor),
+            (AccountHistory::NAME, account_history_cf_descriptor),
         ];
         let cf_names: Vec<_> = cfs.iter().map(|c| c.0).collect();
 
@@ -500,6 +512,7 @@ impl Rocks {
             PerfSamples::NAME,
             BlockHeight::NAME,
             ProgramCosts::NAME,
+            AccountHistory::NAME,
         ]
     }
 
@@ -598,6 +611,10 @@ impl TypedColumn for columns::TransactionStatusIndex {
     type Type = blockstore_meta::TransactionStatusIndexMeta;
 }
 
+impl TypedColumn for columns::AccountHistory {
+    type Type = Account;
+}
+
 pub trait ProtobufColumn: Column {
     type Type: prost::Message + Default;
 }
@@ -708,6 +725,44 @@ impl ColumnName for columns::AddressSignatures {
     const NAME: &'static str = ADDRESS_SIGNATURES_CF;
 }
 
+impl Column for columns::AccountHistory {
+    type Index = (u64, Pubkey, Slot, u64);
+
+    fn key((index, pubkey, slot, write_version): (u64, Pubkey, Slot, u64)) -> Vec<u8> {
+        let mut key = vec![0; 8 + 32 + 8 + 8]; // size_of u64 + size_of Pubkey + size_of Slot + size_of u64
+        BigEndian::write_u64(&mut key[0..8], index);
+        key[8..40].clone_from_slice(&pubkey.as_ref()[0..32]);
+        BigEndian::write_u64(&mut key[40..48], slot);
+        BigEndian::write_u64(&mut key[48..56], write_version);
+        key
+    }
+
+    fn index(key: &[u8]) -> (u64, Pubkey, Slot, u64) {
+        let index = BigEndian::read_u64(&key[0..8]);
+        let pubkey = Pubkey::new(&key[8..40]);
+        let slot = BigEndian::read_u64(&key[40..48]);
+        let write_version = BigEndian::read_u64(&key[48..56]);
+        (index, pubkey, slot, write_version)
+    }
+
+    fn primary_index(index: Self::Index) -> u64 {
+        index.0
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.2
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn as_index(index: u64) -> Self::Index {
+        (index, Pubkey::default(), 0, 0)
+    }
+}
+
+impl ColumnName for columns::AccountHistory {
+    const NAME: &'static str = ACCOUNT_HISTORY_CF;
+}
+
 impl Column for columns::TransactionStatusIndex {
     type Index = u64;
 
@@ -15,8 +15,9 @@ use solana_measure::measure::Measure;
 use solana_metrics::{datapoint_error, inc_new_counter_debug};
 use solana_rayon_threadlimit::get_thread_count;
 use solana_runtime::{
-    accounts_db::AccountShrinkThreshold,
+    accounts_db::{AccountShrinkThreshold, AccountsDbConfig},
     accounts_index::AccountSecondaryIndexes,
+    accounts_update_notifier_interface::AccountsUpdateNotifier,
     bank::{
         Bank, ExecuteTimings, InnerInstructionsList, RentDebits, TransactionBalancesSet,
         TransactionExecutionResult, TransactionLogMessages, TransactionResults,
@@ -34,6 +35,7 @@ use solana_sdk::{
     genesis_config::GenesisConfig,
     hash::Hash,
     pubkey::Pubkey,
+    sanitized_transaction::SanitizedTransaction,
     signature::{Keypair, Signature},
     timing,
     transaction::{Result, Transaction, TransactionError},
@@ -45,7 +47,6 @@ use solana_transaction_status::token_balances::{
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
-    convert::TryFrom,
     path::PathBuf,
     result,
     sync::Arc,
@@ -141,7 +142,11 @@ fn execute_batch(
     } = tx_results;
 
     if let Some(transaction_status_sender) = transaction_status_sender {
-        let txs = batch.transactions_iter().cloned().collect();
+        let txs = batch
+            .sanitized_transactions()
+            .iter()
+            .map(|tx| tx.clone().into_owned())
+            .collect();
         let post_token_balances = if record_token_balances {
             collect_token_balances(bank, batch, &mut mint_decimals)
         } else {
@@ -223,7 +228,11 @@ pub fn process_entries(
     let mut timings = ExecuteTimings::default();
     let mut entry_types: Vec<_> = entries
         .iter()
-        .map(EntryType::try_from)
+        .map(|entry| {
+            EntryType::try_from_entry(entry, |tx, message_hash| {
+                bank.sanitize_transaction(tx, message_hash)
+            })
+        })
         .collect::<Result<_>>()?;
     let result = process_entries_with_callback(
         bank,
@@ -386,6 +395,8 @@ pub struct ProcessOptions {
     pub accounts_db_test_hash_calculation: bool,
     pub verify_index: bool,
     pub shrink_ratio: AccountShrinkThreshold,
+    pub accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    pub accounts_db_config: Option<AccountsDbConfig>,
 }
 
 pub fn process_blockstore(
@@ -415,7 +426,11 @@ pub fn process_blockstore(
         opts.accounts_db_caching_enabled,
         opts.shrink_ratio,
         false,
+        opts.accounts_db_config.clone(),
     );
+    if let Some(accounts_update_notifier) = &opts.accounts_update_notifier {
+        bank0.set_accounts_update_notifier(accounts_update_notifier.clone());
+    }
     let bank0 = Arc::new(bank0);
     info!("processing ledger for slot 0...");
     let recyclers = VerifyRecyclers::default();
@@ -812,6 +827,7 @@ pub fn confirm_slot(
         skip_verification,
         bank.libsecp256k1_0_5_upgrade_enabled(),
         bank.verify_tx_signatures_len_enabled(),
+        |tx, message_hash| bank.sanitize_transaction(tx, message_hash),
     )?;
     let transaction_duration_us = timing::duration_as_us(&check_start.elapsed());
 
@@ -1209,7 +1225,7 @@ pub enum TransactionStatusMessage {
 
 pub struct TransactionStatusBatch {
     pub bank: Arc<Bank>,
-    pub transactions: Vec<Transaction>,
+    pub transactions: Vec<SanitizedTransaction<'static>>,
     pub statuses: Vec<TransactionExecutionResult>,
     pub balances: TransactionBalancesSet,
     pub token_balances: TransactionTokenBalancesSet,
@@ -1228,7 +1244,7 @@ impl TransactionStatusSender {
     pub fn send_transaction_status_batch(
         &self,
         bank: Arc<Bank>,
-        transactions: Vec<Transaction>,
+        transactions: Vec<SanitizedTransaction<'static>>,
         statuses: Vec<TransactionExecutionResult>,
         balances: TransactionBalancesSet,
         token_balances: TransactionTokenBalancesSet,
@@ -2420,8 +2436,16 @@ pub mod tests {
         assert_eq!(bank.get_balance(&keypair2.pubkey()), 4);
 
         // Check all accounts are unlocked
-        let txs1 = &entry_1_to_mint.transactions[..];
-        let txs2 = &entry_2_to_3_mint_to_1.transactions[..];
+        let txs1: Vec<_> = entry_1_to_mint
+            .transactions
+            .into_iter()
+            .map(|tx| tx.into_legacy_transaction().unwrap())
+            .collect();
+        let txs2: Vec<_> = entry_2_to_3_mint_to_1
+            .transactions
+            .into_iter()
+            .map(|tx| tx.into_legacy_transaction().unwrap())
+            .collect();
         let batch1 = bank.prepare_batch(txs1.iter()).unwrap();
         for result in batch1.lock_results() {
             assert!(result.is_ok());
@@ -3135,6 +3159,7 @@ pub mod tests {
             false,
             AccountShrinkThreshold::default(),
             false,
+            None,
         );
         *bank.epoch_schedule()
     }
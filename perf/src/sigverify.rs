@@ -5,18 +5,22 @@
 //!
 
 use crate::cuda_runtime::PinnedVec;
-use crate::packet::{Packet, Packets};
+use crate::packet::{limited_deserialize, Packet, Packets};
 use crate::perf_libs;
 use crate::recycler::Recycler;
 use rayon::ThreadPool;
 use solana_metrics::inc_new_counter_debug;
 use solana_rayon_threadlimit::get_thread_count;
-use solana_sdk::message::MESSAGE_HEADER_LENGTH;
+use solana_sdk::compute_budget::PrioritizationFeeDetails;
+use solana_sdk::fee_calculator::DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE;
+use solana_sdk::message::{MESSAGE_HEADER_LENGTH, MESSAGE_VERSION_PREFIX};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::sanitize::Sanitize;
 use solana_sdk::short_vec::decode_shortu16_len;
 use solana_sdk::signature::Signature;
 #[cfg(test)]
 use solana_sdk::transaction::Transaction;
+use solana_sdk::versioned_transaction::VersionedTransaction;
 use std::convert::TryFrom;
 use std::mem::size_of;
 
@@ -27,6 +31,10 @@ const TRACER_KEY_BYTES: [u8; 32] = [
 ];
 const TRACER_KEY: Pubkey = Pubkey::new_from_array(TRACER_KEY_BYTES);
 
+// The cluster's current lamports per signature isn't known here, so packets are
+// ranked as if signatures cost the minimum of the default fee rate governor
+const PRIORITY_LAMPORTS_PER_SIGNATURE: u64 = DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE / 2;
+
 lazy_static! {
     static ref PAR_THREAD_POOL: ThreadPool = rayon::ThreadPoolBuilder::new()
         .num_threads(get_thread_count())
@@ -66,6 +74,7 @@ pub enum PacketError {
     InvalidSignatureLen,
     MismatchSignatureLen,
     PayerNotWritable,
+    UnsupportedVersion,
 }
 
 impl std::convert::From<std::boxed::Box<bincode::ErrorKind>> for PacketError {
@@ -176,16 +185,38 @@ fn do_get_packet_offsets(
         .checked_add(1)
         .ok_or(PacketError::InvalidLen)?;
 
+    // Packet should have data at least for signatures and the first byte of the message
+    let _ = Some(msg_start_offset_plus_one)
+        .filter(|v| *v <= packet.meta.size)
+        .ok_or(PacketError::InvalidSignatureLen)?;
+
+    // A versioned message starts with its version prefix (serialized with u8), which is signed
+    // along with the rest of the message; a legacy message starts with its MessageHeader
+    let message_prefix = packet.data[msg_start_offset];
+    let msg_header_offset = if message_prefix & MESSAGE_VERSION_PREFIX != 0 {
+        let version = message_prefix & !MESSAGE_VERSION_PREFIX;
+        if version != 0 {
+            return Err(PacketError::UnsupportedVersion);
+        }
+        msg_start_offset_plus_one
+    } else {
+        msg_start_offset
+    };
+
+    let msg_header_offset_plus_one = msg_header_offset
+        .checked_add(1)
+        .ok_or(PacketError::InvalidLen)?;
+
     // Packet should have data at least for signatures, MessageHeader, 1 byte for Message.account_keys.len
-    let _ = msg_start_offset_plus_one
+    let _ = msg_header_offset_plus_one
         .checked_add(MESSAGE_HEADER_LENGTH)
         .filter(|v| *v <= packet.meta.size)
         .ok_or(PacketError::InvalidSignatureLen)?;
 
     // read MessageHeader.num_required_signatures (serialized with u8)
-    let sig_len_maybe_trusted = packet.data[msg_start_offset];
+    let sig_len_maybe_trusted = packet.data[msg_header_offset];
 
-    let message_account_keys_len_offset = msg_start_offset
+    let message_account_keys_len_offset = msg_header_offset
         .checked_add(MESSAGE_HEADER_LENGTH)
         .ok_or(PacketError::InvalidLen)?;
 
@@ -193,7 +224,7 @@ fn do_get_packet_offsets(
     // num_readonly_signed_accounts bytes. If num_required_signatures is not larger than
     // num_readonly_signed_accounts, the first account is not debitable, and cannot be charged
     // required transaction fees.
-    let readonly_signer_offset = msg_start_offset_plus_one;
+    let readonly_signer_offset = msg_header_offset_plus_one;
     if sig_len_maybe_trusted <= packet.data[readonly_signer_offset] {
         return Err(PacketError::PayerNotWritable);
     }
@@ -318,6 +349,42 @@ pub fn ed25519_verify_disabled(batches: &mut [Packets]) {
     inc_new_counter_debug!("ed25519_verify_disabled", count);
 }
 
+/// Fee per requested compute unit in micro-lamports offered by the transaction
+/// in `packet`, zero if the packet doesn't hold a well-formed transaction
+fn packet_priority(packet: &Packet) -> u64 {
+    let tx: VersionedTransaction = match limited_deserialize(&packet.data[..packet.meta.size]) {
+        Ok(tx) => tx,
+        Err(_) => return 0,
+    };
+    if tx.sanitize().is_err() {
+        return 0;
+    }
+    let prioritization_fee_details = match PrioritizationFeeDetails::from_instructions(
+        tx.message.instructions(),
+        tx.message.static_account_keys(),
+    ) {
+        Ok(prioritization_fee_details) => prioritization_fee_details,
+        Err(_) => return 0,
+    };
+    let signature_fee =
+        (tx.signatures.len() as u64).saturating_mul(PRIORITY_LAMPORTS_PER_SIGNATURE);
+    prioritization_fee_details.fee_per_compute_unit(signature_fee)
+}
+
+/// Record the priority of every packet that passed signature verification so
+/// later stages can rank transactions without deserializing them again
+pub fn set_packet_priorities(batches: &mut [Packets]) {
+    use rayon::prelude::*;
+    PAR_THREAD_POOL.install(|| {
+        batches.into_par_iter().for_each(|p| {
+            p.packets
+                .par_iter_mut()
+                .filter(|p| !p.meta.discard)
+                .for_each(|p| p.meta.priority = packet_priority(p))
+        })
+    });
+}
+
 pub fn copy_return_values(sig_lens: &[Vec<u32>], out: &PinnedVec<u8>, rvs: &mut Vec<Vec<u8>>) {
     let mut num = 0;
     for (vs, sig_vs) in rvs.iter_mut().zip(sig_lens.iter()) {
@@ -472,8 +539,14 @@ mod tests {
     use crate::test_tx::{test_multisig_tx, test_tx};
     use bincode::{deserialize, serialize};
     use solana_sdk::hash::Hash;
-    use solana_sdk::message::{Message, MessageHeader};
-    use solana_sdk::signature::Signature;
+    use solana_sdk::instruction::CompiledInstruction;
+    use solana_sdk::message::{
+        v0::{self, MessageAddressTableLookup},
+        Message, MessageHeader, VersionedMessage,
+    };
+    use solana_sdk::signature::{Keypair, Signature, Signer};
+    use solana_sdk::system_instruction::SystemInstruction;
+    use solana_sdk::system_program;
     use solana_sdk::transaction::Transaction;
 
     const SIG_OFFSET: usize = 1;
@@ -578,9 +651,11 @@ mod tests {
 
         let unsanitized_packet_offsets = sigverify::do_get_packet_offsets(&packet, 0);
 
+        // A first message byte with the high bit set is read as a version prefix, just as
+        // VersionedMessage deserialization does
         assert_eq!(
             unsanitized_packet_offsets,
-            Err(PacketError::MismatchSignatureLen)
+            Err(PacketError::UnsupportedVersion)
         );
     }
 
@@ -638,6 +713,43 @@ mod tests {
         assert_eq!(res, Err(PacketError::InvalidPubkeyLen));
     }
 
+    #[test]
+    fn test_set_packet_priorities() {
+        use solana_sdk::compute_budget::ComputeBudgetInstruction;
+        use solana_sdk::signature::{Keypair, Signer};
+        use solana_sdk::system_instruction;
+
+        let keypair = Keypair::new();
+        let transfer = system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 1);
+        let packet = |instructions: &[_]| {
+            let message = Message::new(instructions, Some(&keypair.pubkey()));
+            make_packet_from_transaction(Transaction::new(&[&keypair], message, Hash::default()))
+        };
+        let mut discarded = packet(&[transfer.clone()]);
+        discarded.meta.discard = true;
+        let mut malformed = Packet::default();
+        malformed.meta.size = 2;
+        let mut batches = vec![Packets::new(vec![
+            packet(&[transfer.clone()]),
+            packet(&[
+                ComputeBudgetInstruction::set_compute_unit_price(7),
+                transfer.clone(),
+            ]),
+            packet(&[
+                ComputeBudgetInstruction::request_units(100_000),
+                ComputeBudgetInstruction::set_compute_unit_price(7),
+                transfer,
+            ]),
+            discarded,
+            malformed,
+        ])];
+        set_packet_priorities(&mut batches);
+        let priorities: Vec<_> = batches[0].packets.iter().map(|p| p.meta.priority).collect();
+        // One signature at 5000 lamports over the default 200k compute units is
+        // 25000 micro-lamports per compute unit, twice that over half the units
+        assert_eq!(priorities, vec![25_000, 25_007, 50_007, 0, 0]);
+    }
+
     #[test]
     fn test_fee_payer_is_debitable() {
         let message = Message {
@@ -780,6 +892,62 @@ mod tests {
             .all(|p| p.meta.discard));
     }
 
+    fn test_v0_tx() -> VersionedTransaction {
+        let keypair = Keypair::new();
+        let message = v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![keypair.pubkey(), system_program::id()],
+            recent_blockhash: Hash::default(),
+            instructions: vec![CompiledInstruction::new(
+                1,
+                &SystemInstruction::Transfer { lamports: 42 },
+                vec![0, 2],
+            )],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        };
+        VersionedTransaction::try_new(VersionedMessage::V0(message), &[&keypair]).unwrap()
+    }
+
+    #[test]
+    fn test_verify_v0_tx() {
+        let tx = test_v0_tx();
+        let mut packet = Packet::from_data(None, &tx).unwrap();
+
+        // The version prefix is part of the signed message, and precedes the message header
+        let packet_offsets = sigverify::do_get_packet_offsets(&packet, 0).unwrap();
+        assert_eq!(packet_offsets, PacketOffsets::new(1, 1, 65, 70));
+        assert_eq!(packet.data[65], MESSAGE_VERSION_PREFIX);
+        assert_eq!(
+            &packet.data[70..102],
+            tx.message.static_account_keys()[0].as_ref()
+        );
+
+        let mut batches = generate_packet_vec(&packet, 1, 1);
+        sigverify::ed25519_verify(&mut batches, &Recycler::default(), &Recycler::default());
+        assert!(!batches[0].packets[0].meta.discard);
+
+        // A tampered message fails verification
+        packet.data[80] = packet.data[80].wrapping_add(1);
+        let mut batches = generate_packet_vec(&packet, 1, 1);
+        sigverify::ed25519_verify(&mut batches, &Recycler::default(), &Recycler::default());
+        assert!(batches[0].packets[0].meta.discard);
+
+        // Only version 0 is supported
+        packet.data[65] = MESSAGE_VERSION_PREFIX | 1;
+        assert_eq!(
+            sigverify::do_get_packet_offsets(&packet, 0),
+            Err(PacketError::UnsupportedVersion)
+        );
+    }
+
     #[test]
     fn test_verify_zero() {
         test_verify_n(0, false);
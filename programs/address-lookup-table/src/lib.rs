@@ -0,0 +1,12 @@
+#![allow(clippy::integer_arithmetic)]
+//! Address lookup table program
+//!
+//! Lookup tables store lists of addresses on-chain so that v0 transaction
+//! messages can reference accounts by a one byte index into a table instead of
+//! listing each 32 byte address inline.
+
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+solana_sdk::declare_id!("AddressLookupTab1e1111111111111111111111111");
@@ -0,0 +1,573 @@
+use crate::{
+    instruction::ProgramInstruction,
+    state::{AddressLookupTable, LookupTableMeta, LOOKUP_TABLE_MAX_ADDRESSES},
+};
+use solana_sdk::{
+    account::{ReadableAccount, WritableAccount},
+    clock::{Clock, Slot},
+    ic_msg,
+    instruction::InstructionError,
+    keyed_account::{keyed_account_at_index, KeyedAccount},
+    process_instruction::{get_sysvar, InvokeContext},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    rent::Rent,
+    slot_hashes::SlotHashes,
+    sysvar,
+};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    instruction_data: &[u8],
+    invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    match limited_deserialize(instruction_data)? {
+        ProgramInstruction::CreateLookupTable => {
+            Processor::create_lookup_table(program_id, invoke_context)
+        }
+        ProgramInstruction::FreezeLookupTable => {
+            Processor::freeze_lookup_table(program_id, invoke_context)
+        }
+        ProgramInstruction::ExtendLookupTable { new_addresses } => {
+            Processor::extend_lookup_table(program_id, invoke_context, new_addresses)
+        }
+        ProgramInstruction::DeactivateLookupTable => {
+            Processor::deactivate_lookup_table(program_id, invoke_context)
+        }
+        ProgramInstruction::CloseLookupTable => {
+            Processor::close_lookup_table(program_id, invoke_context)
+        }
+    }
+}
+
+pub struct Processor;
+impl Processor {
+    fn create_lookup_table(
+        program_id: &Pubkey,
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let lookup_table_account = keyed_account_at_index(keyed_accounts, 0)?;
+        if lookup_table_account.owner()? != *program_id {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        let authority_key = *keyed_account_at_index(keyed_accounts, 1)?.unsigned_key();
+
+        {
+            let lookup_table_account = lookup_table_account.try_account_ref()?;
+            if lookup_table_account.data().iter().any(|byte| *byte != 0) {
+                ic_msg!(
+                    invoke_context,
+                    "Lookup table account is already initialized"
+                );
+                return Err(InstructionError::AccountAlreadyInitialized);
+            }
+            if AddressLookupTable::capacity(lookup_table_account.data()) == 0 {
+                ic_msg!(
+                    invoke_context,
+                    "Lookup table account has no room for addresses"
+                );
+                return Err(InstructionError::InvalidAccountData);
+            }
+
+            let rent = get_sysvar::<Rent>(invoke_context, &sysvar::rent::id())?;
+            if !rent.is_exempt(
+                lookup_table_account.lamports(),
+                lookup_table_account.data().len(),
+            ) {
+                return Err(InstructionError::InsufficientFunds);
+            }
+        }
+
+        let mut lookup_table_account = lookup_table_account.try_account_ref_mut()?;
+        AddressLookupTable::overwrite_meta_data(
+            lookup_table_account.data_as_mut_slice(),
+            LookupTableMeta::new(authority_key),
+        )
+    }
+
+    fn freeze_lookup_table(
+        program_id: &Pubkey,
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let lookup_table_account = keyed_account_at_index(keyed_accounts, 0)?;
+        let authority_account = keyed_account_at_index(keyed_accounts, 1)?;
+
+        let mut lookup_table =
+            Self::checked_lookup_table(program_id, lookup_table_account, authority_account)?;
+        if lookup_table.meta.deactivation_slot != Slot::MAX {
+            ic_msg!(invoke_context, "Deactivated tables cannot be frozen");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if lookup_table.addresses.is_empty() {
+            ic_msg!(invoke_context, "Empty lookup tables cannot be frozen");
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        lookup_table.meta.authority = None;
+        let mut lookup_table_account = lookup_table_account.try_account_ref_mut()?;
+        AddressLookupTable::overwrite_meta_data(
+            lookup_table_account.data_as_mut_slice(),
+            lookup_table.meta,
+        )
+    }
+
+    fn extend_lookup_table(
+        program_id: &Pubkey,
+        invoke_context: &mut dyn InvokeContext,
+        new_addresses: Vec<Pubkey>,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let lookup_table_account = keyed_account_at_index(keyed_accounts, 0)?;
+        let authority_account = keyed_account_at_index(keyed_accounts, 1)?;
+
+        let mut lookup_table =
+            Self::checked_lookup_table(program_id, lookup_table_account, authority_account)?;
+        if lookup_table.meta.deactivation_slot != Slot::MAX {
+            ic_msg!(invoke_context, "Deactivated tables cannot be extended");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if new_addresses.is_empty() {
+            ic_msg!(invoke_context, "Must extend with at least one address");
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        let old_table_addresses_len = lookup_table.addresses.len();
+        let new_table_addresses_len = old_table_addresses_len.saturating_add(new_addresses.len());
+        let capacity = AddressLookupTable::capacity(lookup_table_account.try_account_ref()?.data());
+        if new_table_addresses_len > LOOKUP_TABLE_MAX_ADDRESSES
+            || new_table_addresses_len > capacity
+        {
+            ic_msg!(
+                invoke_context,
+                "Extended lookup table length {} would exceed max capacity of {}",
+                new_table_addresses_len,
+                capacity
+            );
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        let clock = get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?;
+        if clock.slot != lookup_table.meta.last_extended_slot {
+            lookup_table.meta.last_extended_slot = clock.slot;
+            lookup_table.meta.last_extended_slot_start_index = old_table_addresses_len as u8;
+        }
+        lookup_table.meta.num_addresses = new_table_addresses_len as u16;
+        lookup_table.addresses.extend(new_addresses);
+
+        let mut lookup_table_account = lookup_table_account.try_account_ref_mut()?;
+        lookup_table.serialize(lookup_table_account.data_as_mut_slice())
+    }
+
+    fn deactivate_lookup_table(
+        program_id: &Pubkey,
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let lookup_table_account = keyed_account_at_index(keyed_accounts, 0)?;
+        let authority_account = keyed_account_at_index(keyed_accounts, 1)?;
+
+        let mut lookup_table =
+            Self::checked_lookup_table(program_id, lookup_table_account, authority_account)?;
+        if lookup_table.meta.deactivation_slot != Slot::MAX {
+            ic_msg!(invoke_context, "Lookup table is already deactivated");
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        let clock = get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?;
+        lookup_table.meta.deactivation_slot = clock.slot;
+
+        let mut lookup_table_account = lookup_table_account.try_account_ref_mut()?;
+        AddressLookupTable::overwrite_meta_data(
+            lookup_table_account.data_as_mut_slice(),
+            lookup_table.meta,
+        )
+    }
+
+    fn close_lookup_table(
+        program_id: &Pubkey,
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let lookup_table_account = keyed_account_at_index(keyed_accounts, 0)?;
+        let authority_account = keyed_account_at_index(keyed_accounts, 1)?;
+        let recipient_account = keyed_account_at_index(keyed_accounts, 2)?;
+        if recipient_account.unsigned_key() == lookup_table_account.unsigned_key() {
+            ic_msg!(
+                invoke_context,
+                "Lookup table cannot be the recipient of reclaimed lamports"
+            );
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        let lookup_table =
+            Self::checked_lookup_table(program_id, lookup_table_account, authority_account)?;
+
+        let clock = get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?;
+        let slot_hashes = get_sysvar::<SlotHashes>(invoke_context, &sysvar::slot_hashes::id())?;
+        if lookup_table.meta.deactivation_slot == Slot::MAX {
+            ic_msg!(invoke_context, "Lookup table is not deactivated");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if lookup_table.meta.is_active(clock.slot, &slot_hashes) {
+            ic_msg!(
+                invoke_context,
+                "Table cannot be closed until its deactivation slot {} is no longer recent",
+                lookup_table.meta.deactivation_slot
+            );
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        let withdrawn_lamports = lookup_table_account.lamports()?;
+        recipient_account
+            .try_account_ref_mut()?
+            .checked_add_lamports(withdrawn_lamports)?;
+
+        let mut lookup_table_account = lookup_table_account.try_account_ref_mut()?;
+        lookup_table_account.set_lamports(0);
+        lookup_table_account.data_as_mut_slice().fill(0);
+        Ok(())
+    }
+
+    /// Deserialize an initialized lookup table which is owned by this program
+    /// and check that its authority signed the instruction
+    fn checked_lookup_table(
+        program_id: &Pubkey,
+        lookup_table_account: &KeyedAccount,
+        authority_account: &KeyedAccount,
+    ) -> Result<AddressLookupTable, InstructionError> {
+        if lookup_table_account.owner()? != *program_id {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+
+        let lookup_table =
+            AddressLookupTable::deserialize(lookup_table_account.try_account_ref()?.data())?;
+        match lookup_table.meta.authority {
+            None => Err(InstructionError::Immutable),
+            Some(authority) if Some(&authority) != authority_account.signer_key() => {
+                if authority_account.signer_key().is_none() {
+                    Err(InstructionError::MissingRequiredSignature)
+                } else {
+                    Err(InstructionError::IncorrectAuthority)
+                }
+            }
+            Some(_) => Ok(lookup_table),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{id, instruction, state::ProgramState};
+    use solana_sdk::{
+        account::{Account, AccountSharedData},
+        hash::Hash,
+        instruction::Instruction,
+        keyed_account::KeyedAccount,
+        process_instruction::{mock_set_sysvar, MockInvokeContext},
+    };
+    use std::cell::RefCell;
+
+    struct TestContext {
+        slot: Slot,
+        slot_hashes: SlotHashes,
+        accounts: Vec<(Pubkey, RefCell<AccountSharedData>)>,
+    }
+
+    impl TestContext {
+        fn new(slot: Slot) -> Self {
+            Self {
+                slot,
+                slot_hashes: SlotHashes::default(),
+                accounts: vec![],
+            }
+        }
+
+        fn add_account(&mut self, address: Pubkey, account: AccountSharedData) {
+            self.accounts.push((address, RefCell::new(account)));
+        }
+
+        fn account(&self, address: &Pubkey) -> AccountSharedData {
+            self.accounts
+                .iter()
+                .find(|(key, _)| key == address)
+                .map(|(_, account)| account.borrow().clone())
+                .unwrap()
+        }
+
+        fn lookup_table(&self, address: &Pubkey) -> AddressLookupTable {
+            AddressLookupTable::deserialize(self.account(address).data()).unwrap()
+        }
+
+        fn process(&self, instruction: &Instruction) -> Result<(), InstructionError> {
+            let keyed_accounts: Vec<_> = instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    let account = &self
+                        .accounts
+                        .iter()
+                        .find(|(key, _)| *key == meta.pubkey)
+                        .unwrap()
+                        .1;
+                    KeyedAccount::new(&meta.pubkey, meta.is_signer, account)
+                })
+                .collect();
+            let mut invoke_context = MockInvokeContext::new(keyed_accounts);
+            mock_set_sysvar(
+                &mut invoke_context,
+                sysvar::clock::id(),
+                Clock {
+                    slot: self.slot,
+                    ..Clock::default()
+                },
+            )?;
+            mock_set_sysvar(
+                &mut invoke_context,
+                sysvar::slot_hashes::id(),
+                SlotHashes::new(&self.slot_hashes),
+            )?;
+            mock_set_sysvar(&mut invoke_context, sysvar::rent::id(), Rent::free())?;
+            process_instruction(&id(), &instruction.data, &mut invoke_context)
+        }
+    }
+
+    fn lookup_table_account(max_addresses: usize) -> AccountSharedData {
+        AccountSharedData::from(Account {
+            lamports: 1,
+            data: vec![0; AddressLookupTable::account_size(max_addresses)],
+            owner: id(),
+            ..Account::default()
+        })
+    }
+
+    fn setup_initialized_table(
+        slot: Slot,
+        max_addresses: usize,
+        addresses: Vec<Pubkey>,
+    ) -> (TestContext, Pubkey, Pubkey) {
+        let mut context = TestContext::new(slot);
+        let table_address = Pubkey::new_unique();
+        let authority_address = Pubkey::new_unique();
+        let mut account = lookup_table_account(max_addresses);
+        AddressLookupTable {
+            meta: LookupTableMeta {
+                num_addresses: addresses.len() as u16,
+                ..LookupTableMeta::new(authority_address)
+            },
+            addresses,
+        }
+        .serialize(account.data_as_mut_slice())
+        .unwrap();
+        context.add_account(table_address, account);
+        context.add_account(authority_address, AccountSharedData::default());
+        (context, table_address, authority_address)
+    }
+
+    #[test]
+    fn test_create_lookup_table() {
+        let mut context = TestContext::new(0);
+        let payer_address = Pubkey::new_unique();
+        let table_address = Pubkey::new_unique();
+        let authority_address = Pubkey::new_unique();
+        context.add_account(table_address, lookup_table_account(2));
+        context.add_account(authority_address, AccountSharedData::default());
+
+        let instruction = &instruction::create_lookup_table(
+            &payer_address,
+            &table_address,
+            &authority_address,
+            1,
+            2,
+        )[1];
+        assert_eq!(context.process(instruction), Ok(()));
+        let lookup_table = context.lookup_table(&table_address);
+        assert_eq!(lookup_table.meta, LookupTableMeta::new(authority_address));
+        assert!(lookup_table.addresses.is_empty());
+
+        assert_eq!(
+            context.process(instruction),
+            Err(InstructionError::AccountAlreadyInitialized)
+        );
+    }
+
+    #[test]
+    fn test_create_lookup_table_with_wrong_owner() {
+        let mut context = TestContext::new(0);
+        let table_address = Pubkey::new_unique();
+        let authority_address = Pubkey::new_unique();
+        let mut account = lookup_table_account(2);
+        account.set_owner(Pubkey::new_unique());
+        context.add_account(table_address, account);
+        context.add_account(authority_address, AccountSharedData::default());
+
+        let instruction = &instruction::create_lookup_table(
+            &Pubkey::new_unique(),
+            &table_address,
+            &authority_address,
+            1,
+            2,
+        )[1];
+        assert_eq!(
+            context.process(instruction),
+            Err(InstructionError::InvalidAccountOwner)
+        );
+    }
+
+    #[test]
+    fn test_extend_lookup_table() {
+        let existing_address = Pubkey::new_unique();
+        let (mut context, table_address, authority_address) =
+            setup_initialized_table(5, 3, vec![existing_address]);
+        let new_addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+
+        assert_eq!(
+            context.process(&instruction::extend_lookup_table(
+                table_address,
+                authority_address,
+                new_addresses.clone()
+            )),
+            Ok(())
+        );
+        let lookup_table = context.lookup_table(&table_address);
+        assert_eq!(
+            lookup_table.addresses,
+            vec![existing_address, new_addresses[0], new_addresses[1]]
+        );
+        assert_eq!(lookup_table.meta.last_extended_slot, 5);
+        assert_eq!(lookup_table.meta.last_extended_slot_start_index, 1);
+
+        // table is full
+        context.slot = 6;
+        assert_eq!(
+            context.process(&instruction::extend_lookup_table(
+                table_address,
+                authority_address,
+                vec![Pubkey::new_unique()]
+            )),
+            Err(InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_extend_lookup_table_without_authority_signature() {
+        let (context, table_address, authority_address) = setup_initialized_table(0, 2, vec![]);
+        let mut instruction = instruction::extend_lookup_table(
+            table_address,
+            authority_address,
+            vec![Pubkey::new_unique()],
+        );
+        instruction.accounts[1].is_signer = false;
+        assert_eq!(
+            context.process(&instruction),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_extend_lookup_table_with_wrong_authority() {
+        let (mut context, table_address, _) = setup_initialized_table(0, 2, vec![]);
+        let wrong_authority_address = Pubkey::new_unique();
+        context.add_account(wrong_authority_address, AccountSharedData::default());
+        assert_eq!(
+            context.process(&instruction::extend_lookup_table(
+                table_address,
+                wrong_authority_address,
+                vec![Pubkey::new_unique()]
+            )),
+            Err(InstructionError::IncorrectAuthority)
+        );
+    }
+
+    #[test]
+    fn test_freeze_lookup_table() {
+        let (context, table_address, authority_address) =
+            setup_initialized_table(0, 2, vec![Pubkey::new_unique()]);
+        assert_eq!(
+            context.process(&instruction::freeze_lookup_table(
+                table_address,
+                authority_address
+            )),
+            Ok(())
+        );
+        assert_eq!(context.lookup_table(&table_address).meta.authority, None);
+
+        // frozen tables are immutable
+        assert_eq!(
+            context.process(&instruction::extend_lookup_table(
+                table_address,
+                authority_address,
+                vec![Pubkey::new_unique()]
+            )),
+            Err(InstructionError::Immutable)
+        );
+        assert_eq!(
+            context.process(&instruction::deactivate_lookup_table(
+                table_address,
+                authority_address
+            )),
+            Err(InstructionError::Immutable)
+        );
+    }
+
+    #[test]
+    fn test_freeze_empty_lookup_table() {
+        let (context, table_address, authority_address) = setup_initialized_table(0, 2, vec![]);
+        assert_eq!(
+            context.process(&instruction::freeze_lookup_table(
+                table_address,
+                authority_address
+            )),
+            Err(InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_deactivate_and_close_lookup_table() {
+        let (mut context, table_address, authority_address) =
+            setup_initialized_table(10, 2, vec![Pubkey::new_unique()]);
+        let recipient_address = Pubkey::new_unique();
+        context.add_account(recipient_address, AccountSharedData::default());
+        let close_instruction =
+            instruction::close_lookup_table(table_address, authority_address, recipient_address);
+
+        // tables must be deactivated before they are closed
+        assert_eq!(
+            context.process(&close_instruction),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        assert_eq!(
+            context.process(&instruction::deactivate_lookup_table(
+                table_address,
+                authority_address
+            )),
+            Ok(())
+        );
+        assert_eq!(
+            context.lookup_table(&table_address).meta.deactivation_slot,
+            10
+        );
+
+        // deactivation slot is still recent
+        context.slot = 11;
+        context.slot_hashes = SlotHashes::new(&[(10, Hash::default())]);
+        assert_eq!(
+            context.process(&close_instruction),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        context.slot_hashes = SlotHashes::default();
+        assert_eq!(context.process(&close_instruction), Ok(()));
+        let table_account = context.account(&table_address);
+        assert_eq!(table_account.lamports(), 0);
+        assert!(table_account.data().iter().all(|byte| *byte == 0));
+        assert_eq!(
+            bincode::deserialize::<ProgramState>(table_account.data()).unwrap(),
+            ProgramState::Uninitialized
+        );
+        assert_eq!(context.account(&recipient_address).lamports(), 1);
+    }
+}
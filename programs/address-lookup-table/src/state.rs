@@ -0,0 +1,334 @@
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    clock::Slot, instruction::InstructionError, pubkey::Pubkey, slot_hashes::SlotHashes,
+};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// The maximum number of addresses that a lookup table can hold
+pub const LOOKUP_TABLE_MAX_ADDRESSES: usize = 256;
+
+/// The serialized size of lookup table metadata
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+/// Size in bytes of a single stored address
+const ADDRESS_SIZE: usize = std::mem::size_of::<Pubkey>();
+
+/// Program account states
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ProgramState {
+    /// Account is not initialized.
+    Uninitialized,
+    /// Initialized `LookupTable` account.
+    LookupTable(LookupTableMeta),
+}
+
+impl Default for ProgramState {
+    fn default() -> Self {
+        Self::Uninitialized
+    }
+}
+
+/// Address lookup table metadata
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct LookupTableMeta {
+    /// Lookup tables cannot be closed until the deactivation slot is
+    /// no longer "recent" (not accessible in the `SlotHashes` sysvar).
+    pub deactivation_slot: Slot,
+    /// The slot that the table was last extended. Address tables may
+    /// only be used to lookup addresses that were extended before
+    /// the current bank's slot.
+    pub last_extended_slot: Slot,
+    /// The start index where the table was last extended from during
+    /// the `last_extended_slot`.
+    pub last_extended_slot_start_index: u8,
+    /// Authority address which must sign for each modification. Frozen
+    /// tables have no authority and can never be modified or closed.
+    pub authority: Option<Pubkey>,
+    /// Number of addresses stored in the table
+    pub num_addresses: u16,
+}
+
+impl Default for LookupTableMeta {
+    fn default() -> Self {
+        Self {
+            deactivation_slot: Slot::MAX,
+            last_extended_slot: 0,
+            last_extended_slot_start_index: 0,
+            authority: None,
+            num_addresses: 0,
+        }
+    }
+}
+
+impl LookupTableMeta {
+    pub fn new(authority: Pubkey) -> Self {
+        LookupTableMeta {
+            authority: Some(authority),
+            ..LookupTableMeta::default()
+        }
+    }
+
+    /// Return true if the table may still be used to look up addresses at
+    /// `current_slot`. Deactivated tables remain usable until their
+    /// deactivation slot is no longer present in the `SlotHashes` sysvar.
+    pub fn is_active(&self, current_slot: Slot, slot_hashes: &SlotHashes) -> bool {
+        if self.deactivation_slot == Slot::MAX || self.deactivation_slot == current_slot {
+            true
+        } else {
+            slot_hashes.get(&self.deactivation_slot).is_some()
+        }
+    }
+
+    /// Return the number of addresses which may be looked up at
+    /// `current_slot`. Addresses appended during the current slot are not
+    /// usable until the next slot.
+    pub fn active_addresses_len(&self, current_slot: Slot) -> usize {
+        if current_slot > self.last_extended_slot {
+            self.num_addresses as usize
+        } else {
+            self.last_extended_slot_start_index as usize
+        }
+    }
+}
+
+/// Errors returned when looking up addresses in a table
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AddressLookupError {
+    #[error("Lookup table is not active")]
+    LookupTableNotActive,
+
+    #[error("Lookup table index is invalid")]
+    InvalidLookupIndex,
+}
+
+/// Deserialized address lookup table account
+#[derive(Debug, PartialEq, Clone)]
+pub struct AddressLookupTable {
+    pub meta: LookupTableMeta,
+    pub addresses: Vec<Pubkey>,
+}
+
+impl AddressLookupTable {
+    /// Return the account data size needed to store `max_addresses` addresses
+    pub fn account_size(max_addresses: usize) -> usize {
+        LOOKUP_TABLE_META_SIZE + max_addresses * ADDRESS_SIZE
+    }
+
+    /// Return the number of addresses the table account data can hold
+    pub fn capacity(data: &[u8]) -> usize {
+        (data.len().saturating_sub(LOOKUP_TABLE_META_SIZE) / ADDRESS_SIZE)
+            .min(LOOKUP_TABLE_MAX_ADDRESSES)
+    }
+
+    /// Deserialize the state of an initialized lookup table account
+    pub fn deserialize(data: &[u8]) -> Result<Self, InstructionError> {
+        if data.len() < LOOKUP_TABLE_META_SIZE {
+            return Err(InstructionError::InvalidAccountData);
+        }
+
+        let meta = match bincode::deserialize(&data[..LOOKUP_TABLE_META_SIZE]) {
+            Ok(ProgramState::LookupTable(meta)) => meta,
+            Ok(ProgramState::Uninitialized) => return Err(InstructionError::UninitializedAccount),
+            Err(_) => return Err(InstructionError::InvalidAccountData),
+        };
+
+        let num_addresses = meta.num_addresses as usize;
+        if num_addresses > Self::capacity(data) {
+            return Err(InstructionError::InvalidAccountData);
+        }
+
+        let addresses_end = LOOKUP_TABLE_META_SIZE + num_addresses * ADDRESS_SIZE;
+        let addresses = data[LOOKUP_TABLE_META_SIZE..addresses_end]
+            .chunks(ADDRESS_SIZE)
+            .map(Pubkey::new)
+            .collect();
+
+        Ok(Self { meta, addresses })
+    }
+
+    /// Serialize the lookup table into the account data, which must be large
+    /// enough to hold all of its addresses
+    pub fn serialize(&self, data: &mut [u8]) -> Result<(), InstructionError> {
+        if self.addresses.len() > Self::capacity(data)
+            || self.addresses.len() != self.meta.num_addresses as usize
+        {
+            return Err(InstructionError::InvalidAccountData);
+        }
+
+        Self::overwrite_meta_data(data, self.meta.clone())?;
+        for (address, chunk) in self
+            .addresses
+            .iter()
+            .zip(data[LOOKUP_TABLE_META_SIZE..].chunks_mut(ADDRESS_SIZE))
+        {
+            chunk.copy_from_slice(address.as_ref());
+        }
+        Ok(())
+    }
+
+    /// Overwrite the metadata region of the account data without touching the
+    /// stored addresses
+    pub fn overwrite_meta_data(
+        data: &mut [u8],
+        lookup_table_meta: LookupTableMeta,
+    ) -> Result<(), InstructionError> {
+        if data.len() < LOOKUP_TABLE_META_SIZE {
+            return Err(InstructionError::InvalidAccountData);
+        }
+        let meta_data = &mut data[..LOOKUP_TABLE_META_SIZE];
+        meta_data.fill(0);
+        bincode::serialize_into(meta_data, &ProgramState::LookupTable(lookup_table_meta))
+            .map_err(|_| InstructionError::GenericError)
+    }
+
+    /// Look up the addresses stored at `indexes`, checking that each one is
+    /// usable at `current_slot`
+    pub fn lookup(
+        &self,
+        current_slot: Slot,
+        indexes: &[u8],
+        slot_hashes: &SlotHashes,
+    ) -> Result<Vec<Pubkey>, AddressLookupError> {
+        if !self.meta.is_active(current_slot, slot_hashes) {
+            return Err(AddressLookupError::LookupTableNotActive);
+        }
+
+        let active_addresses_len = self.meta.active_addresses_len(current_slot);
+        let active_addresses = &self.addresses[..active_addresses_len];
+        indexes
+            .iter()
+            .map(|idx| active_addresses.get(*idx as usize).copied())
+            .collect::<Option<_>>()
+            .ok_or(AddressLookupError::InvalidLookupIndex)
+    }
+}
+
+impl TryFrom<&[u8]> for AddressLookupTable {
+    type Error = InstructionError;
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::deserialize(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+
+    #[test]
+    fn test_lookup_table_meta_size() {
+        let meta_with_authority = ProgramState::LookupTable(LookupTableMeta {
+            deactivation_slot: Slot::MAX,
+            last_extended_slot: Slot::MAX,
+            last_extended_slot_start_index: u8::MAX,
+            authority: Some(Pubkey::new_unique()),
+            num_addresses: u16::MAX,
+        });
+        assert_eq!(
+            bincode::serialized_size(&meta_with_authority).unwrap() as usize,
+            LOOKUP_TABLE_META_SIZE
+        );
+        assert!(
+            bincode::serialized_size(&ProgramState::Uninitialized).unwrap() as usize
+                <= LOOKUP_TABLE_META_SIZE
+        );
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let mut data = vec![0; AddressLookupTable::account_size(4)];
+        assert_eq!(
+            AddressLookupTable::deserialize(&data),
+            Err(InstructionError::UninitializedAccount)
+        );
+
+        let table = AddressLookupTable {
+            meta: LookupTableMeta {
+                num_addresses: 2,
+                ..LookupTableMeta::new(Pubkey::new_unique())
+            },
+            addresses: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        };
+        table.serialize(&mut data).unwrap();
+        assert_eq!(AddressLookupTable::deserialize(&data), Ok(table));
+        assert_eq!(AddressLookupTable::capacity(&data), 4);
+    }
+
+    #[test]
+    fn test_serialize_exceeds_capacity() {
+        let mut data = vec![0; AddressLookupTable::account_size(1)];
+        let table = AddressLookupTable {
+            meta: LookupTableMeta {
+                num_addresses: 2,
+                ..LookupTableMeta::default()
+            },
+            addresses: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        };
+        assert_eq!(
+            table.serialize(&mut data),
+            Err(InstructionError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_is_active() {
+        let slot_hashes = SlotHashes::new(&[(10, Hash::default())]);
+        let mut meta = LookupTableMeta::default();
+        assert!(meta.is_active(100, &slot_hashes));
+
+        meta.deactivation_slot = 100;
+        assert!(meta.is_active(100, &slot_hashes));
+        assert!(!meta.is_active(101, &slot_hashes));
+
+        meta.deactivation_slot = 10;
+        assert!(meta.is_active(101, &slot_hashes));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let table = AddressLookupTable {
+            meta: LookupTableMeta {
+                last_extended_slot: 10,
+                last_extended_slot_start_index: 1,
+                num_addresses: 2,
+                ..LookupTableMeta::default()
+            },
+            addresses: addresses.clone(),
+        };
+        let slot_hashes = SlotHashes::default();
+
+        assert_eq!(
+            table.lookup(11, &[1, 0], &slot_hashes),
+            Ok(vec![addresses[1], addresses[0]])
+        );
+        // addresses appended in the current slot are not usable yet
+        assert_eq!(table.lookup(10, &[0], &slot_hashes), Ok(vec![addresses[0]]));
+        assert_eq!(
+            table.lookup(10, &[1], &slot_hashes),
+            Err(AddressLookupError::InvalidLookupIndex)
+        );
+        assert_eq!(
+            table.lookup(11, &[2], &slot_hashes),
+            Err(AddressLookupError::InvalidLookupIndex)
+        );
+    }
+
+    #[test]
+    fn test_lookup_from_deactivated_table() {
+        let table = AddressLookupTable {
+            meta: LookupTableMeta {
+                deactivation_slot: 10,
+                num_addresses: 1,
+                ..LookupTableMeta::default()
+            },
+            addresses: vec![Pubkey::new_unique()],
+        };
+        assert_eq!(
+            table.lookup(11, &[0], &SlotHashes::default()),
+            Err(AddressLookupError::LookupTableNotActive)
+        );
+    }
+}
@@ -37,7 +37,7 @@ use solana_sdk::{
     entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
     instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
     keyed_account::KeyedAccount,
-    message::Message,
+    message::{v0::LoadedAddresses, Message},
     process_instruction::{InvokeContext, MockInvokeContext},
     pubkey::Pubkey,
     signature::{keypair_from_seed, Keypair, Signer},
@@ -390,12 +390,13 @@ fn execute_transactions(bank: &Bank, txs: &[Transaction]) -> Vec<ConfirmedTransa
                 inner_instructions,
                 log_messages,
                 rewards: None,
+                loaded_addresses: LoadedAddresses::default(),
             };
 
             ConfirmedTransaction {
                 slot: bank.slot(),
                 transaction: TransactionWithStatusMeta {
-                    transaction: tx.clone(),
+                    transaction: tx.clone().into(),
                     meta: Some(tx_status_meta),
                 },
                 block_time: None,
@@ -0,0 +1,315 @@
+/// A reference signing daemon for `remote://` keypair URIs
+///
+/// Holds the keypairs listed in its configuration file and signs with them on behalf of
+/// authorized clients, subject to each key's policy. The configuration is JSON:
+///
+/// {
+///   "authorized_clients": ["<CLIENT AUTH PUBKEY>", ...],
+///   "keys": [
+///     { "keypair": "<KEYPAIR FILE>", "allowed_programs": ["<PROGRAM ID>", ...], "max_lamports": 1000000000 },
+///     ...
+///   ]
+/// }
+///
+/// `allowed_programs` and `max_lamports` are optional; a key without them signs anything.
+///
+/// Built only with the `remote-signer-daemon` feature.
+use {
+    clap::{crate_description, crate_name, crate_version, value_t_or_exit, App, Arg, ArgGroup},
+    serde_derive::Deserialize,
+    solana_remote_wallet::remote_signer::{RemoteSignerError, RemoteSignerServer, SigningPolicy},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Signer},
+    },
+    std::{
+        collections::HashSet,
+        error,
+        fs::File,
+        io::{self, Read, Write},
+        net::{TcpListener, TcpStream},
+        str::FromStr,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+const DEFAULT_MAX_CONNECTIONS: &str = "64";
+// Time a new connection has to complete the handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+// Time an authenticated connection may wait between requests
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Deserialize)]
+struct Config {
+    authorized_clients: Vec<String>,
+    keys: Vec<KeyConfig>,
+}
+
+#[derive(Deserialize)]
+struct KeyConfig {
+    keypair: String,
+    allowed_programs: Option<Vec<String>>,
+    max_lamports: Option<u64>,
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, Box<dyn error::Error>> {
+    Pubkey::from_str(pubkey).map_err(|_| format!("invalid pubkey: {}", pubkey).into())
+}
+
+fn load_server(config_path: &str) -> Result<RemoteSignerServer, Box<dyn error::Error>> {
+    let config: Config = serde_json::from_reader(File::open(config_path)?)?;
+    let authorized_clients = config
+        .authorized_clients
+        .iter()
+        .map(|pubkey| parse_pubkey(pubkey))
+        .collect::<Result<HashSet<_>, _>>()?;
+    let mut keys = vec![];
+    for key in config.keys {
+        let keypair = read_keypair_file(&key.keypair)
+            .map_err(|err| format!("unable to read keypair {}: {}", key.keypair, err))?;
+        let allowed_programs = key
+            .allowed_programs
+            .map(|programs| {
+                programs
+                    .iter()
+                    .map(|pubkey| parse_pubkey(pubkey))
+                    .collect::<Result<HashSet<_>, _>>()
+            })
+            .transpose()?;
+        println!("Serving key {}", keypair.pubkey());
+        keys.push((
+            keypair,
+            SigningPolicy {
+                allowed_programs,
+                max_lamports: key.max_lamports,
+            },
+        ));
+    }
+    Ok(RemoteSignerServer::new(keys, authorized_clients))
+}
+
+/// An accepted client connection, whose reads and writes can time out
+trait ClientStream: Read + Write {
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()>;
+}
+
+impl ClientStream for TcpStream {
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+#[cfg(unix)]
+impl ClientStream for std::os::unix::net::UnixStream {
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+/// Reads and writes `stream` until `deadline`, after which they fail with `TimedOut`
+struct DeadlineStream<'a, S> {
+    stream: &'a mut S,
+    deadline: Instant,
+}
+
+impl<S: ClientStream> DeadlineStream<'_, S> {
+    // A per-call socket timeout alone would let a client trickle in bytes indefinitely, so
+    // each call only gets the time left before the deadline
+    fn set_remaining_timeout(&self) -> io::Result<()> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining == Duration::from_secs(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "handshake timed out",
+            ));
+        }
+        self.stream.set_timeout(remaining)
+    }
+}
+
+impl<S: ClientStream> Read for DeadlineStream<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.set_remaining_timeout()?;
+        self.stream.read(buf)
+    }
+}
+
+impl<S: ClientStream> Write for DeadlineStream<'_, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.set_remaining_timeout()?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.set_remaining_timeout()?;
+        self.stream.flush()
+    }
+}
+
+fn serve_client<S: ClientStream>(
+    server: &RemoteSignerServer,
+    stream: &mut S,
+) -> Result<Pubkey, RemoteSignerError> {
+    // Unauthenticated connections must not hold on to the connection limit
+    let session = server.authenticate(&mut DeadlineStream {
+        stream,
+        deadline: Instant::now() + HANDSHAKE_TIMEOUT,
+    })?;
+    stream.set_timeout(IDLE_TIMEOUT)?;
+    server.serve_session(session, stream)
+}
+
+/// Serve a connection until the client disconnects or a timeout expires, then close it
+fn serve_connection<S: ClientStream>(server: &RemoteSignerServer, mut stream: S, peer: String) {
+    match serve_client(server, &mut stream) {
+        Ok(client) => println!("{}: client {} disconnected", peer, client),
+        Err(err) => eprintln!("{}: {}", peer, err),
+    }
+}
+
+/// Counts a connection against the connection limit for as long as it is held
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn acquire(active_connections: &Arc<AtomicUsize>, max_connections: usize) -> Option<Self> {
+        if active_connections.fetch_add(1, Ordering::SeqCst) >= max_connections {
+            active_connections.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Self(active_connections.clone()))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Serve each accepted connection on its own thread, closing connections beyond
+/// `max_connections` as soon as they are accepted
+fn serve_incoming<S, I, P>(
+    server: Arc<RemoteSignerServer>,
+    incoming: I,
+    peer_name: P,
+    max_connections: usize,
+) where
+    S: ClientStream + Send + 'static,
+    I: Iterator<Item = io::Result<S>>,
+    P: Fn(&S) -> String,
+{
+    let active_connections = Arc::new(AtomicUsize::new(0));
+    for stream in incoming {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("accept failed: {}", err);
+                continue;
+            }
+        };
+        let peer = peer_name(&stream);
+        let slot = match ConnectionSlot::acquire(&active_connections, max_connections) {
+            Some(slot) => slot,
+            None => {
+                eprintln!("{}: refused, {} connections open", peer, max_connections);
+                continue;
+            }
+        };
+        let server = server.clone();
+        thread::spawn(move || {
+            let _slot = slot;
+            serve_connection(&server, stream, peer)
+        });
+    }
+}
+
+fn main() -> Result<(), Box<dyn error::Error>> {
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(true)
+                .help("JSON file listing the keys to serve and the clients allowed to use them"),
+        )
+        .arg(
+            Arg::with_name("bind")
+                .long("bind")
+                .value_name("HOST:PORT")
+                .takes_value(true)
+                .help("Listen for TCP connections on this address"),
+        )
+        .arg(
+            Arg::with_name("unix_socket")
+                .long("unix-socket")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Listen for connections on this unix socket"),
+        )
+        .group(
+            ArgGroup::with_name("listen")
+                .args(&["bind", "unix_socket"])
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("max_connections")
+                .long("max-connections")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value(DEFAULT_MAX_CONNECTIONS)
+                .validator(|value| {
+                    value
+                        .parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|err| format!("invalid number of connections: {}", err))
+                })
+                .help("Most client connections to serve at once; more are closed when accepted"),
+        )
+        .get_matches();
+
+    let server = Arc::new(load_server(&value_t_or_exit!(matches, "config", String))?);
+    let max_connections = value_t_or_exit!(matches, "max_connections", usize);
+
+    if let Some(bind) = matches.value_of("bind") {
+        let listener = TcpListener::bind(bind)?;
+        println!("Listening on {}", listener.local_addr()?);
+        serve_incoming(
+            server,
+            listener.incoming(),
+            |stream| {
+                stream
+                    .peer_addr()
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_default()
+            },
+            max_connections,
+        );
+        return Ok(());
+    }
+
+    #[cfg(unix)]
+    {
+        let path = matches.value_of("unix_socket").unwrap();
+        let listener = std::os::unix::net::UnixListener::bind(path)?;
+        println!("Listening on {}", path);
+        serve_incoming(
+            server,
+            listener.incoming(),
+            |_| path.to_string(),
+            max_connections,
+        );
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    Err("unix sockets are not supported on this platform".into())
+}
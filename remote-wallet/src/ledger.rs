@@ -9,7 +9,9 @@ use {
     log::*,
     num_traits::FromPrimitive,
     semver::Version as FirmwareVersion,
-    solana_sdk::{derivation_path::DerivationPath, pubkey::Pubkey, signature::Signature},
+    solana_sdk::{
+        derivation_path::DerivationPath, offchain_message, pubkey::Pubkey, signature::Signature,
+    },
     std::{cmp::min, convert::TryFrom, fmt, sync::Arc},
 };
 
@@ -58,6 +60,7 @@ mod commands {
     pub const GET_APP_CONFIGURATION: u8 = 0x04;
     pub const GET_PUBKEY: u8 = 0x05;
     pub const SIGN_MESSAGE: u8 = 0x06;
+    pub const SIGN_OFFCHAIN_MESSAGE: u8 = 0x07;
 }
 
 enum ConfigurationVersion {
@@ -345,76 +348,12 @@ impl LedgerWallet {
             Err(RemoteWalletError::Protocol("Unknown error"))
         }
     }
-}
-
-impl RemoteWallet for LedgerWallet {
-    fn name(&self) -> &str {
-        "Ledger hardware wallet"
-    }
-
-    fn read_device(
-        &mut self,
-        dev_info: &hidapi::DeviceInfo,
-    ) -> Result<RemoteWalletInfo, RemoteWalletError> {
-        let manufacturer = dev_info
-            .manufacturer_string()
-            .and_then(|s| Manufacturer::try_from(s).ok())
-            .unwrap_or_default();
-        let model = dev_info
-            .product_string()
-            .unwrap_or("Unknown")
-            .to_lowercase()
-            .replace(" ", "-");
-        let serial = dev_info.serial_number().unwrap_or("Unknown").to_string();
-        let host_device_path = dev_info.path().to_string_lossy().to_string();
-        let version = self.get_firmware_version()?;
-        self.version = version;
-        let pubkey_result = self.get_pubkey(&DerivationPath::default(), false);
-        let (pubkey, error) = match pubkey_result {
-            Ok(pubkey) => (pubkey, None),
-            Err(err) => (Pubkey::default(), Some(err)),
-        };
-        Ok(RemoteWalletInfo {
-            model,
-            manufacturer,
-            serial,
-            host_device_path,
-            pubkey,
-            error,
-        })
-    }
-
-    fn get_pubkey(
-        &self,
-        derivation_path: &DerivationPath,
-        confirm_key: bool,
-    ) -> Result<Pubkey, RemoteWalletError> {
-        let derivation_path = extend_and_serialize(derivation_path);
-
-        let key = self.send_apdu(
-            if self.outdated_app() {
-                commands::DEPRECATED_GET_PUBKEY
-            } else {
-                commands::GET_PUBKEY
-            },
-            if confirm_key {
-                P1_CONFIRM
-            } else {
-                P1_NON_CONFIRM
-            },
-            0,
-            &derivation_path,
-        )?;
-        if key.len() != 32 {
-            return Err(RemoteWalletError::Protocol("Key packet size mismatch"));
-        }
-        Ok(Pubkey::new(&key))
-    }
 
-    fn sign_message(
+    fn sign_data(
         &self,
         derivation_path: &DerivationPath,
         data: &[u8],
+        command: u8,
     ) -> Result<Signature, RemoteWalletError> {
         let mut payload = if self.outdated_app() {
             extend_and_serialize(derivation_path)
@@ -457,7 +396,7 @@ impl RemoteWallet for LedgerWallet {
             if self.outdated_app() {
                 commands::DEPRECATED_SIGN_MESSAGE
             } else {
-                commands::SIGN_MESSAGE
+                command
             },
             p1,
             p2,
@@ -484,7 +423,7 @@ impl RemoteWallet for LedgerWallet {
             chunks.last_mut().unwrap().0 &= !P2_MORE;
 
             for (p2, payload) in chunks {
-                result = self.send_apdu(commands::SIGN_MESSAGE, p1, p2, &payload)?;
+                result = self.send_apdu(command, p1, p2, &payload)?;
             }
         }
 
@@ -497,6 +436,97 @@ impl RemoteWallet for LedgerWallet {
     }
 }
 
+impl RemoteWallet for LedgerWallet {
+    fn name(&self) -> &str {
+        "Ledger hardware wallet"
+    }
+
+    fn read_device(
+        &mut self,
+        dev_info: &hidapi::DeviceInfo,
+    ) -> Result<RemoteWalletInfo, RemoteWalletError> {
+        let manufacturer = dev_info
+            .manufacturer_string()
+            .and_then(|s| Manufacturer::try_from(s).ok())
+            .unwrap_or_default();
+        let model = dev_info
+            .product_string()
+            .unwrap_or("Unknown")
+            .to_lowercase()
+            .replace(" ", "-");
+        let serial = dev_info.serial_number().unwrap_or("Unknown").to_string();
+        let host_device_path = dev_info.path().to_string_lossy().to_string();
+        let version = self.get_firmware_version()?;
+        self.version = version;
+        let pubkey_result = self.get_pubkey(&DerivationPath::default(), false);
+        let (pubkey, error) = match pubkey_result {
+            Ok(pubkey) => (pubkey, None),
+            Err(err) => (Pubkey::default(), Some(err)),
+        };
+        Ok(RemoteWalletInfo {
+            model,
+            manufacturer,
+            serial,
+            host_device_path,
+            pubkey,
+            error,
+        })
+    }
+
+    fn get_pubkey(
+        &self,
+        derivation_path: &DerivationPath,
+        confirm_key: bool,
+    ) -> Result<Pubkey, RemoteWalletError> {
+        let derivation_path = extend_and_serialize(derivation_path);
+
+        let key = self.send_apdu(
+            if self.outdated_app() {
+                commands::DEPRECATED_GET_PUBKEY
+            } else {
+                commands::GET_PUBKEY
+            },
+            if confirm_key {
+                P1_CONFIRM
+            } else {
+                P1_NON_CONFIRM
+            },
+            0,
+            &derivation_path,
+        )?;
+        if key.len() != 32 {
+            return Err(RemoteWalletError::Protocol("Key packet size mismatch"));
+        }
+        Ok(Pubkey::new(&key))
+    }
+
+    fn sign_message(
+        &self,
+        derivation_path: &DerivationPath,
+        data: &[u8],
+    ) -> Result<Signature, RemoteWalletError> {
+        self.sign_data(derivation_path, data, commands::SIGN_MESSAGE)
+    }
+
+    fn sign_offchain_message(
+        &self,
+        derivation_path: &DerivationPath,
+        message: &[u8],
+    ) -> Result<Signature, RemoteWalletError> {
+        if self.outdated_app() {
+            return Err(RemoteWalletError::InvalidInput(
+                "Ledger app is too old to sign off-chain messages".to_string(),
+            ));
+        }
+        if message.len() > offchain_message::MAX_LEN_LEDGER + offchain_message::HEADER_LEN {
+            return Err(RemoteWalletError::InvalidInput(
+                "Off-chain message is too long for Ledger".to_string(),
+            ));
+        }
+        self.sign_data(derivation_path, message, commands::SIGN_OFFCHAIN_MESSAGE)
+    }
+}
+
 /// Check if the detected device is a valid `Ledger device` by checking both the product ID and the vendor ID
 pub fn is_valid_ledger(vendor_id: u16, product_id: u16) -> bool {
     vendor_id == LEDGER_VID
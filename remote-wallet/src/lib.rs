@@ -3,4 +3,5 @@ pub mod ledger;
 pub mod ledger_error;
 pub mod locator;
 pub mod remote_keypair;
+pub mod remote_signer;
 pub mod remote_wallet;
@@ -9,6 +9,7 @@ use {
     },
     solana_sdk::{
         derivation_path::DerivationPath,
+        offchain_message::OffchainMessage,
         pubkey::Pubkey,
         signature::{Signature, Signer, SignerError},
     },
@@ -54,6 +55,14 @@ impl Signer for RemoteKeypair {
         }
     }
 
+    fn sign_offchain_message(&self, message: &OffchainMessage) -> Result<Signature, SignerError> {
+        match &self.wallet_type {
+            RemoteWalletType::Ledger(wallet) => wallet
+                .sign_offchain_message(&self.derivation_path, &message.serialize())
+                .map_err(|e| e.into()),
+        }
+    }
+
     fn is_interactive(&self) -> bool {
         true
     }
@@ -0,0 +1,1079 @@
+//! Signing with keys held by a separate signing daemon, reached over TCP or a unix socket.
+//!
+//! A remote signer is located by a URI of the form
+//! `remote://<HOST>:<PORT>?pubkey=<PUBKEY>` or `remote:///<SOCKET_PATH>?pubkey=<PUBKEY>`,
+//! optionally followed by `&auth=<PATH>`, the keypair with which the client authenticates
+//! itself to the daemon. Without `auth`, the keypair is read from the file named by the
+//! `SOLANA_REMOTE_SIGNER_AUTH` environment variable.
+//!
+//! Each request is made on a fresh connection. The daemon opens with a random nonce and the
+//! client answers with its own nonce and the key it wants signatures from, signed with its
+//! authentication keypair; only clients whose authentication pubkeys the daemon recognizes are
+//! accepted. The daemon in turn proves it holds the requested key by signing both nonces with
+//! it. Every signing request is then signed by the client's authentication keypair over the
+//! daemon's nonce and an increasing sequence number, so requests cannot be forged or replayed
+//! outside the session. The daemon applies each key's [`SigningPolicy`] before signing.
+
+use {
+    rand::{thread_rng, Rng},
+    serde_derive::{Deserialize, Serialize},
+    solana_sdk::{
+        compute_budget::{ComputeBudget, PrioritizationFeeDetails},
+        fee_calculator::DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE,
+        message::Message,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signature, Signer, SignerError},
+        system_instruction::SystemInstruction,
+        system_program,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        convert::TryFrom,
+        env,
+        io::{self, Read, Write},
+        net::TcpStream,
+        path::PathBuf,
+        str::FromStr,
+        time::Duration,
+    },
+    thiserror::Error,
+    uriparse::URIReference,
+};
+
+pub const REMOTE_SIGNER_AUTH_ENV_VAR: &str = "SOLANA_REMOTE_SIGNER_AUTH";
+
+const PROTOCOL_VERSION: u8 = 2;
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+// Protocol domains start with 0xff so that nothing signed for the protocol is also a
+// well-formed transaction message
+const CLIENT_AUTH_DOMAIN: &[u8] = b"\xffsolana-remote-signer-client";
+const SERVER_AUTH_DOMAIN: &[u8] = b"\xffsolana-remote-signer-server";
+const REQUEST_DOMAIN: &[u8] = b"\xffsolana-remote-signer-request";
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+// The signature fee may rise to ten times its target, so a policy counts the most it may be
+const MAX_LAMPORTS_PER_SIGNATURE: u64 = DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE * 10;
+
+#[derive(Debug, Error)]
+pub enum RemoteSignerError {
+    #[error("invalid remote signer locator: {0}")]
+    InvalidLocator(String),
+
+    #[error("no authentication keypair; add `auth=<PATH>` to the locator or set {0}")]
+    MissingAuthKeypair(&'static str),
+
+    #[error("unable to read authentication keypair {0}: {1}")]
+    AuthKeypair(String, String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+
+    #[error("frame of {0} bytes exceeds the limit")]
+    FrameTooLarge(usize),
+
+    #[error("protocol error: {0}")]
+    Protocol(String),
+
+    #[error("request refused: {0}")]
+    Refused(String),
+}
+
+impl From<RemoteSignerError> for SignerError {
+    fn from(err: RemoteSignerError) -> SignerError {
+        match err {
+            RemoteSignerError::Io(_) => SignerError::Connection(err.to_string()),
+            RemoteSignerError::InvalidLocator(_)
+            | RemoteSignerError::MissingAuthKeypair(_)
+            | RemoteSignerError::AuthKeypair(..) => SignerError::InvalidInput(err.to_string()),
+            _ => SignerError::Protocol(err.to_string()),
+        }
+    }
+}
+
+/// Where a remote signer listens
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoteSignerAddress {
+    /// `host:port`
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl std::fmt::Display for RemoteSignerAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{}", address),
+            Self::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemoteSignerLocator {
+    pub address: RemoteSignerAddress,
+    pub pubkey: Pubkey,
+    pub auth_keypair_path: Option<String>,
+}
+
+impl RemoteSignerLocator {
+    pub fn new_from_path<P: AsRef<str>>(path: P) -> Result<Self, RemoteSignerError> {
+        let uri = URIReference::try_from(path.as_ref())
+            .map_err(|err| RemoteSignerError::InvalidLocator(err.to_string()))?;
+        Self::new_from_uri(&uri)
+    }
+
+    pub fn new_from_uri(uri: &URIReference<'_>) -> Result<Self, RemoteSignerError> {
+        let invalid = |reason: &str| RemoteSignerError::InvalidLocator(reason.to_string());
+        let host = uri
+            .host()
+            .map(|host| host.to_string())
+            .ok_or_else(|| invalid("missing host or socket path"))?;
+        let address = if host.is_empty() {
+            let path = uri.path().to_string();
+            if path.is_empty() || path == "/" {
+                return Err(invalid("missing socket path"));
+            }
+            RemoteSignerAddress::Unix(PathBuf::from(path))
+        } else {
+            let port = uri.port().ok_or_else(|| invalid("missing port"))?;
+            RemoteSignerAddress::Tcp(format!("{}:{}", host, port))
+        };
+
+        let query = qstring::QString::from(uri.query().map(|query| query.as_str()).unwrap_or(""));
+        let pubkey = query
+            .get("pubkey")
+            .ok_or_else(|| invalid("missing `pubkey` query"))?;
+        let pubkey = Pubkey::from_str(pubkey).map_err(|_| invalid("invalid pubkey"))?;
+        let auth_keypair_path = query.get("auth").map(|path| path.to_string());
+        if query
+            .to_pairs()
+            .iter()
+            .any(|(key, _)| *key != "pubkey" && *key != "auth")
+        {
+            return Err(invalid("unsupported query field"));
+        }
+
+        Ok(Self {
+            address,
+            pubkey,
+            auth_keypair_path,
+        })
+    }
+}
+
+/// A message from a signing daemon to its client
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum ServerMessage {
+    Challenge {
+        version: u8,
+        nonce: [u8; 32],
+    },
+    /// The requested key's signature of [`server_auth_message`]
+    Authenticated(Signature),
+    Signature(Signature),
+    Error(String),
+}
+
+/// A message from a client to a signing daemon
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum ClientMessage {
+    /// `signature` is the client's signature of [`client_auth_message`]
+    Authenticate {
+        client: Pubkey,
+        pubkey: Pubkey,
+        nonce: [u8; 32],
+        signature: Signature,
+    },
+    /// `signature` is the client's signature of [`request_message`]
+    Sign {
+        pubkey: Pubkey,
+        message: Vec<u8>,
+        seq: u64,
+        signature: Signature,
+    },
+}
+
+fn client_auth_message(
+    server_nonce: &[u8; 32],
+    client_nonce: &[u8; 32],
+    pubkey: &Pubkey,
+) -> Vec<u8> {
+    [
+        CLIENT_AUTH_DOMAIN,
+        server_nonce,
+        client_nonce,
+        pubkey.as_ref(),
+    ]
+    .concat()
+}
+
+fn server_auth_message(
+    server_nonce: &[u8; 32],
+    client_nonce: &[u8; 32],
+    client: &Pubkey,
+) -> Vec<u8> {
+    [
+        SERVER_AUTH_DOMAIN,
+        server_nonce,
+        client_nonce,
+        client.as_ref(),
+    ]
+    .concat()
+}
+
+fn request_message(server_nonce: &[u8; 32], seq: u64, pubkey: &Pubkey, message: &[u8]) -> Vec<u8> {
+    [
+        REQUEST_DOMAIN,
+        server_nonce,
+        &seq.to_le_bytes(),
+        pubkey.as_ref(),
+        message,
+    ]
+    .concat()
+}
+
+fn write_frame<W: Write, T: serde::Serialize>(
+    stream: &mut W,
+    value: &T,
+) -> Result<(), RemoteSignerError> {
+    let bytes = bincode::serialize(value)?;
+    if bytes.len() > MAX_FRAME_SIZE {
+        return Err(RemoteSignerError::FrameTooLarge(bytes.len()));
+    }
+    stream.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    stream.write_all(&bytes)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_frame<R: Read, T: serde::de::DeserializeOwned>(
+    stream: &mut R,
+) -> Result<T, RemoteSignerError> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(RemoteSignerError::FrameTooLarge(len));
+    }
+    let mut bytes = vec![0u8; len];
+    stream.read_exact(&mut bytes)?;
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+enum Connection {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+
+impl Connection {
+    fn connect(address: &RemoteSignerAddress) -> Result<Self, RemoteSignerError> {
+        match address {
+            RemoteSignerAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+                stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+                Ok(Self::Tcp(stream))
+            }
+            #[cfg(unix)]
+            RemoteSignerAddress::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+                stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+                Ok(Self::Unix(stream))
+            }
+            #[cfg(not(unix))]
+            RemoteSignerAddress::Unix(_) => Err(RemoteSignerError::InvalidLocator(
+                "unix sockets are not supported on this platform".to_string(),
+            )),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A key held by a signing daemon
+pub struct RemoteSigner {
+    address: RemoteSignerAddress,
+    pubkey: Pubkey,
+    auth_keypair: Keypair,
+}
+
+impl RemoteSigner {
+    pub fn new(locator: RemoteSignerLocator) -> Result<Self, RemoteSignerError> {
+        let auth_keypair_path = match locator.auth_keypair_path {
+            Some(path) => path,
+            None => env::var(REMOTE_SIGNER_AUTH_ENV_VAR)
+                .map_err(|_| RemoteSignerError::MissingAuthKeypair(REMOTE_SIGNER_AUTH_ENV_VAR))?,
+        };
+        let auth_keypair = read_keypair_file(&auth_keypair_path)
+            .map_err(|err| RemoteSignerError::AuthKeypair(auth_keypair_path, err.to_string()))?;
+        Ok(Self::new_with_auth_keypair(
+            locator.address,
+            locator.pubkey,
+            auth_keypair,
+        ))
+    }
+
+    pub fn new_with_auth_keypair(
+        address: RemoteSignerAddress,
+        pubkey: Pubkey,
+        auth_keypair: Keypair,
+    ) -> Self {
+        Self {
+            address,
+            pubkey,
+            auth_keypair,
+        }
+    }
+
+    fn request_signature(&self, message: &[u8]) -> Result<Signature, RemoteSignerError> {
+        let mut connection = Connection::connect(&self.address)?;
+        let mut session =
+            ClientSession::authenticate(&mut connection, &self.auth_keypair, &self.pubkey)?;
+        session.sign(&mut connection, message)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        Ok(self.request_signature(message)?)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+/// The client side of an authenticated connection
+struct ClientSession<'a> {
+    auth_keypair: &'a Keypair,
+    pubkey: Pubkey,
+    server_nonce: [u8; 32],
+    seq: u64,
+}
+
+impl<'a> ClientSession<'a> {
+    fn authenticate<S: Read + Write>(
+        stream: &mut S,
+        auth_keypair: &'a Keypair,
+        pubkey: &Pubkey,
+    ) -> Result<Self, RemoteSignerError> {
+        let server_nonce = match read_frame(stream)? {
+            ServerMessage::Challenge { version, nonce } if version == PROTOCOL_VERSION => nonce,
+            ServerMessage::Challenge { version, .. } => {
+                return Err(RemoteSignerError::Protocol(format!(
+                    "unsupported protocol version {}",
+                    version
+                )))
+            }
+            message => return Err(unexpected(message)),
+        };
+        let client_nonce: [u8; 32] = thread_rng().gen();
+        let client = auth_keypair.pubkey();
+        write_frame(
+            stream,
+            &ClientMessage::Authenticate {
+                client,
+                pubkey: *pubkey,
+                nonce: client_nonce,
+                signature: auth_keypair.sign_message(&client_auth_message(
+                    &server_nonce,
+                    &client_nonce,
+                    pubkey,
+                )),
+            },
+        )?;
+        match read_frame(stream)? {
+            ServerMessage::Authenticated(signature) => {
+                if signature.verify(
+                    pubkey.as_ref(),
+                    &server_auth_message(&server_nonce, &client_nonce, &client),
+                ) {
+                    Ok(Self {
+                        auth_keypair,
+                        pubkey: *pubkey,
+                        server_nonce,
+                        seq: 0,
+                    })
+                } else {
+                    Err(RemoteSignerError::Protocol(format!(
+                        "signing daemon failed to prove it holds {}",
+                        pubkey
+                    )))
+                }
+            }
+            message => Err(unexpected(message)),
+        }
+    }
+
+    fn sign<S: Read + Write>(
+        &mut self,
+        stream: &mut S,
+        message: &[u8],
+    ) -> Result<Signature, RemoteSignerError> {
+        let seq = self.seq;
+        self.seq += 1;
+        write_frame(
+            stream,
+            &ClientMessage::Sign {
+                pubkey: self.pubkey,
+                message: message.to_vec(),
+                seq,
+                signature: self.auth_keypair.sign_message(&request_message(
+                    &self.server_nonce,
+                    seq,
+                    &self.pubkey,
+                    message,
+                )),
+            },
+        )?;
+        match read_frame(stream)? {
+            ServerMessage::Signature(signature) => {
+                if signature.verify(self.pubkey.as_ref(), message) {
+                    Ok(signature)
+                } else {
+                    Err(RemoteSignerError::Protocol(
+                        "signing daemon returned an invalid signature".to_string(),
+                    ))
+                }
+            }
+            message => Err(unexpected(message)),
+        }
+    }
+}
+
+fn unexpected(message: ServerMessage) -> RemoteSignerError {
+    match message {
+        ServerMessage::Error(err) => RemoteSignerError::Refused(err),
+        message => RemoteSignerError::Protocol(format!("unexpected message: {:?}", message)),
+    }
+}
+
+/// Limits on what a signing daemon will sign with a key
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SigningPolicy {
+    /// Programs that a message may invoke; `None` allows any
+    pub allowed_programs: Option<HashSet<Pubkey>>,
+    /// Most lamports that a message may move out of the key, counting the most it may pay in
+    /// fees. A message with an instruction that uses the key in a way the policy cannot value,
+    /// anything other than System program transfers, account creation and nonce advances, is
+    /// refused.
+    pub max_lamports: Option<u64>,
+}
+
+impl SigningPolicy {
+    /// Check a message to be signed by `signer`, which must be a serialized transaction
+    /// `Message` unless the policy places no limits at all
+    pub fn check(&self, signer: &Pubkey, message: &[u8]) -> Result<(), String> {
+        if self.allowed_programs.is_none() && self.max_lamports.is_none() {
+            return Ok(());
+        }
+        let message: Message = bincode::deserialize(message)
+            .map_err(|_| "message is not a transaction message".to_string())?;
+
+        if let Some(allowed_programs) = &self.allowed_programs {
+            for instruction in &message.instructions {
+                let program_id = instruction.program_id(&message.account_keys);
+                if !allowed_programs.contains(program_id) {
+                    return Err(format!("program {} is not allowed", program_id));
+                }
+            }
+        }
+
+        if let Some(max_lamports) = self.max_lamports {
+            let lamports = Self::lamports_moved_from(signer, &message)?;
+            if lamports > max_lamports {
+                return Err(format!(
+                    "message moves {} lamports, more than the limit of {}",
+                    lamports, max_lamports
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Most lamports that `message` may move out of `signer`, including the fees it pays as fee
+    /// payer, or an error if an instruction uses `signer` in a way that cannot be valued
+    fn lamports_moved_from(signer: &Pubkey, message: &Message) -> Result<u64, String> {
+        let account_at = |accounts: &[u8], position: usize| {
+            accounts
+                .get(position)
+                .and_then(|index| message.account_keys.get(*index as usize))
+        };
+        let mut lamports = 0u64;
+        for instruction in &message.instructions {
+            let uses_signer = instruction
+                .accounts
+                .iter()
+                .any(|index| message.account_keys.get(*index as usize) == Some(signer));
+            if !uses_signer {
+                continue;
+            }
+            let program_id = instruction.program_id(&message.account_keys);
+            let system_instruction = if system_program::check_id(program_id) {
+                bincode::deserialize(&instruction.data).ok()
+            } else {
+                None
+            };
+            let (moved, funder_position) = match system_instruction {
+                Some(SystemInstruction::Transfer { lamports })
+                | Some(SystemInstruction::CreateAccount { lamports, .. })
+                | Some(SystemInstruction::CreateAccountWithSeed { lamports, .. }) => (lamports, 0),
+                // The funding account is derived from the base, which signs
+                Some(SystemInstruction::TransferWithSeed { lamports, .. }) => (lamports, 1),
+                Some(SystemInstruction::AdvanceNonceAccount) => (0, 0),
+                _ => {
+                    return Err(format!(
+                        "instruction for program {} uses the key in a way the policy cannot value",
+                        program_id
+                    ))
+                }
+            };
+            if account_at(&instruction.accounts, funder_position) == Some(signer) {
+                lamports = lamports.saturating_add(moved);
+            }
+        }
+
+        if message.account_keys.first() == Some(signer) {
+            let mut compute_budget = ComputeBudget::new();
+            let compute_unit_price = compute_budget
+                .process_instructions(&message.instructions, &message.account_keys, true)
+                .map_err(|_| "invalid compute budget instruction".to_string())?;
+            let prioritization_fee = PrioritizationFeeDetails {
+                compute_unit_price,
+                compute_unit_limit: compute_budget.max_units,
+            }
+            .fee();
+            let signature_fee = u64::from(message.header.num_required_signatures)
+                .saturating_mul(MAX_LAMPORTS_PER_SIGNATURE);
+            lamports = lamports
+                .saturating_add(signature_fee)
+                .saturating_add(prioritization_fee);
+        }
+        Ok(lamports)
+    }
+}
+
+/// A client connection that completed the handshake with a `RemoteSignerServer`
+pub struct ServerSession {
+    client: Pubkey,
+    pubkey: Pubkey,
+    nonce: [u8; 32],
+}
+
+impl ServerSession {
+    /// The authenticated client
+    pub fn client(&self) -> &Pubkey {
+        &self.client
+    }
+}
+
+/// The server side of the signing protocol, holding keypairs and their policies
+pub struct RemoteSignerServer {
+    keys: HashMap<Pubkey, (Keypair, SigningPolicy)>,
+    authorized_clients: HashSet<Pubkey>,
+}
+
+impl RemoteSignerServer {
+    pub fn new(keys: Vec<(Keypair, SigningPolicy)>, authorized_clients: HashSet<Pubkey>) -> Self {
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|(keypair, policy)| (keypair.pubkey(), (keypair, policy)))
+                .collect(),
+            authorized_clients,
+        }
+    }
+
+    /// Serve one client connection until it closes. Returns the authenticated client.
+    pub fn serve<S: Read + Write>(&self, stream: &mut S) -> Result<Pubkey, RemoteSignerError> {
+        let session = self.authenticate(stream)?;
+        self.serve_session(session, stream)
+    }
+
+    /// Run the handshake on a new client connection, authenticating the client and the key it
+    /// asks to use
+    pub fn authenticate<S: Read + Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<ServerSession, RemoteSignerError> {
+        let nonce: [u8; 32] = thread_rng().gen();
+        write_frame(
+            stream,
+            &ServerMessage::Challenge {
+                version: PROTOCOL_VERSION,
+                nonce,
+            },
+        )?;
+        match read_frame(stream)? {
+            ClientMessage::Authenticate {
+                client,
+                pubkey,
+                nonce: client_nonce,
+                signature,
+            } if self.authorized_clients.contains(&client)
+                && signature.verify(
+                    client.as_ref(),
+                    &client_auth_message(&nonce, &client_nonce, &pubkey),
+                ) =>
+            {
+                match self.keys.get(&pubkey) {
+                    Some((keypair, _policy)) => {
+                        let proof = keypair.sign_message(&server_auth_message(
+                            &nonce,
+                            &client_nonce,
+                            &client,
+                        ));
+                        write_frame(stream, &ServerMessage::Authenticated(proof))?;
+                        Ok(ServerSession {
+                            client,
+                            pubkey,
+                            nonce,
+                        })
+                    }
+                    None => {
+                        let err = format!("unknown key {}", pubkey);
+                        write_frame(stream, &ServerMessage::Error(err.clone()))?;
+                        Err(RemoteSignerError::Refused(err))
+                    }
+                }
+            }
+            _ => Self::refuse(stream, "authentication failed"),
+        }
+    }
+
+    /// Serve the signing requests of an authenticated session until the client closes the
+    /// connection. Returns the authenticated client.
+    pub fn serve_session<S: Read + Write>(
+        &self,
+        session: ServerSession,
+        stream: &mut S,
+    ) -> Result<Pubkey, RemoteSignerError> {
+        let ServerSession {
+            client,
+            pubkey: session_pubkey,
+            nonce,
+        } = session;
+        let mut last_seq = None;
+        loop {
+            let request = match read_frame(stream) {
+                Ok(request) => request,
+                Err(RemoteSignerError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Ok(client)
+                }
+                Err(err) => return Err(err),
+            };
+            let response = match request {
+                ClientMessage::Sign {
+                    pubkey,
+                    message,
+                    seq,
+                    signature,
+                } => {
+                    let replayed = last_seq.map_or(false, |last_seq| seq <= last_seq);
+                    if replayed
+                        || !signature.verify(
+                            client.as_ref(),
+                            &request_message(&nonce, seq, &pubkey, &message),
+                        )
+                    {
+                        return Self::refuse(stream, "request authentication failed");
+                    }
+                    last_seq = Some(seq);
+                    if pubkey == session_pubkey {
+                        self.sign(&pubkey, &message)
+                    } else {
+                        ServerMessage::Error(format!(
+                            "key {} was not authenticated for this session",
+                            pubkey
+                        ))
+                    }
+                }
+                ClientMessage::Authenticate { .. } => {
+                    ServerMessage::Error("already authenticated".to_string())
+                }
+            };
+            write_frame(stream, &response)?;
+        }
+    }
+
+    fn refuse<S: Write, T>(stream: &mut S, reason: &str) -> Result<T, RemoteSignerError> {
+        write_frame(stream, &ServerMessage::Error(reason.to_string()))?;
+        Err(RemoteSignerError::Refused(reason.to_string()))
+    }
+
+    fn sign(&self, pubkey: &Pubkey, message: &[u8]) -> ServerMessage {
+        match self.keys.get(pubkey) {
+            None => ServerMessage::Error(format!("unknown key {}", pubkey)),
+            Some((keypair, policy)) => match policy.check(pubkey, message) {
+                Ok(()) => ServerMessage::Signature(keypair.sign_message(message)),
+                Err(err) => ServerMessage::Error(err),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            compute_budget::ComputeBudgetInstruction,
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            stake, system_instruction,
+        },
+        std::{net::TcpListener, sync::Arc, thread},
+    };
+
+    #[test]
+    fn test_remote_signer_locator() {
+        let pubkey = Pubkey::new_unique();
+        let locator = RemoteSignerLocator::new_from_path(format!(
+            "remote://signer.internal:7700?pubkey={}",
+            pubkey
+        ))
+        .unwrap();
+        assert_eq!(
+            locator,
+            RemoteSignerLocator {
+                address: RemoteSignerAddress::Tcp("signer.internal:7700".to_string()),
+                pubkey,
+                auth_keypair_path: None,
+            }
+        );
+
+        let locator = RemoteSignerLocator::new_from_path(format!(
+            "remote:///run/solana/signer.sock?pubkey={}&auth=/etc/solana/auth.json",
+            pubkey
+        ))
+        .unwrap();
+        assert_eq!(
+            locator,
+            RemoteSignerLocator {
+                address: RemoteSignerAddress::Unix(PathBuf::from("/run/solana/signer.sock")),
+                pubkey,
+                auth_keypair_path: Some("/etc/solana/auth.json".to_string()),
+            }
+        );
+
+        for bad in &[
+            format!("remote://signer.internal?pubkey={}", pubkey),
+            "remote://signer.internal:7700".to_string(),
+            "remote://signer.internal:7700?pubkey=bogus".to_string(),
+            format!("remote://signer.internal:7700?pubkey={}&other=1", pubkey),
+        ] {
+            assert!(matches!(
+                RemoteSignerLocator::new_from_path(bad),
+                Err(RemoteSignerError::InvalidLocator(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_signing_policy() {
+        let signer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let transfer = |lamports| {
+            bincode::serialize(&Message::new(
+                &[system_instruction::transfer(&signer, &to, lamports)],
+                Some(&payer),
+            ))
+            .unwrap()
+        };
+
+        assert!(SigningPolicy::default().check(&signer, b"anything").is_ok());
+
+        let policy = SigningPolicy {
+            allowed_programs: None,
+            max_lamports: Some(100),
+        };
+        assert!(policy.check(&signer, &transfer(100)).is_ok());
+        assert!(policy.check(&signer, &transfer(101)).is_err());
+        // Only lamports leaving the signer count
+        assert!(policy.check(&to, &transfer(101)).is_ok());
+        assert!(policy.check(&signer, b"not a message").is_err());
+
+        let policy = SigningPolicy {
+            allowed_programs: Some(vec![Pubkey::new_unique()].into_iter().collect()),
+            max_lamports: None,
+        };
+        assert!(policy.check(&signer, &transfer(1)).is_err());
+    }
+
+    #[test]
+    fn test_signing_policy_refuses_unvalued_instructions() {
+        let signer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let nonce = Pubkey::new_unique();
+        let message = |instructions: &[Instruction]| {
+            bincode::serialize(&Message::new(instructions, Some(&payer))).unwrap()
+        };
+        let policy = SigningPolicy {
+            allowed_programs: None,
+            max_lamports: Some(100),
+        };
+
+        // Handing the account to another program
+        let assign = system_instruction::assign(&signer, &Pubkey::new_unique());
+        assert!(policy.check(&signer, &message(&[assign])).is_err());
+
+        // Withdrawing from a nonce account the key is authority of
+        let withdraw_nonce =
+            system_instruction::withdraw_nonce_account(&nonce, &signer, &to, 1_000);
+        assert!(policy.check(&signer, &message(&[withdraw_nonce])).is_err());
+        let advance_nonce = system_instruction::advance_nonce_account(&nonce, &signer);
+        assert!(policy.check(&signer, &message(&[advance_nonce])).is_ok());
+
+        // Withdrawing from a stake account the key is withdraw authority of
+        let withdraw_stake =
+            stake::instruction::withdraw(&Pubkey::new_unique(), &signer, &to, 1_000, None);
+        assert!(policy.check(&signer, &message(&[withdraw_stake])).is_err());
+
+        // Transferring tokens the key owns
+        let token_transfer = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[3, 232, 3, 0, 0, 0, 0, 0, 0],
+            vec![
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new(to, false),
+                AccountMeta::new_readonly(signer, true),
+            ],
+        );
+        assert!(policy.check(&signer, &message(&[token_transfer])).is_err());
+
+        // Instructions that don't use the key are of no concern
+        let unrelated = system_instruction::transfer(&payer, &to, 1_000);
+        assert!(policy.check(&signer, &message(&[unrelated])).is_ok());
+    }
+
+    #[test]
+    fn test_signing_policy_counts_fees() {
+        let signer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let message = |instructions: &[Instruction]| {
+            bincode::serialize(&Message::new(instructions, Some(&signer))).unwrap()
+        };
+        let transfer = system_instruction::transfer(&signer, &to, 10);
+        let policy = |max_lamports| SigningPolicy {
+            allowed_programs: None,
+            max_lamports: Some(max_lamports),
+        };
+
+        // Paying the signature fee
+        assert!(policy(10)
+            .check(&signer, &message(&[transfer.clone()]))
+            .is_err());
+        assert!(policy(MAX_LAMPORTS_PER_SIGNATURE + 10)
+            .check(&signer, &message(&[transfer.clone()]))
+            .is_ok());
+
+        // ...and the prioritization fee
+        let prioritized = message(&[
+            ComputeBudgetInstruction::request_units(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(5_000_000),
+            transfer,
+        ]);
+        assert!(policy(MAX_LAMPORTS_PER_SIGNATURE + 10)
+            .check(&signer, &prioritized)
+            .is_err());
+        assert!(policy(MAX_LAMPORTS_PER_SIGNATURE + 1_000_010)
+            .check(&signer, &prioritized)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_remote_signer_round_trip() {
+        let key = Keypair::new();
+        let pubkey = key.pubkey();
+        let auth_keypair = Keypair::new();
+        let server = Arc::new(RemoteSignerServer::new(
+            vec![(
+                key,
+                SigningPolicy {
+                    allowed_programs: None,
+                    max_lamports: Some(MAX_LAMPORTS_PER_SIGNATURE + 10),
+                },
+            )],
+            vec![auth_keypair.pubkey()].into_iter().collect(),
+        ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = RemoteSignerAddress::Tcp(listener.local_addr().unwrap().to_string());
+        let server_thread = thread::spawn(move || {
+            let mut results = vec![];
+            for stream in listener.incoming().take(3) {
+                results.push(server.serve(&mut stream.unwrap()).is_ok());
+            }
+            results
+        });
+
+        let to = Pubkey::new_unique();
+        let message = |lamports| {
+            let mut message = Message::new(
+                &[system_instruction::transfer(&pubkey, &to, lamports)],
+                Some(&pubkey),
+            );
+            message.recent_blockhash = Hash::new_unique();
+            message.serialize()
+        };
+
+        let signer = RemoteSigner::new_with_auth_keypair(address.clone(), pubkey, auth_keypair);
+        assert_eq!(signer.pubkey(), pubkey);
+        let allowed = message(10);
+        let signature = signer.try_sign_message(&allowed).unwrap();
+        assert!(signature.verify(pubkey.as_ref(), &allowed));
+
+        // Refused by the key's policy
+        assert!(matches!(
+            signer.request_signature(&message(11)),
+            Err(RemoteSignerError::Refused(_))
+        ));
+
+        // An unknown client cannot authenticate
+        let signer = RemoteSigner::new_with_auth_keypair(address, pubkey, Keypair::new());
+        assert!(matches!(
+            signer.request_signature(&allowed),
+            Err(RemoteSignerError::Refused(_))
+        ));
+
+        assert_eq!(server_thread.join().unwrap(), vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_remote_signer_request_authentication() {
+        let key = Keypair::new();
+        let pubkey = key.pubkey();
+        let auth_keypair = Keypair::new();
+        let server = Arc::new(RemoteSignerServer::new(
+            vec![(key, SigningPolicy::default())],
+            vec![auth_keypair.pubkey()].into_iter().collect(),
+        ));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = RemoteSignerAddress::Tcp(listener.local_addr().unwrap().to_string());
+        let server_thread = thread::spawn(move || {
+            listener
+                .incoming()
+                .take(3)
+                .map(|stream| server.serve(&mut stream.unwrap()).is_ok())
+                .collect::<Vec<_>>()
+        });
+        let request = |session: &ClientSession, seq, signer: &Keypair| ClientMessage::Sign {
+            pubkey,
+            message: b"message".to_vec(),
+            seq,
+            signature: signer.sign_message(&request_message(
+                &session.server_nonce,
+                seq,
+                &pubkey,
+                b"message",
+            )),
+        };
+
+        // A request signed by anyone but the authenticated client is refused
+        let mut connection = Connection::connect(&address).unwrap();
+        let session = ClientSession::authenticate(&mut connection, &auth_keypair, &pubkey).unwrap();
+        write_frame(&mut connection, &request(&session, 0, &Keypair::new())).unwrap();
+        assert!(matches!(
+            read_frame(&mut connection).unwrap(),
+            ServerMessage::Error(_)
+        ));
+
+        // A request cannot be replayed within the session
+        let mut connection = Connection::connect(&address).unwrap();
+        let session = ClientSession::authenticate(&mut connection, &auth_keypair, &pubkey).unwrap();
+        let replayed = request(&session, 0, &auth_keypair);
+        write_frame(&mut connection, &replayed).unwrap();
+        assert!(matches!(
+            read_frame(&mut connection).unwrap(),
+            ServerMessage::Signature(_)
+        ));
+        write_frame(&mut connection, &replayed).unwrap();
+        assert!(matches!(
+            read_frame(&mut connection).unwrap(),
+            ServerMessage::Error(_)
+        ));
+
+        // ...nor in another session
+        let mut connection = Connection::connect(&address).unwrap();
+        ClientSession::authenticate(&mut connection, &auth_keypair, &pubkey).unwrap();
+        write_frame(&mut connection, &replayed).unwrap();
+        assert!(matches!(
+            read_frame(&mut connection).unwrap(),
+            ServerMessage::Error(_)
+        ));
+
+        assert_eq!(server_thread.join().unwrap(), vec![false, false, false]);
+    }
+
+    #[test]
+    fn test_remote_signer_server_authentication() {
+        // A daemon that doesn't hold the requested key cannot pass for one that does
+        let pubkey = Pubkey::new_unique();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = RemoteSignerAddress::Tcp(listener.local_addr().unwrap().to_string());
+        let impostor = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let nonce = [7; 32];
+            write_frame(
+                &mut stream,
+                &ServerMessage::Challenge {
+                    version: PROTOCOL_VERSION,
+                    nonce,
+                },
+            )
+            .unwrap();
+            let client_nonce = match read_frame(&mut stream).unwrap() {
+                ClientMessage::Authenticate { nonce, .. } => nonce,
+                message => panic!("unexpected message: {:?}", message),
+            };
+            let proof = Keypair::new().sign_message(&server_auth_message(
+                &nonce,
+                &client_nonce,
+                &Pubkey::new_unique(),
+            ));
+            write_frame(&mut stream, &ServerMessage::Authenticated(proof)).unwrap();
+        });
+
+        let signer = RemoteSigner::new_with_auth_keypair(address, pubkey, Keypair::new());
+        assert!(matches!(
+            signer.request_signature(b"message"),
+            Err(RemoteSignerError::Protocol(_))
+        ));
+        impostor.join().unwrap();
+    }
+}
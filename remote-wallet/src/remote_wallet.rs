@@ -219,6 +219,13 @@ pub trait RemoteWallet {
         derivation_path: &DerivationPath,
         data: &[u8],
     ) -> Result<Signature, RemoteWalletError>;
+
+    /// Sign a serialized off-chain message with wallet managing pubkey at derivation path m/44'/501'/<account>'/<change>'.
+    fn sign_offchain_message(
+        &self,
+        derivation_path: &DerivationPath,
+        message: &[u8],
+    ) -> Result<Signature, RemoteWalletError>;
 }
 
 /// `RemoteWallet` device
@@ -0,0 +1,221 @@
+//! The `account_history_service` records every account version stored by AccountsDb into the
+//! blockstore, so that RPC can serve account state as of rooted slots that are no longer held in
+//! BankForks.
+
+use {
+    crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender},
+    solana_ledger::blockstore::Blockstore,
+    solana_runtime::{
+        accounts_update_notifier_interface::{
+            AccountsUpdateNotifier, AccountsUpdateNotifierInterface,
+        },
+        append_vec::StoredMetaWriteVersion,
+    },
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        clock::Slot,
+        pubkey::Pubkey,
+    },
+    std::{
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+// Most account versions written to the blockstore in one batch
+const MAX_ACCOUNT_HISTORY_BATCH: usize = 4096;
+// Most updates queued for the writer. Once full, storing accounts waits on the writer rather than
+// dropping versions from the history.
+const MAX_ACCOUNT_HISTORY_QUEUE: usize = 16 * MAX_ACCOUNT_HISTORY_BATCH;
+
+type AccountVersion = (Slot, Pubkey, StoredMetaWriteVersion, Account);
+
+enum AccountHistoryUpdate {
+    Account(AccountVersion),
+    /// Every update to accounts in this rooted slot was queued before it
+    Rooted(Slot),
+}
+
+/// Forwards account updates to the `AccountHistoryService`, then on to the next notifier, if any
+#[derive(Debug)]
+struct AccountHistoryNotifier {
+    sender: Sender<AccountHistoryUpdate>,
+    next_notifier: Option<AccountsUpdateNotifier>,
+}
+
+impl AccountsUpdateNotifierInterface for AccountHistoryNotifier {
+    fn notify_account_update(
+        &self,
+        slot: Slot,
+        pubkey: &Pubkey,
+        account: &AccountSharedData,
+        write_version: StoredMetaWriteVersion,
+    ) {
+        // The service only stops receiving when exiting, so a failed send can be ignored
+        let _ = self.sender.send(AccountHistoryUpdate::Account((
+            slot,
+            *pubkey,
+            write_version,
+            account.clone().into(),
+        )));
+        if let Some(next_notifier) = &self.next_notifier {
+            next_notifier.notify_account_update(slot, pubkey, account, write_version);
+        }
+    }
+
+    fn notify_slot_rooted(&self, slot: Slot) {
+        let _ = self.sender.send(AccountHistoryUpdate::Rooted(slot));
+        if let Some(next_notifier) = &self.next_notifier {
+            next_notifier.notify_slot_rooted(slot);
+        }
+    }
+}
+
+pub struct AccountHistoryService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl AccountHistoryService {
+    /// Starts the service, returning it along with the notifier that feeds it. The notifier also
+    /// passes every update on to `next_notifier`, so that it can replace that notifier in
+    /// AccountsDb.
+    ///
+    /// `max_complete_account_history_slot` is raised to each rooted slot once every update to
+    /// accounts in it has been written.
+    pub fn new(
+        blockstore: Arc<Blockstore>,
+        next_notifier: Option<AccountsUpdateNotifier>,
+        max_complete_account_history_slot: Arc<AtomicU64>,
+        exit: &Arc<AtomicBool>,
+    ) -> (Self, AccountsUpdateNotifier) {
+        let (sender, receiver) = bounded(MAX_ACCOUNT_HISTORY_QUEUE);
+        let exit = exit.clone();
+        let thread_hdl = Builder::new()
+            .name("solana-account-history-writer".to_string())
+            .spawn(move || {
+                // Cleared once versions fail to write, as the history is then incomplete at every
+                // later root
+                let mut is_complete = true;
+                loop {
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Err(RecvTimeoutError::Disconnected) = Self::write_account_history_batch(
+                        &receiver,
+                        &blockstore,
+                        &max_complete_account_history_slot,
+                        &mut is_complete,
+                    ) {
+                        break;
+                    }
+                }
+            })
+            .unwrap();
+        let notifier = AccountHistoryNotifier {
+            sender,
+            next_notifier,
+        };
+        (Self { thread_hdl }, Arc::new(notifier))
+    }
+
+    fn write_account_history_batch(
+        receiver: &Receiver<AccountHistoryUpdate>,
+        blockstore: &Blockstore,
+        max_complete_account_history_slot: &AtomicU64,
+        is_complete: &mut bool,
+    ) -> Result<(), RecvTimeoutError> {
+        let first_update = receiver.recv_timeout(Duration::from_secs(1))?;
+        let mut versions = vec![];
+        let mut rooted_slot = None;
+        // A rooted slot ends the batch, so that it is only reported once the updates queued
+        // before it are written
+        for update in std::iter::once(first_update).chain(receiver.try_iter()) {
+            match update {
+                AccountHistoryUpdate::Account(version) => versions.push(version),
+                AccountHistoryUpdate::Rooted(slot) => {
+                    rooted_slot = Some(slot);
+                    break;
+                }
+            }
+            if versions.len() == MAX_ACCOUNT_HISTORY_BATCH {
+                break;
+            }
+        }
+        if !versions.is_empty() {
+            if let Err(err) = blockstore.write_account_history(&versions) {
+                error!("Failed to write account history: {:?}", err);
+                datapoint_error!(
+                    "account-history-service-error",
+                    ("error", format!("{:?}", err), String),
+                );
+                *is_complete = false;
+            } else {
+                datapoint_debug!(
+                    "account-history-service",
+                    ("num_versions", versions.len(), i64),
+                );
+            }
+        }
+        if let Some(slot) = rooted_slot.filter(|_| *is_complete) {
+            max_complete_account_history_slot.fetch_max(slot, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, solana_ledger::get_tmp_ledger_path, solana_sdk::system_program,
+        std::time::Instant,
+    };
+
+    #[test]
+    fn test_account_history_complete_at_root() {
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+            blockstore.set_roots(vec![2, 3].iter()).unwrap();
+            let max_complete_account_history_slot = Arc::new(AtomicU64::default());
+            let exit = Arc::new(AtomicBool::new(false));
+            let (service, notifier) = AccountHistoryService::new(
+                blockstore.clone(),
+                None,
+                max_complete_account_history_slot.clone(),
+                &exit,
+            );
+
+            let pubkey = Pubkey::new_unique();
+            let account = AccountSharedData::new(42, 0, &system_program::id());
+            notifier.notify_account_update(2, &pubkey, &account, 1);
+            notifier.notify_account_update(3, &pubkey, &account, 2);
+            notifier.notify_slot_rooted(3);
+
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while max_complete_account_history_slot.load(Ordering::SeqCst) < 3 {
+                assert!(Instant::now() < deadline);
+                thread::sleep(Duration::from_millis(10));
+            }
+            // Every update queued before the root was written by the time it was reported
+            let (slot, stored) = blockstore
+                .read_account_history(&pubkey, 3)
+                .unwrap()
+                .unwrap();
+            assert_eq!(slot, 3);
+            assert_eq!(AccountSharedData::from(stored), account);
+
+            exit.store(true, Ordering::Relaxed);
+            drop(notifier);
+            service.join().unwrap();
+        }
+        Blockstore::destroy(&ledger_path).unwrap();
+    }
+}
@@ -6,7 +6,10 @@ use {
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
         parsed_token_accounts::*,
         rpc_health::*,
-        send_transaction_service::{SendTransactionService, TransactionInfo},
+        send_transaction_service::{
+            self, SendTransactionService, TransactionInfo, TransactionRetryState,
+            TransactionRetryStatuses,
+        },
     },
     bincode::{config::Options, serialize},
     jsonrpc_core::{types::error, Error, Metadata, Result},
@@ -25,7 +28,8 @@ use {
         rpc_request::{
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
-            MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
+            MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE,
+            MAX_GET_PROGRAM_ACCOUNTS_PAGE_SIZE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
             MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
             NUM_LARGEST_ACCOUNTS,
         },
@@ -35,15 +39,17 @@ use {
     solana_faucet::faucet::request_airdrop_transaction,
     solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
     solana_ledger::{
-        blockstore::Blockstore, blockstore_db::BlockstoreError, get_tmp_ledger_path,
+        blockstore::{AccountModifications, Blockstore},
+        blockstore_db::BlockstoreError,
+        get_tmp_ledger_path,
         leader_schedule_cache::LeaderScheduleCache,
     },
     solana_metrics::inc_new_counter_info,
     solana_perf::packet::PACKET_DATA_SIZE,
     solana_runtime::{
         accounts::AccountAddressFilter,
-        accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey},
-        bank::{Bank, TransactionSimulationResult},
+        accounts_index::{AccountIndex, AccountSecondaryIndexes, CustomAccountIndex, IndexKey},
+        bank::{Bank, InnerInstructionsList, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
         inline_spl_token_v2_0::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
@@ -62,17 +68,21 @@ use {
         hash::Hash,
         pubkey::Pubkey,
         sanitize::Sanitize,
+        sanitized_transaction::SanitizedTransaction,
         signature::{Keypair, Signature, Signer},
         stake::state::StakeState,
         stake_history::StakeHistory,
         system_instruction,
         sysvar::stake_history,
         transaction::{self, Transaction, TransactionError},
+        versioned_transaction::VersionedTransaction,
     },
     solana_streamer::socket::SocketAddrSpace,
     solana_transaction_status::{
-        EncodedConfirmedTransaction, Reward, RewardType, TransactionConfirmationStatus,
-        TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
+        token_balances::collect_token_balances_from_accounts, AccountModification,
+        EncodedConfirmedTransaction, InnerInstructions, Reward, RewardType,
+        TransactionConfirmationStatus, TransactionStatus, UiConfirmedBlock, UiInnerInstructions,
+        UiTransactionEncoding, UiTransactionTokenBalance,
     },
     solana_vote_program::vote_state::{VoteState, MAX_LOCKOUT_HISTORY},
     spl_token_v2_0::{
@@ -82,7 +92,10 @@ use {
     std::{
         cmp::{max, min},
         collections::{HashMap, HashSet},
+        convert::TryInto,
         net::SocketAddr,
+        ops::RangeInclusive,
+        path::PathBuf,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -103,6 +116,10 @@ pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
 // response
 const MAX_RPC_EPOCH_CREDITS_HISTORY: usize = 5;
 
+// Limit the number of transactions a `get_account_modifications` request examines, as most of
+// the transactions that lock an account may leave it unchanged
+const MAX_GET_ACCOUNT_MODIFICATIONS_TRANSACTIONS: usize = 1_000;
+
 fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     let context = RpcResponseContext { slot: bank.slot() };
     Response { context, value }
@@ -132,6 +149,7 @@ fn is_finalized(
 pub struct JsonRpcConfig {
     pub enable_rpc_transaction_history: bool,
     pub enable_cpi_and_log_storage: bool,
+    pub enable_rpc_account_history: bool,
     pub faucet_addr: Option<SocketAddr>,
     pub health_check_slot_distance: u64,
     pub enable_bigtable_ledger_storage: bool,
@@ -140,6 +158,7 @@ pub struct JsonRpcConfig {
     pub account_indexes: AccountSecondaryIndexes,
     pub rpc_threads: usize,
     pub rpc_bigtable_timeout: Option<Duration>,
+    pub bigtable_local_storage_path: Option<PathBuf>,
     pub minimal_api: bool,
     pub obsolete_v1_7_api: bool,
     pub rpc_scan_and_fix_roots: bool,
@@ -157,6 +176,7 @@ pub struct JsonRpcRequestProcessor {
     cluster_info: Arc<ClusterInfo>,
     genesis_hash: Hash,
     transaction_sender: Arc<Mutex<Sender<TransactionInfo>>>,
+    transaction_retry_statuses: Arc<TransactionRetryStatuses>,
     runtime: Arc<Runtime>,
     bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
@@ -164,6 +184,8 @@ pub struct JsonRpcRequestProcessor {
     max_slots: Arc<MaxSlots>,
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
+    max_complete_account_history_slot: Arc<AtomicU64>,
+    account_history_start_slot: Slot,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -250,8 +272,11 @@ impl JsonRpcRequestProcessor {
         max_slots: Arc<MaxSlots>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
+        max_complete_account_history_slot: Arc<AtomicU64>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (sender, receiver) = channel();
+        // Account history is complete for the slots replayed after this root
+        let account_history_start_slot = bank_forks.read().unwrap().root();
         (
             Self {
                 config,
@@ -264,6 +289,7 @@ impl JsonRpcRequestProcessor {
                 cluster_info,
                 genesis_hash,
                 transaction_sender: Arc::new(Mutex::new(sender)),
+                transaction_retry_statuses: Arc::default(),
                 runtime,
                 bigtable_ledger_storage,
                 optimistically_confirmed_bank,
@@ -271,6 +297,8 @@ impl JsonRpcRequestProcessor {
                 max_slots,
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
+                max_complete_account_history_slot,
+                account_history_start_slot,
             },
             receiver,
         )
@@ -292,7 +320,19 @@ impl JsonRpcRequestProcessor {
         ));
         let tpu_address = cluster_info.my_contact_info().tpu;
         let (sender, receiver) = channel();
-        SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
+        let transaction_retry_statuses = Arc::<TransactionRetryStatuses>::default();
+        SendTransactionService::new_with_config(
+            tpu_address,
+            &bank_forks,
+            None,
+            receiver,
+            send_transaction_service::Config {
+                retry_rate_ms: 1000,
+                leader_forward_count: 1,
+                ..send_transaction_service::Config::default()
+            },
+            transaction_retry_statuses.clone(),
+        );
 
         Self {
             config: JsonRpcConfig::default(),
@@ -309,6 +349,7 @@ impl JsonRpcRequestProcessor {
             cluster_info,
             genesis_hash,
             transaction_sender: Arc::new(Mutex::new(sender)),
+            transaction_retry_statuses,
             runtime: Arc::new(Runtime::new().expect("Runtime")),
             bigtable_ledger_storage: None,
             optimistically_confirmed_bank: Arc::new(RwLock::new(OptimisticallyConfirmedBank {
@@ -318,21 +359,147 @@ impl JsonRpcRequestProcessor {
             max_slots: Arc::new(MaxSlots::default()),
             leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(bank)),
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
+            max_complete_account_history_slot: Arc::new(AtomicU64::default()),
+            account_history_start_slot: bank.slot(),
+        }
+    }
+
+    /// The bank selected by `commitment`, provided that it has reached `min_context_slot`
+    fn bank_with_min_context_slot(
+        &self,
+        commitment: Option<CommitmentConfig>,
+        min_context_slot: Option<Slot>,
+    ) -> Result<Arc<Bank>> {
+        let bank = self.bank(commitment);
+        match min_context_slot {
+            Some(min_context_slot) if bank.slot() < min_context_slot => {
+                Err(RpcCustomError::MinContextSlotNotReached {
+                    context_slot: bank.slot(),
+                }
+                .into())
+            }
+            _ => Ok(bank),
         }
     }
 
+    /// Where to read account state as of `at_slot`, on the fork of `bank`. Without `at_slot`,
+    /// accounts are read from `bank` itself.
+    fn accounts_at_slot(&self, bank: Arc<Bank>, at_slot: Option<Slot>) -> Result<AccountsAtSlot> {
+        let slot = match at_slot {
+            Some(slot) => slot,
+            None => {
+                return Ok(AccountsAtSlot::Bank {
+                    slot: bank.slot(),
+                    bank,
+                })
+            }
+        };
+        if slot > bank.slot() {
+            return Err(Error::invalid_params(format!(
+                "atSlot {} is later than the context slot {}",
+                slot,
+                bank.slot()
+            )));
+        }
+        // The state at a skipped slot is that of its closest ancestor
+        let parents = bank.parents();
+        if let Some(ancestor) = std::iter::once(bank)
+            .chain(parents)
+            .find(|ancestor| ancestor.slot() <= slot)
+        {
+            return Ok(AccountsAtSlot::Bank {
+                slot,
+                bank: ancestor,
+            });
+        }
+        // The history is written asynchronously, and is only complete up to the last rooted slot
+        // whose updates have all been written
+        if !self.config.enable_rpc_account_history
+            || slot < self.account_history_start_slot
+            || slot <= self.blockstore.lowest_cleanup_slot()
+            || slot
+                > self
+                    .max_complete_account_history_slot
+                    .load(Ordering::SeqCst)
+        {
+            return Err(RpcCustomError::AccountHistoryNotAvailable { slot }.into());
+        }
+        Ok(AccountsAtSlot::History {
+            slot,
+            root_bank: self.bank_forks.read().unwrap().root_bank(),
+        })
+    }
+
+    fn get_account_at_slot(
+        &self,
+        accounts: &AccountsAtSlot,
+        pubkey: &Pubkey,
+    ) -> Result<Option<AccountSharedData>> {
+        let (slot, root_bank) = match accounts {
+            AccountsAtSlot::Bank { bank, .. } => return Ok(bank.get_account(pubkey)),
+            AccountsAtSlot::History { slot, root_bank } => (*slot, root_bank),
+        };
+        let read_account_history = |slot| {
+            self.blockstore
+                .read_account_history(pubkey, slot)
+                .map_err(|err| {
+                    warn!("read_account_history failed: {:?}", err);
+                    Error::internal_error()
+                })
+        };
+        // An account last stored no later than `slot` is unchanged at the root
+        if let Some((account, modified_slot)) = root_bank.get_account_modified_slot(pubkey) {
+            if modified_slot <= slot {
+                return Ok(Some(account).filter(|account| account.lamports() > 0));
+            }
+        }
+        if let Some((_, account)) = read_account_history(slot)? {
+            let account = AccountSharedData::from(account);
+            return Ok(Some(account).filter(|account| account.lamports() > 0));
+        }
+        // Nothing was stored to the account between the start of the history and `slot`. If
+        // nothing has been stored up to the root either, the account did not exist at `slot`;
+        // otherwise its state at `slot` predates the history. Only a history complete up to the
+        // root can tell the two apart.
+        let is_complete_at_root = self
+            .max_complete_account_history_slot
+            .load(Ordering::SeqCst)
+            >= root_bank.slot();
+        if is_complete_at_root && read_account_history(root_bank.slot())?.is_none() {
+            Ok(None)
+        } else {
+            Err(RpcCustomError::AccountHistoryNotAvailable { slot }.into())
+        }
+    }
+
+    fn get_encoded_account_at_slot(
+        &self,
+        accounts: &AccountsAtSlot,
+        pubkey: &Pubkey,
+        encoding: UiAccountEncoding,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Option<UiAccount>> {
+        self.get_account_at_slot(accounts, pubkey)?
+            .map(|account| {
+                encode_account_for_bank(accounts.bank(), pubkey, account, encoding, data_slice)
+            })
+            .transpose()
+    }
+
     pub fn get_account_info(
         &self,
         pubkey: &Pubkey,
         config: Option<RpcAccountInfoConfig>,
     ) -> Result<RpcResponse<Option<UiAccount>>> {
         let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
+        let bank = self.bank_with_min_context_slot(config.commitment, config.min_context_slot)?;
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
 
-        let response = get_encoded_account(&bank, pubkey, encoding, config.data_slice)?;
-        Ok(new_response(&bank, response))
+        let accounts = self.accounts_at_slot(bank, config.at_slot)?;
+        let response =
+            self.get_encoded_account_at_slot(&accounts, pubkey, encoding, config.data_slice)?;
+        Ok(accounts.new_response(response))
     }
 
     pub fn get_multiple_accounts(
@@ -343,16 +510,21 @@ impl JsonRpcRequestProcessor {
         let mut accounts: Vec<Option<UiAccount>> = vec![];
 
         let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
+        let bank = self.bank_with_min_context_slot(config.commitment, config.min_context_slot)?;
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
         check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
 
+        let accounts_at_slot = self.accounts_at_slot(bank, config.at_slot)?;
         for pubkey in pubkeys {
-            let response_account =
-                get_encoded_account(&bank, &pubkey, encoding, config.data_slice)?;
+            let response_account = self.get_encoded_account_at_slot(
+                &accounts_at_slot,
+                &pubkey,
+                encoding,
+                config.data_slice,
+            )?;
             accounts.push(response_account)
         }
-        Ok(new_response(&bank, accounts))
+        Ok(accounts_at_slot.new_response(accounts))
     }
 
     pub fn get_minimum_balance_for_rent_exemption(
@@ -372,38 +544,109 @@ impl JsonRpcRequestProcessor {
         with_context: bool,
     ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
+        check_at_slot_unsupported(config.at_slot)?;
         let bank = self.bank(config.commitment);
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
         let keyed_accounts = {
             if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_owner(&bank, &owner, filters)?
+                self.get_filtered_spl_token_accounts_by_owner(&bank, &owner, filters, None)?
             } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters)?
+                self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters, None)?
             } else {
-                self.get_filtered_program_accounts(&bank, program_id, filters)?
+                self.get_filtered_program_accounts(&bank, program_id, filters, None)?
             }
         };
-        let result =
-            if program_id == &spl_token_id_v2_0() && encoding == UiAccountEncoding::JsonParsed {
-                get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
-            } else {
-                let mut encoded_accounts = vec![];
-                for (pubkey, account) in keyed_accounts {
-                    encoded_accounts.push(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    });
-                }
-                encoded_accounts
-            };
+        let result = encode_keyed_accounts(
+            &bank,
+            program_id,
+            keyed_accounts,
+            encoding,
+            data_slice_config,
+        )?;
         Ok(result).map(|result| match with_context {
             true => OptionalContext::Context(new_response(&bank, result)),
             false => OptionalContext::NoContext(result),
         })
     }
 
+    /// The bank to serve the page following `cursor` from: the bank the cursor was issued from.
+    /// Any other bank could hold a different state than the earlier pages, so the cursor expires
+    /// once BankForks no longer holds its slot, whether it was rooted past or abandoned.
+    fn program_accounts_cursor_bank(&self, cursor: &ProgramAccountsCursor) -> Result<Arc<Bank>> {
+        self.bank_forks
+            .read()
+            .unwrap()
+            .get(cursor.slot)
+            .cloned()
+            .ok_or_else(|| {
+                RpcCustomError::ProgramAccountsCursorExpired { slot: cursor.slot }.into()
+            })
+    }
+
+    /// Get one page of program accounts, in pubkey order. The first page is served from the bank
+    /// selected by the commitment level; later pages are served from the bank slot recorded in
+    /// the cursor, so every page of a query reflects the same state.
+    pub fn get_program_accounts_paginated(
+        &self,
+        program_id: &Pubkey,
+        config: Option<RpcAccountInfoConfig>,
+        filters: Vec<RpcFilterType>,
+        limit: usize,
+        cursor: Option<ProgramAccountsCursor>,
+    ) -> Result<RpcResponse<RpcKeyedAccountsPage>> {
+        let config = config.unwrap_or_default();
+        check_at_slot_unsupported(config.at_slot)?;
+        let bank = match &cursor {
+            Some(cursor) => self.program_accounts_cursor_bank(cursor)?,
+            None => self.bank(config.commitment),
+        };
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        let data_slice_config = config.data_slice;
+        check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+        // Fetch one extra account to learn whether another page follows this one
+        let page = Some(AccountsPage {
+            start_after: cursor.as_ref().map(|cursor| &cursor.last_pubkey),
+            limit: limit + 1,
+        });
+        let mut keyed_accounts = {
+            if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
+                self.get_filtered_spl_token_accounts_by_owner(&bank, &owner, filters, page)?
+            } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
+                self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters, page)?
+            } else {
+                self.get_filtered_program_accounts(&bank, program_id, filters, page)?
+            }
+        };
+        let next_cursor = if keyed_accounts.len() > limit {
+            keyed_accounts.truncate(limit);
+            keyed_accounts.last().map(|(pubkey, _)| {
+                ProgramAccountsCursor {
+                    slot: bank.slot(),
+                    last_pubkey: *pubkey,
+                }
+                .encode()
+            })
+        } else {
+            None
+        };
+        let accounts = encode_keyed_accounts(
+            &bank,
+            program_id,
+            keyed_accounts,
+            encoding,
+            data_slice_config,
+        )?;
+        Ok(new_response(
+            &bank,
+            RpcKeyedAccountsPage {
+                accounts,
+                next_cursor,
+            },
+        ))
+    }
+
     pub fn get_inflation_reward(
         &self,
         addresses: Vec<Pubkey>,
@@ -521,10 +764,16 @@ impl JsonRpcRequestProcessor {
     pub fn get_balance(
         &self,
         pubkey: &Pubkey,
-        commitment: Option<CommitmentConfig>,
-    ) -> RpcResponse<u64> {
-        let bank = self.bank(commitment);
-        new_response(&bank, bank.get_balance(pubkey))
+        config: Option<RpcContextConfig>,
+    ) -> Result<RpcResponse<u64>> {
+        let config = config.unwrap_or_default();
+        let bank = self.bank_with_min_context_slot(config.commitment, config.min_context_slot)?;
+        let accounts = self.accounts_at_slot(bank, config.at_slot)?;
+        let balance = self
+            .get_account_at_slot(&accounts, pubkey)?
+            .map(|account| account.lamports())
+            .unwrap_or_default();
+        Ok(accounts.new_response(balance))
     }
 
     fn get_recent_blockhash(
@@ -625,6 +874,41 @@ impl JsonRpcRequestProcessor {
         self.max_slots.shred_insert.load(Ordering::Relaxed)
     }
 
+    pub(crate) fn transaction_retry_statuses(&self) -> Arc<TransactionRetryStatuses> {
+        self.transaction_retry_statuses.clone()
+    }
+
+    fn get_transaction_retry_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<RpcTransactionRetryStatus> {
+        self.transaction_retry_statuses
+            .get(signature)
+            .map(|status| RpcTransactionRetryStatus {
+                state: match status.state {
+                    TransactionRetryState::Pending => RpcTransactionRetryState::Pending,
+                    TransactionRetryState::Rooted => RpcTransactionRetryState::Rooted,
+                    TransactionRetryState::Failed => RpcTransactionRetryState::Failed,
+                    TransactionRetryState::Expired => RpcTransactionRetryState::Expired,
+                    TransactionRetryState::MaxRetriesReached => {
+                        RpcTransactionRetryState::MaxRetriesReached
+                    }
+                    TransactionRetryState::Dropped => RpcTransactionRetryState::Dropped,
+                },
+                retries: status.retries,
+                max_retries: status.max_retries,
+                last_valid_slot: status.last_valid_slot,
+                leaders: status
+                    .leaders
+                    .into_iter()
+                    .map(|(identity, tpu)| RpcTransactionRetryLeader {
+                        identity: identity.map(|identity| identity.to_string()),
+                        tpu: tpu.to_string(),
+                    })
+                    .collect(),
+            })
+    }
+
     fn get_slot_leader(&self, commitment: Option<CommitmentConfig>) -> String {
         self.bank(commitment).collector_id().to_string()
     }
@@ -1389,6 +1673,44 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// The highest finalized slot, and the highest slot to search the transaction history up to
+    /// at `commitment`
+    fn highest_transaction_history_slots(&self, commitment: CommitmentConfig) -> (Slot, Slot) {
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let highest_slot = if commitment.is_confirmed() {
+            let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
+            confirmed_bank.slot()
+        } else {
+            highest_confirmed_root
+        };
+        (highest_confirmed_root, highest_slot)
+    }
+
+    /// The confirmation status of a transaction in `slot`, and its block time, which is taken
+    /// from the bank while the block is not finalized
+    fn transaction_confirmation(
+        &self,
+        slot: Slot,
+        block_time: Option<UnixTimestamp>,
+        highest_confirmed_root: Slot,
+    ) -> (TransactionConfirmationStatus, Option<UnixTimestamp>) {
+        if slot <= highest_confirmed_root {
+            (TransactionConfirmationStatus::Finalized, block_time)
+        } else {
+            let block_time = block_time.or_else(|| {
+                let r_bank_forks = self.bank_forks.read().unwrap();
+                r_bank_forks
+                    .get(slot)
+                    .map(|bank| bank.clock().unix_timestamp)
+            });
+            (TransactionConfirmationStatus::Confirmed, block_time)
+        }
+    }
+
     pub fn get_signatures_for_address(
         &self,
         address: Pubkey,
@@ -1401,17 +1723,8 @@ impl JsonRpcRequestProcessor {
         check_is_at_least_confirmed(commitment)?;
 
         if self.config.enable_rpc_transaction_history {
-            let highest_confirmed_root = self
-                .block_commitment_cache
-                .read()
-                .unwrap()
-                .highest_confirmed_root();
-            let highest_slot = if commitment.is_confirmed() {
-                let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
-                confirmed_bank.slot()
-            } else {
-                highest_confirmed_root
-            };
+            let (highest_confirmed_root, highest_slot) =
+                self.highest_transaction_history_slots(commitment);
 
             let mut results = self
                 .blockstore
@@ -1448,17 +1761,13 @@ impl JsonRpcRequestProcessor {
                 .into_iter()
                 .map(|x| {
                     let mut item: RpcConfirmedTransactionStatusWithSignature = x.into();
-                    if item.slot <= highest_confirmed_root {
-                        item.confirmation_status = Some(TransactionConfirmationStatus::Finalized);
-                    } else {
-                        item.confirmation_status = Some(TransactionConfirmationStatus::Confirmed);
-                        if item.block_time.is_none() {
-                            let r_bank_forks = self.bank_forks.read().unwrap();
-                            item.block_time = r_bank_forks
-                                .get(item.slot)
-                                .map(|bank| bank.clock().unix_timestamp);
-                        }
-                    }
+                    let (confirmation_status, block_time) = self.transaction_confirmation(
+                        item.slot,
+                        item.block_time,
+                        highest_confirmed_root,
+                    );
+                    item.confirmation_status = Some(confirmation_status);
+                    item.block_time = block_time;
                     item
                 })
                 .collect())
@@ -1467,6 +1776,161 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// The slots in which the account history is complete, if it is enabled. A data change is
+    /// detected by comparing with the preceding slot, so that must be in the history too.
+    fn account_history_slots(&self) -> Option<RangeInclusive<Slot>> {
+        if !self.config.enable_rpc_account_history {
+            return None;
+        }
+        let first_slot = self
+            .account_history_start_slot
+            .max(self.blockstore.lowest_cleanup_slot() + 1)
+            + 1;
+        Some(
+            first_slot
+                ..=self
+                    .max_complete_account_history_slot
+                    .load(Ordering::SeqCst),
+        )
+    }
+
+    pub fn get_account_modifications(
+        &self,
+        address: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<RpcAccountModifications> {
+        let commitment = commitment.unwrap_or_default();
+        check_is_at_least_confirmed(commitment)?;
+
+        if self.config.enable_rpc_transaction_history {
+            let (highest_confirmed_root, highest_slot) =
+                self.highest_transaction_history_slots(commitment);
+
+            let AccountModifications {
+                mut modifications,
+                mut next_before,
+                last_examined,
+                num_examined,
+            } = self
+                .blockstore
+                .get_account_modifications(
+                    address,
+                    highest_slot,
+                    before,
+                    until,
+                    limit,
+                    MAX_GET_ACCOUNT_MODIFICATIONS_TRANSACTIONS,
+                    self.account_history_slots(),
+                )
+                .map_err(|err| Error::invalid_params(format!("{}", err)))?;
+
+            // The search continues into Bigtable once the local ledger is exhausted
+            if next_before.is_none() && modifications.len() < limit {
+                if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
+                    next_before = self.get_account_modifications_from_bigtable(
+                        bigtable_ledger_storage,
+                        address,
+                        last_examined.or(before),
+                        until,
+                        limit,
+                        MAX_GET_ACCOUNT_MODIFICATIONS_TRANSACTIONS - num_examined,
+                        &mut modifications,
+                    );
+                }
+            }
+
+            Ok(RpcAccountModifications {
+                modifications: modifications
+                    .into_iter()
+                    .map(|x| {
+                        let mut item: RpcAccountModification = x.into();
+                        let (confirmation_status, block_time) = self.transaction_confirmation(
+                            item.slot,
+                            item.block_time,
+                            highest_confirmed_root,
+                        );
+                        item.confirmation_status = Some(confirmation_status);
+                        item.block_time = block_time;
+                        item
+                    })
+                    .collect(),
+                next_before: next_before.map(|signature| signature.to_string()),
+            })
+        } else {
+            Err(RpcCustomError::TransactionHistoryNotAvailable.into())
+        }
+    }
+
+    /// Adds the modifications found in Bigtable before `before` to `modifications`, examining
+    /// at most `max_transactions` transactions, and returns where to continue the search from if
+    /// it stopped early. Bigtable holds no account history, so data changes are not detected.
+    #[allow(clippy::too_many_arguments)]
+    fn get_account_modifications_from_bigtable(
+        &self,
+        bigtable_ledger_storage: &solana_storage_bigtable::LedgerStorage,
+        address: Pubkey,
+        mut before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        max_transactions: usize,
+        modifications: &mut Vec<AccountModification>,
+    ) -> Option<Signature> {
+        let mut num_examined = 0;
+        loop {
+            let batch_size = (limit - modifications.len()).min(max_transactions - num_examined);
+            if batch_size == 0 {
+                return before;
+            }
+            let candidates = match self.runtime.block_on(
+                bigtable_ledger_storage.get_confirmed_signatures_for_address(
+                    &address,
+                    before.as_ref(),
+                    until.as_ref(),
+                    batch_size,
+                ),
+            ) {
+                Ok(candidates) => candidates,
+                Err(err) => {
+                    warn!("{:?}", err);
+                    return before;
+                }
+            };
+            let exhausted = candidates.len() < batch_size;
+            for (candidate, _) in candidates {
+                let transaction = match self.runtime.block_on(
+                    bigtable_ledger_storage.get_confirmed_transaction(&candidate.signature),
+                ) {
+                    Ok(transaction) => transaction,
+                    Err(err) => {
+                        warn!("{:?}", err);
+                        return before;
+                    }
+                };
+                before = Some(candidate.signature);
+                num_examined += 1;
+                if let Some(modification) = transaction.and_then(|transaction| {
+                    AccountModification::from_transaction(
+                        &address,
+                        candidate,
+                        &transaction.transaction,
+                        false,
+                    )
+                }) {
+                    modifications.push(modification);
+                    if modifications.len() == limit {
+                        return before;
+                    }
+                }
+            }
+            if exhausted {
+                return None;
+            }
+        }
+    }
+
     pub fn get_first_available_block(&self) -> Slot {
         let slot = self
             .blockstore
@@ -1628,7 +2092,7 @@ impl JsonRpcRequestProcessor {
             ));
         }
         let mut token_balances: Vec<RpcTokenAccountBalance> = self
-            .get_filtered_spl_token_accounts_by_mint(&bank, mint, vec![])?
+            .get_filtered_spl_token_accounts_by_mint(&bank, mint, vec![], None)?
             .into_iter()
             .map(|(address, account)| {
                 let amount = TokenAccount::unpack(account.data())
@@ -1660,6 +2124,7 @@ impl JsonRpcRequestProcessor {
         config: Option<RpcAccountInfoConfig>,
     ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
+        check_at_slot_unsupported(config.at_slot)?;
         let bank = self.bank(config.commitment);
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
@@ -1677,7 +2142,7 @@ impl JsonRpcRequestProcessor {
         }
 
         let keyed_accounts =
-            self.get_filtered_spl_token_accounts_by_owner(&bank, owner, filters)?;
+            self.get_filtered_spl_token_accounts_by_owner(&bank, owner, filters, None)?;
         let accounts = if encoding == UiAccountEncoding::JsonParsed {
             get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
         } else {
@@ -1705,6 +2170,7 @@ impl JsonRpcRequestProcessor {
         config: Option<RpcAccountInfoConfig>,
     ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
+        check_at_slot_unsupported(config.at_slot)?;
         let bank = self.bank(config.commitment);
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
@@ -1729,13 +2195,13 @@ impl JsonRpcRequestProcessor {
         ];
         // Optional filter on Mint address, uses mint account index for scan
         let keyed_accounts = if let Some(mint) = mint {
-            self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters)?
+            self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters, None)?
         } else {
             // Filter on Token Account state
             filters.push(RpcFilterType::DataSize(
                 TokenAccount::get_packed_len() as u64
             ));
-            self.get_filtered_program_accounts(&bank, &token_program_id, filters)?
+            self.get_filtered_program_accounts(&bank, &token_program_id, filters, None)?
         };
         let accounts = if encoding == UiAccountEncoding::JsonParsed {
             get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
@@ -1757,12 +2223,14 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, accounts))
     }
 
-    /// Use a set of filters to get an iterator of keyed program accounts from a bank
+    /// Use a set of filters to get an iterator of keyed program accounts from a bank, or only
+    /// one `page` of them
     fn get_filtered_program_accounts(
         &self,
         bank: &Arc<Bank>,
         program_id: &Pubkey,
         filters: Vec<RpcFilterType>,
+        page: Option<AccountsPage>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         let filter_closure = |account: &AccountSharedData| {
             filters.iter().all(|filter_type| match filter_type {
@@ -1770,6 +2238,19 @@ impl JsonRpcRequestProcessor {
                 RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
             })
         };
+        if let Some((custom_index, index_key)) =
+            get_custom_index_filter(program_id, &filters, &self.config.account_indexes)
+        {
+            return get_filtered_indexed_accounts(
+                bank,
+                &IndexKey::Custom(custom_index, index_key),
+                |account| {
+                    // As with the program-id index below, stale entries are filtered out
+                    account.owner() == program_id && filter_closure(account)
+                },
+                page,
+            );
+        }
         if self
             .config
             .account_indexes
@@ -1780,33 +2261,42 @@ impl JsonRpcRequestProcessor {
                     index_key: program_id.to_string(),
                 });
             }
-            Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::ProgramId(*program_id), |account| {
+            get_filtered_indexed_accounts(
+                bank,
+                &IndexKey::ProgramId(*program_id),
+                |account| {
                     // The program-id account index checks for Account owner on inclusion. However, due
                     // to the current AccountsDb implementation, an account may remain in storage as a
                     // zero-lamport AccountSharedData::Default() after being wiped and reinitialized in later
                     // updates. We include the redundant filters here to avoid returning these
                     // accounts.
                     account.owner() == program_id && filter_closure(account)
-                })
-                .map_err(|e| RpcCustomError::ScanError {
-                    message: e.to_string(),
-                })?)
+                },
+                page,
+            )
         } else {
-            Ok(bank
-                .get_filtered_program_accounts(program_id, filter_closure)
-                .map_err(|e| RpcCustomError::ScanError {
-                    message: e.to_string(),
-                })?)
+            match page {
+                Some(page) => bank.get_filtered_program_accounts_paginated(
+                    program_id,
+                    filter_closure,
+                    page.start_after,
+                    page.limit,
+                ),
+                None => bank.get_filtered_program_accounts(program_id, filter_closure),
+            }
+            .map_err(|e| RpcCustomError::ScanError {
+                message: e.to_string(),
+            })
         }
     }
 
-    /// Get an iterator of spl-token accounts by owner address
+    /// Get an iterator of spl-token accounts by owner address, or only one `page` of them
     fn get_filtered_spl_token_accounts_by_owner(
         &self,
         bank: &Arc<Bank>,
         owner_key: &Pubkey,
         mut filters: Vec<RpcFilterType>,
+        page: Option<AccountsPage>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         // The by-owner accounts index checks for Token Account state and Owner address on
         // inclusion. However, due to the current AccountsDb implementation, an account may remain
@@ -1834,28 +2324,30 @@ impl JsonRpcRequestProcessor {
                     index_key: owner_key.to_string(),
                 });
             }
-            Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::SplTokenOwner(*owner_key), |account| {
+            get_filtered_indexed_accounts(
+                bank,
+                &IndexKey::SplTokenOwner(*owner_key),
+                |account| {
                     account.owner() == &spl_token_id_v2_0()
                         && filters.iter().all(|filter_type| match filter_type {
                             RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
                             RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
                         })
-                })
-                .map_err(|e| RpcCustomError::ScanError {
-                    message: e.to_string(),
-                })?)
+                },
+                page,
+            )
         } else {
-            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters)
+            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters, page)
         }
     }
 
-    /// Get an iterator of spl-token accounts by mint address
+    /// Get an iterator of spl-token accounts by mint address, or only one `page` of them
     fn get_filtered_spl_token_accounts_by_mint(
         &self,
         bank: &Arc<Bank>,
         mint_key: &Pubkey,
         mut filters: Vec<RpcFilterType>,
+        page: Option<AccountsPage>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         // The by-mint accounts index checks for Token Account state and Mint address on inclusion.
         // However, due to the current AccountsDb implementation, an account may remain in storage
@@ -1882,28 +2374,35 @@ impl JsonRpcRequestProcessor {
                     index_key: mint_key.to_string(),
                 });
             }
-            Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::SplTokenMint(*mint_key), |account| {
+            get_filtered_indexed_accounts(
+                bank,
+                &IndexKey::SplTokenMint(*mint_key),
+                |account| {
                     account.owner() == &spl_token_id_v2_0()
                         && filters.iter().all(|filter_type| match filter_type {
                             RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
                             RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
                         })
-                })
-                .map_err(|e| RpcCustomError::ScanError {
-                    message: e.to_string(),
-                })?)
+                },
+                page,
+            )
         } else {
-            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters)
+            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters, page)
         }
     }
 }
 
 fn verify_transaction(
-    transaction: &Transaction,
+    transaction: &SanitizedTransaction,
     libsecp256k1_0_5_upgrade_enabled: bool,
 ) -> Result<()> {
-    if transaction.verify().is_err() {
+    // Signatures are verified against the original message since the message of
+    // a sanitized transaction also lists the addresses loaded from lookup tables
+    if transaction
+        .to_versioned_transaction()
+        .verify_and_hash_message()
+        .is_err()
+    {
         return Err(RpcCustomError::TransactionSignatureVerificationFailure.into());
     }
 
@@ -1966,6 +2465,16 @@ fn check_is_at_least_confirmed(commitment: CommitmentConfig) -> Result<()> {
     Ok(())
 }
 
+fn check_at_slot_unsupported(at_slot: Option<Slot>) -> Result<()> {
+    if at_slot.is_some() {
+        Err(Error::invalid_params(
+            "atSlot is only supported by getAccountInfo, getMultipleAccounts and getBalance",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 fn check_slice_and_encoding(encoding: &UiAccountEncoding, data_slice_is_some: bool) -> Result<()> {
     match encoding {
         UiAccountEncoding::JsonParsed => {
@@ -1989,24 +2498,44 @@ fn check_slice_and_encoding(encoding: &UiAccountEncoding, data_slice_is_some: bo
     }
 }
 
-fn get_encoded_account(
+/// Account state as of a slot: from a bank on the requested fork while one is still held, and
+/// from the account history otherwise
+enum AccountsAtSlot {
+    Bank { slot: Slot, bank: Arc<Bank> },
+    History { slot: Slot, root_bank: Arc<Bank> },
+}
+
+impl AccountsAtSlot {
+    /// A bank to resolve parsed account data against, such as token mint decimals
+    fn bank(&self) -> &Arc<Bank> {
+        match self {
+            Self::Bank { bank, .. } => bank,
+            Self::History { root_bank, .. } => root_bank,
+        }
+    }
+
+    fn new_response<T>(&self, value: T) -> RpcResponse<T> {
+        let slot = match self {
+            Self::Bank { slot, .. } | Self::History { slot, .. } => *slot,
+        };
+        Response {
+            context: RpcResponseContext { slot },
+            value,
+        }
+    }
+}
+
+fn encode_account_for_bank(
     bank: &Arc<Bank>,
     pubkey: &Pubkey,
+    account: AccountSharedData,
     encoding: UiAccountEncoding,
     data_slice: Option<UiDataSliceConfig>,
-) -> Result<Option<UiAccount>> {
-    match bank.get_account(pubkey) {
-        Some(account) => {
-            let response = if account.owner() == &spl_token_id_v2_0()
-                && encoding == UiAccountEncoding::JsonParsed
-            {
-                get_parsed_token_account(bank.clone(), pubkey, account)
-            } else {
-                encode_account(&account, pubkey, encoding, data_slice)?
-            };
-            Ok(Some(response))
-        }
-        None => Ok(None),
+) -> Result<UiAccount> {
+    if account.owner() == &spl_token_id_v2_0() && encoding == UiAccountEncoding::JsonParsed {
+        Ok(get_parsed_token_account(bank.clone(), pubkey, account))
+    } else {
+        encode_account(&account, pubkey, encoding, data_slice)
     }
 }
 
@@ -2032,6 +2561,120 @@ fn encode_account<T: ReadableAccount>(
     }
 }
 
+fn encode_keyed_accounts(
+    bank: &Arc<Bank>,
+    program_id: &Pubkey,
+    keyed_accounts: Vec<(Pubkey, AccountSharedData)>,
+    encoding: UiAccountEncoding,
+    data_slice_config: Option<UiDataSliceConfig>,
+) -> Result<Vec<RpcKeyedAccount>> {
+    if program_id == &spl_token_id_v2_0() && encoding == UiAccountEncoding::JsonParsed {
+        Ok(get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect())
+    } else {
+        let mut encoded_accounts = vec![];
+        for (pubkey, account) in keyed_accounts {
+            encoded_accounts.push(RpcKeyedAccount {
+                pubkey: pubkey.to_string(),
+                account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
+            });
+        }
+        Ok(encoded_accounts)
+    }
+}
+
+/// Position of a paginated `getProgramAccountsPaginated` query: the bank slot every page is
+/// served from, and the last pubkey returned so far
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgramAccountsCursor {
+    pub slot: Slot,
+    pub last_pubkey: Pubkey,
+}
+
+impl ProgramAccountsCursor {
+    const ENCODED_LEN: usize = std::mem::size_of::<Slot>() + std::mem::size_of::<Pubkey>();
+
+    /// Encode as the opaque base-58 string handed to clients
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+        bytes.extend_from_slice(&self.slot.to_le_bytes());
+        bytes.extend_from_slice(self.last_pubkey.as_ref());
+        bs58::encode(bytes).into_string()
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid_cursor = || Error::invalid_params("Invalid cursor".to_string());
+        let bytes = bs58::decode(cursor)
+            .into_vec()
+            .map_err(|_| invalid_cursor())?;
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(invalid_cursor());
+        }
+        let (slot_bytes, pubkey_bytes) = bytes.split_at(std::mem::size_of::<Slot>());
+        Ok(Self {
+            slot: Slot::from_le_bytes(slot_bytes.try_into().unwrap()),
+            last_pubkey: Pubkey::new(pubkey_bytes),
+        })
+    }
+}
+
+/// A page of accounts in pubkey order: at most `limit` accounts following `start_after`
+#[derive(Debug, Clone, Copy)]
+struct AccountsPage<'a> {
+    start_after: Option<&'a Pubkey>,
+    limit: usize,
+}
+
+/// Get the keyed accounts under a secondary index key that pass `filter`, or only one `page` of
+/// them, which is range-scanned from its start
+fn get_filtered_indexed_accounts<F: Fn(&AccountSharedData) -> bool>(
+    bank: &Bank,
+    index_key: &IndexKey,
+    filter: F,
+    page: Option<AccountsPage>,
+) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
+    match page {
+        Some(page) => bank.get_filtered_indexed_accounts_paginated(
+            index_key,
+            filter,
+            page.start_after,
+            page.limit,
+        ),
+        None => bank.get_filtered_indexed_accounts(index_key, filter),
+    }
+    .map_err(|e| RpcCustomError::ScanError {
+        message: e.to_string(),
+    })
+}
+
+/// Find a user-defined index able to serve a program accounts query: the index must cover
+/// `program_id`, and a memcmp filter must match at least the full indexed field
+fn get_custom_index_filter(
+    program_id: &Pubkey,
+    filters: &[RpcFilterType],
+    account_indexes: &AccountSecondaryIndexes,
+) -> Option<(CustomAccountIndex, Pubkey)> {
+    if !account_indexes.include_key(program_id) {
+        return None;
+    }
+    account_indexes
+        .custom_indexes_for_program(program_id)
+        .find_map(|custom_index| {
+            filters.iter().find_map(|filter| match filter {
+                RpcFilterType::Memcmp(Memcmp {
+                    offset,
+                    bytes: MemcmpEncodedBytes::Binary(bytes),
+                    ..
+                }) if *offset == custom_index.offset => {
+                    let bytes = bs58::decode(bytes).into_vec().ok()?;
+                    custom_index
+                        .index_key(&bytes)
+                        .map(|index_key| (*custom_index, index_key))
+                }
+                _ => None,
+            })
+        })
+}
+
 fn get_spl_token_owner_filter(program_id: &Pubkey, filters: &[RpcFilterType]) -> Option<Pubkey> {
     if program_id != &spl_token_id_v2_0() {
         return None;
@@ -2116,12 +2759,62 @@ fn get_token_program_id_and_mint(
     }
 }
 
+/// Encode the inner instructions recorded while simulating `transaction`, omitting top-level
+/// instructions that made no cross-program invocations
+fn encode_simulation_inner_instructions(
+    transaction: &Transaction,
+    inner_instructions: InnerInstructionsList,
+    encoding: UiAccountEncoding,
+) -> Vec<UiInnerInstructions> {
+    inner_instructions
+        .into_iter()
+        .enumerate()
+        .filter(|(_, instructions)| !instructions.is_empty())
+        .map(|(index, instructions)| {
+            let inner_instructions = InnerInstructions {
+                index: index as u8,
+                instructions,
+            };
+            if encoding == UiAccountEncoding::JsonParsed {
+                UiInnerInstructions::parse(inner_instructions, &transaction.message)
+            } else {
+                UiInnerInstructions::from(inner_instructions)
+            }
+        })
+        .collect()
+}
+
+/// Pair the pre- and post-simulation state of each writable account of a successfully simulated
+/// transaction
+fn encode_simulation_account_diffs(
+    transaction: &Transaction,
+    pre_simulation_accounts: &[(Pubkey, AccountSharedData)],
+    post_simulation_accounts: &[(Pubkey, AccountSharedData)],
+    encoding: UiAccountEncoding,
+) -> Vec<RpcSimulateTransactionAccountDiff> {
+    pre_simulation_accounts
+        .iter()
+        .filter_map(|(pubkey, pre_account)| {
+            let (_, post_account) = post_simulation_accounts
+                .iter()
+                .take(transaction.message.account_keys.len())
+                .find(|(key, _)| key == pubkey)?;
+            Some(RpcSimulateTransactionAccountDiff {
+                pubkey: pubkey.to_string(),
+                pre: UiAccount::encode(pubkey, pre_account, encoding, None, None),
+                post: UiAccount::encode(pubkey, post_account, encoding, None, None),
+            })
+        })
+        .collect()
+}
+
 fn _send_transaction(
     meta: JsonRpcRequestProcessor,
-    transaction: Transaction,
+    transaction: &Transaction,
     wire_transaction: Vec<u8>,
     last_valid_slot: Slot,
     durable_nonce_info: Option<(Pubkey, Hash)>,
+    max_retries: Option<usize>,
 ) -> Result<String> {
     if transaction.signatures.is_empty() {
         return Err(RpcCustomError::TransactionSignatureVerificationFailure.into());
@@ -2132,6 +2825,7 @@ fn _send_transaction(
         wire_transaction,
         last_valid_slot,
         durable_nonce_info,
+        max_retries,
     );
     meta.transaction_sender
         .lock()
@@ -2154,7 +2848,7 @@ pub mod rpc_minimal {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            commitment: Option<CommitmentConfig>,
+            config: Option<RpcContextConfig>,
         ) -> Result<RpcResponse<u64>>;
 
         #[rpc(meta, name = "getEpochInfo")]
@@ -2225,11 +2919,11 @@ pub mod rpc_minimal {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            commitment: Option<CommitmentConfig>,
+            config: Option<RpcContextConfig>,
         ) -> Result<RpcResponse<u64>> {
             debug!("get_balance rpc request received: {:?}", pubkey_str);
             let pubkey = verify_pubkey(&pubkey_str)?;
-            Ok(meta.get_balance(&pubkey, commitment))
+            meta.get_balance(&pubkey, config)
         }
 
         fn get_epoch_info(
@@ -2676,6 +3370,14 @@ pub mod rpc_accounts {
             config: Option<RpcProgramAccountsConfig>,
         ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>>;
 
+        #[rpc(meta, name = "getProgramAccountsPaginated")]
+        fn get_program_accounts_paginated(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcProgramAccountsPaginatedConfig>,
+        ) -> Result<RpcResponse<RpcKeyedAccountsPage>>;
+
         #[rpc(meta, name = "getBlockCommitment")]
         fn get_block_commitment(
             &self,
@@ -2827,6 +3529,52 @@ pub mod rpc_accounts {
             meta.get_program_accounts(&program_id, config, filters, with_context)
         }
 
+        fn get_program_accounts_paginated(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcProgramAccountsPaginatedConfig>,
+        ) -> Result<RpcResponse<RpcKeyedAccountsPage>> {
+            debug!(
+                "get_program_accounts_paginated rpc request received: {:?}",
+                program_id_str
+            );
+            let program_id = verify_pubkey(&program_id_str)?;
+            let RpcProgramAccountsPaginatedConfig {
+                filters,
+                account_config,
+                limit,
+                cursor,
+            } = config.unwrap_or_default();
+            let filters = filters.unwrap_or_default();
+            if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
+                return Err(Error::invalid_params(format!(
+                    "Too many filters provided; max {}",
+                    MAX_GET_PROGRAM_ACCOUNT_FILTERS
+                )));
+            }
+            for filter in &filters {
+                verify_filter(filter)?;
+            }
+            let limit = limit.unwrap_or(MAX_GET_PROGRAM_ACCOUNTS_PAGE_SIZE);
+            if limit == 0 || limit > MAX_GET_PROGRAM_ACCOUNTS_PAGE_SIZE {
+                return Err(Error::invalid_params(format!(
+                    "Invalid limit; max {}",
+                    MAX_GET_PROGRAM_ACCOUNTS_PAGE_SIZE
+                )));
+            }
+            let cursor = cursor
+                .map(|cursor| ProgramAccountsCursor::decode(&cursor))
+                .transpose()?;
+            meta.get_program_accounts_paginated(
+                &program_id,
+                Some(account_config),
+                filters,
+                limit,
+                cursor,
+            )
+        }
+
         fn get_block_commitment(
             &self,
             meta: Self::Metadata,
@@ -3001,6 +3749,13 @@ pub mod rpc_full {
             config: Option<RpcSendTransactionConfig>,
         ) -> Result<String>;
 
+        #[rpc(meta, name = "getTransactionRetryStatus")]
+        fn get_transaction_retry_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcTransactionRetryStatus>>;
+
         #[rpc(meta, name = "simulateTransaction")]
         fn simulate_transaction(
             &self,
@@ -3058,6 +3813,14 @@ pub mod rpc_full {
             config: Option<RpcSignaturesForAddressConfig>,
         ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
 
+        #[rpc(meta, name = "getAccountModifications")]
+        fn get_account_modifications(
+            &self,
+            meta: Self::Metadata,
+            address: String,
+            config: Option<RpcSignaturesForAddressConfig>,
+        ) -> Result<RpcAccountModifications>;
+
         #[rpc(meta, name = "getFirstAvailableBlock")]
         fn get_first_available_block(&self, meta: Self::Metadata) -> Result<Slot>;
     }
@@ -3220,7 +3983,14 @@ pub mod rpc_full {
                 Error::internal_error()
             })?;
 
-            _send_transaction(meta, transaction, wire_transaction, last_valid_slot, None)
+            _send_transaction(
+                meta,
+                &transaction,
+                wire_transaction,
+                last_valid_slot,
+                None,
+                None,
+            )
         }
 
         fn send_transaction(
@@ -3232,12 +4002,13 @@ pub mod rpc_full {
             debug!("send_transaction rpc request received");
             let config = config.unwrap_or_default();
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
-            let (wire_transaction, transaction) = deserialize_transaction(data, encoding)?;
+            let (wire_transaction, unsanitized_tx) = deserialize_transaction(data, encoding)?;
 
             let preflight_commitment = config
                 .preflight_commitment
                 .map(|commitment| CommitmentConfig { commitment });
             let preflight_bank = &*meta.bank(preflight_commitment);
+            let transaction = sanitize_transaction(unsanitized_tx, preflight_bank)?;
 
             let mut last_valid_slot = preflight_bank
                 .get_blockhash_last_valid_slot(&transaction.message.recent_blockhash)
@@ -3288,9 +4059,9 @@ pub mod rpc_full {
                 if let TransactionSimulationResult {
                     result: Err(err),
                     logs,
-                    post_simulation_accounts: _,
                     units_consumed,
-                } = preflight_bank.simulate_transaction(&transaction)
+                    ..
+                } = preflight_bank.simulate_transaction(&transaction, false)
                 {
                     match err {
                         TransactionError::BlockhashNotFound => {
@@ -3307,6 +4078,11 @@ pub mod rpc_full {
                             logs: Some(logs),
                             accounts: None,
                             units_consumed: Some(units_consumed),
+                            units_consumed_per_instruction: None,
+                            account_diffs: None,
+                            inner_instructions: None,
+                            pre_token_balances: None,
+                            post_token_balances: None,
                         },
                     }
                     .into());
@@ -3315,13 +4091,27 @@ pub mod rpc_full {
 
             _send_transaction(
                 meta,
-                transaction,
+                &transaction,
                 wire_transaction,
                 last_valid_slot,
                 durable_nonce_info,
+                config.max_retries,
             )
         }
 
+        fn get_transaction_retry_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcTransactionRetryStatus>> {
+            debug!(
+                "get_transaction_retry_status rpc request received: {:?}",
+                signature_str
+            );
+            let signature = verify_signature(&signature_str)?;
+            Ok(meta.get_transaction_retry_status(&signature))
+        }
+
         fn simulate_transaction(
             &self,
             meta: Self::Metadata,
@@ -3331,31 +4121,37 @@ pub mod rpc_full {
             debug!("simulate_transaction rpc request received");
             let config = config.unwrap_or_default();
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
-            let (_, mut transaction) = deserialize_transaction(data, encoding)?;
+            let (_, mut unsanitized_tx) = deserialize_transaction(data, encoding)?;
 
             let bank = &*meta.bank(config.commitment);
-            if config.sig_verify {
-                if config.replace_recent_blockhash {
+            if config.replace_recent_blockhash {
+                if config.sig_verify {
                     return Err(Error::invalid_params(
                         "sigVerify may not be used with replaceRecentBlockhash",
                     ));
                 }
-
+                unsanitized_tx
+                    .message
+                    .set_recent_blockhash(bank.last_blockhash());
+            }
+            let transaction = sanitize_transaction(unsanitized_tx, bank)?;
+            if config.sig_verify {
                 if let Err(e) =
                     verify_transaction(&transaction, bank.libsecp256k1_0_5_upgrade_enabled())
                 {
                     return Err(e);
                 }
             }
-            if config.replace_recent_blockhash {
-                transaction.message.recent_blockhash = bank.last_blockhash();
-            }
             let TransactionSimulationResult {
                 result,
                 logs,
+                pre_simulation_accounts,
                 post_simulation_accounts,
+                inner_instructions,
                 units_consumed,
-            } = bank.simulate_transaction(&transaction);
+                units_consumed_per_instruction,
+                fee,
+            } = bank.simulate_transaction(&transaction, config.state_diff.is_some());
 
             let accounts = if let Some(config_accounts) = config.accounts {
                 let accounts_encoding = config_accounts
@@ -3404,6 +4200,101 @@ pub mod rpc_full {
                 None
             };
 
+            let (
+                units_consumed_per_instruction,
+                account_diffs,
+                inner_instructions,
+                pre_token_balances,
+                post_token_balances,
+            ) = if let Some(config_state_diff) = config.state_diff {
+                let accounts_encoding = config_state_diff
+                    .encoding
+                    .unwrap_or(UiAccountEncoding::Base64);
+
+                if accounts_encoding == UiAccountEncoding::Binary
+                    || accounts_encoding == UiAccountEncoding::Base58
+                {
+                    return Err(Error::invalid_params("base58 encoding not supported"));
+                }
+
+                let inner_instructions = inner_instructions.map(|inner_instructions| {
+                    encode_simulation_inner_instructions(
+                        &transaction,
+                        inner_instructions,
+                        accounts_encoding,
+                    )
+                });
+
+                match &result {
+                    // Nothing but the fee is charged
+                    Err(TransactionError::InstructionError(_, _)) => {
+                        let mut post_simulation_accounts = pre_simulation_accounts.clone();
+                        if let Some((_, fee_payer)) = post_simulation_accounts.first_mut() {
+                            fee_payer.set_lamports(fee_payer.lamports().saturating_sub(fee));
+                        }
+                        let account_diffs = encode_simulation_account_diffs(
+                            &transaction,
+                            &pre_simulation_accounts,
+                            &post_simulation_accounts,
+                            accounts_encoding,
+                        );
+                        (
+                            Some(units_consumed_per_instruction),
+                            Some(account_diffs),
+                            inner_instructions,
+                            None,
+                            None,
+                        )
+                    }
+                    Err(_) => (
+                        Some(units_consumed_per_instruction),
+                        None,
+                        inner_instructions,
+                        None,
+                        None,
+                    ),
+                    Ok(()) => {
+                        let account_diffs = encode_simulation_account_diffs(
+                            &transaction,
+                            &pre_simulation_accounts,
+                            &post_simulation_accounts,
+                            accounts_encoding,
+                        );
+
+                        let mut mint_decimals = HashMap::new();
+                        let pre_accounts: Vec<_> = transaction
+                            .message
+                            .account_keys
+                            .iter()
+                            .map(|key| (*key, bank.get_account(key).unwrap_or_default()))
+                            .collect();
+                        let mut token_balances = |accounts: &[(Pubkey, AccountSharedData)]| {
+                            collect_token_balances_from_accounts(
+                                bank,
+                                &transaction,
+                                accounts,
+                                &mut mint_decimals,
+                            )
+                            .into_iter()
+                            .map(UiTransactionTokenBalance::from)
+                            .collect::<Vec<_>>()
+                        };
+                        let pre_token_balances = token_balances(&pre_accounts);
+                        let post_token_balances = token_balances(&post_simulation_accounts);
+
+                        (
+                            Some(units_consumed_per_instruction),
+                            Some(account_diffs),
+                            inner_instructions,
+                            Some(pre_token_balances),
+                            Some(post_token_balances),
+                        )
+                    }
+                }
+            } else {
+                (None, None, None, None, None)
+            };
+
             Ok(new_response(
                 bank,
                 RpcSimulateTransactionResult {
@@ -3411,6 +4302,11 @@ pub mod rpc_full {
                     logs: Some(logs),
                     accounts,
                     units_consumed: Some(units_consumed),
+                    units_consumed_per_instruction,
+                    account_diffs,
+                    inner_instructions,
+                    pre_token_balances,
+                    post_token_balances,
                 },
             ))
         }
@@ -3510,6 +4406,41 @@ pub mod rpc_full {
             meta.get_signatures_for_address(address, before, until, limit, config.commitment)
         }
 
+        fn get_account_modifications(
+            &self,
+            meta: Self::Metadata,
+            address: String,
+            config: Option<RpcSignaturesForAddressConfig>,
+        ) -> Result<RpcAccountModifications> {
+            debug!(
+                "get_account_modifications rpc request received: {:?}",
+                address
+            );
+            let address = verify_pubkey(&address)?;
+
+            let config = config.unwrap_or_default();
+            let before = config
+                .before
+                .map(|ref before| verify_signature(before))
+                .transpose()?;
+            let until = config
+                .until
+                .map(|ref until| verify_signature(until))
+                .transpose()?;
+            let limit = config
+                .limit
+                .unwrap_or(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT);
+
+            if limit == 0 || limit > MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT {
+                return Err(Error::invalid_params(format!(
+                    "Invalid limit; max {}",
+                    MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT
+                )));
+            }
+
+            meta.get_account_modifications(address, before, until, limit, config.commitment)
+        }
+
         fn get_first_available_block(&self, meta: Self::Metadata) -> Result<Slot> {
             debug!("get_first_available_block rpc request received");
             Ok(meta.get_first_available_block())
@@ -3826,7 +4757,7 @@ const WORST_CASE_BASE64_TX: usize = 1644; // Golden, bump if PACKET_DATA_SIZE ch
 fn deserialize_transaction(
     encoded_transaction: String,
     encoding: UiTransactionEncoding,
-) -> Result<(Vec<u8>, Transaction)> {
+) -> Result<(Vec<u8>, VersionedTransaction)> {
     let wire_transaction = match encoding {
         UiTransactionEncoding::Base58 => {
             inc_new_counter_info!("rpc-base58_encoded_tx", 1);
@@ -3880,7 +4811,7 @@ fn deserialize_transaction(
             info!("transaction deserialize error: {:?}", err);
             Error::invalid_params(&err.to_string())
         })
-        .and_then(|transaction: Transaction| {
+        .and_then(|transaction: VersionedTransaction| {
             if let Err(err) = transaction.sanitize() {
                 Err(Error::invalid_params(format!(
                     "invalid transaction: {}",
@@ -3893,6 +4824,15 @@ fn deserialize_transaction(
         .map(|transaction| (wire_transaction, transaction))
 }
 
+fn sanitize_transaction(
+    transaction: VersionedTransaction,
+    bank: &Bank,
+) -> Result<SanitizedTransaction<'static>> {
+    let message_hash = transaction.message.hash();
+    bank.sanitize_transaction(transaction, message_hash)
+        .map_err(|err| Error::invalid_params(format!("invalid transaction: {}", err)))
+}
+
 pub(crate) fn create_validator_exit(exit: &Arc<AtomicBool>) -> Arc<RwLock<Exit>> {
     let mut validator_exit = Exit::default();
     let exit_ = exit.clone();
@@ -3952,6 +4892,8 @@ pub fn create_test_transactions_and_populate_blockstore(
         crate::transaction_status_service::TransactionStatusService::new(
             transaction_status_receiver,
             max_complete_transaction_status_slot,
+            true,
+            None,
             blockstore,
             &Arc::new(AtomicBool::new(false)),
         );
@@ -3989,7 +4931,10 @@ pub mod tests {
         bincode::deserialize,
         jsonrpc_core::{futures, ErrorCode, MetaIoHandler, Output, Response, Value},
         jsonrpc_core_client::transports::local,
-        solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        solana_client::{
+            rpc_custom_error::JSON_RPC_SERVER_ERROR_PROGRAM_ACCOUNTS_CURSOR_EXPIRED,
+            rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        },
         solana_gossip::{contact_info::ContactInfo, socketaddr},
         solana_ledger::{
             blockstore_meta::PerfSample,
@@ -4202,6 +5147,7 @@ pub mod tests {
             max_slots,
             Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
             max_complete_transaction_status_slot,
+            Arc::new(AtomicU64::default()),
         );
         SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
 
@@ -4298,6 +5244,94 @@ pub mod tests {
         assert_eq!(response, 20);
     }
 
+    #[test]
+    fn test_get_accounts_at_slot() {
+        let genesis = create_genesis_config(20);
+        let mint_keypair = genesis.mint_keypair;
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let untouched_pubkey = solana_sdk::pubkey::new_rand();
+        let bank0 = Arc::new(Bank::new(&genesis.genesis_config));
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank1.transfer(5, &mint_keypair, &pubkey).unwrap();
+        let bank2 = Arc::new(Bank::new_from_parent(&bank1, &Pubkey::default(), 2));
+        bank2.transfer(3, &mint_keypair, &pubkey).unwrap();
+        bank2.squash();
+
+        let mut meta = JsonRpcRequestProcessor::new_from_bank(&bank2, SocketAddrSpace::Unspecified);
+        meta.account_history_start_slot = 0;
+        meta.blockstore.set_roots(vec![1, 2].iter()).unwrap();
+        let account = |lamports| Account {
+            lamports,
+            owner: system_program::id(),
+            ..Account::default()
+        };
+        meta.blockstore
+            .write_account_history(&[(1, pubkey, 1, account(5)), (2, pubkey, 2, account(8))])
+            .unwrap();
+        let at_slot = |slot| {
+            Some(RpcContextConfig {
+                at_slot: Some(slot),
+                ..RpcContextConfig::default()
+            })
+        };
+
+        // Account history is opt-in
+        assert!(meta.get_balance(&pubkey, at_slot(1)).is_err());
+        meta.config.enable_rpc_account_history = true;
+        // Nor is it served past the last rooted slot that has been completely written
+        assert!(meta.get_balance(&pubkey, at_slot(1)).is_err());
+        meta.max_complete_account_history_slot
+            .store(1, Ordering::SeqCst);
+        assert_eq!(meta.get_balance(&pubkey, at_slot(1)).unwrap().value, 5);
+        // Whether an account never stored since the history began existed takes a history
+        // complete up to the root
+        assert!(meta.get_balance(&untouched_pubkey, at_slot(1)).is_err());
+        meta.max_complete_account_history_slot
+            .store(2, Ordering::SeqCst);
+
+        let response = meta.get_balance(&pubkey, at_slot(1)).unwrap();
+        assert_eq!(response.context.slot, 1);
+        assert_eq!(response.value, 5);
+        let response = meta
+            .get_account_info(
+                &pubkey,
+                Some(RpcAccountInfoConfig {
+                    at_slot: Some(1),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .unwrap();
+        assert_eq!(response.context.slot, 1);
+        assert_eq!(response.value.unwrap().lamports, 5);
+        // Slot 2 is still served from BankForks
+        assert_eq!(meta.get_balance(&pubkey, at_slot(2)).unwrap().value, 8);
+        // An account never stored since the history began did not exist
+        assert_eq!(
+            meta.get_balance(&untouched_pubkey, at_slot(1))
+                .unwrap()
+                .value,
+            0
+        );
+        // The state of an account at the start of the history is unknown
+        assert!(meta.get_balance(&pubkey, at_slot(0)).is_err());
+        // Slots later than the context slot are invalid
+        assert!(meta.get_balance(&pubkey, at_slot(3)).is_err());
+
+        let min_context_slot = |slot| {
+            Some(RpcContextConfig {
+                min_context_slot: Some(slot),
+                ..RpcContextConfig::default()
+            })
+        };
+        assert_eq!(
+            meta.get_balance(&pubkey, min_context_slot(2))
+                .unwrap()
+                .value,
+            8
+        );
+        assert!(meta.get_balance(&pubkey, min_context_slot(3)).is_err());
+    }
+
     #[test]
     fn test_rpc_get_cluster_nodes() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -5189,6 +6223,133 @@ pub mod tests {
         assert_eq!(accounts.len(), 0);
     }
 
+    #[test]
+    fn test_rpc_get_program_accounts_paginated() {
+        let bob = Keypair::new();
+        let RpcHandler { io, meta, bank, .. } = start_rpc_handler_with_tx(&bob.pubkey());
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getProgramAccounts","params":["{}"]}}"#,
+            system_program::id(),
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let accounts: Vec<RpcKeyedAccount> = serde_json::from_value(json["result"].clone())
+            .expect("actual response deserialization");
+        let mut expected_pubkeys: Vec<_> = accounts.into_iter().map(|a| a.pubkey).collect();
+        expected_pubkeys.sort_by_key(|pubkey| Pubkey::from_str(pubkey).unwrap());
+        assert!(expected_pubkeys.len() > 1);
+
+        // Page through the accounts one at a time
+        let mut paged_pubkeys = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let cursor_param = cursor
+                .as_ref()
+                .map(|cursor| format!(r#","cursor":"{}""#, cursor))
+                .unwrap_or_default();
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getProgramAccountsPaginated","params":["{}",{{"limit":1{}}}]}}"#,
+                system_program::id(),
+                cursor_param,
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+            let page: RpcResponse<RpcKeyedAccountsPage> =
+                serde_json::from_value(json["result"].clone())
+                    .expect("actual response deserialization");
+            assert_eq!(page.context.slot, bank.slot());
+            assert!(page.value.accounts.len() <= 1);
+            paged_pubkeys.extend(page.value.accounts.into_iter().map(|a| a.pubkey));
+            cursor = page.value.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(paged_pubkeys, expected_pubkeys);
+
+        // Cursors are opaque, malformed ones are rejected
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getProgramAccountsPaginated","params":["{}",{{"cursor":"{}"}}]}}"#,
+            system_program::id(),
+            bs58::encode(vec![1, 2, 3]).into_string(),
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+
+        // Cursors pinned to a slot no longer in BankForks have expired
+        let cursor = ProgramAccountsCursor {
+            slot: bank.slot() + 100,
+            last_pubkey: Pubkey::default(),
+        };
+        assert_eq!(
+            ProgramAccountsCursor::decode(&cursor.encode()).unwrap(),
+            cursor
+        );
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getProgramAccountsPaginated","params":["{}",{{"cursor":"{}"}}]}}"#,
+            system_program::id(),
+            cursor.encode(),
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(
+            json["error"]["code"],
+            JSON_RPC_SERVER_ERROR_PROGRAM_ACCOUNTS_CURSOR_EXPIRED
+        );
+    }
+
+    #[test]
+    fn test_get_program_accounts_paginated_expired_cursor() {
+        let genesis = create_genesis_config(100);
+        let bank0 = Arc::new(Bank::new(&genesis.genesis_config));
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let program_id = solana_sdk::pubkey::new_rand();
+        let mut pubkeys: Vec<_> = (0..3).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        for pubkey in &pubkeys {
+            bank1.store_account(pubkey, &AccountSharedData::new(1, 0, &program_id));
+        }
+        pubkeys.sort();
+        let bank2 = Arc::new(Bank::new_from_parent(&bank1, &Pubkey::default(), 2));
+        bank2.squash();
+        // BankForks only holds the root bank, at slot 2
+        let meta = JsonRpcRequestProcessor::new_from_bank(&bank2, SocketAddrSpace::Unspecified);
+        let page_after = |slot| {
+            meta.get_program_accounts_paginated(
+                &program_id,
+                None,
+                vec![],
+                1,
+                Some(ProgramAccountsCursor {
+                    slot,
+                    last_pubkey: pubkeys[0],
+                }),
+            )
+        };
+
+        // The bank for the cursor slot is still held, so the query continues from it
+        let page = page_after(2).unwrap();
+        assert_eq!(page.context.slot, 2);
+        assert_eq!(page.value.accounts.len(), 1);
+        assert_eq!(page.value.accounts[0].pubkey, pubkeys[1].to_string());
+        let next_cursor =
+            ProgramAccountsCursor::decode(page.value.next_cursor.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            next_cursor,
+            ProgramAccountsCursor {
+                slot: 2,
+                last_pubkey: pubkeys[1],
+            }
+        );
+
+        // Slot 1 is rooted but gone from BankForks, and the root bank holds a different state,
+        // so its cursors expire rather than continue from the root
+        meta.blockstore.set_roots(vec![1, 2].iter()).unwrap();
+        let expected_error: Error = RpcCustomError::ProgramAccountsCursorExpired { slot: 1 }.into();
+        assert_eq!(page_after(1).unwrap_err(), expected_error);
+    }
+
     #[test]
     fn test_rpc_simulate_transaction() {
         let RpcHandler {
@@ -5457,6 +6618,97 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_simulate_transaction_state_diff() {
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair: alice,
+            ..
+        } = create_genesis_config(TEST_MINT_LAMPORTS);
+        let lamports_per_signature = 5_000;
+        genesis_config.fee_rate_governor =
+            solana_sdk::fee_calculator::FeeRateGovernor::new(lamports_per_signature, 0);
+        let bank = Arc::new(Bank::new(&genesis_config));
+        bank.freeze();
+        let meta = JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+        let mut io = MetaIoHandler::default();
+        io.extend_with(rpc_full::FullImpl.to_delegate());
+
+        let simulate = |tx: &Transaction| {
+            let tx_serialized_encoded = bs58::encode(serialize(tx).unwrap()).into_string();
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{}", {{"stateDiff": {{"encoding": "base64"}}}}]}}"#,
+                tx_serialized_encoded,
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+            let result: RpcResponse<RpcSimulateTransactionResult> =
+                serde_json::from_value(json["result"].clone())
+                    .expect("actual response deserialization");
+            result.value
+        };
+        let blockhash = bank.last_blockhash();
+        let alice_balance = bank.get_balance(&alice.pubkey());
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+
+        let result = simulate(&system_transaction::transfer(
+            &alice,
+            &bob_pubkey,
+            1234,
+            blockhash,
+        ));
+        assert_eq!(result.err, None);
+        assert_eq!(result.units_consumed_per_instruction, Some(vec![0]));
+        assert_eq!(result.inner_instructions, Some(vec![]));
+        assert_eq!(result.pre_token_balances, Some(vec![]));
+        assert_eq!(result.post_token_balances, Some(vec![]));
+
+        // Only the writable accounts are diffed, the system program is not. Alice pays the fee.
+        let account_diffs = result.account_diffs.unwrap();
+        assert_eq!(account_diffs.len(), 2);
+        let alice_diff = &account_diffs[0];
+        assert_eq!(alice_diff.pubkey, alice.pubkey().to_string());
+        assert_eq!(alice_diff.pre.lamports, alice_balance);
+        assert_eq!(
+            alice_diff.post.lamports,
+            alice_balance - 1234 - lamports_per_signature
+        );
+        let bob_diff = &account_diffs[1];
+        assert_eq!(bob_diff.pubkey, bob_pubkey.to_string());
+        assert_eq!(bob_diff.pre.lamports, 0);
+        assert_eq!(bob_diff.post.lamports, 1234);
+
+        // A transaction whose instruction fails is still charged the fee, and nothing else
+        let result = simulate(&system_transaction::transfer(
+            &alice,
+            &bob_pubkey,
+            alice_balance,
+            blockhash,
+        ));
+        assert_eq!(
+            result.err,
+            Some(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(1)
+            ))
+        );
+        assert_eq!(result.pre_token_balances, None);
+        assert_eq!(result.post_token_balances, None);
+        let account_diffs = result.account_diffs.unwrap();
+        assert_eq!(account_diffs.len(), 2);
+        assert_eq!(account_diffs[0].pre.lamports, alice_balance);
+        assert_eq!(
+            account_diffs[0].post.lamports,
+            alice_balance - lamports_per_signature
+        );
+        assert_eq!(account_diffs[1].pre.lamports, 0);
+        assert_eq!(account_diffs[1].post.lamports, 0);
+
+        // Nothing changes in the bank
+        assert_eq!(bank.get_balance(&alice.pubkey()), alice_balance);
+        assert_eq!(bank.get_balance(&bob_pubkey), 0);
+    }
+
     #[test]
     #[should_panic]
     fn test_rpc_simulate_transaction_panic_on_unfrozen_bank() {
@@ -5769,6 +7021,7 @@ pub mod tests {
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
         );
         SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
 
@@ -6052,6 +7305,7 @@ pub mod tests {
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
         );
         SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
         assert_eq!(
@@ -6293,6 +7547,71 @@ pub mod tests {
         assert_eq!(confirmed_block.rewards.unwrap(), vec![]);
     }
 
+    #[test]
+    fn test_get_account_modifications() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            mut meta,
+            alice,
+            confirmed_block_signatures,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        // Alice paid for the successful transfer in the rooted block
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountModifications","params":["{}"]}}"#,
+            alice.pubkey()
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let result: RpcAccountModifications =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(result.next_before, None);
+        assert_eq!(result.modifications.len(), 1);
+        let modification = &result.modifications[0];
+        assert_eq!(
+            modification.signature,
+            confirmed_block_signatures[0].to_string()
+        );
+        assert_eq!(modification.slot, 0);
+        assert_eq!(modification.err, None);
+        assert!(modification.pre_lamports > modification.post_lamports);
+        assert_eq!(
+            modification.confirmation_status,
+            Some(TransactionConfirmationStatus::Finalized)
+        );
+
+        // An address no transaction locked has no modifications
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountModifications","params":["{}"]}}"#,
+            solana_sdk::pubkey::new_rand()
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let result: RpcAccountModifications =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(
+            result,
+            RpcAccountModifications {
+                modifications: vec![],
+                next_before: None,
+            }
+        );
+
+        // disable rpc-tx-history
+        meta.config.enable_rpc_transaction_history = false;
+        let res = io.handle_request_sync(&req, meta);
+        assert_eq!(
+            res,
+            Some(
+                r#"{"jsonrpc":"2.0","error":{"code":-32011,"message":"Transaction history is not available from this node"},"id":1}"#.to_string(),
+            )
+        );
+    }
+
     #[test]
     fn test_get_block_production() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -7424,6 +8743,64 @@ pub mod tests {
         .is_none());
     }
 
+    #[test]
+    fn test_get_custom_index_filter() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex {
+            program_id,
+            offset: 8,
+            length: 4,
+        };
+        let account_indexes = AccountSecondaryIndexes {
+            indexes: [AccountIndex::Custom(custom_index)]
+                .iter()
+                .cloned()
+                .collect(),
+            keys: None,
+        };
+        let memcmp = |offset, bytes: Vec<u8>| {
+            RpcFilterType::Memcmp(Memcmp {
+                offset,
+                bytes: MemcmpEncodedBytes::Binary(bs58::encode(bytes).into_string()),
+                encoding: None,
+            })
+        };
+
+        // The filter may match more bytes than are indexed
+        assert_eq!(
+            get_custom_index_filter(
+                &program_id,
+                &[RpcFilterType::DataSize(64), memcmp(8, vec![1, 2, 3, 4, 5])],
+                &account_indexes,
+            ),
+            Some((custom_index, custom_index.index_key(&[1, 2, 3, 4]).unwrap()))
+        );
+
+        // Too few bytes to determine the index key
+        assert!(get_custom_index_filter(
+            &program_id,
+            &[memcmp(8, vec![1, 2, 3])],
+            &account_indexes
+        )
+        .is_none());
+
+        // Wrong offset
+        assert!(get_custom_index_filter(
+            &program_id,
+            &[memcmp(7, vec![1, 2, 3, 4])],
+            &account_indexes
+        )
+        .is_none());
+
+        // Wrong program id
+        assert!(get_custom_index_filter(
+            &Pubkey::new_unique(),
+            &[memcmp(8, vec![1, 2, 3, 4])],
+            &account_indexes
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_rpc_single_gossip() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -7478,6 +8855,7 @@ pub mod tests {
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
         );
 
         let mut io = MetaIoHandler::default();
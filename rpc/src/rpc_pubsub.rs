@@ -5,19 +5,20 @@ use solana_runtime::bank_forks::BankForks;
 #[cfg(test)]
 use std::sync::RwLock;
 use {
-    crate::rpc_subscriptions::{RpcSubscriptions, RpcVote},
+    crate::rpc_subscriptions::RpcSubscriptions,
     jsonrpc_core::{Error, ErrorCode, Result},
     jsonrpc_derive::rpc,
     jsonrpc_pubsub::{typed::Subscriber, Session, SubscriptionId},
     solana_account_decoder::UiAccount,
     solana_client::{
         rpc_config::{
-            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
-            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
+            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
+            RpcTransactionLogsFilter,
         },
         rpc_response::{
-            Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult,
-            SlotInfo, SlotUpdate,
+            Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
+            RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
         },
     },
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
@@ -189,6 +190,24 @@ pub trait RpcSolPubSub {
         name = "rootUnsubscribe"
     )]
     fn root_unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+
+    // Get notification when a block is confirmed or finalized
+    #[pubsub(subscription = "blockNotification", subscribe, name = "blockSubscribe")]
+    fn block_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcBlockUpdate>>,
+        filter: RpcBlockSubscribeFilter,
+        config: Option<RpcBlockSubscribeConfig>,
+    );
+
+    // Unsubscribe from block notification subscription.
+    #[pubsub(
+        subscription = "blockNotification",
+        unsubscribe,
+        name = "blockUnsubscribe"
+    )]
+    fn block_unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
 }
 
 pub struct RpcSolPubSubImpl {
@@ -551,6 +570,70 @@ impl RpcSolPubSub for RpcSolPubSubImpl {
             })
         }
     }
+
+    fn block_subscribe(
+        &self,
+        _meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcBlockUpdate>>,
+        filter: RpcBlockSubscribeFilter,
+        config: Option<RpcBlockSubscribeConfig>,
+    ) {
+        info!("block_subscribe");
+        if let Err(err) = self.check_subscription_count() {
+            subscriber.reject(err).unwrap_or_default();
+            return;
+        }
+
+        // Processed blocks may still be missing transaction statuses, and may never be
+        // confirmed
+        let commitment = config
+            .as_ref()
+            .and_then(|config| config.commitment)
+            .unwrap_or_default();
+        if !commitment.is_at_least_confirmed() {
+            subscriber
+                .reject(Error {
+                    code: ErrorCode::InvalidParams,
+                    message: "Invalid Request: Only confirmed or finalized commitment supported"
+                        .into(),
+                    data: None,
+                })
+                .unwrap_or_default();
+            return;
+        }
+
+        let address = match filter {
+            RpcBlockSubscribeFilter::All => None,
+            RpcBlockSubscribeFilter::MentionsAccountOrProgram(address) => {
+                match param::<Pubkey>(&address, "mentions") {
+                    Ok(address) => Some(address),
+                    Err(e) => {
+                        subscriber.reject(e).unwrap_or_default();
+                        return;
+                    }
+                }
+            }
+        };
+
+        let id = self.uid.fetch_add(1, atomic::Ordering::Relaxed);
+        let sub_id = SubscriptionId::Number(id as u64);
+        info!("block_subscribe: address={:?} id={:?}", address, sub_id);
+        self.subscriptions
+            .add_block_subscription(address, config, sub_id, subscriber);
+    }
+
+    fn block_unsubscribe(&self, _meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+        info!("block_unsubscribe");
+        if self.subscriptions.remove_block_subscription(&id) {
+            Ok(true)
+        } else {
+            Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "Invalid Request: Subscription id does not exist".into(),
+                data: None,
+            })
+        }
+    }
 }
 
 #[cfg(test)]
@@ -836,6 +919,8 @@ mod tests {
                 commitment: Some(CommitmentConfig::processed()),
                 encoding: None,
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
         );
 
@@ -947,6 +1032,8 @@ mod tests {
                 commitment: Some(CommitmentConfig::processed()),
                 encoding: Some(UiAccountEncoding::JsonParsed),
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
         );
 
@@ -1069,6 +1156,8 @@ mod tests {
                 commitment: Some(CommitmentConfig::finalized()),
                 encoding: None,
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
         );
 
@@ -1123,6 +1212,8 @@ mod tests {
                 commitment: Some(CommitmentConfig::finalized()),
                 encoding: None,
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
         );
 
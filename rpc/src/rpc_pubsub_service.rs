@@ -21,6 +21,7 @@ use {
 #[derive(Debug, Clone)]
 pub struct PubSubConfig {
     pub enable_vote_subscription: bool,
+    pub enable_block_subscription: bool,
 
     // See the corresponding fields in
     // https://github.com/paritytech/ws-rs/blob/be4d47575bae55c60d9f51b47480d355492a94fc/src/lib.rs#L131
@@ -36,6 +37,7 @@ impl Default for PubSubConfig {
     fn default() -> Self {
         Self {
             enable_vote_subscription: false,
+            enable_block_subscription: false,
             max_connections: 1000, // Arbitrary, default of 100 is too low
             max_fragment_size: 50 * 1024, // 50KB
             max_in_buffer_capacity: 50 * 1024, // 50KB
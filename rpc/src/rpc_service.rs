@@ -9,7 +9,7 @@ use {
             rpc_obsolete_v1_7::*, *,
         },
         rpc_health::*,
-        send_transaction_service::{LeaderInfo, SendTransactionService},
+        send_transaction_service::{self, LeaderInfo, SendTransactionService},
     },
     jsonrpc_core::{futures::prelude::*, MetaIoHandler},
     jsonrpc_http_server::{
@@ -74,7 +74,7 @@ impl RpcRequestMiddleware {
         Self {
             ledger_path,
             snapshot_archive_path_regex: Regex::new(
-                r"^/snapshot-\d+-[[:alnum:]]+\.(tar|tar\.bz2|tar\.zst|tar\.gz)$",
+                r"^/(snapshot|incremental-snapshot-\d+)-\d+-[[:alnum:]]+\.(tar|tar\.bz2|tar\.zst|tar\.gz)$",
             )
             .unwrap(),
             snapshot_config,
@@ -223,6 +223,34 @@ impl RequestMiddleware for RpcRequestMiddleware {
                 }
                 .into();
             }
+
+            if request.uri().path() == "/incremental-snapshot.tar.bz2" {
+                // Convenience redirect to the latest incremental snapshot for the latest full
+                // snapshot
+                return if let Some(incremental_snapshot_archive_info) =
+                    snapshot_utils::get_highest_full_snapshot_archive_slot(
+                        &snapshot_config.snapshot_package_output_path,
+                    )
+                    .and_then(|full_snapshot_slot| {
+                        snapshot_utils::get_highest_incremental_snapshot_archive_info(
+                            &snapshot_config.snapshot_package_output_path,
+                            full_snapshot_slot,
+                        )
+                    }) {
+                    RpcRequestMiddleware::redirect(&format!(
+                        "/{}",
+                        incremental_snapshot_archive_info
+                            .path()
+                            .file_name()
+                            .unwrap_or_else(|| std::ffi::OsStr::new(""))
+                            .to_str()
+                            .unwrap_or("")
+                    ))
+                } else {
+                    RpcRequestMiddleware::not_found()
+                }
+                .into();
+            }
         }
 
         if let Some(result) = process_rest(&self.bank_forks, request.uri().path()) {
@@ -287,11 +315,11 @@ impl JsonRpcService {
         trusted_validators: Option<HashSet<Pubkey>>,
         override_health_check: Arc<AtomicBool>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
-        send_transaction_retry_ms: u64,
-        send_transaction_leader_forward_count: u64,
+        send_transaction_service_config: send_transaction_service::Config,
         max_slots: Arc<MaxSlots>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         current_transaction_status_slot: Arc<AtomicU64>,
+        max_complete_account_history_slot: Arc<AtomicU64>,
     ) -> Self {
         info!("rpc bound to {:?}", rpc_addr);
         info!("rpc configuration: {:?}", config);
@@ -322,9 +350,12 @@ impl JsonRpcService {
         let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
             if config.enable_bigtable_ledger_storage || config.enable_bigtable_ledger_upload {
                 runtime
-                    .block_on(solana_storage_bigtable::LedgerStorage::new(
-                        !config.enable_bigtable_ledger_upload,
-                        config.rpc_bigtable_timeout,
+                    .block_on(solana_storage_bigtable::LedgerStorage::new_with_config(
+                        solana_storage_bigtable::LedgerStorageConfig {
+                            read_only: !config.enable_bigtable_ledger_upload,
+                            timeout: config.rpc_bigtable_timeout,
+                            local_storage_path: config.bigtable_local_storage_path.clone(),
+                        },
                     ))
                     .map(|bigtable_ledger_storage| {
                         info!("BigTable ledger storage initialized");
@@ -374,17 +405,18 @@ impl JsonRpcService {
             max_slots,
             leader_schedule_cache,
             current_transaction_status_slot,
+            max_complete_account_history_slot,
         );
 
         let leader_info =
             poh_recorder.map(|recorder| LeaderInfo::new(cluster_info.clone(), recorder));
-        let _send_transaction_service = Arc::new(SendTransactionService::new(
+        let _send_transaction_service = Arc::new(SendTransactionService::new_with_config(
             tpu_address,
             &bank_forks,
             leader_info,
             receiver,
-            send_transaction_retry_ms,
-            send_transaction_leader_forward_count,
+            send_transaction_service_config,
+            request_processor.transaction_retry_statuses(),
         ));
 
         #[cfg(test)]
@@ -508,6 +540,7 @@ mod tests {
             },
         },
         solana_sdk::{
+            clock::Slot,
             genesis_config::{ClusterType, DEFAULT_GENESIS_ARCHIVE},
             signature::Signer,
             signer::keypair::Keypair,
@@ -560,11 +593,15 @@ mod tests {
             None,
             Arc::new(AtomicBool::new(false)),
             optimistically_confirmed_bank,
-            1000,
-            1,
+            send_transaction_service::Config {
+                retry_rate_ms: 1000,
+                leader_forward_count: 1,
+                ..send_transaction_service::Config::default()
+            },
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
         );
         let thread = rpc_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solana-jsonrpc");
@@ -613,6 +650,7 @@ mod tests {
             PathBuf::from("/"),
             Some(SnapshotConfig {
                 snapshot_interval_slots: 0,
+                incremental_snapshot_archive_interval_slots: Slot::MAX,
                 snapshot_package_output_path: PathBuf::from("/"),
                 snapshot_path: PathBuf::from("/"),
                 archive_format: ArchiveFormat::TarBzip2,
@@ -646,6 +684,20 @@ mod tests {
             "/snapshot-notaslotnumber-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.bz2"
         ));
 
+        assert!(!rrm_with_snapshot_config.is_file_get_path("/incremental-snapshot.tar.bz2")); // This is a redirect
+        assert!(!rrm.is_file_get_path(
+            "/incremental-snapshot-100-200-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.bz2"
+        ));
+        assert!(rrm_with_snapshot_config.is_file_get_path(
+            "/incremental-snapshot-100-200-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.bz2"
+        ));
+        assert!(rrm_with_snapshot_config.is_file_get_path(
+            "/incremental-snapshot-100-200-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.zst"
+        ));
+        assert!(!rrm_with_snapshot_config.is_file_get_path(
+            "/incremental-snapshot-notaslotnumber-200-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.bz2"
+        ));
+
         assert!(!rrm_with_snapshot_config.is_file_get_path("../../../test/snapshot-123-xxx.tar"));
 
         assert!(!rrm.is_file_get_path("/"));
@@ -4,6 +4,7 @@ use {
     crate::{
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
         parsed_token_accounts::{get_parsed_token_account, get_parsed_token_accounts},
+        rpc_pubsub_service::PubSubConfig,
     },
     core::hash::Hash,
     jsonrpc_pubsub::{
@@ -13,13 +14,18 @@ use {
     serde::Serialize,
     solana_account_decoder::{parse_token::spl_token_id_v2_0, UiAccount, UiAccountEncoding},
     solana_client::{
-        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig},
+        rpc_config::{
+            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcProgramAccountsConfig,
+            RpcSignatureSubscribeConfig,
+        },
         rpc_filter::RpcFilterType,
         rpc_response::{
-            ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcKeyedAccount,
-            RpcLogsResponse, RpcResponseContext, RpcSignatureResult, SlotInfo, SlotUpdate,
+            ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcBlockUpdate,
+            RpcBlockUpdateError, RpcKeyedAccount, RpcLogsResponse, RpcResponseContext,
+            RpcSignatureResult, SlotInfo, SlotUpdate,
         },
     },
+    solana_ledger::blockstore::Blockstore,
     solana_measure::measure::Measure,
     solana_runtime::{
         bank::{
@@ -30,19 +36,23 @@ use {
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
-        clock::{Slot, UnixTimestamp},
+        clock::Slot,
         commitment_config::CommitmentConfig,
         pubkey::Pubkey,
         signature::Signature,
         timing::timestamp,
         transaction,
     },
+    solana_transaction_status::{
+        resolve_message, ConfirmedBlock, TransactionDetails, UiConfirmedBlock,
+        UiTransactionEncoding,
+    },
     solana_vote_program::vote_state::Vote,
     std::{
         collections::{HashMap, HashSet},
         iter,
         sync::{
-            atomic::{AtomicBool, Ordering},
+            atomic::{AtomicBool, AtomicU64, Ordering},
             mpsc::{Receiver, RecvTimeoutError, SendError, Sender},
         },
         sync::{Arc, Mutex, RwLock},
@@ -51,6 +61,9 @@ use {
     },
 };
 
+// `RpcVote` used to be defined here; keep the old path working
+pub use solana_client::rpc_response::RpcVote;
+
 const RECEIVE_DELAY_MILLIS: u64 = 100;
 
 trait BankGetTransactionLogsAdapter {
@@ -75,14 +88,6 @@ impl BankGetTransactionLogsAdapter for Bank {
     }
 }
 
-// A more human-friendly version of Vote, with the bank state signature base58 encoded.
-#[derive(Serialize, Deserialize, Debug)]
-pub struct RpcVote {
-    pub slots: Vec<Slot>,
-    pub hash: String,
-    pub timestamp: Option<UnixTimestamp>,
-}
-
 enum NotificationEntry {
     Slot(SlotInfo),
     SlotUpdate(SlotUpdate),
@@ -124,6 +129,12 @@ struct ProgramConfig {
     filters: Vec<RpcFilterType>,
     encoding: Option<UiAccountEncoding>,
 }
+#[derive(Clone)]
+struct BlockConfig {
+    encoding: UiTransactionEncoding,
+    transaction_details: TransactionDetails,
+    show_rewards: bool,
+}
 type RpcAccountSubscriptions = RwLock<
     HashMap<
         Pubkey,
@@ -148,6 +159,12 @@ type RpcSignatureSubscriptions = RwLock<
         HashMap<SubscriptionId, SubscriptionData<Response<RpcSignatureResult>, bool>>,
     >,
 >;
+type RpcBlockSubscriptions = RwLock<
+    HashMap<
+        Option<Pubkey>,
+        HashMap<SubscriptionId, SubscriptionData<Response<RpcBlockUpdate>, BlockConfig>>,
+    >,
+>;
 type RpcSlotSubscriptions = RwLock<HashMap<SubscriptionId, Sink<SlotInfo>>>;
 type RpcSlotUpdateSubscriptions = RwLock<HashMap<SubscriptionId, Sink<Arc<SlotUpdate>>>>;
 type RpcVoteSubscriptions = RwLock<HashMap<SubscriptionId, Sink<RpcVote>>>;
@@ -374,6 +391,33 @@ fn filter_logs_results(
     }
 }
 
+fn filter_block_result(
+    mut block: ConfirmedBlock,
+    address: &Option<Pubkey>,
+    config: BlockConfig,
+) -> Option<UiConfirmedBlock> {
+    if let Some(address) = address {
+        block.transactions.retain(|transaction| {
+            let loaded_addresses = transaction
+                .meta
+                .as_ref()
+                .map(|meta| meta.loaded_addresses.clone())
+                .unwrap_or_default();
+            resolve_message(&transaction.transaction.message, &loaded_addresses)
+                .map(|message| message.account_keys.contains(address))
+                .unwrap_or(false)
+        });
+        if block.transactions.is_empty() {
+            return None;
+        }
+    }
+    Some(block.configure(
+        config.encoding,
+        config.transaction_details,
+        config.show_rewards,
+    ))
+}
+
 fn total_nested_subscriptions<K, L, V>(
     subscription_map: &RwLock<HashMap<K, HashMap<L, V>>>,
 ) -> usize {
@@ -394,6 +438,8 @@ struct Subscriptions {
     gossip_logs_subscriptions: Arc<RpcLogsSubscriptions>,
     gossip_program_subscriptions: Arc<RpcProgramSubscriptions>,
     gossip_signature_subscriptions: Arc<RpcSignatureSubscriptions>,
+    block_subscriptions: Arc<RpcBlockSubscriptions>,
+    gossip_block_subscriptions: Arc<RpcBlockSubscriptions>,
     slot_subscriptions: Arc<RpcSlotSubscriptions>,
     slots_updates_subscriptions: Arc<RpcSlotUpdateSubscriptions>,
     vote_subscriptions: Arc<RpcVoteSubscriptions>,
@@ -411,6 +457,8 @@ impl Subscriptions {
         total += total_nested_subscriptions(&self.gossip_logs_subscriptions);
         total += total_nested_subscriptions(&self.gossip_program_subscriptions);
         total += total_nested_subscriptions(&self.gossip_signature_subscriptions);
+        total += total_nested_subscriptions(&self.block_subscriptions);
+        total += total_nested_subscriptions(&self.gossip_block_subscriptions);
         total += self.slot_subscriptions.read().unwrap().len();
         total += self.vote_subscriptions.read().unwrap().len();
         total += self.root_subscriptions.read().unwrap().len();
@@ -418,6 +466,14 @@ impl Subscriptions {
     }
 }
 
+/// Source of the blocks streamed to block subscribers. A block is only streamed once
+/// `TransactionStatusService` has written the statuses of all of its transactions.
+#[derive(Clone)]
+struct BlockSource {
+    blockstore: Arc<Blockstore>,
+    max_complete_transaction_status_slot: Arc<AtomicU64>,
+}
+
 pub struct RpcSubscriptions {
     subscriptions: Subscriptions,
     notification_sender: Arc<Mutex<Sender<NotificationEntry>>>,
@@ -427,6 +483,7 @@ pub struct RpcSubscriptions {
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     exit: Arc<AtomicBool>,
     enable_vote_subscription: bool,
+    block_source: Option<BlockSource>,
 }
 
 impl Drop for RpcSubscriptions {
@@ -459,6 +516,49 @@ impl RpcSubscriptions {
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         enable_vote_subscription: bool,
+    ) -> Self {
+        Self::new_with_block_source(
+            exit,
+            bank_forks,
+            block_commitment_cache,
+            optimistically_confirmed_bank,
+            enable_vote_subscription,
+            None,
+        )
+    }
+
+    /// Create subscriptions as configured for the PubSub service. Block subscriptions, if
+    /// enabled, stream complete blocks read back from `blockstore`.
+    pub fn new_with_config(
+        exit: &Arc<AtomicBool>,
+        max_complete_transaction_status_slot: Arc<AtomicU64>,
+        blockstore: Arc<Blockstore>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+        optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
+        config: &PubSubConfig,
+    ) -> Self {
+        let block_source = config.enable_block_subscription.then(|| BlockSource {
+            blockstore,
+            max_complete_transaction_status_slot,
+        });
+        Self::new_with_block_source(
+            exit,
+            bank_forks,
+            block_commitment_cache,
+            optimistically_confirmed_bank,
+            config.enable_vote_subscription,
+            block_source,
+        )
+    }
+
+    fn new_with_block_source(
+        exit: &Arc<AtomicBool>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+        optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
+        enable_vote_subscription: bool,
+        block_source: Option<BlockSource>,
     ) -> Self {
         let (notification_sender, notification_receiver): (
             Sender<NotificationEntry>,
@@ -473,6 +573,8 @@ impl RpcSubscriptions {
         let gossip_logs_subscriptions = Arc::new(RpcLogsSubscriptions::default());
         let gossip_program_subscriptions = Arc::new(RpcProgramSubscriptions::default());
         let gossip_signature_subscriptions = Arc::new(RpcSignatureSubscriptions::default());
+        let block_subscriptions = Arc::new(RpcBlockSubscriptions::default());
+        let gossip_block_subscriptions = Arc::new(RpcBlockSubscriptions::default());
         let slot_subscriptions = Arc::new(RpcSlotSubscriptions::default());
         let slots_updates_subscriptions = Arc::new(RpcSlotUpdateSubscriptions::default());
         let vote_subscriptions = Arc::new(RpcVoteSubscriptions::default());
@@ -491,6 +593,8 @@ impl RpcSubscriptions {
             gossip_logs_subscriptions,
             gossip_program_subscriptions,
             gossip_signature_subscriptions,
+            block_subscriptions,
+            gossip_block_subscriptions,
             slot_subscriptions,
             slots_updates_subscriptions,
             vote_subscriptions,
@@ -499,6 +603,7 @@ impl RpcSubscriptions {
         let _subscriptions = subscriptions.clone();
 
         let notifier = RpcNotifier {};
+        let _block_source = block_source.clone();
         let t_cleanup = Builder::new()
             .name("solana-rpc-notifications".to_string())
             .spawn(move || {
@@ -508,6 +613,7 @@ impl RpcSubscriptions {
                     notification_receiver,
                     _subscriptions,
                     _bank_forks,
+                    _block_source,
                 );
             })
             .unwrap();
@@ -521,6 +627,7 @@ impl RpcSubscriptions {
             optimistically_confirmed_bank,
             exit: exit.clone(),
             enable_vote_subscription,
+            block_source,
         }
     }
 
@@ -897,6 +1004,78 @@ impl RpcSubscriptions {
         }
     }
 
+    pub fn add_block_subscription(
+        &self,
+        address: Option<Pubkey>,
+        config: Option<RpcBlockSubscribeConfig>,
+        sub_id: SubscriptionId,
+        subscriber: Subscriber<Response<RpcBlockUpdate>>,
+    ) {
+        if self.block_source.is_none() {
+            let _ = subscriber.reject(jsonrpc_core::Error::new(
+                jsonrpc_core::ErrorCode::MethodNotFound,
+            ));
+            return;
+        }
+
+        let config = config.unwrap_or_default();
+        let commitment = config.commitment.unwrap_or_default();
+
+        // Only blocks that reach the commitment level after the subscription is made are
+        // streamed
+        let last_notified_slot = if commitment.is_finalized() {
+            self.block_commitment_cache
+                .read()
+                .unwrap()
+                .highest_confirmed_root()
+        } else if commitment.is_confirmed() {
+            self.optimistically_confirmed_bank
+                .read()
+                .unwrap()
+                .bank
+                .slot()
+        } else {
+            self.block_commitment_cache.read().unwrap().slot()
+        };
+
+        let mut subscriptions = if commitment.is_confirmed() {
+            self.subscriptions
+                .gossip_block_subscriptions
+                .write()
+                .unwrap()
+        } else {
+            self.subscriptions.block_subscriptions.write().unwrap()
+        };
+
+        add_subscription(
+            &mut subscriptions,
+            address,
+            commitment,
+            sub_id,
+            subscriber,
+            last_notified_slot,
+            Some(BlockConfig {
+                encoding: config.encoding.unwrap_or(UiTransactionEncoding::Json),
+                transaction_details: config.transaction_details.unwrap_or_default(),
+                show_rewards: config.show_rewards.unwrap_or(true),
+            }),
+        );
+    }
+
+    pub fn remove_block_subscription(&self, id: &SubscriptionId) -> bool {
+        let mut subscriptions = self.subscriptions.block_subscriptions.write().unwrap();
+        if remove_subscription(&mut subscriptions, id) {
+            true
+        } else {
+            let mut subscriptions = self
+                .subscriptions
+                .gossip_block_subscriptions
+                .write()
+                .unwrap();
+            remove_subscription(&mut subscriptions, id)
+        }
+    }
+
     /// Notify subscribers of changes to any accounts or new signatures since
     /// the bank's last checkpoint.
     pub fn notify_subscribers(&self, commitment_slots: CommitmentSlots) {
@@ -1026,6 +1205,7 @@ impl RpcSubscriptions {
         notification_receiver: Receiver<NotificationEntry>,
         subscriptions: Subscriptions,
         bank_forks: Arc<RwLock<BankForks>>,
+        block_source: Option<BlockSource>,
     ) {
         loop {
             if exit.load(Ordering::Relaxed) {
@@ -1105,7 +1285,16 @@ impl RpcSubscriptions {
                             &commitment_slots,
                             &notifier,
                             "bank",
-                        )
+                        );
+                        if let Some(block_source) = &block_source {
+                            Self::notify_blocks(
+                                &subscriptions.block_subscriptions,
+                                block_source,
+                                &bank_forks,
+                                &commitment_slots,
+                                &notifier,
+                            );
+                        }
                     }
                     NotificationEntry::Gossip(slot) => {
                         Self::process_gossip_notification(
@@ -1113,6 +1302,7 @@ impl RpcSubscriptions {
                             &notifier,
                             &subscriptions,
                             &bank_forks,
+                            block_source.as_ref(),
                         );
                     }
                     NotificationEntry::SignaturesReceived(slot_signatures) => {
@@ -1144,6 +1334,7 @@ impl RpcSubscriptions {
         notifier: &RpcNotifier,
         subscriptions: &Subscriptions,
         bank_forks: &Arc<RwLock<BankForks>>,
+        block_source: Option<&BlockSource>,
     ) {
         let commitment_slots = CommitmentSlots {
             highest_confirmed_slot: slot,
@@ -1159,6 +1350,101 @@ impl RpcSubscriptions {
             notifier,
             "gossip",
         );
+        if let Some(block_source) = block_source {
+            Self::notify_blocks(
+                &subscriptions.gossip_block_subscriptions,
+                block_source,
+                bank_forks,
+                &commitment_slots,
+                notifier,
+            );
+        }
+    }
+
+    /// Stream every block on the fork of the newly processed, confirmed or finalized slot that
+    /// has not yet been sent to each subscriber, oldest first. Each block is read from the
+    /// blockstore once, however many subscribers it is sent to.
+    fn notify_blocks(
+        block_subscriptions: &Arc<RpcBlockSubscriptions>,
+        block_source: &BlockSource,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        commitment_slots: &CommitmentSlots,
+        notifier: &RpcNotifier,
+    ) {
+        // `None` for blocks that could not be read
+        let mut blocks: HashMap<Slot, Option<ConfirmedBlock>> = HashMap::new();
+        let subscriptions = block_subscriptions.read().unwrap();
+        for (address, hashmap) in subscriptions.iter() {
+            for SubscriptionData {
+                sink,
+                commitment,
+                last_notified_slot,
+                config,
+            } in hashmap.values()
+            {
+                let slot = if commitment.is_finalized() {
+                    commitment_slots.highest_confirmed_root
+                } else if commitment.is_confirmed() {
+                    commitment_slots.highest_confirmed_slot
+                } else {
+                    commitment_slots.slot
+                };
+                let bank = match bank_forks.read().unwrap().get(slot).cloned() {
+                    Some(bank) => bank,
+                    None => continue,
+                };
+
+                let mut w_last_notified_slot = last_notified_slot.write().unwrap();
+                for slot in (*w_last_notified_slot + 1..=slot)
+                    .filter(|slot| bank.ancestors.contains_key(slot))
+                {
+                    // Blocks whose transaction statuses are still being written are picked up by
+                    // a later notification
+                    if slot
+                        > block_source
+                            .max_complete_transaction_status_slot
+                            .load(Ordering::SeqCst)
+                    {
+                        break;
+                    }
+                    let block = blocks.entry(slot).or_insert_with(|| {
+                        block_source
+                            .blockstore
+                            .get_complete_block(slot, false)
+                            .map_err(|err| {
+                                warn!("block notification for slot {} failed: {:?}", slot, err);
+                            })
+                            .ok()
+                    });
+                    let block_update = match block {
+                        Some(block) => {
+                            filter_block_result(block.clone(), address, config.clone().unwrap())
+                                .map(|block| RpcBlockUpdate {
+                                    slot,
+                                    block: Some(block),
+                                    err: None,
+                                })
+                        }
+                        None => Some(RpcBlockUpdate {
+                            slot,
+                            block: None,
+                            err: Some(RpcBlockUpdateError::BlockStoreError),
+                        }),
+                    };
+                    if let Some(block_update) = block_update {
+                        inc_new_counter_info!("rpc-subscription-notify-block", 1);
+                        notifier.notify(
+                            Response {
+                                context: RpcResponseContext { slot },
+                                value: block_update,
+                            },
+                            sink,
+                        );
+                    }
+                    *w_last_notified_slot = slot;
+                }
+            }
+        }
     }
 
     fn notify_accounts_logs_programs_signatures(
@@ -1336,6 +1622,7 @@ pub(crate) mod tests {
         jsonrpc_core::futures::StreamExt,
         jsonrpc_pubsub::typed::Subscriber,
         serial_test::serial,
+        solana_ledger::get_tmp_ledger_path,
         solana_runtime::{
             commitment::BlockCommitment,
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
@@ -1346,6 +1633,7 @@ pub(crate) mod tests {
             stake, system_instruction, system_program, system_transaction,
             transaction::Transaction,
         },
+        solana_transaction_status::TransactionWithStatusMeta,
         std::{fmt::Debug, sync::mpsc::channel},
         tokio::{
             runtime::Runtime,
@@ -1418,6 +1706,8 @@ pub(crate) mod tests {
                 commitment: Some(CommitmentConfig::processed()),
                 encoding: None,
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
             create_sub_id.clone(),
             create_sub,
@@ -1477,6 +1767,8 @@ pub(crate) mod tests {
                 commitment: Some(CommitmentConfig::processed()),
                 encoding: None,
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
             close_sub_id.clone(),
             close_sub,
@@ -2026,6 +2318,8 @@ pub(crate) mod tests {
                 commitment: Some(CommitmentConfig::confirmed()),
                 encoding: None,
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
             sub_id0.clone(),
             subscriber0,
@@ -2107,6 +2401,8 @@ pub(crate) mod tests {
                 commitment: Some(CommitmentConfig::confirmed()),
                 encoding: None,
                 data_slice: None,
+                min_context_slot: None,
+                at_slot: None,
             }),
             sub_id1.clone(),
             subscriber1,
@@ -2194,6 +2490,165 @@ pub(crate) mod tests {
         assert_eq!(total_nested_subscriptions(&mock_subscriptions), 3);
     }
 
+    #[test]
+    #[serial]
+    fn test_check_block_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let bank1 = bank_forks.write().unwrap().insert(bank1);
+
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        bank1
+            .transfer(4, &mint_keypair, &keypair2.pubkey())
+            .unwrap();
+        let signatures = crate::rpc::create_test_transactions_and_populate_blockstore(
+            vec![&mint_keypair, &keypair1, &keypair2, &keypair3],
+            0,
+            bank1,
+            blockstore.clone(),
+            max_complete_transaction_status_slot.clone(),
+        );
+
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = RpcSubscriptions::new_with_config(
+            &exit,
+            max_complete_transaction_status_slot,
+            blockstore,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+            &PubSubConfig {
+                enable_block_subscription: true,
+                ..PubSubConfig::default()
+            },
+        );
+
+        // Processed subscribers are sent the newly processed block, which is read once for both
+        let config = RpcBlockSubscribeConfig {
+            commitment: Some(CommitmentConfig::processed()),
+            encoding: None,
+            transaction_details: Some(TransactionDetails::Signatures),
+            show_rewards: Some(false),
+        };
+        let transport_receivers: Vec<_> = (0..2)
+            .map(|i| {
+                let (subscriber, _id_receiver, transport_receiver) =
+                    Subscriber::new_test("blockNotification");
+                subscriptions.add_block_subscription(
+                    None,
+                    Some(config.clone()),
+                    SubscriptionId::Number(i),
+                    subscriber,
+                );
+                transport_receiver
+            })
+            .collect();
+
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        });
+        for (i, transport_receiver) in transport_receivers.into_iter().enumerate() {
+            let (response, _) = robust_poll_or_panic(transport_receiver);
+            let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+            assert_eq!(response["params"]["subscription"], i);
+            let result = &response["params"]["result"];
+            assert_eq!(result["context"]["slot"], 1);
+            assert_eq!(result["value"]["slot"], 1);
+            assert_eq!(result["value"]["err"], serde_json::Value::Null);
+            let block: UiConfirmedBlock =
+                serde_json::from_value(result["value"]["block"].clone()).unwrap();
+            assert_eq!(block.parent_slot, 0);
+            assert_eq!(
+                block.signatures.unwrap()[..2],
+                [signatures[0].to_string(), signatures[1].to_string()]
+            );
+            assert!(block.rewards.is_none());
+        }
+        assert_eq!(
+            *subscriptions
+                .subscriptions
+                .block_subscriptions
+                .read()
+                .unwrap()
+                .get(&None)
+                .unwrap()
+                .get(&SubscriptionId::Number(0))
+                .unwrap()
+                .last_notified_slot
+                .read()
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_filter_block_result() {
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let blockhash = solana_sdk::hash::Hash::default();
+        let transactions = vec![
+            system_transaction::transfer(&alice, &bob.pubkey(), 1, blockhash),
+            system_transaction::transfer(&Keypair::new(), &bob.pubkey(), 1, blockhash),
+        ];
+        let block = ConfirmedBlock {
+            previous_blockhash: blockhash.to_string(),
+            blockhash: blockhash.to_string(),
+            parent_slot: 0,
+            transactions: transactions
+                .iter()
+                .map(|transaction| TransactionWithStatusMeta {
+                    transaction: transaction.clone().into(),
+                    meta: None,
+                })
+                .collect(),
+            rewards: vec![],
+            block_time: None,
+            block_height: None,
+        };
+        let config = BlockConfig {
+            encoding: UiTransactionEncoding::Base64,
+            transaction_details: TransactionDetails::Signatures,
+            show_rewards: false,
+        };
+
+        let all = filter_block_result(block.clone(), &None, config.clone()).unwrap();
+        assert_eq!(
+            all.signatures.unwrap(),
+            vec![
+                transactions[0].signatures[0].to_string(),
+                transactions[1].signatures[0].to_string()
+            ]
+        );
+        assert!(all.rewards.is_none());
+
+        let mentions_alice =
+            filter_block_result(block.clone(), &Some(alice.pubkey()), config.clone()).unwrap();
+        assert_eq!(
+            mentions_alice.signatures.unwrap(),
+            vec![transactions[0].signatures[0].to_string()]
+        );
+
+        let mentions_bob = filter_block_result(block.clone(), &Some(bob.pubkey()), config.clone());
+        assert_eq!(mentions_bob.unwrap().signatures.unwrap().len(), 2);
+
+        assert!(filter_block_result(block, &Some(Pubkey::new_unique()), config).is_none());
+    }
+
     #[test]
     fn test_total_subscriptions() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
@@ -1,6 +1,7 @@
 // TODO: Merge this implementation with the one at `banks-server/src/send_transaction_service.rs`
 use {
     log::*,
+    solana_client::quic_client::QuicClient,
     solana_gossip::cluster_info::ClusterInfo,
     solana_metrics::{datapoint_warn, inc_new_counter_info},
     solana_poh::poh_recorder::PohRecorder,
@@ -14,7 +15,9 @@ use {
     },
     std::{
         collections::HashMap,
+        fs,
         net::{SocketAddr, UdpSocket},
+        path::{Path, PathBuf},
         sync::{
             mpsc::{Receiver, RecvTimeoutError},
             Arc, Mutex, RwLock,
@@ -27,15 +30,55 @@ use {
 /// Maximum size of the transaction queue
 const MAX_TRANSACTION_QUEUE_SIZE: usize = 10_000; // This seems like a lot but maybe it needs to be bigger one day
 
+/// How long the retry status of a transaction that has left the queue remains queryable
+const RETRY_STATUS_RETENTION: Duration = Duration::from_secs(120);
+
 pub struct SendTransactionService {
     thread: JoinHandle<()>,
 }
 
+/// Sends transactions to TPU addresses over UDP, or over QUIC when a client is present
+struct TpuSender {
+    send_socket: UdpSocket,
+    quic_client: Option<QuicClient>,
+}
+
+impl TpuSender {
+    fn new(use_quic: bool) -> Self {
+        let quic_client = if use_quic {
+            QuicClient::new()
+                .map_err(|err| warn!("Failed to create QUIC client, sending over UDP: {}", err))
+                .ok()
+        } else {
+            None
+        };
+        Self {
+            send_socket: UdpSocket::bind("0.0.0.0:0").unwrap(),
+            quic_client,
+        }
+    }
+
+    /// QUIC sends are queued and fall back to UDP on their own
+    fn send(&self, tpu_address: &SocketAddr, wire_transaction: &[u8]) {
+        match &self.quic_client {
+            Some(quic_client) => quic_client.send_wire_transaction(tpu_address, wire_transaction),
+            None => {
+                if let Err(err) = self.send_socket.send_to(wire_transaction, tpu_address) {
+                    warn!("Failed to send transaction to {}: {}", tpu_address, err);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TransactionInfo {
     pub signature: Signature,
     pub wire_transaction: Vec<u8>,
     pub last_valid_slot: Slot,
     pub durable_nonce_info: Option<(Pubkey, Hash)>,
+    pub max_retries: Option<usize>,
+    retries: usize,
 }
 
 impl TransactionInfo {
@@ -44,16 +87,131 @@ impl TransactionInfo {
         wire_transaction: Vec<u8>,
         last_valid_slot: Slot,
         durable_nonce_info: Option<(Pubkey, Hash)>,
+        max_retries: Option<usize>,
     ) -> Self {
         Self {
             signature,
             wire_transaction,
             last_valid_slot,
             durable_nonce_info,
+            max_retries,
+            retries: 0,
         }
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub retry_rate_ms: u64,
+    pub leader_forward_count: u64,
+    /// Maximum retries for transactions that do not specify their own. `None` retries until
+    /// the transaction's blockhash expires
+    pub default_max_retries: Option<usize>,
+    /// Upper bound on the retries of any transaction, whatever it requests
+    pub service_max_retries: usize,
+    /// File in which queued transactions are saved, so that their retries continue across
+    /// restarts
+    pub retry_queue_path: Option<PathBuf>,
+    /// Send transactions to the leaders' QUIC TPU endpoints rather than over UDP
+    pub use_quic: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            retry_rate_ms: 2000,
+            leader_forward_count: 2,
+            default_max_retries: None,
+            service_max_retries: usize::MAX,
+            retry_queue_path: None,
+            use_quic: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionRetryState {
+    /// The transaction is queued and will be sent again if it does not land
+    Pending,
+    Rooted,
+    Failed,
+    /// The transaction's blockhash or durable nonce expired before it was rooted
+    Expired,
+    MaxRetriesReached,
+    /// The queue was full, so the transaction was sent once and never retried
+    Dropped,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionRetryStatus {
+    pub state: TransactionRetryState,
+    pub retries: usize,
+    pub max_retries: Option<usize>,
+    pub last_valid_slot: Slot,
+    /// Identity and TPU address of each leader the transaction was sent to.  The identity is
+    /// unknown when the transaction was sent to this node's TPU for lack of leader info
+    pub leaders: Vec<(Option<Pubkey>, SocketAddr)>,
+}
+
+/// Retry progress of recently sent transactions, shared with the RPC service
+#[derive(Default)]
+pub struct TransactionRetryStatuses {
+    statuses: RwLock<HashMap<Signature, (TransactionRetryStatus, Instant)>>,
+}
+
+impl TransactionRetryStatuses {
+    pub fn get(&self, signature: &Signature) -> Option<TransactionRetryStatus> {
+        self.statuses
+            .read()
+            .unwrap()
+            .get(signature)
+            .map(|(status, _)| status.clone())
+    }
+
+    fn update(
+        &self,
+        transaction_info: &TransactionInfo,
+        state: TransactionRetryState,
+        leaders: &[(Option<Pubkey>, &SocketAddr)],
+    ) {
+        let mut statuses = self.statuses.write().unwrap();
+        let (status, last_update) =
+            statuses
+                .entry(transaction_info.signature)
+                .or_insert_with(|| {
+                    (
+                        TransactionRetryStatus {
+                            state,
+                            retries: 0,
+                            max_retries: transaction_info.max_retries,
+                            last_valid_slot: transaction_info.last_valid_slot,
+                            leaders: vec![],
+                        },
+                        Instant::now(),
+                    )
+                });
+        status.state = state;
+        status.retries = transaction_info.retries;
+        for (identity, address) in leaders {
+            let leader = (*identity, **address);
+            if !status.leaders.contains(&leader) {
+                status.leaders.push(leader);
+            }
+        }
+        *last_update = Instant::now();
+    }
+
+    fn purge_finished(&self) {
+        self.statuses
+            .write()
+            .unwrap()
+            .retain(|_, (status, last_update)| {
+                status.state == TransactionRetryState::Pending
+                    || last_update.elapsed() < RETRY_STATUS_RETENTION
+            });
+    }
+}
+
 pub struct LeaderInfo {
     cluster_info: Arc<ClusterInfo>,
     poh_recorder: Arc<Mutex<PohRecorder>>,
@@ -79,16 +237,24 @@ impl LeaderInfo {
     }
 
     pub fn get_leader_tpus(&self, max_count: u64) -> Vec<&SocketAddr> {
+        self.get_leaders(max_count)
+            .into_iter()
+            .map(|(_, addr)| addr)
+            .collect()
+    }
+
+    /// Identity and TPU address of each of the next `max_count` distinct leaders
+    pub fn get_leaders(&self, max_count: u64) -> Vec<(Pubkey, &SocketAddr)> {
         let recorder = self.poh_recorder.lock().unwrap();
         let leaders: Vec<_> = (0..max_count)
             .filter_map(|i| recorder.leader_after_n_slots(i * NUM_CONSECUTIVE_LEADER_SLOTS))
             .collect();
         drop(recorder);
-        let mut unique_leaders = vec![];
+        let mut unique_leaders: Vec<(Pubkey, &SocketAddr)> = vec![];
         for leader in leaders.iter() {
             if let Some(addr) = self.recent_peers.get(leader) {
-                if !unique_leaders.contains(&addr) {
-                    unique_leaders.push(addr);
+                if !unique_leaders.iter().any(|(_, a)| *a == addr) {
+                    unique_leaders.push((*leader, addr));
                 }
             }
         }
@@ -101,10 +267,20 @@ struct ProcessTransactionsResult {
     rooted: u64,
     expired: u64,
     retried: u64,
+    max_retries_elapsed: u64,
     failed: u64,
     retained: u64,
 }
 
+impl ProcessTransactionsResult {
+    /// Whether any transaction was dropped from the queue.  Retry counts alone don't count, so
+    /// that pending retries don't rewrite the whole queue on every pass; the saved counts are
+    /// brought up to date whenever the set of queued transactions changes.
+    fn queue_changed(&self) -> bool {
+        self.rooted + self.expired + self.max_retries_elapsed + self.failed > 0
+    }
+}
+
 impl SendTransactionService {
     pub fn new(
         tpu_address: SocketAddr,
@@ -113,14 +289,37 @@ impl SendTransactionService {
         receiver: Receiver<TransactionInfo>,
         retry_rate_ms: u64,
         leader_forward_count: u64,
+    ) -> Self {
+        let config = Config {
+            retry_rate_ms,
+            leader_forward_count,
+            ..Config::default()
+        };
+        Self::new_with_config(
+            tpu_address,
+            bank_forks,
+            leader_info,
+            receiver,
+            config,
+            Arc::default(),
+        )
+    }
+
+    pub fn new_with_config(
+        tpu_address: SocketAddr,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        leader_info: Option<LeaderInfo>,
+        receiver: Receiver<TransactionInfo>,
+        config: Config,
+        retry_statuses: Arc<TransactionRetryStatuses>,
     ) -> Self {
         let thread = Self::retry_thread(
             tpu_address,
             receiver,
             bank_forks.clone(),
             leader_info,
-            retry_rate_ms,
-            leader_forward_count,
+            config,
+            retry_statuses,
         );
         Self { thread }
     }
@@ -130,48 +329,64 @@ impl SendTransactionService {
         receiver: Receiver<TransactionInfo>,
         bank_forks: Arc<RwLock<BankForks>>,
         mut leader_info: Option<LeaderInfo>,
-        retry_rate_ms: u64,
-        leader_forward_count: u64,
+        config: Config,
+        retry_statuses: Arc<TransactionRetryStatuses>,
     ) -> JoinHandle<()> {
         let mut last_status_check = Instant::now();
         let mut last_leader_refresh = Instant::now();
-        let mut transactions = HashMap::new();
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+        let mut transactions = config
+            .retry_queue_path
+            .as_deref()
+            .map(Self::load_retry_queue)
+            .unwrap_or_default();
+        let mut retry_queue_dirty = false;
+        let tpu_sender = TpuSender::new(config.use_quic);
 
         if let Some(leader_info) = leader_info.as_mut() {
             leader_info.refresh_recent_peers();
         }
+        for transaction_info in transactions.values() {
+            retry_statuses.update(transaction_info, TransactionRetryState::Pending, &[]);
+        }
 
         Builder::new()
             .name("send-tx-sv2".to_string())
             .spawn(move || loop {
+                let retry_rate_ms = config.retry_rate_ms;
                 match receiver.recv_timeout(Duration::from_millis(1000.min(retry_rate_ms))) {
                     Err(RecvTimeoutError::Disconnected) => break,
                     Err(RecvTimeoutError::Timeout) => {}
-                    Ok(transaction_info) => {
-                        let addresses = leader_info
-                            .as_ref()
-                            .map(|leader_info| leader_info.get_leader_tpus(leader_forward_count));
-                        let addresses = addresses
-                            .map(|address_list| {
-                                if address_list.is_empty() {
-                                    vec![&tpu_address]
-                                } else {
-                                    address_list
-                                }
-                            })
-                            .unwrap_or_else(|| vec![&tpu_address]);
-                        for address in addresses {
-                            Self::send_transaction(
-                                &send_socket,
-                                address,
-                                &transaction_info.wire_transaction,
-                            );
+                    Ok(mut transaction_info) => {
+                        let leaders = Self::get_tpu_addresses(
+                            &tpu_address,
+                            leader_info.as_ref(),
+                            config.leader_forward_count,
+                        );
+                        for (_, address) in &leaders {
+                            tpu_sender.send(address, &transaction_info.wire_transaction);
                         }
+                        let max_retries = transaction_info
+                            .max_retries
+                            .or(config.default_max_retries)
+                            .unwrap_or(usize::MAX)
+                            .min(config.service_max_retries);
+                        transaction_info.max_retries =
+                            (max_retries != usize::MAX).then(|| max_retries);
                         if transactions.len() < MAX_TRANSACTION_QUEUE_SIZE {
+                            retry_statuses.update(
+                                &transaction_info,
+                                TransactionRetryState::Pending,
+                                &leaders,
+                            );
                             transactions.insert(transaction_info.signature, transaction_info);
+                            retry_queue_dirty = true;
                         } else {
                             datapoint_warn!("send_transaction_service-queue-overflow");
+                            retry_statuses.update(
+                                &transaction_info,
+                                TransactionRetryState::Dropped,
+                                &leaders,
+                            );
                         }
                     }
                 }
@@ -190,16 +405,28 @@ impl SendTransactionService {
                             )
                         };
 
-                        let _result = Self::process_transactions(
+                        let result = Self::process_transactions(
                             &working_bank,
                             &root_bank,
-                            &send_socket,
+                            &tpu_sender,
                             &tpu_address,
                             &mut transactions,
                             &leader_info,
-                            leader_forward_count,
+                            &config,
+                            &retry_statuses,
                         );
+                        retry_queue_dirty |= result.queue_changed();
+                    }
+                    if retry_queue_dirty {
+                        retry_queue_dirty = match &config.retry_queue_path {
+                            // A failed write stays dirty, to be retried on the next check
+                            Some(retry_queue_path) => {
+                                !Self::save_retry_queue(retry_queue_path, &transactions)
+                            }
+                            None => false,
+                        };
                     }
+                    retry_statuses.purge_finished();
                     last_status_check = Instant::now();
                     if last_leader_refresh.elapsed().as_millis() > 1000 {
                         if let Some(leader_info) = leader_info.as_mut() {
@@ -212,14 +439,85 @@ impl SendTransactionService {
             .unwrap()
     }
 
+    fn load_retry_queue(retry_queue_path: &Path) -> HashMap<Signature, TransactionInfo> {
+        if !retry_queue_path.exists() {
+            return HashMap::new();
+        }
+        match fs::read(retry_queue_path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| {
+                bincode::deserialize::<Vec<TransactionInfo>>(&data).map_err(|err| err.to_string())
+            }) {
+            Ok(transactions) => {
+                info!(
+                    "Loaded {} queued transactions from {:?}",
+                    transactions.len(),
+                    retry_queue_path
+                );
+                transactions
+                    .into_iter()
+                    .take(MAX_TRANSACTION_QUEUE_SIZE)
+                    .map(|transaction_info| (transaction_info.signature, transaction_info))
+                    .collect()
+            }
+            Err(err) => {
+                warn!(
+                    "Discarding unreadable transaction retry queue {:?}: {}",
+                    retry_queue_path, err
+                );
+                HashMap::new()
+            }
+        }
+    }
+
+    fn save_retry_queue(
+        retry_queue_path: &Path,
+        transactions: &HashMap<Signature, TransactionInfo>,
+    ) -> bool {
+        // Write to a temporary file first so that a crash never leaves a truncated queue behind
+        let tmp_path = retry_queue_path.with_extension("tmp");
+        let transactions: Vec<_> = transactions.values().collect();
+        let result = bincode::serialize(&transactions)
+            .map_err(|err| err.to_string())
+            .and_then(|data| fs::write(&tmp_path, data).map_err(|err| err.to_string()))
+            .and_then(|()| fs::rename(&tmp_path, retry_queue_path).map_err(|err| err.to_string()));
+        if let Err(err) = &result {
+            warn!(
+                "Failed to save transaction retry queue to {:?}: {}",
+                retry_queue_path, err
+            );
+        }
+        result.is_ok()
+    }
+
+    fn get_tpu_addresses<'a>(
+        tpu_address: &'a SocketAddr,
+        leader_info: Option<&'a LeaderInfo>,
+        leader_forward_count: u64,
+    ) -> Vec<(Option<Pubkey>, &'a SocketAddr)> {
+        let leaders = leader_info
+            .map(|leader_info| leader_info.get_leaders(leader_forward_count))
+            .unwrap_or_default();
+        if leaders.is_empty() {
+            vec![(None, tpu_address)]
+        } else {
+            leaders
+                .into_iter()
+                .map(|(identity, address)| (Some(identity), address))
+                .collect()
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn process_transactions(
         working_bank: &Arc<Bank>,
         root_bank: &Arc<Bank>,
-        send_socket: &UdpSocket,
+        tpu_sender: &TpuSender,
         tpu_address: &SocketAddr,
         transactions: &mut HashMap<Signature, TransactionInfo>,
         leader_info: &Option<LeaderInfo>,
-        leader_forward_count: u64,
+        config: &Config,
+        retry_statuses: &TransactionRetryStatuses,
     ) -> ProcessTransactionsResult {
         let mut result = ProcessTransactionsResult::default();
 
@@ -231,6 +529,7 @@ impl SendTransactionService {
                 info!("Transaction is rooted: {}", signature);
                 result.rooted += 1;
                 inc_new_counter_info!("send_transaction_service-rooted", 1);
+                retry_statuses.update(transaction_info, TransactionRetryState::Rooted, &[]);
                 return false;
             }
             if let Some((nonce_pubkey, durable_nonce)) = transaction_info.durable_nonce_info {
@@ -241,6 +540,7 @@ impl SendTransactionService {
                     info!("Dropping expired durable-nonce transaction: {}", signature);
                     result.expired += 1;
                     inc_new_counter_info!("send_transaction_service-expired", 1);
+                    retry_statuses.update(transaction_info, TransactionRetryState::Expired, &[]);
                     return false;
                 }
             }
@@ -248,35 +548,49 @@ impl SendTransactionService {
                 info!("Dropping expired transaction: {}", signature);
                 result.expired += 1;
                 inc_new_counter_info!("send_transaction_service-expired", 1);
+                retry_statuses.update(transaction_info, TransactionRetryState::Expired, &[]);
                 return false;
             }
 
             match working_bank.get_signature_status_slot(signature) {
                 None => {
                     // Transaction is unknown to the working bank, it might have been
-                    // dropped or landed in another fork.  Re-send it
+                    // dropped or landed in another fork.  Re-send it, unless it has used up
+                    // its retries
+                    if let Some(max_retries) = transaction_info.max_retries {
+                        if transaction_info.retries >= max_retries {
+                            info!("Dropping transaction due to max retries: {}", signature);
+                            result.max_retries_elapsed += 1;
+                            inc_new_counter_info!(
+                                "send_transaction_service-max_retries_elapsed",
+                                1
+                            );
+                            retry_statuses.update(
+                                transaction_info,
+                                TransactionRetryState::MaxRetriesReached,
+                                &[],
+                            );
+                            return false;
+                        }
+                    }
+
                     info!("Retrying transaction: {}", signature);
                     result.retried += 1;
+                    transaction_info.retries += 1;
                     inc_new_counter_info!("send_transaction_service-retry", 1);
-                    let addresses = leader_info
-                        .as_ref()
-                        .map(|leader_info| leader_info.get_leader_tpus(leader_forward_count));
-                    let addresses = addresses
-                        .map(|address_list| {
-                            if address_list.is_empty() {
-                                vec![tpu_address]
-                            } else {
-                                address_list
-                            }
-                        })
-                        .unwrap_or_else(|| vec![tpu_address]);
-                    for address in addresses {
-                        Self::send_transaction(
-                            send_socket,
-                            address,
-                            &transaction_info.wire_transaction,
-                        );
+                    let leaders = Self::get_tpu_addresses(
+                        tpu_address,
+                        leader_info.as_ref(),
+                        config.leader_forward_count,
+                    );
+                    for (_, address) in &leaders {
+                        tpu_sender.send(address, &transaction_info.wire_transaction);
                     }
+                    retry_statuses.update(
+                        transaction_info,
+                        TransactionRetryState::Pending,
+                        &leaders,
+                    );
                     true
                 }
                 Some((_slot, status)) => {
@@ -284,6 +598,7 @@ impl SendTransactionService {
                         info!("Dropping failed transaction: {}", signature);
                         result.failed += 1;
                         inc_new_counter_info!("send_transaction_service-failed", 1);
+                        retry_statuses.update(transaction_info, TransactionRetryState::Failed, &[]);
                         false
                     } else {
                         result.retained += 1;
@@ -296,16 +611,6 @@ impl SendTransactionService {
         result
     }
 
-    fn send_transaction(
-        send_socket: &UdpSocket,
-        tpu_address: &SocketAddr,
-        wire_transaction: &[u8],
-    ) {
-        if let Err(err) = send_socket.send_to(wire_transaction, tpu_address) {
-            warn!("Failed to send transaction to {}: {:?}", tpu_address, err);
-        }
-    }
-
     pub fn join(self) -> thread::Result<()> {
         self.thread.join()
     }
@@ -358,9 +663,13 @@ mod test {
         let (genesis_config, mint_keypair) = create_genesis_config(4);
         let bank = Bank::new(&genesis_config);
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+        let tpu_sender = TpuSender::new(false);
         let tpu_address = "127.0.0.1:0".parse().unwrap();
-        let leader_forward_count = 1;
+        let config = Config {
+            leader_forward_count: 1,
+            ..Config::default()
+        };
+        let retry_statuses = TransactionRetryStatuses::default();
 
         let root_bank = Arc::new(Bank::new_from_parent(
             &bank_forks.read().unwrap().working_bank(),
@@ -391,16 +700,23 @@ mod test {
         info!("Expired transactions are dropped...");
         transactions.insert(
             Signature::default(),
-            TransactionInfo::new(Signature::default(), vec![], root_bank.slot() - 1, None),
+            TransactionInfo::new(
+                Signature::default(),
+                vec![],
+                root_bank.slot() - 1,
+                None,
+                None,
+            ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -414,16 +730,17 @@ mod test {
         info!("Rooted transactions are dropped...");
         transactions.insert(
             rooted_signature,
-            TransactionInfo::new(rooted_signature, vec![], working_bank.slot(), None),
+            TransactionInfo::new(rooted_signature, vec![], working_bank.slot(), None, None),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -433,20 +750,22 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+        assert!(result.queue_changed());
 
         info!("Failed transactions are dropped...");
         transactions.insert(
             failed_signature,
-            TransactionInfo::new(failed_signature, vec![], working_bank.slot(), None),
+            TransactionInfo::new(failed_signature, vec![], working_bank.slot(), None, None),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -460,16 +779,23 @@ mod test {
         info!("Non-rooted transactions are kept...");
         transactions.insert(
             non_rooted_signature,
-            TransactionInfo::new(non_rooted_signature, vec![], working_bank.slot(), None),
+            TransactionInfo::new(
+                non_rooted_signature,
+                vec![],
+                working_bank.slot(),
+                None,
+                None,
+            ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert_eq!(transactions.len(), 1);
         assert_eq!(
@@ -479,21 +805,30 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+        // Nothing to persist when every transaction is simply still pending
+        assert!(!result.queue_changed());
         transactions.clear();
 
         info!("Unknown transactions are retried...");
         transactions.insert(
             Signature::default(),
-            TransactionInfo::new(Signature::default(), vec![], working_bank.slot(), None),
+            TransactionInfo::new(
+                Signature::default(),
+                vec![],
+                working_bank.slot(),
+                None,
+                None,
+            ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert_eq!(transactions.len(), 1);
         assert_eq!(
@@ -503,6 +838,100 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+        // Retries leave the set of queued transactions as it was, so nothing is persisted
+        assert!(!result.queue_changed());
+        assert_eq!(
+            retry_statuses.get(&Signature::default()),
+            Some(TransactionRetryStatus {
+                state: TransactionRetryState::Pending,
+                retries: 1,
+                max_retries: None,
+                last_valid_slot: working_bank.slot(),
+                leaders: vec![(None, tpu_address)],
+            })
+        );
+        assert_eq!(
+            retry_statuses.get(&failed_signature).unwrap().state,
+            TransactionRetryState::Failed
+        );
+        transactions.clear();
+
+        info!("Transactions are dropped once they reach max retries...");
+        let signature = Signature::new(&[1; 64]);
+        transactions.insert(
+            signature,
+            TransactionInfo::new(signature, vec![], working_bank.slot(), None, Some(1)),
+        );
+        let result = SendTransactionService::process_transactions(
+            &working_bank,
+            &root_bank,
+            &tpu_sender,
+            &tpu_address,
+            &mut transactions,
+            &None,
+            &config,
+            &retry_statuses,
+        );
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                retried: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+        let result = SendTransactionService::process_transactions(
+            &working_bank,
+            &root_bank,
+            &tpu_sender,
+            &tpu_address,
+            &mut transactions,
+            &None,
+            &config,
+            &retry_statuses,
+        );
+        assert!(transactions.is_empty());
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                max_retries_elapsed: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+        let status = retry_statuses.get(&signature).unwrap();
+        assert_eq!(status.state, TransactionRetryState::MaxRetriesReached);
+        assert_eq!(status.retries, 1);
+    }
+
+    #[test]
+    fn test_retry_queue_persistence() {
+        let ledger_path = get_tmp_ledger_path!();
+        std::fs::create_dir_all(&ledger_path).unwrap();
+        let retry_queue_path = ledger_path.join("retry_queue");
+        assert!(SendTransactionService::load_retry_queue(&retry_queue_path).is_empty());
+
+        let mut transaction_info = TransactionInfo::new(
+            Signature::new(&[1; 64]),
+            vec![1, 2, 3],
+            42,
+            Some((Pubkey::new_unique(), Hash::new_unique())),
+            Some(5),
+        );
+        transaction_info.retries = 2;
+        let mut transactions = HashMap::new();
+        transactions.insert(transaction_info.signature, transaction_info.clone());
+        assert!(SendTransactionService::save_retry_queue(
+            &retry_queue_path,
+            &transactions
+        ));
+        assert_eq!(
+            SendTransactionService::load_retry_queue(&retry_queue_path),
+            transactions
+        );
+
+        std::fs::write(&retry_queue_path, b"garbage").unwrap();
+        assert!(SendTransactionService::load_retry_queue(&retry_queue_path).is_empty());
+        std::fs::remove_dir_all(&ledger_path).unwrap();
     }
 
     #[test]
@@ -512,9 +941,13 @@ mod test {
         let (genesis_config, mint_keypair) = create_genesis_config(4);
         let bank = Bank::new(&genesis_config);
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+        let tpu_sender = TpuSender::new(false);
         let tpu_address = "127.0.0.1:0".parse().unwrap();
-        let leader_forward_count = 1;
+        let config = Config {
+            leader_forward_count: 1,
+            ..Config::default()
+        };
+        let retry_statuses = TransactionRetryStatuses::default();
 
         let root_bank = Arc::new(Bank::new_from_parent(
             &bank_forks.read().unwrap().working_bank(),
@@ -563,16 +996,18 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, durable_nonce)),
+                None,
             ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -590,16 +1025,18 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())),
+                None,
             ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -619,16 +1056,18 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())),
+                None,
             ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -646,16 +1085,18 @@ mod test {
                 vec![],
                 root_bank.slot() - 1,
                 Some((nonce_address, durable_nonce)),
+                None,
             ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -674,16 +1115,18 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())), // runtime should advance nonce on failed transactions
+                None,
             ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -702,16 +1145,18 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())), // runtime advances nonce when transaction lands
+                None,
             ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert_eq!(transactions.len(), 1);
         assert_eq!(
@@ -731,16 +1176,18 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, durable_nonce)),
+                None,
             ),
         );
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert_eq!(transactions.len(), 1);
         assert_eq!(
@@ -764,11 +1211,12 @@ mod test {
         let result = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &tpu_sender,
             &tpu_address,
             &mut transactions,
             &None,
-            leader_forward_count,
+            &config,
+            &retry_statuses,
         );
         assert_eq!(transactions.len(), 0);
         assert_eq!(
@@ -0,0 +1,18 @@
+use {
+    solana_sdk::{clock::Slot, sanitized_transaction::SanitizedTransaction, signature::Signature},
+    solana_transaction_status::TransactionStatusMeta,
+    std::sync::Arc,
+};
+
+pub trait TransactionNotifierInterface {
+    /// Notified when a transaction has been processed and its status recorded
+    fn notify_transaction(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        transaction_status_meta: &TransactionStatusMeta,
+        transaction: &SanitizedTransaction,
+    );
+}
+
+pub type TransactionNotifier = Arc<dyn TransactionNotifierInterface + Sync + Send>;
@@ -1,4 +1,5 @@
 use {
+    crate::transaction_notifier_interface::TransactionNotifier,
     crossbeam_channel::{Receiver, RecvTimeoutError},
     itertools::izip,
     solana_ledger::{
@@ -8,6 +9,7 @@ use {
     solana_runtime::bank::{
         Bank, InnerInstructionsList, NonceRollbackInfo, TransactionLogMessages,
     },
+    solana_sdk::feature_set::prioritization_fee,
     solana_transaction_status::{InnerInstructions, Reward, TransactionStatusMeta},
     std::{
         sync::{
@@ -28,6 +30,8 @@ impl TransactionStatusService {
     pub fn new(
         write_transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
+        enable_rpc_transaction_history: bool,
+        transaction_notifier: Option<TransactionNotifier>,
         blockstore: Arc<Blockstore>,
         exit: &Arc<AtomicBool>,
     ) -> Self {
@@ -41,6 +45,8 @@ impl TransactionStatusService {
                 if let Err(RecvTimeoutError::Disconnected) = Self::write_transaction_status_batch(
                     &write_transaction_status_receiver,
                     &max_complete_transaction_status_slot,
+                    enable_rpc_transaction_history,
+                    transaction_notifier.as_ref(),
                     &blockstore,
                 ) {
                     break;
@@ -53,6 +59,8 @@ impl TransactionStatusService {
     fn write_transaction_status_batch(
         write_transaction_status_receiver: &Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
+        enable_rpc_transaction_history: bool,
+        transaction_notifier: Option<&TransactionNotifier>,
         blockstore: &Arc<Blockstore>,
     ) -> Result<(), RecvTimeoutError> {
         match write_transaction_status_receiver.recv_timeout(Duration::from_secs(1))? {
@@ -109,7 +117,11 @@ impl TransactionStatusService {
                                 bank.get_fee_calculator(&transaction.message().recent_blockhash)
                             })
                             .expect("FeeCalculator must exist");
-                        let fee = fee_calculator.calculate_fee(transaction.message());
+                        let fee = Bank::calculate_fee(
+                            &fee_calculator,
+                            transaction,
+                            bank.feature_set.is_active(&prioritization_fee::id()),
+                        );
                         let (writable_keys, readonly_keys) =
                             transaction.message.get_account_keys_by_lock_type();
 
@@ -141,25 +153,42 @@ impl TransactionStatusService {
                                 .collect(),
                         );
 
-                        blockstore
-                            .write_transaction_status(
+                        let transaction_status_meta = TransactionStatusMeta {
+                            status,
+                            fee,
+                            pre_balances,
+                            post_balances,
+                            inner_instructions,
+                            log_messages,
+                            pre_token_balances,
+                            post_token_balances,
+                            rewards,
+                            loaded_addresses: transaction
+                                .loaded_addresses()
+                                .cloned()
+                                .unwrap_or_default(),
+                        };
+
+                        if let Some(transaction_notifier) = transaction_notifier {
+                            transaction_notifier.notify_transaction(
                                 slot,
-                                transaction.signatures[0],
-                                writable_keys,
-                                readonly_keys,
-                                TransactionStatusMeta {
-                                    status,
-                                    fee,
-                                    pre_balances,
-                                    post_balances,
-                                    inner_instructions,
-                                    log_messages,
-                                    pre_token_balances,
-                                    post_token_balances,
-                                    rewards,
-                                },
-                            )
-                            .expect("Expect database write to succeed");
+                                &transaction.signatures[0],
+                                &transaction_status_meta,
+                                transaction,
+                            );
+                        }
+
+                        if enable_rpc_transaction_history {
+                            blockstore
+                                .write_transaction_status(
+                                    slot,
+                                    transaction.signatures[0],
+                                    writable_keys,
+                                    readonly_keys,
+                                    transaction_status_meta,
+                                )
+                                .expect("Expect database write to succeed");
+                        }
                     }
                 }
             }
@@ -1,12 +1,12 @@
 use crate::{
     accounts_db::{
-        AccountShrinkThreshold, AccountsDb, BankHashInfo, ErrorCounters, LoadHint, LoadedAccount,
-        ScanStorageResult,
+        AccountShrinkThreshold, AccountsDb, AccountsDbConfig, BankHashInfo, ErrorCounters,
+        LoadHint, LoadedAccount, ScanStorageResult,
     },
     accounts_index::{AccountSecondaryIndexes, IndexKey, ScanResult},
     ancestors::Ancestors,
     bank::{
-        NonceRollbackFull, NonceRollbackInfo, RentDebits, TransactionCheckResult,
+        Bank, NonceRollbackFull, NonceRollbackInfo, RentDebits, TransactionCheckResult,
         TransactionExecutionResult,
     },
     blockhash_queue::BlockhashQueue,
@@ -37,7 +37,7 @@ use solana_sdk::{
 use std::{
     cmp::Reverse,
     collections::{hash_map, BinaryHeap, HashMap, HashSet},
-    ops::RangeBounds,
+    ops::{Bound, RangeBounds},
     path::PathBuf,
     sync::{Arc, Mutex},
 };
@@ -127,6 +127,7 @@ impl Accounts {
             AccountSecondaryIndexes::default(),
             false,
             shrink_ratio,
+            None,
         )
     }
 
@@ -136,6 +137,7 @@ impl Accounts {
         account_indexes: AccountSecondaryIndexes,
         caching_enabled: bool,
         shrink_ratio: AccountShrinkThreshold,
+        accounts_db_config: Option<AccountsDbConfig>,
     ) -> Self {
         Self {
             accounts_db: Arc::new(AccountsDb::new_with_config(
@@ -144,6 +146,7 @@ impl Accounts {
                 account_indexes,
                 caching_enabled,
                 shrink_ratio,
+                accounts_db_config,
             )),
             account_locks: Mutex::new(AccountLocks::default()),
         }
@@ -423,7 +426,11 @@ impl Accounts {
                                 .cloned()
                         });
                     let fee = if let Some(fee_calculator) = fee_calculator {
-                        fee_calculator.calculate_fee(tx.message())
+                        Bank::calculate_fee(
+                            &fee_calculator,
+                            tx,
+                            feature_set.is_active(&feature_set::prioritization_fee::id()),
+                        )
                     } else {
                         return (Err(TransactionError::BlockhashNotFound), None);
                     };
@@ -710,6 +717,31 @@ impl Accounts {
         )
     }
 
+    /// Load at most `limit` accounts owned by `program_id` that pass `filter`, in pubkey order,
+    /// starting after `start_after`
+    pub fn load_by_program_with_filter_paginated<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_id: &Pubkey,
+        filter: F,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        let start_bound = start_after.map_or(Bound::Unbounded, |pubkey| Bound::Excluded(*pubkey));
+        self.accounts_db.checked_range_scan_accounts(
+            ancestors,
+            bank_id,
+            (start_bound, Bound::Unbounded),
+            |collector: &mut Vec<(Pubkey, AccountSharedData)>, some_account_tuple| {
+                Self::load_while_filtering(collector, some_account_tuple, |account| {
+                    account.owner() == program_id && filter(account)
+                })
+            },
+            |collector| collector.len() >= limit,
+        )
+    }
+
     pub fn load_by_index_key_with_filter<F: Fn(&AccountSharedData) -> bool>(
         &self,
         ancestors: &Ancestors,
@@ -731,6 +763,30 @@ impl Accounts {
             .map(|result| result.0)
     }
 
+    /// Load at most `limit` accounts under `index_key` that pass `filter`, in pubkey order,
+    /// starting after `start_after`
+    pub fn load_by_index_key_with_filter_paginated<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: &IndexKey,
+        filter: F,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        let start_bound = start_after.map_or(Bound::Unbounded, |pubkey| Bound::Excluded(*pubkey));
+        self.accounts_db.checked_index_range_scan_accounts(
+            ancestors,
+            bank_id,
+            *index_key,
+            (start_bound, Bound::Unbounded),
+            |collector: &mut Vec<(Pubkey, AccountSharedData)>, some_account_tuple| {
+                Self::load_while_filtering(collector, some_account_tuple, |account| filter(account))
+            },
+            |collector| collector.len() >= limit,
+        )
+    }
+
     pub fn account_indexes_include_key(&self, key: &Pubkey) -> bool {
         self.accounts_db.account_indexes.include_key(key)
     }
@@ -1109,6 +1165,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         for ka in ka.iter() {
             accounts.store_slow_uncached(0, &ka.0, &ka.1);
@@ -1647,6 +1704,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
 
         // Load accounts owned by various programs into AccountsDb
@@ -1668,6 +1726,127 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test]
+    fn test_load_by_program_with_filter_paginated() {
+        let accounts = Accounts::new_with_config(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+            None,
+        );
+        let program_id = Pubkey::new(&[2; 32]);
+        let mut pubkeys: Vec<_> = (0..5).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        for (i, pubkey) in pubkeys.iter().enumerate() {
+            let account = AccountSharedData::new(1, i, &program_id);
+            accounts.store_slow_uncached(0, pubkey, &account);
+        }
+        // Accounts owned by other programs are never returned
+        let other_account = AccountSharedData::new(1, 0, &Pubkey::new(&[3; 32]));
+        accounts.store_slow_uncached(0, &solana_sdk::pubkey::new_rand(), &other_account);
+        pubkeys.sort();
+
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let mut start_after = None;
+        let mut loaded_pubkeys = vec![];
+        loop {
+            let page = accounts
+                .load_by_program_with_filter_paginated(
+                    &ancestors,
+                    0,
+                    &program_id,
+                    |_| true,
+                    start_after.as_ref(),
+                    2,
+                )
+                .unwrap();
+            assert!(page.len() <= 2);
+            if page.is_empty() {
+                break;
+            }
+            start_after = page.last().map(|(pubkey, _)| *pubkey);
+            loaded_pubkeys.extend(page.into_iter().map(|(pubkey, _)| pubkey));
+        }
+        assert_eq!(loaded_pubkeys, pubkeys);
+
+        // Filters are applied before the page size limit
+        let page = accounts
+            .load_by_program_with_filter_paginated(
+                &ancestors,
+                0,
+                &program_id,
+                |account| account.data().len() >= 3,
+                None,
+                10,
+            )
+            .unwrap();
+        assert_eq!(page.len(), 2);
+    }
+
+    #[test]
+    fn test_load_by_index_key_with_filter_paginated() {
+        let accounts = Accounts::new_with_config(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes {
+                keys: None,
+                indexes: vec![crate::accounts_index::AccountIndex::ProgramId]
+                    .into_iter()
+                    .collect(),
+            },
+            false,
+            AccountShrinkThreshold::default(),
+            None,
+        );
+        let program_id = Pubkey::new(&[2; 32]);
+        let mut pubkeys: Vec<_> = (0..5).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        for (i, pubkey) in pubkeys.iter().enumerate() {
+            let account = AccountSharedData::new(1, i, &program_id);
+            accounts.store_slow_uncached(0, pubkey, &account);
+        }
+        let other_account = AccountSharedData::new(1, 0, &Pubkey::new(&[3; 32]));
+        accounts.store_slow_uncached(0, &solana_sdk::pubkey::new_rand(), &other_account);
+        pubkeys.sort();
+
+        // Pages of the index are cut in pubkey order
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let index_key = IndexKey::ProgramId(program_id);
+        let mut start_after = None;
+        let mut loaded_pubkeys = vec![];
+        loop {
+            let page = accounts
+                .load_by_index_key_with_filter_paginated(
+                    &ancestors,
+                    0,
+                    &index_key,
+                    |_| true,
+                    start_after.as_ref(),
+                    2,
+                )
+                .unwrap();
+            assert!(page.len() <= 2);
+            if page.is_empty() {
+                break;
+            }
+            start_after = page.last().map(|(pubkey, _)| *pubkey);
+            loaded_pubkeys.extend(page.into_iter().map(|(pubkey, _)| pubkey));
+        }
+        assert_eq!(loaded_pubkeys, pubkeys);
+
+        let page = accounts
+            .load_by_index_key_with_filter_paginated(
+                &ancestors,
+                0,
+                &index_key,
+                |account| account.data().len() >= 3,
+                None,
+                10,
+            )
+            .unwrap();
+        assert_eq!(page.len(), 2);
+    }
+
     #[test]
     fn test_accounts_account_not_found() {
         let accounts = Accounts::new_with_config(
@@ -1676,6 +1855,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         let mut error_counters = ErrorCounters::default();
         let ancestors = vec![(0, 0)].into_iter().collect();
@@ -1700,6 +1880,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         accounts.bank_hash_at(1);
     }
@@ -1722,6 +1903,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         accounts.store_slow_uncached(0, &keypair0.pubkey(), &account0);
         accounts.store_slow_uncached(0, &keypair1.pubkey(), &account1);
@@ -1832,6 +2014,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         accounts.store_slow_uncached(0, &keypair0.pubkey(), &account0);
         accounts.store_slow_uncached(0, &keypair1.pubkey(), &account1);
@@ -1971,6 +2154,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         {
             accounts
@@ -2019,6 +2203,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         let mut old_pubkey = Pubkey::default();
         let zero_account = AccountSharedData::new(0, 0, AccountSharedData::default().owner());
@@ -2036,7 +2221,7 @@ mod tests {
             }
         }
         info!("done..cleaning..");
-        accounts.accounts_db.clean_accounts(None, false);
+        accounts.accounts_db.clean_accounts(None, false, None);
     }
 
     fn load_accounts_no_store(accounts: &Accounts, tx: Transaction) -> Vec<TransactionLoadResult> {
@@ -2067,6 +2252,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
 
         let instructions_key = solana_sdk::sysvar::instructions::id();
@@ -2349,6 +2535,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         let txs = &[tx];
         let collected_accounts = accounts.collect_accounts_to_store(
@@ -2466,6 +2653,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         let txs = &[tx];
         let collected_accounts = accounts.collect_accounts_to_store(
@@ -2502,6 +2690,7 @@ mod tests {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
 
         let pubkey0 = Pubkey::new_unique();
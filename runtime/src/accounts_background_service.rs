@@ -6,7 +6,7 @@ use crate::{
     bank::{Bank, BankSlotDelta, DropCallback},
     bank_forks::BankForks,
     snapshot_config::SnapshotConfig,
-    snapshot_package::AccountsPackageSender,
+    snapshot_package::{AccountsPackageSender, SnapshotType},
     snapshot_utils,
 };
 use crossbeam_channel::{Receiver, SendError, Sender};
@@ -94,6 +94,7 @@ impl SnapshotRequestHandler {
         test_hash_calculation: bool,
         use_index_hash_calculation: bool,
         non_snapshot_time_us: u128,
+        last_full_snapshot_slot: &mut Option<Slot>,
     ) -> Option<u64> {
         self.snapshot_request_receiver
             .try_iter()
@@ -105,6 +106,9 @@ impl SnapshotRequestHandler {
                     status_cache_slot_deltas,
                 } = snapshot_request;
 
+                let snapshot_type = self
+                    .get_snapshot_type(snapshot_root_bank.block_height(), *last_full_snapshot_slot);
+
                 let previous_hash = if test_hash_calculation {
                     // We have to use the index version here.
                     // We cannot calculate the non-index way because cache has not been flushed and stores don't match reality.
@@ -147,9 +151,13 @@ impl SnapshotRequestHandler {
                     use_index_hash_calculation,
                     test_hash_calculation,
                 );
+                // An incremental snapshot package only holds a subset of the storages, so its
+                // accounts hash cannot be recalculated from them
                 let hash_for_testing = if test_hash_calculation {
                     assert_eq!(previous_hash, this_hash);
-                    Some(snapshot_root_bank.get_accounts_hash())
+                    snapshot_type
+                        .map_or(true, |snapshot_type| snapshot_type.is_full_snapshot())
+                        .then(|| snapshot_root_bank.get_accounts_hash())
                 } else {
                     None
                 };
@@ -160,7 +168,7 @@ impl SnapshotRequestHandler {
                 // accounts that were included in the bank delta hash when the bank was frozen,
                 // and if we clean them here, the newly created snapshot's hash may not match
                 // the frozen hash.
-                snapshot_root_bank.clean_accounts(true, false);
+                snapshot_root_bank.clean_accounts(true, false, *last_full_snapshot_slot);
                 clean_time.stop();
 
                 if accounts_db_caching_enabled {
@@ -180,6 +188,7 @@ impl SnapshotRequestHandler {
                     self.snapshot_config.snapshot_version,
                     &self.snapshot_config.archive_format,
                     hash_for_testing,
+                    snapshot_type,
                 );
                 if r.is_err() {
                     warn!(
@@ -187,6 +196,10 @@ impl SnapshotRequestHandler {
                         snapshot_root_bank.slot(),
                         r
                     );
+                } else if snapshot_type == Some(SnapshotType::FullSnapshot)
+                    && self.is_incremental_snapshot_enabled()
+                {
+                    *last_full_snapshot_slot = Some(snapshot_root_bank.slot());
                 }
                 snapshot_time.stop();
 
@@ -218,6 +231,41 @@ impl SnapshotRequestHandler {
                 snapshot_root_bank.block_height()
             })
     }
+
+    fn is_incremental_snapshot_enabled(&self) -> bool {
+        self.snapshot_config
+            .incremental_snapshot_archive_interval_slots
+            != Slot::MAX
+    }
+
+    /// Decide which kind of snapshot archive, if any, should be made for the bank at
+    /// `block_height`.  Full snapshots take precedence; incremental snapshots need a full snapshot
+    /// to build on.
+    ///
+    /// Both intervals count blocks, as do the snapshot requests, so that skipped slots can't make
+    /// the two drift apart.  Full snapshots are made at multiples of the full snapshot interval,
+    /// and incremental snapshots at multiples of their own interval past the last of those.
+    fn get_snapshot_type(
+        &self,
+        block_height: Slot,
+        last_full_snapshot_slot: Option<Slot>,
+    ) -> Option<SnapshotType> {
+        let blocks_since_full_snapshot =
+            block_height % self.snapshot_config.snapshot_interval_slots;
+        if blocks_since_full_snapshot == 0 {
+            Some(SnapshotType::FullSnapshot)
+        } else if self.is_incremental_snapshot_enabled()
+            && blocks_since_full_snapshot
+                % self
+                    .snapshot_config
+                    .incremental_snapshot_archive_interval_slots
+                == 0
+        {
+            last_full_snapshot_slot.map(SnapshotType::IncrementalSnapshot)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Default)]
@@ -261,6 +309,7 @@ impl AbsRequestHandler {
         test_hash_calculation: bool,
         use_index_hash_calculation: bool,
         non_snapshot_time_us: u128,
+        last_full_snapshot_slot: &mut Option<Slot>,
     ) -> Option<u64> {
         self.snapshot_request_handler
             .as_ref()
@@ -270,6 +319,7 @@ impl AbsRequestHandler {
                     test_hash_calculation,
                     use_index_hash_calculation,
                     non_snapshot_time_us,
+                    last_full_snapshot_slot,
                 )
             })
     }
@@ -300,6 +350,7 @@ impl AccountsBackgroundService {
         accounts_db_caching_enabled: bool,
         test_hash_calculation: bool,
         use_index_hash_calculation: bool,
+        mut last_full_snapshot_slot: Option<Slot>,
     ) -> Self {
         info!("AccountsBackgroundService active");
         let exit = exit.clone();
@@ -358,6 +409,7 @@ impl AccountsBackgroundService {
                         test_hash_calculation,
                         use_index_hash_calculation,
                         non_snapshot_time,
+                        &mut last_full_snapshot_slot,
                     );
                     if snapshot_block_height.is_some() {
                         last_snapshot_end_time = Some(Instant::now());
@@ -399,10 +451,16 @@ impl AccountsBackgroundService {
                                 // slots >= bank.slot()
                                 bank.force_flush_accounts_cache();
                             }
-                            bank.clean_accounts(true, false);
+                            bank.clean_accounts(true, false, last_full_snapshot_slot);
                             last_cleaned_block_height = bank.block_height();
+                            if accounts_db_caching_enabled {
+                                bank.compress_cold_storages();
+                            }
                         }
                     }
+                    if accounts_db_caching_enabled {
+                        bank.evict_cold_storage_cache();
+                    }
                     sleep(Duration::from_millis(INTERVAL_MS));
                 }
             })
@@ -450,9 +508,15 @@ impl AccountsBackgroundService {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::genesis_utils::create_genesis_config;
+    use crate::{
+        genesis_utils::create_genesis_config,
+        snapshot_utils::{
+            ArchiveFormat, SnapshotVersion, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        },
+    };
     use crossbeam_channel::unbounded;
     use solana_sdk::{account::AccountSharedData, pubkey::Pubkey};
+    use std::{path::PathBuf, sync::mpsc::channel};
 
     #[test]
     fn test_accounts_background_service_remove_dead_slots() {
@@ -479,4 +543,79 @@ mod test {
 
         assert!(bank0.rc.accounts.scan_slot(0, |_| Some(())).is_empty());
     }
+
+    #[test]
+    fn test_get_snapshot_type() {
+        let (_snapshot_request_sender, snapshot_request_receiver) = unbounded();
+        let (accounts_package_sender, _accounts_package_receiver) = channel();
+        let snapshot_request_handler = SnapshotRequestHandler {
+            snapshot_config: SnapshotConfig {
+                snapshot_interval_slots: 100,
+                incremental_snapshot_archive_interval_slots: 30,
+                snapshot_package_output_path: PathBuf::default(),
+                snapshot_path: PathBuf::default(),
+                archive_format: ArchiveFormat::TarBzip2,
+                snapshot_version: SnapshotVersion::default(),
+                maximum_snapshots_to_retain: DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            },
+            snapshot_request_receiver,
+            accounts_package_sender,
+        };
+
+        // Full snapshots follow the block height
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(100, None),
+            Some(SnapshotType::FullSnapshot)
+        );
+        // Incremental snapshots need a full snapshot to build on
+        assert_eq!(snapshot_request_handler.get_snapshot_type(90, None), None);
+
+        // The incremental cadence restarts at every full snapshot, whichever slots the blocks
+        // landed in, e.g. a full snapshot at block height 100 in slot 107
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(120, Some(107)),
+            None
+        );
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(130, Some(107)),
+            Some(SnapshotType::IncrementalSnapshot(107))
+        );
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(150, Some(107)),
+            None
+        );
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(160, Some(107)),
+            Some(SnapshotType::IncrementalSnapshot(107))
+        );
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(190, Some(107)),
+            Some(SnapshotType::IncrementalSnapshot(107))
+        );
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(200, Some(107)),
+            Some(SnapshotType::FullSnapshot)
+        );
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(210, Some(215)),
+            None
+        );
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(230, Some(215)),
+            Some(SnapshotType::IncrementalSnapshot(215))
+        );
+
+        // Incremental snapshots can be disabled
+        let snapshot_request_handler = SnapshotRequestHandler {
+            snapshot_config: SnapshotConfig {
+                incremental_snapshot_archive_interval_slots: Slot::MAX,
+                ..snapshot_request_handler.snapshot_config
+            },
+            ..snapshot_request_handler
+        };
+        assert_eq!(
+            snapshot_request_handler.get_snapshot_type(130, Some(107)),
+            None
+        );
+    }
 }
@@ -23,9 +23,10 @@ use crate::{
     accounts_cache::{AccountsCache, CachedAccount, SlotCache},
     accounts_hash::{AccountsHash, CalculateHashIntermediate, HashStats, PreviousPass},
     accounts_index::{
-        AccountIndexGetResult, AccountSecondaryIndexes, AccountsIndex, AccountsIndexRootsStats,
-        IndexKey, IsCached, ScanResult, SlotList, SlotSlice, ZeroLamport,
+        AccountIndexGetResult, AccountSecondaryIndexes, AccountsIndex, AccountsIndexConfig,
+        AccountsIndexRootsStats, IndexKey, IsCached, ScanResult, SlotList, SlotSlice, ZeroLamport,
     },
+    accounts_update_notifier_interface::AccountsUpdateNotifier,
     ancestors::Ancestors,
     append_vec::{AppendVec, StoredAccountMeta, StoredMeta, StoredMetaWriteVersion},
     contains::Contains,
@@ -78,10 +79,14 @@ const STORE_META_OVERHEAD: usize = 256;
 const MAX_CACHE_SLOTS: usize = 200;
 const FLUSH_CACHE_RANDOM_THRESHOLD: usize = MAX_LOCKOUT_HISTORY;
 const SCAN_SLOT_PAR_ITER_THRESHOLD: usize = 4000;
+// Caps the time each pass of compress_cold_storages() holds up AccountsBackgroundService
+const MAX_COLD_STORAGE_BYTES_PER_PASS: u64 = 256 * 1024 * 1024;
 
 pub const DEFAULT_FILE_SIZE: u64 = PAGE_SIZE * 1024;
 pub const DEFAULT_NUM_THREADS: u32 = 8;
 pub const DEFAULT_NUM_DIRS: u32 = 4;
+pub const DEFAULT_COLD_STORAGE_MIN_AGE_SLOTS: Slot = 432_000;
+pub const DEFAULT_COLD_STORAGE_CACHE_BYTES: usize = 1024 * 1024 * 1024;
 
 // A specially reserved storage id just for entries in the cache, so that
 // operations that take a storage entry can maintain a common interface
@@ -138,6 +143,22 @@ impl Default for AccountShrinkThreshold {
     }
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct AccountsDbConfig {
+    pub index: Option<AccountsIndexConfig>,
+    pub cold_storage: Option<ColdStorageConfig>,
+}
+
+/// Rooted storages without dead accounts are compressed into `path` once their slot is
+/// `min_age_slots` older than the max root. About `cache_bytes` of them are kept decompressed in
+/// memory.
+#[derive(Debug, Clone)]
+pub struct ColdStorageConfig {
+    pub path: PathBuf,
+    pub min_age_slots: Slot,
+    pub cache_bytes: usize,
+}
+
 pub enum ScanStorageResult<R, B> {
     Cached(Vec<R>),
     Stored(B),
@@ -203,7 +224,7 @@ impl GenerateIndexTimings {
     }
 }
 
-#[derive(Default, Debug, PartialEq, Clone)]
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct AccountInfo {
     /// index identifying the append storage
     store_id: AppendVecId,
@@ -810,6 +831,10 @@ pub const EXPIRATION_TTL_SECONDS: u64 = 1800;
 
 impl RecycleStores {
     fn add_entry(&mut self, new_entry: Arc<AccountStorageEntry>) {
+        // compressed storages can't be appended to, so they are dropped rather than recycled
+        if new_entry.accounts.is_compressed() {
+            return;
+        }
         self.total_bytes += new_entry.total_bytes();
         self.entries.push((Instant::now(), new_entry))
     }
@@ -819,10 +844,12 @@ impl RecycleStores {
     }
 
     fn add_entries(&mut self, new_entries: Vec<Arc<AccountStorageEntry>>) {
-        self.total_bytes += new_entries.iter().map(|e| e.total_bytes()).sum::<u64>();
         let now = Instant::now();
         for new_entry in new_entries {
-            self.entries.push((now, new_entry));
+            if !new_entry.accounts.is_compressed() {
+                self.total_bytes += new_entry.total_bytes();
+                self.entries.push((now, new_entry));
+            }
         }
     }
 
@@ -969,6 +996,20 @@ pub struct AccountsDb {
     /// such that potentially a 0-lamport account update could be present which
     /// means we can remove the account from the index entirely.
     dirty_stores: DashMap<(Slot, AppendVecId), Arc<AccountStorageEntry>>,
+
+    /// Zero-lamport accounts that are *not* purged during clean because they need to stay alive
+    /// for incremental snapshot support.  They are reconsidered once a full snapshot is taken at
+    /// or after their slot.
+    zero_lamport_accounts_to_purge_after_full_snapshot: DashSet<(Slot, Pubkey)>,
+
+    /// Notifies interested parties, such as accountsdb plugins, of account updates
+    accounts_update_notifier: RwLock<Option<AccountsUpdateNotifier>>,
+
+    /// Where and when to compress cold storages, if at all
+    cold_storage: Option<ColdStorageConfig>,
+
+    /// The number of bytes decompressed from cold storages
+    cold_storage_loaded_bytes: Arc<AtomicUsize>,
 }
 
 #[derive(Debug, Default)]
@@ -1414,6 +1455,10 @@ impl Default for AccountsDb {
             remove_unrooted_slots_synchronization: RemoveUnrootedSlotsSynchronization::default(),
             shrink_ratio: AccountShrinkThreshold::default(),
             dirty_stores: DashMap::default(),
+            zero_lamport_accounts_to_purge_after_full_snapshot: DashSet::default(),
+            accounts_update_notifier: RwLock::new(None),
+            cold_storage: None,
+            cold_storage_loaded_bytes: Arc::default(),
         }
     }
 }
@@ -1429,6 +1474,7 @@ impl AccountsDb {
             AccountSecondaryIndexes::default(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         )
     }
 
@@ -1438,9 +1484,15 @@ impl AccountsDb {
         account_indexes: AccountSecondaryIndexes,
         caching_enabled: bool,
         shrink_ratio: AccountShrinkThreshold,
+        accounts_db_config: Option<AccountsDbConfig>,
     ) -> Self {
+        let accounts_db_config = accounts_db_config.unwrap_or_default();
+        let accounts_index = AccountsIndex::new(accounts_db_config.index);
+        let cold_storage = accounts_db_config.cold_storage;
         let mut new = if !paths.is_empty() {
             Self {
+                accounts_index,
+                cold_storage,
                 paths,
                 temp_paths: None,
                 cluster_type: Some(*cluster_type),
@@ -1454,6 +1506,8 @@ impl AccountsDb {
             // for testing
             let (temp_dirs, paths) = get_temp_accounts_paths(DEFAULT_NUM_DIRS).unwrap();
             Self {
+                accounts_index,
+                cold_storage,
                 paths,
                 temp_paths: Some(temp_dirs),
                 cluster_type: Some(*cluster_type),
@@ -1469,6 +1523,9 @@ impl AccountsDb {
             for path in new.paths.iter() {
                 std::fs::create_dir_all(path).expect("Create directory failed.");
             }
+            if let Some(cold_storage) = &new.cold_storage {
+                std::fs::create_dir_all(&cold_storage.path).expect("Create directory failed.");
+            }
         }
         new
     }
@@ -1482,6 +1539,10 @@ impl AccountsDb {
         *shrink_paths = Some(paths);
     }
 
+    pub fn set_accounts_update_notifier(&self, accounts_update_notifier: AccountsUpdateNotifier) {
+        *self.accounts_update_notifier.write().unwrap() = Some(accounts_update_notifier);
+    }
+
     pub fn file_size(&self) -> u64 {
         self.file_size
     }
@@ -1741,6 +1802,7 @@ impl AccountsDb {
     fn construct_candidate_clean_keys(
         &self,
         max_clean_root: Option<Slot>,
+        last_full_snapshot_slot: Option<Slot>,
         timings: &mut CleanKeyTimings,
     ) -> Vec<Pubkey> {
         let mut dirty_store_processing_time = Measure::start("dirty_store_processing");
@@ -1788,6 +1850,20 @@ impl AccountsDb {
 
         timings.delta_key_count = pubkeys.len() as u64;
 
+        // Zero-lamport accounts held back by a previous clean can be reconsidered once a full
+        // snapshot has been taken at or after the slot they were last updated in.
+        if let Some(last_full_snapshot_slot) = last_full_snapshot_slot {
+            self.zero_lamport_accounts_to_purge_after_full_snapshot
+                .retain(|(slot, pubkey)| {
+                    let is_candidate_for_clean =
+                        max_slot >= *slot && last_full_snapshot_slot >= *slot;
+                    if is_candidate_for_clean {
+                        pubkeys.insert(*pubkey);
+                    }
+                    !is_candidate_for_clean
+                });
+        }
+
         let mut hashset_to_vec = Measure::start("flat_map");
         let pubkeys: Vec<Pubkey> = pubkeys.into_iter().collect();
         hashset_to_vec.stop();
@@ -1796,11 +1872,64 @@ impl AccountsDb {
         pubkeys
     }
 
+    /// During clean, some zero-lamport accounts that are marked for purge should *not* actually
+    /// get purged.  Filter out those accounts here.
+    ///
+    /// Only keep purges_zero_lamports where the entire history of the account in the root set
+    /// can be purged.  All AppendVecs for those updates are dead.
+    ///
+    /// When using incremental snapshots, also do not purge zero-lamport accounts whose latest
+    /// update is in a slot higher than the last full snapshot slot.  The full snapshot still
+    /// holds the account's old, non-zero state, so if the zero-lamport update were purged, an
+    /// incremental snapshot taken afterwards would carry no record of it and booting from the
+    /// full+incremental pair would resurrect the account.  Such accounts are remembered and
+    /// reconsidered after the next full snapshot.
+    fn filter_zero_lamport_clean_for_incremental_snapshots(
+        &self,
+        max_clean_root: Option<Slot>,
+        last_full_snapshot_slot: Option<Slot>,
+        store_counts: &HashMap<AppendVecId, (usize, HashSet<Pubkey>)>,
+        purges_zero_lamports: &mut HashMap<Pubkey, (SlotList<AccountInfo>, u64)>,
+    ) {
+        let should_filter_for_incremental_snapshots =
+            max_clean_root.unwrap_or(Slot::MAX) > last_full_snapshot_slot.unwrap_or(Slot::MAX);
+
+        purges_zero_lamports.retain(|pubkey, (account_infos, _ref_count)| {
+            for (_slot, account_info) in account_infos.iter() {
+                if store_counts.get(&account_info.store_id).unwrap().0 != 0 {
+                    return false;
+                }
+            }
+
+            if !should_filter_for_incremental_snapshots {
+                return true;
+            }
+
+            account_infos
+                .iter()
+                .map(|(slot, _account_info)| *slot)
+                .max()
+                .map_or(true, |slot| {
+                    let cannot_purge = slot > last_full_snapshot_slot.unwrap();
+                    if cannot_purge {
+                        self.zero_lamport_accounts_to_purge_after_full_snapshot
+                            .insert((slot, *pubkey));
+                    }
+                    !cannot_purge
+                })
+        });
+    }
+
     // Purge zero lamport accounts and older rooted account states as garbage
     // collection
     // Only remove those accounts where the entire rooted history of the account
     // can be purged because there are no live append vecs in the ancestors
-    pub fn clean_accounts(&self, max_clean_root: Option<Slot>, is_startup: bool) {
+    pub fn clean_accounts(
+        &self,
+        max_clean_root: Option<Slot>,
+        is_startup: bool,
+        last_full_snapshot_slot: Option<Slot>,
+    ) {
         let max_clean_root = self.max_clean_root(max_clean_root);
 
         // hold a lock to prevent slot shrinking from running because it might modify some rooted
@@ -1810,7 +1939,11 @@ impl AccountsDb {
         self.report_store_stats();
 
         let mut key_timings = CleanKeyTimings::default();
-        let pubkeys = self.construct_candidate_clean_keys(max_clean_root, &mut key_timings);
+        let pubkeys = self.construct_candidate_clean_keys(
+            max_clean_root,
+            last_full_snapshot_slot,
+            &mut key_timings,
+        );
 
         let total_keys_count = pubkeys.len();
         let mut accounts_scan = Measure::start("accounts_scan");
@@ -1944,17 +2077,13 @@ impl AccountsDb {
         Self::calc_delete_dependencies(&purges_zero_lamports, &mut store_counts);
         calc_deps_time.stop();
 
-        // Only keep purges_zero_lamports where the entire history of the account in the root set
-        // can be purged. All AppendVecs for those updates are dead.
         let mut purge_filter = Measure::start("purge_filter");
-        purges_zero_lamports.retain(|_pubkey, (account_infos, _ref_count)| {
-            for (_slot, account_info) in account_infos.iter() {
-                if store_counts.get(&account_info.store_id).unwrap().0 != 0 {
-                    return false;
-                }
-            }
-            true
-        });
+        self.filter_zero_lamport_clean_for_incremental_snapshots(
+            max_clean_root,
+            last_full_snapshot_slot,
+            &store_counts,
+            &mut purges_zero_lamports,
+        );
         purge_filter.stop();
 
         let mut reclaims_time = Measure::start("reclaims");
@@ -1989,6 +2118,9 @@ impl AccountsDb {
 
         reclaims_time.stop();
 
+        // Evict index entries that were in use, and so kept in memory, while clean ran
+        self.accounts_index.evict_over_limit();
+
         self.clean_accounts_stats.report();
         datapoint_info!(
             "clean_accounts",
@@ -2133,9 +2265,11 @@ impl AccountsDb {
         let mut stored_accounts: HashMap<Pubkey, FoundStoredAccount> = HashMap::new();
         let mut original_bytes = 0;
         let mut num_stores = 0;
+        let mut is_compressed = false;
         for store in stores {
             let mut start = 0;
             original_bytes += store.total_bytes();
+            is_compressed |= store.accounts.is_compressed();
             while let Some((account, next)) = store.accounts.get_account(start) {
                 let new_entry = FoundStoredAccount {
                     account,
@@ -2257,11 +2391,12 @@ impl AccountsDb {
             // here, we're writing back alive_accounts. That should be an atomic operation
             // without use of rather wide locks in this whole function, because we're
             // mutating rooted slots; There should be no writers to them.
+            let store_for_write = shrunken_store.clone();
             store_accounts_timing = self.store_accounts_frozen(
                 slot,
                 &accounts,
                 Some(&hashes),
-                Some(Box::new(move |_, _| shrunken_store.clone())),
+                Some(Box::new(move |_, _| store_for_write.clone())),
                 Some(Box::new(write_versions.into_iter())),
             );
 
@@ -2288,6 +2423,22 @@ impl AccountsDb {
             }
             start.stop();
             write_storage_elapsed = start.as_us();
+
+            // Shrinking a cold storage must not leave it uncompressed
+            if is_compressed {
+                if let Some(cold_storage) = &self.cold_storage {
+                    if let Err(err) =
+                        self.compress_storage(slot, &shrunken_store, &cold_storage.path)
+                    {
+                        error!(
+                            "Unable to compress shrunken store {} of slot {}: {}",
+                            shrunken_store.append_vec_id(),
+                            slot,
+                            err
+                        );
+                    }
+                }
+            }
         }
         rewrite_elapsed.stop();
 
@@ -2508,6 +2659,197 @@ impl AccountsDb {
         }
     }
 
+    /// Compress rooted storages without dead accounts once their slot is old enough, oldest
+    /// first. Returns the number of storages compressed.
+    ///
+    /// The compressed storage replaces the original under the same id and holds the accounts at
+    /// the same offsets, so the accounts index is unaffected. Like shrinking, this must run
+    /// serially with clean and cache flushes, which also remove accounts from rooted storages.
+    pub fn compress_cold_storages(&self) -> usize {
+        let cold_storage = match &self.cold_storage {
+            Some(cold_storage) if self.caching_enabled => cold_storage,
+            _ => return 0,
+        };
+        let max_slot = match self
+            .accounts_index
+            .max_root()
+            .checked_sub(cold_storage.min_age_slots)
+        {
+            Some(max_slot) => max_slot,
+            None => return 0,
+        };
+
+        let mut measure = Measure::start("compress_cold_storages-ms");
+        let mut slots = self.all_slots_in_storage();
+        slots.retain(|slot| *slot <= max_slot && self.accounts_index.is_root(*slot));
+        slots.sort_unstable();
+        let mut compressed_bytes = 0;
+        let mut num_compressed = 0;
+        'slots: for slot in slots {
+            let stores = self
+                .storage
+                .get_slot_storage_entries(slot)
+                .unwrap_or_default();
+            for store in stores {
+                if !Self::is_cold_storage_candidate(&store) {
+                    continue;
+                }
+                if compressed_bytes >= MAX_COLD_STORAGE_BYTES_PER_PASS {
+                    break 'slots;
+                }
+                match self.compress_storage(slot, &store, &cold_storage.path) {
+                    Ok(true) => {
+                        compressed_bytes += store.written_bytes();
+                        num_compressed += 1;
+                    }
+                    Ok(false) => {}
+                    Err(err) => {
+                        error!(
+                            "Unable to compress store {} of slot {}: {}",
+                            store.append_vec_id(),
+                            slot,
+                            err
+                        );
+                        break 'slots;
+                    }
+                }
+            }
+        }
+        measure.stop();
+        inc_new_counter_info!("compress_cold_storages-ms", measure.as_ms() as usize);
+        inc_new_counter_info!("compress_cold_storages-count", num_compressed);
+        num_compressed
+    }
+
+    /// Replace the compressed storages read least recently with copies that have nothing
+    /// decompressed, until the blocks decompressed from cold storages fit in the configured
+    /// cache. Returns the number of storages replaced.
+    ///
+    /// A replaced storage, and the memory holding its blocks, is freed once the readers holding
+    /// it are done. Like compress_cold_storages(), this must run serially with clean and cache
+    /// flushes.
+    pub fn evict_cold_storage_cache(&self) -> usize {
+        let cache_bytes = match &self.cold_storage {
+            Some(cold_storage) => cold_storage.cache_bytes,
+            None => return 0,
+        };
+        let mut loaded_bytes = self.cold_storage_loaded_bytes.load(Ordering::Relaxed);
+        if loaded_bytes <= cache_bytes {
+            return 0;
+        }
+
+        let mut measure = Measure::start("evict_cold_storage_cache-ms");
+        let mut stores = vec![];
+        for slot_stores in self.storage.0.iter() {
+            let slot = *slot_stores.key();
+            stores.extend(
+                slot_stores
+                    .value()
+                    .read()
+                    .unwrap()
+                    .values()
+                    .filter(|store| store.accounts.loaded_bytes() > 0)
+                    .map(|store| (slot, store.clone())),
+            );
+        }
+        stores.sort_unstable_by_key(|(_slot, store)| store.accounts.last_read_ms());
+        let mut num_evicted = 0;
+        for (slot, store) in stores {
+            if loaded_bytes <= cache_bytes {
+                break;
+            }
+            let store_loaded_bytes = store.accounts.loaded_bytes();
+            match store.accounts.reopen_compressed() {
+                Ok(accounts) => {
+                    if self.replace_storage(slot, &store, accounts) {
+                        loaded_bytes = loaded_bytes.saturating_sub(store_loaded_bytes);
+                        num_evicted += 1;
+                    }
+                }
+                Err(err) => {
+                    error!(
+                        "Unable to reopen store {} of slot {}: {}",
+                        store.append_vec_id(),
+                        slot,
+                        err
+                    );
+                    break;
+                }
+            }
+        }
+        measure.stop();
+        inc_new_counter_info!("evict_cold_storage_cache-ms", measure.as_ms() as usize);
+        inc_new_counter_info!("evict_cold_storage_cache-count", num_evicted);
+        num_evicted
+    }
+
+    fn is_cold_storage_candidate(store: &AccountStorageEntry) -> bool {
+        // alive bytes include the alignment padding after the last account, so they only fall
+        // below the written bytes once an account is dead
+        !store.accounts.is_compressed()
+            && store.count() > 0
+            && store.alive_bytes() as u64 >= store.written_bytes()
+    }
+
+    /// Replace `store` with a compressed copy written to `path`. Returns false if `store` was
+    /// removed from `slot` meanwhile.
+    fn compress_storage(
+        &self,
+        slot: Slot,
+        store: &Arc<AccountStorageEntry>,
+        path: &Path,
+    ) -> IoResult<bool> {
+        let id = store.append_vec_id();
+        let accounts = store.accounts.compress(
+            &path.join(AppendVec::file_name(slot, id)),
+            &self.cold_storage_loaded_bytes,
+        )?;
+
+        // dropping `accounts` if it isn't used removes the compressed file
+        if !self.replace_storage(slot, store, accounts) {
+            return Ok(false);
+        }
+        debug!(
+            "compressed store: {} slot: {} len: {} path: {:?}",
+            id,
+            slot,
+            store.written_bytes(),
+            path
+        );
+        Ok(true)
+    }
+
+    /// Replace `store` with a storage of the same id and counts holding the same accounts, at
+    /// the same offsets, in `accounts`. Returns false if `store` was removed from `slot`
+    /// meanwhile.
+    fn replace_storage(
+        &self,
+        slot: Slot,
+        store: &Arc<AccountStorageEntry>,
+        accounts: AppendVec,
+    ) -> bool {
+        let slot_stores = match self.storage.get_slot_stores(slot) {
+            Some(slot_stores) => slot_stores,
+            None => return false,
+        };
+        let mut slot_stores = slot_stores.write().unwrap();
+        let id = store.append_vec_id();
+        match slot_stores.get(&id) {
+            Some(current) if Arc::ptr_eq(current, store) => (),
+            _ => return false,
+        }
+        let count_and_status = store.count_and_status.read().unwrap();
+        let replacement =
+            AccountStorageEntry::new_existing(slot, id, accounts, store.approx_stored_count());
+        *replacement.count_and_status.write().unwrap() =
+            (count_and_status.0, AccountStorageStatus::Full);
+        replacement
+            .alive_bytes
+            .store(store.alive_bytes(), Ordering::SeqCst);
+        slot_stores.insert(id, Arc::new(replacement));
+        true
+    }
+
     pub fn scan_accounts<F, A>(
         &self,
         ancestors: &Ancestors,
@@ -2597,6 +2939,46 @@ impl AccountsDb {
         collector
     }
 
+    /// Scan the accounts in `range` in pubkey order, stopping as soon as `is_done` returns true
+    /// for the collector
+    pub fn checked_range_scan_accounts<F, D, A, R>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        range: R,
+        scan_func: F,
+        is_done: D,
+    ) -> ScanResult<A>
+    where
+        F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
+        D: Fn(&A) -> bool,
+        A: Default,
+        R: RangeBounds<Pubkey>,
+    {
+        let mut collector = A::default();
+        let abort_scan = AtomicBool::new(false);
+
+        // This can error out if the slots being scanned over are aborted
+        self.accounts_index.checked_range_scan_accounts(
+            ancestors,
+            bank_id,
+            range,
+            |pubkey, (account_info, slot)| {
+                let account_slot = self
+                    .get_account_accessor(slot, pubkey, account_info.store_id, account_info.offset)
+                    .get_loaded_account()
+                    .map(|loaded_account| (pubkey, loaded_account.take_account(), slot));
+                scan_func(&mut collector, account_slot);
+                if is_done(&collector) {
+                    abort_scan.store(true, Ordering::Relaxed);
+                }
+            },
+            &abort_scan,
+        )?;
+
+        Ok(collector)
+    }
+
     pub fn index_scan_accounts<F, A>(
         &self,
         ancestors: &Ancestors,
@@ -2608,12 +2990,7 @@ impl AccountsDb {
         F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
         A: Default,
     {
-        let key = match &index_key {
-            IndexKey::ProgramId(key) => key,
-            IndexKey::SplTokenMint(key) => key,
-            IndexKey::SplTokenOwner(key) => key,
-        };
-        if !self.account_indexes.include_key(key) {
+        if !self.is_index_key_included(&index_key) {
             // the requested key was not indexed in the secondary index, so do a normal scan
             let used_index = false;
             let scan_result = self.scan_accounts(ancestors, bank_id, scan_func)?;
@@ -2637,6 +3014,60 @@ impl AccountsDb {
         Ok((collector, used_index))
     }
 
+    /// Scan the accounts under `index_key` within `range`, in pubkey order, until `is_done`
+    pub fn checked_index_range_scan_accounts<F, D, A, R>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: IndexKey,
+        range: R,
+        scan_func: F,
+        is_done: D,
+    ) -> ScanResult<A>
+    where
+        F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
+        D: Fn(&A) -> bool,
+        A: Default,
+        R: RangeBounds<Pubkey>,
+    {
+        if !self.is_index_key_included(&index_key) {
+            // the requested key was not indexed in the secondary index, so do a normal scan
+            return self.checked_range_scan_accounts(ancestors, bank_id, range, scan_func, is_done);
+        }
+
+        let mut collector = A::default();
+        let abort_scan = AtomicBool::new(false);
+        self.accounts_index.checked_index_range_scan_accounts(
+            ancestors,
+            bank_id,
+            index_key,
+            range,
+            |pubkey, (account_info, slot)| {
+                let account_slot = self
+                    .get_account_accessor(slot, pubkey, account_info.store_id, account_info.offset)
+                    .get_loaded_account()
+                    .map(|loaded_account| (pubkey, loaded_account.take_account(), slot));
+                scan_func(&mut collector, account_slot);
+                if is_done(&collector) {
+                    abort_scan.store(true, Ordering::Relaxed);
+                }
+            },
+            &abort_scan,
+        )?;
+        Ok(collector)
+    }
+
+    fn is_index_key_included(&self, index_key: &IndexKey) -> bool {
+        let key = match index_key {
+            IndexKey::ProgramId(key) => key,
+            IndexKey::SplTokenMint(key) => key,
+            IndexKey::SplTokenOwner(key) => key,
+            // User-defined indexes include or exclude accounts by their owning program
+            IndexKey::Custom(custom_index, _) => &custom_index.program_id,
+        };
+        self.account_indexes.include_key(key)
+    }
+
     /// Scan a specific slot through all the account storage in parallel
     pub fn scan_account_storage<R, B>(
         &self,
@@ -4476,13 +4907,13 @@ impl AccountsDb {
     ) -> Result<(Hash, u64), BankHashVerificationError> {
         use BankHashVerificationError::*;
         let mut collect = Measure::start("collect");
-        let keys: Vec<_> = self
-            .accounts_index
-            .account_maps
-            .iter()
-            .map(|btree| btree.read().unwrap().keys().cloned().collect::<Vec<_>>())
-            .flatten()
-            .collect();
+        let mut keys = vec![];
+        for bin in 0..self.accounts_index.bins() {
+            self.accounts_index
+                .scan_bin(bin, |pubkey, _, _| keys.push(*pubkey));
+        }
+        // the hash depends on the order of the keys, and entries on disk are visited unordered
+        keys.sort_unstable();
         collect.stop();
 
         let mut scan = Measure::start("scan");
@@ -5506,11 +5937,41 @@ impl AccountsDb {
             .or_insert_with(BankHashInfo::default);
         slot_info.stats.merge(&stats);
 
+        // assign write versions up front so that they can be reported to the notifier
+        let first_write_version = self.bulk_assign_write_version(accounts.len());
         // we use default hashes for now since the same account may be stored to the cache multiple times
-        self.store_accounts_unfrozen(slot, accounts, None, is_cached_store);
+        self.store_accounts_unfrozen(
+            slot,
+            accounts,
+            None,
+            Some(Box::new(first_write_version..)),
+            is_cached_store,
+        );
+        // the notifier may block, so it is called without holding any locks
+        drop(bank_hashes);
+        self.notify_account_updates(slot, accounts, first_write_version);
         self.report_store_timings();
     }
 
+    fn notify_account_updates(
+        &self,
+        slot: Slot,
+        accounts: &[(&Pubkey, &AccountSharedData)],
+        first_write_version: StoredMetaWriteVersion,
+    ) {
+        let accounts_update_notifier = self.accounts_update_notifier.read().unwrap().clone();
+        if let Some(accounts_update_notifier) = accounts_update_notifier {
+            for ((pubkey, account), write_version) in accounts.iter().zip(first_write_version..) {
+                accounts_update_notifier.notify_account_update(
+                    slot,
+                    pubkey,
+                    account,
+                    write_version,
+                );
+            }
+        }
+    }
+
     fn report_store_timings(&self) {
         let last = self.stats.last_store_report.load(Ordering::Relaxed);
         let now = solana_sdk::timing::timestamp();
@@ -5637,6 +6098,7 @@ impl AccountsDb {
         slot: Slot,
         accounts: &[(&Pubkey, &AccountSharedData)],
         hashes: Option<&[&Hash]>,
+        write_version_producer: Option<Box<dyn Iterator<Item = StoredMetaWriteVersion>>>,
         is_cached_store: bool,
     ) {
         // This path comes from a store to a non-frozen slot.
@@ -5652,7 +6114,7 @@ impl AccountsDb {
             accounts,
             hashes,
             None::<StorageFinder>,
-            None::<Box<dyn Iterator<Item = u64>>>,
+            write_version_producer,
             is_cached_store,
             reset_accounts,
         );
@@ -5781,6 +6243,10 @@ impl AccountsDb {
                 self.dirty_stores.insert((slot, *store_id), store.clone());
             }
         }
+        let accounts_update_notifier = self.accounts_update_notifier.read().unwrap().clone();
+        if let Some(accounts_update_notifier) = accounts_update_notifier {
+            accounts_update_notifier.notify_slot_rooted(slot);
+        }
     }
 
     pub fn get_snapshot_storages(
@@ -5987,9 +6453,8 @@ impl AccountsDb {
                             let mut lookup_time = Measure::start("lookup_time");
                             for account in accounts_map.into_iter() {
                                 let (key, account_info) = account;
-                                let lock = self.accounts_index.get_account_maps_read_lock(&key);
-                                let x = lock.get(&key).unwrap();
-                                let sl = x.slot_list.read().unwrap();
+                                let x = self.accounts_index.get_account_read_entry(&key).unwrap();
+                                let sl = x.slot_list();
                                 let mut count = 0;
                                 for (slot2, account_info2) in sl.iter() {
                                     if slot2 == slot {
@@ -6017,12 +6482,9 @@ impl AccountsDb {
 
             let mut min_bin_size = usize::MAX;
             let mut max_bin_size = usize::MIN;
-            let total_items = self
-                .accounts_index
-                .account_maps
-                .iter()
-                .map(|map_bin| {
-                    let len = map_bin.read().unwrap().len();
+            let total_items = (0..self.accounts_index.bins())
+                .map(|bin| {
+                    let len = self.accounts_index.bin_len(bin);
                     min_bin_size = std::cmp::min(min_bin_size, len);
                     max_bin_size = std::cmp::max(max_bin_size, len);
                     len
@@ -6057,26 +6519,20 @@ impl AccountsDb {
     ) -> HashMap<usize, (usize, usize)> {
         // look at every account in the account index and calculate for each storage: stored_size and count
         let mut storage_size_accounts_map_time = Measure::start("storage_size_accounts_map");
-        let mut maps = self
-            .accounts_index
-            .account_maps
-            .par_iter()
-            .map(|bin_map| {
+        let mut maps = (0..self.accounts_index.bins())
+            .into_par_iter()
+            .map(|bin| {
                 let mut stored_sizes_and_counts = HashMap::new();
-                bin_map.read().unwrap().values().for_each(|entry| {
-                    entry
-                        .slot_list
-                        .read()
-                        .unwrap()
-                        .iter()
-                        .for_each(|(_slot, account_entry)| {
+                self.accounts_index
+                    .scan_bin(bin, |_pubkey, slot_list, _ref_count| {
+                        slot_list.iter().for_each(|(_slot, account_entry)| {
                             let storage_entry_meta = stored_sizes_and_counts
                                 .entry(account_entry.store_id)
                                 .or_insert((0, 0));
                             storage_entry_meta.0 += account_entry.stored_size;
                             storage_entry_meta.1 += 1;
                         })
-                });
+                    });
                 stored_sizes_and_counts
             })
             .collect::<Vec<_>>();
@@ -6154,15 +6610,13 @@ impl AccountsDb {
         #[allow(clippy::stable_sort_primitive)]
         roots.sort();
         info!("{}: accounts_index roots: {:?}", label, roots,);
-        self.accounts_index.account_maps.iter().for_each(|i| {
-            for (pubkey, account_entry) in i.read().unwrap().iter() {
-                info!("  key: {} ref_count: {}", pubkey, account_entry.ref_count(),);
-                info!(
-                    "      slots: {:?}",
-                    *account_entry.slot_list.read().unwrap()
-                );
-            }
-        });
+        for bin in 0..self.accounts_index.bins() {
+            self.accounts_index
+                .scan_bin(bin, |pubkey, slot_list, ref_count| {
+                    info!("  key: {} ref_count: {}", pubkey, ref_count);
+                    info!("      slots: {:?}", slot_list);
+                });
+        }
     }
 
     fn print_count_and_status(&self, label: &str) {
@@ -6381,6 +6835,7 @@ pub mod tests {
         accounts_hash::MERKLE_FANOUT,
         accounts_index::RefCount,
         accounts_index::{tests::*, AccountSecondaryIndexesIncludeExclude},
+        accounts_update_notifier_interface::AccountsUpdateNotifierInterface,
         append_vec::{test_utils::TempFile, AccountMeta},
         inline_spl_token_v2_0,
     };
@@ -6983,6 +7438,116 @@ pub mod tests {
         );
     }
 
+    #[derive(Debug, Default)]
+    struct AccountsUpdateRecorder {
+        updates: Mutex<Vec<(Slot, Pubkey, AccountSharedData, StoredMetaWriteVersion)>>,
+    }
+
+    impl AccountsUpdateNotifierInterface for AccountsUpdateRecorder {
+        fn notify_account_update(
+            &self,
+            slot: Slot,
+            pubkey: &Pubkey,
+            account: &AccountSharedData,
+            write_version: StoredMetaWriteVersion,
+        ) {
+            self.updates
+                .lock()
+                .unwrap()
+                .push((slot, *pubkey, account.clone(), write_version));
+        }
+    }
+
+    #[test]
+    fn test_accountsdb_notify_account_updates() {
+        solana_logger::setup();
+        let db = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        let key0 = Pubkey::new_unique();
+        let key1 = Pubkey::new_unique();
+        let account0 = AccountSharedData::new(1, 0, &key0);
+        let account1 = AccountSharedData::new(2, 0, &key1);
+
+        // Updates stored before a notifier is set are not reported
+        db.store_uncached(0, &[(&key0, &account0)]);
+
+        let recorder = Arc::new(AccountsUpdateRecorder::default());
+        db.set_accounts_update_notifier(recorder.clone());
+        db.store_uncached(1, &[(&key0, &account0), (&key1, &account1)]);
+
+        let updates = recorder.updates.lock().unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].0, 1);
+        assert_eq!(updates[0].1, key0);
+        assert_eq!(updates[0].2, account0);
+        assert_eq!(updates[1].1, key1);
+        assert_eq!(updates[1].2, account1);
+        assert_eq!(updates[1].3, updates[0].3 + 1);
+
+        assert_eq!(db.write_version.load(Ordering::Relaxed), updates[1].3 + 1);
+    }
+
+    #[test]
+    fn test_accountsdb_notify_account_updates_write_version_order() {
+        solana_logger::setup();
+        let db = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        let recorder = Arc::new(AccountsUpdateRecorder::default());
+        db.set_accounts_update_notifier(recorder.clone());
+        let key = Pubkey::new_unique();
+        let accounts: Vec<_> = (1..=4)
+            .map(|lamports| AccountSharedData::new(lamports, 0, &key))
+            .collect();
+
+        // Two stores to the same slot, then one to a later slot
+        db.store_uncached(1, &[(&key, &accounts[0]), (&key, &accounts[1])]);
+        db.store_uncached(1, &[(&key, &accounts[2])]);
+        db.store_uncached(2, &[(&key, &accounts[3])]);
+
+        let updates = recorder.updates.lock().unwrap();
+        assert_eq!(
+            updates
+                .iter()
+                .map(|(slot, _, account, _)| (*slot, account.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, accounts[0].clone()),
+                (1, accounts[1].clone()),
+                (1, accounts[2].clone()),
+                (2, accounts[3].clone()),
+            ]
+        );
+        // Versions are reported in the order they were stored
+        assert!(updates.windows(2).all(|pair| pair[0].3 < pair[1].3));
+
+        // ... and match the versions written to storage, the latest of which is loaded
+        let stores: Vec<_> = [1, 2]
+            .iter()
+            .flat_map(|slot| db.storage.get_slot_storage_entries(*slot).unwrap())
+            .collect();
+        let mut stored: Vec<_> = stores
+            .iter()
+            .flat_map(|store| store.all_accounts())
+            .map(|stored_account| {
+                (
+                    stored_account.meta.write_version,
+                    stored_account.account_meta.lamports,
+                )
+            })
+            .collect();
+        stored.sort_unstable();
+        assert_eq!(
+            stored,
+            updates
+                .iter()
+                .map(|(_, _, account, write_version)| (*write_version, account.lamports()))
+                .collect::<Vec<_>>()
+        );
+        let ancestors = vec![(1, 1), (2, 2)].into_iter().collect();
+        assert_eq!(
+            db.load_without_fixed_root(&ancestors, &key),
+            Some((accounts[3].clone(), 2))
+        );
+    }
+
     #[test]
     fn test_accountsdb_latest_ancestor() {
         solana_logger::setup();
@@ -7183,7 +7748,7 @@ pub mod tests {
         // overwrite old rooted account version; only the r_slot_0_stores.count() should be
         // decremented
         db.store_uncached(2, &[(&pubkeys[0], &account)]);
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         {
             let slot_0_stores = &db.storage.get_slot_stores(0).unwrap();
             let slot_1_stores = &db.storage.get_slot_stores(1).unwrap();
@@ -7622,7 +8187,7 @@ pub mod tests {
 
         //slot is gone
         accounts.print_accounts_stats("pre-clean");
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         assert!(accounts.storage.0.get(&0).is_none());
 
         //new value is there
@@ -7705,7 +8270,7 @@ pub mod tests {
 
         // Slot 1 should be removed, slot 0 cannot be removed because it still has
         // the latest update for pubkey 2
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         assert!(accounts.storage.get_slot_stores(0).is_some());
         assert!(accounts.storage.get_slot_stores(1).is_none());
 
@@ -7740,7 +8305,7 @@ pub mod tests {
         assert_eq!(accounts.accounts_index.ref_count_from_storage(&pubkey1), 3);
         assert_eq!(accounts.accounts_index.ref_count_from_storage(&pubkey2), 1);
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         // Slots 0 and 1 should each have been cleaned because all of their
         // accounts are zero lamports
         assert!(accounts.storage.get_slot_stores(0).is_none());
@@ -7754,7 +8319,7 @@ pub mod tests {
         assert_eq!(accounts.accounts_index.ref_count_from_storage(&pubkey1), 1);
         assert_eq!(accounts.accounts_index.ref_count_from_storage(&pubkey2), 0);
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         // Slot 2 will now be cleaned, which will leave account 1 with a ref count of 0
         assert!(accounts.storage.get_slot_stores(2).is_none());
         assert_eq!(accounts.accounts_index.ref_count_from_storage(&pubkey1), 0);
@@ -7781,7 +8346,7 @@ pub mod tests {
 
         // Slot 0 should be removed, and
         // zero-lamport account should be cleaned
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         assert!(accounts.storage.get_slot_stores(0).is_none());
         assert!(accounts.storage.get_slot_stores(1).is_none());
@@ -7800,6 +8365,34 @@ pub mod tests {
         assert!(accounts.accounts_index.get(&pubkey, None, None).is_none());
     }
 
+    #[test]
+    fn test_clean_zero_lamport_account_after_last_full_snapshot() {
+        solana_logger::setup();
+
+        let accounts = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let account = AccountSharedData::new(1, 0, AccountSharedData::default().owner());
+        let zero_lamport_account =
+            AccountSharedData::new(0, 0, AccountSharedData::default().owner());
+
+        accounts.store_uncached(0, &[(&pubkey, &account)]);
+        accounts.store_uncached(1, &[(&pubkey, &zero_lamport_account)]);
+        accounts.add_root(0);
+        accounts.add_root(1);
+
+        // The last full snapshot is at slot 0, so the zero-lamport update in slot 1 must be
+        // kept around for incremental snapshots
+        accounts.clean_accounts(None, false, Some(0));
+        assert_eq!(accounts.alive_account_count_in_slot(0), 0);
+        assert_eq!(accounts.alive_account_count_in_slot(1), 1);
+        assert!(accounts.accounts_index.get(&pubkey, None, None).is_some());
+
+        // Once a full snapshot covers slot 1, the zero-lamport account can be purged
+        accounts.clean_accounts(None, false, Some(1));
+        assert_eq!(accounts.alive_account_count_in_slot(1), 0);
+        assert!(accounts.accounts_index.get(&pubkey, None, None).is_none());
+    }
+
     #[test]
     fn test_clean_old_with_normal_account() {
         solana_logger::setup();
@@ -7821,7 +8414,7 @@ pub mod tests {
         assert_eq!(accounts.alive_account_count_in_slot(0), 1);
         assert_eq!(accounts.alive_account_count_in_slot(1), 1);
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         //now old state is cleaned up
         assert_eq!(accounts.alive_account_count_in_slot(0), 0);
@@ -7855,7 +8448,7 @@ pub mod tests {
 
         accounts.print_accounts_stats("");
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         //Old state behind zero-lamport account is cleaned up
         assert_eq!(accounts.alive_account_count_in_slot(0), 0);
@@ -7872,6 +8465,7 @@ pub mod tests {
             spl_token_mint_index_enabled(),
             false,
             AccountShrinkThreshold::default(),
+            None,
         );
         let pubkey1 = solana_sdk::pubkey::new_rand();
         let pubkey2 = solana_sdk::pubkey::new_rand();
@@ -7965,7 +8559,7 @@ pub mod tests {
             accounts.account_indexes.keys = None;
         }
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         //both zero lamport and normal accounts are cleaned up
         assert_eq!(accounts.alive_account_count_in_slot(0), 0);
@@ -8015,13 +8609,13 @@ pub mod tests {
         // updates in later slots in slot 1
         assert_eq!(accounts.alive_account_count_in_slot(0), 1);
         assert_eq!(accounts.alive_account_count_in_slot(1), 1);
-        accounts.clean_accounts(Some(0), false);
+        accounts.clean_accounts(Some(0), false, None);
         assert_eq!(accounts.alive_account_count_in_slot(0), 1);
         assert_eq!(accounts.alive_account_count_in_slot(1), 1);
         assert!(accounts.accounts_index.get(&pubkey, None, None).is_some());
 
         // Now the account can be cleaned up
-        accounts.clean_accounts(Some(1), false);
+        accounts.clean_accounts(Some(1), false, None);
         assert_eq!(accounts.alive_account_count_in_slot(0), 0);
         assert_eq!(accounts.alive_account_count_in_slot(1), 0);
 
@@ -8046,7 +8640,7 @@ pub mod tests {
         assert_eq!(accounts.accounts_index.uncleaned_roots_len(), 1);
 
         //now uncleaned roots are cleaned up
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         assert_eq!(accounts.accounts_index.uncleaned_roots_len(), 0);
     }
 
@@ -8063,7 +8657,7 @@ pub mod tests {
         assert_eq!(accounts.accounts_index.uncleaned_roots_len(), 1);
 
         //now uncleaned roots are cleaned up
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         assert_eq!(accounts.accounts_index.uncleaned_roots_len(), 0);
     }
 
@@ -8075,7 +8669,7 @@ pub mod tests {
 
         // Create 100 accounts in slot 0
         create_account(&accounts, &mut pubkeys, 0, 100, 0, 0);
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         check_accounts(&accounts, &pubkeys, 0, 100, 1);
 
         // do some updates to those accounts and re-check
@@ -8111,7 +8705,7 @@ pub mod tests {
 
         // Modify first 20 of the accounts from slot 0 in slot 2
         modify_accounts(&accounts, &pubkeys, latest_slot, 20, 4);
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         // Overwrite account 31 from slot 0 with lamports=0 into slot 2.
         // Slot 2 should now have 20 + 1 = 21 accounts
         let account = AccountSharedData::new(0, 0, AccountSharedData::default().owner());
@@ -8125,7 +8719,7 @@ pub mod tests {
         accounts.add_root(latest_slot);
         assert!(check_storage(&accounts, 2, 31));
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         // The first 20 accounts of slot 0 have been updated in slot 2, as well as
         // accounts 30 and  31 (overwritten with zero-lamport accounts in slot 1 and
         // slot 2 respectively), so only 78 accounts are left in slot 0's storage entries.
@@ -8261,7 +8855,7 @@ pub mod tests {
 
         accounts.print_accounts_stats("pre_purge");
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         accounts.print_accounts_stats("post_purge");
 
@@ -8326,7 +8920,7 @@ pub mod tests {
         info!("ancestors: {:?}", ancestors);
         let hash = accounts.update_accounts_hash_test(current_slot, &ancestors);
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         assert_eq!(
             accounts.update_accounts_hash_test(current_slot, &ancestors),
@@ -8393,7 +8987,7 @@ pub mod tests {
 
         accounts.print_accounts_stats("accounts");
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         accounts.print_accounts_stats("accounts_post_purge");
         let accounts = reconstruct_accounts_db_via_serialization(&accounts, current_slot);
@@ -8466,7 +9060,7 @@ pub mod tests {
     fn test_accounts_purge_chained_purge_before_snapshot_restore() {
         solana_logger::setup();
         with_chained_zero_lamport_accounts(|accounts, current_slot| {
-            accounts.clean_accounts(None, false);
+            accounts.clean_accounts(None, false, None);
             reconstruct_accounts_db_via_serialization(&accounts, current_slot)
         });
     }
@@ -8477,7 +9071,7 @@ pub mod tests {
         with_chained_zero_lamport_accounts(|accounts, current_slot| {
             let accounts = reconstruct_accounts_db_via_serialization(&accounts, current_slot);
             accounts.print_accounts_stats("after_reconstruct");
-            accounts.clean_accounts(None, false);
+            accounts.clean_accounts(None, false, None);
             reconstruct_accounts_db_via_serialization(&accounts, current_slot)
         });
     }
@@ -8886,6 +9480,7 @@ pub mod tests {
             some_slot,
             &[(&key, &account)],
             Some(&[&Hash::default()]),
+            None,
             false,
         );
         db.add_root(some_slot);
@@ -9045,7 +9640,7 @@ pub mod tests {
         }
         // provide bogus account hashes
         let some_hash = Hash::new(&[0xca; HASH_BYTES]);
-        db.store_accounts_unfrozen(some_slot, accounts, Some(&[&some_hash]), false);
+        db.store_accounts_unfrozen(some_slot, accounts, Some(&[&some_hash]), None, false);
         db.add_root(some_slot);
         assert_matches!(
             db.verify_bank_hash_and_lamports(some_slot, &ancestors, 1, true),
@@ -9228,7 +9823,7 @@ pub mod tests {
         accounts.print_count_and_status("before reconstruct");
         let accounts = reconstruct_accounts_db_via_serialization(&accounts, current_slot);
         accounts.print_count_and_status("before purge zero");
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         accounts.print_count_and_status("after purge zero");
 
         assert_load_account(&accounts, current_slot, pubkey, old_lamport);
@@ -9289,7 +9884,7 @@ pub mod tests {
 
         accounts.print_accounts_stats("Post-B pre-clean");
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         info!("post B");
         accounts.print_accounts_stats("Post-B");
@@ -9329,7 +9924,7 @@ pub mod tests {
 
         accounts.get_accounts_delta_hash(current_slot);
         accounts.add_root(current_slot);
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         accounts.print_accounts_stats("Post-D clean");
 
@@ -9419,7 +10014,7 @@ pub mod tests {
         current_slot += 1;
         assert_eq!(3, accounts.ref_count_for_pubkey(&pubkey1));
         accounts.store_uncached(current_slot, &[(&pubkey1, &zero_lamport_account)]);
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         assert_eq!(
             // Removed one reference from the dead slot (reference only counted once
@@ -9444,9 +10039,9 @@ pub mod tests {
         // If step C and step D should be purged, snapshot restore would cause
         // pubkey1 to be revived as the state of step A.
         // So, prevent that from happening by introducing refcount
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         let accounts = reconstruct_accounts_db_via_serialization(&accounts, current_slot);
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         info!("pubkey: {}", pubkey1);
         accounts.print_accounts_stats("pre_clean");
@@ -9461,10 +10056,10 @@ pub mod tests {
         accounts.add_root(current_slot);
 
         // Do clean
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         // 2nd clean needed to clean-up pubkey1
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         // Ensure pubkey2 is cleaned from the index finally
         assert_not_load_account(&accounts, current_slot, pubkey1);
@@ -9605,7 +10200,7 @@ pub mod tests {
             accounts.get_accounts_delta_hash(current_slot);
             accounts.add_root(current_slot);
 
-            accounts.clean_accounts(None, false);
+            accounts.clean_accounts(None, false, None);
 
             assert_eq!(
                 pubkey_count,
@@ -9673,7 +10268,7 @@ pub mod tests {
         }
         accounts.get_accounts_delta_hash(current_slot);
         accounts.add_root(current_slot);
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         assert_eq!(
             pubkey_count,
@@ -9818,7 +10413,7 @@ pub mod tests {
         accounts.get_accounts_delta_hash(current_slot);
         accounts.add_root(current_slot);
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         assert_eq!(
             pubkey_count,
@@ -10021,6 +10616,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             true,
             AccountShrinkThreshold::default(),
+            None,
         );
 
         let account = AccountSharedData::new(1, 16 * 4096, &Pubkey::default());
@@ -10039,7 +10635,7 @@ pub mod tests {
         accounts.flush_accounts_cache(true, None);
 
         // clear out the dirty keys
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         // flush 1
         accounts.get_accounts_delta_hash(1);
@@ -10051,11 +10647,11 @@ pub mod tests {
         // clean to remove pubkey1 from 0,
         // shrink to shrink pubkey1 from 0
         // then another clean to remove pubkey1 from slot 1
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         accounts.shrink_candidate_slots();
 
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
 
         accounts.print_accounts_stats("post-clean");
         assert_eq!(accounts.accounts_index.ref_count_from_storage(&pubkey1), 0);
@@ -10083,12 +10679,12 @@ pub mod tests {
             accounts.store_uncached(1, &[(key, &account)]);
         }
         accounts.add_root(1);
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         accounts.shrink_all_slots(false);
 
         // Clean again to flush the dirty stores
         // and allow them to be recycled in the next step
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         accounts.print_accounts_stats("post-shrink");
         let num_stores = accounts.recycle_stores.read().unwrap().entry_count();
         assert!(num_stores > 0);
@@ -10135,7 +10731,7 @@ pub mod tests {
         db.add_root(1);
 
         // Only clean zero lamport accounts up to slot 0
-        db.clean_accounts(Some(0), false);
+        db.clean_accounts(Some(0), false, None);
 
         // Should still be able to find zero lamport account in slot 1
         assert_eq!(
@@ -10177,6 +10773,88 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_compress_cold_storages() {
+        solana_logger::setup();
+        let cold_dir = TempDir::new().unwrap();
+        let db = AccountsDb::new_with_config(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            true,
+            AccountShrinkThreshold::default(),
+            Some(AccountsDbConfig {
+                cold_storage: Some(ColdStorageConfig {
+                    path: cold_dir.path().to_path_buf(),
+                    min_age_slots: 2,
+                    cache_bytes: 0,
+                }),
+                ..AccountsDbConfig::default()
+            }),
+        );
+        let owner = Pubkey::default();
+        let account = |lamports| AccountSharedData::new(lamports, 1000, &owner);
+        let keys: Vec<_> = (0..100).map(|_| Pubkey::new_unique()).collect();
+        let store_rooted = |slot: Slot, keys: &[Pubkey]| {
+            for key in keys {
+                db.store_cached(slot, &[(key, &account(slot + 1))]);
+            }
+            db.get_accounts_delta_hash(slot);
+            db.add_root(slot);
+            db.flush_accounts_cache(true, None);
+            db.clean_accounts(None, false, None);
+        };
+        let load = |key| {
+            db.load_without_fixed_root(&Ancestors::default(), key)
+                .map(|(account, _slot)| account.lamports())
+        };
+        let slot_stores = |slot| db.storage.get_slot_storage_entries(slot).unwrap();
+
+        // the account updated in slot 2 is dead in slot 0
+        store_rooted(0, &keys[..50]);
+        store_rooted(1, &keys[50..]);
+        store_rooted(2, &keys[..1]);
+        store_rooted(3, &[]);
+
+        // only slot 1 is both old enough and without dead accounts
+        assert_eq!(db.compress_cold_storages(), 1);
+        assert!(slot_stores(0)
+            .iter()
+            .all(|store| !store.accounts.is_compressed()));
+        let stores = slot_stores(1);
+        assert_eq!(stores.len(), 1);
+        assert!(stores[0].accounts.is_compressed());
+        assert_eq!(stores[0].count(), 50);
+        assert!(stores[0].get_path().starts_with(cold_dir.path()));
+        assert_eq!(load(&keys[60]), Some(2));
+        assert_eq!(db.compress_cold_storages(), 0);
+
+        // storages over the cache size are replaced to release their decompressed blocks
+        assert!(stores[0].accounts.loaded_bytes() > 0);
+        assert_eq!(db.evict_cold_storage_cache(), 1);
+        let evicted = slot_stores(1);
+        assert!(!Arc::ptr_eq(&evicted[0], &stores[0]));
+        assert_eq!(evicted[0].accounts.loaded_bytes(), 0);
+        assert_eq!(evicted[0].count(), 50);
+        drop(stores);
+        assert_eq!(db.cold_storage_loaded_bytes.load(Ordering::Relaxed), 0);
+        assert_eq!(load(&keys[60]), Some(2));
+        drop(evicted);
+
+        // shrinking a compressed storage leaves it compressed
+        store_rooted(4, &keys[50..90]);
+        assert!(db.shrink_candidate_slots() > 0);
+        let stores = slot_stores(1);
+        assert_eq!(stores.len(), 1);
+        assert!(stores[0].accounts.is_compressed());
+        assert_eq!(stores[0].count(), 10);
+        assert_eq!(load(&keys[95]), Some(2));
+        assert_eq!(load(&keys[60]), Some(5));
+        // the replaced compressed storage is dropped rather than recycled
+        db.clean_accounts(None, false, None);
+        assert_eq!(std::fs::read_dir(cold_dir.path()).unwrap().count(), 1);
+    }
+
     #[test]
     fn test_store_flush_load_cached() {
         let mut db = AccountsDb::new(Vec::new(), &ClusterType::Development);
@@ -10332,6 +11010,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         ));
 
         let account_key = Pubkey::new_unique();
@@ -10343,9 +11022,9 @@ pub mod tests {
 
         db.add_root(0);
         db.add_root(1);
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         db.flush_accounts_cache(true, None);
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         db.add_root(2);
 
         assert_eq!(db.read_only_accounts_cache.cache_len(), 0);
@@ -10380,6 +11059,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         ));
 
         let account_key = Pubkey::new_unique();
@@ -10393,7 +11073,7 @@ pub mod tests {
         db.add_root(1);
 
         // Clean should not remove anything yet as nothing has been flushed
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         let account = db
             .do_load(
                 &Ancestors::default(),
@@ -10409,7 +11089,7 @@ pub mod tests {
         // Flush, then clean again. Should not need another root to initiate the cleaning
         // because `accounts_index.uncleaned_roots` should be correct
         db.flush_accounts_cache(true, None);
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         assert!(db
             .do_load(
                 &Ancestors::default(),
@@ -10429,6 +11109,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         ));
 
         let zero_lamport_account_key = Pubkey::new_unique();
@@ -10465,7 +11146,7 @@ pub mod tests {
         // Flush, then clean. Should not need another root to initiate the cleaning
         // because `accounts_index.uncleaned_roots` should be correct
         db.flush_accounts_cache(true, None);
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
 
         // The `zero_lamport_account_key` is still alive in slot 1, so refcount for the
         // pubkey should be 2
@@ -10564,6 +11245,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         ));
         let account_key = Pubkey::new_unique();
         let account_key2 = Pubkey::new_unique();
@@ -10624,7 +11306,7 @@ pub mod tests {
 
         // Run clean, unrooted slot 1 should not be purged, and still readable from the cache,
         // because we're still doing a scan on it.
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         let account = db
             .do_load(
                 &scan_ancestors,
@@ -10638,7 +11320,7 @@ pub mod tests {
         // When the scan is over, clean should not panic and should not purge something
         // still in the cache.
         scan_tracker.exit().unwrap();
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         let account = db
             .do_load(
                 &scan_ancestors,
@@ -10671,6 +11353,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         );
         let slot: Slot = 0;
         let num_keys = 10;
@@ -10726,6 +11409,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         ));
         let slots: Vec<_> = (0..num_slots as Slot).into_iter().collect();
         let stall_slot = num_slots as Slot;
@@ -11131,6 +11815,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         );
         let account_key1 = Pubkey::new_unique();
         let account_key2 = Pubkey::new_unique();
@@ -11160,7 +11845,7 @@ pub mod tests {
         db.get_accounts_delta_hash(1);
 
         // Clean to remove outdated entry from slot 0
-        db.clean_accounts(Some(1), false);
+        db.clean_accounts(Some(1), false, None);
 
         // Shrink Slot 0
         let mut slot0_stores = db.storage.get_slot_storage_entries(0).unwrap();
@@ -11185,7 +11870,7 @@ pub mod tests {
         // Should be one store before clean for slot 0
         assert_eq!(db.storage.get_slot_storage_entries(0).unwrap().len(), 1);
         db.get_accounts_delta_hash(2);
-        db.clean_accounts(Some(2), false);
+        db.clean_accounts(Some(2), false, None);
 
         // No stores should exist for slot 0 after clean
         assert!(db.storage.get_slot_storage_entries(0).is_none());
@@ -11231,7 +11916,7 @@ pub mod tests {
         // Checking that the uncleaned_pubkeys are not pre-maturely removed
         // such that when the slots are rooted, and can actually be cleaned, then the
         // delta keys are still there.
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
 
         db.print_accounts_stats("post-clean1");
         // Check stores > 0
@@ -11246,12 +11931,12 @@ pub mod tests {
         db.store_uncached(2, &[(&account_key1, &account3)]);
         db.get_accounts_delta_hash(2);
 
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
         db.print_accounts_stats("post-clean2");
 
         // root slots 1
         db.add_root(1);
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
 
         db.print_accounts_stats("post-clean3");
 
@@ -11260,7 +11945,7 @@ pub mod tests {
         db.add_root(3);
 
         // Check that we can clean where max_root=3 and slot=2 is not rooted
-        db.clean_accounts(None, false);
+        db.clean_accounts(None, false, None);
 
         assert!(db.uncleaned_pubkeys.is_empty());
 
@@ -11388,6 +12073,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         );
         db.load_delay = RACY_SLEEP_MS;
         let db = Arc::new(db);
@@ -11460,6 +12146,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         );
         db.load_delay = RACY_SLEEP_MS;
         let db = Arc::new(db);
@@ -11536,6 +12223,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         );
         db.load_delay = RACY_SLEEP_MS;
         let db = Arc::new(db);
@@ -11608,6 +12296,7 @@ pub mod tests {
             AccountSecondaryIndexes::default(),
             caching_enabled,
             AccountShrinkThreshold::default(),
+            None,
         );
         let db = Arc::new(db);
         let num_cached_slots = 100;
@@ -12032,7 +12721,7 @@ pub mod tests {
 
         // The later rooted zero-lamport update to `shared_key` cannot be cleaned
         // because it is kept alive by the unrooted slot.
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         assert!(accounts
             .accounts_index
             .get_account_read_entry(&shared_key)
@@ -12043,7 +12732,7 @@ pub mod tests {
         accounts.purge_slot(slot0, 0, is_from_abs);
 
         // Now clean should clean up the remaining key
-        accounts.clean_accounts(None, false);
+        accounts.clean_accounts(None, false, None);
         assert!(accounts
             .accounts_index
             .get_account_read_entry(&shared_key)
@@ -1,12 +1,15 @@
 use crate::{
     ancestors::Ancestors,
+    bucket_map::{BucketMap, BucketMapConfig},
     contains::Contains,
     inline_spl_token_v2_0::{self, SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
     secondary_index::*,
 };
 use bv::BitVec;
+use dashmap::DashMap;
 use log::*;
 use ouroboros::self_referencing;
+use serde::{de::DeserializeOwned, Serialize};
 use solana_measure::measure::Measure;
 use solana_sdk::{
     clock::{BankId, Slot},
@@ -15,16 +18,19 @@ use solana_sdk::{
 use std::{
     collections::{
         btree_map::{self, BTreeMap, Entry},
-        HashSet,
+        HashSet, VecDeque,
     },
+    convert::TryInto,
     fmt::Debug,
     ops::{
         Bound,
         Bound::{Excluded, Included, Unbounded},
-        Range, RangeBounds,
+        Range, RangeBounds, RangeFull,
     },
+    path::PathBuf,
+    str::FromStr,
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
     },
 };
@@ -32,6 +38,12 @@ use thiserror::Error;
 
 pub const ITER_BATCH_SIZE: usize = 1000;
 const BINS: usize = 16;
+// With a disk-based index, iteration splits each bin's keys into windows of about this many
+// entries. Each window is one pass over the bin's entries on disk.
+const DEFAULT_DISK_ITER_WINDOW_SIZE: usize = 64 * ITER_BATCH_SIZE;
+// Rough size of an in-memory entry with a single slot, used to turn a memory limit into a
+// number of entries
+const IN_MEM_ENTRY_SIZE_ESTIMATE: usize = 200;
 pub type ScanResult<T> = Result<T, ScanError>;
 pub type SlotList<T> = Vec<(Slot, T)>;
 pub type SlotSlice<'s, T> = &'s [(Slot, T)];
@@ -56,6 +68,16 @@ impl IsCached for u64 {
     }
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct AccountsIndexConfig {
+    /// Keep no more than this many megabytes of entries in memory, evicting the rest to the
+    /// disk-based index. The index is held entirely in memory if `None`.
+    pub memory_limit_mb: Option<usize>,
+    /// Directories for the disk-based index, which are erased at startup. Defaults to a
+    /// temporary directory.
+    pub drives: Option<Vec<PathBuf>>,
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ScanError {
     #[error("Node detected it replayed bad version of slot {slot:?} with id {bank_id:?}, thus the scan on said slot was aborted")]
@@ -64,7 +86,7 @@ pub enum ScanError {
 
 enum ScanTypes<R: RangeBounds<Pubkey>> {
     Unindexed(Option<R>),
-    Indexed(IndexKey),
+    Indexed(IndexKey, Option<R>),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -72,6 +94,8 @@ pub enum IndexKey {
     ProgramId(Pubkey),
     SplTokenMint(Pubkey),
     SplTokenOwner(Pubkey),
+    /// Key into a user-defined index, as produced by `CustomAccountIndex::index_key()`
+    Custom(CustomAccountIndex, Pubkey),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -79,6 +103,78 @@ pub enum AccountIndex {
     ProgramId,
     SplTokenMint,
     SplTokenOwner,
+    Custom(CustomAccountIndex),
+}
+
+/// A user-defined secondary index over the accounts owned by `program_id`, keyed by the
+/// `length` bytes of account data starting at `offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomAccountIndex {
+    pub program_id: Pubkey,
+    pub offset: usize,
+    pub length: usize,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CustomAccountIndexError {
+    #[error("expected PROGRAM_ID:OFFSET:LENGTH")]
+    InvalidFormat,
+    #[error("invalid program id")]
+    InvalidProgramId,
+    #[error("invalid offset")]
+    InvalidOffset,
+    #[error("length must be between 1 and {}", PUBKEY_BYTES)]
+    InvalidLength,
+}
+
+impl FromStr for CustomAccountIndex {
+    type Err = CustomAccountIndexError;
+
+    /// Parse an index definition of the form `PROGRAM_ID:OFFSET:LENGTH`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.split(':');
+        let (program_id, offset, length) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(program_id), Some(offset), Some(length)) if parts.next().is_none() => {
+                (program_id, offset, length)
+            }
+            _ => return Err(CustomAccountIndexError::InvalidFormat),
+        };
+        let program_id = program_id
+            .parse::<Pubkey>()
+            .map_err(|_| CustomAccountIndexError::InvalidProgramId)?;
+        let offset = offset
+            .parse::<usize>()
+            .map_err(|_| CustomAccountIndexError::InvalidOffset)?;
+        let length = length
+            .parse::<usize>()
+            .map_err(|_| CustomAccountIndexError::InvalidLength)?;
+        if length == 0 || length > PUBKEY_BYTES {
+            return Err(CustomAccountIndexError::InvalidLength);
+        }
+        Ok(Self {
+            program_id,
+            offset,
+            length,
+        })
+    }
+}
+
+impl CustomAccountIndex {
+    /// Map the indexed bytes to a secondary index key. The bytes are zero-padded to the width
+    /// of a Pubkey; since every key in one index has the same length, this is collision free.
+    /// Returns None if `bytes` is shorter than the indexed field.
+    pub fn index_key(&self, bytes: &[u8]) -> Option<Pubkey> {
+        let indexed_bytes = bytes.get(..self.length)?;
+        let mut key = [0u8; PUBKEY_BYTES];
+        key[..self.length].copy_from_slice(indexed_bytes);
+        Some(Pubkey::new(&key))
+    }
+
+    /// The secondary index key for `account_data`, if the account is large enough to hold the
+    /// indexed field
+    pub fn index_key_for_account_data(&self, account_data: &[u8]) -> Option<Pubkey> {
+        self.index_key(account_data.get(self.offset..)?)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -100,6 +196,18 @@ impl AccountSecondaryIndexes {
     pub fn contains(&self, index: &AccountIndex) -> bool {
         self.indexes.contains(index)
     }
+    /// The user-defined indexes over accounts owned by `program_id`
+    pub fn custom_indexes_for_program<'a>(
+        &'a self,
+        program_id: &'a Pubkey,
+    ) -> impl Iterator<Item = &'a CustomAccountIndex> + 'a {
+        self.indexes.iter().filter_map(move |index| match index {
+            AccountIndex::Custom(custom_index) if custom_index.program_id == *program_id => {
+                Some(custom_index)
+            }
+            _ => None,
+        })
+    }
     pub fn include_key(&self, key: &Pubkey) -> bool {
         match &self.keys {
             Some(options) => options.exclude ^ options.keys.contains(key),
@@ -526,10 +634,15 @@ pub struct AccountsIndexRootsStats {
 }
 
 pub struct AccountsIndexIterator<'a, T> {
-    account_maps: &'a LockMapTypeSlice<T>,
+    index: &'a AccountsIndex<T>,
     start_bound: Bound<Pubkey>,
     end_bound: Bound<Pubkey>,
     is_finished: bool,
+    // With a disk-based index, which has no order to resume from, the remaining entries of the
+    // window of keys being iterated, sorted. `start_bound` is then past the window.
+    window: VecDeque<(Pubkey, AccountMapEntry<T>)>,
+    // Whether the window being iterated is the last one
+    is_last_window: bool,
 }
 
 impl<'a, T> AccountsIndexIterator<'a, T> {
@@ -551,7 +664,7 @@ impl<'a, T> AccountsIndexIterator<'a, T> {
         }
     }
 
-    pub fn new<R>(account_maps: &'a LockMapTypeSlice<T>, range: Option<R>) -> Self
+    pub fn new<R>(index: &'a AccountsIndex<T>, range: Option<R>) -> Self
     where
         R: RangeBounds<Pubkey>,
     {
@@ -564,22 +677,81 @@ impl<'a, T> AccountsIndexIterator<'a, T> {
                 .as_ref()
                 .map(|r| Self::clone_bound(r.end_bound()))
                 .unwrap_or(Unbounded),
-            account_maps,
+            index,
             is_finished: false,
+            window: VecDeque::new(),
+            is_last_window: false,
         }
     }
 }
 
-impl<'a, T: 'static + Clone> Iterator for AccountsIndexIterator<'a, T> {
+impl<'a, T: 'static + Clone + Serialize + DeserializeOwned> AccountsIndexIterator<'a, T> {
+    fn is_range_empty(&self) -> bool {
+        match (&self.start_bound, &self.end_bound) {
+            (Unbounded, _) | (_, Unbounded) => false,
+            (Included(start), Included(end)) => start > end,
+            (Included(start), Excluded(end))
+            | (Excluded(start), Included(end))
+            | (Excluded(start), Excluded(end)) => start >= end,
+        }
+    }
+
+    // Load the entries of the next window of keys into `window` and move `start_bound` past it.
+    // Returns false once no keys remain.
+    fn load_window(&mut self) -> bool {
+        if self.is_last_window || self.is_range_empty() {
+            return false;
+        }
+        let bin = self.start_bin();
+        let window_end = self.index.disk_window_end(bin, &self.start_bound);
+        let end_bound = self.end_bound;
+        let mut items = self
+            .index
+            .bin_items_in_range(bin, &(self.start_bound, window_end));
+        items.retain(|(pubkey, _)| (Unbounded, end_bound).contains(pubkey));
+        self.window = items.into();
+        match window_end {
+            Excluded(end) => self.start_bound = Included(end),
+            _ => self.is_last_window = true,
+        }
+        true
+    }
+
+    fn next_from_windows(&mut self) -> Option<Vec<(Pubkey, AccountMapEntry<T>)>> {
+        let mut chunk = Vec::with_capacity(ITER_BATCH_SIZE);
+        while chunk.len() < ITER_BATCH_SIZE {
+            match self.window.pop_front() {
+                Some(item) => chunk.push(item),
+                None => {
+                    if !self.load_window() {
+                        break;
+                    }
+                }
+            }
+        }
+        if chunk.is_empty() {
+            self.is_finished = true;
+            return None;
+        }
+        Some(chunk)
+    }
+}
+
+impl<'a, T: 'static + Clone + Serialize + DeserializeOwned> Iterator
+    for AccountsIndexIterator<'a, T>
+{
     type Item = Vec<(Pubkey, AccountMapEntry<T>)>;
     fn next(&mut self) -> Option<Self::Item> {
         if self.is_finished {
             return None;
         }
+        if self.index.disk.is_some() {
+            return self.next_from_windows();
+        }
 
         let start_bin = self.start_bin();
         let mut chunk: Vec<(Pubkey, AccountMapEntry<T>)> = Vec::with_capacity(ITER_BATCH_SIZE);
-        'outer: for i in self.account_maps.iter().skip(start_bin) {
+        'outer: for i in self.index.account_maps.iter().skip(start_bin) {
             for (pubkey, account_map_entry) in
                 i.read().unwrap().range((self.start_bound, self.end_bound))
             {
@@ -612,7 +784,6 @@ pub(crate) fn get_bin_pubkey(pubkey: &Pubkey) -> usize {
 
 type MapType<T> = AccountMap<Pubkey, AccountMapEntry<T>>;
 type LockMapType<T> = Vec<RwLock<MapType<T>>>;
-type LockMapTypeSlice<T> = [RwLock<MapType<T>>];
 type AccountMapsWriteLock<'a, T> = RwLockWriteGuard<'a, MapType<T>>;
 type AccountMapsReadLock<'a, T> = RwLockReadGuard<'a, MapType<T>>;
 
@@ -638,6 +809,9 @@ pub struct AccountsIndex<T> {
     program_id_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     spl_token_mint_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     spl_token_owner_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
+    // User-defined indexes, created on first insert. Each index is behind an Arc so scans and
+    // updates don't hold a lock on this map while using the index.
+    custom_indexes: DashMap<CustomAccountIndex, Arc<SecondaryIndex<DashMapSecondaryIndexEntry>>>,
     roots_tracker: RwLock<RootsTracker>,
     ongoing_scan_roots: RwLock<BTreeMap<Slot, u64>>,
     // Each scan has some latest slot `S` that is the tip of the fork the scan
@@ -651,6 +825,14 @@ pub struct AccountsIndex<T> {
     // on any of these slots fails. This is safe to purge once the associated Bank is dropped and
     // scanning the fork with that Bank at the tip is no longer possible.
     pub removed_bank_ids: Mutex<HashSet<BankId>>,
+    // Entries evicted from `account_maps` once a bin holds more than
+    // `max_in_mem_entries_per_bin`. An entry is either in memory or on disk, never both, and
+    // moves between the two only while its bin's write lock is held.
+    disk: Option<BucketMap<T>>,
+    max_in_mem_entries_per_bin: usize,
+    // The key after which each bin's next eviction pass starts
+    eviction_cursors: Vec<Mutex<Pubkey>>,
+    disk_iter_window_size: usize,
 }
 
 impl<T> Default for AccountsIndex<T> {
@@ -669,22 +851,233 @@ impl<T> Default for AccountsIndex<T> {
             spl_token_owner_index: SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
                 "spl_token_owner_index_stats",
             ),
+            custom_indexes: DashMap::default(),
             roots_tracker: RwLock::<RootsTracker>::default(),
             ongoing_scan_roots: RwLock::<BTreeMap<Slot, u64>>::default(),
             removed_bank_ids: Mutex::<HashSet<BankId>>::default(),
+            disk: None,
+            max_in_mem_entries_per_bin: usize::MAX,
+            eviction_cursors: (0..BINS).map(|_| Mutex::default()).collect(),
+            disk_iter_window_size: DEFAULT_DISK_ITER_WINDOW_SIZE,
         }
     }
 }
 
+impl<T: 'static + Clone + Serialize + DeserializeOwned> AccountsIndex<T> {
+    // All of `bin`'s entries in `range`, in memory and on disk, sorted by pubkey. Entries on
+    // disk are copied out rather than paged in.
+    fn bin_items_in_range<R>(&self, bin: usize, range: &R) -> Vec<(Pubkey, AccountMapEntry<T>)>
+    where
+        R: RangeBounds<Pubkey>,
+    {
+        let map = self.account_maps[bin].read().unwrap();
+        let mut items = map
+            .range((range.start_bound(), range.end_bound()))
+            .map(|(pubkey, entry)| (*pubkey, entry.clone()))
+            .collect::<Vec<_>>();
+        if let Some(disk) = &self.disk {
+            let range = Some((range.start_bound(), range.end_bound()));
+            items.extend(disk.items_in_range(bin, &range).into_iter().map(
+                |(pubkey, slot_list, ref_count)| {
+                    let entry = Arc::new(AccountMapEntryInner {
+                        ref_count: AtomicU64::new(ref_count),
+                        slot_list: RwLock::new(slot_list),
+                    });
+                    (pubkey, entry)
+                },
+            ));
+            items.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        }
+        items
+    }
+
+    // The end of the window of `bin`'s keys holding `start`. A bin's keys are split evenly by
+    // their leading 8 bytes, which are uniformly distributed, into windows expected to hold
+    // about `disk_iter_window_size` entries each.
+    fn disk_window_end(&self, bin: usize, start: &Bound<Pubkey>) -> Bound<Pubkey> {
+        let position =
+            |pubkey: &Pubkey| u64::from_be_bytes(pubkey.as_ref()[..8].try_into().unwrap()) as u128;
+        let bin_start = (bin as u128 * (1u128 << 64)) / BINS as u128;
+        let bin_end = ((bin + 1) as u128 * (1u128 << 64)) / BINS as u128;
+        let start = match start {
+            Included(start) | Excluded(start) => position(start),
+            Unbounded => bin_start,
+        };
+        let len = self.account_maps[bin].read().unwrap().len()
+            + self
+                .disk
+                .as_ref()
+                .map(|disk| disk.bucket_len(bin))
+                .unwrap_or_default();
+        let window_size = self.disk_iter_window_size;
+        let num_windows = std::cmp::max(1, (len + window_size - 1) / window_size) as u128;
+        let width = (bin_end - bin_start + num_windows - 1) / num_windows;
+        let end = std::cmp::min(
+            bin_start + ((start - bin_start) / width + 1) * width,
+            bin_end,
+        );
+        if end >= 1u128 << 64 {
+            return Unbounded;
+        }
+        let mut end_key = [0; 32];
+        end_key[..8].copy_from_slice(&(end as u64).to_be_bytes());
+        Excluded(Pubkey::new_from_array(end_key))
+    }
+}
+
 impl<
-        T: 'static + Clone + IsCached + ZeroLamport + std::marker::Sync + std::marker::Send + Debug,
+        T: 'static
+            + Clone
+            + IsCached
+            + ZeroLamport
+            + std::marker::Sync
+            + std::marker::Send
+            + Debug
+            + Serialize
+            + DeserializeOwned,
     > AccountsIndex<T>
 {
+    pub fn new(config: Option<AccountsIndexConfig>) -> Self {
+        let mut index = Self::default();
+        if let Some(config) = config {
+            if let Some(memory_limit_mb) = config.memory_limit_mb {
+                let max_in_mem_entries = memory_limit_mb * 1024 * 1024 / IN_MEM_ENTRY_SIZE_ESTIMATE;
+                index.max_in_mem_entries_per_bin = std::cmp::max(1, max_in_mem_entries / BINS);
+                index.disk = Some(BucketMap::new(BucketMapConfig {
+                    max_buckets: BINS,
+                    drives: config.drives,
+                }));
+            }
+        }
+        index
+    }
+
     fn iter<R>(&self, range: Option<R>) -> AccountsIndexIterator<T>
     where
         R: RangeBounds<Pubkey>,
     {
-        AccountsIndexIterator::new(&self.account_maps, range)
+        AccountsIndexIterator::new(self, range)
+    }
+
+    fn is_on_disk(&self, bin: usize, pubkey: &Pubkey) -> bool {
+        self.disk
+            .as_ref()
+            .map(|disk| disk.contains_key(bin, pubkey))
+            .unwrap_or_default()
+    }
+
+    // Move `pubkey`'s entry back into `map` from the disk, if it was evicted there
+    fn page_in(&self, bin: usize, pubkey: &Pubkey, map: &mut MapType<T>) {
+        if let Some(disk) = &self.disk {
+            if !map.contains_key(pubkey) {
+                if let Some((slot_list, ref_count)) = disk.take(bin, pubkey) {
+                    map.insert(
+                        *pubkey,
+                        Arc::new(AccountMapEntryInner {
+                            ref_count: AtomicU64::new(ref_count),
+                            slot_list: RwLock::new(slot_list),
+                        }),
+                    );
+                    self.evict_if_over_limit(bin, map, Some(pubkey));
+                }
+            }
+        }
+    }
+
+    /// Move entries of every bin over the in-memory limit to the disk. Entries that were in use
+    /// when their bin last went over the limit are evicted once they have been released.
+    pub fn evict_over_limit(&self) {
+        if self.disk.is_none() {
+            return;
+        }
+        for (bin, map) in self.account_maps.iter().enumerate() {
+            self.evict_if_over_limit(bin, &mut map.write().unwrap(), None);
+        }
+    }
+
+    // Move entries of `bin` other than `keep` to the disk until no more than
+    // `max_in_mem_entries_per_bin` remain in memory, less some slack so that eviction isn't
+    // repeated on every insert. Bins are swept round-robin, skipping entries with an empty slot
+    // list, which are about to be removed, and entries that are in use (cloned out of the map),
+    // since updates through the clone would be lost. The bin stays over the limit until those
+    // are evicted by a later call, or by `evict_over_limit()`.
+    fn evict_if_over_limit(&self, bin: usize, map: &mut MapType<T>, keep: Option<&Pubkey>) {
+        let disk = match &self.disk {
+            Some(disk) => disk,
+            None => return,
+        };
+        if map.len() <= self.max_in_mem_entries_per_bin {
+            return;
+        }
+        let target = self.max_in_mem_entries_per_bin - self.max_in_mem_entries_per_bin / 10;
+        let mut cursor = self.eviction_cursors[bin].lock().unwrap();
+        let evict = map
+            .range((Excluded(*cursor), Unbounded))
+            .chain(map.range(..=*cursor))
+            .filter(|(pubkey, entry)| {
+                keep != Some(*pubkey)
+                    && Arc::strong_count(entry) == 1
+                    && !entry.slot_list.read().unwrap().is_empty()
+            })
+            .map(|(pubkey, _)| *pubkey)
+            .take(map.len() - target)
+            .collect::<Vec<_>>();
+        for pubkey in &evict {
+            let entry = map.remove(pubkey).unwrap();
+            let slot_list = entry.slot_list.read().unwrap();
+            disk.insert(bin, pubkey, (&slot_list[..], entry.ref_count()));
+        }
+        if let Some(last) = evict.last() {
+            *cursor = *last;
+        }
+    }
+
+    // Clone `pubkey`'s entry out of `account_maps`, paging it in from the disk if necessary
+    fn get_account_map_entry(&self, pubkey: &Pubkey) -> Option<AccountMapEntry<T>> {
+        let bin = get_bin_pubkey(pubkey);
+        {
+            let map = self.account_maps[bin].read().unwrap();
+            if let Some(entry) = map.get(pubkey) {
+                return Some(entry.clone());
+            }
+            if !self.is_on_disk(bin, pubkey) {
+                return None;
+            }
+        }
+        let mut map = self.account_maps[bin].write().unwrap();
+        self.page_in(bin, pubkey, &mut map);
+        map.get(pubkey).cloned()
+    }
+
+    pub(crate) fn bins(&self) -> usize {
+        self.account_maps.len()
+    }
+
+    /// Number of entries in `bin`, in memory and on disk
+    pub(crate) fn bin_len(&self, bin: usize) -> usize {
+        let map = self.account_maps[bin].read().unwrap();
+        map.len()
+            + self
+                .disk
+                .as_ref()
+                .map(|disk| disk.bucket_len(bin))
+                .unwrap_or_default()
+    }
+
+    /// Call `func` with every entry in `bin`, in memory and on disk, in no particular order
+    pub(crate) fn scan_bin<F>(&self, bin: usize, mut func: F)
+    where
+        F: FnMut(&Pubkey, &SlotList<T>, RefCount),
+    {
+        let map = self.account_maps[bin].read().unwrap();
+        for (pubkey, entry) in map.iter() {
+            func(pubkey, &entry.slot_list.read().unwrap(), entry.ref_count());
+        }
+        if let Some(disk) = &self.disk {
+            for (pubkey, slot_list, ref_count) in disk.items_in_range(bin, &None::<RangeFull>) {
+                func(&pubkey, &slot_list, ref_count);
+            }
+        }
     }
 
     fn do_checked_scan_accounts<F, R>(
@@ -694,6 +1087,7 @@ impl<
         scan_bank_id: BankId,
         func: F,
         scan_type: ScanTypes<R>,
+        abort_scan: Option<&AtomicBool>,
     ) -> Result<(), ScanError>
     where
         F: FnMut(&Pubkey, (&T, Slot)),
@@ -846,35 +1240,66 @@ impl<
         match scan_type {
             ScanTypes::Unindexed(range) => {
                 // Pass "" not to log metrics, so RPC doesn't get spammy
-                self.do_scan_accounts(metric_name, ancestors, func, range, Some(max_root));
+                self.do_scan_accounts(
+                    metric_name,
+                    ancestors,
+                    func,
+                    range,
+                    Some(max_root),
+                    abort_scan,
+                );
             }
-            ScanTypes::Indexed(IndexKey::ProgramId(program_id)) => {
+            ScanTypes::Indexed(IndexKey::ProgramId(program_id), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.program_id_index,
                     &program_id,
+                    range,
                     Some(max_root),
+                    abort_scan,
                 );
             }
-            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key)) => {
+            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.spl_token_mint_index,
                     &mint_key,
+                    range,
                     Some(max_root),
+                    abort_scan,
                 );
             }
-            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key)) => {
+            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.spl_token_owner_index,
                     &owner_key,
+                    range,
                     Some(max_root),
+                    abort_scan,
                 );
             }
+            ScanTypes::Indexed(IndexKey::Custom(custom_index, index_key), range) => {
+                // An index that was never inserted into has no entries to scan
+                let secondary_index = self
+                    .custom_indexes
+                    .get(&custom_index)
+                    .map(|secondary_index| secondary_index.value().clone());
+                if let Some(secondary_index) = secondary_index {
+                    self.do_scan_secondary_index(
+                        ancestors,
+                        func,
+                        &secondary_index,
+                        &index_key,
+                        range,
+                        Some(max_root),
+                        abort_scan,
+                    );
+                }
+            }
         }
 
         {
@@ -914,12 +1339,13 @@ impl<
         F: FnMut(&Pubkey, (&T, Slot)),
         R: RangeBounds<Pubkey>,
     {
-        self.do_scan_accounts(metric_name, ancestors, func, range, None);
+        self.do_scan_accounts(metric_name, ancestors, func, range, None, None);
     }
 
     // Scan accounts and return latest version of each account that is either:
     // 1) rooted or
     // 2) present in ancestors
+    // The scan stops early, between pubkeys, once `abort_scan` is set.
     fn do_scan_accounts<F, R>(
         &self,
         metric_name: &'static str,
@@ -927,6 +1353,7 @@ impl<
         mut func: F,
         range: Option<R>,
         max_root: Option<Slot>,
+        abort_scan: Option<&AtomicBool>,
     ) where
         F: FnMut(&Pubkey, (&T, Slot)),
         R: RangeBounds<Pubkey>,
@@ -939,11 +1366,16 @@ impl<
         let mut load_account_elapsed = 0;
         let mut read_lock_elapsed = 0;
         let mut iterator_elapsed = 0;
+        let is_aborted =
+            || abort_scan.map_or(false, |abort_scan| abort_scan.load(Ordering::Relaxed));
         let mut iterator_timer = Measure::start("iterator_elapsed");
-        for pubkey_list in self.iter(range) {
+        'outer: for pubkey_list in self.iter(range) {
             iterator_timer.stop();
             iterator_elapsed += iterator_timer.as_us();
             for (pubkey, list) in pubkey_list {
+                if is_aborted() {
+                    break 'outer;
+                }
                 num_keys_iterated += 1;
                 let mut read_lock_timer = Measure::start("read_lock");
                 let list_r = &list.slot_list.read().unwrap();
@@ -976,8 +1408,13 @@ impl<
         }
     }
 
+    // Scan the accounts under `index_key` in a secondary index. Only the accounts within `range`
+    // are scanned, and they are visited in pubkey order, so that a range scan can be cut short by
+    // `abort_scan` between pubkeys.
+    #[allow(clippy::too_many_arguments)]
     fn do_scan_secondary_index<
         F,
+        R,
         SecondaryIndexEntryType: SecondaryIndexEntry + Default + Sync + Send,
     >(
         &self,
@@ -985,11 +1422,22 @@ impl<
         mut func: F,
         index: &SecondaryIndex<SecondaryIndexEntryType>,
         index_key: &Pubkey,
+        range: Option<R>,
         max_root: Option<Slot>,
+        abort_scan: Option<&AtomicBool>,
     ) where
         F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey>,
     {
-        for pubkey in index.get(index_key) {
+        let mut pubkeys = index.get(index_key);
+        if let Some(range) = range {
+            pubkeys.retain(|pubkey| range.contains(pubkey));
+            pubkeys.sort_unstable();
+        }
+        for pubkey in pubkeys {
+            if abort_scan.map_or(false, |abort_scan| abort_scan.load(Ordering::Relaxed)) {
+                break;
+            }
             // Maybe these reads from the AccountsIndex can be batched every time it
             // grabs the read lock as well...
             if let AccountIndexGetResult::Found(list_r, index) =
@@ -1004,11 +1452,13 @@ impl<
     }
 
     pub fn get_account_read_entry(&self, pubkey: &Pubkey) -> Option<ReadAccountMapEntry<T>> {
-        let lock = self.get_account_maps_read_lock(pubkey);
-        self.get_account_read_entry_with_lock(pubkey, &lock)
+        self.get_account_map_entry(pubkey)
+            .map(ReadAccountMapEntry::from_account_map_entry)
     }
 
-    pub fn get_account_read_entry_with_lock(
+    // Only finds entries in memory, so only for indexes without a disk
+    #[cfg(test)]
+    fn get_account_read_entry_with_lock(
         &self,
         pubkey: &Pubkey,
         lock: &AccountMapsReadLock<'_, T>,
@@ -1019,11 +1469,7 @@ impl<
     }
 
     fn get_account_write_entry(&self, pubkey: &Pubkey) -> Option<WriteAccountMapEntry<T>> {
-        self.account_maps[get_bin_pubkey(pubkey)]
-            .read()
-            .unwrap()
-            .get(pubkey)
-            .cloned()
+        self.get_account_map_entry(pubkey)
             .map(WriteAccountMapEntry::from_account_map_entry)
     }
 
@@ -1055,6 +1501,8 @@ impl<
         w_account_maps: &mut AccountMapsWriteLock<T>,
         new_entry: AccountMapEntry<T>,
     ) -> Option<(WriteAccountMapEntry<T>, T, Pubkey)> {
+        let bin = get_bin_pubkey(&pubkey);
+        self.page_in(bin, &pubkey, w_account_maps);
         let account_entry = w_account_maps.entry(pubkey);
         match account_entry {
             Entry::Occupied(account_entry) => Some((
@@ -1065,6 +1513,7 @@ impl<
             )),
             Entry::Vacant(account_entry) => {
                 account_entry.insert(new_entry);
+                self.evict_if_over_limit(bin, w_account_maps, Some(&pubkey));
                 None
             }
         }
@@ -1121,6 +1570,34 @@ impl<
             scan_bank_id,
             func,
             ScanTypes::Unindexed(None::<Range<Pubkey>>),
+            None,
+        )
+    }
+
+    /// call func with every pubkey and index visible from a given set of ancestors within
+    /// `range`, in pubkey order. Unlike `range_scan_accounts()`, this is safe to call from
+    /// outside of consensus processing, e.g. RPC. Setting `abort_scan` stops the scan before
+    /// the next pubkey is visited.
+    pub(crate) fn checked_range_scan_accounts<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        scan_bank_id: BankId,
+        range: R,
+        func: F,
+        abort_scan: &AtomicBool,
+    ) -> Result<(), ScanError>
+    where
+        F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey>,
+    {
+        // Pass "" not to log metrics, so RPC doesn't get spammy
+        self.do_checked_scan_accounts(
+            "",
+            ancestors,
+            scan_bank_id,
+            func,
+            ScanTypes::Unindexed(Some(range)),
+            Some(abort_scan),
         )
     }
 
@@ -1167,7 +1644,34 @@ impl<
             ancestors,
             scan_bank_id,
             func,
-            ScanTypes::<Range<Pubkey>>::Indexed(index_key),
+            ScanTypes::<Range<Pubkey>>::Indexed(index_key, None),
+            None,
+        )
+    }
+
+    /// call func with the pubkeys under `index_key` within `range`, in order, and their index
+    /// visible from a given set of ancestors, stopping early once `abort_scan` is set
+    pub(crate) fn checked_index_range_scan_accounts<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        scan_bank_id: BankId,
+        index_key: IndexKey,
+        range: R,
+        func: F,
+        abort_scan: &AtomicBool,
+    ) -> Result<(), ScanError>
+    where
+        F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey>,
+    {
+        // Pass "" not to log metrics, so RPC doesn't get spammy
+        self.do_checked_scan_accounts(
+            "",
+            ancestors,
+            scan_bank_id,
+            func,
+            ScanTypes::Indexed(index_key, Some(range)),
+            Some(abort_scan),
         )
     }
 
@@ -1278,7 +1782,15 @@ impl<
         ancestors: Option<&Ancestors>,
         max_root: Option<Slot>,
     ) -> AccountIndexGetResult<'_, T> {
-        let read_lock = self.account_maps[get_bin_pubkey(pubkey)].read().unwrap();
+        let bin = get_bin_pubkey(pubkey);
+        let read_lock = loop {
+            let read_lock = self.account_maps[bin].read().unwrap();
+            if read_lock.contains_key(pubkey) || !self.is_on_disk(bin, pubkey) {
+                break read_lock;
+            }
+            drop(read_lock);
+            self.page_in(bin, pubkey, &mut self.account_maps[bin].write().unwrap());
+        };
         let account = read_lock
             .get(pubkey)
             .cloned()
@@ -1370,12 +1882,29 @@ impl<
                 }
             }
         }
+
+        // The same zero-lamport reasoning as above applies to the user-defined indexes, whose
+        // key is only computed when the account data is long enough to hold the indexed field.
+        if account_indexes.include_key(account_owner) {
+            for custom_index in account_indexes.custom_indexes_for_program(account_owner) {
+                if let Some(index_key) = custom_index.index_key_for_account_data(account_data) {
+                    let secondary_index = self
+                        .custom_indexes
+                        .entry(*custom_index)
+                        .or_insert_with(|| Arc::new(SecondaryIndex::new("custom_index_stats")))
+                        .value()
+                        .clone();
+                    secondary_index.insert(&index_key, pubkey);
+                }
+            }
+        }
     }
 
     fn get_account_maps_write_lock(&self, pubkey: &Pubkey) -> AccountMapsWriteLock<T> {
         self.account_maps[get_bin_pubkey(pubkey)].write().unwrap()
     }
 
+    #[cfg(test)]
     pub(crate) fn get_account_maps_read_lock(&self, pubkey: &Pubkey) -> AccountMapsReadLock<T> {
         self.account_maps[get_bin_pubkey(pubkey)].read().unwrap()
     }
@@ -1509,6 +2038,15 @@ impl<
         if account_indexes.contains(&AccountIndex::SplTokenMint) {
             self.spl_token_mint_index.remove_by_inner_key(inner_key);
         }
+
+        let custom_indexes: Vec<_> = self
+            .custom_indexes
+            .iter()
+            .map(|secondary_index| secondary_index.value().clone())
+            .collect();
+        for secondary_index in custom_indexes {
+            secondary_index.remove_by_inner_key(inner_key);
+        }
     }
 
     fn purge_older_root_entries(
@@ -2656,7 +3194,9 @@ pub mod tests {
             + IsCached
             + ZeroLamport
             + std::cmp::PartialEq
-            + std::fmt::Debug,
+            + std::fmt::Debug
+            + Serialize
+            + DeserializeOwned,
     >(
         account_infos: [T; 2],
         is_cached: bool,
@@ -3015,6 +3555,176 @@ pub mod tests {
         assert!(iter.next().is_none());
     }
 
+    #[test]
+    fn test_disk_index() {
+        // a limit of zero keeps a single entry per bin in memory
+        let index = AccountsIndex::<bool>::new(Some(AccountsIndexConfig {
+            memory_limit_mb: Some(0),
+            drives: None,
+        }));
+        let slot = 0;
+        let mut pubkeys: Vec<_> = std::iter::repeat_with(solana_sdk::pubkey::new_rand)
+            .take(3 * ITER_BATCH_SIZE)
+            .collect();
+        for pubkey in &pubkeys {
+            index.upsert(
+                slot,
+                pubkey,
+                &Pubkey::default(),
+                &[],
+                &AccountSecondaryIndexes::default(),
+                true,
+                &mut vec![],
+            );
+        }
+        index.add_root(slot, false);
+        let in_mem: usize = index
+            .account_maps
+            .iter()
+            .map(|map| map.read().unwrap().len())
+            .sum();
+        assert!(in_mem <= BINS);
+        let total: usize = (0..index.bins()).map(|bin| index.bin_len(bin)).sum();
+        assert_eq!(total, pubkeys.len());
+
+        // evicted entries are paged back in to be updated, and keep the update when evicted again
+        let mut gc = vec![];
+        for pubkey in &pubkeys {
+            index.upsert(
+                slot + 1,
+                pubkey,
+                &Pubkey::default(),
+                &[],
+                &AccountSecondaryIndexes::default(),
+                false,
+                &mut gc,
+            );
+        }
+        assert!(gc.is_empty());
+        let ancestors = vec![(slot + 1, 0)].into_iter().collect();
+        for pubkey in &pubkeys {
+            let (list, idx) = index.get(pubkey, Some(&ancestors), None).unwrap();
+            assert_eq!(list.slot_list()[idx], (slot + 1, false));
+            assert_eq!(list.ref_count().load(Ordering::Relaxed), 2);
+        }
+        assert_eq!(index.ref_count_from_storage(&pubkeys[0]), 2);
+
+        // scans see entries on disk, in order
+        pubkeys.sort();
+        let mut scanned = vec![];
+        index.unchecked_scan_accounts("", &ancestors, |pubkey, _| scanned.push(*pubkey));
+        assert_eq!(scanned, pubkeys);
+        let mut scanned = vec![];
+        index.range_scan_accounts("", &ancestors, pubkeys[10]..pubkeys[2000], |pubkey, _| {
+            scanned.push(*pubkey)
+        });
+        assert_eq!(scanned, pubkeys[10..2000].to_vec());
+
+        let mut reclaims = vec![];
+        let slots = vec![slot].into_iter().collect::<HashSet<Slot>>();
+        assert!(!index.purge_exact(&pubkeys[0], &slots, &mut reclaims));
+        assert_eq!(reclaims, vec![(slot, true)]);
+        let (list, idx) = index.get(&pubkeys[0], Some(&ancestors), None).unwrap();
+        assert_eq!(list.slot_list().to_vec(), vec![(slot + 1, false)]);
+        assert_eq!(idx, 0);
+    }
+
+    #[test]
+    fn test_disk_index_iter_windows() {
+        let mut index = AccountsIndex::<bool>::new(Some(AccountsIndexConfig {
+            memory_limit_mb: Some(0),
+            drives: None,
+        }));
+        index.disk_iter_window_size = 50;
+        let mut pubkeys: Vec<_> = std::iter::repeat_with(solana_sdk::pubkey::new_rand)
+            .take(3 * ITER_BATCH_SIZE)
+            .collect();
+        for pubkey in &pubkeys {
+            index.upsert(
+                0,
+                pubkey,
+                &Pubkey::default(),
+                &[],
+                &AccountSecondaryIndexes::default(),
+                true,
+                &mut vec![],
+            );
+        }
+        pubkeys.sort();
+
+        // a bin's first window ends within the bin, and the last bin's last window is unbounded
+        assert!(matches!(
+            index.disk_window_end(0, &Unbounded),
+            Excluded(end) if get_bin_pubkey(&end) == 0
+        ));
+        assert_eq!(
+            index.disk_window_end(BINS - 1, &Included(Pubkey::new_from_array([0xff; 32]))),
+            Unbounded
+        );
+
+        // iteration holds no more than about a window of entries at a time
+        let mut iter = index.iter(None::<RangeFull>);
+        let mut scanned = vec![];
+        while let Some(chunk) = iter.next() {
+            assert!(chunk.len() <= ITER_BATCH_SIZE);
+            assert!(iter.window.len() <= 4 * index.disk_iter_window_size);
+            scanned.extend(chunk.into_iter().map(|(pubkey, _)| pubkey));
+        }
+        assert_eq!(scanned, pubkeys);
+
+        let mut scanned = vec![];
+        for chunk in index.iter(Some(pubkeys[10]..=pubkeys[2000])) {
+            scanned.extend(chunk.into_iter().map(|(pubkey, _)| pubkey));
+        }
+        assert_eq!(scanned, pubkeys[10..=2000].to_vec());
+    }
+
+    #[test]
+    fn test_disk_index_evicts_released_entries() {
+        // a limit of zero keeps a single entry per bin in memory
+        let index = AccountsIndex::<bool>::new(Some(AccountsIndexConfig {
+            memory_limit_mb: Some(0),
+            drives: None,
+        }));
+        let in_mem = || -> usize {
+            index
+                .account_maps
+                .iter()
+                .map(|map| map.read().unwrap().len())
+                .sum()
+        };
+        let pubkeys: Vec<_> = std::iter::repeat_with(solana_sdk::pubkey::new_rand)
+            .take(1000)
+            .collect();
+
+        // entries in use can't be evicted
+        let mut held = vec![];
+        for pubkey in &pubkeys {
+            index.upsert(
+                0,
+                pubkey,
+                &Pubkey::default(),
+                &[],
+                &AccountSecondaryIndexes::default(),
+                true,
+                &mut vec![],
+            );
+            held.push(index.get_account_read_entry(pubkey).unwrap());
+        }
+        assert_eq!(in_mem(), pubkeys.len());
+
+        // once released, they are evicted
+        drop(held);
+        index.evict_over_limit();
+        assert!(in_mem() <= BINS);
+        let total: usize = (0..index.bins()).map(|bin| index.bin_len(bin)).sum();
+        assert_eq!(total, pubkeys.len());
+        for pubkey in &pubkeys {
+            let entry = index.get_account_read_entry(pubkey).unwrap();
+            assert_eq!(entry.slot_list().to_vec(), vec![(0, true)]);
+        }
+    }
+
     #[test]
     fn test_is_root() {
         let index = AccountsIndex::<bool>::default();
@@ -3671,6 +4381,124 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_custom_account_index_from_str() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            format!("{}:8:32", program_id).parse::<CustomAccountIndex>(),
+            Ok(CustomAccountIndex {
+                program_id,
+                offset: 8,
+                length: 32,
+            })
+        );
+        assert_eq!(
+            format!("{}:8", program_id).parse::<CustomAccountIndex>(),
+            Err(CustomAccountIndexError::InvalidFormat)
+        );
+        assert_eq!(
+            format!("{}:8:4:1", program_id).parse::<CustomAccountIndex>(),
+            Err(CustomAccountIndexError::InvalidFormat)
+        );
+        assert_eq!(
+            "not-a-pubkey:8:4".parse::<CustomAccountIndex>(),
+            Err(CustomAccountIndexError::InvalidProgramId)
+        );
+        assert_eq!(
+            format!("{}:-1:4", program_id).parse::<CustomAccountIndex>(),
+            Err(CustomAccountIndexError::InvalidOffset)
+        );
+        assert_eq!(
+            format!("{}:8:0", program_id).parse::<CustomAccountIndex>(),
+            Err(CustomAccountIndexError::InvalidLength)
+        );
+        assert_eq!(
+            format!("{}:8:33", program_id).parse::<CustomAccountIndex>(),
+            Err(CustomAccountIndexError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_custom_secondary_index() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex {
+            program_id,
+            offset: 4,
+            length: 8,
+        };
+        let secondary_indexes = AccountSecondaryIndexes {
+            indexes: [AccountIndex::Custom(custom_index)]
+                .iter()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            keys: None,
+        };
+        let index = AccountsIndex::<bool>::default();
+        let account_key = Pubkey::new_unique();
+        let mut account_data = vec![0; 16];
+        account_data[4..12].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let index_key = custom_index.index_key(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        assert_eq!(
+            custom_index.index_key_for_account_data(&account_data),
+            Some(index_key)
+        );
+
+        // Accounts owned by other programs, or too short to hold the field, are not indexed
+        index.update_secondary_indexes(
+            &account_key,
+            &Pubkey::new_unique(),
+            &account_data,
+            &secondary_indexes,
+        );
+        index.update_secondary_indexes(
+            &account_key,
+            &program_id,
+            &account_data[..11],
+            &secondary_indexes,
+        );
+        assert!(index.custom_indexes.is_empty());
+
+        index.upsert(
+            0,
+            &account_key,
+            &program_id,
+            &account_data,
+            &secondary_indexes,
+            true,
+            &mut vec![],
+        );
+        {
+            let secondary_index = index.custom_indexes.get(&custom_index).unwrap();
+            check_secondary_index_mapping_correct(
+                secondary_index.value().as_ref(),
+                &[index_key],
+                &account_key,
+            );
+        }
+
+        let mut found = vec![];
+        let ancestors: Ancestors = vec![(0, 0)].into_iter().collect();
+        index
+            .index_scan_accounts(
+                &ancestors,
+                0,
+                IndexKey::Custom(custom_index, index_key),
+                |pubkey, _| found.push(*pubkey),
+            )
+            .unwrap();
+        assert_eq!(found, vec![account_key]);
+
+        // Everything should be deleted
+        index
+            .get_account_write_entry(&account_key)
+            .unwrap()
+            .slot_list_mut(|slot_list| slot_list.clear());
+        index.handle_dead_keys(&[&account_key], &secondary_indexes);
+        let secondary_index = index.custom_indexes.get(&custom_index).unwrap();
+        assert!(secondary_index.index.is_empty());
+        assert!(secondary_index.reverse_index.is_empty());
+    }
+
     fn run_test_secondary_indexes_same_slot_and_forks<
         SecondaryIndexEntryType: SecondaryIndexEntry + Default + Sync + Send,
     >(
@@ -3800,29 +4628,23 @@ pub mod tests {
     #[test]
     fn test_start_bin() {
         let index = AccountsIndex::<bool>::default();
-        let iter = AccountsIndexIterator::new(&index.account_maps, None::<RangeInclusive<Pubkey>>);
+        let iter = AccountsIndexIterator::new(&index, None::<RangeInclusive<Pubkey>>);
         assert_eq!(iter.start_bin(), 0); // no range, so 0
 
         let key = Pubkey::new(&[0; 32]);
-        let iter =
-            AccountsIndexIterator::new(&index.account_maps, Some(RangeInclusive::new(key, key)));
+        let iter = AccountsIndexIterator::new(&index, Some(RangeInclusive::new(key, key)));
         assert_eq!(iter.start_bin(), 0); // start at pubkey 0, so 0
-        let iter =
-            AccountsIndexIterator::new(&index.account_maps, Some((Included(key), Excluded(key))));
+        let iter = AccountsIndexIterator::new(&index, Some((Included(key), Excluded(key))));
         assert_eq!(iter.start_bin(), 0); // start at pubkey 0, so 0
-        let iter =
-            AccountsIndexIterator::new(&index.account_maps, Some((Excluded(key), Excluded(key))));
+        let iter = AccountsIndexIterator::new(&index, Some((Excluded(key), Excluded(key))));
         assert_eq!(iter.start_bin(), 0); // start at pubkey 0, so 0
 
         let key = Pubkey::new(&[0xff; 32]);
-        let iter =
-            AccountsIndexIterator::new(&index.account_maps, Some(RangeInclusive::new(key, key)));
+        let iter = AccountsIndexIterator::new(&index, Some(RangeInclusive::new(key, key)));
         assert_eq!(iter.start_bin(), BINS - 1); // start at highest possible pubkey, so BINS - 1
-        let iter =
-            AccountsIndexIterator::new(&index.account_maps, Some((Included(key), Excluded(key))));
+        let iter = AccountsIndexIterator::new(&index, Some((Included(key), Excluded(key))));
         assert_eq!(iter.start_bin(), BINS - 1); // start at highest possible pubkey, so BINS - 1
-        let iter =
-            AccountsIndexIterator::new(&index.account_maps, Some((Excluded(key), Excluded(key))));
+        let iter = AccountsIndexIterator::new(&index, Some((Excluded(key), Excluded(key))));
         assert_eq!(iter.start_bin(), BINS - 1); // start at highest possible pubkey, so BINS - 1
     }
 }
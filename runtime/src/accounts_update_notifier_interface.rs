@@ -0,0 +1,21 @@
+use {
+    crate::append_vec::StoredMetaWriteVersion,
+    solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey},
+    std::{fmt::Debug, sync::Arc},
+};
+
+pub trait AccountsUpdateNotifierInterface: Debug {
+    /// Notified when an account is updated at runtime, due to transaction activities
+    fn notify_account_update(
+        &self,
+        slot: Slot,
+        pubkey: &Pubkey,
+        account: &AccountSharedData,
+        write_version: StoredMetaWriteVersion,
+    );
+
+    /// Notified when a slot is rooted, after every update to accounts in that slot
+    fn notify_slot_rooted(&self, _slot: Slot) {}
+}
+
+pub type AccountsUpdateNotifier = Arc<dyn AccountsUpdateNotifierInterface + Sync + Send>;
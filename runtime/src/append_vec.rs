@@ -10,18 +10,20 @@ use serde::{Deserialize, Serialize};
 use solana_sdk::{
     account::{Account, AccountSharedData, ReadableAccount},
     clock::{Epoch, Slot},
-    hash::Hash,
+    hash::{hash, Hash},
     pubkey::Pubkey,
+    timing::timestamp,
 };
 use std::{
     borrow::Borrow,
-    fs::{remove_file, OpenOptions},
+    convert::TryInto,
+    fs::{remove_file, File, OpenOptions},
     io,
-    io::{Seek, SeekFrom, Write},
+    io::{BufWriter, Seek, SeekFrom, Write},
     mem,
     path::{Path, PathBuf},
-    sync::atomic::{AtomicUsize, Ordering},
-    sync::Mutex,
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    sync::{Arc, Mutex},
 };
 
 // Data placement should be aligned at the next boundary. Without alignment accessing the memory may
@@ -35,6 +37,14 @@ macro_rules! u64_align {
 
 const MAXIMUM_APPEND_VEC_FILE_SIZE: usize = 16 * 1024 * 1024 * 1024; // 16 GiB
 
+// Compressed AppendVecs are split into blocks of this many uncompressed bytes, so that reading an
+// account only requires decompressing the blocks holding it.
+const COMPRESSED_BLOCK_SIZE: usize = 64 * 1024;
+const COMPRESSED_MAGIC: &[u8; 8] = b"SOLAVZ02";
+// magic, uncompressed length and number of blocks
+const COMPRESSED_HEADER_LEN: usize = COMPRESSED_MAGIC.len() + 2 * mem::size_of::<u64>();
+const COMPRESSION_LEVEL: i32 = 3;
+
 pub type StoredMetaWriteVersion = u64;
 
 /// Meta contains enough context to recover the index from storage itself
@@ -155,11 +165,191 @@ pub struct AppendVec {
 
     /// True if the file should automatically be deleted when this AppendVec is dropped.
     remove_on_drop: bool,
+
+    /// The compressed contents of `map`, if the file is a compressed AppendVec. `map` is then an
+    /// anonymous map into which blocks are decompressed as they are first read.
+    compressed: Option<CompressedBlocks>,
+}
+
+/// A compressed AppendVec file, shared by the AppendVecs reading it and removed when the last of
+/// them is dropped.
+///
+/// The file holds the magic bytes, the uncompressed length and the number of blocks, each as
+/// little-endian `u64`, followed by the file offset of each block plus the end of the last one,
+/// the hash of each compressed block, and then the zstd-compressed blocks themselves.
+#[derive(Debug, AbiExample)]
+struct CompressedFile {
+    path: PathBuf,
+
+    /// The mapped file
+    map: MmapMut,
+
+    /// The uncompressed length
+    len: usize,
+
+    /// Offsets of the blocks in `map`, followed by the end of the last block
+    block_offsets: Vec<usize>,
+}
+
+impl Drop for CompressedFile {
+    fn drop(&mut self) {
+        let _ignored = remove_file(&self.path);
+    }
+}
+
+impl CompressedFile {
+    /// Map the compressed file at `path`, checking every block against its hash.
+    fn open(path: &Path) -> io::Result<Self> {
+        let data = OpenOptions::new().read(true).write(true).open(path)?;
+        let map = unsafe { MmapMut::map_mut(&data)? };
+
+        let invalid = |reason: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), reason),
+            )
+        };
+        if !map.starts_with(COMPRESSED_MAGIC) {
+            return Err(invalid("not a compressed AppendVec"));
+        }
+        let read_u64 = |offset: usize| {
+            map.get(offset..offset + mem::size_of::<u64>())
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize)
+        };
+        let len = read_u64(COMPRESSED_MAGIC.len()).ok_or_else(|| invalid("truncated header"))?;
+        let num_blocks = read_u64(COMPRESSED_MAGIC.len() + mem::size_of::<u64>())
+            .ok_or_else(|| invalid("truncated header"))?;
+        if len > MAXIMUM_APPEND_VEC_FILE_SIZE
+            || num_blocks != (len + COMPRESSED_BLOCK_SIZE - 1) / COMPRESSED_BLOCK_SIZE
+        {
+            return Err(invalid("invalid length"));
+        }
+        let block_offsets = (0..=num_blocks)
+            .map(|block| read_u64(COMPRESSED_HEADER_LEN + block * mem::size_of::<u64>()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid("truncated block offsets"))?;
+        let hashes_offset = COMPRESSED_HEADER_LEN + (num_blocks + 1) * mem::size_of::<u64>();
+        if block_offsets[0] != hashes_offset + num_blocks * mem::size_of::<Hash>()
+            || block_offsets
+                .windows(2)
+                .any(|offsets| offsets[0] > offsets[1])
+            || block_offsets[num_blocks] > map.len()
+        {
+            return Err(invalid("invalid block offsets"));
+        }
+        for block in 0..num_blocks {
+            let expected = hashes_offset + block * mem::size_of::<Hash>();
+            let expected = Hash::new(&map[expected..expected + mem::size_of::<Hash>()]);
+            if hash(&map[block_offsets[block]..block_offsets[block + 1]]) != expected {
+                return Err(invalid(&format!("block {} is corrupt", block)));
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            map,
+            len,
+            block_offsets,
+        })
+    }
+
+    fn block_range(&self, block: usize) -> std::ops::Range<usize> {
+        let start = block * COMPRESSED_BLOCK_SIZE;
+        start..std::cmp::min(start + COMPRESSED_BLOCK_SIZE, self.len)
+    }
+
+    fn decompress_block(&self, block: usize) -> io::Result<Vec<u8>> {
+        let data = &self.map[self.block_offsets[block]..self.block_offsets[block + 1]];
+        let data = zstd::bulk::decompress(data, COMPRESSED_BLOCK_SIZE)?;
+        if data.len() != self.block_range(block).len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("block {} decompressed to {} bytes", block, data.len()),
+            ));
+        }
+        Ok(data)
+    }
+}
+
+/// The blocks of a compressed AppendVec decompressed so far.
+///
+/// References into decompressed blocks are handed out for the life of the AppendVec, so blocks
+/// are never released individually. Instead `loaded_bytes` adds up the blocks decompressed by all
+/// AppendVecs sharing it, and the AppendVecs read least recently are replaced with fresh ones from
+/// `reopen_compressed()` once it grows too large.
+#[derive(Debug, AbiExample)]
+struct CompressedBlocks {
+    file: Arc<CompressedFile>,
+
+    /// Whether each block has been decompressed into the AppendVec's map
+    loaded: Vec<AtomicBool>,
+
+    /// A lock used to serialize decompression into the AppendVec's map
+    load_lock: Mutex<()>,
+
+    /// The number of bytes decompressed into the AppendVec's map
+    loaded_bytes: AtomicUsize,
+
+    /// The number of bytes decompressed by all AppendVecs sharing this counter
+    total_loaded_bytes: Arc<AtomicUsize>,
+
+    /// When the AppendVec was last read, in ms since the UNIX epoch
+    last_read_ms: AtomicU64,
+}
+
+impl Drop for CompressedBlocks {
+    fn drop(&mut self) {
+        self.total_loaded_bytes
+            .fetch_sub(self.loaded_bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+impl CompressedBlocks {
+    fn new(file: Arc<CompressedFile>, total_loaded_bytes: Arc<AtomicUsize>) -> Self {
+        let num_blocks = file.block_offsets.len() - 1;
+        Self {
+            file,
+            loaded: (0..num_blocks).map(|_| AtomicBool::new(false)).collect(),
+            load_lock: Mutex::new(()),
+            loaded_bytes: AtomicUsize::new(0),
+            total_loaded_bytes,
+            last_read_ms: AtomicU64::new(timestamp()),
+        }
+    }
+
+    /// Decompress `block` into `map`, unless it already is.
+    fn load_block(&self, block: usize, map: &MmapMut) {
+        if self.loaded[block].load(Ordering::Acquire) {
+            return;
+        }
+        let _lock = self.load_lock.lock().unwrap();
+        if self.loaded[block].load(Ordering::Relaxed) {
+            return;
+        }
+        // Blocks were checked against their hashes when the file was opened
+        let data = self.file.decompress_block(block).unwrap_or_else(|err| {
+            panic!(
+                "Unable to decompress verified block {} of AppendVec {:?}: {}",
+                block, self.file.path, err
+            )
+        });
+        let dst = &map[self.file.block_range(block)];
+        //UNSAFE: This write is safe because no reference into a block is handed out before the
+        //block is loaded, and `load_lock` guarantees exclusive write access while loading.
+        unsafe {
+            std::ptr::copy(data.as_ptr(), dst.as_ptr() as *mut u8, data.len());
+        }
+        self.loaded[block].store(true, Ordering::Release);
+        self.loaded_bytes.fetch_add(data.len(), Ordering::Relaxed);
+        self.total_loaded_bytes
+            .fetch_add(data.len(), Ordering::Relaxed);
+    }
 }
 
 impl Drop for AppendVec {
     fn drop(&mut self) {
-        if self.remove_on_drop {
+        // a compressed file is removed by the last AppendVec sharing it
+        if self.remove_on_drop && !self.is_compressed() {
             if let Err(_e) = remove_file(&self.path) {
                 // promote this to panic soon.
                 // disabled due to many false positive warnings while running tests.
@@ -223,6 +413,7 @@ impl AppendVec {
             current_len: AtomicUsize::new(initial_len),
             file_size: size as u64,
             remove_on_drop: true,
+            compressed: None,
         }
     }
 
@@ -247,6 +438,7 @@ impl AppendVec {
             current_len: AtomicUsize::new(current_len),
             file_size: 0, // will be filled by set_file()
             remove_on_drop: true,
+            compressed: None,
         }
     }
 
@@ -272,6 +464,10 @@ impl AppendVec {
     }
 
     pub fn flush(&self) -> io::Result<()> {
+        if self.is_compressed() {
+            // the map is anonymous, and the compressed file is synced when written
+            return Ok(());
+        }
         self.map.flush()
     }
 
@@ -294,6 +490,26 @@ impl AppendVec {
         self.file_size
     }
 
+    pub fn is_compressed(&self) -> bool {
+        self.compressed.is_some()
+    }
+
+    /// The number of bytes of a compressed AppendVec decompressed into memory so far
+    pub fn loaded_bytes(&self) -> usize {
+        self.compressed
+            .as_ref()
+            .map(|compressed| compressed.loaded_bytes.load(Ordering::Relaxed))
+            .unwrap_or_default()
+    }
+
+    /// When a compressed AppendVec was last read, in ms since the UNIX epoch
+    pub fn last_read_ms(&self) -> u64 {
+        self.compressed
+            .as_ref()
+            .map(|compressed| compressed.last_read_ms.load(Ordering::Relaxed))
+            .unwrap_or_default()
+    }
+
     pub fn file_name(slot: Slot, id: usize) -> String {
         format!("{}.{}", slot, id)
     }
@@ -324,6 +540,7 @@ impl AppendVec {
             current_len: AtomicUsize::new(current_len),
             file_size,
             remove_on_drop: true,
+            compressed: None,
         };
 
         let (sanitized, num_accounts) = new.sanitize_layout_and_length();
@@ -337,6 +554,117 @@ impl AppendVec {
         Ok((new, num_accounts))
     }
 
+    /// Write the accounts of this AppendVec to a compressed file at `path` and return an
+    /// AppendVec reading from that file.
+    ///
+    /// The compressed AppendVec holds the accounts at the same offsets as this one, so references
+    /// to them stay valid, but it can't be appended to. The bytes it decompresses as it is read
+    /// are added to `total_loaded_bytes`.
+    pub fn compress(&self, path: &Path, total_loaded_bytes: &Arc<AtomicUsize>) -> io::Result<Self> {
+        let (data, _) = self.get_slice(0, self.len()).unwrap();
+        let blocks = data
+            .chunks(COMPRESSED_BLOCK_SIZE)
+            .map(|block| zstd::bulk::compress(block, COMPRESSION_LEVEL))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(COMPRESSED_MAGIC)?;
+        file.write_all(&(data.len() as u64).to_le_bytes())?;
+        file.write_all(&(blocks.len() as u64).to_le_bytes())?;
+        let mut offset = COMPRESSED_HEADER_LEN
+            + (blocks.len() + 1) * mem::size_of::<u64>()
+            + blocks.len() * mem::size_of::<Hash>();
+        for block in &blocks {
+            file.write_all(&(offset as u64).to_le_bytes())?;
+            offset += block.len();
+        }
+        file.write_all(&(offset as u64).to_le_bytes())?;
+        for block in &blocks {
+            file.write_all(hash(block).as_ref())?;
+        }
+        for block in &blocks {
+            file.write_all(block)?;
+        }
+        file.into_inner()?.sync_all()?;
+
+        Self::new_from_compressed_file(path, total_loaded_bytes).map_err(|err| {
+            let _ignored = remove_file(path);
+            err
+        })
+    }
+
+    /// Open the compressed AppendVec at `path`, failing if any block doesn't match its hash.
+    fn new_from_compressed_file(
+        path: &Path,
+        total_loaded_bytes: &Arc<AtomicUsize>,
+    ) -> io::Result<Self> {
+        let file = CompressedFile::open(path)?;
+        Self::new_compressed(Arc::new(file), total_loaded_bytes.clone())
+    }
+
+    fn new_compressed(
+        file: Arc<CompressedFile>,
+        total_loaded_bytes: Arc<AtomicUsize>,
+    ) -> io::Result<Self> {
+        let len = file.len;
+        let map = MmapMut::map_anon(std::cmp::max(len, 1))?;
+        Ok(AppendVec {
+            path: file.path.clone(),
+            map,
+            append_lock: Mutex::new(()),
+            current_len: AtomicUsize::new(len),
+            file_size: len as u64,
+            remove_on_drop: true,
+            compressed: Some(CompressedBlocks::new(file, total_loaded_bytes)),
+        })
+    }
+
+    /// Return an AppendVec reading the same compressed file as this one, with none of its blocks
+    /// decompressed yet. The decompressed blocks of this AppendVec are released when it is
+    /// dropped.
+    pub fn reopen_compressed(&self) -> io::Result<Self> {
+        let compressed = self.compressed.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "AppendVec is not compressed")
+        })?;
+        Self::new_compressed(
+            compressed.file.clone(),
+            compressed.total_loaded_bytes.clone(),
+        )
+    }
+
+    /// Write the accounts of this AppendVec to `path` in the uncompressed format read by
+    /// `new_from_file()`. Blocks of a compressed AppendVec that haven't been read yet are not
+    /// kept in memory.
+    pub fn write_uncompressed(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        match &self.compressed {
+            Some(compressed) => {
+                for (block, loaded) in compressed.loaded.iter().enumerate() {
+                    if loaded.load(Ordering::Acquire) {
+                        file.write_all(&self.map[compressed.file.block_range(block)])?;
+                    } else {
+                        file.write_all(&compressed.file.decompress_block(block)?)?;
+                    }
+                }
+            }
+            None => file.write_all(&self.map[..self.len()])?,
+        }
+        file.into_inner()?.sync_all()
+    }
+
+    /// Decompress the blocks of a compressed AppendVec that hold `offset..end`.
+    fn load_compressed(&self, offset: usize, end: usize) {
+        if let Some(compressed) = &self.compressed {
+            compressed
+                .last_read_ms
+                .store(timestamp(), Ordering::Relaxed);
+            let end = (end + COMPRESSED_BLOCK_SIZE - 1) / COMPRESSED_BLOCK_SIZE;
+            for block in offset / COMPRESSED_BLOCK_SIZE..end {
+                compressed.load_block(block, &self.map);
+            }
+        }
+    }
+
     fn sanitize_layout_and_length(&self) -> (bool, usize) {
         let mut offset = 0;
 
@@ -367,6 +695,7 @@ impl AppendVec {
         if overflow || next > self.len() {
             return None;
         }
+        self.load_compressed(offset, next);
         let data = &self.map[offset..next];
         let next = u64_align!(next);
 
@@ -404,7 +733,8 @@ impl AppendVec {
             end += val.1;
         }
 
-        if (self.file_size as usize) < end {
+        // compressed AppendVecs are read-only
+        if self.is_compressed() || (self.file_size as usize) < end {
             return None;
         }
 
@@ -777,6 +1107,127 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_append_vec_compress() {
+        let path = get_append_vec_path("test_append_compress");
+        let av = AppendVec::new(&path.path, true, 1024 * 1024);
+        let size = 1000;
+        let indexes: Vec<_> = (0..size)
+            .map(|sample| {
+                av.append_account_test(&create_test_account(sample))
+                    .unwrap()
+            })
+            .collect();
+        assert!(av.len() > 2 * COMPRESSED_BLOCK_SIZE);
+
+        let compressed_path = get_append_vec_path("test_append_compress.z");
+        let total_loaded_bytes = Arc::new(AtomicUsize::new(0));
+        let compressed = av
+            .compress(&compressed_path.path, &total_loaded_bytes)
+            .unwrap();
+        assert!(compressed.is_compressed());
+        assert_eq!(compressed.len(), av.len());
+        assert!(std::fs::metadata(&compressed_path.path).unwrap().len() < av.len() as u64);
+        assert!(compressed
+            .compressed
+            .as_ref()
+            .unwrap()
+            .loaded
+            .iter()
+            .all(|loaded| !loaded.load(Ordering::Relaxed)));
+
+        // random reads only decompress the blocks they touch
+        let sample = size - 1;
+        assert_eq!(
+            compressed.get_account_test(indexes[sample]).unwrap(),
+            create_test_account(sample)
+        );
+        assert!(!compressed.compressed.as_ref().unwrap().loaded[0].load(Ordering::Relaxed));
+        let loaded_bytes = compressed.loaded_bytes();
+        assert!(loaded_bytes > 0 && loaded_bytes <= 2 * COMPRESSED_BLOCK_SIZE);
+        assert_eq!(total_loaded_bytes.load(Ordering::Relaxed), loaded_bytes);
+
+        let accounts = compressed.accounts(0);
+        assert_eq!(accounts.len(), size);
+        for (sample, account) in accounts.iter().enumerate() {
+            assert_eq!(account.offset, indexes[sample]);
+            assert_eq!(account.clone_account(), create_test_account(sample).1);
+        }
+        assert_eq!(compressed.loaded_bytes(), av.len());
+        drop(accounts);
+
+        // a reopened AppendVec shares the file but not the decompressed blocks, which are
+        // released with the AppendVec that holds them
+        let reopened = compressed.reopen_compressed().unwrap();
+        assert_eq!(reopened.loaded_bytes(), 0);
+        assert_eq!(
+            reopened.get_account_test(indexes[0]).unwrap(),
+            create_test_account(0)
+        );
+        assert_eq!(
+            total_loaded_bytes.load(Ordering::Relaxed),
+            av.len() + reopened.loaded_bytes()
+        );
+        assert_matches!(av.reopen_compressed(), Err(_));
+
+        // compressed AppendVecs are read-only
+        assert_eq!(
+            compressed.append_account_test(&create_test_account(0)),
+            None
+        );
+
+        // the uncompressed form can be read back as a plain AppendVec
+        let uncompressed_path = get_append_vec_path("test_append_compress.plain");
+        compressed
+            .write_uncompressed(&uncompressed_path.path)
+            .unwrap();
+        let (uncompressed, num_accounts) =
+            AppendVec::new_from_file(&uncompressed_path.path, av.len()).unwrap();
+        assert_eq!(num_accounts, size);
+        assert_eq!(
+            uncompressed.get_account_test(indexes[1]).unwrap(),
+            create_test_account(1)
+        );
+
+        // a plain AppendVec is not a compressed one
+        assert_matches!(
+            AppendVec::new_from_compressed_file(&path.path, &total_loaded_bytes),
+            Err(_)
+        );
+
+        // the file is removed along with the last AppendVec reading it
+        drop(compressed);
+        assert!(compressed_path.path.exists());
+        drop(reopened);
+        assert!(!compressed_path.path.exists());
+        assert_eq!(total_loaded_bytes.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_append_vec_compress_corrupt() {
+        let path = get_append_vec_path("test_append_compress_corrupt");
+        let av = AppendVec::new(&path.path, true, 1024 * 1024);
+        for sample in 0..100 {
+            av.append_account_test(&create_test_account(sample))
+                .unwrap();
+        }
+        let compressed_path = get_append_vec_path("test_append_compress_corrupt.z");
+        let total_loaded_bytes = Arc::new(AtomicUsize::new(0));
+        let _compressed = av
+            .compress(&compressed_path.path, &total_loaded_bytes)
+            .unwrap();
+
+        // flip a bit of the last block of a copy
+        let mut data = std::fs::read(&compressed_path.path).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        let corrupt_path = get_append_vec_path("test_append_compress_corrupt.bad");
+        std::fs::write(&corrupt_path.path, &data).unwrap();
+        assert_matches!(
+            AppendVec::new_from_compressed_file(&corrupt_path.path, &total_loaded_bytes),
+            Err(err) if err.kind() == io::ErrorKind::InvalidData
+        );
+    }
+
     #[test]
     fn test_new_from_file_crafted_zero_lamport_account() {
         let file = get_append_vec_path("test_append");
@@ -38,8 +38,11 @@ use crate::{
         AccountAddressFilter, Accounts, TransactionAccounts, TransactionLoadResult,
         TransactionLoaders,
     },
-    accounts_db::{AccountShrinkThreshold, ErrorCounters, SnapshotStorage, SnapshotStorages},
+    accounts_db::{
+        AccountShrinkThreshold, AccountsDbConfig, ErrorCounters, SnapshotStorage, SnapshotStorages,
+    },
     accounts_index::{AccountSecondaryIndexes, IndexKey, ScanResult},
+    accounts_update_notifier_interface::AccountsUpdateNotifier,
     ancestors::{Ancestors, AncestorsForSerialization},
     blockhash_queue::BlockhashQueue,
     builtins::{self, ActivationType},
@@ -63,6 +66,7 @@ use byteorder::{ByteOrder, LittleEndian};
 use itertools::Itertools;
 use log::*;
 use rayon::ThreadPool;
+use solana_address_lookup_table_program::state::{AddressLookupError, AddressLookupTable};
 use solana_measure::measure::Measure;
 use solana_metrics::{datapoint_debug, inc_new_counter_debug, inc_new_counter_info};
 use solana_sdk::{
@@ -76,11 +80,11 @@ use solana_sdk::{
         INITIAL_RENT_EPOCH, MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES,
         MAX_TRANSACTION_FORWARDING_DELAY, SECONDS_PER_DAY,
     },
-    compute_budget::ComputeBudget,
+    compute_budget::{ComputeBudget, PrioritizationFeeDetails},
     epoch_info::EpochInfo,
     epoch_schedule::EpochSchedule,
     feature,
-    feature_set::{self, tx_wide_compute_cap, FeatureSet},
+    feature_set::{self, prioritization_fee, tx_wide_compute_cap, FeatureSet},
     fee_calculator::{FeeCalculator, FeeRateGovernor},
     genesis_config::{ClusterType, GenesisConfig},
     hard_forks::HardForks,
@@ -89,7 +93,10 @@ use solana_sdk::{
     inflation::Inflation,
     instruction::{CompiledInstruction, InstructionError},
     lamports::LamportsError,
-    message::Message,
+    message::{
+        v0::{LoadedAddresses, MessageAddressTableLookup},
+        Message,
+    },
     native_loader,
     native_token::sol_to_lamports,
     nonce, nonce_account,
@@ -106,6 +113,7 @@ use solana_sdk::{
     sysvar::{self},
     timing::years_as_slots,
     transaction::{self, Result, Transaction, TransactionError},
+    versioned_transaction::VersionedTransaction,
 };
 use solana_stake_program::stake_state::{self, InflationPointCalculationEvent, PointValue};
 use solana_vote_program::{
@@ -527,8 +535,16 @@ pub struct TransactionResults {
 pub struct TransactionSimulationResult {
     pub result: Result<()>,
     pub logs: TransactionLogMessages,
+    /// State of the transaction's writable accounts before simulation
+    pub pre_simulation_accounts: Vec<(Pubkey, AccountSharedData)>,
     pub post_simulation_accounts: Vec<(Pubkey, AccountSharedData)>,
+    /// Only recorded when requested
+    pub inner_instructions: Option<InnerInstructionsList>,
     pub units_consumed: u64,
+    /// Compute units consumed by each executed top-level instruction
+    pub units_consumed_per_instruction: Vec<u64>,
+    /// Fee the transaction is charged, which it is even if an instruction fails
+    pub fee: u64,
 }
 pub struct TransactionBalancesSet {
     pub pre_balances: TransactionBalances,
@@ -1057,6 +1073,7 @@ impl Bank {
             false,
             AccountShrinkThreshold::default(),
             false,
+            None,
         )
     }
 
@@ -1071,6 +1088,7 @@ impl Bank {
             false,
             AccountShrinkThreshold::default(),
             false,
+            None,
         );
 
         bank.ns_per_slot = std::u128::MAX;
@@ -1094,6 +1112,7 @@ impl Bank {
             accounts_db_caching_enabled,
             shrink_ratio,
             false,
+            None,
         )
     }
 
@@ -1107,6 +1126,7 @@ impl Bank {
         accounts_db_caching_enabled: bool,
         shrink_ratio: AccountShrinkThreshold,
         debug_do_not_add_builtins: bool,
+        accounts_db_config: Option<AccountsDbConfig>,
     ) -> Self {
         let mut bank = Self::default();
         bank.ancestors = Ancestors::from(vec![bank.slot()]);
@@ -1119,6 +1139,7 @@ impl Bank {
             account_indexes,
             accounts_db_caching_enabled,
             shrink_ratio,
+            accounts_db_config,
         ));
         bank.process_genesis_config(genesis_config);
         bank.finish_init(
@@ -2325,7 +2346,7 @@ impl Bank {
         // accounts that were included in the bank delta hash when the bank was frozen,
         // and if we clean them here, any newly created snapshot's hash for this bank
         // may not match the frozen hash.
-        self.clean_accounts(true, false);
+        self.clean_accounts(true, false, None);
         clean.stop();
 
         let mut shrink = Measure::start("shrink");
@@ -2718,8 +2739,13 @@ impl Bank {
         batch
     }
 
-    /// Run transactions against a frozen bank without committing the results
-    pub fn simulate_transaction(&self, transaction: &Transaction) -> TransactionSimulationResult {
+    /// Run transactions against a frozen bank without committing the results, recording the
+    /// inner instructions if `enable_cpi_recording`
+    pub fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
         let batch = match SanitizedTransaction::try_from(transaction) {
@@ -2728,18 +2754,32 @@ impl Bank {
                 return TransactionSimulationResult {
                     result: Err(err),
                     logs: vec![],
+                    pre_simulation_accounts: vec![],
                     post_simulation_accounts: vec![],
+                    inner_instructions: None,
                     units_consumed: 0,
+                    units_consumed_per_instruction: vec![],
+                    fee: 0,
                 }
             }
         };
 
+        let message = transaction.message();
+        let pre_simulation_accounts = message
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| message.is_writable(*i))
+            .map(|(_, key)| (*key, self.get_account(key).unwrap_or_default()))
+            .collect();
+
         let mut timings = ExecuteTimings::default();
+        timings.details.per_instruction_units = Some(vec![]);
 
         let (
             loaded_transactions,
             executed,
-            _inner_instructions,
+            inner_instructions,
             logs,
             _retryable_transactions,
             _transaction_count,
@@ -2750,13 +2790,33 @@ impl Bank {
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
             MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
-            false,
+            enable_cpi_recording,
             true,
             &mut timings,
         );
 
-        let result = executed[0].0.clone().map(|_| ());
+        let (result, nonce_rollback) = &executed[0];
+        let fee = nonce_rollback
+            .as_ref()
+            .map(|nonce_rollback| nonce_rollback.fee_calculator())
+            .unwrap_or_else(|| {
+                self.blockhash_queue
+                    .read()
+                    .unwrap()
+                    .get_fee_calculator(&message.recent_blockhash)
+                    .cloned()
+            })
+            .map(|fee_calculator| {
+                Self::calculate_fee(
+                    &fee_calculator,
+                    transaction,
+                    self.feature_set.is_active(&prioritization_fee::id()),
+                )
+            })
+            .unwrap_or_default();
+        let result = result.clone().map(|_| ());
         let logs = logs.get(0).cloned().flatten().unwrap_or_default();
+        let inner_instructions = inner_instructions.into_iter().next().flatten();
         let post_simulation_accounts = loaded_transactions
             .into_iter()
             .next()
@@ -2779,8 +2839,15 @@ impl Bank {
         TransactionSimulationResult {
             result,
             logs,
+            pre_simulation_accounts,
             post_simulation_accounts,
+            inner_instructions,
             units_consumed,
+            units_consumed_per_instruction: timings
+                .details
+                .per_instruction_units
+                .unwrap_or_default(),
+            fee,
         }
     }
 
@@ -2801,6 +2868,13 @@ impl Bank {
         self.rc.accounts.accounts_db.set_shrink_paths(paths);
     }
 
+    pub fn set_accounts_update_notifier(&self, accounts_update_notifier: AccountsUpdateNotifier) {
+        self.rc
+            .accounts
+            .accounts_db
+            .set_accounts_update_notifier(accounts_update_notifier);
+    }
+
     fn check_age<'a>(
         &self,
         txs: impl Iterator<Item = &'a Transaction>,
@@ -3233,7 +3307,12 @@ impl Bank {
                     let mut compute_budget = self.compute_budget.unwrap_or_else(ComputeBudget::new);
 
                     let mut process_result = if feature_set.is_active(&tx_wide_compute_cap::id()) {
-                        compute_budget.process_transaction(tx)
+                        compute_budget
+                            .process_transaction(
+                                tx,
+                                feature_set.is_active(&prioritization_fee::id()),
+                            )
+                            .map(|_| ())
                     } else {
                         Ok(())
                     };
@@ -3422,12 +3501,34 @@ impl Bank {
         )
     }
 
+    /// Calculate the fee charged for a transaction: the signature fee plus,
+    /// when enabled, the prioritization fee requested through its compute unit
+    /// price. Malformed compute budget instructions fail execution, so such
+    /// transactions are only charged the signature fee.
+    pub fn calculate_fee(
+        fee_calculator: &FeeCalculator,
+        tx: &Transaction,
+        prioritization_fee_enabled: bool,
+    ) -> u64 {
+        let prioritization_fee = if prioritization_fee_enabled {
+            PrioritizationFeeDetails::from_transaction(tx)
+                .map(|details| details.fee())
+                .unwrap_or_default()
+        } else {
+            0
+        };
+        fee_calculator
+            .calculate_fee(tx.message())
+            .saturating_add(prioritization_fee)
+    }
+
     fn filter_program_errors_and_collect_fee<'a>(
         &self,
         txs: impl Iterator<Item = &'a Transaction>,
         executed: &[TransactionExecutionResult],
     ) -> Vec<Result<()>> {
         let hash_queue = self.blockhash_queue.read().unwrap();
+        let prioritization_fee_enabled = self.feature_set.is_active(&prioritization_fee::id());
         let mut fees = 0;
 
         let results = txs
@@ -3447,7 +3548,7 @@ impl Bank {
                     });
                 let fee_calculator = fee_calculator.ok_or(TransactionError::BlockhashNotFound)?;
 
-                let fee = fee_calculator.calculate_fee(tx.message());
+                let fee = Self::calculate_fee(&fee_calculator, tx, prioritization_fee_enabled);
 
                 let message = tx.message();
                 match *res {
@@ -4223,6 +4324,97 @@ impl Bank {
         Ok(self.process_transaction_batch(&batch))
     }
 
+    /// Process entry transactions in a single batch. This is used for benches and unit tests.
+    /// If any of the transactions fail sanitization, the sanitization error is returned for
+    /// the whole batch.
+    #[must_use]
+    pub fn process_entry_transactions(&self, txs: Vec<VersionedTransaction>) -> Vec<Result<()>> {
+        let sanitized_txs = match txs
+            .into_iter()
+            .map(|tx| {
+                let message_hash = tx.message.hash();
+                self.sanitize_transaction(tx, message_hash)
+            })
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(sanitized_txs) => sanitized_txs,
+            Err(err) => return vec![Err(err)],
+        };
+        let batch = self.prepare_sanitized_batch(&sanitized_txs);
+        self.process_transaction_batch(&batch)
+    }
+
+    /// Sanitize a versioned transaction, loading the addresses referenced by its
+    /// address table lookups from this bank. Signatures are not verified.
+    pub fn sanitize_transaction(
+        &self,
+        tx: VersionedTransaction,
+        message_hash: Hash,
+    ) -> Result<SanitizedTransaction<'static>> {
+        if !tx.is_legacy()
+            && !self
+                .feature_set
+                .is_active(&feature_set::versioned_tx_message_enabled::id())
+        {
+            return Err(TransactionError::UnsupportedVersion);
+        }
+
+        SanitizedTransaction::try_create_versioned(tx, message_hash, |address_table_lookups| {
+            self.load_lookup_table_addresses(address_table_lookups)
+        })
+    }
+
+    /// Load the addresses referenced by address table lookups from the
+    /// lookup table accounts stored in this bank
+    pub fn load_lookup_table_addresses(
+        &self,
+        address_table_lookups: &[MessageAddressTableLookup],
+    ) -> Result<LoadedAddresses> {
+        if address_table_lookups.is_empty() {
+            return Ok(LoadedAddresses::default());
+        }
+
+        let slot_hashes = self
+            .get_account_with_fixed_root(&sysvar::slot_hashes::id())
+            .and_then(|account| from_account::<SlotHashes, _>(&account))
+            .unwrap_or_default();
+
+        let mut loaded_addresses = LoadedAddresses::default();
+        for address_table_lookup in address_table_lookups {
+            let table_account = self
+                .get_account_with_fixed_root(&address_table_lookup.account_key)
+                .ok_or(TransactionError::AddressLookupTableNotFound)?;
+
+            if table_account.owner() != &solana_address_lookup_table_program::id() {
+                return Err(TransactionError::InvalidAddressLookupTableOwner);
+            }
+
+            let lookup_table = AddressLookupTable::deserialize(table_account.data())
+                .map_err(|_| TransactionError::InvalidAddressLookupTableData)?;
+
+            let lookup = |indexes: &[u8]| {
+                lookup_table
+                    .lookup(self.slot(), indexes, &slot_hashes)
+                    .map_err(|err| match err {
+                        AddressLookupError::LookupTableNotActive => {
+                            TransactionError::AddressLookupTableNotFound
+                        }
+                        AddressLookupError::InvalidLookupIndex => {
+                            TransactionError::InvalidAddressLookupTableIndex
+                        }
+                    })
+            };
+            loaded_addresses
+                .writable
+                .extend(lookup(&address_table_lookup.writable_indexes)?);
+            loaded_addresses
+                .readonly
+                .extend(lookup(&address_table_lookup.readonly_indexes)?);
+        }
+
+        Ok(loaded_addresses)
+    }
+
     #[must_use]
     fn process_transaction_batch(&self, batch: &TransactionBatch) -> Vec<Result<()>> {
         self.load_execute_and_commit_transactions(
@@ -4529,6 +4721,25 @@ impl Bank {
         )
     }
 
+    /// Get at most `limit` program accounts passing `filter`, in pubkey order, starting after
+    /// `start_after`
+    pub fn get_filtered_program_accounts_paginated<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        program_id: &Pubkey,
+        filter: F,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        self.rc.accounts.load_by_program_with_filter_paginated(
+            &self.ancestors,
+            self.bank_id,
+            program_id,
+            filter,
+            start_after,
+            limit,
+        )
+    }
+
     pub fn get_filtered_indexed_accounts<F: Fn(&AccountSharedData) -> bool>(
         &self,
         index_key: &IndexKey,
@@ -4542,6 +4753,25 @@ impl Bank {
         )
     }
 
+    /// Get at most `limit` accounts under `index_key` passing `filter`, in pubkey order, starting
+    /// after `start_after`
+    pub fn get_filtered_indexed_accounts_paginated<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        index_key: &IndexKey,
+        filter: F,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        self.rc.accounts.load_by_index_key_with_filter_paginated(
+            &self.ancestors,
+            self.bank_id,
+            index_key,
+            filter,
+            start_after,
+            limit,
+        )
+    }
+
     pub fn account_indexes_include_key(&self, key: &Pubkey) -> bool {
         self.rc.accounts.account_indexes_include_key(key)
     }
@@ -4854,11 +5084,15 @@ impl Bank {
 
     /// A snapshot bank should be purged of 0 lamport accounts which are not part of the hash
     /// calculation and could shield other real accounts.
-    pub fn verify_snapshot_bank(&self, test_hash_calculation: bool) -> bool {
+    pub fn verify_snapshot_bank(
+        &self,
+        test_hash_calculation: bool,
+        last_full_snapshot_slot: Option<Slot>,
+    ) -> bool {
         info!("cleaning..");
         let mut clean_time = Measure::start("clean");
         if self.slot() > 0 {
-            self.clean_accounts(true, true);
+            self.clean_accounts(true, true, last_full_snapshot_slot);
         }
         clean_time.stop();
 
@@ -5141,7 +5375,12 @@ impl Bank {
             .add_program(program_id, process_instruction_with_context);
     }
 
-    pub fn clean_accounts(&self, skip_last: bool, is_startup: bool) {
+    pub fn clean_accounts(
+        &self,
+        skip_last: bool,
+        is_startup: bool,
+        last_full_snapshot_slot: Option<Slot>,
+    ) {
         let max_clean_slot = if skip_last {
             // Don't clean the slot we're snapshotting because it may have zero-lamport
             // accounts that were included in the bank delta hash when the bank was frozen,
@@ -5151,10 +5390,11 @@ impl Bank {
         } else {
             None
         };
-        self.rc
-            .accounts
-            .accounts_db
-            .clean_accounts(max_clean_slot, is_startup);
+        self.rc.accounts.accounts_db.clean_accounts(
+            max_clean_slot,
+            is_startup,
+            last_full_snapshot_slot,
+        );
     }
 
     pub fn shrink_all_slots(&self, is_startup: bool) {
@@ -5187,6 +5427,14 @@ impl Bank {
         self.rc.accounts.accounts_db.shrink_candidate_slots()
     }
 
+    pub fn compress_cold_storages(&self) -> usize {
+        self.rc.accounts.accounts_db.compress_cold_storages()
+    }
+
+    pub fn evict_cold_storage_cache(&self) -> usize {
+        self.rc.accounts.accounts_db.evict_cold_storage_cache()
+    }
+
     pub fn no_overflow_rent_distribution_enabled(&self) -> bool {
         self.feature_set
             .is_active(&feature_set::no_overflow_rent_distribution::id())
@@ -5573,15 +5821,16 @@ pub(crate) mod tests {
         status_cache::MAX_CACHE_ENTRIES,
     };
     use crossbeam_channel::{bounded, unbounded};
+    use solana_address_lookup_table_program::state::LookupTableMeta;
     use solana_sdk::{
         account::Account,
         clock::{DEFAULT_SLOTS_PER_EPOCH, DEFAULT_TICKS_PER_SLOT},
-        compute_budget::ComputeBudgetInstruction,
+        compute_budget::{ComputeBudgetInstruction, MICRO_LAMPORTS_PER_LAMPORT},
         epoch_schedule::MINIMUM_SLOTS_PER_EPOCH,
         feature::Feature,
         genesis_config::create_genesis_config,
         instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
-        message::{Message, MessageHeader},
+        message::{v0, Message, MessageHeader, VersionedMessage},
         nonce,
         poh_config::PohConfig,
         process_instruction::InvokeContext,
@@ -7739,7 +7988,7 @@ pub(crate) mod tests {
         bank.squash();
         bank.force_flush_accounts_cache();
         let hash = bank.update_accounts_hash();
-        bank.clean_accounts(false, false);
+        bank.clean_accounts(false, false, None);
         assert_eq!(bank.update_accounts_hash(), hash);
 
         let bank0 = Arc::new(new_from_parent(&bank));
@@ -7759,14 +8008,14 @@ pub(crate) mod tests {
 
         info!("bank0 purge");
         let hash = bank0.update_accounts_hash();
-        bank0.clean_accounts(false, false);
+        bank0.clean_accounts(false, false, None);
         assert_eq!(bank0.update_accounts_hash(), hash);
 
         assert_eq!(bank0.get_account(&keypair.pubkey()).unwrap().lamports(), 10);
         assert_eq!(bank1.get_account(&keypair.pubkey()), None);
 
         info!("bank1 purge");
-        bank1.clean_accounts(false, false);
+        bank1.clean_accounts(false, false, None);
 
         assert_eq!(bank0.get_account(&keypair.pubkey()).unwrap().lamports(), 10);
         assert_eq!(bank1.get_account(&keypair.pubkey()), None);
@@ -7787,7 +8036,7 @@ pub(crate) mod tests {
         assert_eq!(bank0.get_account(&keypair.pubkey()), None);
         assert_eq!(bank1.get_account(&keypair.pubkey()), None);
         bank1.force_flush_accounts_cache();
-        bank1.clean_accounts(false, false);
+        bank1.clean_accounts(false, false, None);
 
         assert!(bank1.verify_bank_hash(true));
     }
@@ -8652,11 +8901,11 @@ pub(crate) mod tests {
         bank.transfer(1_000, &mint_keypair, &pubkey).unwrap();
         bank.freeze();
         bank.update_accounts_hash();
-        assert!(bank.verify_snapshot_bank(true));
+        assert!(bank.verify_snapshot_bank(true, None));
 
         // tamper the bank after freeze!
         bank.increment_signature_count(1);
-        assert!(!bank.verify_snapshot_bank(true));
+        assert!(!bank.verify_snapshot_bank(true, None));
     }
 
     // Test that two bank forks with the same accounts should not hash to the same value.
@@ -11029,7 +11278,7 @@ pub(crate) mod tests {
 
         // Clean accounts, which should add earlier slots to the shrink
         // candidate set
-        bank2.clean_accounts(false, false);
+        bank2.clean_accounts(false, false, None);
 
         // Slots 0 and 1 should be candidates for shrinking, but slot 2
         // shouldn't because none of its accounts are outdated by a later
@@ -11085,7 +11334,7 @@ pub(crate) mod tests {
         goto_end_of_slot(Arc::<Bank>::get_mut(&mut bank).unwrap());
 
         bank.squash();
-        bank.clean_accounts(false, false);
+        bank.clean_accounts(false, false, None);
         let force_to_return_alive_account = 0;
         assert_eq!(
             bank.process_stale_slot_with_budget(22, force_to_return_alive_account),
@@ -12066,6 +12315,7 @@ pub(crate) mod tests {
             false,
             AccountShrinkThreshold::default(),
             false,
+            None,
         ));
         // move to next epoch to create now deprecated rewards sysvar intentionally
         let bank1 = Arc::new(Bank::new_from_parent(
@@ -12709,7 +12959,7 @@ pub(crate) mod tests {
                         current_major_fork_bank.squash();
                         // Try to get cache flush/clean to overlap with the scan
                         current_major_fork_bank.force_flush_accounts_cache();
-                        current_major_fork_bank.clean_accounts(false, false);
+                        current_major_fork_bank.clean_accounts(false, false, None);
                         // Move purge here so that Bank::drop()->purge_slots() doesn't race
                         // with clean. Simulates the call from AccountsBackgroundService
                         let is_abs_service = true;
@@ -12759,7 +13009,7 @@ pub(crate) mod tests {
                         current_bank.squash();
                         if current_bank.slot() % 2 == 0 {
                             current_bank.force_flush_accounts_cache();
-                            current_bank.clean_accounts(true, false);
+                            current_bank.clean_accounts(true, false, None);
                         }
                         prev_bank = current_bank.clone();
                         current_bank = Arc::new(Bank::new_from_parent(
@@ -13727,7 +13977,7 @@ pub(crate) mod tests {
         bank2.squash();
 
         drop(bank1);
-        bank2.clean_accounts(false, false);
+        bank2.clean_accounts(false, false, None);
 
         let expected_ref_count_for_cleaned_up_keys = 0;
         let expected_ref_count_for_keys_in_both_slot1_and_slot2 = 1;
@@ -13838,4 +14088,201 @@ pub(crate) mod tests {
         let tx = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
         bank.process_transaction(&tx).unwrap();
     }
+
+    #[test]
+    fn test_prioritization_fee() {
+        solana_logger::setup();
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_leader(
+            1_000_000_000,
+            &Pubkey::new_unique(),
+            bootstrap_validator_stake_lamports(),
+        );
+        genesis_config.fee_rate_governor = FeeRateGovernor::new(4, 0);
+        let lamports_per_signature = genesis_config
+            .fee_rate_governor
+            .create_fee_calculator()
+            .lamports_per_signature;
+
+        let priority_transfer = |bank: &Bank| {
+            let message = Message::new(
+                &[
+                    ComputeBudgetInstruction::request_units(1_000),
+                    ComputeBudgetInstruction::set_compute_unit_price(
+                        2 * MICRO_LAMPORTS_PER_LAMPORT,
+                    ),
+                    system_instruction::transfer(&mint_keypair.pubkey(), &Pubkey::new_unique(), 1),
+                ],
+                Some(&mint_keypair.pubkey()),
+            );
+            Transaction::new(&[&mint_keypair], message, bank.last_blockhash())
+        };
+
+        // The prioritization fee is charged on top of the signature fee
+        let bank = Bank::new(&genesis_config);
+        let tx = priority_transfer(&bank);
+        let fee_calculator = bank.get_fee_calculator(&bank.last_blockhash()).unwrap();
+        let expected_fee = lamports_per_signature + 2_000;
+        assert_eq!(
+            Bank::calculate_fee(&fee_calculator, &tx, true),
+            expected_fee
+        );
+        let balance = bank.get_balance(&mint_keypair.pubkey());
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(
+            bank.get_balance(&mint_keypair.pubkey()),
+            balance - 1 - expected_fee
+        );
+
+        // ...but only once the feature is active. Until then the compute unit price instruction
+        // is rejected, as it was before the feature existed, and only the signature fee is charged
+        let mut bank = Bank::new(&genesis_config);
+        bank.deactivate_feature(&feature_set::prioritization_fee::id());
+        let tx = priority_transfer(&bank);
+        let fee_calculator = bank.get_fee_calculator(&bank.last_blockhash()).unwrap();
+        assert_eq!(
+            Bank::calculate_fee(&fee_calculator, &tx, false),
+            lamports_per_signature
+        );
+        let balance = bank.get_balance(&mint_keypair.pubkey());
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+        assert_eq!(
+            bank.get_balance(&mint_keypair.pubkey()),
+            balance - lamports_per_signature
+        );
+    }
+
+    fn store_address_lookup_table(bank: &Bank, addresses: Vec<Pubkey>) -> Pubkey {
+        let lookup_table = AddressLookupTable {
+            meta: LookupTableMeta {
+                num_addresses: addresses.len() as u16,
+                ..LookupTableMeta::new(Pubkey::new_unique())
+            },
+            addresses,
+        };
+        let mut data = vec![0; AddressLookupTable::account_size(lookup_table.addresses.len())];
+        lookup_table.serialize(&mut data).unwrap();
+
+        let lookup_table_key = Pubkey::new_unique();
+        let lookup_table_account = AccountSharedData::from(Account {
+            lamports: 1,
+            data,
+            owner: solana_address_lookup_table_program::id(),
+            ..Account::default()
+        });
+        bank.store_account(&lookup_table_key, &lookup_table_account);
+        lookup_table_key
+    }
+
+    fn v0_transfer_transaction(
+        from_keypair: &Keypair,
+        lookup_table_key: Pubkey,
+        lamports: u64,
+        recent_blockhash: Hash,
+    ) -> VersionedTransaction {
+        let message = v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![from_keypair.pubkey(), system_program::id()],
+            recent_blockhash,
+            instructions: vec![CompiledInstruction::new(
+                1,
+                &system_instruction::SystemInstruction::Transfer { lamports },
+                vec![0, 2],
+            )],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: lookup_table_key,
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        };
+        VersionedTransaction::try_new(VersionedMessage::V0(message), &[from_keypair]).unwrap()
+    }
+
+    #[test]
+    fn test_process_v0_transaction() {
+        let (genesis_config, mint_keypair) = create_genesis_config(10_000);
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let mut bank = new_from_parent(&bank0);
+
+        let recipient = Pubkey::new_unique();
+        let lookup_table_key = store_address_lookup_table(&bank, vec![recipient]);
+        let tx =
+            v0_transfer_transaction(&mint_keypair, lookup_table_key, 10, bank.last_blockhash());
+
+        bank.deactivate_feature(&feature_set::versioned_tx_message_enabled::id());
+        assert_eq!(
+            bank.process_entry_transactions(vec![tx.clone()]),
+            vec![Err(TransactionError::UnsupportedVersion)]
+        );
+
+        bank.activate_feature(&feature_set::versioned_tx_message_enabled::id());
+        assert_eq!(bank.process_entry_transactions(vec![tx]), vec![Ok(())]);
+        assert_eq!(bank.get_balance(&recipient), 10);
+    }
+
+    #[test]
+    fn test_load_lookup_table_addresses() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(10_000);
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank = new_from_parent(&bank0);
+
+        let addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let lookup_table_key = store_address_lookup_table(&bank, addresses.clone());
+        assert_eq!(
+            bank.load_lookup_table_addresses(&[MessageAddressTableLookup {
+                account_key: lookup_table_key,
+                writable_indexes: vec![1],
+                readonly_indexes: vec![0],
+            }]),
+            Ok(LoadedAddresses {
+                writable: vec![addresses[1]],
+                readonly: vec![addresses[0]],
+            })
+        );
+
+        assert_eq!(
+            bank.load_lookup_table_addresses(&[MessageAddressTableLookup {
+                account_key: lookup_table_key,
+                writable_indexes: vec![2],
+                readonly_indexes: vec![],
+            }]),
+            Err(TransactionError::InvalidAddressLookupTableIndex)
+        );
+
+        assert_eq!(
+            bank.load_lookup_table_addresses(&[MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }]),
+            Err(TransactionError::AddressLookupTableNotFound)
+        );
+
+        let invalid_owner_key = Pubkey::new_unique();
+        bank.store_account(
+            &invalid_owner_key,
+            &AccountSharedData::new(1, 0, &system_program::id()),
+        );
+        assert_eq!(
+            bank.load_lookup_table_addresses(&[MessageAddressTableLookup {
+                account_key: invalid_owner_key,
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }]),
+            Err(TransactionError::InvalidAddressLookupTableOwner)
+        );
+    }
 }
@@ -0,0 +1,639 @@
+//! A memory-mapped hash map from pubkey to slot list and ref count, which holds the entries
+//! that `AccountsIndex` has evicted from memory.
+//!
+//! The map is split into buckets, one per index bin. Each bucket is an open-addressed table of
+//! fixed-size index cells, placed by a seeded hash of the pubkey, plus data files holding the
+//! bincode-serialized slot lists, one file per power-of-two cell size. Files grow by doubling
+//! when a key's search window is full and are removed on drop: nothing on disk outlives the
+//! process, since the index is rebuilt from the account storages at startup.
+
+use {
+    crate::accounts_index::RefCount,
+    log::*,
+    memmap2::MmapMut,
+    rand::{thread_rng, Rng},
+    serde::{de::DeserializeOwned, Serialize},
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::hash_map::RandomState,
+        convert::TryInto,
+        fs::{self, remove_file, OpenOptions},
+        hash::{BuildHasher, Hash, Hasher},
+        io::{Seek, SeekFrom, Write},
+        marker::PhantomData,
+        mem::size_of,
+        ops::RangeBounds,
+        path::PathBuf,
+        sync::{Arc, RwLock},
+    },
+    tempfile::TempDir,
+};
+
+/// Number of cells, starting at the one a key hashes to, searched for that key
+const MAX_SEARCH: u64 = 32;
+
+/// A new file holds 2^`DEFAULT_CAPACITY_POW2` cells
+const DEFAULT_CAPACITY_POW2: u8 = 5;
+
+/// Each cell starts with a `u64` that is zero while the cell is free
+const CELL_HEADER_SIZE: usize = size_of::<u64>();
+
+/// Index cell: pubkey, ref count, data cell size class, data cell index, data length
+const INDEX_CELL_SIZE: usize = size_of::<Pubkey>() + 4 * size_of::<u64>();
+
+/// Smallest data cell, as a power of two
+const MIN_DATA_POW2: u8 = 5;
+
+#[derive(Debug, Clone, Default)]
+pub struct BucketMapConfig {
+    /// Number of buckets, which must match the number of bins of the index
+    pub max_buckets: usize,
+    /// Directories to create the bucket files in, spread at random. These are erased when the
+    /// map is created. A temporary directory is used if none are given.
+    pub drives: Option<Vec<PathBuf>>,
+}
+
+/// A memory-mapped file of fixed-size cells
+#[derive(Debug)]
+struct BucketStorage {
+    path: PathBuf,
+    mmap: MmapMut,
+    cell_size: u64,
+    capacity_pow2: u8,
+    used: u64,
+}
+
+impl Drop for BucketStorage {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.path);
+    }
+}
+
+impl BucketStorage {
+    fn new(drives: &[PathBuf], cell_size: u64, capacity_pow2: u8) -> Self {
+        let drive = &drives[thread_rng().gen_range(0, drives.len())];
+        let path = drive.join(format!("{}.{}", cell_size, thread_rng().gen::<u128>()));
+        let size = cell_size << capacity_pow2;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&path)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Unable to create accounts index file {}: {}",
+                    path.display(),
+                    err
+                )
+            });
+        // Extend the file to its full size up front, so that it is never resized while mapped
+        file.seek(SeekFrom::Start(size - 1)).unwrap();
+        file.write_all(&[0]).unwrap();
+        file.flush().unwrap();
+
+        //UNSAFE: Required to create a Mmap
+        let mmap = unsafe { MmapMut::map_mut(&file) }.unwrap_or_else(|err| {
+            error!(
+                "Failed to map the accounts index file (size: {}): {}.\n
+                    Please increase sysctl vm.max_map_count or equivalent for your platform.",
+                size, err
+            );
+            std::process::exit(1);
+        });
+        Self {
+            path,
+            mmap,
+            cell_size,
+            capacity_pow2,
+            used: 0,
+        }
+    }
+
+    fn capacity(&self) -> u64 {
+        1 << self.capacity_pow2
+    }
+
+    fn offset(&self, ix: u64) -> usize {
+        assert!(ix < self.capacity(), "cell index out of bounds");
+        (ix * self.cell_size) as usize
+    }
+
+    fn is_free(&self, ix: u64) -> bool {
+        let offset = self.offset(ix);
+        self.mmap[offset..offset + CELL_HEADER_SIZE]
+            .iter()
+            .all(|byte| *byte == 0)
+    }
+
+    fn allocate(&mut self, ix: u64) {
+        assert!(self.is_free(ix), "cell already in use");
+        let offset = self.offset(ix);
+        self.mmap[offset..offset + CELL_HEADER_SIZE].copy_from_slice(&1u64.to_le_bytes());
+        self.used += 1;
+    }
+
+    fn free(&mut self, ix: u64) {
+        assert!(!self.is_free(ix), "cell already free");
+        let offset = self.offset(ix);
+        self.mmap[offset..offset + CELL_HEADER_SIZE].copy_from_slice(&0u64.to_le_bytes());
+        self.used -= 1;
+    }
+
+    fn cell(&self, ix: u64) -> &[u8] {
+        let offset = self.offset(ix);
+        &self.mmap[offset + CELL_HEADER_SIZE..offset + self.cell_size as usize]
+    }
+
+    fn cell_mut(&mut self, ix: u64) -> &mut [u8] {
+        let offset = self.offset(ix);
+        let end = offset + self.cell_size as usize;
+        &mut self.mmap[offset + CELL_HEADER_SIZE..end]
+    }
+
+    /// Find a free cell in the search window of a key with hash `hash`
+    fn find_free(&self, hash: u64) -> Option<u64> {
+        let start = hash % self.capacity();
+        (0..MAX_SEARCH)
+            .map(|i| (start + i) % self.capacity())
+            .find(|ix| self.is_free(*ix))
+    }
+
+    /// A copy of this storage with twice the capacity, each cell at the same index
+    fn grow(&self, drives: &[PathBuf]) -> Self {
+        let mut new = Self::new(drives, self.cell_size, self.capacity_pow2 + 1);
+        let len = self.mmap.len();
+        new.mmap[..len].copy_from_slice(&self.mmap);
+        new.used = self.used;
+        new
+    }
+}
+
+/// The location of a pubkey's entry, decoded from its index cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IndexEntry {
+    key: Pubkey,
+    ref_count: RefCount,
+    data_pow2: u8,
+    data_ix: u64,
+    data_len: u64,
+}
+
+impl IndexEntry {
+    fn read(cell: &[u8]) -> Self {
+        let u64_at = |i: usize| {
+            let offset = size_of::<Pubkey>() + i * size_of::<u64>();
+            u64::from_le_bytes(cell[offset..offset + size_of::<u64>()].try_into().unwrap())
+        };
+        Self {
+            key: Pubkey::new(&cell[..size_of::<Pubkey>()]),
+            ref_count: u64_at(0),
+            data_pow2: u64_at(1) as u8,
+            data_ix: u64_at(2),
+            data_len: u64_at(3),
+        }
+    }
+
+    fn write(&self, cell: &mut [u8]) {
+        let mut offset = 0;
+        let mut put = |bytes: &[u8]| {
+            cell[offset..offset + bytes.len()].copy_from_slice(bytes);
+            offset += bytes.len();
+        };
+        put(self.key.as_ref());
+        put(&self.ref_count.to_le_bytes());
+        put(&(self.data_pow2 as u64).to_le_bytes());
+        put(&self.data_ix.to_le_bytes());
+        put(&self.data_len.to_le_bytes());
+    }
+}
+
+/// One bucket: an index of pubkeys and the serialized slot lists it points to
+#[derive(Debug)]
+struct Bucket {
+    drives: Arc<Vec<PathBuf>>,
+    hasher: RandomState,
+    index: BucketStorage,
+    // indexed by size class; `None` until the first slot list of that size is stored
+    data: Vec<Option<BucketStorage>>,
+}
+
+impl Bucket {
+    fn new(drives: Arc<Vec<PathBuf>>, hasher: RandomState) -> Self {
+        let index = BucketStorage::new(
+            &drives,
+            (CELL_HEADER_SIZE + INDEX_CELL_SIZE) as u64,
+            DEFAULT_CAPACITY_POW2,
+        );
+        Self {
+            drives,
+            hasher,
+            index,
+            data: vec![],
+        }
+    }
+
+    fn hash(&self, key: &Pubkey) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn len(&self) -> usize {
+        self.index.used as usize
+    }
+
+    fn find(&self, key: &Pubkey) -> Option<(u64, IndexEntry)> {
+        let start = self.hash(key) % self.index.capacity();
+        (0..MAX_SEARCH)
+            .map(|i| (start + i) % self.index.capacity())
+            .filter(|ix| !self.index.is_free(*ix))
+            .map(|ix| (ix, IndexEntry::read(self.index.cell(ix))))
+            .find(|(_, entry)| entry.key == *key)
+    }
+
+    fn data(&self, entry: &IndexEntry) -> &[u8] {
+        if entry.data_len == 0 {
+            return &[];
+        }
+        let storage = self.data[entry.data_pow2 as usize].as_ref().unwrap();
+        &storage.cell(entry.data_ix)[..entry.data_len as usize]
+    }
+
+    fn read(&self, key: &Pubkey) -> Option<(&[u8], RefCount)> {
+        self.find(key)
+            .map(|(_, entry)| (self.data(&entry), entry.ref_count))
+    }
+
+    fn entries(&self) -> impl Iterator<Item = IndexEntry> + '_ {
+        (0..self.index.capacity())
+            .filter(move |ix| !self.index.is_free(*ix))
+            .map(move |ix| IndexEntry::read(self.index.cell(ix)))
+    }
+
+    fn free_data(&mut self, entry: &IndexEntry) {
+        if entry.data_len > 0 {
+            self.data[entry.data_pow2 as usize]
+                .as_mut()
+                .unwrap()
+                .free(entry.data_ix);
+        }
+    }
+
+    /// Store `data` in a cell of the smallest size class that fits it, returning the class and
+    /// the cell index
+    fn store_data(&mut self, key: &Pubkey, data: &[u8]) -> (u8, u64) {
+        let data_pow2 = std::cmp::max(
+            MIN_DATA_POW2,
+            (data.len() as u64).next_power_of_two().trailing_zeros() as u8,
+        );
+        if self.data.len() <= data_pow2 as usize {
+            self.data.resize_with(data_pow2 as usize + 1, || None);
+        }
+        let hash = self.hash(key);
+        let drives = self.drives.clone();
+        let storage = self.data[data_pow2 as usize].get_or_insert_with(|| {
+            BucketStorage::new(
+                &drives,
+                CELL_HEADER_SIZE as u64 + (1 << data_pow2),
+                DEFAULT_CAPACITY_POW2,
+            )
+        });
+        let data_ix = loop {
+            match storage.find_free(hash) {
+                Some(ix) => break ix,
+                None => {
+                    let grown = storage.grow(&drives);
+                    *storage = grown;
+                }
+            }
+        };
+        storage.allocate(data_ix);
+        storage.cell_mut(data_ix)[..data.len()].copy_from_slice(data);
+        (data_pow2, data_ix)
+    }
+
+    fn insert(&mut self, key: &Pubkey, data: &[u8], ref_count: RefCount) {
+        let existing = self.find(key);
+        if let Some((_, entry)) = &existing {
+            self.free_data(entry);
+        }
+        let (data_pow2, data_ix) = if data.is_empty() {
+            (0, 0)
+        } else {
+            self.store_data(key, data)
+        };
+        let entry = IndexEntry {
+            key: *key,
+            ref_count,
+            data_pow2,
+            data_ix,
+            data_len: data.len() as u64,
+        };
+        let ix = match existing {
+            Some((ix, _)) => ix,
+            None => {
+                let ix = loop {
+                    match self.index.find_free(self.hash(key)) {
+                        Some(ix) => break ix,
+                        None => self.grow_index(),
+                    }
+                };
+                self.index.allocate(ix);
+                ix
+            }
+        };
+        entry.write(self.index.cell_mut(ix));
+    }
+
+    fn delete(&mut self, key: &Pubkey) -> bool {
+        match self.find(key) {
+            Some((ix, entry)) => {
+                self.free_data(&entry);
+                self.index.free(ix);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rehash the index into a larger file. Unlike data cells, an index cell's position
+    /// depends on the capacity, so cells can't simply be copied.
+    fn grow_index(&mut self) {
+        let mut capacity_pow2 = self.index.capacity_pow2 + 1;
+        'rehash: loop {
+            let mut index = BucketStorage::new(&self.drives, self.index.cell_size, capacity_pow2);
+            for old_ix in 0..self.index.capacity() {
+                if self.index.is_free(old_ix) {
+                    continue;
+                }
+                let cell = self.index.cell(old_ix);
+                let key = IndexEntry::read(cell).key;
+                match index.find_free(self.hash(&key)) {
+                    Some(ix) => {
+                        index.allocate(ix);
+                        index.cell_mut(ix).copy_from_slice(cell);
+                    }
+                    None => {
+                        capacity_pow2 += 1;
+                        continue 'rehash;
+                    }
+                }
+            }
+            debug!("accounts index bucket grew to {} cells", index.capacity());
+            self.index = index;
+            return;
+        }
+    }
+}
+
+/// A disk-backed map from pubkey to `(slot list, ref count)`
+#[derive(Debug)]
+pub struct BucketMap<T> {
+    buckets: Vec<RwLock<Option<Bucket>>>,
+    drives: Arc<Vec<PathBuf>>,
+    hasher: RandomState,
+    // holds the files when no drives are configured
+    _temp_dir: Option<TempDir>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> BucketMap<T> {
+    pub fn new(config: BucketMapConfig) -> Self {
+        assert!(
+            config.max_buckets > 0,
+            "a bucket map needs at least one bucket"
+        );
+        let (drives, temp_dir) = match config.drives {
+            Some(drives) if !drives.is_empty() => {
+                Self::erase_previous_drives(&drives);
+                (drives, None)
+            }
+            _ => {
+                let temp_dir = TempDir::new().expect("create accounts index temp dir");
+                (vec![temp_dir.path().to_path_buf()], Some(temp_dir))
+            }
+        };
+        Self {
+            buckets: (0..config.max_buckets).map(|_| RwLock::default()).collect(),
+            drives: Arc::new(drives),
+            hasher: RandomState::new(),
+            _temp_dir: temp_dir,
+            _phantom: PhantomData::default(),
+        }
+    }
+
+    fn erase_previous_drives(drives: &[PathBuf]) {
+        for drive in drives {
+            let _ = fs::remove_dir_all(drive);
+            fs::create_dir_all(drive).unwrap_or_else(|err| {
+                panic!(
+                    "Unable to create accounts index directory {}: {}",
+                    drive.display(),
+                    err
+                )
+            });
+        }
+    }
+
+    pub fn num_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Number of keys held in bucket `ix`
+    pub fn bucket_len(&self, ix: usize) -> usize {
+        self.buckets[ix]
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|bucket| bucket.len())
+            .unwrap_or_default()
+    }
+
+    pub fn contains_key(&self, ix: usize, key: &Pubkey) -> bool {
+        self.buckets[ix]
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|bucket| bucket.find(key).is_some())
+            .unwrap_or_default()
+    }
+
+    pub fn read_value(&self, ix: usize, key: &Pubkey) -> Option<(Vec<(Slot, T)>, RefCount)> {
+        let bucket = self.buckets[ix].read().unwrap();
+        bucket
+            .as_ref()?
+            .read(key)
+            .map(|(data, ref_count)| (Self::deserialize(data), ref_count))
+    }
+
+    pub fn insert(&self, ix: usize, key: &Pubkey, value: (&[(Slot, T)], RefCount)) {
+        let (slot_list, ref_count) = value;
+        let data = if slot_list.is_empty() {
+            vec![]
+        } else {
+            bincode::serialize(slot_list).unwrap()
+        };
+        let mut bucket = self.buckets[ix].write().unwrap();
+        bucket
+            .get_or_insert_with(|| Bucket::new(self.drives.clone(), self.hasher.clone()))
+            .insert(key, &data, ref_count);
+    }
+
+    /// Remove `key`, returning its value
+    pub fn take(&self, ix: usize, key: &Pubkey) -> Option<(Vec<(Slot, T)>, RefCount)> {
+        let mut bucket = self.buckets[ix].write().unwrap();
+        let bucket = bucket.as_mut()?;
+        let value = bucket
+            .read(key)
+            .map(|(data, ref_count)| (Self::deserialize(data), ref_count))?;
+        bucket.delete(key);
+        Some(value)
+    }
+
+    pub fn delete_key(&self, ix: usize, key: &Pubkey) -> bool {
+        self.buckets[ix]
+            .write()
+            .unwrap()
+            .as_mut()
+            .map(|bucket| bucket.delete(key))
+            .unwrap_or_default()
+    }
+
+    /// All of bucket `ix`'s entries whose keys are in `range`, in no particular order
+    pub fn items_in_range<R>(
+        &self,
+        ix: usize,
+        range: &Option<R>,
+    ) -> Vec<(Pubkey, Vec<(Slot, T)>, RefCount)>
+    where
+        R: RangeBounds<Pubkey>,
+    {
+        let bucket = self.buckets[ix].read().unwrap();
+        match bucket.as_ref() {
+            Some(bucket) => bucket
+                .entries()
+                .filter(|entry| {
+                    range
+                        .as_ref()
+                        .map(|range| range.contains(&entry.key))
+                        .unwrap_or(true)
+                })
+                .map(|entry| {
+                    (
+                        entry.key,
+                        Self::deserialize(bucket.data(&entry)),
+                        entry.ref_count,
+                    )
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn deserialize(data: &[u8]) -> Vec<(Slot, T)> {
+        if data.is_empty() {
+            vec![]
+        } else {
+            bincode::deserialize(data).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::HashMap};
+
+    fn new_map() -> BucketMap<u64> {
+        BucketMap::new(BucketMapConfig {
+            max_buckets: 1,
+            drives: None,
+        })
+    }
+
+    #[test]
+    fn test_bucket_map_insert_read_delete() {
+        let map = new_map();
+        let key = Pubkey::new_unique();
+        assert_eq!(map.read_value(0, &key), None);
+        assert!(!map.delete_key(0, &key));
+
+        map.insert(0, &key, (&[(0, 10)], 1));
+        assert!(map.contains_key(0, &key));
+        assert_eq!(map.read_value(0, &key), Some((vec![(0, 10)], 1)));
+
+        // a longer slot list moves to a larger data cell
+        let slot_list: Vec<_> = (0..10).map(|slot| (slot, slot * 2)).collect();
+        map.insert(0, &key, (&slot_list, 3));
+        assert_eq!(map.read_value(0, &key), Some((slot_list.clone(), 3)));
+        assert_eq!(map.bucket_len(0), 1);
+
+        map.insert(0, &key, (&[], 0));
+        assert_eq!(map.read_value(0, &key), Some((vec![], 0)));
+
+        assert!(map.delete_key(0, &key));
+        assert_eq!(map.read_value(0, &key), None);
+        assert_eq!(map.bucket_len(0), 0);
+
+        map.insert(0, &key, (&slot_list, 2));
+        assert_eq!(map.take(0, &key), Some((slot_list, 2)));
+        assert_eq!(map.take(0, &key), None);
+    }
+
+    #[test]
+    fn test_bucket_map_grow() {
+        let map = new_map();
+        let mut expected = HashMap::new();
+        // enough keys to grow the index and data files several times
+        for i in 0..2000u64 {
+            let key = Pubkey::new_unique();
+            let slot_list: Vec<_> = (0..i % 7).map(|slot| (slot, i)).collect();
+            map.insert(0, &key, (&slot_list, i));
+            expected.insert(key, (slot_list, i));
+        }
+        assert_eq!(map.bucket_len(0), expected.len());
+        for (key, value) in &expected {
+            assert_eq!(map.read_value(0, key).as_ref(), Some(value));
+        }
+
+        let keys: Vec<_> = expected.keys().cloned().collect();
+        for key in keys.iter().step_by(2) {
+            assert!(map.delete_key(0, key));
+            expected.remove(key);
+        }
+        let mut items = map.items_in_range(0, &None::<std::ops::RangeFull>);
+        items.sort_by_key(|(key, _, _)| *key);
+        let mut expected: Vec<_> = expected
+            .into_iter()
+            .map(|(key, (slot_list, ref_count))| (key, slot_list, ref_count))
+            .collect();
+        expected.sort_by_key(|(key, _, _)| *key);
+        assert_eq!(items, expected);
+
+        let range = Some(expected[10].0..expected[20].0);
+        let mut items = map.items_in_range(0, &range);
+        items.sort_by_key(|(key, _, _)| *key);
+        assert_eq!(items, expected[10..20].to_vec());
+    }
+
+    #[test]
+    fn test_bucket_map_drives() {
+        let drive = TempDir::new().unwrap();
+        let path = drive.path().join("accounts_index");
+        fs::create_dir_all(&path).unwrap();
+        let stale = path.join("stale");
+        fs::write(&stale, b"left over from a previous run").unwrap();
+
+        let map = BucketMap::<u64>::new(BucketMapConfig {
+            max_buckets: 2,
+            drives: Some(vec![path.clone()]),
+        });
+        assert!(!stale.exists());
+
+        map.insert(1, &Pubkey::new_unique(), (&[(1, 1)], 1));
+        assert_eq!(map.bucket_len(0), 0);
+        assert_eq!(map.bucket_len(1), 1);
+        assert_eq!(fs::read_dir(&path).unwrap().count(), 2);
+        drop(map);
+        assert_eq!(fs::read_dir(&path).unwrap().count(), 0);
+    }
+}
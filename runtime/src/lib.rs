@@ -6,6 +6,7 @@ pub mod accounts_cache;
 pub mod accounts_db;
 pub mod accounts_hash;
 pub mod accounts_index;
+pub mod accounts_update_notifier_interface;
 pub mod ancestors;
 pub mod append_vec;
 pub mod bank;
@@ -14,6 +15,7 @@ pub mod bank_forks;
 pub mod bank_utils;
 pub mod blockhash_queue;
 pub mod bloom;
+pub mod bucket_map;
 pub mod builtins;
 pub mod commitment;
 pub mod contains;
@@ -76,6 +76,9 @@ pub struct ExecuteDetailsTimings {
     pub total_data_size: usize,
     pub data_size_changed: usize,
     pub per_program_timings: HashMap<Pubkey, ProgramTiming>,
+    /// Compute units consumed by each top-level instruction, in execution order.  Only
+    /// recorded when initialized to `Some`, as when simulating a transaction
+    pub per_instruction_units: Option<Vec<u64>>,
 }
 impl ExecuteDetailsTimings {
     pub fn accumulate(&mut self, other: &ExecuteDetailsTimings) {
@@ -97,6 +100,12 @@ impl ExecuteDetailsTimings {
                 .saturating_add(other.accumulated_units);
             program_timing.count = program_timing.count.saturating_add(other.count);
         }
+        if let (Some(per_instruction_units), Some(other)) = (
+            self.per_instruction_units.as_mut(),
+            other.per_instruction_units.as_ref(),
+        ) {
+            per_instruction_units.extend(other);
+        }
     }
     pub fn accumulate_program(&mut self, program_id: &Pubkey, us: u64, units: u64) {
         let program_timing = self.per_program_timings.entry(*program_id).or_default();
@@ -1278,12 +1287,16 @@ impl MessageProcessor {
                 .map_err(|err| TransactionError::InstructionError(instruction_index as u8, err));
             time.stop();
             let post_remaining_units = compute_meter.borrow().get_remaining();
+            let units_consumed = pre_remaining_units - post_remaining_units;
 
             timings.accumulate_program(
                 instruction.program_id(&message.account_keys),
                 time.as_us(),
-                pre_remaining_units - post_remaining_units,
+                units_consumed,
             );
+            if let Some(per_instruction_units) = timings.per_instruction_units.as_mut() {
+                per_instruction_units.push(units_consumed);
+            }
 
             err?;
         }
@@ -2,7 +2,8 @@ use {
     crate::{
         accounts::Accounts,
         accounts_db::{
-            AccountShrinkThreshold, AccountStorageEntry, AccountsDb, AppendVecId, BankHashInfo,
+            AccountShrinkThreshold, AccountStorageEntry, AccountsDb, AccountsDbConfig, AppendVecId,
+            BankHashInfo,
         },
         accounts_index::AccountSecondaryIndexes,
         ancestors::Ancestors,
@@ -197,6 +198,7 @@ pub(crate) fn bank_from_streams<R>(
     limit_load_slot_count_from_snapshot: Option<usize>,
     shrink_ratio: AccountShrinkThreshold,
     verify_index: bool,
+    accounts_db_config: Option<AccountsDbConfig>,
 ) -> std::result::Result<Bank, Error>
 where
     R: Read,
@@ -234,6 +236,7 @@ where
                 limit_load_slot_count_from_snapshot,
                 shrink_ratio,
                 verify_index,
+                accounts_db_config,
             )?;
             Ok(bank)
         }};
@@ -326,6 +329,7 @@ fn reconstruct_bank_from_fields<E>(
     limit_load_slot_count_from_snapshot: Option<usize>,
     shrink_ratio: AccountShrinkThreshold,
     verify_index: bool,
+    accounts_db_config: Option<AccountsDbConfig>,
 ) -> Result<Bank, Error>
 where
     E: SerializableStorage + std::marker::Sync,
@@ -340,6 +344,7 @@ where
         limit_load_slot_count_from_snapshot,
         shrink_ratio,
         verify_index,
+        accounts_db_config,
     )?;
     accounts_db.freeze_accounts(
         &Ancestors::from(&bank_fields.ancestors),
@@ -390,6 +395,7 @@ fn reconstruct_accountsdb_from_fields<E>(
     limit_load_slot_count_from_snapshot: Option<usize>,
     shrink_ratio: AccountShrinkThreshold,
     verify_index: bool,
+    accounts_db_config: Option<AccountsDbConfig>,
 ) -> Result<AccountsDb, Error>
 where
     E: SerializableStorage + std::marker::Sync,
@@ -400,6 +406,7 @@ where
         account_secondary_indexes,
         caching_enabled,
         shrink_ratio,
+        accounts_db_config,
     );
 
     let AccountsDbFields(
@@ -81,6 +81,7 @@ where
         None,
         AccountShrinkThreshold::default(),
         false,
+        None,
     )
 }
 
@@ -137,6 +138,7 @@ fn test_accounts_serialize_style(serde_style: SerdeStyle) {
         AccountSecondaryIndexes::default(),
         false,
         AccountShrinkThreshold::default(),
+        None,
     );
 
     let mut pubkeys: Vec<Pubkey> = vec![];
@@ -242,6 +244,7 @@ fn test_bank_serialize_style(serde_style: SerdeStyle) {
         None,
         AccountShrinkThreshold::default(),
         false,
+        None,
     )
     .unwrap();
     dbank.src = ref_sc;
@@ -9,6 +9,10 @@ pub struct SnapshotConfig {
     /// Generate a new snapshot every this many slots
     pub snapshot_interval_slots: Slot,
 
+    /// Generate a new incremental snapshot every this many slots, relative to the last full
+    /// snapshot.  `Slot::MAX` disables incremental snapshots.
+    pub incremental_snapshot_archive_interval_slots: Slot,
+
     /// Where to store the latest packaged snapshot
     pub snapshot_package_output_path: PathBuf,
 
@@ -13,6 +13,32 @@ pub type AccountsPackageSender = Sender<AccountsPackagePre>;
 pub type AccountsPackageReceiver = Receiver<AccountsPackagePre>;
 pub type AccountsPackageSendError = SendError<AccountsPackagePre>;
 
+/// Snapshots come in two flavors, full and incremental.  `IncrementalSnapshot` carries the
+/// incremental snapshot base slot, which is the slot of the full snapshot it builds on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SnapshotType {
+    FullSnapshot,
+    IncrementalSnapshot(Slot),
+}
+
+impl SnapshotType {
+    pub fn is_full_snapshot(&self) -> bool {
+        matches!(self, SnapshotType::FullSnapshot)
+    }
+
+    pub fn is_incremental_snapshot(&self) -> bool {
+        matches!(self, SnapshotType::IncrementalSnapshot(_))
+    }
+
+    /// The base slot if this is an incremental snapshot
+    pub fn incremental_snapshot_base_slot(&self) -> Option<Slot> {
+        match self {
+            SnapshotType::FullSnapshot => None,
+            SnapshotType::IncrementalSnapshot(base_slot) => Some(*base_slot),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AccountsPackagePre {
     pub slot: Slot,
@@ -27,6 +53,9 @@ pub struct AccountsPackagePre {
     pub expected_capitalization: u64,
     pub hash_for_testing: Option<Hash>,
     pub cluster_type: ClusterType,
+    /// The kind of snapshot archive to create from this package, if any.  Packages without a
+    /// snapshot type are only used to calculate and verify the accounts hash.
+    pub snapshot_type: Option<SnapshotType>,
 }
 
 impl AccountsPackagePre {
@@ -44,6 +73,7 @@ impl AccountsPackagePre {
         expected_capitalization: u64,
         hash_for_testing: Option<Hash>,
         cluster_type: ClusterType,
+        snapshot_type: Option<SnapshotType>,
     ) -> Self {
         Self {
             slot,
@@ -58,6 +88,7 @@ impl AccountsPackagePre {
             expected_capitalization,
             hash_for_testing,
             cluster_type,
+            snapshot_type,
         }
     }
 }
@@ -1,6 +1,6 @@
 use {
     crate::{
-        accounts_db::{AccountShrinkThreshold, AccountsDb},
+        accounts_db::{AccountShrinkThreshold, AccountsDb, AccountsDbConfig},
         accounts_index::AccountSecondaryIndexes,
         bank::{Bank, BankSlotDelta, Builtins},
         hardened_unpack::{unpack_snapshot, ParallelSelector, UnpackError, UnpackedAppendVecMap},
@@ -11,6 +11,7 @@ use {
         shared_buffer_reader::{SharedBuffer, SharedBufferReader},
         snapshot_package::{
             AccountsPackage, AccountsPackagePre, AccountsPackageSendError, AccountsPackageSender,
+            SnapshotType,
         },
         sorted_storages::SortedStorages,
     },
@@ -133,23 +134,23 @@ impl IncrementalSnapshotArchiveInfo {
         })
     }
 
-    fn path(&self) -> &PathBuf {
+    pub fn path(&self) -> &PathBuf {
         &self.inner.path
     }
 
-    fn base_slot(&self) -> &Slot {
+    pub fn base_slot(&self) -> &Slot {
         &self.base_slot
     }
 
-    fn slot(&self) -> &Slot {
+    pub fn slot(&self) -> &Slot {
         &self.inner.slot
     }
 
-    fn _hash(&self) -> &Hash {
+    pub fn hash(&self) -> &Hash {
         &self.inner.hash
     }
 
-    fn _archive_format(&self) -> &ArchiveFormat {
+    pub fn archive_format(&self) -> &ArchiveFormat {
         &self.inner.archive_format
     }
 }
@@ -324,6 +325,11 @@ pub enum SnapshotError {
 
     #[error("snapshots are incompatible: full snapshot slot ({0}) and incremental snapshot base slot ({1}) do not match")]
     MismatchedBaseSlot(Slot, Slot),
+
+    #[error(
+        "snapshot archive slot ({0}) does not match the slot of the bank snapshot inside it ({1})"
+    )]
+    MismatchedSlot(Slot, Slot),
 }
 pub type Result<T> = std::result::Result<T, SnapshotError>;
 
@@ -363,6 +369,7 @@ where
         snapshot_version,
         hash_for_testing,
         snapshot_tmpdir,
+        Some(SnapshotType::FullSnapshot),
     )
 }
 
@@ -417,9 +424,13 @@ where
         snapshot_version,
         hash_for_testing,
         snapshot_tmpdir,
+        Some(SnapshotType::IncrementalSnapshot(
+            incremental_snapshot_base_slot,
+        )),
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn do_package_snapshot<P>(
     bank: &Bank,
     bank_snapshot_info: &BankSnapshotInfo,
@@ -430,6 +441,7 @@ fn do_package_snapshot<P>(
     snapshot_version: SnapshotVersion,
     hash_for_testing: Option<Hash>,
     snapshot_tmpdir: TempDir,
+    snapshot_type: Option<SnapshotType>,
 ) -> Result<AccountsPackagePre>
 where
     P: AsRef<Path>,
@@ -461,6 +473,7 @@ where
         bank.capitalization(),
         hash_for_testing,
         bank.cluster_type(),
+        snapshot_type,
     );
 
     Ok(package)
@@ -551,6 +564,8 @@ pub fn archive_snapshot_package(
     .map_err(|e| SnapshotError::IoWithSource(e, "create staging symlinks"))?;
 
     // Add the AppendVecs into the compressible list
+    let mut cold_storage_bytes = 0;
+    let mut cold_storage_us = 0;
     for storage in snapshot_package.storages.iter().flatten() {
         storage.flush()?;
         let storage_path = storage.get_path();
@@ -559,6 +574,22 @@ pub fn archive_snapshot_package(
             storage.append_vec_id(),
         ));
 
+        // Snapshots hold AppendVecs in the uncompressed format, so unlike other storages, which
+        // are symlinked, cold storages are decompressed and written out in full to the staging
+        // directory for every snapshot. That costs their uncompressed size in disk space and
+        // writes, and the time it takes is reported in the datapoint below.
+        if storage.accounts.is_compressed() {
+            let mut write_time = Measure::start("write_uncompressed");
+            storage
+                .accounts
+                .write_uncompressed(&output_path)
+                .map_err(|e| SnapshotError::IoWithSource(e, "write uncompressed storage"))?;
+            write_time.stop();
+            cold_storage_us += write_time.as_us();
+            cold_storage_bytes += storage.written_bytes();
+            continue;
+        }
+
         // `storage_path` - The file path where the AppendVec itself is located
         // `output_path` - The file path where the AppendVec will be placed in the staging directory.
         let storage_path =
@@ -666,7 +697,9 @@ pub fn archive_snapshot_package(
         "snapshot-package",
         ("slot", snapshot_package.slot, i64),
         ("duration_ms", timer.as_ms(), i64),
-        ("size", metadata.len(), i64)
+        ("size", metadata.len(), i64),
+        ("cold_storage_us", cold_storage_us, i64),
+        ("cold_storage_bytes", cold_storage_bytes, i64)
     );
     Ok(())
 }
@@ -988,6 +1021,13 @@ pub struct BankFromArchiveTimings {
 // From testing, 4 seems to be a sweet spot for ranges of 60M-360M accounts and 16-64 cores. This may need to be tuned later.
 const PARALLEL_UNTAR_READERS_DEFAULT: usize = 4;
 
+fn parallel_untar_readers() -> usize {
+    std::cmp::min(
+        PARALLEL_UNTAR_READERS_DEFAULT,
+        std::cmp::max(1, num_cpus::get() / 4),
+    )
+}
+
 /// Rebuild bank from snapshot archives.  Handles either just a full snapshot, or both a full
 /// snapshot and an incremental snapshot.
 #[allow(clippy::too_many_arguments)]
@@ -1007,14 +1047,12 @@ pub fn bank_from_snapshot_archives<P>(
     shrink_ratio: AccountShrinkThreshold,
     test_hash_calculation: bool,
     verify_index: bool,
+    accounts_db_config: Option<AccountsDbConfig>,
 ) -> Result<(Bank, BankFromArchiveTimings)>
 where
     P: AsRef<Path> + std::marker::Sync,
 {
-    let parallel_divisions = std::cmp::min(
-        PARALLEL_UNTAR_READERS_DEFAULT,
-        std::cmp::max(1, num_cpus::get() / 4),
-    );
+    let parallel_divisions = parallel_untar_readers();
 
     let unarchived_full_snapshot = unarchive_snapshot(
         snapshots_dir,
@@ -1073,12 +1111,24 @@ where
         limit_load_slot_count_from_snapshot,
         shrink_ratio,
         verify_index,
+        accounts_db_config,
     )?;
     measure_rebuild.stop();
     info!("{}", measure_rebuild);
 
+    // When booting from a full+incremental pair, zero-lamport accounts updated after the full
+    // snapshot must survive the startup clean, see `AccountsDb::clean_accounts()`.
+    let last_full_snapshot_slot = if unarchived_incremental_snapshot.is_some() {
+        let full_snapshot_filename = path_to_file_name_str(full_snapshot_archive_path.as_ref())?;
+        let (full_snapshot_slot, _, _) =
+            parse_full_snapshot_archive_filename(full_snapshot_filename)?;
+        Some(full_snapshot_slot)
+    } else {
+        None
+    };
+
     let mut measure_verify = Measure::start("verify");
-    if !bank.verify_snapshot_bank(test_hash_calculation)
+    if !bank.verify_snapshot_bank(test_hash_calculation, last_full_snapshot_slot)
         && limit_load_slot_count_from_snapshot.is_none()
     {
         panic!("Snapshot bank for slot {} failed to verify", bank.slot());
@@ -1148,6 +1198,271 @@ where
     })
 }
 
+/// A snapshot archive unpacked by `unpack_snapshot_archive()`.  The unpacked bank snapshot is
+/// removed when this is dropped; the account storages remain in the account paths.
+#[derive(Debug)]
+pub struct UnpackedSnapshotArchive {
+    /// Slot parsed from the archive's file name
+    pub archive_slot: Slot,
+
+    /// Accounts hash parsed from the archive's file name
+    pub archive_hash: Hash,
+
+    /// For an incremental snapshot archive, the slot of the full snapshot it is based on
+    pub base_slot: Option<Slot>,
+
+    /// Snapshot version embedded in the archive
+    pub snapshot_version: SnapshotVersion,
+
+    /// Slot, file name and size in bytes of each account storage, ordered by slot
+    pub storages: Vec<(Slot, String, u64)>,
+
+    unarchived_snapshot: UnarchivedSnapshot,
+}
+
+/// Unpack a full or incremental snapshot archive without rebuilding a bank from it.  The archive
+/// is streamed through `hardened_unpack`, its embedded snapshot version is validated, and the
+/// slot of the bank snapshot inside it is checked against the archive's file name.
+pub fn unpack_snapshot_archive<P: AsRef<Path>>(
+    snapshots_dir: &Path,
+    snapshot_archive_path: P,
+    account_paths: &[PathBuf],
+) -> Result<UnpackedSnapshotArchive> {
+    let snapshot_archive_path = snapshot_archive_path.as_ref();
+    let (base_slot, archive_slot, archive_hash, archive_format) =
+        parse_snapshot_archive_path(snapshot_archive_path)?;
+
+    let unarchived_snapshot = unarchive_snapshot(
+        snapshots_dir,
+        if base_slot.is_some() {
+            TMP_INCREMENTAL_SNAPSHOT_PREFIX
+        } else {
+            TMP_FULL_SNAPSHOT_PREFIX
+        },
+        snapshot_archive_path,
+        "snapshot untar",
+        account_paths,
+        archive_format,
+        parallel_untar_readers(),
+    )?;
+
+    let (snapshot_version, bank_snapshot_info) = verify_unpacked_snapshots_dir_and_version(
+        &unarchived_snapshot.unpacked_snapshots_dir_and_version,
+    )?;
+    if bank_snapshot_info.slot != archive_slot {
+        return Err(SnapshotError::MismatchedSlot(
+            archive_slot,
+            bank_snapshot_info.slot,
+        ));
+    }
+
+    let mut storages = unarchived_snapshot
+        .unpacked_append_vec_map
+        .iter()
+        .map(|(file_name, path)| {
+            Ok((
+                parse_storage_slot(file_name)?,
+                file_name.clone(),
+                fs::metadata(path)?.len(),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    storages.sort_unstable();
+
+    Ok(UnpackedSnapshotArchive {
+        archive_slot,
+        archive_hash,
+        base_slot,
+        snapshot_version,
+        storages,
+        unarchived_snapshot,
+    })
+}
+
+/// Parse the base slot (for an incremental snapshot archive), slot, accounts hash and archive
+/// format from a snapshot archive's file name
+fn parse_snapshot_archive_path(
+    snapshot_archive_path: &Path,
+) -> Result<(Option<Slot>, Slot, Hash, ArchiveFormat)> {
+    let archive_filename = path_to_file_name_str(snapshot_archive_path)?;
+    match parse_full_snapshot_archive_filename(archive_filename) {
+        Ok((slot, hash, archive_format)) => Ok((None, slot, hash, archive_format)),
+        Err(_) => {
+            let (base_slot, slot, hash, archive_format) =
+                parse_incremental_snapshot_archive_filename(archive_filename)?;
+            Ok((Some(base_slot), slot, hash, archive_format))
+        }
+    }
+}
+
+/// Parse the slot from an account storage file name, "<slot>.<id>"
+fn parse_storage_slot(file_name: &str) -> Result<Slot> {
+    file_name
+        .split('.')
+        .next()
+        .and_then(|slot| slot.parse().ok())
+        .ok_or_else(|| get_io_error(&format!("invalid account storage file name: {}", file_name)))
+}
+
+/// The contents of a snapshot archive, as listed by `inspect_snapshot_archive()`
+#[derive(Debug, PartialEq)]
+pub struct SnapshotArchiveContents {
+    /// Slot parsed from the archive's file name
+    pub archive_slot: Slot,
+
+    /// Accounts hash parsed from the archive's file name
+    pub archive_hash: Hash,
+
+    /// For an incremental snapshot archive, the slot of the full snapshot it is based on
+    pub base_slot: Option<Slot>,
+
+    /// Snapshot version embedded in the archive
+    pub snapshot_version: SnapshotVersion,
+
+    /// Slot, file name and size in bytes of each account storage, ordered by slot
+    pub storages: Vec<(Slot, String, u64)>,
+}
+
+/// List the contents of a full or incremental snapshot archive without unpacking it.  Only the
+/// version file is read; the sizes of the account storages come from their tar headers.  As
+/// with `unpack_snapshot_archive()`, the snapshot version is validated and the slot of the bank
+/// snapshot inside the archive is checked against the archive's file name.
+pub fn inspect_snapshot_archive<P: AsRef<Path>>(
+    snapshot_archive_path: P,
+) -> Result<SnapshotArchiveContents> {
+    let snapshot_archive_path = snapshot_archive_path.as_ref();
+    let (base_slot, archive_slot, archive_hash, archive_format) =
+        parse_snapshot_archive_path(snapshot_archive_path)?;
+
+    let file = BufReader::new(File::open(snapshot_archive_path)?);
+    let reader: Box<dyn Read> = match archive_format {
+        ArchiveFormat::TarBzip2 => Box::new(BzDecoder::new(file)),
+        ArchiveFormat::TarGzip => Box::new(GzDecoder::new(file)),
+        ArchiveFormat::TarZstd => Box::new(zstd::stream::read::Decoder::new(file)?),
+        ArchiveFormat::Tar => Box::new(file),
+    };
+    let mut archive = Archive::new(reader);
+
+    let mut snapshot_version = None;
+    let mut bank_snapshot_slots = HashSet::new();
+    let mut storages = vec![];
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let parts: Vec<_> = path.iter().filter_map(|part| part.to_str()).collect();
+        match parts.as_slice() {
+            ["version"] => {
+                let mut version = String::new();
+                entry.read_to_string(&mut version)?;
+                snapshot_version = Some(version.trim().to_string());
+            }
+            ["accounts", file_name] => {
+                storages.push((
+                    parse_storage_slot(file_name)?,
+                    file_name.to_string(),
+                    entry.header().size()?,
+                ));
+            }
+            ["snapshots", dir, _file] => {
+                if let Ok(slot) = dir.parse::<Slot>() {
+                    bank_snapshot_slots.insert(slot);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let snapshot_version =
+        snapshot_version.ok_or_else(|| get_io_error("snapshot archive has no version file"))?;
+    let snapshot_version =
+        SnapshotVersion::maybe_from_string(&snapshot_version).ok_or_else(|| {
+            get_io_error(&format!(
+                "unsupported snapshot version: {}",
+                snapshot_version
+            ))
+        })?;
+    if bank_snapshot_slots.len() > 1 {
+        return Err(get_io_error("invalid snapshot format"));
+    }
+    let bank_snapshot_slot = bank_snapshot_slots
+        .into_iter()
+        .next()
+        .ok_or_else(|| get_io_error("No snapshots found in snapshots directory"))?;
+    if bank_snapshot_slot != archive_slot {
+        return Err(SnapshotError::MismatchedSlot(
+            archive_slot,
+            bank_snapshot_slot,
+        ));
+    }
+    storages.sort_unstable();
+
+    Ok(SnapshotArchiveContents {
+        archive_slot,
+        archive_hash,
+        base_slot,
+        snapshot_version,
+        storages,
+    })
+}
+
+/// Rebuild a bank from a full snapshot archive, and optionally an incremental snapshot archive,
+/// unpacked by `unpack_snapshot_archive()`.  Unlike `bank_from_snapshot_archives()`, the bank is
+/// not verified; see `Bank::verify_snapshot_bank()`.
+pub fn bank_from_unpacked_snapshot_archives(
+    account_paths: &[PathBuf],
+    full_snapshot: UnpackedSnapshotArchive,
+    mut incremental_snapshot: Option<UnpackedSnapshotArchive>,
+    genesis_config: &GenesisConfig,
+    accounts_db_config: Option<AccountsDbConfig>,
+) -> Result<Bank> {
+    if full_snapshot.base_slot.is_some() {
+        return Err(get_io_error("expected a full snapshot archive"));
+    }
+    if let Some(incremental_snapshot) = &incremental_snapshot {
+        let base_slot = incremental_snapshot
+            .base_slot
+            .ok_or_else(|| get_io_error("expected an incremental snapshot archive"))?;
+        if base_slot != full_snapshot.archive_slot {
+            return Err(SnapshotError::MismatchedBaseSlot(
+                full_snapshot.archive_slot,
+                base_slot,
+            ));
+        }
+    }
+
+    let mut unpacked_append_vec_map = full_snapshot.unarchived_snapshot.unpacked_append_vec_map;
+    if let Some(ref mut incremental_snapshot) = incremental_snapshot {
+        unpacked_append_vec_map.extend(std::mem::take(
+            &mut incremental_snapshot
+                .unarchived_snapshot
+                .unpacked_append_vec_map,
+        ));
+    }
+
+    rebuild_bank_from_snapshots(
+        &full_snapshot
+            .unarchived_snapshot
+            .unpacked_snapshots_dir_and_version,
+        incremental_snapshot.as_ref().map(|incremental_snapshot| {
+            &incremental_snapshot
+                .unarchived_snapshot
+                .unpacked_snapshots_dir_and_version
+        }),
+        &[],
+        account_paths,
+        unpacked_append_vec_map,
+        genesis_config,
+        None,
+        None,
+        AccountSecondaryIndexes::default(),
+        false,
+        None,
+        AccountShrinkThreshold::default(),
+        false,
+        accounts_db_config,
+    )
+}
+
 /// Check if an incremental snapshot is compatible with a full snapshot.  This function parses the
 /// paths to see if the incremental snapshot's base slot is the same as the full snapshot's slot.
 /// Return an error if they are incompatible (or if the paths cannot be parsed), otherwise return a
@@ -1563,6 +1878,7 @@ fn rebuild_bank_from_snapshots(
     limit_load_slot_count_from_snapshot: Option<usize>,
     shrink_ratio: AccountShrinkThreshold,
     verify_index: bool,
+    accounts_db_config: Option<AccountsDbConfig>,
 ) -> Result<Bank> {
     let (full_snapshot_version, full_snapshot_root_paths) =
         verify_unpacked_snapshots_dir_and_version(
@@ -1610,6 +1926,7 @@ fn rebuild_bank_from_snapshots(
                     limit_load_slot_count_from_snapshot,
                     shrink_ratio,
                     verify_index,
+                    accounts_db_config,
                 ),
             }?,
         )
@@ -1716,6 +2033,11 @@ where
 }
 
 /// Gather the necessary elements for a snapshot of the given `root_bank`
+///
+/// The `snapshot_type` determines which storages are packaged: an incremental snapshot only
+/// contains the storages newer than its base slot.  Packages without a snapshot type contain all
+/// storages and are not archived.
+#[allow(clippy::too_many_arguments)]
 pub fn snapshot_bank(
     root_bank: &Bank,
     status_cache_slot_deltas: Vec<BankSlotDelta>,
@@ -1725,8 +2047,14 @@ pub fn snapshot_bank(
     snapshot_version: SnapshotVersion,
     archive_format: &ArchiveFormat,
     hash_for_testing: Option<Hash>,
+    snapshot_type: Option<SnapshotType>,
 ) -> Result<()> {
-    let storages: Vec<_> = root_bank.get_snapshot_storages();
+    let incremental_snapshot_base_slot =
+        snapshot_type.and_then(|snapshot_type| snapshot_type.incremental_snapshot_base_slot());
+    let storages: Vec<_> = match incremental_snapshot_base_slot {
+        None => root_bank.get_snapshot_storages(),
+        Some(base_slot) => root_bank.get_incremental_snapshot_storages(base_slot),
+    };
     let mut add_snapshot_time = Measure::start("add-snapshot-ms");
     add_bank_snapshot(snapshots_dir, root_bank, &storages, snapshot_version)?;
     add_snapshot_time.stop();
@@ -1736,17 +2064,32 @@ pub fn snapshot_bank(
     let highest_bank_snapshot_info = get_highest_bank_snapshot_info(snapshots_dir)
         .expect("no snapshots found in config snapshots_dir");
 
-    let package = package_full_snapshot(
-        root_bank,
-        &highest_bank_snapshot_info,
-        snapshots_dir,
-        status_cache_slot_deltas,
-        snapshot_package_output_path,
-        storages,
-        *archive_format,
-        snapshot_version,
-        hash_for_testing,
-    )?;
+    let mut package = match incremental_snapshot_base_slot {
+        None => package_full_snapshot(
+            root_bank,
+            &highest_bank_snapshot_info,
+            snapshots_dir,
+            status_cache_slot_deltas,
+            snapshot_package_output_path,
+            storages,
+            *archive_format,
+            snapshot_version,
+            hash_for_testing,
+        )?,
+        Some(base_slot) => package_incremental_snapshot(
+            root_bank,
+            base_slot,
+            &highest_bank_snapshot_info,
+            snapshots_dir,
+            status_cache_slot_deltas,
+            snapshot_package_output_path,
+            storages,
+            *archive_format,
+            snapshot_version,
+            hash_for_testing,
+        )?,
+    };
+    package.snapshot_type = snapshot_type;
 
     accounts_package_sender.send(package)?;
 
@@ -1772,7 +2115,7 @@ pub fn bank_to_full_snapshot_archive<P: AsRef<Path>, Q: AsRef<Path>>(
     assert!(bank.is_complete());
     bank.squash(); // Bank may not be a root
     bank.force_flush_accounts_cache();
-    bank.clean_accounts(true, false);
+    bank.clean_accounts(true, false, None);
     bank.update_accounts_hash();
     bank.rehash(); // Bank accounts may have been manually modified by the caller
 
@@ -1820,7 +2163,7 @@ pub fn bank_to_incremental_snapshot_archive<P: AsRef<Path>, Q: AsRef<Path>>(
     assert!(bank.slot() > full_snapshot_slot);
     bank.squash(); // Bank may not be a root
     bank.force_flush_accounts_cache();
-    bank.clean_accounts(true, false);
+    bank.clean_accounts(true, false, None);
     bank.update_accounts_hash();
     bank.rehash(); // Bank accounts may have been manually modified by the caller
 
@@ -2539,12 +2882,149 @@ mod tests {
             AccountShrinkThreshold::default(),
             false,
             false,
+            None,
         )
         .unwrap();
 
         assert_eq!(original_bank, roundtrip_bank);
     }
 
+    #[test]
+    fn test_unpack_snapshot_archive() {
+        solana_logger::setup();
+        let genesis_config = GenesisConfig::default();
+        let original_bank = Bank::new(&genesis_config);
+
+        while !original_bank.is_complete() {
+            original_bank.register_tick(&Hash::new_unique());
+        }
+
+        let accounts_dir = tempfile::TempDir::new().unwrap();
+        let snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+
+        let snapshot_archive_path = bank_to_full_snapshot_archive(
+            snapshots_dir.path(),
+            &original_bank,
+            None,
+            snapshot_archives_dir.path(),
+            ArchiveFormat::Tar,
+            None,
+            1,
+        )
+        .unwrap();
+
+        let account_paths = &[PathBuf::from(accounts_dir.path())];
+        let unpacked_snapshot =
+            unpack_snapshot_archive(snapshots_dir.path(), &snapshot_archive_path, account_paths)
+                .unwrap();
+        assert_eq!(unpacked_snapshot.archive_slot, original_bank.slot());
+        assert_eq!(
+            unpacked_snapshot.archive_hash,
+            original_bank.get_accounts_hash()
+        );
+        assert_eq!(unpacked_snapshot.base_slot, None);
+        assert_eq!(
+            unpacked_snapshot.snapshot_version,
+            SnapshotVersion::default()
+        );
+        assert!(!unpacked_snapshot.storages.is_empty());
+        for (slot, file_name, size) in &unpacked_snapshot.storages {
+            assert_eq!(*slot, original_bank.slot());
+            assert_eq!(
+                *size,
+                fs::metadata(accounts_dir.path().join("accounts").join(file_name))
+                    .unwrap()
+                    .len()
+            );
+        }
+
+        let roundtrip_bank = bank_from_unpacked_snapshot_archives(
+            account_paths,
+            unpacked_snapshot,
+            None,
+            &genesis_config,
+            None,
+        )
+        .unwrap();
+        assert!(roundtrip_bank.verify_snapshot_bank(false, None));
+        assert_eq!(original_bank, roundtrip_bank);
+
+        // The slot in the file name must match the bank snapshot inside the archive
+        let renamed_archive_path = build_full_snapshot_archive_path(
+            snapshot_archives_dir.path().to_path_buf(),
+            original_bank.slot() + 1,
+            &original_bank.get_accounts_hash(),
+            ArchiveFormat::Tar,
+        );
+        fs::rename(&snapshot_archive_path, &renamed_archive_path).unwrap();
+        assert_matches!(
+            unpack_snapshot_archive(snapshots_dir.path(), &renamed_archive_path, account_paths),
+            Err(SnapshotError::MismatchedSlot(_, _))
+        );
+    }
+
+    #[test]
+    fn test_inspect_snapshot_archive() {
+        solana_logger::setup();
+        let genesis_config = GenesisConfig::default();
+        let original_bank = Bank::new(&genesis_config);
+
+        while !original_bank.is_complete() {
+            original_bank.register_tick(&Hash::new_unique());
+        }
+
+        let accounts_dir = tempfile::TempDir::new().unwrap();
+        let snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+
+        let snapshot_archive_path = bank_to_full_snapshot_archive(
+            snapshots_dir.path(),
+            &original_bank,
+            None,
+            snapshot_archives_dir.path(),
+            ArchiveFormat::TarZstd,
+            None,
+            1,
+        )
+        .unwrap();
+
+        // Listing the archive agrees with unpacking it
+        let contents = inspect_snapshot_archive(&snapshot_archive_path).unwrap();
+        let account_paths = &[PathBuf::from(accounts_dir.path())];
+        let unpacked_snapshot =
+            unpack_snapshot_archive(snapshots_dir.path(), &snapshot_archive_path, account_paths)
+                .unwrap();
+        assert_eq!(
+            contents,
+            SnapshotArchiveContents {
+                archive_slot: unpacked_snapshot.archive_slot,
+                archive_hash: unpacked_snapshot.archive_hash,
+                base_slot: unpacked_snapshot.base_slot,
+                snapshot_version: unpacked_snapshot.snapshot_version,
+                storages: unpacked_snapshot.storages.clone(),
+            }
+        );
+
+        let renamed_archive_path = build_full_snapshot_archive_path(
+            snapshot_archives_dir.path().to_path_buf(),
+            original_bank.slot() + 1,
+            &original_bank.get_accounts_hash(),
+            ArchiveFormat::TarZstd,
+        );
+        fs::rename(&snapshot_archive_path, &renamed_archive_path).unwrap();
+        assert_matches!(
+            inspect_snapshot_archive(&renamed_archive_path),
+            Err(SnapshotError::MismatchedSlot(_, _))
+        );
+    }
+
+    #[test]
+    fn test_parse_storage_slot() {
+        assert_eq!(parse_storage_slot("42.7").unwrap(), 42);
+        assert!(parse_storage_slot("x.7").is_err());
+    }
+
     /// Test roundtrip of bank to a full snapshot, then back again.  This test is more involved
     /// than the simple version above; creating multiple banks over multiple slots and doing
     /// multiple transfers.  So this full snapshot should contain more data.
@@ -2629,6 +3109,7 @@ mod tests {
             AccountShrinkThreshold::default(),
             false,
             false,
+            None,
         )
         .unwrap();
 
@@ -2738,6 +3219,7 @@ mod tests {
             AccountShrinkThreshold::default(),
             false,
             false,
+            None,
         )
         .unwrap();
 
@@ -1372,7 +1372,7 @@ mod tests {
             bank.force_flush_accounts_cache();
             // do clean and assert that it actually did its job
             assert_eq!(3, bank.get_snapshot_storages().len());
-            bank.clean_accounts(false, false);
+            bank.clean_accounts(false, false, None);
             assert_eq!(2, bank.get_snapshot_storages().len());
         });
     }
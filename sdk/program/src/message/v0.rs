@@ -0,0 +1,414 @@
+#![allow(clippy::integer_arithmetic)]
+//! Version 0 of the transaction message format. In addition to the statically
+//! listed account keys of a legacy [`Message`](super::Message), a v0 message may
+//! reference addresses stored in on-chain address lookup table accounts.
+
+use crate::{
+    hash::Hash,
+    instruction::CompiledInstruction,
+    message::{Message as LegacyMessage, MessageHeader},
+    pubkey::Pubkey,
+    sanitize::{Sanitize, SanitizeError},
+    short_vec,
+};
+
+/// Maximum number of accounts, static and looked up, that a v0 message may
+/// reference. Account indexes are encoded as a `u8`.
+pub const MAX_ACCOUNT_KEYS: usize = u8::MAX as usize + 1;
+
+/// Address table lookups describe an on-chain address lookup table to use
+/// for loading more readonly and writable accounts in a single tx.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, AbiExample)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageAddressTableLookup {
+    /// Address lookup table account key
+    pub account_key: Pubkey,
+    /// List of indexes used to load writable account addresses
+    #[serde(with = "short_vec")]
+    pub writable_indexes: Vec<u8>,
+    /// List of indexes used to load readonly account addresses
+    #[serde(with = "short_vec")]
+    pub readonly_indexes: Vec<u8>,
+}
+
+/// Collection of addresses loaded from on-chain lookup tables, split by
+/// readonly and writable.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedAddresses {
+    /// List of addresses for writable loaded accounts
+    pub writable: Vec<Pubkey>,
+    /// List of addresses for readonly loaded accounts
+    pub readonly: Vec<Pubkey>,
+}
+
+impl LoadedAddresses {
+    /// Return the total number of loaded addresses
+    pub fn len(&self) -> usize {
+        self.writable.len() + self.readonly.len()
+    }
+
+    /// Return true if no addresses were loaded
+    pub fn is_empty(&self) -> bool {
+        self.writable.is_empty() && self.readonly.is_empty()
+    }
+}
+
+/// Transaction message format which supports succinct account loading with
+/// on-chain address lookup tables.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, AbiExample)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    /// The message header, identifying signed and read-only `account_keys`
+    pub header: MessageHeader,
+
+    /// List of accounts loaded by this transaction, not including the
+    /// accounts loaded through address table lookups
+    #[serde(with = "short_vec")]
+    pub account_keys: Vec<Pubkey>,
+
+    /// The blockhash of a recent block.
+    pub recent_blockhash: Hash,
+
+    /// Instructions that invoke a designated program, are executed in sequence,
+    /// and committed in one atomic transaction if all succeed.
+    ///
+    /// Account and program indexes refer to the static `account_keys` first,
+    /// then to the writable addresses and finally the readonly addresses
+    /// loaded by `address_table_lookups`, in order.
+    #[serde(with = "short_vec")]
+    pub instructions: Vec<CompiledInstruction>,
+
+    /// List of address table lookups used to load additional accounts
+    /// for this transaction.
+    #[serde(with = "short_vec")]
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
+}
+
+impl Sanitize for Message {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        let num_static_account_keys = self.account_keys.len();
+
+        // signing area and read-only non-signing area should not overlap
+        if self.header.num_required_signatures as usize
+            + self.header.num_readonly_unsigned_accounts as usize
+            > num_static_account_keys
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        // there should be at least 1 RW fee-payer account.
+        if self.header.num_readonly_signed_accounts >= self.header.num_required_signatures {
+            return Err(SanitizeError::InvalidValue);
+        }
+
+        let mut num_loaded_accounts: usize = 0;
+        for lookup in &self.address_table_lookups {
+            let num_table_loaded_accounts =
+                lookup.writable_indexes.len() + lookup.readonly_indexes.len();
+
+            // each lookup table must be used to load at least one account
+            if num_table_loaded_accounts == 0 {
+                return Err(SanitizeError::InvalidValue);
+            }
+
+            num_loaded_accounts += num_table_loaded_accounts;
+        }
+
+        // the combined number of static and loaded accounts must be addressable
+        // by a u8 index
+        let total_account_keys = num_static_account_keys + num_loaded_accounts;
+        if total_account_keys > MAX_ACCOUNT_KEYS {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        for ci in &self.instructions {
+            // programs may only be invoked through the static account keys
+            if ci.program_id_index as usize >= num_static_account_keys {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            // A program cannot be a payer.
+            if ci.program_id_index == 0 {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            for ai in &ci.accounts {
+                if *ai as usize >= total_account_keys {
+                    return Err(SanitizeError::IndexOutOfBounds);
+                }
+            }
+        }
+        self.account_keys.sanitize()?;
+        self.recent_blockhash.sanitize()?;
+        self.instructions.sanitize()?;
+        Ok(())
+    }
+}
+
+impl Message {
+    /// Return the number of addresses this message loads from lookup tables
+    pub fn num_loaded_addresses(&self) -> usize {
+        self.address_table_lookups
+            .iter()
+            .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+            .sum()
+    }
+
+    /// Return the number of writable addresses this message loads from lookup tables
+    pub fn num_loaded_writable_addresses(&self) -> usize {
+        self.address_table_lookups
+            .iter()
+            .map(|lookup| lookup.writable_indexes.len())
+            .sum()
+    }
+
+    /// Combine the static account keys with `loaded_addresses` into a legacy
+    /// message that the runtime can execute directly.
+    ///
+    /// The resulting `account_keys` list keeps the legacy layout: signed keys,
+    /// then writable unsigned keys (static followed by loaded), then readonly
+    /// unsigned keys (static followed by loaded). Instruction account indexes
+    /// are remapped accordingly. The message must already be sanitized.
+    pub fn try_into_legacy_message(
+        self,
+        loaded_addresses: LoadedAddresses,
+    ) -> Result<LegacyMessage, SanitizeError> {
+        if loaded_addresses.writable.len() != self.num_loaded_writable_addresses()
+            || loaded_addresses.len() != self.num_loaded_addresses()
+        {
+            return Err(SanitizeError::InvalidValue);
+        }
+
+        let num_static_account_keys = self.account_keys.len();
+        let num_static_readonly_unsigned = self.header.num_readonly_unsigned_accounts as usize;
+        let num_static_writable = num_static_account_keys - num_static_readonly_unsigned;
+        let num_loaded_writable = loaded_addresses.writable.len();
+
+        let remap_index = |index: u8| -> u8 {
+            let index = index as usize;
+            let remapped = if index < num_static_writable {
+                index
+            } else if index < num_static_account_keys {
+                // static readonly keys move past the loaded writable keys
+                index + num_loaded_writable
+            } else if index < num_static_account_keys + num_loaded_writable {
+                // loaded writable keys move ahead of the static readonly keys
+                index - num_static_readonly_unsigned
+            } else {
+                // loaded readonly keys keep their position at the end
+                index
+            };
+            remapped as u8
+        };
+
+        let instructions = self
+            .instructions
+            .into_iter()
+            .map(|ix| CompiledInstruction {
+                program_id_index: remap_index(ix.program_id_index),
+                accounts: ix.accounts.into_iter().map(remap_index).collect(),
+                data: ix.data,
+            })
+            .collect();
+
+        let mut account_keys = self.account_keys;
+        let static_readonly_keys = account_keys.split_off(num_static_writable);
+        account_keys.extend(loaded_addresses.writable);
+        account_keys.extend(static_readonly_keys);
+        account_keys.extend(loaded_addresses.readonly.iter().copied());
+
+        let num_readonly_unsigned_accounts =
+            num_static_readonly_unsigned + loaded_addresses.readonly.len();
+        Ok(LegacyMessage {
+            header: MessageHeader {
+                num_readonly_unsigned_accounts: num_readonly_unsigned_accounts as u8,
+                ..self.header
+            },
+            account_keys,
+            recent_blockhash: self.recent_blockhash,
+            instructions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple_message() -> Message {
+        Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![1],
+            }],
+            ..Message::default()
+        }
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert!(simple_message().sanitize().is_ok());
+    }
+
+    #[test]
+    fn test_sanitize_with_instruction() {
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![0, 2, 3],
+                data: vec![]
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_sanitize_without_signer() {
+        assert!(Message {
+            header: MessageHeader::default(),
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_empty_table_lookup() {
+        assert!(Message {
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![],
+                readonly_indexes: vec![],
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_max_account_keys() {
+        assert!(Message {
+            account_keys: (0..=u8::MAX).map(|_| Pubkey::new_unique()).collect(),
+            address_table_lookups: vec![],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_sanitize_with_too_many_account_keys() {
+        assert!(Message {
+            account_keys: (0..u8::MAX).map(|_| Pubkey::new_unique()).collect(),
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_loaded_program_id() {
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![],
+                data: vec![]
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_invalid_account_index() {
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![4],
+                data: vec![]
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_try_into_legacy_message() {
+        let payer = Pubkey::new_unique();
+        let static_writable = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let loaded_writable = Pubkey::new_unique();
+        let loaded_readonly = Pubkey::new_unique();
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer, static_writable, program_id],
+            instructions: vec![CompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![0, 1, 3, 4],
+                data: vec![7],
+            }],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![1],
+            }],
+            ..Message::default()
+        };
+        assert!(message.sanitize().is_ok());
+
+        let legacy_message = message
+            .try_into_legacy_message(LoadedAddresses {
+                writable: vec![loaded_writable],
+                readonly: vec![loaded_readonly],
+            })
+            .unwrap();
+        assert!(legacy_message.sanitize().is_ok());
+        assert_eq!(
+            legacy_message.account_keys,
+            vec![
+                payer,
+                static_writable,
+                loaded_writable,
+                program_id,
+                loaded_readonly
+            ]
+        );
+        assert_eq!(legacy_message.header.num_readonly_unsigned_accounts, 2);
+        assert_eq!(
+            legacy_message.instructions,
+            vec![CompiledInstruction {
+                program_id_index: 3,
+                accounts: vec![0, 1, 2, 4],
+                data: vec![7],
+            }]
+        );
+        assert!(legacy_message.is_writable(2));
+        assert!(!legacy_message.is_writable(4));
+    }
+
+    #[test]
+    fn test_try_into_legacy_message_with_mismatched_addresses() {
+        assert_eq!(
+            simple_message().try_into_legacy_message(LoadedAddresses {
+                writable: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                readonly: vec![],
+            }),
+            Err(SanitizeError::InvalidValue)
+        );
+    }
+}
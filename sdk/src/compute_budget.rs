@@ -4,13 +4,18 @@ use crate::transaction::{Transaction, TransactionError};
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use solana_sdk::{
     borsh::try_from_slice_unchecked,
-    instruction::{Instruction, InstructionError},
+    instruction::{CompiledInstruction, Instruction, InstructionError},
+    pubkey::Pubkey,
 };
+use std::convert::TryFrom;
 
 crate::declare_id!("ComputeBudget111111111111111111111111111111");
 
 const MAX_UNITS: u64 = 1_000_000;
 
+/// Number of micro-lamports in a lamport, the unit of the compute unit price
+pub const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
 /// Compute Budget Instructions
 #[derive(
     Serialize,
@@ -28,12 +33,78 @@ pub enum ComputeBudgetInstruction {
     /// Request a specific maximum number of compute units the transaction is
     /// allowed to consume.
     RequestUnits(u64),
+    /// Set a compute unit price in micro-lamports to pay a higher transaction
+    /// fee for higher transaction prioritization.
+    SetComputeUnitPrice(u64),
 }
 impl ComputeBudgetInstruction {
     /// Create a `ComputeBudgetInstruction::RequestUnits` `Instruction`
     pub fn request_units(units: u64) -> Instruction {
         Instruction::new_with_borsh(id(), &ComputeBudgetInstruction::RequestUnits(units), vec![])
     }
+
+    /// Create a `ComputeBudgetInstruction::SetComputeUnitPrice` `Instruction`
+    pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+        Instruction::new_with_borsh(
+            id(),
+            &ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports),
+            vec![],
+        )
+    }
+}
+
+/// The prioritization fee requested by a transaction, derived from its compute
+/// unit price and compute unit limit
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PrioritizationFeeDetails {
+    /// Price per compute unit in micro-lamports
+    pub compute_unit_price: u64,
+    /// Number of compute units the transaction may consume
+    pub compute_unit_limit: u64,
+}
+impl PrioritizationFeeDetails {
+    pub fn from_transaction(tx: &Transaction) -> Result<Self, TransactionError> {
+        Self::from_instructions(&tx.message().instructions, &tx.message().account_keys)
+    }
+
+    /// Read the compute unit price and the compute unit limit requested by a
+    /// message's compute budget instructions.  A message that doesn't request
+    /// a limit is allowed the default `ComputeBudget` limit.
+    pub fn from_instructions(
+        instructions: &[CompiledInstruction],
+        account_keys: &[Pubkey],
+    ) -> Result<Self, TransactionError> {
+        let mut compute_budget = ComputeBudget::new();
+        let compute_unit_price =
+            compute_budget.process_instructions(instructions, account_keys, true)?;
+        // `process_instructions` applies any `RequestUnits` instruction to the limit
+        let compute_unit_limit = compute_budget.max_units;
+        Ok(Self {
+            compute_unit_price,
+            compute_unit_limit,
+        })
+    }
+
+    /// Prioritization fee in lamports, rounded up to the nearest lamport
+    pub fn fee(&self) -> u64 {
+        let micro_lamports =
+            (self.compute_unit_price as u128).saturating_mul(self.compute_unit_limit as u128);
+        let lamports = micro_lamports.saturating_add(MICRO_LAMPORTS_PER_LAMPORT as u128 - 1)
+            / MICRO_LAMPORTS_PER_LAMPORT as u128;
+        u64::try_from(lamports).unwrap_or(u64::MAX)
+    }
+
+    /// Total fee in micro-lamports per requested compute unit, counting both
+    /// the prioritization fee and the given signature fee in lamports
+    pub fn fee_per_compute_unit(&self, signature_fee: u64) -> u64 {
+        let micro_lamports = (signature_fee as u128)
+            .saturating_mul(MICRO_LAMPORTS_PER_LAMPORT as u128)
+            .saturating_add(
+                (self.compute_unit_price as u128).saturating_mul(self.compute_unit_limit as u128),
+            );
+        let fee_per_compute_unit = micro_lamports / self.compute_unit_limit.max(1) as u128;
+        u64::try_from(fee_per_compute_unit).unwrap_or(u64::MAX)
+    }
 }
 
 #[derive(Clone, Copy, Debug, AbiExample, PartialEq)]
@@ -96,28 +167,66 @@ impl ComputeBudget {
             heap_size: None,
         }
     }
-    pub fn process_transaction(&mut self, tx: &Transaction) -> Result<(), TransactionError> {
+    /// Apply the transaction's compute budget instructions, returning the
+    /// requested compute unit price in micro-lamports
+    pub fn process_transaction(
+        &mut self,
+        tx: &Transaction,
+        prioritization_fee_enabled: bool,
+    ) -> Result<u64, TransactionError> {
+        self.process_instructions(
+            &tx.message().instructions,
+            &tx.message().account_keys,
+            prioritization_fee_enabled,
+        )
+    }
+
+    /// Apply the compute budget instructions of a message given its compiled
+    /// instructions and account keys. Compute budget program ids are never
+    /// loaded from address lookup tables, so the static account keys suffice.
+    ///
+    /// Until the `prioritization_fee` feature is active, only `RequestUnits`
+    /// in the first two instructions is recognized, as before the compute unit
+    /// price was introduced.
+    pub fn process_instructions(
+        &mut self,
+        instructions: &[CompiledInstruction],
+        account_keys: &[Pubkey],
+        prioritization_fee_enabled: bool,
+    ) -> Result<u64, TransactionError> {
         let error = TransactionError::InstructionError(0, InstructionError::InvalidInstructionData);
-        // Compute budget instruction must be in 1st or 2nd instruction (avoid nonce marker)
-        for instruction in tx.message().instructions.iter().take(2) {
-            if check_id(instruction.program_id(&tx.message().account_keys)) {
-                let ComputeBudgetInstruction::RequestUnits(units) =
-                    try_from_slice_unchecked::<ComputeBudgetInstruction>(&instruction.data)
-                        .map_err(|_| error.clone())?;
-                if units > MAX_UNITS {
-                    return Err(error);
+        let mut compute_unit_price = 0;
+        // Compute budget instructions must be in the first two instructions
+        // (avoid nonce marker), or three once the compute unit price may be set
+        let max_instructions = if prioritization_fee_enabled { 3 } else { 2 };
+        for instruction in instructions.iter().take(max_instructions) {
+            if check_id(instruction.program_id(account_keys)) {
+                match try_from_slice_unchecked::<ComputeBudgetInstruction>(&instruction.data)
+                    .map_err(|_| error.clone())?
+                {
+                    ComputeBudgetInstruction::RequestUnits(units) => {
+                        if units > MAX_UNITS {
+                            return Err(error);
+                        }
+                        self.max_units = units;
+                    }
+                    ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports) => {
+                        if !prioritization_fee_enabled {
+                            return Err(error);
+                        }
+                        compute_unit_price = micro_lamports;
+                    }
                 }
-                self.max_units = units;
             }
         }
-        Ok(())
+        Ok(compute_unit_price)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{hash::Hash, message::Message, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use crate::{hash::Hash, message::Message, signature::Keypair, signer::Signer};
 
     #[test]
     fn test_process_transaction() {
@@ -129,7 +238,7 @@ mod tests {
             Message::new(&[], Some(&payer_keypair.pubkey())),
             Hash::default(),
         );
-        compute_budget.process_transaction(&tx).unwrap();
+        compute_budget.process_transaction(&tx, true).unwrap();
         assert_eq!(compute_budget, ComputeBudget::default());
 
         let tx = Transaction::new(
@@ -143,7 +252,7 @@ mod tests {
             ),
             Hash::default(),
         );
-        compute_budget.process_transaction(&tx).unwrap();
+        compute_budget.process_transaction(&tx, true).unwrap();
         assert_eq!(
             compute_budget,
             ComputeBudget {
@@ -163,7 +272,7 @@ mod tests {
             ),
             Hash::default(),
         );
-        let result = compute_budget.process_transaction(&tx);
+        let result = compute_budget.process_transaction(&tx, true);
         assert_eq!(
             result,
             Err(TransactionError::InstructionError(
@@ -183,7 +292,7 @@ mod tests {
             ),
             Hash::default(),
         );
-        compute_budget.process_transaction(&tx).unwrap();
+        compute_budget.process_transaction(&tx, true).unwrap();
         assert_eq!(
             compute_budget,
             ComputeBudget {
@@ -192,4 +301,109 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_process_transaction_compute_unit_price() {
+        let payer_keypair = Keypair::new();
+        let mut compute_budget = ComputeBudget::default();
+
+        let tx = Transaction::new(
+            &[&payer_keypair],
+            Message::new(
+                &[
+                    ComputeBudgetInstruction::request_units(10_000),
+                    ComputeBudgetInstruction::set_compute_unit_price(42),
+                    Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+                ],
+                Some(&payer_keypair.pubkey()),
+            ),
+            Hash::default(),
+        );
+        assert_eq!(compute_budget.process_transaction(&tx, true), Ok(42));
+        assert_eq!(compute_budget.max_units, 10_000);
+        assert_eq!(
+            PrioritizationFeeDetails::from_transaction(&tx),
+            Ok(PrioritizationFeeDetails {
+                compute_unit_price: 42,
+                compute_unit_limit: 10_000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_process_instructions_prioritization_fee_disabled() {
+        let payer = Pubkey::new_unique();
+        let message = |instructions: &[Instruction]| Message::new(instructions, Some(&payer));
+        let process = |message: &Message, prioritization_fee_enabled| {
+            let mut compute_budget = ComputeBudget::default();
+            compute_budget
+                .process_instructions(
+                    &message.instructions,
+                    &message.account_keys,
+                    prioritization_fee_enabled,
+                )
+                .map(|compute_unit_price| (compute_unit_price, compute_budget.max_units))
+        };
+
+        // A unit request in the third instruction is only honored once the feature is active
+        let third_instruction_request = message(&[
+            Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ComputeBudgetInstruction::request_units(1_000),
+        ]);
+        assert_eq!(
+            process(&third_instruction_request, false),
+            Ok((0, ComputeBudget::default().max_units))
+        );
+        assert_eq!(process(&third_instruction_request, true), Ok((0, 1_000)));
+
+        // The compute unit price is invalid instruction data until the feature is active
+        let price = message(&[
+            ComputeBudgetInstruction::request_units(1_000),
+            ComputeBudgetInstruction::set_compute_unit_price(42),
+        ]);
+        assert_eq!(
+            process(&price, false),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+        assert_eq!(process(&price, true), Ok((42, 1_000)));
+    }
+
+    #[test]
+    fn test_prioritization_fee() {
+        let details = |compute_unit_price, compute_unit_limit| PrioritizationFeeDetails {
+            compute_unit_price,
+            compute_unit_limit,
+        };
+        assert_eq!(details(0, 200_000).fee(), 0);
+        assert_eq!(details(1, 200_000).fee(), 1);
+        assert_eq!(details(MICRO_LAMPORTS_PER_LAMPORT, 200_000).fee(), 200_000);
+        assert_eq!(details(1, 1).fee(), 1);
+        assert_eq!(details(u64::MAX, u64::MAX).fee(), u64::MAX);
+    }
+
+    #[test]
+    fn test_fee_per_compute_unit() {
+        let details = |compute_unit_price, compute_unit_limit| PrioritizationFeeDetails {
+            compute_unit_price,
+            compute_unit_limit,
+        };
+        assert_eq!(details(0, 200_000).fee_per_compute_unit(0), 0);
+        assert_eq!(details(7, 200_000).fee_per_compute_unit(0), 7);
+        assert_eq!(details(0, 200_000).fee_per_compute_unit(5_000), 25_000);
+        assert_eq!(details(7, 200_000).fee_per_compute_unit(5_000), 25_007);
+        // Requesting fewer compute units for the same fee ranks higher
+        assert_eq!(details(0, 100_000).fee_per_compute_unit(5_000), 50_000);
+        assert_eq!(
+            details(1, 0).fee_per_compute_unit(1),
+            MICRO_LAMPORTS_PER_LAMPORT
+        );
+        assert_eq!(
+            details(u64::MAX, 1).fee_per_compute_unit(u64::MAX),
+            u64::MAX
+        );
+    }
 }
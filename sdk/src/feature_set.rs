@@ -199,6 +199,18 @@ pub mod spl_token_v2_set_authority_fix {
     solana_sdk::declare_id!("FToKNBYyiF4ky9s8WsmLBXHCht17Ek7RXaLZGHzzQhJ1");
 }
 
+pub mod versioned_tx_message_enabled {
+    solana_sdk::declare_id!("2xhdobcLW8hkS2QDU3TpUqKMGXtEk88ehoFroEGBS7Nn");
+}
+
+pub mod address_lookup_table_program {
+    solana_sdk::declare_id!("CxdbYW6pehxiDxvqq1G9HxAJq3bNeaFXxh78svmophcJ");
+}
+
+pub mod prioritization_fee {
+    solana_sdk::declare_id!("GgcabnbMLdLxv4v1yqoMvrBQkj8RWkqw1gdFGFpS27LZ");
+}
+
 lazy_static! {
     /// Map of feature identifiers to user-visible description
     pub static ref FEATURE_NAMES: HashMap<Pubkey, &'static str> = [
@@ -243,6 +255,9 @@ lazy_static! {
         (libsecp256k1_0_5_upgrade_enabled::id(), "upgrade libsecp256k1 to v0.5.0"),
         (tx_wide_compute_cap::id(), "Transaction wide compute cap"),
         (spl_token_v2_set_authority_fix::id(), "spl-token set_authority fix"),
+        (versioned_tx_message_enabled::id(), "enable versioned transaction message processing"),
+        (address_lookup_table_program::id(), "address lookup table program"),
+        (prioritization_fee::id(), "charge prioritization fees from compute unit price"),
         /*************** ADD NEW FEATURES HERE ***************/
     ]
     .iter()
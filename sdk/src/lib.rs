@@ -33,11 +33,13 @@ pub mod log;
 pub mod native_loader;
 pub mod nonce_account;
 pub mod nonce_keyed_account;
+pub mod offchain_message;
 pub mod packet;
 pub mod poh_config;
 pub mod process_instruction;
 pub mod program_utils;
 pub mod pubkey;
+pub mod quic;
 pub mod recent_blockhashes_account;
 pub mod rpc_port;
 pub mod sanitized_transaction;
@@ -49,6 +51,7 @@ pub mod system_transaction;
 pub mod timing;
 pub mod transaction;
 pub mod transport;
+pub mod versioned_transaction;
 
 /// Convenience macro to declare a static public key and functions to interact with it
 ///
@@ -0,0 +1,271 @@
+//! Off-chain messages: arbitrary data signed by a key holder, framed so that the
+//! signed bytes can never also be a valid transaction message.
+//!
+//! A serialized message is the signing domain, `\xffsolana offchain`, followed by a
+//! header version, the message format, the little-endian `u16` length of the message
+//! and the message itself. No transaction message can start with the `0xff` byte of the
+//! signing domain, so a signature over an off-chain message cannot be replayed as a
+//! transaction signature.
+#![cfg(feature = "full")]
+
+use {
+    crate::{
+        packet::PACKET_DATA_SIZE,
+        pubkey::Pubkey,
+        signature::Signature,
+        signer::{Signer, SignerError},
+    },
+    num_derive::{FromPrimitive, ToPrimitive},
+    num_traits::FromPrimitive,
+    std::convert::TryInto,
+    thiserror::Error,
+};
+
+/// Prefix of every serialized off-chain message
+pub const SIGNING_DOMAIN: &[u8] = b"\xffsolana offchain";
+
+/// The only header version defined so far
+pub const HEADER_VERSION: u8 = 0;
+
+/// Length of the signing domain, version, format and message length
+pub const HEADER_LEN: usize = SIGNING_DOMAIN.len() + 1 + 1 + 2;
+
+/// Longest message that a hardware wallet can be expected to display and sign
+pub const MAX_LEN_LEDGER: usize = PACKET_DATA_SIZE - HEADER_LEN;
+
+/// Longest message of any format
+pub const MAX_LEN: usize = u16::MAX as usize - HEADER_LEN;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum OffchainMessageError {
+    #[error("message is empty")]
+    Empty,
+
+    #[error("message of {0} bytes is too long")]
+    TooLong(usize),
+
+    #[error("missing signing domain")]
+    InvalidSigningDomain,
+
+    #[error("unsupported header version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("unknown message format {0}")]
+    UnknownFormat(u8),
+
+    #[error("message content does not match its format")]
+    InvalidContent,
+
+    #[error("message length does not match its header")]
+    InvalidLength,
+}
+
+/// How the message bytes are to be interpreted, and displayed by hardware wallets
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+pub enum MessageFormat {
+    /// Printable ASCII, no longer than `MAX_LEN_LEDGER`
+    RestrictedAscii,
+    /// UTF-8, no longer than `MAX_LEN_LEDGER`
+    LimitedUtf8,
+    /// UTF-8, no longer than `MAX_LEN`
+    ExtendedUtf8,
+    /// Arbitrary bytes, no longer than `MAX_LEN`
+    Binary,
+}
+
+impl MessageFormat {
+    /// The most restrictive format that `message` fits
+    fn detect(message: &[u8]) -> Self {
+        let short = message.len() <= MAX_LEN_LEDGER;
+        if short && message.iter().all(|byte| (0x20..=0x7e).contains(byte)) {
+            Self::RestrictedAscii
+        } else if std::str::from_utf8(message).is_ok() {
+            if short {
+                Self::LimitedUtf8
+            } else {
+                Self::ExtendedUtf8
+            }
+        } else {
+            Self::Binary
+        }
+    }
+
+    fn is_valid(&self, message: &[u8]) -> bool {
+        match self {
+            Self::RestrictedAscii => {
+                message.len() <= MAX_LEN_LEDGER
+                    && message.iter().all(|byte| (0x20..=0x7e).contains(byte))
+            }
+            Self::LimitedUtf8 => {
+                message.len() <= MAX_LEN_LEDGER && std::str::from_utf8(message).is_ok()
+            }
+            Self::ExtendedUtf8 => std::str::from_utf8(message).is_ok(),
+            Self::Binary => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffchainMessage {
+    version: u8,
+    format: MessageFormat,
+    message: Vec<u8>,
+}
+
+impl OffchainMessage {
+    /// Frame `message` in the most restrictive format that it fits
+    pub fn new(message: &[u8]) -> Result<Self, OffchainMessageError> {
+        Self::new_with_format(MessageFormat::detect(message), message)
+    }
+
+    pub fn new_with_format(
+        format: MessageFormat,
+        message: &[u8],
+    ) -> Result<Self, OffchainMessageError> {
+        if message.is_empty() {
+            return Err(OffchainMessageError::Empty);
+        }
+        if message.len() > MAX_LEN {
+            return Err(OffchainMessageError::TooLong(message.len()));
+        }
+        if !format.is_valid(message) {
+            return Err(OffchainMessageError::InvalidContent);
+        }
+        Ok(Self {
+            version: HEADER_VERSION,
+            format,
+            message: message.to_vec(),
+        })
+    }
+
+    pub fn get_version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn get_format(&self) -> MessageFormat {
+        self.format
+    }
+
+    pub fn get_message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// The bytes that are signed
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEADER_LEN + self.message.len());
+        data.extend_from_slice(SIGNING_DOMAIN);
+        data.push(self.version);
+        data.push(self.format as u8);
+        data.extend_from_slice(&(self.message.len() as u16).to_le_bytes());
+        data.extend_from_slice(&self.message);
+        data
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, OffchainMessageError> {
+        if data.len() < HEADER_LEN || !data.starts_with(SIGNING_DOMAIN) {
+            return Err(OffchainMessageError::InvalidSigningDomain);
+        }
+        let header = &data[SIGNING_DOMAIN.len()..HEADER_LEN];
+        let version = header[0];
+        if version != HEADER_VERSION {
+            return Err(OffchainMessageError::UnsupportedVersion(version));
+        }
+        let format = MessageFormat::from_u8(header[1])
+            .ok_or(OffchainMessageError::UnknownFormat(header[1]))?;
+        let len = u16::from_le_bytes(header[2..4].try_into().unwrap()) as usize;
+        let message = &data[HEADER_LEN..];
+        if message.len() != len {
+            return Err(OffchainMessageError::InvalidLength);
+        }
+        Self::new_with_format(format, message)
+    }
+
+    pub fn sign(&self, signer: &dyn Signer) -> Result<Signature, SignerError> {
+        signer.sign_offchain_message(self)
+    }
+
+    pub fn verify(&self, pubkey: &Pubkey, signature: &Signature) -> bool {
+        signature.verify(pubkey.as_ref(), &self.serialize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::signature::{Keypair, Presigner},
+    };
+
+    #[test]
+    fn test_format_detection() {
+        let format = |message: &[u8]| OffchainMessage::new(message).unwrap().get_format();
+        assert_eq!(format(b"Hello, world!"), MessageFormat::RestrictedAscii);
+        assert_eq!(format(b"line\nbreak"), MessageFormat::LimitedUtf8);
+        assert_eq!(format("ʇxǝʇ".as_bytes()), MessageFormat::LimitedUtf8);
+        assert_eq!(
+            format(&vec![b'a'; MAX_LEN_LEDGER + 1]),
+            MessageFormat::ExtendedUtf8
+        );
+        assert_eq!(format(&[0xff, 0x00]), MessageFormat::Binary);
+
+        assert_eq!(OffchainMessage::new(&[]), Err(OffchainMessageError::Empty));
+        assert_eq!(
+            OffchainMessage::new(&vec![b'a'; MAX_LEN + 1]),
+            Err(OffchainMessageError::TooLong(MAX_LEN + 1))
+        );
+        assert_eq!(
+            OffchainMessage::new_with_format(MessageFormat::RestrictedAscii, "ʇxǝʇ".as_bytes()),
+            Err(OffchainMessageError::InvalidContent)
+        );
+    }
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let message = OffchainMessage::new(b"Hello, world!").unwrap();
+        let data = message.serialize();
+        assert_eq!(data.len(), HEADER_LEN + 13);
+        assert!(data.starts_with(SIGNING_DOMAIN));
+        assert_eq!(
+            &data[SIGNING_DOMAIN.len()..HEADER_LEN],
+            &[HEADER_VERSION, MessageFormat::RestrictedAscii as u8, 13, 0]
+        );
+        assert_eq!(OffchainMessage::deserialize(&data).unwrap(), message);
+
+        assert_eq!(
+            OffchainMessage::deserialize(&data[1..]),
+            Err(OffchainMessageError::InvalidSigningDomain)
+        );
+        assert_eq!(
+            OffchainMessage::deserialize(&data[..data.len() - 1]),
+            Err(OffchainMessageError::InvalidLength)
+        );
+        let mut bad_version = data.clone();
+        bad_version[SIGNING_DOMAIN.len()] = 1;
+        assert_eq!(
+            OffchainMessage::deserialize(&bad_version),
+            Err(OffchainMessageError::UnsupportedVersion(1))
+        );
+        let mut bad_format = data;
+        bad_format[SIGNING_DOMAIN.len() + 1] = 4;
+        assert_eq!(
+            OffchainMessage::deserialize(&bad_format),
+            Err(OffchainMessageError::UnknownFormat(4))
+        );
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let keypair = Keypair::new();
+        let message = OffchainMessage::new(b"Hello, world!").unwrap();
+        let signature = message.sign(&keypair).unwrap();
+        assert!(message.verify(&keypair.pubkey(), &signature));
+        // The signature covers the framed message, not the raw bytes
+        assert!(!signature.verify(keypair.pubkey().as_ref(), b"Hello, world!"));
+        let other = OffchainMessage::new(b"Goodbye, world!").unwrap();
+        assert!(!other.verify(&keypair.pubkey(), &signature));
+
+        let presigner = Presigner::new(&keypair.pubkey(), &signature);
+        assert_eq!(presigner.sign_offchain_message(&message), Ok(signature));
+        assert!(presigner.sign_offchain_message(&other).is_err());
+    }
+}
@@ -25,6 +25,9 @@ pub struct Meta {
     pub seed: [u8; 32],
     pub slot: Slot,
     pub is_tracer_tx: bool,
+    /// Fee per requested compute unit in micro-lamports of the transaction in
+    /// this packet, set once its signatures are verified
+    pub priority: u64,
 }
 
 #[derive(Clone)]
@@ -0,0 +1,8 @@
+/// The QUIC TPU endpoint listens this many ports above the UDP TPU port advertised in gossip
+pub const QUIC_PORT_OFFSET: u16 = 6;
+
+/// ALPN protocol identifier negotiated by TPU QUIC connections
+pub const QUIC_TPU_ALPN_PROTOCOL_ID: &[u8] = b"solana-tpu";
+
+/// Default number of concurrent unidirectional streams allowed on an unstaked connection
+pub const QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS: usize = 128;
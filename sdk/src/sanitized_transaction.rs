@@ -2,16 +2,36 @@
 
 use crate::{
     hash::Hash,
+    message::{
+        v0::{self, LoadedAddresses, MessageAddressTableLookup},
+        VersionedMessage,
+    },
     sanitize::Sanitize,
     transaction::{Result, Transaction, TransactionError},
+    versioned_transaction::VersionedTransaction,
 };
 use std::{borrow::Cow, convert::TryFrom, ops::Deref};
 
 /// Sanitized transaction and the hash of its message
+///
+/// Versioned transactions are sanitized into a legacy `Transaction` whose
+/// message lists both the static account keys and the addresses loaded from
+/// address lookup tables, so that the runtime can execute them unchanged. The
+/// signatures of such a transaction only verify against the original message,
+/// which is retained alongside the loaded addresses.
 #[derive(Debug, Clone)]
 pub struct SanitizedTransaction<'a> {
     transaction: Cow<'a, Transaction>,
     pub message_hash: Hash,
+    loaded_message: Option<LoadedMessage>,
+}
+
+/// Original v0 message of a sanitized transaction along with the addresses
+/// that were loaded from on-chain address lookup tables
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedMessage {
+    pub message: v0::Message,
+    pub loaded_addresses: LoadedAddresses,
 }
 
 impl<'a> SanitizedTransaction<'a> {
@@ -24,9 +44,88 @@ impl<'a> SanitizedTransaction<'a> {
         Ok(Self {
             transaction,
             message_hash,
+            loaded_message: None,
         })
     }
 
+    /// Create a sanitized transaction from a versioned transaction, using
+    /// `address_loader` to resolve any address table lookups
+    pub fn try_create_versioned<F>(
+        transaction: VersionedTransaction,
+        message_hash: Hash,
+        address_loader: F,
+    ) -> Result<SanitizedTransaction<'static>>
+    where
+        F: FnOnce(&[MessageAddressTableLookup]) -> Result<LoadedAddresses>,
+    {
+        transaction.sanitize()?;
+        let VersionedTransaction {
+            signatures,
+            message,
+        } = transaction;
+        match message {
+            VersionedMessage::Legacy(message) => SanitizedTransaction::try_create(
+                Cow::Owned(Transaction {
+                    signatures,
+                    message,
+                }),
+                message_hash,
+            ),
+            VersionedMessage::V0(message) => {
+                let loaded_addresses = address_loader(&message.address_table_lookups)?;
+                let legacy_message = message
+                    .clone()
+                    .try_into_legacy_message(loaded_addresses.clone())?;
+                let mut sanitized_tx = SanitizedTransaction::try_create(
+                    Cow::Owned(Transaction {
+                        signatures,
+                        message: legacy_message,
+                    }),
+                    message_hash,
+                )?;
+                sanitized_tx.loaded_message = Some(LoadedMessage {
+                    message,
+                    loaded_addresses,
+                });
+                Ok(sanitized_tx)
+            }
+        }
+    }
+
+    /// Convert into a sanitized transaction which owns its transaction data
+    pub fn into_owned(self) -> SanitizedTransaction<'static> {
+        SanitizedTransaction {
+            transaction: Cow::Owned(self.transaction.into_owned()),
+            message_hash: self.message_hash,
+            loaded_message: self.loaded_message,
+        }
+    }
+
+    /// Return the addresses loaded from address lookup tables, if this
+    /// transaction was created from a versioned message
+    pub fn loaded_addresses(&self) -> Option<&LoadedAddresses> {
+        self.loaded_message
+            .as_ref()
+            .map(|loaded_message| &loaded_message.loaded_addresses)
+    }
+
+    /// Return true if this transaction was created from a legacy message
+    pub fn is_legacy(&self) -> bool {
+        self.loaded_message.is_none()
+    }
+
+    /// Return the transaction as it was signed, which is the form recorded in
+    /// the ledger
+    pub fn to_versioned_transaction(&self) -> VersionedTransaction {
+        match &self.loaded_message {
+            Some(loaded_message) => VersionedTransaction {
+                signatures: self.transaction.signatures.clone(),
+                message: VersionedMessage::V0(loaded_message.message.clone()),
+            },
+            None => VersionedTransaction::from(self.transaction.clone().into_owned()),
+        }
+    }
+
     /// Return true if the slice has any duplicate elements
     pub fn has_duplicates<T: PartialEq>(xs: &[T]) -> bool {
         // Note: This is an O(n^2) algorithm, but requires no heap allocations. The benchmark
@@ -79,9 +178,114 @@ impl<'a> SanitizedTransactionSlice<'a> for [SanitizedTransaction<'a>] {
 mod tests {
     use super::*;
 
+    use crate::{
+        message::{Message, MessageHeader},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+
     #[test]
     fn test_has_duplicates() {
         assert!(!SanitizedTransaction::has_duplicates(&[1, 2]));
         assert!(SanitizedTransaction::has_duplicates(&[1, 2, 1]));
     }
+
+    fn v0_transaction(keypair: &Keypair, lookup_table_key: Pubkey) -> VersionedTransaction {
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 0,
+            },
+            account_keys: vec![keypair.pubkey()],
+            recent_blockhash: Hash::new_unique(),
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: lookup_table_key,
+                writable_indexes: vec![0],
+                readonly_indexes: vec![1],
+            }],
+            instructions: vec![],
+        });
+        VersionedTransaction::try_new(message, &[keypair]).unwrap()
+    }
+
+    #[test]
+    fn test_try_create_versioned() {
+        let keypair = Keypair::new();
+        let lookup_table_key = Pubkey::new_unique();
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![Pubkey::new_unique()],
+            readonly: vec![Pubkey::new_unique()],
+        };
+        let tx = v0_transaction(&keypair, lookup_table_key);
+        let message_hash = tx.message.hash();
+
+        let sanitized_tx =
+            SanitizedTransaction::try_create_versioned(tx.clone(), message_hash, |lookups| {
+                assert_eq!(lookups[0].account_key, lookup_table_key);
+                Ok(loaded_addresses.clone())
+            })
+            .unwrap();
+        assert!(!sanitized_tx.is_legacy());
+        assert_eq!(sanitized_tx.loaded_addresses(), Some(&loaded_addresses));
+        assert_eq!(
+            sanitized_tx.message().account_keys,
+            vec![
+                keypair.pubkey(),
+                loaded_addresses.writable[0],
+                loaded_addresses.readonly[0]
+            ]
+        );
+        assert_eq!(sanitized_tx.to_versioned_transaction(), tx);
+    }
+
+    #[test]
+    fn test_try_create_versioned_with_duplicate_loaded_address() {
+        let keypair = Keypair::new();
+        let tx = v0_transaction(&keypair, Pubkey::new_unique());
+        let message_hash = tx.message.hash();
+        assert_eq!(
+            SanitizedTransaction::try_create_versioned(tx, message_hash, |_| Ok(LoadedAddresses {
+                writable: vec![keypair.pubkey()],
+                readonly: vec![Pubkey::new_unique()],
+            }))
+            .unwrap_err(),
+            TransactionError::AccountLoadedTwice
+        );
+    }
+
+    #[test]
+    fn test_try_create_versioned_with_loader_error() {
+        let keypair = Keypair::new();
+        let tx = v0_transaction(&keypair, Pubkey::new_unique());
+        let message_hash = tx.message.hash();
+        assert_eq!(
+            SanitizedTransaction::try_create_versioned(tx, message_hash, |_| Err(
+                TransactionError::AddressLookupTableNotFound
+            ))
+            .unwrap_err(),
+            TransactionError::AddressLookupTableNotFound
+        );
+    }
+
+    #[test]
+    fn test_try_create_versioned_legacy() {
+        let keypair = Keypair::new();
+        let instruction = system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 1);
+        let message = Message::new(&[instruction], Some(&keypair.pubkey()));
+        let tx = Transaction::new(&[&keypair], message, Hash::new_unique());
+        let versioned_tx = VersionedTransaction::from(tx.clone());
+
+        let sanitized_tx = SanitizedTransaction::try_create_versioned(
+            versioned_tx.clone(),
+            tx.message().hash(),
+            |_| panic!("legacy transactions do not load addresses"),
+        )
+        .unwrap();
+        assert!(sanitized_tx.is_legacy());
+        assert_eq!(sanitized_tx.loaded_addresses(), None);
+        assert_eq!(*sanitized_tx, tx);
+        assert_eq!(sanitized_tx.to_versioned_transaction(), versioned_tx);
+    }
 }
@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        offchain_message::OffchainMessage,
         pubkey::Pubkey,
         signature::{PresignerError, Signature},
         transaction::TransactionError,
@@ -23,6 +24,9 @@ pub enum SignerError {
     #[error("not enough signers")]
     NotEnoughSigners,
 
+    #[error("too many signers")]
+    TooManySigners,
+
     #[error("transaction error")]
     TransactionError(#[from] TransactionError),
 
@@ -68,6 +72,11 @@ pub trait Signer {
     }
     /// Fallibly produces an Ed25519 signature over the provided `message` bytes.
     fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError>;
+    /// Fallibly produces an Ed25519 signature over a serialized off-chain `message`.
+    /// Signers that display what they sign, such as hardware wallets, should override this
+    fn sign_offchain_message(&self, message: &OffchainMessage) -> Result<Signature, SignerError> {
+        self.try_sign_message(&message.serialize())
+    }
     /// Whether the impelmentation requires user interaction to sign
     fn is_interactive(&self) -> bool;
 }
@@ -0,0 +1,245 @@
+//! Defines a transaction which supports multiple versions of messages.
+
+#![cfg(feature = "full")]
+
+use crate::{
+    hash::Hash,
+    message::{Message, VersionedMessage},
+    sanitize::{Sanitize, SanitizeError},
+    short_vec,
+    signature::Signature,
+    signer::SignerError,
+    signers::Signers,
+    transaction::{Result, Transaction, TransactionError},
+};
+
+/// An atomic transaction which supports versioned messages
+#[derive(Debug, PartialEq, Default, Eq, Clone, Serialize, Deserialize, AbiExample)]
+pub struct VersionedTransaction {
+    /// List of signatures
+    #[serde(with = "short_vec")]
+    pub signatures: Vec<Signature>,
+    /// Message to sign.
+    pub message: VersionedMessage,
+}
+
+impl Sanitize for VersionedTransaction {
+    fn sanitize(&self) -> std::result::Result<(), SanitizeError> {
+        self.message.sanitize()?;
+
+        // Once the "verify_tx_signatures_len" feature is enabled, this may be
+        // updated to an equality check.
+        if usize::from(self.message.header().num_required_signatures) > self.signatures.len() {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        // Signatures are verified before message keys are mapped so all signers
+        // must correspond to static account keys.
+        if self.signatures.len() > self.message.static_account_keys().len() {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Transaction> for VersionedTransaction {
+    fn from(transaction: Transaction) -> Self {
+        Self {
+            signatures: transaction.signatures,
+            message: VersionedMessage::Legacy(transaction.message),
+        }
+    }
+}
+
+impl VersionedTransaction {
+    /// Signs a versioned message and if successful, returns a signed
+    /// transaction.
+    pub fn try_new<T: Signers>(
+        message: VersionedMessage,
+        keypairs: &T,
+    ) -> std::result::Result<Self, SignerError> {
+        let static_account_keys = message.static_account_keys();
+        let num_required_signatures = message.header().num_required_signatures as usize;
+        if static_account_keys.len() < num_required_signatures {
+            return Err(SignerError::InvalidInput("invalid message".to_string()));
+        }
+
+        let signer_keys = keypairs.pubkeys();
+        let expected_signer_keys = &static_account_keys[0..num_required_signatures];
+        if signer_keys.len() < expected_signer_keys.len() {
+            return Err(SignerError::NotEnoughSigners);
+        } else if signer_keys.len() > expected_signer_keys.len() {
+            return Err(SignerError::TooManySigners);
+        }
+
+        let mut signatures = vec![Signature::default(); num_required_signatures];
+        let message_data = message.serialize();
+        for (signer_key, signature) in signer_keys
+            .iter()
+            .zip(keypairs.try_sign_message(&message_data)?)
+        {
+            let position = expected_signer_keys
+                .iter()
+                .position(|key| key == signer_key)
+                .ok_or(SignerError::KeypairPubkeyMismatch)?;
+            signatures[position] = signature;
+        }
+
+        Ok(Self {
+            signatures,
+            message,
+        })
+    }
+
+    /// Returns a legacy transaction if the transaction message is legacy.
+    pub fn into_legacy_transaction(self) -> Option<Transaction> {
+        match self.message {
+            VersionedMessage::Legacy(message) => Some(Transaction {
+                signatures: self.signatures,
+                message,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Return true if the transaction message uses the legacy format
+    pub fn is_legacy(&self) -> bool {
+        matches!(self.message, VersionedMessage::Legacy(_))
+    }
+
+    /// Return the serialized message data to sign.
+    pub fn message_data(&self) -> Vec<u8> {
+        self.message.serialize()
+    }
+
+    /// Return the recent blockhash of the transaction message
+    pub fn recent_blockhash(&self) -> &Hash {
+        self.message.recent_blockhash()
+    }
+
+    /// Verify the length of signatures matches the value in the message header
+    pub fn verify_signatures_len(&self) -> bool {
+        self.signatures.len() == self.message.header().num_required_signatures as usize
+    }
+
+    /// Verify the transaction and hash its message
+    pub fn verify_and_hash_message(&self) -> Result<Hash> {
+        let message_bytes = self.message_data();
+        if !self
+            ._verify_with_results(&message_bytes)
+            .iter()
+            .all(|verify_result| *verify_result)
+        {
+            Err(TransactionError::SignatureFailure)
+        } else {
+            Ok(Message::hash_raw_message(&message_bytes))
+        }
+    }
+
+    pub fn verify_with_results(&self) -> Vec<bool> {
+        self._verify_with_results(&self.message_data())
+    }
+
+    fn _verify_with_results(&self, message_bytes: &[u8]) -> Vec<bool> {
+        self.signatures
+            .iter()
+            .zip(self.message.static_account_keys().iter())
+            .map(|(signature, pubkey)| signature.verify(pubkey.as_ref(), message_bytes))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        message::{v0, MessageHeader},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+
+    fn v0_message(payer: &Pubkey) -> VersionedMessage {
+        VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 0,
+            },
+            account_keys: vec![*payer],
+            recent_blockhash: Hash::new_unique(),
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+            instructions: vec![],
+        })
+    }
+
+    #[test]
+    fn test_try_new() {
+        let keypair = Keypair::new();
+        let tx = VersionedTransaction::try_new(v0_message(&keypair.pubkey()), &[&keypair]).unwrap();
+        assert!(tx.sanitize().is_ok());
+        assert!(tx.verify_signatures_len());
+        assert_eq!(tx.verify_and_hash_message(), Ok(tx.message.hash()));
+        assert!(!tx.is_legacy());
+        assert_eq!(tx.clone().into_legacy_transaction(), None);
+
+        let bytes = bincode::serialize(&tx).unwrap();
+        let tx_from_bytes: VersionedTransaction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(tx, tx_from_bytes);
+    }
+
+    #[test]
+    fn test_try_new_with_wrong_signer() {
+        let keypair = Keypair::new();
+        assert_eq!(
+            VersionedTransaction::try_new(v0_message(&Pubkey::new_unique()), &[&keypair]),
+            Err(SignerError::KeypairPubkeyMismatch)
+        );
+        assert_eq!(
+            VersionedTransaction::try_new(
+                v0_message(&keypair.pubkey()),
+                &[&keypair, &Keypair::new()]
+            ),
+            Err(SignerError::TooManySigners)
+        );
+    }
+
+    #[test]
+    fn test_verify_tampered_message() {
+        let keypair = Keypair::new();
+        let mut tx =
+            VersionedTransaction::try_new(v0_message(&keypair.pubkey()), &[&keypair]).unwrap();
+        tx.message.set_recent_blockhash(Hash::new_unique());
+        assert_eq!(
+            tx.verify_and_hash_message(),
+            Err(TransactionError::SignatureFailure)
+        );
+    }
+
+    #[test]
+    fn test_legacy_transaction_round_trip() {
+        let keypair = Keypair::new();
+        let to = Pubkey::new_unique();
+        let instruction = system_instruction::transfer(&keypair.pubkey(), &to, 42);
+        let legacy_tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            Hash::new_unique(),
+        );
+
+        let tx = VersionedTransaction::from(legacy_tx.clone());
+        assert!(tx.is_legacy());
+        assert_eq!(
+            bincode::serialize(&tx).unwrap(),
+            bincode::serialize(&legacy_tx).unwrap()
+        );
+        assert_eq!(tx.verify_and_hash_message(), Ok(legacy_tx.message().hash()));
+        assert_eq!(tx.into_legacy_transaction(), Some(legacy_tx));
+    }
+}
@@ -4,8 +4,9 @@ use {
     crate::{
         access_token::{AccessToken, Scope},
         compression::{compress_best, decompress},
-        root_ca_certificate,
+        root_ca_certificate, LedgerStorageBackend,
     },
+    async_trait::async_trait,
     log::*,
     std::time::{Duration, Instant},
     thiserror::Error,
@@ -77,6 +78,9 @@ pub enum Error {
 
     #[error("Timeout")]
     Timeout,
+
+    #[error("Local storage: {0}")]
+    LocalStorage(String),
 }
 
 impl std::convert::From<std::io::Error> for Error {
@@ -209,35 +213,53 @@ impl BigTableConnection {
             timeout: self.timeout,
         }
     }
+}
 
-    pub async fn put_bincode_cells_with_retry<T>(
+#[async_trait]
+impl LedgerStorageBackend for BigTableConnection {
+    async fn get_row_keys(
         &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        use backoff::{future::retry, ExponentialBackoff};
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.put_bincode_cells(table, cells).await?)
-        })
-        .await
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>> {
+        let mut client = self.client();
+        client
+            .get_row_keys(table_name, start_at, end_at, rows_limit)
+            .await
+    }
+
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        let mut client = self.client();
+        client
+            .get_row_data(table_name, start_at, end_at, rows_limit)
+            .await
     }
 
-    pub async fn put_protobuf_cells_with_retry<T>(
+    async fn get_single_row_data(&self, table_name: &str, row_key: RowKey) -> Result<RowData> {
+        let mut client = self.client();
+        client.get_single_row_data(table_name, row_key).await
+    }
+
+    async fn put_row_data(
         &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
+        table_name: &str,
+        family_name: &str,
+        row_data: &[(&RowKey, RowData)],
+    ) -> Result<()> {
         use backoff::{future::retry, ExponentialBackoff};
         retry(ExponentialBackoff::default(), || async {
             let mut client = self.client();
-            Ok(client.put_protobuf_cells(table, cells).await?)
+            Ok(client
+                .put_row_data(table_name, family_name, row_data)
+                .await?)
         })
         .await
     }
@@ -534,69 +556,6 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
 
         Ok(())
     }
-
-    pub async fn get_bincode_cell<T>(&mut self, table: &str, key: RowKey) -> Result<T>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_bincode_cell_data(&row_data, table, key.to_string())
-    }
-
-    pub async fn get_protobuf_or_bincode_cell<B, P>(
-        &mut self,
-        table: &str,
-        key: RowKey,
-    ) -> Result<CellData<B, P>>
-    where
-        B: serde::de::DeserializeOwned,
-        P: prost::Message + Default,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_protobuf_or_bincode_cell_data(&row_data, table, key)
-    }
-
-    pub async fn put_bincode_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let data = compress_best(&bincode::serialize(&data).unwrap())?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("bin".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
-    }
-
-    pub async fn put_protobuf_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let mut buf = Vec::with_capacity(data.encoded_len());
-            data.encode(&mut buf).unwrap();
-            let data = compress_best(&buf)?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("proto".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
-    }
 }
 
 pub(crate) fn deserialize_protobuf_or_bincode_cell_data<B, P>(
@@ -660,12 +619,33 @@ where
     })
 }
 
+pub(crate) fn serialize_protobuf_cell_data<T>(data: &T) -> Result<RowData>
+where
+    T: prost::Message,
+{
+    let mut buf = Vec::with_capacity(data.encoded_len());
+    data.encode(&mut buf).unwrap();
+    Ok(vec![("proto".to_string(), compress_best(&buf)?)])
+}
+
+pub(crate) fn serialize_bincode_cell_data<T>(data: &T) -> Result<RowData>
+where
+    T: serde::ser::Serialize,
+{
+    Ok(vec![(
+        "bin".to_string(),
+        compress_best(&bincode::serialize(data).unwrap())?,
+    )])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::StoredConfirmedBlock;
     use prost::Message;
-    use solana_sdk::{hash::Hash, signature::Keypair, system_transaction};
+    use solana_sdk::{
+        hash::Hash, message::v0::LoadedAddresses, signature::Keypair, system_transaction,
+    };
     use solana_storage_proto::convert::generated;
     use solana_transaction_status::{
         ConfirmedBlock, TransactionStatusMeta, TransactionWithStatusMeta,
@@ -678,7 +658,7 @@ mod tests {
         let recipient = solana_sdk::pubkey::new_rand();
         let transaction = system_transaction::transfer(&from, &recipient, 42, Hash::default());
         let with_meta = TransactionWithStatusMeta {
-            transaction,
+            transaction: transaction.into(),
             meta: Some(TransactionStatusMeta {
                 status: Ok(()),
                 fee: 1,
@@ -689,6 +669,7 @@ mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                loaded_addresses: LoadedAddresses::default(),
             }),
         };
         let block = ConfirmedBlock {
@@ -1,14 +1,17 @@
 #![allow(clippy::integer_arithmetic)]
 use {
+    async_trait::async_trait,
     log::*,
     serde::{Deserialize, Serialize},
     solana_sdk::{
         clock::{Slot, UnixTimestamp},
         deserialize_utils::default_on_eof,
+        message::v0::LoadedAddresses,
         pubkey::Pubkey,
         signature::Signature,
         sysvar::is_sysvar_id,
-        transaction::{Transaction, TransactionError},
+        transaction::TransactionError,
+        versioned_transaction::VersionedTransaction,
     },
     solana_storage_proto::convert::{generated, tx_by_addr},
     solana_transaction_status::{
@@ -16,7 +19,13 @@ use {
         TransactionByAddrInfo, TransactionConfirmationStatus, TransactionStatus,
         TransactionStatusMeta, TransactionWithStatusMeta,
     },
-    std::{collections::HashMap, convert::TryInto},
+    std::{
+        collections::HashMap,
+        convert::TryInto,
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    },
     thiserror::Error,
 };
 
@@ -26,8 +35,12 @@ extern crate serde_derive;
 mod access_token;
 mod bigtable;
 mod compression;
+mod local_storage;
 mod root_ca_certificate;
 
+pub use bigtable::{CellName, CellValue, Error as BigTableError, RowData, RowKey};
+pub use local_storage::LocalStorage;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("BigTable: {0}")]
@@ -148,7 +161,7 @@ impl From<StoredConfirmedBlock> for ConfirmedBlock {
 
 #[derive(Serialize, Deserialize)]
 struct StoredConfirmedBlockTransaction {
-    transaction: Transaction,
+    transaction: VersionedTransaction,
     meta: Option<StoredConfirmedBlockTransactionStatusMeta>,
 }
 
@@ -200,6 +213,7 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            loaded_addresses: LoadedAddresses::default(),
         }
     }
 }
@@ -305,22 +319,154 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+/// Row storage behind `LedgerStorage`.  Tables, row keys and cells follow the BigTable data
+/// model, and cell values are the compressed bincode or protobuf encoded rows written by
+/// `LedgerStorage`.
+#[async_trait]
+pub trait LedgerStorageBackend: Send + Sync {
+    /// Get `table_name` row keys in lexical order, starting with `start_at` and ending with
+    /// `end_at` (both inclusive) if provided.  If `rows_limit` is zero, the listing will
+    /// continue until the end of the table.
+    async fn get_row_keys(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> bigtable::Result<Vec<RowKey>>;
+
+    /// Get the data of `table_name` rows in lexical order, with the same range semantics as
+    /// `get_row_keys`
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> bigtable::Result<Vec<(RowKey, RowData)>>;
+
+    /// Get the data of a single row, returning `RowNotFound` if the row does not exist
+    async fn get_single_row_data(
+        &self,
+        table_name: &str,
+        row_key: RowKey,
+    ) -> bigtable::Result<RowData>;
+
+    /// Store cells for one or more `table_name` rows in the `family_name` column family,
+    /// replacing any existing cells of the same name
+    async fn put_row_data(
+        &self,
+        table_name: &str,
+        family_name: &str,
+        row_data: &[(&RowKey, RowData)],
+    ) -> bigtable::Result<()>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LedgerStorageConfig {
+    pub read_only: bool,
+    pub timeout: Option<Duration>,
+    /// Use an embedded database at this path instead of connecting to a BigTable instance
+    pub local_storage_path: Option<PathBuf>,
+}
+
 #[derive(Clone)]
 pub struct LedgerStorage {
-    connection: bigtable::BigTableConnection,
+    backend: Arc<dyn LedgerStorageBackend>,
 }
 
 impl LedgerStorage {
-    pub async fn new(read_only: bool, timeout: Option<std::time::Duration>) -> Result<Self> {
-        let connection =
-            bigtable::BigTableConnection::new("solana-ledger", read_only, timeout).await?;
-        Ok(Self { connection })
+    pub async fn new(read_only: bool, timeout: Option<Duration>) -> Result<Self> {
+        Self::new_with_config(LedgerStorageConfig {
+            read_only,
+            timeout,
+            local_storage_path: None,
+        })
+        .await
+    }
+
+    pub async fn new_with_config(config: LedgerStorageConfig) -> Result<Self> {
+        let LedgerStorageConfig {
+            read_only,
+            timeout,
+            local_storage_path,
+        } = config;
+        match local_storage_path {
+            Some(path) => Self::new_local(&path, read_only),
+            None => {
+                let connection =
+                    bigtable::BigTableConnection::new("solana-ledger", read_only, timeout).await?;
+                Ok(Self::new_with_backend(Arc::new(connection)))
+            }
+        }
+    }
+
+    /// Store ledger data in an embedded database at `path`, as a local stand-in for BigTable
+    pub fn new_local(path: &Path, read_only: bool) -> Result<Self> {
+        let local_storage = LocalStorage::open(path, read_only)?;
+        Ok(Self::new_with_backend(Arc::new(local_storage)))
+    }
+
+    pub fn new_with_backend(backend: Arc<dyn LedgerStorageBackend>) -> Self {
+        Self { backend }
+    }
+
+    async fn get_bincode_cell<T>(&self, table: &str, key: RowKey) -> bigtable::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let row_data = self.backend.get_single_row_data(table, key.clone()).await?;
+        bigtable::deserialize_bincode_cell_data(&row_data, table, key)
+    }
+
+    async fn get_protobuf_or_bincode_cell<B, P>(
+        &self,
+        table: &str,
+        key: RowKey,
+    ) -> bigtable::Result<bigtable::CellData<B, P>>
+    where
+        B: serde::de::DeserializeOwned,
+        P: prost::Message + Default,
+    {
+        let row_data = self.backend.get_single_row_data(table, key.clone()).await?;
+        bigtable::deserialize_protobuf_or_bincode_cell_data(&row_data, table, key)
+    }
+
+    async fn put_bincode_cells<T>(&self, table: &str, cells: &[(RowKey, T)]) -> Result<usize>
+    where
+        T: serde::ser::Serialize,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let row_data = bigtable::serialize_bincode_cell_data(data)?;
+            bytes_written += row_data.iter().map(|(_, value)| value.len()).sum::<usize>();
+            new_row_data.push((row_key, row_data));
+        }
+
+        self.backend.put_row_data(table, "x", &new_row_data).await?;
+        Ok(bytes_written)
+    }
+
+    async fn put_protobuf_cells<T>(&self, table: &str, cells: &[(RowKey, T)]) -> Result<usize>
+    where
+        T: prost::Message,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let row_data = bigtable::serialize_protobuf_cell_data(data)?;
+            bytes_written += row_data.iter().map(|(_, value)| value.len()).sum::<usize>();
+            new_row_data.push((row_key, row_data));
+        }
+
+        self.backend.put_row_data(table, "x", &new_row_data).await?;
+        Ok(bytes_written)
     }
 
     /// Return the available slot that contains a block
     pub async fn get_first_available_block(&self) -> Result<Option<Slot>> {
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable.get_row_keys("blocks", None, None, 1).await?;
+        let blocks = self.backend.get_row_keys("blocks", None, None, 1).await?;
         if blocks.is_empty() {
             return Ok(None);
         }
@@ -333,8 +479,8 @@ impl LedgerStorage {
     /// limit: stop after this many slots have been found; if limit==0, all records in the table
     /// after start_slot will be read
     pub async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable
+        let blocks = self
+            .backend
             .get_row_keys("blocks", Some(slot_to_key(start_slot)), None, limit as i64)
             .await?;
         Ok(blocks.into_iter().filter_map(|s| key_to_slot(&s)).collect())
@@ -342,8 +488,7 @@ impl LedgerStorage {
 
     /// Fetch the confirmed block from the desired slot
     pub async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
-        let mut bigtable = self.connection.client();
-        let block_cell_data = bigtable
+        let block_cell_data = self
             .get_protobuf_or_bincode_cell::<StoredConfirmedBlock, generated::ConfirmedBlock>(
                 "blocks",
                 slot_to_key(slot),
@@ -362,8 +507,7 @@ impl LedgerStorage {
     }
 
     pub async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
-        let mut bigtable = self.connection.client();
-        let transaction_info = bigtable
+        let transaction_info = self
             .get_bincode_cell::<TransactionInfo>("tx", signature.to_string())
             .await
             .map_err(|err| match err {
@@ -378,10 +522,8 @@ impl LedgerStorage {
         &self,
         signature: &Signature,
     ) -> Result<Option<ConfirmedTransaction>> {
-        let mut bigtable = self.connection.client();
-
         // Figure out which block the transaction is located in
-        let TransactionInfo { slot, index, .. } = bigtable
+        let TransactionInfo { slot, index, .. } = self
             .get_bincode_cell("tx", signature.to_string())
             .await
             .map_err(|err| match err {
@@ -432,14 +574,13 @@ impl LedgerStorage {
             u32, /*slot index*/
         )>,
     > {
-        let mut bigtable = self.connection.client();
         let address_prefix = format!("{}/", address);
 
         // Figure out where to start listing from based on `before_signature`
         let (first_slot, before_transaction_index) = match before_signature {
             None => (Slot::MAX, 0),
             Some(before_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = self
                     .get_bincode_cell("tx", before_signature.to_string())
                     .await?;
 
@@ -451,7 +592,7 @@ impl LedgerStorage {
         let (last_slot, until_transaction_index) = match until_signature {
             None => (0, u32::MAX),
             Some(until_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = self
                     .get_bincode_cell("tx", until_signature.to_string())
                     .await?;
 
@@ -461,7 +602,7 @@ impl LedgerStorage {
 
         let mut infos = vec![];
 
-        let starting_slot_tx_len = bigtable
+        let starting_slot_tx_len = self
             .get_protobuf_or_bincode_cell::<Vec<LegacyTransactionByAddrInfo>, tx_by_addr::TransactionByAddr>(
                 "tx-by-addr",
                 format!("{}{}", address_prefix, slot_to_key(!first_slot)),
@@ -477,7 +618,8 @@ impl LedgerStorage {
 
         // Return the next tx-by-addr data of amount `limit` plus extra to account for the largest
         // number that might be flitered out
-        let tx_by_addr_data = bigtable
+        let tx_by_addr_data = self
+            .backend
             .get_row_data(
                 "tx-by-addr",
                 Some(format!("{}{}", address_prefix, slot_to_key(!first_slot))),
@@ -560,7 +702,13 @@ impl LedgerStorage {
             let index = index as u32;
             let signature = transaction.signatures[0];
 
-            for address in &transaction.message.account_keys {
+            let mut addresses: Vec<&Pubkey> =
+                transaction.message.static_account_keys().iter().collect();
+            if let Some(meta) = meta {
+                addresses.extend(meta.loaded_addresses.writable.iter());
+                addresses.extend(meta.loaded_addresses.readonly.iter());
+            }
+            for address in addresses {
                 if !is_sysvar_id(address) {
                     by_addr
                         .entry(address)
@@ -603,15 +751,13 @@ impl LedgerStorage {
 
         if !tx_cells.is_empty() {
             bytes_written += self
-                .connection
-                .put_bincode_cells_with_retry::<TransactionInfo>("tx", &tx_cells)
+                .put_bincode_cells::<TransactionInfo>("tx", &tx_cells)
                 .await?;
         }
 
         if !tx_by_addr_cells.is_empty() {
             bytes_written += self
-                .connection
-                .put_protobuf_cells_with_retry::<tx_by_addr::TransactionByAddr>(
+                .put_protobuf_cells::<tx_by_addr::TransactionByAddr>(
                     "tx-by-addr",
                     &tx_by_addr_cells,
                 )
@@ -625,8 +771,7 @@ impl LedgerStorage {
         // `get_confirmed_block()` and `get_confirmed_blocks()`
         let blocks_cells = [(slot_to_key(slot), confirmed_block.into())];
         bytes_written += self
-            .connection
-            .put_protobuf_cells_with_retry::<generated::ConfirmedBlock>("blocks", &blocks_cells)
+            .put_protobuf_cells::<generated::ConfirmedBlock>("blocks", &blocks_cells)
             .await?;
         info!(
             "uploaded block for slot {}: {} transactions, {} bytes",
@@ -0,0 +1,243 @@
+// An embedded, RocksDB-backed stand-in for a BigTable instance
+//
+// Rows are kept in a single keyspace keyed by `<table>/<row key>`, so that lexical ordering of
+// row keys within a table matches BigTable.  Each row value is the bincode-serialized list of its
+// cells, which hold the same compressed bincode or protobuf payloads that would be written to
+// BigTable.
+
+use {
+    crate::{
+        bigtable::{Error, Result, RowData, RowKey},
+        LedgerStorageBackend,
+    },
+    async_trait::async_trait,
+    log::*,
+    rocksdb::{Direction, IteratorMode, Options, DB},
+    std::path::Path,
+};
+
+pub struct LocalStorage {
+    db: DB,
+    read_only: bool,
+}
+
+impl std::convert::From<rocksdb::Error> for Error {
+    fn from(err: rocksdb::Error) -> Self {
+        Self::LocalStorage(err.to_string())
+    }
+}
+
+impl LocalStorage {
+    /// Open the local storage database at `path`, creating it if necessary.  A read-only
+    /// instance does not take the database lock and so may be used alongside a writer.
+    pub fn open(path: &Path, read_only: bool) -> Result<Self> {
+        info!("Opening local ledger storage at {:?}", path);
+        let db = if read_only {
+            DB::open_for_read_only(&Options::default(), path, false)?
+        } else {
+            std::fs::create_dir_all(path)?;
+            let mut options = Options::default();
+            options.create_if_missing(true);
+            DB::open(&options, path)?
+        };
+        Ok(Self { db, read_only })
+    }
+
+    fn table_prefix(table_name: &str) -> Vec<u8> {
+        format!("{}/", table_name).into_bytes()
+    }
+
+    fn row_key(table_name: &str, row_key: &str) -> Vec<u8> {
+        let mut key = Self::table_prefix(table_name);
+        key.extend_from_slice(row_key.as_bytes());
+        key
+    }
+
+    fn deserialize_row(table_name: &str, row_key: &str, value: &[u8]) -> Result<RowData> {
+        bincode::deserialize(value).map_err(|err| {
+            warn!("Failed to deserialize {}/{}: {}", table_name, row_key, err);
+            Error::ObjectCorrupt(format!("{}/{}", table_name, row_key))
+        })
+    }
+
+    fn scan(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        let prefix = Self::table_prefix(table_name);
+        let start_key = Self::row_key(table_name, start_at.as_deref().unwrap_or(""));
+        let mut rows = vec![];
+
+        for (key, value) in self
+            .db
+            .iterator(IteratorMode::From(&start_key, Direction::Forward))
+        {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let row_key = match std::str::from_utf8(&key[prefix.len()..]) {
+                Ok(row_key) => row_key.to_string(),
+                Err(_) => continue, // Require UTF-8 for row keys
+            };
+            if let Some(end_at) = &end_at {
+                if row_key > *end_at {
+                    break;
+                }
+            }
+            let row_data = Self::deserialize_row(table_name, &row_key, &value)?;
+            rows.push((row_key, row_data));
+            if rows_limit > 0 && rows.len() as i64 >= rows_limit {
+                break;
+            }
+        }
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl LedgerStorageBackend for LocalStorage {
+    async fn get_row_keys(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>> {
+        let rows = self.scan(table_name, start_at, end_at, rows_limit)?;
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.scan(table_name, start_at, end_at, rows_limit)
+    }
+
+    async fn get_single_row_data(&self, table_name: &str, row_key: RowKey) -> Result<RowData> {
+        let value = self
+            .db
+            .get(Self::row_key(table_name, &row_key))?
+            .ok_or(Error::RowNotFound)?;
+        Self::deserialize_row(table_name, &row_key, &value)
+    }
+
+    async fn put_row_data(
+        &self,
+        table_name: &str,
+        _family_name: &str,
+        row_data: &[(&RowKey, RowData)],
+    ) -> Result<()> {
+        if self.read_only {
+            return Err(Error::LocalStorage(
+                "local storage was opened read-only".to_string(),
+            ));
+        }
+
+        for (row_key, new_cells) in row_data {
+            let key = Self::row_key(table_name, row_key);
+
+            // Like a BigTable `SetCell` mutation, replace the named cells and leave any other
+            // cells in the row untouched
+            let mut cells = match self.db.get(&key)? {
+                Some(value) => Self::deserialize_row(table_name, row_key, &value)?,
+                None => vec![],
+            };
+            for (cell_name, cell_value) in new_cells {
+                match cells.iter_mut().find(|(name, _)| name == cell_name) {
+                    Some(cell) => cell.1 = cell_value.clone(),
+                    None => cells.push((cell_name.clone(), cell_value.clone())),
+                }
+            }
+
+            self.db.put(
+                &key,
+                bincode::serialize(&cells).map_err(|err| Error::LocalStorage(err.to_string()))?,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cell_name: &str, cell_value: &[u8]) -> RowData {
+        vec![(cell_name.to_string(), cell_value.to_vec())]
+    }
+
+    #[test]
+    fn test_local_storage_rows() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let path = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::open(path.path(), false).unwrap();
+
+        let keys: Vec<RowKey> = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        runtime
+            .block_on(storage.put_row_data(
+                "table",
+                "x",
+                &[
+                    (&keys[2], row("bin", &[3])),
+                    (&keys[0], row("bin", &[1])),
+                    (&keys[1], row("bin", &[2])),
+                ],
+            ))
+            .unwrap();
+        runtime
+            .block_on(storage.put_row_data("other", "x", &[(&keys[0], row("bin", &[9]))]))
+            .unwrap();
+
+        assert_eq!(
+            runtime
+                .block_on(storage.get_row_keys("table", None, None, 0))
+                .unwrap(),
+            keys
+        );
+        assert_eq!(
+            runtime
+                .block_on(storage.get_row_keys("table", Some("b".to_string()), None, 1))
+                .unwrap(),
+            vec!["b".to_string()]
+        );
+        assert_eq!(
+            runtime
+                .block_on(storage.get_row_data(
+                    "table",
+                    Some("a".to_string()),
+                    Some("b".to_string()),
+                    0
+                ))
+                .unwrap(),
+            vec![
+                ("a".to_string(), row("bin", &[1])),
+                ("b".to_string(), row("bin", &[2]))
+            ]
+        );
+
+        // Writing a new cell keeps the existing cells, and rewriting a cell replaces it
+        runtime
+            .block_on(storage.put_row_data("table", "x", &[(&keys[0], row("proto", &[4]))]))
+            .unwrap();
+        runtime
+            .block_on(storage.put_row_data("table", "x", &[(&keys[0], row("bin", &[5]))]))
+            .unwrap();
+        assert_eq!(
+            runtime
+                .block_on(storage.get_single_row_data("table", "a".to_string()))
+                .unwrap(),
+            vec![("bin".to_string(), vec![5]), ("proto".to_string(), vec![4])]
+        );
+        assert!(matches!(
+            runtime.block_on(storage.get_single_row_data("table", "d".to_string())),
+            Err(Error::RowNotFound)
+        ));
+    }
+}
@@ -0,0 +1,616 @@
+//! The `quic` module implements the QUIC TPU endpoint, which receives one transaction per
+//! unidirectional stream and forwards them in batches of packets, alongside the UDP TPU sockets.
+//!
+//! Senders are identified by their IP address. Connections from the addresses of staked nodes
+//! are admitted into their own, larger connection table and may open more concurrent streams
+//! the more stake they hold.
+
+use {
+    crate::{
+        packet::{Packet, Packets, PACKETS_PER_BATCH, PACKET_DATA_SIZE},
+        streamer::PacketSender,
+    },
+    futures::StreamExt,
+    quinn::{
+        Connecting, Connection, Endpoint, EndpointConfig, IdleTimeout, Incoming,
+        IncomingUniStreams, NewConnection, ServerConfig, VarInt,
+    },
+    solana_sdk::{
+        quic::{QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS, QUIC_TPU_ALPN_PROTOCOL_ID},
+        timing::timestamp,
+    },
+    std::{
+        collections::HashMap,
+        net::{IpAddr, SocketAddr, UdpSocket},
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        thread::{self, JoinHandle},
+        time::Duration,
+    },
+    thiserror::Error,
+    tokio::{
+        runtime::Runtime,
+        sync::mpsc,
+        time::{self, timeout},
+    },
+};
+
+/// Default limit on the connections from staked nodes
+pub const MAX_STAKED_CONNECTIONS: usize = 2000;
+/// Default limit on the connections from unstaked peers
+pub const MAX_UNSTAKED_CONNECTIONS: usize = 500;
+/// Default limit on the connections from any one IP address
+pub const MAX_CONNECTIONS_PER_IP: usize = 8;
+
+/// Concurrent streams shared out among staked connections in proportion to their stake
+const QUIC_TOTAL_STAKED_CONCURRENT_STREAMS: usize = 100_000;
+/// Concurrent streams allowed on a connection from a node with only a little stake
+const QUIC_MIN_STAKED_CONCURRENT_STREAMS: usize = 128;
+
+const QUIC_MAX_TIMEOUT_MS: u32 = 2_000;
+const QUIC_KEEP_ALIVE_MS: u64 = 1_000;
+
+/// How often the server checks its exit flag while idle
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest a received packet waits for others from the same connection to fill its batch
+const PACKET_COALESCE_DURATION: Duration = Duration::from_millis(5);
+
+#[derive(Error, Debug)]
+pub enum QuicServerError {
+    #[error("certificate generation failed: {0}")]
+    CertificateError(#[from] rcgen::RcgenError),
+
+    #[error("TLS configuration error: {0}")]
+    TlsError(#[from] rustls::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Stake of the nodes at each IP address, by which QUIC connections are admitted.
+#[derive(Default, Debug)]
+pub struct StakedNodes {
+    pub ip_stake_map: HashMap<IpAddr, u64>,
+    pub total_stake: u64,
+}
+
+/// Limits on the connections a QUIC TPU server admits.
+#[derive(Clone, Debug)]
+pub struct QuicServerConfig {
+    pub max_connections_per_ip: usize,
+    pub max_staked_connections: usize,
+    pub max_unstaked_connections: usize,
+}
+
+impl Default for QuicServerConfig {
+    fn default() -> Self {
+        Self {
+            max_connections_per_ip: MAX_CONNECTIONS_PER_IP,
+            max_staked_connections: MAX_STAKED_CONNECTIONS,
+            max_unstaked_connections: MAX_UNSTAKED_CONNECTIONS,
+        }
+    }
+}
+
+fn configure_server() -> Result<ServerConfig, QuicServerError> {
+    // Senders are identified by address rather than by certificate, so a throwaway
+    // self-signed certificate is sufficient
+    let cert = rcgen::generate_simple_self_signed(vec!["solana-tpu".to_string()])?;
+    let cert_chain = vec![rustls::Certificate(cert.serialize_der()?)];
+    let key = rustls::PrivateKey(cert.serialize_private_key_der());
+
+    let mut server_tls_config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)?;
+    server_tls_config.alpn_protocols = vec![QUIC_TPU_ALPN_PROTOCOL_ID.to_vec()];
+
+    let mut server_config = ServerConfig::with_crypto(Arc::new(server_tls_config));
+    let transport_config = Arc::get_mut(&mut server_config.transport).unwrap();
+    transport_config
+        .max_concurrent_uni_streams(VarInt::from(QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS as u32));
+    transport_config.max_concurrent_bidi_streams(VarInt::from(0u8));
+    transport_config.stream_receive_window(VarInt::from(PACKET_DATA_SIZE as u32));
+    transport_config.receive_window(VarInt::from(
+        (PACKET_DATA_SIZE * QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS) as u32,
+    ));
+    transport_config.max_idle_timeout(Some(IdleTimeout::from(VarInt::from(QUIC_MAX_TIMEOUT_MS))));
+    transport_config.keep_alive_interval(Some(Duration::from_millis(QUIC_KEEP_ALIVE_MS)));
+    Ok(server_config)
+}
+
+/// Concurrent streams allowed on a connection from a node holding `stake` of `total_stake`
+fn compute_max_allowed_uni_streams(stake: u64, total_stake: u64) -> usize {
+    if stake == 0 || total_stake == 0 {
+        QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS
+    } else {
+        let streams = (QUIC_TOTAL_STAKED_CONCURRENT_STREAMS as u128 * stake as u128
+            / total_stake as u128) as usize;
+        streams.max(QUIC_MIN_STAKED_CONCURRENT_STREAMS)
+    }
+}
+
+struct ConnectionEntry {
+    exit: Arc<AtomicBool>,
+    stake: u64,
+    last_update: Arc<AtomicU64>,
+    port: u16,
+}
+
+/// Live connections, keyed by the sender's IP address
+#[derive(Default)]
+struct ConnectionTable {
+    table: HashMap<IpAddr, Vec<ConnectionEntry>>,
+    total_size: usize,
+}
+
+impl ConnectionTable {
+    /// Evict the least recently active connections until fewer than `max_size` remain
+    fn prune_oldest(&mut self, max_size: usize) {
+        while self.total_size >= max_size {
+            let oldest = self
+                .table
+                .iter()
+                .flat_map(|(ip, entries)| {
+                    entries.iter().enumerate().map(move |(index, entry)| {
+                        (entry.last_update.load(Ordering::Relaxed), *ip, index)
+                    })
+                })
+                .min();
+            match oldest {
+                Some((_, ip, index)) => self.remove_index(&ip, index),
+                None => break,
+            }
+        }
+    }
+
+    /// Evict the connection with the least stake, if it has less than `stake`
+    fn prune_lowest_stake(&mut self, stake: u64) -> bool {
+        let lowest = self
+            .table
+            .iter()
+            .flat_map(|(ip, entries)| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, entry)| (entry.stake, *ip, index))
+            })
+            .min();
+        match lowest {
+            Some((lowest_stake, ip, index)) if lowest_stake < stake => {
+                self.remove_index(&ip, index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn remove_index(&mut self, ip: &IpAddr, index: usize) {
+        if let Some(entries) = self.table.get_mut(ip) {
+            let entry = entries.remove(index);
+            entry.exit.store(true, Ordering::Relaxed);
+            self.total_size -= 1;
+            if entries.is_empty() {
+                self.table.remove(ip);
+            }
+        }
+    }
+
+    fn connections_from(&self, ip: &IpAddr) -> usize {
+        self.table.get(ip).map(Vec::len).unwrap_or_default()
+    }
+
+    /// Admit a connection from a staked node, evicting the connection with the least stake if
+    /// the table is full and that connection has less stake than this one
+    fn try_add_staked(
+        &mut self,
+        addr: SocketAddr,
+        stake: u64,
+        last_update: u64,
+        max_connections_per_ip: usize,
+        max_connections: usize,
+    ) -> Option<(Arc<AtomicU64>, Arc<AtomicBool>)> {
+        if self.connections_from(&addr.ip()) >= max_connections_per_ip
+            || (self.total_size >= max_connections && !self.prune_lowest_stake(stake))
+        {
+            return None;
+        }
+        self.try_add(addr, stake, last_update, max_connections_per_ip)
+    }
+
+    /// Admit an unstaked connection, evicting the least recently active connections if the
+    /// table is full
+    fn try_add_unstaked(
+        &mut self,
+        addr: SocketAddr,
+        last_update: u64,
+        max_connections_per_ip: usize,
+        max_connections: usize,
+    ) -> Option<(Arc<AtomicU64>, Arc<AtomicBool>)> {
+        if self.connections_from(&addr.ip()) >= max_connections_per_ip {
+            return None;
+        }
+        self.prune_oldest(max_connections);
+        self.try_add(addr, 0, last_update, max_connections_per_ip)
+    }
+
+    fn try_add(
+        &mut self,
+        addr: SocketAddr,
+        stake: u64,
+        last_update: u64,
+        max_connections_per_ip: usize,
+    ) -> Option<(Arc<AtomicU64>, Arc<AtomicBool>)> {
+        let entries = self.table.entry(addr.ip()).or_default();
+        if entries.len() >= max_connections_per_ip {
+            return None;
+        }
+        let exit = Arc::new(AtomicBool::new(false));
+        let last_update = Arc::new(AtomicU64::new(last_update));
+        entries.push(ConnectionEntry {
+            exit: exit.clone(),
+            stake,
+            last_update: last_update.clone(),
+            port: addr.port(),
+        });
+        self.total_size += 1;
+        Some((last_update, exit))
+    }
+
+    fn remove_connection(&mut self, addr: &SocketAddr) {
+        if let Some(entries) = self.table.get_mut(&addr.ip()) {
+            if let Some(index) = entries.iter().position(|entry| entry.port == addr.port()) {
+                self.remove_index(&addr.ip(), index);
+            }
+        }
+    }
+}
+
+/// Start a QUIC TPU server on `sock`, forwarding received transactions to `packet_sender`
+/// until `exit` is set.
+pub fn spawn_server(
+    sock: UdpSocket,
+    packet_sender: PacketSender,
+    exit: Arc<AtomicBool>,
+    staked_nodes: Arc<RwLock<StakedNodes>>,
+    config: QuicServerConfig,
+) -> Result<JoinHandle<()>, QuicServerError> {
+    let server_config = configure_server()?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .thread_name("sol-quic-server")
+        .enable_all()
+        .build()?;
+    let (endpoint, incoming) = {
+        let _guard = runtime.enter();
+        Endpoint::new(EndpointConfig::default(), Some(server_config), sock)?
+    };
+
+    Ok(thread::Builder::new()
+        .name("solana-quic-server".to_string())
+        .spawn(move || {
+            run_server(
+                &runtime,
+                incoming,
+                packet_sender,
+                exit,
+                staked_nodes,
+                config,
+            );
+            // Dropping the endpoint closes any remaining connections
+            drop(endpoint);
+        })
+        .unwrap())
+}
+
+fn run_server(
+    runtime: &Runtime,
+    mut incoming: Incoming,
+    packet_sender: PacketSender,
+    exit: Arc<AtomicBool>,
+    staked_nodes: Arc<RwLock<StakedNodes>>,
+    config: QuicServerConfig,
+) {
+    let staked_connection_table = Arc::new(Mutex::new(ConnectionTable::default()));
+    let unstaked_connection_table = Arc::new(Mutex::new(ConnectionTable::default()));
+
+    runtime.block_on(async {
+        while !exit.load(Ordering::Relaxed) {
+            match timeout(EXIT_CHECK_INTERVAL, incoming.next()).await {
+                Ok(Some(connecting)) => {
+                    tokio::spawn(setup_connection(
+                        connecting,
+                        packet_sender.clone(),
+                        staked_connection_table.clone(),
+                        unstaked_connection_table.clone(),
+                        staked_nodes.clone(),
+                        config.clone(),
+                        exit.clone(),
+                    ));
+                }
+                Ok(None) => break,
+                Err(_) => continue,
+            }
+        }
+    });
+}
+
+async fn setup_connection(
+    connecting: Connecting,
+    packet_sender: PacketSender,
+    staked_connection_table: Arc<Mutex<ConnectionTable>>,
+    unstaked_connection_table: Arc<Mutex<ConnectionTable>>,
+    staked_nodes: Arc<RwLock<StakedNodes>>,
+    config: QuicServerConfig,
+    exit: Arc<AtomicBool>,
+) {
+    let NewConnection {
+        connection,
+        uni_streams,
+        ..
+    } = match connecting.await {
+        Ok(new_connection) => new_connection,
+        Err(err) => {
+            debug!("quic connection failed: {:?}", err);
+            return;
+        }
+    };
+
+    let remote_addr = connection.remote_address();
+    let (stake, total_stake) = {
+        let staked_nodes = staked_nodes.read().unwrap();
+        (
+            staked_nodes
+                .ip_stake_map
+                .get(&remote_addr.ip())
+                .copied()
+                .unwrap_or_default(),
+            staked_nodes.total_stake,
+        )
+    };
+
+    // Each table is checked and updated under a single lock, so that concurrent connections
+    // cannot both claim the last free slot or the same evicted one
+    let staked = if stake > 0 {
+        let added = staked_connection_table.lock().unwrap().try_add_staked(
+            remote_addr,
+            stake,
+            timestamp(),
+            config.max_connections_per_ip,
+            config.max_staked_connections,
+        );
+        added.map(|added| (added, staked_connection_table, stake))
+    } else {
+        None
+    };
+    let added = staked.or_else(|| {
+        // Unstaked peers, and staked nodes unable to displace one with less stake, compete
+        // for the unstaked connections
+        let added = unstaked_connection_table.lock().unwrap().try_add_unstaked(
+            remote_addr,
+            timestamp(),
+            config.max_connections_per_ip,
+            config.max_unstaked_connections,
+        );
+        added.map(|added| (added, unstaked_connection_table, 0))
+    });
+    let ((last_update, connection_exit), connection_table, stake) = match added {
+        Some(added) => added,
+        None => {
+            inc_new_counter_info!("quic-server-connection-rejected", 1);
+            connection.close(0u32.into(), b"too many connections");
+            return;
+        }
+    };
+
+    let max_streams = compute_max_allowed_uni_streams(stake, total_stake);
+    connection.set_max_concurrent_uni_streams(VarInt::from(max_streams as u32));
+    inc_new_counter_info!("quic-server-connection-added", 1);
+
+    handle_connection(
+        connection,
+        uni_streams,
+        packet_sender,
+        remote_addr,
+        last_update,
+        connection_table,
+        connection_exit,
+        exit,
+    )
+    .await;
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_connection(
+    connection: Connection,
+    mut uni_streams: IncomingUniStreams,
+    packet_sender: PacketSender,
+    remote_addr: SocketAddr,
+    last_update: Arc<AtomicU64>,
+    connection_table: Arc<Mutex<ConnectionTable>>,
+    connection_exit: Arc<AtomicBool>,
+    exit: Arc<AtomicBool>,
+) {
+    // Packets read from this connection's streams are coalesced into batches
+    let (batch_sender, batch_receiver) = mpsc::channel(PACKETS_PER_BATCH);
+    tokio::spawn(coalesce_packets(batch_receiver, packet_sender));
+
+    while !exit.load(Ordering::Relaxed) && !connection_exit.load(Ordering::Relaxed) {
+        let stream = match timeout(EXIT_CHECK_INTERVAL, uni_streams.next()).await {
+            Ok(Some(Ok(stream))) => stream,
+            Ok(Some(Err(err))) => {
+                debug!("quic connection from {} ended: {:?}", remote_addr, err);
+                break;
+            }
+            Ok(None) => break,
+            Err(_) => continue,
+        };
+        last_update.store(timestamp(), Ordering::Relaxed);
+
+        let batch_sender = batch_sender.clone();
+        tokio::spawn(async move {
+            let data = match stream.read_to_end(PACKET_DATA_SIZE).await {
+                Ok(data) => data,
+                Err(err) => {
+                    inc_new_counter_info!("quic-server-stream-read-error", 1);
+                    debug!("quic stream from {} failed: {:?}", remote_addr, err);
+                    return;
+                }
+            };
+            if data.is_empty() {
+                return;
+            }
+            let mut packet = Packet::default();
+            packet.data[..data.len()].copy_from_slice(&data);
+            packet.meta.size = data.len();
+            packet.meta.set_addr(&remote_addr);
+            // Fails only once the coalescing task has stopped for lack of a receiver
+            let _ = batch_sender.send(packet).await;
+        });
+    }
+
+    if connection_exit.load(Ordering::Relaxed) {
+        connection.close(0u32.into(), b"connection pruned");
+    }
+    connection_table
+        .lock()
+        .unwrap()
+        .remove_connection(&remote_addr);
+}
+
+/// Forward packets from `receiver` to `packet_sender` in batches of up to `PACKETS_PER_BATCH`,
+/// sending a partial batch once its first packet has waited `PACKET_COALESCE_DURATION`
+async fn coalesce_packets(mut receiver: mpsc::Receiver<Packet>, packet_sender: PacketSender) {
+    while let Some(packet) = receiver.recv().await {
+        let mut packets = Vec::with_capacity(PACKETS_PER_BATCH);
+        packets.push(packet);
+        let deadline = time::Instant::now() + PACKET_COALESCE_DURATION;
+        while packets.len() < PACKETS_PER_BATCH {
+            match time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(packet)) => packets.push(packet),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        if packet_sender.send(Packets::new(packets)).is_err() {
+            debug!("quic server packet receiver disconnected");
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_max_allowed_uni_streams() {
+        assert_eq!(
+            compute_max_allowed_uni_streams(0, 100),
+            QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS
+        );
+        assert_eq!(
+            compute_max_allowed_uni_streams(10, 0),
+            QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS
+        );
+        assert_eq!(
+            compute_max_allowed_uni_streams(1, 1_000_000),
+            QUIC_MIN_STAKED_CONCURRENT_STREAMS
+        );
+        assert_eq!(
+            compute_max_allowed_uni_streams(50, 100),
+            QUIC_TOTAL_STAKED_CONCURRENT_STREAMS / 2
+        );
+    }
+
+    #[test]
+    fn test_connection_table_pruning() {
+        let addr = |ip: u8, port: u16| SocketAddr::from(([127, 0, 0, ip], port));
+        let mut table = ConnectionTable::default();
+
+        // Per-IP limit
+        assert!(table.try_add(addr(1, 1), 0, 1, 2).is_some());
+        assert!(table.try_add(addr(1, 2), 0, 2, 2).is_some());
+        assert!(table.try_add(addr(1, 3), 0, 3, 2).is_none());
+        let (_, exit) = table.try_add(addr(2, 1), 0, 0, 2).unwrap();
+        assert_eq!(table.total_size, 3);
+
+        // The least recently active connection is evicted first
+        table.prune_oldest(3);
+        assert_eq!(table.total_size, 2);
+        assert!(exit.load(Ordering::Relaxed));
+        assert!(!table.table.contains_key(&addr(2, 1).ip()));
+
+        table.remove_connection(&addr(1, 1));
+        assert_eq!(table.total_size, 1);
+
+        // Only a connection with less stake may be evicted
+        let mut table = ConnectionTable::default();
+        table.try_add(addr(1, 1), 10, 0, 8).unwrap();
+        table.try_add(addr(2, 1), 20, 0, 8).unwrap();
+        assert!(!table.prune_lowest_stake(5));
+        assert!(table.prune_lowest_stake(15));
+        assert_eq!(table.total_size, 1);
+        assert!(table.table.contains_key(&addr(2, 1).ip()));
+    }
+
+    #[test]
+    fn test_connection_table_admission() {
+        let addr = |ip: u8, port: u16| SocketAddr::from(([127, 0, 0, ip], port));
+
+        // A full staked table admits a connection only by evicting one with less stake
+        let mut table = ConnectionTable::default();
+        let (_, exit) = table.try_add_staked(addr(1, 1), 10, 0, 1, 2).unwrap();
+        table.try_add_staked(addr(2, 1), 20, 0, 1, 2).unwrap();
+        assert!(table.try_add_staked(addr(3, 1), 5, 0, 1, 2).is_none());
+        assert!(table.try_add_staked(addr(3, 1), 15, 0, 1, 2).is_some());
+        assert!(exit.load(Ordering::Relaxed));
+        assert_eq!(table.total_size, 2);
+        // ...but never for an address already at its limit
+        assert!(table.try_add_staked(addr(3, 2), 30, 0, 1, 2).is_none());
+        assert_eq!(table.total_size, 2);
+        assert!(table.table.contains_key(&addr(2, 1).ip()));
+
+        // A full unstaked table evicts its least recently active connection
+        let mut table = ConnectionTable::default();
+        let (_, exit) = table.try_add_unstaked(addr(1, 1), 1, 1, 2).unwrap();
+        table.try_add_unstaked(addr(2, 1), 2, 1, 2).unwrap();
+        assert!(table.try_add_unstaked(addr(2, 2), 3, 1, 2).is_none());
+        assert!(!exit.load(Ordering::Relaxed));
+        assert!(table.try_add_unstaked(addr(3, 1), 3, 1, 2).is_some());
+        assert!(exit.load(Ordering::Relaxed));
+        assert_eq!(table.total_size, 2);
+    }
+
+    #[test]
+    fn test_coalesce_packets() {
+        let runtime = Runtime::new().unwrap();
+        let (packet_sender, packet_receiver) = std::sync::mpsc::channel();
+        runtime.block_on(async {
+            // Packets already waiting are sent in full batches
+            let (batch_sender, batch_receiver) = mpsc::channel(2 * PACKETS_PER_BATCH);
+            for _ in 0..PACKETS_PER_BATCH + 1 {
+                batch_sender.send(Packet::default()).await.unwrap();
+            }
+            drop(batch_sender);
+            coalesce_packets(batch_receiver, packet_sender.clone()).await;
+        });
+        let batch_sizes: Vec<_> = packet_receiver
+            .try_iter()
+            .map(|packets| packets.packets.len())
+            .collect();
+        assert_eq!(batch_sizes, vec![PACKETS_PER_BATCH, 1]);
+
+        // A partial batch is sent without waiting for the connection to close
+        let (batch_sender, batch_receiver) = mpsc::channel(PACKETS_PER_BATCH);
+        runtime.spawn(coalesce_packets(batch_receiver, packet_sender));
+        runtime
+            .block_on(batch_sender.send(Packet::default()))
+            .unwrap();
+        let packets = packet_receiver
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(packets.packets.len(), 1);
+        drop(batch_sender);
+    }
+}
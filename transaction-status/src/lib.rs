@@ -25,13 +25,17 @@ use solana_sdk::{
     commitment_config::CommitmentConfig,
     deserialize_utils::default_on_eof,
     instruction::CompiledInstruction,
-    message::{Message, MessageHeader},
-    pubkey::Pubkey,
+    message::{
+        v0::{self, LoadedAddresses, MessageAddressTableLookup},
+        Message, MessageHeader, VersionedMessage,
+    },
+    pubkey::{ParsePubkeyError, Pubkey},
     sanitize::Sanitize,
     signature::Signature,
     transaction::{Result, Transaction, TransactionError},
+    versioned_transaction::VersionedTransaction,
 };
-use std::fmt;
+use std::{convert::TryFrom, fmt, result, str::FromStr};
 /// A duplicate representation of an Instruction for pretty JSON serialization
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", untagged)]
@@ -145,7 +149,7 @@ impl From<TransactionTokenBalance> for UiTransactionTokenBalance {
 }
 
 impl UiInnerInstructions {
-    fn parse(inner_instructions: InnerInstructions, message: &Message) -> Self {
+    pub fn parse(inner_instructions: InnerInstructions, message: &Message) -> Self {
         Self {
             index: inner_instructions.index,
             instructions: inner_instructions
@@ -187,6 +191,8 @@ pub struct TransactionStatusMeta {
     pub post_token_balances: Option<Vec<TransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Rewards>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: LoadedAddresses,
 }
 
 impl Default for TransactionStatusMeta {
@@ -201,6 +207,7 @@ impl Default for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            loaded_addresses: LoadedAddresses::default(),
         }
     }
 }
@@ -219,6 +226,67 @@ pub struct UiTransactionStatusMeta {
     pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub rewards: Option<Rewards>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loaded_addresses: Option<UiLoadedAddresses>,
+}
+
+/// A duplicate representation of LoadedAddresses for pretty JSON serialization.
+///
+/// The balances and inner instructions of a versioned transaction are ordered
+/// by its resolved account keys, which list the signer keys, the remaining
+/// writable static keys, the loaded writable addresses, the remaining readonly
+/// static keys and finally the loaded readonly addresses.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl From<&LoadedAddresses> for UiLoadedAddresses {
+    fn from(loaded_addresses: &LoadedAddresses) -> Self {
+        Self {
+            writable: loaded_addresses
+                .writable
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            readonly: loaded_addresses
+                .readonly
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&UiLoadedAddresses> for LoadedAddresses {
+    type Error = ParsePubkeyError;
+
+    fn try_from(loaded_addresses: &UiLoadedAddresses) -> result::Result<Self, Self::Error> {
+        Ok(Self {
+            writable: loaded_addresses
+                .writable
+                .iter()
+                .map(|address| Pubkey::from_str(address))
+                .collect::<result::Result<_, _>>()?,
+            readonly: loaded_addresses
+                .readonly
+                .iter()
+                .map(|address| Pubkey::from_str(address))
+                .collect::<result::Result<_, _>>()?,
+        })
+    }
+}
+
+impl UiLoadedAddresses {
+    fn from_meta(loaded_addresses: &LoadedAddresses) -> Option<Self> {
+        if loaded_addresses.is_empty() {
+            None
+        } else {
+            Some(loaded_addresses.into())
+        }
+    }
 }
 
 impl UiTransactionStatusMeta {
@@ -242,6 +310,7 @@ impl UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            loaded_addresses: UiLoadedAddresses::from_meta(&meta.loaded_addresses),
         }
     }
 }
@@ -265,6 +334,7 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            loaded_addresses: UiLoadedAddresses::from_meta(&meta.loaded_addresses),
         }
     }
 }
@@ -332,6 +402,63 @@ pub struct ConfirmedTransactionStatusWithSignature {
     pub block_time: Option<UnixTimestamp>,
 }
 
+/// A transaction that changed an account, with the account's balance before and after it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountModification {
+    pub signature: Signature,
+    pub slot: Slot,
+    pub err: Option<TransactionError>,
+    pub block_time: Option<UnixTimestamp>,
+    pub pre_lamports: u64,
+    pub post_lamports: u64,
+}
+
+impl AccountModification {
+    /// Describes how `transaction` changed the account at `address`, if it did: its balance or
+    /// token amount changed, or `data_changed` reports that its data or owner changed in the
+    /// transaction's slot. Transactions that did not lock the account for writing never change
+    /// it, and failed transactions only charge fees.
+    pub fn from_transaction(
+        address: &Pubkey,
+        status: ConfirmedTransactionStatusWithSignature,
+        transaction: &TransactionWithStatusMeta,
+        data_changed: bool,
+    ) -> Option<Self> {
+        let meta = transaction.meta.as_ref()?;
+        let message = resolve_message(&transaction.transaction.message, &meta.loaded_addresses)?;
+        let index = message.account_keys.iter().position(|key| key == address)?;
+        if !message.is_writable(index) {
+            return None;
+        }
+        let pre_lamports = *meta.pre_balances.get(index)?;
+        let post_lamports = *meta.post_balances.get(index)?;
+        let token_amount = |balances: &Option<Vec<TransactionTokenBalance>>| {
+            balances
+                .as_ref()?
+                .iter()
+                .find(|balance| usize::from(balance.account_index) == index)
+                .map(|balance| balance.ui_token_amount.amount.clone())
+        };
+        let is_changed = pre_lamports != post_lamports
+            || (meta.status.is_ok()
+                && (data_changed
+                    || token_amount(&meta.pre_token_balances)
+                        != token_amount(&meta.post_token_balances)));
+        if !is_changed {
+            return None;
+        }
+        Some(Self {
+            signature: status.signature,
+            slot: status.slot,
+            err: status.err,
+            block_time: status.block_time,
+            pre_lamports,
+            post_lamports,
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Reward {
@@ -429,7 +556,7 @@ pub struct EncodedConfirmedBlock {
     pub block_height: Option<u64>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UiConfirmedBlock {
     pub previous_blockhash: String,
@@ -539,6 +666,71 @@ pub struct UiRawMessage {
     pub account_keys: Vec<String>,
     pub recent_blockhash: String,
     pub instructions: Vec<UiCompiledInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
+}
+
+/// A duplicate representation of a MessageAddressTableLookup, in raw format, for pretty JSON serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAddressTableLookup {
+    pub account_key: String,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+impl From<&MessageAddressTableLookup> for UiAddressTableLookup {
+    fn from(lookup: &MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: lookup.account_key.to_string(),
+            writable_indexes: lookup.writable_indexes.clone(),
+            readonly_indexes: lookup.readonly_indexes.clone(),
+        }
+    }
+}
+
+impl UiRawMessage {
+    fn from_legacy(message: &Message) -> Self {
+        Self {
+            header: message.header.clone(),
+            account_keys: message
+                .account_keys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            recent_blockhash: message.recent_blockhash.to_string(),
+            instructions: message
+                .instructions
+                .iter()
+                .map(|instruction| instruction.into())
+                .collect(),
+            address_table_lookups: None,
+        }
+    }
+
+    fn from_v0(message: &v0::Message) -> Self {
+        Self {
+            header: message.header.clone(),
+            account_keys: message
+                .account_keys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            recent_blockhash: message.recent_blockhash.to_string(),
+            instructions: message
+                .instructions
+                .iter()
+                .map(|instruction| instruction.into())
+                .collect(),
+            address_table_lookups: Some(
+                message
+                    .address_table_lookups
+                    .iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            ),
+        }
+    }
 }
 
 /// A duplicate representation of a Message, in parsed format, for pretty JSON serialization
@@ -550,25 +742,66 @@ pub struct UiParsedMessage {
     pub instructions: Vec<UiInstruction>,
 }
 
+impl UiParsedMessage {
+    fn from_legacy(message: &Message) -> Self {
+        Self {
+            account_keys: parse_accounts(message),
+            recent_blockhash: message.recent_blockhash.to_string(),
+            instructions: message
+                .instructions
+                .iter()
+                .map(|instruction| UiInstruction::parse(instruction, message))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionWithStatusMeta {
-    pub transaction: Transaction,
+    pub transaction: VersionedTransaction,
     pub meta: Option<TransactionStatusMeta>,
 }
 
 impl TransactionWithStatusMeta {
-    fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
-        let message = self.transaction.message();
-        let meta = self.meta.map(|meta| meta.encode(encoding, message));
+    pub fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
+        let loaded_addresses = self
+            .meta
+            .as_ref()
+            .map(|meta| meta.loaded_addresses.clone())
+            .unwrap_or_default();
+        let resolved_message = resolve_message(&self.transaction.message, &loaded_addresses);
+        let meta = match &resolved_message {
+            Some(message) => self.meta.map(|meta| meta.encode(encoding, message)),
+            None => self.meta.map(|meta| meta.into()),
+        };
         EncodedTransactionWithStatusMeta {
-            transaction: EncodedTransaction::encode(self.transaction, encoding),
+            transaction: EncodedTransaction::encode_versioned(
+                self.transaction,
+                resolved_message.as_ref(),
+                encoding,
+            ),
             meta,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// Resolve a versioned message into a message which lists all of the account
+/// keys used by the transaction, in the order used by the runtime
+pub fn resolve_message(
+    message: &VersionedMessage,
+    loaded_addresses: &LoadedAddresses,
+) -> Option<Message> {
+    match message {
+        VersionedMessage::Legacy(message) => Some(message.clone()),
+        VersionedMessage::V0(message) => message
+            .clone()
+            .try_into_legacy_message(loaded_addresses.clone())
+            .ok(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EncodedTransactionWithStatusMeta {
     pub transaction: EncodedTransaction,
@@ -612,6 +845,18 @@ pub enum EncodedTransaction {
 
 impl EncodedTransaction {
     pub fn encode(transaction: Transaction, encoding: UiTransactionEncoding) -> Self {
+        let message = transaction.message.clone();
+        Self::encode_versioned(transaction.into(), Some(&message), encoding)
+    }
+
+    /// Encode a versioned transaction. `resolved_message` lists every account
+    /// key used by the transaction and is required to produce a parsed
+    /// message; when it is unavailable, the raw message is returned instead.
+    pub fn encode_versioned(
+        transaction: VersionedTransaction,
+        resolved_message: Option<&Message>,
+        encoding: UiTransactionEncoding,
+    ) -> Self {
         match encoding {
             UiTransactionEncoding::Binary => EncodedTransaction::LegacyBinary(
                 bs58::encode(bincode::serialize(&transaction).unwrap()).into_string(),
@@ -625,36 +870,16 @@ impl EncodedTransaction {
                 encoding,
             ),
             UiTransactionEncoding::Json | UiTransactionEncoding::JsonParsed => {
-                let message = if encoding == UiTransactionEncoding::Json {
-                    UiMessage::Raw(UiRawMessage {
-                        header: transaction.message.header,
-                        account_keys: transaction
-                            .message
-                            .account_keys
-                            .iter()
-                            .map(|pubkey| pubkey.to_string())
-                            .collect(),
-                        recent_blockhash: transaction.message.recent_blockhash.to_string(),
-                        instructions: transaction
-                            .message
-                            .instructions
-                            .iter()
-                            .map(|instruction| instruction.into())
-                            .collect(),
-                    })
-                } else {
-                    UiMessage::Parsed(UiParsedMessage {
-                        account_keys: parse_accounts(&transaction.message),
-                        recent_blockhash: transaction.message.recent_blockhash.to_string(),
-                        instructions: transaction
-                            .message
-                            .instructions
-                            .iter()
-                            .map(|instruction| {
-                                UiInstruction::parse(instruction, &transaction.message)
-                            })
-                            .collect(),
-                    })
+                let message = match (encoding, resolved_message, &transaction.message) {
+                    (UiTransactionEncoding::JsonParsed, Some(resolved_message), _) => {
+                        UiMessage::Parsed(UiParsedMessage::from_legacy(resolved_message))
+                    }
+                    (_, _, VersionedMessage::Legacy(message)) => {
+                        UiMessage::Raw(UiRawMessage::from_legacy(message))
+                    }
+                    (_, _, VersionedMessage::V0(message)) => {
+                        UiMessage::Raw(UiRawMessage::from_v0(message))
+                    }
                 };
                 EncodedTransaction::Json(UiTransaction {
                     signatures: transaction
@@ -667,8 +892,8 @@ impl EncodedTransaction {
             }
         }
     }
-    pub fn decode(&self) -> Option<Transaction> {
-        let transaction: Option<Transaction> = match self {
+    pub fn decode(&self) -> Option<VersionedTransaction> {
+        let transaction: Option<VersionedTransaction> = match self {
             EncodedTransaction::Json(_) => None,
             EncodedTransaction::LegacyBinary(blob) => bs58::decode(blob)
                 .into_vec()
@@ -705,6 +930,7 @@ pub struct TransactionByAddrInfo {
 #[cfg(test)]
 mod test {
     use super::*;
+    use solana_sdk::hash::Hash;
 
     #[test]
     fn test_decode_invalid_transaction() {
@@ -720,6 +946,99 @@ mod test {
         assert!(unsanitary_transaction.decode().is_none());
     }
 
+    #[test]
+    fn test_encode_v0_transaction() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let lookup_table_key = Pubkey::new_unique();
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![Pubkey::new_unique()],
+            readonly: vec![Pubkey::new_unique()],
+        };
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![payer, program_id],
+                recent_blockhash: Hash::default(),
+                instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2, 3])],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: lookup_table_key,
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![1],
+                }],
+            }),
+        };
+        let transaction_with_meta = TransactionWithStatusMeta {
+            transaction: transaction.clone(),
+            meta: Some(TransactionStatusMeta {
+                loaded_addresses: loaded_addresses.clone(),
+                ..TransactionStatusMeta::default()
+            }),
+        };
+
+        let encoded = transaction_with_meta
+            .clone()
+            .encode(UiTransactionEncoding::Json);
+        assert_eq!(
+            encoded.meta.unwrap().loaded_addresses,
+            Some(UiLoadedAddresses::from(&loaded_addresses))
+        );
+        match encoded.transaction {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Raw(message),
+                ..
+            }) => {
+                assert_eq!(
+                    message.account_keys,
+                    vec![payer.to_string(), program_id.to_string()]
+                );
+                assert_eq!(
+                    message.address_table_lookups,
+                    Some(vec![UiAddressTableLookup {
+                        account_key: lookup_table_key.to_string(),
+                        writable_indexes: vec![0],
+                        readonly_indexes: vec![1],
+                    }])
+                );
+            }
+            _ => panic!("expected raw json message"),
+        }
+
+        let encoded = transaction_with_meta
+            .clone()
+            .encode(UiTransactionEncoding::JsonParsed);
+        match encoded.transaction {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Parsed(message),
+                ..
+            }) => {
+                let account_keys: Vec<_> = message
+                    .account_keys
+                    .into_iter()
+                    .map(|account| (account.pubkey, account.writable))
+                    .collect();
+                assert_eq!(
+                    account_keys,
+                    vec![
+                        (payer.to_string(), true),
+                        (loaded_addresses.writable[0].to_string(), true),
+                        (program_id.to_string(), false),
+                        (loaded_addresses.readonly[0].to_string(), false),
+                    ]
+                );
+            }
+            _ => panic!("expected parsed json message"),
+        }
+
+        let encoded = transaction_with_meta.encode(UiTransactionEncoding::Base64);
+        assert_eq!(encoded.transaction.decode(), Some(transaction));
+    }
+
     #[test]
     fn test_satisfies_commitment() {
         let status = TransactionStatus {
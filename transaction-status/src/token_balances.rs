@@ -3,7 +3,11 @@ use solana_account_decoder::parse_token::{
     spl_token_id_v2_0, spl_token_v2_0_native_mint, token_amount_to_ui_amount, UiTokenAmount,
 };
 use solana_runtime::{bank::Bank, transaction_batch::TransactionBatch};
-use solana_sdk::{account::ReadableAccount, pubkey::Pubkey};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
 use spl_token_v2_0::{
     solana_program::program_pack::Pack,
     state::{Account as TokenAccount, Mint},
@@ -84,13 +88,54 @@ pub fn collect_token_balances(
     balances
 }
 
+/// Collect the token balances of a transaction from the given states of its accounts, indexed
+/// like the transaction's account keys, rather than from the bank. Used to report the token
+/// balance changes of a simulated transaction, whose results are never stored.
+pub fn collect_token_balances_from_accounts(
+    bank: &Bank,
+    transaction: &Transaction,
+    accounts: &[(Pubkey, AccountSharedData)],
+    mint_decimals: &mut HashMap<Pubkey, u8>,
+) -> Vec<TransactionTokenBalance> {
+    let account_keys = &transaction.message.account_keys;
+    if !account_keys.iter().any(is_token_program) {
+        return vec![];
+    }
+
+    let program_ids = transaction.message.program_ids();
+    accounts
+        .iter()
+        .take(account_keys.len())
+        .enumerate()
+        .filter(|(_, (account_id, _))| {
+            !is_token_program(account_id) && !program_ids.contains(&account_id)
+        })
+        .filter_map(|(index, (_, account))| {
+            let (mint, ui_token_amount) =
+                collect_token_balance_from_account_data(bank, account, mint_decimals)?;
+            Some(TransactionTokenBalance {
+                account_index: index as u8,
+                mint,
+                ui_token_amount,
+            })
+        })
+        .collect()
+}
+
 pub fn collect_token_balance_from_account(
     bank: &Bank,
     account_id: &Pubkey,
     mint_decimals: &mut HashMap<Pubkey, u8>,
 ) -> Option<(String, UiTokenAmount)> {
     let account = bank.get_account(account_id)?;
+    collect_token_balance_from_account_data(bank, &account, mint_decimals)
+}
 
+fn collect_token_balance_from_account_data(
+    bank: &Bank,
+    account: &AccountSharedData,
+    mint_decimals: &mut HashMap<Pubkey, u8>,
+) -> Option<(String, UiTokenAmount)> {
     let token_account = TokenAccount::unpack(account.data()).ok()?;
     let mint_string = &token_account.mint.to_string();
     let mint = &Pubkey::from_str(mint_string).unwrap_or_default();
@@ -23,7 +23,8 @@ use {
         ledger_cleanup_service::{DEFAULT_MAX_LEDGER_SHREDS, DEFAULT_MIN_MAX_LEDGER_SHREDS},
         tpu::DEFAULT_TPU_COALESCE_MS,
         validator::{
-            is_snapshot_config_invalid, Validator, ValidatorConfig, ValidatorStartProgress,
+            is_incremental_snapshot_config_invalid, is_snapshot_config_invalid, Validator,
+            ValidatorConfig, ValidatorStartProgress,
         },
     },
     solana_download_utils::{download_snapshot, DownloadProgressRecord},
@@ -36,14 +37,16 @@ use {
     solana_ledger::blockstore_db::BlockstoreRecoveryMode,
     solana_perf::recycler::enable_recycler_warming,
     solana_poh::poh_service,
-    solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
+    solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig, send_transaction_service},
     solana_runtime::{
         accounts_db::{
-            AccountShrinkThreshold, DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE,
-            DEFAULT_ACCOUNTS_SHRINK_RATIO,
+            AccountShrinkThreshold, AccountsDbConfig, ColdStorageConfig,
+            DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE, DEFAULT_ACCOUNTS_SHRINK_RATIO,
+            DEFAULT_COLD_STORAGE_CACHE_BYTES, DEFAULT_COLD_STORAGE_MIN_AGE_SLOTS,
         },
         accounts_index::{
             AccountIndex, AccountSecondaryIndexes, AccountSecondaryIndexesIncludeExclude,
+            AccountsIndexConfig, CustomAccountIndex,
         },
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
         snapshot_config::SnapshotConfig,
@@ -682,6 +685,7 @@ fn verify_reachable_ports(
     }
     if ContactInfo::is_valid_address(&node.info.tpu, socket_addr_space) {
         udp_sockets.extend(node.sockets.tpu.iter());
+        udp_sockets.push(&node.sockets.tpu_quic);
     }
     if ContactInfo::is_valid_address(&node.info.tpu_forwards, socket_addr_space) {
         udp_sockets.extend(node.sockets.tpu_forwards.iter());
@@ -1048,10 +1052,12 @@ pub fn main() {
     let default_rpc_pubsub_max_active_subscriptions =
         PubSubConfig::default().max_active_subscriptions.to_string();
     let default_rpc_send_transaction_retry_ms = ValidatorConfig::default()
-        .send_transaction_retry_ms
+        .send_transaction_service_config
+        .retry_rate_ms
         .to_string();
     let default_rpc_send_transaction_leader_forward_count = ValidatorConfig::default()
-        .send_transaction_leader_forward_count
+        .send_transaction_service_config
+        .leader_forward_count
         .to_string();
     let default_rpc_threads = num_cpus::get().to_string();
     let default_max_snapshot_to_retain = &DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN.to_string();
@@ -1060,6 +1066,9 @@ pub fn main() {
     let default_accounts_shrink_optimize_total_space =
         &DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE.to_string();
     let default_accounts_shrink_ratio = &DEFAULT_ACCOUNTS_SHRINK_RATIO.to_string();
+    let default_accounts_cold_storage_min_age = &DEFAULT_COLD_STORAGE_MIN_AGE_SLOTS.to_string();
+    let default_accounts_cold_storage_cache_mb =
+        &(DEFAULT_COLD_STORAGE_CACHE_BYTES / (1024 * 1024)).to_string();
 
     let matches = App::new(crate_name!()).about(crate_description!())
         .version(solana_version::version!())
@@ -1232,6 +1241,15 @@ pub fn main() {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("bigtable_local_storage")
+                .long("bigtable-local-storage")
+                .value_name("DIR")
+                .takes_value(true)
+                .help("Use an embedded database in DIR instead of a BigTable instance \
+                       for --enable-rpc-bigtable-ledger-storage and \
+                       --enable-bigtable-ledger-upload"),
+        )
         .arg(
             Arg::with_name("enable_cpi_and_log_storage")
                 .long("enable-cpi-and-log-storage")
@@ -1240,6 +1258,15 @@ pub fn main() {
                 .help("Include CPI inner instructions and logs in the \
                         historical transaction info stored"),
         )
+        .arg(
+            Arg::with_name("enable_rpc_account_history")
+                .long("enable-rpc-account-history")
+                .takes_value(false)
+                .help("Record every account version in the ledger, so that \
+                       'getAccountInfo', 'getMultipleAccounts' and 'getBalance' \
+                       can serve account state at a past rooted slot via 'atSlot'.  \
+                       This will cause a large increase in disk usage and IOPS"),
+        )
         .arg(
             Arg::with_name("rpc_max_multiple_accounts")
                 .long("rpc-max-multiple-accounts")
@@ -1285,6 +1312,14 @@ pub fn main() {
                 .multiple(true)
                 .help("Path to accounts shrink path which can hold a compacted account set."),
         )
+        .arg(
+            Arg::with_name("accountsdb_plugin_config")
+                .long("accountsdb-plugin-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .multiple(true)
+                .help("Specify the configuration file for the AccountsDb plugin."),
+        )
         .arg(
             Arg::with_name("snapshots")
                 .long("snapshots")
@@ -1355,6 +1390,16 @@ pub fn main() {
                 .help("Number of slots between generating snapshots, \
                       0 to disable snapshots"),
         )
+        .arg(
+            Arg::with_name("incremental_snapshot_interval_slots")
+                .long("incremental-snapshot-interval-slots")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value("0")
+                .help("Number of slots between generating incremental snapshots, \
+                      relative to the last full snapshot. Must be less than \
+                      --snapshot-interval-slots, 0 to disable incremental snapshots"),
+        )
         .arg(
             Arg::with_name("maximum_snapshots_to_retain")
                 .long("maximum-snapshots-to-retain")
@@ -1567,6 +1612,12 @@ pub fn main() {
                 .validator(is_parsable::<u64>)
                 .help("Milliseconds to wait in the TPU receiver for packet coalescing."),
         )
+        .arg(
+            Arg::with_name("tpu_use_quic")
+                .long("tpu-use-quic")
+                .takes_value(false)
+                .help("Also receive transactions on the QUIC TPU endpoint"),
+        )
         .arg(
             Arg::with_name("rocksdb_max_compaction_jitter")
                 .long("rocksdb-max-compaction-jitter-slots")
@@ -1615,6 +1666,13 @@ pub fn main() {
                 .takes_value(false)
                 .help("Enable the unstable RPC PubSub `voteSubscribe` subscription"),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_block_subscription")
+                .long("rpc-pubsub-enable-block-subscription")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Enable the unstable RPC PubSub `blockSubscribe` subscription"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_max_connections")
                 .long("rpc-pubsub-max-connections")
@@ -1682,6 +1740,37 @@ pub fn main() {
                 .default_value(&default_rpc_send_transaction_leader_forward_count)
                 .help("The number of upcoming leaders to which to forward transactions sent via rpc service."),
         )
+        .arg(
+            Arg::with_name("rpc_send_transaction_default_max_retries")
+                .long("rpc-send-default-max-retries")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("The maximum number of transaction broadcast retries when unspecified by the request, otherwise retried until expiration."),
+        )
+        .arg(
+            Arg::with_name("rpc_send_transaction_service_max_retries")
+                .long("rpc-send-service-max-retries")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("The maximum number of transaction broadcast retries, regardless of requested value."),
+        )
+        .arg(
+            Arg::with_name("rpc_send_transaction_retry_queue")
+                .long("rpc-send-retry-queue")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Save transactions awaiting retry to this file, so that their retries \
+                       resume after a restart"),
+        )
+        .arg(
+            Arg::with_name("rpc_send_transaction_use_quic")
+                .long("rpc-send-transaction-use-quic")
+                .takes_value(false)
+                .help("Send transactions received over RPC to the leaders' QUIC TPU \
+                       endpoints rather than over UDP"),
+        )
         .arg(
             Arg::with_name("rpc_scan_and_fix_roots")
                 .long("rpc-scan-and-fix-roots")
@@ -1788,6 +1877,23 @@ pub fn main() {
                 .value_name("INDEX")
                 .help("Enable an accounts index, indexed by the selected account field"),
         )
+        .arg(
+            Arg::with_name("account_index_custom")
+                .long("account-index-custom")
+                .takes_value(true)
+                .multiple(true)
+                .validator(|value| {
+                    value
+                        .parse::<CustomAccountIndex>()
+                        .map(|_| ())
+                        .map_err(|err| format!("invalid custom account index {}: {}", value, err))
+                })
+                .value_name("PROGRAM_ID:OFFSET:LENGTH")
+                .help("Enable an accounts index over the accounts owned by PROGRAM_ID, \
+                       indexed by the LENGTH bytes (at most 32) of account data at OFFSET. \
+                       getProgramAccounts requests for PROGRAM_ID with a memcmp filter on \
+                       exactly that field are served from the index"),
+        )
         .arg(
             Arg::with_name("account_index_exclude_key")
                 .long(EXCLUDE_KEY)
@@ -1861,6 +1967,56 @@ pub fn main() {
                        it becomes a candidate for shrinking. The value must between 0. and 1.0 \
                        inclusive."),
         )
+        .arg(
+            Arg::with_name("accounts_index_memory_limit_mb")
+                .long("accounts-index-memory-limit-mb")
+                .value_name("MEGABYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Enable the disk-based accounts index, keeping about this much of the \
+                       index in memory and the rest in memory-mapped files"),
+        )
+        .arg(
+            Arg::with_name("accounts_index_path")
+                .long("accounts-index-path")
+                .value_name("PATH")
+                .takes_value(true)
+                .multiple(true)
+                .requires("accounts_index_memory_limit_mb")
+                .help("Directory for the disk-based accounts index, which is erased at startup. \
+                       May be specified multiple times. \
+                       [default: [ledger]/accounts_index]"),
+        )
+        .arg(
+            Arg::with_name("accounts_cold_storage_path")
+                .long("accounts-cold-storage-path")
+                .value_name("PATH")
+                .takes_value(true)
+                .conflicts_with("no_accounts_db_caching")
+                .help("Compress old account storages into this directory, which may be on \
+                       slower storage than the accounts paths. The directory is cleared at \
+                       startup. Each snapshot decompresses these storages again"),
+        )
+        .arg(
+            Arg::with_name("accounts_cold_storage_min_age")
+                .long("accounts-cold-storage-min-age")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .validator(is_parsable::<Slot>)
+                .default_value(default_accounts_cold_storage_min_age)
+                .help("Compress account storages once they are this many slots older \
+                       than the latest root"),
+        )
+        .arg(
+            Arg::with_name("accounts_cold_storage_cache_mb")
+                .long("accounts-cold-storage-cache-mb")
+                .value_name("MEGABYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value(default_accounts_cold_storage_cache_mb)
+                .help("How much of the compressed account storages to keep decompressed \
+                       in memory"),
+        )
         .arg(
             Arg::with_name("no_duplicate_instance_check")
                 .long("no-duplicate-instance-check")
@@ -2271,6 +2427,7 @@ pub fn main() {
         rpc_config: JsonRpcConfig {
             enable_rpc_transaction_history: matches.is_present("enable_rpc_transaction_history"),
             enable_cpi_and_log_storage: matches.is_present("enable_cpi_and_log_storage"),
+            enable_rpc_account_history: matches.is_present("enable_rpc_account_history"),
             enable_bigtable_ledger_storage: matches
                 .is_present("enable_rpc_bigtable_ledger_storage"),
             enable_bigtable_ledger_upload: matches.is_present("enable_bigtable_ledger_upload"),
@@ -2293,6 +2450,9 @@ pub fn main() {
             rpc_bigtable_timeout: value_t!(matches, "rpc_bigtable_timeout", u64)
                 .ok()
                 .map(Duration::from_secs),
+            bigtable_local_storage_path: matches
+                .value_of("bigtable_local_storage")
+                .map(PathBuf::from),
             account_indexes: account_indexes.clone(),
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
         },
@@ -2307,6 +2467,7 @@ pub fn main() {
         }),
         pubsub_config: PubSubConfig {
             enable_vote_subscription: matches.is_present("rpc_pubsub_enable_vote_subscription"),
+            enable_block_subscription: matches.is_present("rpc_pubsub_enable_block_subscription"),
             max_connections: value_t_or_exit!(matches, "rpc_pubsub_max_connections", usize),
             max_fragment_size: value_t_or_exit!(matches, "rpc_pubsub_max_fragment_size", usize),
             max_in_buffer_capacity: value_t_or_exit!(
@@ -2339,12 +2500,30 @@ pub fn main() {
         debug_keys,
         contact_debug_interval,
         bpf_jit: !matches.is_present("no_bpf_jit"),
-        send_transaction_retry_ms: value_t_or_exit!(matches, "rpc_send_transaction_retry_ms", u64),
-        send_transaction_leader_forward_count: value_t_or_exit!(
-            matches,
-            "rpc_send_transaction_leader_forward_count",
-            u64
-        ),
+        send_transaction_service_config: send_transaction_service::Config {
+            retry_rate_ms: value_t_or_exit!(matches, "rpc_send_transaction_retry_ms", u64),
+            leader_forward_count: value_t_or_exit!(
+                matches,
+                "rpc_send_transaction_leader_forward_count",
+                u64
+            ),
+            default_max_retries: value_t!(
+                matches,
+                "rpc_send_transaction_default_max_retries",
+                usize
+            )
+            .ok(),
+            service_max_retries: value_t!(
+                matches,
+                "rpc_send_transaction_service_max_retries",
+                usize
+            )
+            .unwrap_or(send_transaction_service::Config::default().service_max_retries),
+            retry_queue_path: matches
+                .value_of("rpc_send_transaction_retry_queue")
+                .map(PathBuf::from),
+            use_quic: matches.is_present("rpc_send_transaction_use_quic"),
+        },
         no_poh_speed_test: matches.is_present("no_poh_speed_test"),
         poh_pinned_cpu_core: value_of(&matches, "poh_pinned_cpu_core")
             .unwrap_or(poh_service::DEFAULT_PINNED_CPU_CORE),
@@ -2355,6 +2534,7 @@ pub fn main() {
         accounts_db_test_hash_calculation: matches.is_present("accounts_db_test_hash_calculation"),
         accounts_db_use_index_hash_calculation: matches.is_present("accounts_db_index_hashing"),
         tpu_coalesce_ms,
+        tpu_use_quic: matches.is_present("tpu_use_quic"),
         no_wait_for_vote_to_start_leader: matches.is_present("no_wait_for_vote_to_start_leader"),
         accounts_shrink_ratio,
         ..ValidatorConfig::default()
@@ -2382,11 +2562,41 @@ pub fn main() {
         } else {
             vec![ledger_path.join("accounts")]
         };
+    let accounts_index_config = value_t!(matches, "accounts_index_memory_limit_mb", usize)
+        .ok()
+        .map(|memory_limit_mb| AccountsIndexConfig {
+            memory_limit_mb: Some(memory_limit_mb),
+            drives: Some(
+                values_t!(matches, "accounts_index_path", String)
+                    .map(|paths| paths.into_iter().map(PathBuf::from).collect())
+                    .unwrap_or_else(|_| vec![ledger_path.join("accounts_index")]),
+            ),
+        });
+    let cold_storage_config = value_t!(matches, "accounts_cold_storage_path", String)
+        .ok()
+        .map(|path| ColdStorageConfig {
+            path: PathBuf::from(path),
+            min_age_slots: value_t_or_exit!(matches, "accounts_cold_storage_min_age", Slot),
+            cache_bytes: value_t_or_exit!(matches, "accounts_cold_storage_cache_mb", usize)
+                * 1024
+                * 1024,
+        });
+    if accounts_index_config.is_some() || cold_storage_config.is_some() {
+        validator_config.accounts_db_config = Some(AccountsDbConfig {
+            index: accounts_index_config,
+            cold_storage: cold_storage_config,
+        });
+    }
     let account_shrink_paths: Option<Vec<PathBuf>> =
         values_t!(matches, "account_shrink_path", String)
             .map(|shrink_paths| shrink_paths.into_iter().map(PathBuf::from).collect())
             .ok();
 
+    validator_config.accountsdb_plugin_config_files =
+        values_t!(matches, "accountsdb_plugin_config", String)
+            .map(|config_files| config_files.into_iter().map(PathBuf::from).collect())
+            .ok();
+
     // Create and canonicalize account paths to avoid issues with symlink creation
     validator_config.account_paths = account_paths
         .into_iter()
@@ -2425,6 +2635,8 @@ pub fn main() {
     });
 
     let snapshot_interval_slots = value_t_or_exit!(matches, "snapshot_interval_slots", u64);
+    let incremental_snapshot_interval_slots =
+        value_t_or_exit!(matches, "incremental_snapshot_interval_slots", u64);
     let maximum_local_snapshot_age = value_t_or_exit!(matches, "maximum_local_snapshot_age", u64);
     let maximum_snapshots_to_retain =
         value_t_or_exit!(matches, "maximum_snapshots_to_retain", usize);
@@ -2473,6 +2685,11 @@ pub fn main() {
         } else {
             std::u64::MAX
         },
+        incremental_snapshot_archive_interval_slots: if incremental_snapshot_interval_slots > 0 {
+            incremental_snapshot_interval_slots
+        } else {
+            std::u64::MAX
+        },
         snapshot_path,
         snapshot_package_output_path: snapshot_output_dir.clone(),
         archive_format,
@@ -2496,6 +2713,24 @@ pub fn main() {
         );
         exit(1);
     }
+    if incremental_snapshot_interval_slots > 0
+        && is_incremental_snapshot_config_invalid(
+            incremental_snapshot_interval_slots,
+            if snapshot_interval_slots > 0 {
+                snapshot_interval_slots
+            } else {
+                std::u64::MAX
+            },
+            validator_config.accounts_hash_interval_slots,
+        )
+    {
+        eprintln!("Invalid incremental snapshot interval provided ({}), must be a multiple of accounts_hash_interval_slots ({}) and less than the snapshot interval ({})",
+            incremental_snapshot_interval_slots,
+            validator_config.accounts_hash_interval_slots,
+            snapshot_interval_slots,
+        );
+        exit(1);
+    }
 
     if matches.is_present("limit_ledger_size") {
         let limit_ledger_size = match matches.value_of("limit_ledger_size") {
@@ -2731,6 +2966,12 @@ fn process_account_indexes(matches: &ArgMatches) -> AccountSecondaryIndexes {
             "spl-token-owner" => AccountIndex::SplTokenOwner,
             _ => unreachable!(),
         })
+        .chain(
+            matches
+                .values_of("account_index_custom")
+                .unwrap_or_default()
+                .map(|value| AccountIndex::Custom(value.parse().unwrap())),
+        )
         .collect();
 
     let account_indexes_include_keys: HashSet<Pubkey> =
@@ -6,19 +6,26 @@ use {
     log::*,
     solana_clap_utils::{
         input_parsers::pubkeys_of,
-        input_validators::{is_parsable, is_pubkey_or_keypair, is_url},
+        input_validators::{is_parsable, is_pubkey_or_keypair, is_url, is_valid_percentage},
     },
     solana_cli_output::display::format_labeled_address,
-    solana_client::{client_error, rpc_client::RpcClient, rpc_response::RpcVoteAccountStatus},
+    solana_client::{
+        client_error,
+        rpc_client::RpcClient,
+        rpc_config::{RpcBlockProductionConfig, RpcBlockProductionConfigRange},
+        rpc_response::RpcVoteAccountStatus,
+    },
     solana_metrics::{datapoint_error, datapoint_info},
     solana_notifier::Notifier,
     solana_sdk::{
+        clock::Slot,
+        commitment_config::CommitmentConfig,
         hash::Hash,
         native_token::{sol_to_lamports, Sol},
         pubkey::Pubkey,
     },
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         error,
         thread::sleep,
         time::{Duration, Instant},
@@ -26,12 +33,17 @@ use {
 };
 
 struct Config {
+    active_stake_alert_threshold: f64,
     address_labels: HashMap<String, String>,
     ignore_http_bad_gateway: bool,
     interval: Duration,
     json_rpc_url: String,
+    max_root_stall: Option<Duration>,
+    max_skip_rate: Option<f64>,
+    max_vote_lag: Option<u64>,
     minimum_validator_identity_balance: u64,
     monitor_active_stake: bool,
+    skip_rate_window: u64,
     unhealthy_threshold: usize,
     validator_identity_pubkeys: Vec<Pubkey>,
 }
@@ -123,7 +135,54 @@ fn get_config() -> Config {
             Arg::with_name("monitor_active_stake")
                 .long("monitor-active-stake")
                 .takes_value(false)
-                .help("Alert when the current stake for the cluster drops below 80%"),
+                .help("Alert when the current stake for the cluster drops below \
+                       --active-stake-alert-threshold"),
+        )
+        .arg(
+            Arg::with_name("active_stake_alert_threshold")
+                .long("active-stake-alert-threshold")
+                .value_name("PERCENT")
+                .takes_value(true)
+                .default_value("80")
+                .validator(is_valid_percentage)
+                .help("Percentage of the total stake that must be current (not delinquent) \
+                       when --monitor-active-stake is enabled"),
+        )
+        .arg(
+            Arg::with_name("vote_lag_threshold")
+                .long("vote-lag-threshold")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Alert when a monitored validator's last vote is more than this many \
+                       slots behind the most recent vote in the cluster"),
+        )
+        .arg(
+            Arg::with_name("skip_rate_threshold")
+                .long("skip-rate-threshold")
+                .value_name("PERCENT")
+                .takes_value(true)
+                .validator(is_valid_percentage)
+                .help("Alert when a monitored validator skips more than this percentage of \
+                       its leader slots within --skip-rate-window"),
+        )
+        .arg(
+            Arg::with_name("skip_rate_window")
+                .long("skip-rate-window")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .default_value("4500")
+                .validator(is_parsable::<u64>)
+                .help("Number of most recently finalized slots used to compute the skip rate"),
+        )
+        .arg(
+            Arg::with_name("root_stall_threshold")
+                .long("root-stall-threshold")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Alert when the cluster's finalized root has not advanced for this \
+                       many seconds"),
         )
         .arg(
             Arg::with_name("ignore_http_bad_gateway")
@@ -157,15 +216,28 @@ fn get_config() -> Config {
         .collect();
 
     let monitor_active_stake = matches.is_present("monitor_active_stake");
+    let active_stake_alert_threshold =
+        value_t_or_exit!(matches, "active_stake_alert_threshold", f64);
     let ignore_http_bad_gateway = matches.is_present("ignore_http_bad_gateway");
+    let max_vote_lag = value_t!(matches, "vote_lag_threshold", u64).ok();
+    let max_skip_rate = value_t!(matches, "skip_rate_threshold", f64).ok();
+    let skip_rate_window = value_t_or_exit!(matches, "skip_rate_window", u64);
+    let max_root_stall = value_t!(matches, "root_stall_threshold", u64)
+        .ok()
+        .map(Duration::from_secs);
 
     let config = Config {
+        active_stake_alert_threshold,
         address_labels: config.address_labels,
         ignore_http_bad_gateway,
         interval,
         json_rpc_url,
+        max_root_stall,
+        max_skip_rate,
+        max_vote_lag,
         minimum_validator_identity_balance,
         monitor_active_stake,
+        skip_rate_window,
         unhealthy_threshold,
         validator_identity_pubkeys,
     };
@@ -178,12 +250,20 @@ fn get_config() -> Config {
     config
 }
 
-fn get_cluster_info(
-    config: &Config,
-    rpc_client: &RpcClient,
-) -> client_error::Result<(u64, Hash, RpcVoteAccountStatus, HashMap<Pubkey, u64>)> {
+struct ClusterInfo {
+    transaction_count: u64,
+    recent_blockhash: Hash,
+    root_slot: Slot,
+    vote_accounts: RpcVoteAccountStatus,
+    validator_balances: HashMap<Pubkey, u64>,
+    // (leader slots, blocks produced) of each monitored validator within the skip rate window
+    validator_block_production: HashMap<Pubkey, (usize, usize)>,
+}
+
+fn get_cluster_info(config: &Config, rpc_client: &RpcClient) -> client_error::Result<ClusterInfo> {
     let transaction_count = rpc_client.get_transaction_count()?;
     let recent_blockhash = rpc_client.get_recent_blockhash()?.0;
+    let root_slot = rpc_client.get_slot_with_commitment(CommitmentConfig::finalized())?;
     let vote_accounts = rpc_client.get_vote_accounts()?;
 
     let mut validator_balances = HashMap::new();
@@ -194,12 +274,36 @@ fn get_cluster_info(
         );
     }
 
-    Ok((
+    let mut validator_block_production = HashMap::new();
+    if config.max_skip_rate.is_some() && !config.validator_identity_pubkeys.is_empty() {
+        let block_production = rpc_client
+            .get_block_production_with_config(RpcBlockProductionConfig {
+                identity: None,
+                range: Some(RpcBlockProductionConfigRange {
+                    first_slot: root_slot.saturating_sub(config.skip_rate_window),
+                    last_slot: Some(root_slot),
+                }),
+                commitment: Some(CommitmentConfig::finalized()),
+            })?
+            .value;
+        for validator_identity in &config.validator_identity_pubkeys {
+            if let Some(production) = block_production
+                .by_identity
+                .get(&validator_identity.to_string())
+            {
+                validator_block_production.insert(*validator_identity, *production);
+            }
+        }
+    }
+
+    Ok(ClusterInfo {
         transaction_count,
         recent_blockhash,
+        root_slot,
         vote_accounts,
         validator_balances,
-    ))
+        validator_block_production,
+    })
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
@@ -215,12 +319,23 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let mut last_notification_msg = "".into();
     let mut num_consecutive_failures = 0;
     let mut last_success = Instant::now();
+    let mut last_root_slot = 0;
+    let mut last_root_advance = Instant::now();
+    let mut notified_failures = HashSet::new();
 
     loop {
-        let failure = match get_cluster_info(&config, &rpc_client) {
-            Ok((transaction_count, recent_blockhash, vote_accounts, validator_balances)) => {
+        let failures = match get_cluster_info(&config, &rpc_client) {
+            Ok(ClusterInfo {
+                transaction_count,
+                recent_blockhash,
+                root_slot,
+                vote_accounts,
+                validator_balances,
+                validator_block_production,
+            }) => {
                 info!("Current transaction count: {}", transaction_count);
                 info!("Recent blockhash: {}", recent_blockhash);
+                info!("Root slot: {}", root_slot);
                 info!("Current validator count: {}", vote_accounts.current.len());
                 info!(
                     "Delinquent validator count: {}",
@@ -271,19 +386,86 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     ));
                 }
 
-                if config.monitor_active_stake && current_stake_percent < 80. {
+                if root_slot > last_root_slot {
+                    last_root_slot = root_slot;
+                    last_root_advance = Instant::now();
+                } else if let Some(max_root_stall) = config.max_root_stall {
+                    let root_stall = Instant::now().duration_since(last_root_advance);
+                    if root_stall > max_root_stall {
+                        failures.push((
+                            "root-stall",
+                            format!(
+                                "Root has not advanced past slot {} for {}",
+                                root_slot,
+                                humantime::format_duration(Duration::from_secs(
+                                    root_stall.as_secs()
+                                ))
+                            ),
+                        ));
+                    }
+                }
+
+                if config.monitor_active_stake
+                    && current_stake_percent < config.active_stake_alert_threshold
+                {
                     failures.push((
                         "current-stake",
                         format!("Current stake is {:.2}%", current_stake_percent),
                     ));
                 }
 
+                let highest_last_vote = vote_accounts
+                    .current
+                    .iter()
+                    .map(|vote_account| vote_account.last_vote)
+                    .max()
+                    .unwrap_or_default();
+
                 let mut validator_errors = vec![];
+                let mut vote_lag_errors = vec![];
+                let mut skip_rate_errors = vec![];
                 for validator_identity in config.validator_identity_pubkeys.iter() {
                     let formatted_validator_identity = format_labeled_address(
                         &validator_identity.to_string(),
                         &config.address_labels,
                     );
+
+                    if let Some(max_vote_lag) = config.max_vote_lag {
+                        let last_vote = vote_accounts
+                            .current
+                            .iter()
+                            .chain(vote_accounts.delinquent.iter())
+                            .filter(|vai| vai.node_pubkey == *validator_identity.to_string())
+                            .map(|vai| vai.last_vote)
+                            .max();
+                        if let Some(last_vote) = last_vote {
+                            let vote_lag = highest_last_vote.saturating_sub(last_vote);
+                            if vote_lag > max_vote_lag {
+                                vote_lag_errors.push(format!(
+                                    "{} is {} slots behind",
+                                    formatted_validator_identity, vote_lag
+                                ));
+                            }
+                        }
+                    }
+
+                    if let Some(max_skip_rate) = config.max_skip_rate {
+                        if let Some((leader_slots, blocks_produced)) =
+                            validator_block_production.get(validator_identity)
+                        {
+                            if *leader_slots > 0 {
+                                let skip_rate = (leader_slots - blocks_produced) as f64 * 100.
+                                    / *leader_slots as f64;
+                                if skip_rate > max_skip_rate {
+                                    skip_rate_errors.push(format!(
+                                        "{} skipped {:.2}% of {} leader slots",
+                                        formatted_validator_identity, skip_rate, leader_slots
+                                    ));
+                                }
+                            }
+                        }
+                    }
+
                     if vote_accounts
                         .delinquent
                         .iter()
@@ -312,14 +494,20 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 if !validator_errors.is_empty() {
                     failures.push(("delinquent", validator_errors.join(",")));
                 }
+                if !vote_lag_errors.is_empty() {
+                    failures.push(("vote-lag", vote_lag_errors.join(",")));
+                }
+                if !skip_rate_errors.is_empty() {
+                    failures.push(("skip-rate", skip_rate_errors.join(",")));
+                }
 
                 for failure in failures.iter() {
                     error!("{} sanity failure: {}", failure.0, failure.1);
                 }
-                failures.into_iter().next() // Only report the first failure if any
+                failures
             }
             Err(err) => {
-                let mut failure = Some(("rpc-error", err.to_string()));
+                let mut failures = vec![("rpc-error", err.to_string())];
 
                 if let client_error::ClientErrorKind::Reqwest(reqwest_err) = err.kind() {
                     if let Some(client_error::reqwest::StatusCode::BAD_GATEWAY) =
@@ -327,15 +515,33 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     {
                         if config.ignore_http_bad_gateway {
                             warn!("Error suppressed: {}", err);
-                            failure = None;
+                            failures.clear();
                         }
                     }
                 }
-                failure
+                failures
             }
         };
 
-        if let Some((failure_test_name, failure_error_message)) = &failure {
+        // Notify when a previously reported failure clears while others remain
+        notified_failures.retain(|notified_test_name| {
+            if failures
+                .iter()
+                .any(|(failure_test_name, _)| failure_test_name == notified_test_name)
+            {
+                true
+            } else {
+                if !failures.is_empty() {
+                    let recovered_msg = format!("{} recovered", notified_test_name);
+                    info!("{}", recovered_msg);
+                    notifier.send(&format!("solana-watchtower: {}", recovered_msg));
+                }
+                false
+            }
+        });
+
+        // Only report the first failure if any
+        if let Some((failure_test_name, failure_error_message)) = failures.first() {
             let notification_msg = format!(
                 "solana-watchtower: Error: {}: {}",
                 failure_test_name, failure_error_message
@@ -346,6 +552,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 if last_notification_msg != notification_msg {
                     notifier.send(&notification_msg);
                 }
+                notified_failures.insert(*failure_test_name);
                 datapoint_error!(
                     "watchtower-sanity-failure",
                     ("test", failure_test_name, String),